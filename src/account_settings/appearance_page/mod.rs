@@ -0,0 +1,280 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+
+use tracing::error;
+
+use crate::{
+    Application,
+    system_settings::ReduceMotionPreference,
+    utils::{
+        matrix::LinkFormat,
+        spell_check::{self, SETTINGS_KEY_SPELL_CHECKING_LANGUAGE},
+    },
+};
+
+/// The preferences to reduce motion, in the order they are presented.
+const REDUCE_MOTION_PREFERENCES: [ReduceMotionPreference; 3] = [
+    ReduceMotionPreference::System,
+    ReduceMotionPreference::Always,
+    ReduceMotionPreference::Never,
+];
+
+/// The formats of copied links, in the order they are presented.
+const LINK_FORMATS: [LinkFormat; 2] = [LinkFormat::MatrixTo, LinkFormat::Matrix];
+
+/// The delays before marking messages as read, in seconds, in the order they
+/// are presented.
+///
+/// `-1` means that messages are never marked as read automatically.
+const MARK_AS_READ_DELAYS: [i32; 4] = [0, 5, 30, -1];
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/account_settings/appearance_page/mod.ui")]
+    pub struct AppearancePage {
+        #[template_child]
+        timeline_zoom_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        message_bubbles_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        mark_as_read_delay_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        undo_send_delay_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        link_format_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        spell_checking_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        spell_checking_language_row: TemplateChild<adw::ComboRow>,
+        /// The codes of the spell checking languages, in the order they are
+        /// presented.
+        ///
+        /// An empty string means that the language of the system is used.
+        spell_checking_languages: RefCell<Vec<String>>,
+        #[template_child]
+        emoji_replacement_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        ctrl_enter_to_send_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        sidebar_message_preview_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        reduce_motion_row: TemplateChild<adw::ComboRow>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AppearancePage {
+        const NAME: &'static str = "AppearancePage";
+        type Type = super::AppearancePage;
+        type ParentType = adw::PreferencesPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AppearancePage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let app = Application::default();
+            let settings = app.settings();
+            settings
+                .bind("timeline-zoom", &*self.timeline_zoom_row, "value")
+                .build();
+            settings
+                .bind("message-bubbles", &*self.message_bubbles_row, "active")
+                .build();
+            settings
+                .bind("undo-send-delay", &*self.undo_send_delay_row, "value")
+                .build();
+            settings
+                .bind(
+                    "sidebar-message-preview",
+                    &*self.sidebar_message_preview_row,
+                    "active",
+                )
+                .build();
+
+            let delay = settings.int("mark-as-read-delay");
+            let position = MARK_AS_READ_DELAYS
+                .iter()
+                .position(|d| *d == delay)
+                .and_then(|position| u32::try_from(position).ok())
+                .unwrap_or_default();
+            self.mark_as_read_delay_row.set_selected(position);
+
+            // Connect the handler after setting the initial value, to avoid
+            // overwriting the setting.
+            self.mark_as_read_delay_row.connect_selected_notify(|row| {
+                let Some(delay) = usize::try_from(row.selected())
+                    .ok()
+                    .and_then(|position| MARK_AS_READ_DELAYS.get(position))
+                else {
+                    return;
+                };
+
+                let settings = Application::default().settings();
+                if settings.int("mark-as-read-delay") != *delay
+                    && let Err(error) = settings.set_int("mark-as-read-delay", *delay)
+                {
+                    error!("Could not change mark as read delay setting: {error}");
+                }
+            });
+
+            let link_format = LinkFormat::from_settings();
+            let position = LINK_FORMATS
+                .iter()
+                .position(|f| *f == link_format)
+                .and_then(|position| u32::try_from(position).ok())
+                .unwrap_or_default();
+            self.link_format_row.set_selected(position);
+
+            // Connect the handler after setting the initial value, to avoid
+            // overwriting the setting.
+            self.link_format_row.connect_selected_notify(|row| {
+                let Some(link_format) = usize::try_from(row.selected())
+                    .ok()
+                    .and_then(|position| LINK_FORMATS.get(position))
+                else {
+                    return;
+                };
+
+                if LinkFormat::from_settings() != *link_format {
+                    link_format.save_to_settings();
+                }
+            });
+
+            settings
+                .bind(
+                    "spell-checking-enabled",
+                    &*self.spell_checking_row,
+                    "active",
+                )
+                .build();
+            self.init_spell_checking_languages();
+            settings
+                .bind("emoji-replacement", &*self.emoji_replacement_row, "active")
+                .build();
+            settings
+                .bind(
+                    "ctrl-enter-to-send",
+                    &*self.ctrl_enter_to_send_row,
+                    "active",
+                )
+                .build();
+
+            let preference = app.system_settings().reduce_motion_preference();
+            let position = REDUCE_MOTION_PREFERENCES
+                .iter()
+                .position(|p| *p == preference)
+                .and_then(|position| u32::try_from(position).ok())
+                .unwrap_or_default();
+            self.reduce_motion_row.set_selected(position);
+
+            // Connect the handler after setting the initial value, to avoid
+            // overwriting the preference.
+            self.reduce_motion_row.connect_selected_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.set_reduce_motion_preference();
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for AppearancePage {}
+    impl PreferencesPageImpl for AppearancePage {}
+
+    impl AppearancePage {
+        /// Initialize the list of spell checking languages.
+        fn init_spell_checking_languages(&self) {
+            let mut codes = vec![String::new()];
+            let names = gtk::StringList::new(&[&gettext("Follow System")]);
+
+            for (code, name) in spell_check::available_languages() {
+                codes.push(code.into());
+                names.append(&name);
+            }
+
+            let code = Application::default()
+                .settings()
+                .string(SETTINGS_KEY_SPELL_CHECKING_LANGUAGE);
+            let position = codes
+                .iter()
+                .position(|c| *c == code)
+                .and_then(|position| u32::try_from(position).ok())
+                .unwrap_or_default();
+
+            self.spell_checking_languages.replace(codes);
+            self.spell_checking_language_row.set_model(Some(&names));
+            self.spell_checking_language_row.set_selected(position);
+
+            // Connect the handler after setting the initial value, to avoid
+            // overwriting the setting.
+            self.spell_checking_language_row
+                .connect_selected_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.set_spell_checking_language();
+                    }
+                ));
+        }
+
+        /// Set the spell checking language from the selected row.
+        fn set_spell_checking_language(&self) {
+            let Some(code) = usize::try_from(self.spell_checking_language_row.selected())
+                .ok()
+                .and_then(|position| {
+                    self.spell_checking_languages
+                        .borrow()
+                        .get(position)
+                        .cloned()
+                })
+            else {
+                return;
+            };
+
+            let settings = Application::default().settings();
+            if settings.string(SETTINGS_KEY_SPELL_CHECKING_LANGUAGE) != code
+                && let Err(error) = settings.set_string(SETTINGS_KEY_SPELL_CHECKING_LANGUAGE, &code)
+            {
+                error!("Could not change spell checking language setting: {error}");
+            }
+        }
+
+        /// Set the preference to reduce motion from the selected row.
+        fn set_reduce_motion_preference(&self) {
+            let Some(preference) = usize::try_from(self.reduce_motion_row.selected())
+                .ok()
+                .and_then(|position| REDUCE_MOTION_PREFERENCES.get(position))
+            else {
+                return;
+            };
+
+            let system_settings = Application::default().system_settings();
+            if system_settings.reduce_motion_preference() != *preference {
+                system_settings.set_reduce_motion_preference(*preference);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Preferences page to customize the appearance of the application.
+    pub struct AppearancePage(ObjectSubclass<imp::AppearancePage>)
+        @extends gtk::Widget, adw::PreferencesPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
@@ -1,16 +1,25 @@
 use adw::{prelude::*, subclass::prelude::*};
+use futures_util::StreamExt;
 use gettextrs::gettext;
-use gtk::{glib, glib::clone};
+use gtk::{gio, glib, glib::clone};
+use matrix_sdk::encryption::backups::UploadState;
+use tracing::error;
 
 mod import_export_keys_subpage;
 
 pub(super) use self::import_export_keys_subpage::{
     ImportExportKeysSubpage, ImportExportKeysSubpageMode,
 };
-use crate::session::{CryptoIdentityState, RecoveryState, Session, SessionVerificationState};
+use crate::{
+    components::LoadingButton,
+    ngettext_f,
+    session::{CryptoIdentityState, RecoveryState, Session, SessionVerificationState, UserSession},
+    spawn, spawn_tokio, toast,
+    utils::ExpressionListModel,
+};
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{Cell, OnceCell, RefCell};
 
     use glib::subclass::InitializingObject;
 
@@ -36,10 +45,24 @@ mod imp {
         recovery_description: TemplateChild<gtk::Label>,
         #[template_child]
         recovery_btn: TemplateChild<gtk::Button>,
+        #[template_child]
+        cross_signing_keys_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        cross_signing_keys_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        verified_sessions_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        backup_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        backup_btn: TemplateChild<LoadingButton>,
         /// The current session.
         #[property(get, set = Self::set_session, nullable)]
         session: glib::WeakRef<Session>,
         security_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+        /// The model used to watch the verification state of the user sessions.
+        user_sessions_model: OnceCell<ExpressionListModel>,
+        /// The latest known counts of room keys, as `(backed_up, total)`.
+        backup_counts: Cell<Option<(usize, usize)>>,
     }
 
     #[glib::object_subclass]
@@ -50,6 +73,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -72,6 +96,7 @@ mod imp {
     impl WidgetImpl for EncryptionPage {}
     impl PreferencesPageImpl for EncryptionPage {}
 
+    #[gtk::template_callbacks]
     impl EncryptionPage {
         /// Set the current session.
         fn set_session(&self, session: Option<&Session>) {
@@ -104,6 +129,8 @@ mod imp {
                         self,
                         move |_| {
                             imp.update_crypto_identity();
+                            // The verification state of the current session changed.
+                            imp.update_verified_sessions();
                         }
                     ));
                 let recovery_state_handler = security.connect_recovery_state_notify(clone!(
@@ -113,18 +140,53 @@ mod imp {
                         imp.update_recovery();
                     }
                 ));
+                let backup_enabled_handler = security.connect_backup_enabled_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_backup();
+                    }
+                ));
+                let backup_exists_on_server_handler = security
+                    .connect_backup_exists_on_server_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_backup();
+                        }
+                    ));
+                let cross_signing_keys_handler = security
+                    .connect_cross_signing_keys_available_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_cross_signing_keys();
+                        }
+                    ));
 
                 self.security_handlers.replace(vec![
                     crypto_identity_state_handler,
                     verification_state_handler,
                     recovery_state_handler,
+                    cross_signing_keys_handler,
+                    backup_enabled_handler,
+                    backup_exists_on_server_handler,
                 ]);
+
+                self.user_sessions_model()
+                    .set_model(Some(session.user_sessions().other_sessions()));
+            } else {
+                self.user_sessions_model().set_model(None::<gio::ListModel>);
             }
 
             self.session.set(session);
 
             self.update_crypto_identity();
             self.update_recovery();
+            self.update_cross_signing_keys();
+            self.update_verified_sessions();
+            self.backup_counts.set(None);
+            self.update_backup();
 
             self.obj().notify_session();
         }
@@ -153,10 +215,10 @@ mod imp {
                     "Verifying your own devices or other users is not possible",
                 ));
 
-                self.crypto_identity_btn.set_label(&gettext("Enable…"));
+                self.crypto_identity_btn.set_label(&gettext("Set Up…"));
                 self.crypto_identity_btn
                     .update_property(&[gtk::accessible::Property::Label(&gettext(
-                        "Enable Crypto Identity",
+                        "Set Up Encryption",
                     ))]);
                 self.crypto_identity_btn.add_css_class("suggested-action");
 
@@ -206,6 +268,174 @@ mod imp {
             }
         }
 
+        /// The model used to watch the verification state of the other user
+        /// sessions.
+        fn user_sessions_model(&self) -> &ExpressionListModel {
+            self.user_sessions_model.get_or_init(|| {
+                let model = ExpressionListModel::new();
+                model.set_expressions(vec![UserSession::this_expression("verified").upcast()]);
+                model.connect_items_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _, _, _| {
+                        imp.update_verified_sessions();
+                    }
+                ));
+                model
+            })
+        }
+
+        /// Update the row presenting the state of the cross-signing keys.
+        fn update_cross_signing_keys(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            if session.security().cross_signing_keys_available() {
+                self.cross_signing_keys_icon
+                    .set_icon_name(Some("checkmark-symbolic"));
+                self.cross_signing_keys_icon.add_css_class("success");
+                self.cross_signing_keys_icon.remove_css_class("warning");
+
+                self.cross_signing_keys_row
+                    .set_subtitle(&gettext("All the keys are available in this session"));
+            } else {
+                self.cross_signing_keys_icon
+                    .set_icon_name(Some("warning-symbolic"));
+                self.cross_signing_keys_icon.remove_css_class("success");
+                self.cross_signing_keys_icon.add_css_class("warning");
+
+                self.cross_signing_keys_row
+                    .set_subtitle(&gettext("Some keys are missing from this session"));
+            }
+        }
+
+        /// Update the row presenting the number of verified sessions.
+        fn update_verified_sessions(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let user_sessions = session.user_sessions();
+
+            let sessions = user_sessions
+                .current_session()
+                .into_iter()
+                .chain(
+                    self.user_sessions_model()
+                        .iter::<glib::Object>()
+                        .filter_map(|item| item.ok().and_downcast::<UserSession>()),
+                )
+                .collect::<Vec<_>>();
+            let total = sessions.len();
+            let verified = sessions.iter().filter(|s| s.verified()).count();
+
+            let subtitle = ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}',
+                // these are variable names.
+                "{verified} of {total} session verified",
+                "{verified} of {total} sessions verified",
+                total.try_into().unwrap_or(u32::MAX),
+                &[
+                    ("verified", &verified.to_string()),
+                    ("total", &total.to_string()),
+                ],
+            );
+            self.verified_sessions_row.set_subtitle(&subtitle);
+        }
+
+        /// Update the row presenting the state of the room keys backup.
+        fn update_backup(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let security = session.security();
+
+            let backup_enabled = security.backup_enabled();
+            let subtitle = if backup_enabled {
+                if let Some((backed_up, total)) = self.backup_counts.get() {
+                    ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}',
+                        // these are variable names.
+                        "{backed_up} of {total} key backed up",
+                        "{backed_up} of {total} keys backed up",
+                        total.try_into().unwrap_or(u32::MAX),
+                        &[
+                            ("backed_up", &backed_up.to_string()),
+                            ("total", &total.to_string()),
+                        ],
+                    )
+                } else {
+                    gettext("Enabled")
+                }
+            } else if security.backup_exists_on_server() {
+                gettext("A backup exists but is not used by this session")
+            } else {
+                gettext("Disabled")
+            };
+
+            self.backup_row.set_subtitle(&subtitle);
+            self.backup_btn.set_visible(backup_enabled);
+        }
+
+        /// Upload the room keys that are not backed up yet.
+        #[template_callback]
+        async fn back_up_room_keys(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            self.backup_btn.set_is_loading(true);
+
+            let backups = session.client().encryption().backups();
+            let steady_state = backups.wait_for_steady_state();
+
+            // Watch the progress of the upload.
+            let progress_stream = steady_state.subscribe_to_progress();
+            let obj_weak = glib::SendWeakRef::from(self.obj().downgrade());
+            let fut = progress_stream.for_each(move |state| {
+                let obj_weak = obj_weak.clone();
+
+                async move {
+                    let Ok(
+                        UploadState::CheckingIfUploadNeeded(counts)
+                        | UploadState::Uploading(counts),
+                    ) = state
+                    else {
+                        return;
+                    };
+
+                    let ctx = glib::MainContext::default();
+                    ctx.spawn(async move {
+                        spawn!(async move {
+                            if let Some(obj) = obj_weak.upgrade() {
+                                let imp = obj.imp();
+                                imp.backup_counts
+                                    .set(Some((counts.backed_up, counts.total)));
+                                imp.update_backup();
+                            }
+                        });
+                    });
+                }
+            });
+            let progress_abort_handle = spawn_tokio!(fut).abort_handle();
+
+            let handle = spawn_tokio!(async move { steady_state.await });
+            let result = handle.await.unwrap();
+
+            progress_abort_handle.abort();
+
+            if let Err(error) = result {
+                error!("Could not back up room keys: {error}");
+                toast!(self.obj(), gettext("Could not back up room keys"));
+            } else if let Some((_, total)) = self.backup_counts.get() {
+                // All the keys are backed up now.
+                self.backup_counts.set(Some((total, total)));
+                self.update_backup();
+            }
+
+            self.backup_btn.set_is_loading(false);
+        }
+
         /// Update the recovery section.
         fn update_recovery(&self) {
             let Some(session) = self.session.upgrade() else {
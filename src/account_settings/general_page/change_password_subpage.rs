@@ -1,7 +1,7 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::glib;
-use ruma::api::client::error::ErrorKind;
+use ruma::api::client::{account::change_password, error::ErrorKind};
 use tracing::error;
 
 use crate::{
@@ -37,6 +37,8 @@ mod imp {
         #[template_child]
         confirm_password_error: TemplateChild<gtk::Label>,
         #[template_child]
+        log_out_devices_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         button: TemplateChild<LoadingButtonRow>,
         /// The current session.
         #[property(get, set, nullable)]
@@ -75,6 +77,9 @@ mod imp {
             let label = &self.password_error;
             let password = entry.text();
 
+            // Reset the error returned by the server, if any.
+            entry.remove_css_class("error");
+
             if password.is_empty() {
                 revealer.set_reveal_child(false);
                 entry.remove_css_class("success");
@@ -172,18 +177,23 @@ mod imp {
             }
 
             let password = self.password.text();
+            let logout_devices = self.log_out_devices_row.is_active();
 
             self.button.set_is_loading(true);
             self.password.set_sensitive(false);
             self.confirm_password.set_sensitive(false);
+            self.log_out_devices_row.set_sensitive(false);
 
             let obj = self.obj();
             let dialog = AuthDialog::new(&session);
 
             let result = dialog
                 .authenticate(&*obj, move |client, auth| {
-                    let password = password.clone();
-                    async move { client.account().change_password(&password, auth).await }
+                    let mut request = change_password::v3::Request::new(password.to_string());
+                    request.logout_devices = logout_devices;
+                    request.auth = auth;
+
+                    async move { client.send(request).await.map_err(Into::into) }
                 })
                 .await;
 
@@ -203,7 +213,9 @@ mod imp {
                         ) =>
                     {
                         error!("Weak password: {error}");
-                        toast!(obj, gettext("Password rejected for being too weak"));
+                        self.show_server_error(&gettext(
+                            "The homeserver rejected the password for being too weak",
+                        ));
                     }
                     _ => {
                         error!("Could not change the password: {error}");
@@ -215,6 +227,16 @@ mod imp {
             self.button.set_is_loading(false);
             self.password.set_sensitive(true);
             self.confirm_password.set_sensitive(true);
+            self.log_out_devices_row.set_sensitive(true);
+        }
+
+        /// Show the given error returned by the server about the new password.
+        fn show_server_error(&self, message: &str) {
+            self.password.remove_css_class("success");
+            self.password.add_css_class("error");
+            self.password_error.set_label(message);
+            self.password_error_revealer.set_reveal_child(true);
+            self.password.grab_focus();
         }
     }
 }
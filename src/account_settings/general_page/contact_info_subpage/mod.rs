@@ -0,0 +1,1011 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+use matrix_sdk::reqwest;
+use rand::{
+    distr::{Alphanumeric, SampleString},
+    rng,
+};
+use ruma::{
+    ClientSecret, OwnedClientSecret, OwnedSessionId,
+    api::client::error::ErrorKind,
+    thirdparty::{Medium, ThirdPartyIdentifier},
+    uint,
+};
+use tracing::error;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    components::{AuthDialog, AuthError, EntryAddRow, RemovableRow, connect_to_identity_server},
+    gettext_f,
+    prelude::*,
+    session::{IdentityServer, IdentityServerError, Session},
+    spawn, spawn_tokio, toast,
+};
+
+/// The length of the client secret used to request validation tokens.
+const CLIENT_SECRET_LENGTH: usize = 32;
+
+/// The maximum number of digits in a phone number, according to E.164.
+const MAX_PHONE_NUMBER_DIGITS: usize = 15;
+
+mod imp {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashSet,
+    };
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/account_settings/general_page/contact_info_subpage/mod.ui"
+    )]
+    #[properties(wrapper_type = super::ContactInfoSubpage)]
+    pub struct ContactInfoSubpage {
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        page: TemplateChild<adw::PreferencesPage>,
+        #[template_child]
+        email_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        phone_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        email_add_row: TemplateChild<EntryAddRow>,
+        #[template_child]
+        phone_add_row: TemplateChild<EntryAddRow>,
+        #[template_child]
+        discoverability_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        discoverability_list: TemplateChild<gtk::ListBox>,
+        /// The current session.
+        #[property(get, set = Self::set_session, construct_only)]
+        session: glib::WeakRef<Session>,
+        /// The third-party identifiers associated to the account.
+        threepids: RefCell<Vec<ThirdPartyIdentifier>>,
+        /// Whether the user can change the third-party identifiers.
+        can_change: Cell<bool>,
+        /// The identity server of the account, if any.
+        identity_server: RefCell<Option<IdentityServer>>,
+        /// The access token for the identity server, if the user accepted its
+        /// terms.
+        identity_server_access_token: RefCell<Option<String>>,
+        /// The addresses of the third-party identifiers that are discoverable
+        /// on the identity server.
+        discoverable_addresses: RefCell<HashSet<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ContactInfoSubpage {
+        const NAME: &'static str = "ContactInfoSubpage";
+        type Type = super::ContactInfoSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ContactInfoSubpage {}
+
+    impl WidgetImpl for ContactInfoSubpage {}
+    impl NavigationPageImpl for ContactInfoSubpage {}
+
+    #[gtk::template_callbacks]
+    impl ContactInfoSubpage {
+        /// Set the current session.
+        fn set_session(&self, session: &Session) {
+            self.session.set(Some(session));
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.load().await;
+                }
+            ));
+        }
+
+        /// Load the third-party identifiers and the settings of the account.
+        async fn load(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            self.stack.set_visible_child_name("loading");
+
+            let client = session.client();
+            let http_client = session.http_client();
+            let handle = spawn_tokio!(async move {
+                let account = client.account();
+                (
+                    account.get_3pids().await,
+                    client.get_capabilities().await,
+                    IdentityServer::from_account_data(&client, http_client).await,
+                )
+            });
+            let (threepids_result, capabilities_result, identity_server_result) =
+                handle.await.expect("task was not aborted");
+
+            match threepids_result {
+                Ok(response) => {
+                    self.threepids.replace(response.threepids);
+                }
+                Err(error) => {
+                    error!("Could not get third-party identifiers: {error}");
+                    toast!(self.obj(), gettext("Could not load contact information"));
+                }
+            }
+
+            match capabilities_result {
+                Ok(capabilities) => {
+                    self.can_change
+                        .set(capabilities.thirdparty_id_changes.enabled);
+                }
+                Err(error) => {
+                    error!("Could not get server capabilities: {error}");
+                    self.can_change.set(false);
+                }
+            }
+
+            let identity_server = match identity_server_result {
+                Ok(identity_server) => identity_server,
+                Err(error) => {
+                    error!("Could not get identity server: {error}");
+                    None
+                }
+            };
+            self.update_description(identity_server.as_ref());
+            self.identity_server.replace(identity_server);
+
+            self.update_lists();
+            self.stack.set_visible_child_name("content");
+
+            self.load_discoverability().await;
+        }
+
+        /// Update the description of the page for the given identity server.
+        fn update_description(&self, identity_server: Option<&IdentityServer>) {
+            let description = if let Some(identity_server) = identity_server {
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "Your contact information can be used by people who know it to find you, if it is shared with the identity server {identity_server}.",
+                    &[("identity_server", &identity_server.server_name())],
+                )
+            } else {
+                gettext(
+                    "No identity server is configured, so people cannot use your contact information to find you. It can still be used to recover your account.",
+                )
+            };
+
+            self.page.set_description(&description);
+        }
+
+        /// Update the lists of third-party identifiers.
+        fn update_lists(&self) {
+            self.email_list.remove_all();
+            self.phone_list.remove_all();
+
+            let can_change = self.can_change.get();
+
+            for threepid in self.threepids.borrow().iter() {
+                let list = match threepid.medium {
+                    Medium::Email => &self.email_list,
+                    Medium::Msisdn => &self.phone_list,
+                    _ => continue,
+                };
+
+                list.append(&self.create_row(threepid, can_change));
+            }
+
+            if can_change {
+                for (list, add_row) in [
+                    (&*self.email_list, &*self.email_add_row),
+                    (&*self.phone_list, &*self.phone_add_row),
+                ] {
+                    if let Some(parent) = add_row.parent().and_downcast::<gtk::ListBox>() {
+                        parent.remove(add_row);
+                    }
+                    list.append(add_row);
+                }
+            }
+
+            // Show a placeholder in empty lists.
+            for list in [&*self.email_list, &*self.phone_list] {
+                if list.first_child().is_none() {
+                    let row = adw::ActionRow::builder()
+                        .title(gettext("None"))
+                        .css_classes(["dimmed"])
+                        .build();
+                    list.append(&row);
+                }
+            }
+
+            if self.discoverability_group.is_visible() {
+                self.update_discoverability_list();
+            }
+        }
+
+        /// Load whether the third-party identifiers are discoverable on the
+        /// identity server.
+        async fn load_discoverability(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let Some(identity_server) = self.identity_server.borrow().clone() else {
+                self.identity_server_access_token.take();
+                self.discoverability_group.set_visible(false);
+                return;
+            };
+
+            let threepids = self
+                .threepids
+                .borrow()
+                .iter()
+                .filter(|threepid| matches!(threepid.medium, Medium::Email | Medium::Msisdn))
+                .map(|threepid| (threepid.medium.clone(), threepid.address.clone()))
+                .collect::<Vec<_>>();
+
+            let client = session.client();
+            let handle = spawn_tokio!(async move {
+                let access_token = identity_server.register(&client).await?;
+
+                // We can only use the identity server once its terms were accepted.
+                if !identity_server.unaccepted_terms(&client).await?.is_empty() {
+                    return Ok(None);
+                }
+
+                let user_ids = identity_server.lookup(&access_token, &threepids).await?;
+                let discoverable = threepids
+                    .into_iter()
+                    .zip(user_ids)
+                    .filter(|(_, user_id)| user_id.as_deref() == client.user_id())
+                    .map(|((_, address), _)| address)
+                    .collect::<HashSet<_>>();
+
+                Ok::<_, IdentityServerError>(Some((access_token, discoverable)))
+            });
+
+            match handle.await.expect("task was not aborted") {
+                Ok(Some((access_token, discoverable))) => {
+                    self.identity_server_access_token
+                        .replace(Some(access_token));
+                    self.discoverable_addresses.replace(discoverable);
+                }
+                Ok(None) => {
+                    self.identity_server_access_token.take();
+                    self.discoverable_addresses.borrow_mut().clear();
+                }
+                Err(error) => {
+                    error!("Could not load discoverability of contact information: {error}");
+                    self.discoverability_group.set_visible(false);
+                    return;
+                }
+            }
+
+            self.update_discoverability_list();
+            self.discoverability_group.set_visible(true);
+        }
+
+        /// Update the list of the discoverability of the third-party
+        /// identifiers.
+        fn update_discoverability_list(&self) {
+            self.discoverability_list.remove_all();
+
+            if self.identity_server_access_token.borrow().is_none() {
+                // The user must accept the terms of the identity server first.
+                let button = gtk::Button::builder()
+                    .label(gettext("Review Terms"))
+                    .valign(gtk::Align::Center)
+                    .build();
+                button.connect_clicked(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        spawn!(async move {
+                            imp.accept_identity_server_terms().await;
+                        });
+                    }
+                ));
+
+                let row = adw::ActionRow::builder()
+                    .title(gettext(
+                        "Accept the terms of the identity server to choose which contact information is discoverable",
+                    ))
+                    .activatable_widget(&button)
+                    .build();
+                row.add_suffix(&button);
+
+                self.discoverability_list.append(&row);
+                return;
+            }
+
+            let discoverable = self.discoverable_addresses.borrow();
+
+            for threepid in self
+                .threepids
+                .borrow()
+                .iter()
+                .filter(|threepid| matches!(threepid.medium, Medium::Email | Medium::Msisdn))
+            {
+                let row = adw::SwitchRow::builder()
+                    .title(&threepid.address)
+                    .use_markup(false)
+                    .active(discoverable.contains(&threepid.address))
+                    .build();
+
+                let threepid = threepid.clone();
+                row.connect_active_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |row| {
+                        let threepid = threepid.clone();
+                        let row = row.clone();
+
+                        spawn!(async move {
+                            imp.set_discoverable(&row, threepid).await;
+                        });
+                    }
+                ));
+
+                self.discoverability_list.append(&row);
+            }
+
+            if self.discoverability_list.first_child().is_none() {
+                let row = adw::ActionRow::builder()
+                    .title(gettext("None"))
+                    .css_classes(["dimmed"])
+                    .build();
+                self.discoverability_list.append(&row);
+            }
+        }
+
+        /// Ask the user to accept the terms of the identity server.
+        async fn accept_identity_server_terms(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let Some(identity_server) = self.identity_server.borrow().clone() else {
+                return;
+            };
+
+            if connect_to_identity_server(&session, &identity_server, &*self.obj())
+                .await
+                .is_some()
+            {
+                self.load_discoverability().await;
+            }
+        }
+
+        /// Make the given third-party identifier discoverable or not on the
+        /// identity server, according to the state of the given row.
+        async fn set_discoverable(&self, row: &adw::SwitchRow, threepid: ThirdPartyIdentifier) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let Some(identity_server) = self.identity_server.borrow().clone() else {
+                return;
+            };
+            let Some(access_token) = self.identity_server_access_token.borrow().clone() else {
+                return;
+            };
+
+            let discoverable = row.is_active();
+            row.set_sensitive(false);
+
+            let success = if discoverable {
+                self.bind_threepid(&session, &identity_server, access_token, &threepid)
+                    .await
+            } else {
+                let client = session.client();
+                let medium = threepid.medium.clone();
+                let address = threepid.address.clone();
+                let handle =
+                    spawn_tokio!(
+                        async move { identity_server.unbind(&client, medium, address).await }
+                    );
+
+                match handle.await.expect("task was not aborted") {
+                    Ok(()) => true,
+                    Err(error) => {
+                        error!("Could not unbind third-party identifier: {error}");
+                        toast!(
+                            self.obj(),
+                            gettext("Could not stop the discoverability of contact information")
+                        );
+                        false
+                    }
+                }
+            };
+
+            if success {
+                let mut discoverable_addresses = self.discoverable_addresses.borrow_mut();
+                if discoverable {
+                    discoverable_addresses.insert(threepid.address);
+                } else {
+                    discoverable_addresses.remove(&threepid.address);
+                }
+            }
+
+            // Rebuild the list, to reset the state of the row in case of failure.
+            self.update_discoverability_list();
+        }
+
+        /// Validate the given third-party identifier with the given identity
+        /// server, and bind it to the account.
+        ///
+        /// Returns `true` if the identifier was bound.
+        async fn bind_threepid(
+            &self,
+            session: &Session,
+            identity_server: &IdentityServer,
+            access_token: String,
+            threepid: &ThirdPartyIdentifier,
+        ) -> bool {
+            let obj = self.obj();
+            let is_email = threepid.medium == Medium::Email;
+            let client_secret = new_client_secret();
+
+            let identity_server_clone = identity_server.clone();
+            let access_token_clone = access_token.clone();
+            let secret_clone = client_secret.clone();
+            let medium = threepid.medium.clone();
+            let address = threepid.address.clone();
+            let handle = spawn_tokio!(async move {
+                identity_server_clone
+                    .request_validation_token(
+                        &access_token_clone,
+                        &secret_clone,
+                        &medium,
+                        &address,
+                        uint!(1),
+                    )
+                    .await
+            });
+
+            let sid = match handle.await.expect("task was not aborted") {
+                Ok(sid) => sid,
+                Err(error) => {
+                    error!("Could not request validation token from identity server: {error}");
+                    let message = if is_email {
+                        gettext("Could not send a verification email")
+                    } else {
+                        gettext("Could not send a verification code")
+                    };
+                    toast!(obj, message);
+                    return false;
+                }
+            };
+
+            if !is_email {
+                loop {
+                    let Some(code) = self.ask_phone_code(&threepid.address).await else {
+                        return false;
+                    };
+
+                    let identity_server = identity_server.clone();
+                    let access_token = access_token.clone();
+                    let client_secret = client_secret.clone();
+                    let sid = sid.clone();
+                    let handle = spawn_tokio!(async move {
+                        identity_server
+                            .submit_msisdn_token(&access_token, &client_secret, &sid, &code)
+                            .await
+                    });
+
+                    match handle.await.expect("task was not aborted") {
+                        Ok(()) => break,
+                        Err(IdentityServerError::InvalidToken) => {
+                            // Let the user try again.
+                            toast!(obj, gettext("The code is invalid"));
+                        }
+                        Err(error) => {
+                            error!("Could not submit validation token to identity server: {error}");
+                            toast!(obj, gettext("Could not verify the phone number"));
+                            return false;
+                        }
+                    }
+                }
+            }
+
+            loop {
+                if is_email && !self.ask_email_verified(&threepid.address).await {
+                    return false;
+                }
+
+                let client = session.client();
+                let identity_server = identity_server.clone();
+                let access_token = access_token.clone();
+                let client_secret = client_secret.clone();
+                let sid = sid.clone();
+                let handle = spawn_tokio!(async move {
+                    identity_server
+                        .bind(&client, access_token, &client_secret, sid)
+                        .await
+                });
+
+                match handle.await.expect("task was not aborted") {
+                    Ok(()) => return true,
+                    Err(IdentityServerError::Homeserver(error))
+                        if is_email
+                            && matches!(
+                                error.client_api_error_kind(),
+                                Some(ErrorKind::ThreepidAuthFailed)
+                            ) =>
+                    {
+                        // Let the user try again.
+                        toast!(obj, gettext("The email address was not verified yet"));
+                    }
+                    Err(error) => {
+                        error!("Could not bind third-party identifier: {error}");
+                        toast!(
+                            obj,
+                            gettext("Could not make contact information discoverable")
+                        );
+                        return false;
+                    }
+                }
+            }
+        }
+
+        /// Create a row for the given third-party identifier.
+        fn create_row(&self, threepid: &ThirdPartyIdentifier, can_change: bool) -> gtk::Widget {
+            if !can_change {
+                let row = adw::ActionRow::builder()
+                    .title(&threepid.address)
+                    .use_markup(false)
+                    .build();
+                return row.upcast();
+            }
+
+            let row = RemovableRow::new();
+            row.set_use_markup(false);
+            row.set_title(&threepid.address);
+            row.set_remove_button_tooltip_text(Some(gettext("Remove")));
+            row.set_remove_button_accessible_label(Some(gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}',
+                // this is a variable name.
+                "Remove “{address}”",
+                &[("address", &threepid.address)],
+            )));
+
+            let threepid = threepid.clone();
+            row.connect_remove(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |row| {
+                    let threepid = threepid.clone();
+                    let row = row.clone();
+
+                    spawn!(async move {
+                        imp.remove_threepid(&row, threepid).await;
+                    });
+                }
+            ));
+
+            row.upcast()
+        }
+
+        /// Remove the given third-party identifier from the account.
+        async fn remove_threepid(&self, row: &RemovableRow, threepid: ThirdPartyIdentifier) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            row.set_is_loading(true);
+
+            let client = session.client();
+            let address = threepid.address.clone();
+            let medium = threepid.medium.clone();
+            let handle = spawn_tokio!(async move {
+                // Let the homeserver unbind it from the identity server it was bound with.
+                client.account().delete_3pid(&address, medium, None).await
+            });
+
+            match handle.await.expect("task was not aborted") {
+                Ok(_) => {
+                    self.threepids
+                        .borrow_mut()
+                        .retain(|t| t.address != threepid.address || t.medium != threepid.medium);
+                    self.update_lists();
+                }
+                Err(error) => {
+                    error!("Could not remove third-party identifier: {error}");
+                    toast!(self.obj(), gettext("Could not remove contact information"));
+                    row.set_is_loading(false);
+                }
+            }
+        }
+
+        /// Add the email address in the add row to the account.
+        #[template_callback]
+        async fn add_email(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let row = &self.email_add_row;
+            let email = row.text().trim().to_owned();
+
+            if email.is_empty() || !email.contains('@') || email.chars().any(char::is_whitespace) {
+                row.add_css_class("error");
+                return;
+            }
+            row.remove_css_class("error");
+
+            row.set_is_loading(true);
+
+            let client_secret = new_client_secret();
+
+            let client = session.client();
+            let secret_clone = client_secret.clone();
+            let email_clone = email.clone();
+            let handle = spawn_tokio!(async move {
+                client
+                    .account()
+                    .request_3pid_email_token(&secret_clone, &email_clone, uint!(1))
+                    .await
+            });
+
+            let sid = match handle.await.expect("task was not aborted") {
+                Ok(response) => response.sid,
+                Err(error) => {
+                    error!("Could not request email validation token: {error}");
+
+                    let message = if matches!(
+                        error.client_api_error_kind(),
+                        Some(ErrorKind::ThreepidInUse)
+                    ) {
+                        gettext("This email address is already used by another account")
+                    } else {
+                        gettext("Could not send a verification email")
+                    };
+                    toast!(self.obj(), message);
+
+                    row.set_is_loading(false);
+                    return;
+                }
+            };
+
+            if self
+                .confirm_email(&session, &email, client_secret, sid)
+                .await
+            {
+                row.set_text("");
+                self.load().await;
+            }
+
+            row.set_is_loading(false);
+        }
+
+        /// Wait for the user to validate the given email address, and add it to
+        /// the account.
+        ///
+        /// Returns `true` if the email address was added.
+        async fn confirm_email(
+            &self,
+            session: &Session,
+            email: &str,
+            client_secret: OwnedClientSecret,
+            sid: OwnedSessionId,
+        ) -> bool {
+            let obj = self.obj();
+
+            loop {
+                if !self.ask_email_verified(email).await {
+                    return false;
+                }
+
+                let client_secret = client_secret.clone();
+                let sid = sid.clone();
+                let result = AuthDialog::new(session)
+                    .authenticate(&*obj, move |client, auth| {
+                        let client_secret = client_secret.clone();
+                        let sid = sid.clone();
+                        async move { client.account().add_3pid(&client_secret, &sid, auth).await }
+                    })
+                    .await;
+
+                match result {
+                    Ok(_) => return true,
+                    Err(AuthError::UserCancelled) => return false,
+                    Err(AuthError::ServerResponse(error))
+                        if matches!(
+                            error.client_api_error_kind(),
+                            Some(ErrorKind::ThreepidAuthFailed)
+                        ) =>
+                    {
+                        // Let the user try again.
+                        toast!(obj, gettext("The email address was not verified yet"));
+                    }
+                    Err(error) => {
+                        error!("Could not add email address: {error}");
+                        toast!(obj, gettext("Could not add email address"));
+                        return false;
+                    }
+                }
+            }
+        }
+
+        /// Ask the user to follow the link in the email sent to the given
+        /// address.
+        ///
+        /// Returns `true` if the user wants to continue.
+        async fn ask_email_verified(&self, email: &str) -> bool {
+            let dialog = adw::AlertDialog::builder()
+                .heading(gettext("Verify Your Email Address"))
+                .body(gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "An email was sent to {email}. Follow the link that it contains, then come back here to continue.",
+                    &[("email", email)],
+                ))
+                .default_response("continue")
+                .build();
+            dialog.add_responses(&[
+                ("cancel", &gettext("Cancel")),
+                ("continue", &gettext("Continue")),
+            ]);
+            dialog.set_response_appearance("continue", adw::ResponseAppearance::Suggested);
+
+            dialog.choose_future(Some(&*self.obj())).await == "continue"
+        }
+
+        /// Ask the user for the code in the text message sent to the given
+        /// phone number.
+        ///
+        /// Returns `None` if the user cancelled.
+        async fn ask_phone_code(&self, phone_number: &str) -> Option<String> {
+            let entry = adw::EntryRow::builder()
+                .title(gettext("Code"))
+                .input_purpose(gtk::InputPurpose::Digits)
+                .activates_default(true)
+                .build();
+            let list_box = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .margin_top(6)
+                .accessible_role(gtk::AccessibleRole::Group)
+                .build();
+            list_box.append(&entry);
+
+            let dialog = adw::AlertDialog::builder()
+                .heading(gettext("Verify Your Phone Number"))
+                .body(gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "A text message with a code was sent to {phone_number}. Enter the code to continue.",
+                    &[("phone_number", phone_number)],
+                ))
+                .default_response("verify")
+                .extra_child(&list_box)
+                .build();
+            dialog.add_responses(&[
+                ("cancel", &gettext("Cancel")),
+                ("verify", &gettext("Verify")),
+            ]);
+            dialog.set_response_appearance("verify", adw::ResponseAppearance::Suggested);
+
+            if dialog.choose_future(Some(&*self.obj())).await != "verify" {
+                return None;
+            }
+
+            Some(entry.text().trim().to_owned()).filter(|code| !code.is_empty())
+        }
+
+        /// Add the phone number in the add row to the account.
+        #[template_callback]
+        async fn add_phone(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let row = &self.phone_add_row;
+            // Ignore the separators that are commonly used in phone numbers.
+            let phone_number = row
+                .text()
+                .chars()
+                .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '.' | '(' | ')'))
+                .collect::<String>();
+
+            if !is_international_phone_number(&phone_number) {
+                row.add_css_class("error");
+                toast!(
+                    self.obj(),
+                    gettext("Enter the phone number in the international format, starting with +")
+                );
+                return;
+            }
+            row.remove_css_class("error");
+
+            row.set_is_loading(true);
+
+            let client_secret = new_client_secret();
+
+            let client = session.client();
+            let secret_clone = client_secret.clone();
+            let phone_number_clone = phone_number.clone();
+            let handle = spawn_tokio!(async move {
+                client
+                    .account()
+                    // The country is ignored for numbers in the international format.
+                    .request_3pid_msisdn_token(&secret_clone, "", &phone_number_clone, uint!(1))
+                    .await
+            });
+
+            let response = match handle.await.expect("task was not aborted") {
+                Ok(response) => response,
+                Err(error) => {
+                    error!("Could not request phone number validation token: {error}");
+
+                    let message = if matches!(
+                        error.client_api_error_kind(),
+                        Some(ErrorKind::ThreepidInUse)
+                    ) {
+                        gettext("This phone number is already used by another account")
+                    } else {
+                        gettext("Could not send a verification code")
+                    };
+                    toast!(self.obj(), message);
+
+                    row.set_is_loading(false);
+                    return;
+                }
+            };
+
+            if self
+                .confirm_phone_number(
+                    &session,
+                    &phone_number,
+                    client_secret,
+                    response.sid,
+                    response.submit_url,
+                )
+                .await
+            {
+                row.set_text("");
+                self.load().await;
+            }
+
+            row.set_is_loading(false);
+        }
+
+        /// Ask the user for the code sent to the given phone number, and add
+        /// it to the account.
+        ///
+        /// If `submit_url` is set, the code is submitted to it. Otherwise, the
+        /// homeserver validates the phone number by itself.
+        ///
+        /// Returns `true` if the phone number was added.
+        async fn confirm_phone_number(
+            &self,
+            session: &Session,
+            phone_number: &str,
+            client_secret: OwnedClientSecret,
+            sid: OwnedSessionId,
+            submit_url: Option<String>,
+        ) -> bool {
+            let obj = self.obj();
+
+            if let Some(submit_url) = submit_url {
+                loop {
+                    let Some(code) = self.ask_phone_code(phone_number).await else {
+                        return false;
+                    };
+
+                    let http_client = session.http_client();
+                    let submit_url = submit_url.clone();
+                    let body = serde_json::json!({
+                        "client_secret": client_secret,
+                        "sid": sid,
+                        "token": code,
+                    });
+                    let handle = spawn_tokio!(async move {
+                        submit_msisdn_token(http_client, submit_url, &body).await
+                    });
+
+                    match handle.await.expect("task was not aborted") {
+                        Ok(true) => break,
+                        Ok(false) => {
+                            // Let the user try again.
+                            toast!(obj, gettext("The code is invalid"));
+                        }
+                        Err(error) => {
+                            error!("Could not submit phone number validation token: {error}");
+                            toast!(obj, gettext("Could not verify the phone number"));
+                            return false;
+                        }
+                    }
+                }
+            }
+
+            let result = AuthDialog::new(session)
+                .authenticate(&*obj, move |client, auth| {
+                    let client_secret = client_secret.clone();
+                    let sid = sid.clone();
+                    async move { client.account().add_3pid(&client_secret, &sid, auth).await }
+                })
+                .await;
+
+            match result {
+                Ok(_) => true,
+                Err(AuthError::UserCancelled) => false,
+                Err(error) => {
+                    error!("Could not add phone number: {error}");
+                    toast!(obj, gettext("Could not add phone number"));
+                    false
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Subpage allowing the user to manage the contact information of their
+    /// account.
+    pub struct ContactInfoSubpage(ObjectSubclass<imp::ContactInfoSubpage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ContactInfoSubpage {
+    pub fn new(session: &Session) -> Self {
+        glib::Object::builder().property("session", session).build()
+    }
+}
+
+/// Generate a new random client secret, to request validation tokens.
+fn new_client_secret() -> OwnedClientSecret {
+    ClientSecret::parse(Alphanumeric.sample_string(&mut rng(), CLIENT_SECRET_LENGTH))
+        .expect("alphanumeric string should be a valid client secret")
+}
+
+/// Whether the given string is a phone number in the international format,
+/// without separators.
+fn is_international_phone_number(s: &str) -> bool {
+    s.strip_prefix('+').is_some_and(|digits| {
+        (1..=MAX_PHONE_NUMBER_DIGITS).contains(&digits.len())
+            && digits.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+/// Submit the token to validate a phone number to the given URL, provided by
+/// the homeserver.
+///
+/// Returns whether the token was accepted.
+async fn submit_msisdn_token(
+    http_client: reqwest::Client,
+    url: String,
+    body: &serde_json::Value,
+) -> Result<bool, reqwest::Error> {
+    let response = http_client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await?;
+
+    if response.status().is_client_error() {
+        // The token is invalid.
+        return Ok(false);
+    }
+
+    let bytes = response.error_for_status()?.bytes().await?;
+    let success = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|value| value.get("success")?.as_bool())
+        .unwrap_or_default();
+
+    Ok(success)
+}
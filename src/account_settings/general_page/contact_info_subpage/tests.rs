@@ -0,0 +1,16 @@
+use super::is_international_phone_number;
+
+#[test]
+fn international_phone_numbers() {
+    assert!(is_international_phone_number("+33612345678"));
+    assert!(is_international_phone_number("+1"));
+    assert!(is_international_phone_number("+123456789012345"));
+
+    assert!(!is_international_phone_number(""));
+    assert!(!is_international_phone_number("+"));
+    assert!(!is_international_phone_number("0612345678"));
+    assert!(!is_international_phone_number("+33 6 12 34 56 78"));
+    assert!(!is_international_phone_number("+336123456a8"));
+    assert!(!is_international_phone_number("+1234567890123456"));
+    assert!(!is_international_phone_number("+٣٣٦١٢"));
+}
@@ -6,7 +6,8 @@ use tracing::error;
 
 use super::AccountSettings;
 use crate::{
-    components::{AuthDialog, LoadingButtonRow},
+    components::{AuthDialog, AuthError, LoadingButtonRow},
+    gettext_f,
     prelude::*,
     session::Session,
     toast,
@@ -23,6 +24,10 @@ mod imp {
     )]
     #[properties(wrapper_type = super::DeactivateAccountSubpage)]
     pub struct DeactivateAccountSubpage {
+        #[template_child]
+        erase_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        acknowledgement: TemplateChild<gtk::CheckButton>,
         #[template_child]
         confirmation: TemplateChild<adw::EntryRow>,
         #[template_child]
@@ -106,7 +111,8 @@ mod imp {
 
         /// Whether the account can be deactivated with the current state.
         fn can_deactivate_account(&self) -> bool {
-            self.confirmation.text() == self.confirmation.title()
+            self.acknowledgement.is_active()
+                && self.confirmation.text() == self.confirmation.title()
         }
 
         /// Deactivate the account with the proper method.
@@ -132,14 +138,15 @@ mod imp {
             }
 
             self.loading_button.set_is_loading(true);
-            self.confirmation.set_sensitive(false);
+            self.set_form_sensitive(false);
 
+            let erase = self.erase_row.is_active();
             let dialog = AuthDialog::new(&session);
             let obj = self.obj();
 
             let result = dialog
                 .authenticate(&*obj, move |client, auth| async move {
-                    client.account().deactivate(None, auth, false).await
+                    client.account().deactivate(None, auth, erase).await
                 })
                 .await;
 
@@ -153,13 +160,33 @@ mod imp {
                     }
                     let _ = obj.activate_action("account-settings.close", None);
                 }
+                Err(AuthError::UserCancelled) => {}
+                Err(AuthError::ServerResponse(error)) => {
+                    error!("Could not deactivate account: {error:?}");
+                    toast!(
+                        obj,
+                        gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}',
+                            // this is a variable name.
+                            "Could not deactivate account: {error}",
+                            &[("error", &error.to_user_facing())],
+                        )
+                    );
+                }
                 Err(error) => {
                     error!("Could not deactivate account: {error:?}");
                     toast!(obj, gettext("Could not deactivate account"));
                 }
             }
             self.loading_button.set_is_loading(false);
-            self.confirmation.set_sensitive(true);
+            self.set_form_sensitive(true);
+        }
+
+        /// Set whether the form can be edited.
+        fn set_form_sensitive(&self, sensitive: bool) {
+            self.erase_row.set_sensitive(sensitive);
+            self.acknowledgement.set_sensitive(sensitive);
+            self.confirmation.set_sensitive(sensitive);
         }
 
         // Open the account management URL to deactivate the account.
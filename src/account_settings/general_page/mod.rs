@@ -9,24 +9,30 @@ use ruma::{
         client::{
             discovery::get_capabilities::v3::Capabilities,
             profile::{ProfileFieldName, delete_profile_field},
+            session::get_login_types::v3::LoginType,
         },
     },
 };
 use tracing::error;
+use url::Url;
 
 mod change_password_subpage;
+mod contact_info_subpage;
 mod deactivate_account_subpage;
 mod log_out_subpage;
 
 pub use self::{
-    change_password_subpage::ChangePasswordSubpage,
+    change_password_subpage::ChangePasswordSubpage, contact_info_subpage::ContactInfoSubpage,
     deactivate_account_subpage::DeactivateAccountSubpage, log_out_subpage::LogOutSubpage,
 };
 use super::AccountSettings;
 use crate::{
-    components::{ActionButton, ActionState, ButtonCountRow, CopyableRow, EditableAvatar},
+    Application,
+    components::{
+        ActionButton, ActionState, ButtonCountRow, CopyableRow, EditableAvatar, LoadingButtonRow,
+    },
     prelude::*,
-    session::Session,
+    session::{Session, SessionSyncMode},
     spawn, spawn_tokio, toast,
     utils::{OngoingAsyncAction, TemplateCallbacks, media::FileInfo},
 };
@@ -62,6 +68,26 @@ mod imp {
         session_id: TemplateChild<CopyableRow>,
         #[template_child]
         deactivate_account_button: TemplateChild<adw::ButtonRow>,
+        #[template_child]
+        pagination_batch_size_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        cache_usage_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        media_cache_max_size_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        clear_cache_button: TemplateChild<LoadingButtonRow>,
+        #[template_child]
+        connectivity_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        connectivity_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        proxy_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        reduce_data_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        sliding_sync_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        sync_mode_row: TemplateChild<adw::ActionRow>,
         /// The current session.
         #[property(get, set = Self::set_session, nullable)]
         session: glib::WeakRef<Session>,
@@ -74,6 +100,10 @@ mod imp {
         avatar_uri_handler: RefCell<Option<glib::SignalHandlerId>>,
         display_name_handler: RefCell<Option<glib::SignalHandlerId>>,
         user_sessions_count_handler: RefCell<Option<glib::SignalHandlerId>>,
+        pagination_batch_size_binding: RefCell<Option<glib::Binding>>,
+        reduce_data_binding: RefCell<Option<glib::Binding>>,
+        sliding_sync_binding: RefCell<Option<glib::Binding>>,
+        connectivity_handlers: RefCell<Vec<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -94,7 +124,20 @@ mod imp {
     }
 
     #[glib::derived_properties]
-    impl ObjectImpl for GeneralPage {}
+    impl ObjectImpl for GeneralPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            Application::default()
+                .settings()
+                .bind(
+                    "media-cache-max-size",
+                    &*self.media_cache_max_size_row,
+                    "value",
+                )
+                .build();
+        }
+    }
 
     impl WidgetImpl for GeneralPage {}
     impl PreferencesPageImpl for GeneralPage {}
@@ -123,6 +166,18 @@ mod imp {
                 if let Some(handler) = self.user_sessions_count_handler.take() {
                     session.user_sessions().other_sessions().disconnect(handler);
                 }
+                if let Some(binding) = self.pagination_batch_size_binding.take() {
+                    binding.unbind();
+                }
+                if let Some(binding) = self.reduce_data_binding.take() {
+                    binding.unbind();
+                }
+                if let Some(binding) = self.sliding_sync_binding.take() {
+                    binding.unbind();
+                }
+                for handler in self.connectivity_handlers.take() {
+                    session.disconnect(handler);
+                }
             }
 
             self.session.set(session.as_ref());
@@ -174,6 +229,73 @@ mod imp {
             self.user_sessions_count_handler
                 .replace(Some(user_sessions_count_handler));
 
+            let pagination_batch_size_binding = session
+                .settings()
+                .bind_property(
+                    "pagination-batch-size",
+                    &*self.pagination_batch_size_row,
+                    "value",
+                )
+                .bidirectional()
+                .sync_create()
+                .build();
+            self.pagination_batch_size_binding
+                .replace(Some(pagination_batch_size_binding));
+
+            self.proxy_row.set_text(&session.settings().proxy());
+
+            let reduce_data_binding = session
+                .settings()
+                .bind_property(
+                    "reduce-data-on-metered-connections",
+                    &*self.reduce_data_row,
+                    "active",
+                )
+                .bidirectional()
+                .sync_create()
+                .build();
+            self.reduce_data_binding.replace(Some(reduce_data_binding));
+
+            let is_offline_handler = session.connect_is_offline_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_connectivity();
+                }
+            ));
+            let is_saving_data_handler = session.connect_is_saving_data_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_connectivity();
+                }
+            ));
+            let sync_mode_handler = session.connect_sync_mode_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_sync_mode();
+                }
+            ));
+            self.connectivity_handlers.replace(vec![
+                is_offline_handler,
+                is_saving_data_handler,
+                sync_mode_handler,
+            ]);
+            self.update_connectivity();
+            self.update_sync_mode();
+
+            spawn!(
+                glib::Priority::LOW,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    async move {
+                        imp.update_cache_usage().await;
+                    }
+                )
+            );
+
             spawn!(
                 glib::Priority::LOW,
                 clone!(
@@ -186,6 +308,102 @@ mod imp {
             );
         }
 
+        /// Update the cache usage of the session.
+        async fn update_cache_usage(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let size = session.media_cache_size().await;
+            self.cache_usage_row.set_subtitle(&glib::format_size(size));
+        }
+
+        /// Remove all the media from the cache of the session.
+        #[template_callback]
+        async fn clear_media_cache(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            self.clear_cache_button.set_is_loading(true);
+
+            if let Err(error) = session.clear_media_cache().await {
+                error!("Could not clear media cache: {error}");
+                toast!(self.obj(), gettext("Could not clear the media cache"));
+            } else {
+                toast!(self.obj(), gettext("Media cache cleared"));
+            }
+
+            self.update_cache_usage().await;
+            self.clear_cache_button.set_is_loading(false);
+        }
+
+        /// Update the connectivity status of the session.
+        fn update_connectivity(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let (icon_name, status) = if session.is_offline() {
+                ("sync-off-symbolic", gettext("Offline"))
+            } else if session.is_saving_data() {
+                (
+                    "sync-partial-symbolic",
+                    gettext("Connected, reducing data usage"),
+                )
+            } else {
+                ("sync-on-symbolic", gettext("Connected"))
+            };
+
+            self.connectivity_icon.set_icon_name(Some(icon_name));
+            self.connectivity_row.set_subtitle(&status);
+        }
+
+        /// Update the synchronization mode of the session.
+        fn update_sync_mode(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let sync_mode = match session.sync_mode() {
+                SessionSyncMode::Classic => gettext("Classic"),
+                SessionSyncMode::Sliding => gettext("Simplified Sliding Sync"),
+            };
+            self.sync_mode_row.set_subtitle(&sync_mode);
+        }
+
+        /// Apply the proxy in the entry.
+        #[template_callback]
+        fn apply_proxy(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            let proxy = self.proxy_row.text();
+            let proxy = proxy.trim();
+
+            if !proxy.is_empty() {
+                let is_valid = Url::parse(proxy).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h")
+                        && url.has_host()
+                });
+
+                if !is_valid {
+                    toast!(
+                        self.obj(),
+                        gettext("The proxy must be a valid HTTP or SOCKS5 URL")
+                    );
+                    return;
+                }
+            }
+
+            session.settings().set_proxy(proxy.to_owned());
+            toast!(
+                self.obj(),
+                gettext("The proxy will be used after restarting the app")
+            );
+        }
+
         /// Set the acestor [`AccountSettings`].
         fn set_account_settings(&self, account_settings: Option<&AccountSettings>) {
             self.account_settings.set(account_settings);
@@ -219,14 +437,23 @@ mod imp {
 
             let mut capabilities_data = CapabilitiesData::default();
 
+            let uses_oauth_api = session.uses_oauth_api();
             let client = session.client();
             let handle = spawn_tokio!(async move {
+                // With the OAuth 2.0 API, the password is managed by the authorization server.
+                let login_types = if uses_oauth_api {
+                    None
+                } else {
+                    Some(client.matrix_auth().get_login_types().await)
+                };
+
                 (
                     client.get_capabilities().await,
                     client.supported_versions().await,
+                    login_types,
                 )
             });
-            let (capabilities_result, supported_versions_result) =
+            let (capabilities_result, supported_versions_result, login_types_result) =
                 handle.await.expect("task was not aborted");
 
             match capabilities_result {
@@ -243,6 +470,22 @@ mod imp {
                 }
             }
 
+            match login_types_result {
+                Some(Ok(response)) => {
+                    capabilities_data.supports_password_login = response
+                        .flows
+                        .iter()
+                        .any(|login_type| matches!(login_type, LoginType::Password(_)));
+                }
+                Some(Err(error)) => {
+                    error!("Could not get server login types: {error}");
+                }
+                None => {
+                    // The password cannot be changed through the Matrix API.
+                    capabilities_data.supports_password_login = false;
+                }
+            }
+
             self.capabilities_data.replace(capabilities_data);
             self.update_capabilities();
         }
@@ -534,7 +777,8 @@ mod imp {
             match handle.await.expect("task was not aborted") {
                 Ok(()) => {
                     // If the user is in no rooms, we won't receive the update via sync, so change
-                    // the avatar manually if this request succeeds before the avatar is updated.
+                    // the display name manually if this request succeeds before the display name
+                    // is updated.
                     // Because this action can finish in user_display_name_changed, we must only act
                     // if this is still the current action.
                     if weak_action.is_ongoing() {
@@ -578,6 +822,11 @@ struct CapabilitiesData {
     capabilities: Capabilities,
     /// The Matrix versions supported by the homeserver.
     supported_versions: SupportedVersions,
+    /// Whether the homeserver supports logging in with a password.
+    ///
+    /// If it doesn't, the account likely doesn't have a password, e.g. it is
+    /// only accessible via SSO.
+    supports_password_login: bool,
 }
 
 impl CapabilitiesData {
@@ -609,7 +858,7 @@ impl CapabilitiesData {
     /// Whether the user can change their account password according to these
     /// capabilities.
     fn can_change_password(&self) -> bool {
-        self.capabilities.change_password.enabled
+        self.supports_password_login && self.capabilities.change_password.enabled
     }
 }
 
@@ -621,6 +870,7 @@ impl Default for CapabilitiesData {
                 versions: Default::default(),
                 features: Default::default(),
             },
+            supports_password_login: true,
         }
     }
 }
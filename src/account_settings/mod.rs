@@ -8,6 +8,7 @@ use matrix_sdk::authentication::oauth::{
 };
 use tracing::{error, warn};
 
+mod appearance_page;
 mod encryption_page;
 mod general_page;
 mod notifications_page;
@@ -15,15 +16,21 @@ mod safety_page;
 mod user_session;
 
 use self::{
+    appearance_page::AppearancePage,
     encryption_page::{EncryptionPage, ImportExportKeysSubpage, ImportExportKeysSubpageMode},
-    general_page::{ChangePasswordSubpage, DeactivateAccountSubpage, GeneralPage, LogOutSubpage},
-    notifications_page::NotificationsPage,
+    general_page::{
+        ChangePasswordSubpage, ContactInfoSubpage, DeactivateAccountSubpage, GeneralPage,
+        LogOutSubpage,
+    },
+    notifications_page::{NotificationsPage, PushRulesSubpage},
     safety_page::{IgnoredUsersSubpage, SafetyPage},
     user_session::{UserSessionListSubpage, UserSessionSubpage},
 };
 use crate::{
-    components::crypto::{CryptoIdentitySetupView, CryptoRecoverySetupView},
-    session::Session,
+    components::crypto::{
+        CryptoIdentitySetupNextStep, CryptoIdentitySetupView, CryptoRecoverySetupView,
+    },
+    session::{RecoveryState, Session},
     spawn, spawn_tokio,
     utils::BoundObjectWeakRef,
 };
@@ -33,6 +40,8 @@ use crate::{
 pub(crate) enum AccountSettingsSubpage {
     /// A form to change the account's password.
     ChangePassword,
+    /// A page to manage the contact information of the account.
+    ContactInfo,
     /// A page to view the list of account's sessions.
     UserSessionList,
     /// A page to confirm the logout.
@@ -41,6 +50,8 @@ pub(crate) enum AccountSettingsSubpage {
     DeactivateAccount,
     /// The list of ignored users.
     IgnoredUsers,
+    /// The editor of the push rules.
+    PushRules,
     /// A form to import encryption keys.
     ImportKeys,
     /// A form to export encryption keys.
@@ -81,6 +92,7 @@ mod imp {
             NotificationsPage::ensure_type();
             SafetyPage::ensure_type();
             EncryptionPage::ensure_type();
+            AppearancePage::ensure_type();
 
             Self::bind_template(klass);
 
@@ -273,6 +285,7 @@ impl AccountSettings {
 
         let page: adw::NavigationPage = match subpage {
             AccountSettingsSubpage::ChangePassword => ChangePasswordSubpage::new(&session).upcast(),
+            AccountSettingsSubpage::ContactInfo => ContactInfoSubpage::new(&session).upcast(),
             AccountSettingsSubpage::UserSessionList => {
                 UserSessionListSubpage::new(&session).upcast()
             }
@@ -281,6 +294,9 @@ impl AccountSettings {
                 DeactivateAccountSubpage::new(&session, self).upcast()
             }
             AccountSettingsSubpage::IgnoredUsers => IgnoredUsersSubpage::new(&session).upcast(),
+            AccountSettingsSubpage::PushRules => {
+                PushRulesSubpage::new(&session.notifications().settings()).upcast()
+            }
             AccountSettingsSubpage::ImportKeys => {
                 ImportExportKeysSubpage::new(&session, ImportExportKeysSubpageMode::Import).upcast()
             }
@@ -292,8 +308,30 @@ impl AccountSettings {
                 view.connect_completed(clone!(
                     #[weak(rename_to = obj)]
                     self,
-                    move |_, _| {
+                    move |_, next| {
                         obj.pop_subpage();
+
+                        let Some(session) = obj.session() else {
+                            return;
+                        };
+
+                        // Continue with the setup of the recovery, if needed, so a fresh
+                        // account ends up with a secret storage.
+                        let recovery_state = session.security().recovery_state();
+                        let show_recovery = match next {
+                            CryptoIdentitySetupNextStep::None => false,
+                            CryptoIdentitySetupNextStep::EnableRecovery => {
+                                recovery_state == RecoveryState::Disabled
+                            }
+                            CryptoIdentitySetupNextStep::CompleteRecovery => matches!(
+                                recovery_state,
+                                RecoveryState::Disabled | RecoveryState::Incomplete
+                            ),
+                        };
+
+                        if show_recovery {
+                            obj.show_subpage(AccountSettingsSubpage::RecoverySetup);
+                        }
                     }
                 ));
 
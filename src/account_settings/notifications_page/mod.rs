@@ -1,16 +1,27 @@
+use std::time::Duration;
+
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{gio, glib, glib::clone};
 use tracing::error;
 
+mod push_rules_subpage;
+#[cfg(test)]
+mod tests;
+
+pub use self::push_rules_subpage::PushRulesSubpage;
 use crate::{
+    Application,
     components::{CheckLoadingRow, EntryAddRow, RemovableRow, SwitchLoadingRow},
     i18n::gettext_f,
-    session::{NotificationsGlobalSetting, NotificationsSettings},
+    session::{NotificationPreview, NotificationsGlobalSetting, NotificationsSettings},
     spawn, toast,
     utils::{BoundObjectWeakRef, PlaceholderObject, SingleItemListModel},
 };
 
+/// The duration for which notifications are snoozed.
+const SNOOZE_DURATION: Duration = Duration::from_secs(60 * 60);
+
 mod imp {
     use std::{cell::Cell, marker::PhantomData};
 
@@ -19,7 +30,7 @@ mod imp {
     use super::*;
 
     #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
-    #[template(resource = "/org/gnome/Fractal/ui/account_settings/notifications_page.ui")]
+    #[template(resource = "/org/gnome/Fractal/ui/account_settings/notifications_page/mod.ui")]
     #[properties(wrapper_type = super::NotificationsPage)]
     pub struct NotificationsPage {
         #[template_child]
@@ -27,6 +38,22 @@ mod imp {
         #[template_child]
         session_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
+        snooze_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        dnd_schedule_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        dnd_start_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        dnd_end_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        dnd_allow_mentions_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        preview_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        preview_hide_encrypted_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        launcher_badge_mentions_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         global: TemplateChild<adw::PreferencesGroup>,
         #[template_child]
         global_all_row: TemplateChild<CheckLoadingRow>,
@@ -71,7 +98,75 @@ mod imp {
     }
 
     #[glib::derived_properties]
-    impl ObjectImpl for NotificationsPage {}
+    impl ObjectImpl for NotificationsPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let app = Application::default();
+            let settings = app.settings();
+            settings
+                .bind(
+                    "launcher-badge-mentions-only",
+                    &*self.launcher_badge_mentions_row,
+                    "active",
+                )
+                .build();
+
+            // Do not disturb.
+            settings
+                .bind(
+                    "do-not-disturb-schedule-enabled",
+                    &*self.dnd_schedule_row,
+                    "active",
+                )
+                .build();
+            settings
+                .bind(
+                    "do-not-disturb-allow-mentions",
+                    &*self.dnd_allow_mentions_row,
+                    "active",
+                )
+                .build();
+
+            for (key, row) in [
+                ("do-not-disturb-start", &*self.dnd_start_row),
+                ("do-not-disturb-end", &*self.dnd_end_row),
+            ] {
+                // Display the value, in minutes since midnight, as a time.
+                row.connect_output(|row| {
+                    row.set_text(&format_minutes(row.value()));
+                    glib::Propagation::Stop
+                });
+                row.connect_input(|row| parse_minutes(&row.text()).map(Ok));
+
+                settings.bind(key, row, "value").build();
+            }
+
+            app.system_settings()
+                .bind_property("snoozed", &*self.snooze_row, "active")
+                .sync_create()
+                .build();
+
+            // Privacy.
+            settings
+                .bind(
+                    "notification-preview-hide-encrypted",
+                    &*self.preview_hide_encrypted_row,
+                    "active",
+                )
+                .build();
+
+            self.preview_row
+                .set_selected(NotificationPreview::global() as u32);
+            // Connect the handler after setting the initial value, to avoid
+            // overwriting the setting.
+            self.preview_row.connect_selected_notify(|row| {
+                if let Some(preview) = NotificationPreview::from_repr(row.selected()) {
+                    preview.set_global();
+                }
+            });
+        }
+    }
 
     impl WidgetImpl for NotificationsPage {}
     impl PreferencesPageImpl for NotificationsPage {}
@@ -228,6 +323,21 @@ mod imp {
             settings.set_session_enabled(self.session_row.is_active());
         }
 
+        /// Snooze notifications or stop snoozing them, according to the
+        /// snooze row.
+        #[template_callback]
+        fn set_snoozed(&self) {
+            let system_settings = Application::default().system_settings();
+            let snoozed = self.snooze_row.is_active();
+
+            if snoozed == system_settings.snoozed() {
+                // Nothing to do.
+                return;
+            }
+
+            system_settings.snooze_notifications(snoozed.then_some(SNOOZE_DURATION));
+        }
+
         /// The global notifications setting, as a string.
         fn global_setting(&self) -> String {
             let Some(settings) = self.notifications_settings.obj() else {
@@ -462,3 +572,22 @@ impl NotificationsPage {
             .build()
     }
 }
+
+/// Format the given number of minutes since midnight as a time.
+fn format_minutes(minutes: f64) -> String {
+    // The value is bounded by the adjustment, so it fits.
+    #[allow(clippy::cast_sign_loss)]
+    let minutes = minutes.round().max(0.0) as u32;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Parse the given time as a number of minutes since midnight.
+///
+/// Returns `None` if the time is not in the `HH:MM` format.
+fn parse_minutes(time: &str) -> Option<f64> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours = hours.parse::<u32>().ok().filter(|h| *h < 24)?;
+    let minutes = minutes.parse::<u32>().ok().filter(|m| *m < 60)?;
+
+    Some(f64::from(hours * 60 + minutes))
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+use strum::IntoEnumIterator;
+
+use crate::{
+    components::{LoadingBin, LoadingButtonRow},
+    session::{NotificationsSettings, PushRuleSettings, StandardPushRule},
+    spawn, toast,
+};
+
+/// The rows to edit the settings of a push rule.
+#[derive(Debug, Clone)]
+struct PushRuleRows {
+    /// The row to enable the rule, that contains the other rows.
+    expander: adw::ExpanderRow,
+    /// The row to toggle notifications.
+    notify: adw::SwitchRow,
+    /// The row to toggle highlights.
+    highlight: adw::SwitchRow,
+    /// The row to toggle sounds.
+    sound: adw::SwitchRow,
+}
+
+impl PushRuleRows {
+    /// Construct the rows for the given rule.
+    fn new(rule: StandardPushRule) -> Self {
+        let expander = adw::ExpanderRow::builder()
+            .title(rule.title())
+            .show_enable_switch(true)
+            .build();
+
+        let notify = adw::SwitchRow::builder()
+            .title(gettext("Notify"))
+            .subtitle(gettext(
+                "Show a notification and count the message as unread",
+            ))
+            .build();
+        let highlight = adw::SwitchRow::builder()
+            .title(gettext("Highlight"))
+            .subtitle(gettext("Count the message as a mention"))
+            .build();
+        let sound = adw::SwitchRow::builder()
+            .title(gettext("Sound"))
+            .subtitle(gettext("Play a sound on devices that support it"))
+            .build();
+
+        // The tweaks are only applied when the message notifies.
+        notify
+            .bind_property("active", &highlight, "sensitive")
+            .sync_create()
+            .build();
+        notify
+            .bind_property("active", &sound, "sensitive")
+            .sync_create()
+            .build();
+
+        expander.add_row(&notify);
+        expander.add_row(&highlight);
+        expander.add_row(&sound);
+
+        Self {
+            expander,
+            notify,
+            highlight,
+            sound,
+        }
+    }
+
+    /// The settings currently presented by the rows.
+    fn settings(&self) -> PushRuleSettings {
+        PushRuleSettings {
+            enabled: self.expander.enables_expansion(),
+            notify: self.notify.is_active(),
+            highlight: self.highlight.is_active(),
+            sound: self.sound.is_active(),
+        }
+    }
+
+    /// Present the given settings.
+    fn set_settings(&self, settings: PushRuleSettings) {
+        self.expander.set_enable_expansion(settings.enabled);
+        self.notify.set_active(settings.notify);
+        self.highlight.set_active(settings.highlight);
+        self.sound.set_active(settings.sound);
+    }
+
+    /// Set whether the rows can be edited.
+    fn set_sensitive(&self, sensitive: bool) {
+        self.expander.set_sensitive(sensitive);
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/account_settings/notifications_page/push_rules_subpage.ui"
+    )]
+    #[properties(wrapper_type = super::PushRulesSubpage)]
+    pub struct PushRulesSubpage {
+        #[template_child]
+        loading_bin: TemplateChild<LoadingBin>,
+        #[template_child]
+        rules_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        reset_button: TemplateChild<LoadingButtonRow>,
+        /// The notifications settings of the current session.
+        #[property(get, set = Self::set_notifications_settings, construct_only)]
+        notifications_settings: glib::WeakRef<NotificationsSettings>,
+        /// The rows of the rules.
+        rows: RefCell<HashMap<StandardPushRule, PushRuleRows>>,
+        /// Whether the rows are being updated from the push rules.
+        updating: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PushRulesSubpage {
+        const NAME: &'static str = "PushRulesSubpage";
+        type Type = super::PushRulesSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for PushRulesSubpage {}
+
+    impl WidgetImpl for PushRulesSubpage {}
+    impl NavigationPageImpl for PushRulesSubpage {}
+
+    #[gtk::template_callbacks]
+    impl PushRulesSubpage {
+        /// Set the notifications settings of the current session.
+        fn set_notifications_settings(&self, settings: &NotificationsSettings) {
+            self.notifications_settings.set(Some(settings));
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.load().await;
+                }
+            ));
+        }
+
+        /// Load the settings of the push rules.
+        async fn load(&self) {
+            let Some(settings) = self.notifications_settings.upgrade() else {
+                return;
+            };
+
+            self.loading_bin.set_is_loading(true);
+
+            let Some(push_rules_settings) = settings.push_rules_settings().await else {
+                toast!(self.obj(), gettext("Could not load notification rules"));
+                return;
+            };
+
+            self.updating.set(true);
+
+            for rule in StandardPushRule::iter() {
+                let rule_settings = push_rules_settings.get(&rule).copied();
+
+                let rows = self
+                    .rows
+                    .borrow_mut()
+                    .entry(rule)
+                    .or_insert_with(|| self.create_rows(rule))
+                    .clone();
+
+                // Hide the rules that do not exist on the homeserver.
+                rows.expander.set_visible(rule_settings.is_some());
+
+                if let Some(rule_settings) = rule_settings {
+                    rows.set_settings(rule_settings);
+                }
+            }
+
+            self.updating.set(false);
+            self.loading_bin.set_is_loading(false);
+        }
+
+        /// Create the rows for the given rule.
+        fn create_rows(&self, rule: StandardPushRule) -> PushRuleRows {
+            let rows = PushRuleRows::new(rule);
+
+            let callback = clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move || {
+                    imp.rule_changed(rule);
+                }
+            );
+            let expander_callback = callback.clone();
+            rows.expander
+                .connect_enable_expansion_notify(move |_| expander_callback());
+            for row in [&rows.notify, &rows.highlight, &rows.sound] {
+                let callback = callback.clone();
+                row.connect_active_notify(move |_| callback());
+            }
+
+            self.rules_group.add(&rows.expander);
+
+            rows
+        }
+
+        /// Handle when the settings of the given rule changed in the rows.
+        fn rule_changed(&self, rule: StandardPushRule) {
+            if self.updating.get() {
+                return;
+            }
+            let Some(rows) = self.rows.borrow().get(&rule).cloned() else {
+                return;
+            };
+            let Some(settings) = self.notifications_settings.upgrade() else {
+                return;
+            };
+
+            rows.set_sensitive(false);
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    if settings
+                        .set_push_rule_settings(rule, rows.settings())
+                        .await
+                        .is_err()
+                    {
+                        toast!(imp.obj(), gettext("Could not change notification rule"));
+                        // Restore the current settings.
+                        imp.load().await;
+                    }
+
+                    rows.set_sensitive(true);
+                }
+            ));
+        }
+
+        /// Reset the push rules to their default values.
+        #[template_callback]
+        async fn reset(&self) {
+            let Some(settings) = self.notifications_settings.upgrade() else {
+                return;
+            };
+
+            self.reset_button.set_is_loading(true);
+
+            if settings.reset_push_rules().await.is_err() {
+                toast!(self.obj(), gettext("Could not reset notification rules"));
+            }
+
+            self.load().await;
+            self.reset_button.set_is_loading(false);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Subpage to edit the push rules of the account.
+    pub struct PushRulesSubpage(ObjectSubclass<imp::PushRulesSubpage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl PushRulesSubpage {
+    pub fn new(notifications_settings: &NotificationsSettings) -> Self {
+        glib::Object::builder()
+            .property("notifications-settings", notifications_settings)
+            .build()
+    }
+}
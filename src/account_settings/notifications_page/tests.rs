@@ -0,0 +1,34 @@
+use super::{format_minutes, parse_minutes};
+
+#[test]
+fn format_schedule_minutes() {
+    assert_eq!(format_minutes(0.0), "00:00");
+    assert_eq!(format_minutes(61.0), "01:01");
+    assert_eq!(format_minutes(1439.0), "23:59");
+    // The value is rounded.
+    assert_eq!(format_minutes(89.6), "01:30");
+    // Negative values are clamped.
+    assert_eq!(format_minutes(-5.0), "00:00");
+}
+
+#[test]
+fn parse_schedule_minutes() {
+    assert_eq!(parse_minutes("00:00"), Some(0.0));
+    assert_eq!(parse_minutes("01:01"), Some(61.0));
+    assert_eq!(parse_minutes(" 23:59 "), Some(1439.0));
+    assert_eq!(parse_minutes("7:05"), Some(425.0));
+
+    assert_eq!(parse_minutes("24:00"), None);
+    assert_eq!(parse_minutes("12:60"), None);
+    assert_eq!(parse_minutes("1200"), None);
+    assert_eq!(parse_minutes("ab:cd"), None);
+    assert_eq!(parse_minutes("-1:30"), None);
+    assert_eq!(parse_minutes(""), None);
+}
+
+#[test]
+fn format_and_parse_schedule_minutes() {
+    for minutes in [0.0, 59.0, 60.0, 754.0, 1439.0] {
+        assert_eq!(parse_minutes(&format_minutes(minutes)), Some(minutes));
+    }
+}
@@ -8,11 +8,16 @@ mod ignored_users_subpage;
 
 pub(super) use self::ignored_users_subpage::IgnoredUsersSubpage;
 use crate::{
+    Application,
     components::{ButtonCountRow, CheckLoadingRow, SwitchLoadingRow},
     session::Session,
     spawn, toast,
+    utils::links::SETTINGS_KEY_ALLOWED_LINK_DOMAINS,
 };
 
+/// The settings key for the URL of the map tile server.
+const SETTINGS_KEY_MAP_TILE_URL: &str = "map-tile-url";
+
 mod imp {
     use std::{
         cell::{Cell, RefCell},
@@ -32,6 +37,8 @@ mod imp {
         #[template_child]
         typing_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
+        typing_public_rooms_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         ignored_users_row: TemplateChild<ButtonCountRow>,
         #[template_child]
         media_previews: TemplateChild<adw::PreferencesGroup>,
@@ -43,6 +50,14 @@ mod imp {
         media_previews_off_row: TemplateChild<CheckLoadingRow>,
         #[template_child]
         invite_avatars_row: TemplateChild<SwitchLoadingRow>,
+        #[template_child]
+        confirm_opening_links_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        allowed_link_domains_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        location_map_previews_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        map_tile_url_row: TemplateChild<adw::EntryRow>,
         /// The current session.
         #[property(get, set = Self::set_session, nullable)]
         session: glib::WeakRef<Session>,
@@ -58,6 +73,7 @@ mod imp {
         ignored_users_count_handler: RefCell<Option<glib::SignalHandlerId>>,
         global_account_data_handlers: RefCell<Vec<glib::SignalHandlerId>>,
         bindings: RefCell<Vec<glib::Binding>>,
+        allowed_link_domains_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -83,8 +99,48 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for SafetyPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let settings = Application::default().settings();
+            settings
+                .bind(
+                    "confirm-opening-links",
+                    &*self.confirm_opening_links_row,
+                    "active",
+                )
+                .build();
+            settings
+                .bind(
+                    "location-map-previews",
+                    &*self.location_map_previews_row,
+                    "active",
+                )
+                .build();
+            self.map_tile_url_row
+                .set_text(&settings.string(SETTINGS_KEY_MAP_TILE_URL));
+
+            let allowed_link_domains_handler = settings.connect_changed(
+                Some(SETTINGS_KEY_ALLOWED_LINK_DOMAINS),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_allowed_link_domains();
+                    }
+                ),
+            );
+            self.allowed_link_domains_handler
+                .replace(Some(allowed_link_domains_handler));
+            self.update_allowed_link_domains();
+        }
+
         fn dispose(&self) {
             self.disconnect_signals();
+
+            if let Some(handler) = self.allowed_link_domains_handler.take() {
+                Application::default().settings().disconnect(handler);
+            }
         }
     }
 
@@ -154,9 +210,21 @@ mod imp {
                     .bidirectional()
                     .sync_create()
                     .build();
+                let typing_public_rooms_binding = session_settings
+                    .bind_property(
+                        "typing-in-public-rooms-enabled",
+                        &*self.typing_public_rooms_row,
+                        "active",
+                    )
+                    .bidirectional()
+                    .sync_create()
+                    .build();
 
-                self.bindings
-                    .replace(vec![public_read_receipts_binding, typing_binding]);
+                self.bindings.replace(vec![
+                    public_read_receipts_binding,
+                    typing_binding,
+                    typing_public_rooms_binding,
+                ]);
             }
 
             self.session.set(session);
@@ -303,6 +371,49 @@ mod imp {
             self.obj().notify_invite_avatars_loading();
         }
 
+        /// Update the row presenting the domains of links that can be opened
+        /// without confirmation.
+        fn update_allowed_link_domains(&self) {
+            let domains = Application::default()
+                .settings()
+                .strv(SETTINGS_KEY_ALLOWED_LINK_DOMAINS)
+                .iter()
+                .map(|domain| domain.as_str().to_owned())
+                .collect::<Vec<_>>();
+
+            self.allowed_link_domains_row
+                .set_subtitle(&domains.join(", "));
+            self.allowed_link_domains_row
+                .set_visible(!domains.is_empty());
+        }
+
+        /// Forget the domains of links that can be opened without
+        /// confirmation.
+        #[template_callback]
+        fn forget_allowed_link_domains(&self) {
+            Application::default()
+                .settings()
+                .reset(SETTINGS_KEY_ALLOWED_LINK_DOMAINS);
+        }
+
+        /// Save the URL of the map tile server.
+        #[template_callback]
+        fn save_map_tile_url(&self) {
+            let settings = Application::default().settings();
+            let text = self.map_tile_url_row.text();
+            let url = text.trim();
+
+            if url.is_empty() {
+                // Go back to the default server.
+                settings.reset(SETTINGS_KEY_MAP_TILE_URL);
+                self.map_tile_url_row
+                    .set_text(&settings.string(SETTINGS_KEY_MAP_TILE_URL));
+            } else if let Err(error) = settings.set_string(SETTINGS_KEY_MAP_TILE_URL, url) {
+                error!("Could not save map tile server URL: {error}");
+                toast!(self.obj(), gettext("Could not change map tile server"));
+            }
+        }
+
         /// Disconnect the signal handlers and bindings.
         fn disconnect_signals(&self) {
             if let Some(session) = self.session.upgrade() {
@@ -1,10 +1,13 @@
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::{gio, glib, glib::clone};
 use tracing::error;
 
 use super::UserSessionRow;
 use crate::{
+    components::{AuthError, LoadingButtonRow, confirm_disconnect_user_sessions_dialog},
     session::{Session, UserSession, UserSessionsList},
+    toast,
     utils::{BoundObject, LoadingState},
 };
 
@@ -31,6 +34,10 @@ mod imp {
         stack: TemplateChild<gtk::Stack>,
         #[template_child]
         other_sessions: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        disconnect_other_sessions_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        disconnect_other_sessions_button: TemplateChild<LoadingButtonRow>,
         /// The list of user sessions.
         #[property(get, set = Self::set_user_sessions, explicit_notify, nullable)]
         user_sessions: BoundObject<UserSessionsList>,
@@ -100,15 +107,12 @@ mod imp {
                 let other_sessions_handler = other_sessions.connect_items_changed(clone!(
                     #[weak(rename_to = imp)]
                     self,
-                    move |other_sessions, _, _, _| {
-                        imp.other_sessions_group
-                            .set_visible(other_sessions.n_items() > 0);
+                    move |_, _, _, _| {
+                        imp.update_other_sessions_visibility();
                     }
                 ));
                 self.other_sessions_handler
                     .replace(Some(other_sessions_handler));
-                self.other_sessions_group
-                    .set_visible(other_sessions.n_items() > 0);
                 self.other_sessions_sorted_model
                     .set_model(Some(&other_sessions));
 
@@ -151,6 +155,27 @@ mod imp {
 
             self.update_current_session();
             self.update_other_sessions_state();
+            self.update_other_sessions_visibility();
+        }
+
+        /// Update the visibility of the sections about the other sessions.
+        fn update_other_sessions_visibility(&self) {
+            let Some(user_sessions) = self.user_sessions.obj() else {
+                self.other_sessions_group.set_visible(false);
+                self.disconnect_other_sessions_group.set_visible(false);
+                return;
+            };
+
+            let has_other_sessions = user_sessions.other_sessions().n_items() > 0;
+            self.other_sessions_group.set_visible(has_other_sessions);
+
+            // Sessions can only be disconnected with the account management URL
+            // with the OAuth 2.0 API.
+            let uses_oauth_api = user_sessions
+                .session()
+                .is_some_and(|session| session.uses_oauth_api());
+            self.disconnect_other_sessions_group
+                .set_visible(has_other_sessions && !uses_oauth_api);
         }
 
         /// Initialize the list of other sessions.
@@ -228,6 +253,30 @@ mod imp {
                 Some(&row.user_session().unwrap().device_id_string().to_variant()),
             );
         }
+
+        /// Disconnect all the other sessions.
+        #[template_callback]
+        async fn disconnect_other_sessions(&self) {
+            let Some(user_sessions) = self.user_sessions.obj() else {
+                return;
+            };
+            let obj = self.obj();
+
+            if !confirm_disconnect_user_sessions_dialog(None, &*obj).await {
+                return;
+            }
+
+            self.disconnect_other_sessions_button.set_is_loading(true);
+
+            match user_sessions.delete_other_sessions(&*obj).await {
+                Ok(()) | Err(AuthError::UserCancelled) => {}
+                Err(_) => {
+                    toast!(obj, gettext("Could not disconnect other sessions"));
+                }
+            }
+
+            self.disconnect_other_sessions_button.set_is_loading(false);
+        }
     }
 }
 
@@ -1,7 +1,11 @@
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::glib;
 
-use crate::{session::UserSession, utils::TemplateCallbacks};
+use crate::{
+    Window, account_settings::AccountSettings, components::LoadingButton, session::UserSession,
+    toast, utils::TemplateCallbacks,
+};
 
 mod imp {
     use std::cell::RefCell;
@@ -16,6 +20,8 @@ mod imp {
     )]
     #[properties(wrapper_type = super::UserSessionRow)]
     pub struct UserSessionRow {
+        #[template_child]
+        verify_button: TemplateChild<LoadingButton>,
         /// The user session displayed by this row.
         #[property(get, set = Self::set_user_session, construct_only)]
         user_session: RefCell<Option<UserSession>>,
@@ -54,6 +60,56 @@ mod imp {
 
             obj.notify_user_session();
         }
+
+        /// The tooltip of the badge of a verified session.
+        #[template_callback]
+        fn verified_tooltip(cross_signed: bool) -> String {
+            if cross_signed {
+                // Translators: As in 'The session is verified with your crypto identity'.
+                gettext("Verified with your crypto identity")
+            } else {
+                // Translators: As in 'The session was verified manually'.
+                gettext("Verified manually")
+            }
+        }
+
+        /// Whether a session with the given state can be verified.
+        #[template_callback]
+        fn can_be_verified(verified: bool, is_current: bool) -> bool {
+            !verified && !is_current
+        }
+
+        /// Start the verification of the user session.
+        #[template_callback]
+        async fn verify(&self) {
+            let Some(user_session) = self.user_session.borrow().clone() else {
+                return;
+            };
+            let obj = self.obj();
+
+            self.verify_button.set_is_loading(true);
+
+            let Ok(verification) = user_session.verify().await else {
+                toast!(obj, gettext("Could not start session verification"));
+                self.verify_button.set_is_loading(false);
+                return;
+            };
+
+            self.verify_button.set_is_loading(false);
+
+            // The verification is presented in the main window.
+            if let Some(window) = obj.root().and_downcast::<Window>() {
+                window
+                    .session_view()
+                    .select_identity_verification(verification);
+            }
+            if let Some(account_settings) = obj
+                .ancestor(AccountSettings::static_type())
+                .and_downcast::<AccountSettings>()
+            {
+                account_settings.close();
+            }
+        }
     }
 }
 
@@ -6,7 +6,10 @@ use tracing::error;
 
 use crate::{
     account_settings::AccountSettings,
-    components::{ActionButton, ActionState, AuthError, LoadingButtonRow},
+    components::{
+        ActionButton, ActionState, AuthError, LoadingButtonRow,
+        confirm_disconnect_user_sessions_dialog,
+    },
     gettext_f,
     prelude::*,
     session::UserSession,
@@ -228,6 +231,11 @@ mod imp {
                 return;
             };
 
+            let device_name = user_session.display_name_or_device_id();
+            if !confirm_disconnect_user_sessions_dialog(Some(&device_name), &*obj).await {
+                return;
+            }
+
             self.loading_disconnect_button.set_is_loading(true);
 
             match user_session.delete(&*obj).await {
@@ -238,7 +246,6 @@ mod imp {
                     self.loading_disconnect_button.set_is_loading(false);
                 }
                 Err(_) => {
-                    let device_name = user_session.display_name_or_device_id();
                     // Translators: Do NOT translate the content between '{' and '}', this is a
                     // variable name.
                     let error_message = gettext_f(
@@ -1,11 +1,12 @@
-use gtk::{glib, prelude::*, subclass::prelude::*};
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 
 use super::avatar_with_selection::AvatarWithSelection;
 use crate::{
     components::AvatarData,
     prelude::*,
-    session::Session,
+    session::{HighlightFlags, Session},
     session_list::{FailedSession, SessionInfo},
+    utils::BoundObjectWeakRef,
 };
 
 mod imp {
@@ -26,6 +27,8 @@ mod imp {
         #[template_child]
         user_id: TemplateChild<gtk::Label>,
         #[template_child]
+        notification_count: TemplateChild<gtk::Label>,
+        #[template_child]
         state_stack: TemplateChild<gtk::Stack>,
         #[template_child]
         error_image: TemplateChild<gtk::Image>,
@@ -33,6 +36,8 @@ mod imp {
         #[property(get, set = Self::set_session, explicit_notify)]
         session: glib::WeakRef<SessionInfo>,
         user_bindings: RefCell<Vec<glib::Binding>>,
+        /// The logged-in session, to watch its unread counts.
+        logged_in_session: BoundObjectWeakRef<Session>,
         /// Whether this session is selected.
         #[property(get = Self::is_selected, set = Self::set_selected, explicit_notify)]
         selected: PhantomData<bool>,
@@ -102,9 +107,28 @@ mod imp {
             for binding in self.user_bindings.take() {
                 binding.unbind();
             }
+            self.logged_in_session.disconnect_signals();
 
             if let Some(session) = session {
                 if let Some(session) = session.downcast_ref::<Session>() {
+                    let notification_count_handler =
+                        session.connect_notification_count_notify(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move |_| {
+                                imp.update_notification_count();
+                            }
+                        ));
+                    let highlight_handler = session.connect_highlight_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_notification_count();
+                        }
+                    ));
+                    self.logged_in_session
+                        .set(session, vec![notification_count_handler, highlight_handler]);
+
                     let user = session.user();
 
                     let avatar_data_handler = user
@@ -144,9 +168,29 @@ mod imp {
             }
 
             self.session.set(session);
+            self.update_notification_count();
             self.obj().notify_session();
         }
 
+        /// Update the badge with the unread counts of the session.
+        fn update_notification_count(&self) {
+            let Some(session) = self.logged_in_session.obj() else {
+                self.notification_count.set_visible(false);
+                return;
+            };
+
+            let notification_count = session.notification_count();
+            self.notification_count
+                .set_label(&notification_count.to_string());
+            self.notification_count.set_visible(notification_count > 0);
+
+            if session.highlight().contains(HighlightFlags::HIGHLIGHT) {
+                self.notification_count.add_css_class("highlight");
+            } else {
+                self.notification_count.remove_css_class("highlight");
+            }
+        }
+
         /// Show the account settings for the session of this row.
         #[template_callback]
         fn show_account_settings(&self) {
@@ -10,11 +10,15 @@ use crate::{
     intent::SessionIntent,
     prelude::*,
     session::{Session, SessionState},
-    session_list::{FailedSession, SessionInfo, SessionList},
+    session_list::{FailedSession, LauncherBadge, SessionInfo, SessionList},
     spawn,
     system_settings::SystemSettings,
     toast,
-    utils::{BoundObjectWeakRef, LoadingState, matrix::MatrixIdUri},
+    utils::{
+        BoundObjectWeakRef, LoadingState,
+        matrix::{MatrixEventIdUri, MatrixIdUri},
+        spell_check, timeline_zoom,
+    },
 };
 
 /// The key for the current session setting.
@@ -25,7 +29,7 @@ pub(crate) const APP_NAME: &str = "Fractal";
 pub(crate) const APP_HOMEPAGE_URL: &str = "https://gitlab.gnome.org/World/fractal/";
 
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, OnceCell};
 
     use super::*;
 
@@ -37,6 +41,8 @@ mod imp {
         pub(super) system_settings: SystemSettings,
         /// The list of logged-in sessions.
         pub(super) session_list: SessionList,
+        /// The badge on the icon of the application.
+        launcher_badge: OnceCell<LauncherBadge>,
         intent_handler: BoundObjectWeakRef<glib::Object>,
         last_network_state: Cell<NetworkState>,
     }
@@ -47,6 +53,7 @@ mod imp {
                 settings: gio::Settings::new(config::APP_ID),
                 system_settings: Default::default(),
                 session_list: Default::default(),
+                launcher_badge: Default::default(),
                 intent_handler: Default::default(),
                 last_network_state: Default::default(),
             }
@@ -122,6 +129,16 @@ mod imp {
 
             // Set icons for shell
             gtk::Window::set_default_icon_name(crate::APP_ID);
+
+            // Scale the room history according to the user's preference.
+            timeline_zoom::set_up(&self.settings);
+
+            // Check the spelling of messages in the user's preferred language.
+            spell_check::set_up(&self.settings);
+
+            // Show the number of unread rooms on the icon of the application.
+            self.launcher_badge
+                .get_or_init(|| LauncherBadge::new(&self.session_list));
         }
 
         fn open(&self, files: &[gio::File], _hint: &str) {
@@ -226,6 +243,52 @@ mod imp {
                         obj.imp().process_session_intent(session_id, intent);
                     })
                     .build(),
+                // Mark a room as read. This is the action triggered by the button of a
+                // notification about a message.
+                gio::ActionEntry::builder(SessionIntent::MARK_ROOM_AS_READ_ACTION_NAME)
+                    .parameter_type(Some(&SessionIntent::static_variant_type()))
+                    .activate(|obj: &super::Application, _, variant| {
+                        debug!(
+                            "`{}` action activated",
+                            SessionIntent::MARK_ROOM_AS_READ_APP_ACTION_NAME
+                        );
+
+                        let Some((session_id, intent)) =
+                            variant.and_then(SessionIntent::mark_room_as_read_from_variant)
+                        else {
+                            error!(
+                                "Activated `{}` action without the proper payload",
+                                SessionIntent::MARK_ROOM_AS_READ_APP_ACTION_NAME
+                            );
+                            return;
+                        };
+
+                        obj.imp().process_session_intent(session_id, intent);
+                    })
+                    .build(),
+                // Reply in a room. This is the action triggered by the button of a
+                // notification about a message.
+                gio::ActionEntry::builder(SessionIntent::REPLY_IN_ROOM_ACTION_NAME)
+                    .parameter_type(Some(&SessionIntent::static_variant_type()))
+                    .activate(|obj: &super::Application, _, variant| {
+                        debug!(
+                            "`{}` action activated",
+                            SessionIntent::REPLY_IN_ROOM_APP_ACTION_NAME
+                        );
+
+                        let Some((session_id, intent)) =
+                            variant.and_then(SessionIntent::reply_in_room_from_variant)
+                        else {
+                            error!(
+                                "Activated `{}` action without the proper payload",
+                                SessionIntent::REPLY_IN_ROOM_APP_ACTION_NAME
+                            );
+                            return;
+                        };
+
+                        obj.imp().process_session_intent(session_id, intent);
+                    })
+                    .build(),
             ]);
         }
 
@@ -234,6 +297,20 @@ mod imp {
             let obj = self.obj();
             obj.set_accels_for_action("app.quit", &["<Control>q"]);
             obj.set_accels_for_action("window.close", &["<Control>w"]);
+            obj.set_accels_for_action("win.jump-to-room", &["<Control>k"]);
+
+            // Quick switch between accounts.
+            for index in 0..9_u32 {
+                obj.set_accels_for_action(
+                    &format!("win.select-session(uint32 {index})"),
+                    &[&format!("<Control>{}", index + 1)],
+                );
+            }
+            obj.set_accels_for_action("win.select-next-session", &["<Control>Tab"]);
+            obj.set_accels_for_action(
+                "win.select-previous-session",
+                &["<Control><Shift>Tab", "<Control><Shift>ISO_Left_Tab"],
+            );
         }
 
         /// Show the dialog with information about the application.
@@ -313,6 +390,10 @@ mod imp {
                 match self.session_list.n_items() {
                     0 => {
                         warn!("Cannot process intent with no logged in session");
+                        toast!(
+                            self.present_main_window(),
+                            gettext("Log in to open Matrix links")
+                        );
                     }
                     1 => {
                         let session = self
@@ -322,6 +403,11 @@ mod imp {
                         self.process_session_intent(session.session_id(), intent);
                     }
                     _ => {
+                        if let Some(session_id) = self.only_session_with_room_for_intent(&intent) {
+                            self.process_session_intent(session_id, intent);
+                            return;
+                        }
+
                         spawn!(clone!(
                             #[weak(rename_to = imp)]
                             self,
@@ -355,6 +441,34 @@ mod imp {
             }
         }
 
+        /// The ID of the only session that knows the room targeted by the given
+        /// intent, if any.
+        ///
+        /// Returns `None` if the intent does not target a room, or if none or
+        /// several sessions know the room.
+        fn only_session_with_room_for_intent(&self, intent: &SessionIntent) -> Option<String> {
+            let SessionIntent::ShowMatrixId(
+                MatrixIdUri::Room(room_uri) | MatrixIdUri::Event(MatrixEventIdUri { room_uri, .. }),
+            ) = intent
+            else {
+                return None;
+            };
+
+            let mut sessions = self
+                .session_list
+                .iter::<glib::Object>()
+                .filter_map(|session| session.ok().and_downcast::<Session>())
+                .filter(|session| {
+                    session
+                        .room_list()
+                        .get_by_identifier(&room_uri.id)
+                        .is_some()
+                });
+
+            let session = sessions.next()?;
+            sessions.next().is_none().then(|| session.session_id())
+        }
+
         /// Ask the user to choose a session to process the given Matrix ID URI.
         ///
         /// The session list needs to be ready.
@@ -394,8 +508,7 @@ mod imp {
                 );
             } else if let Some(session) = session_info.downcast_ref::<Session>() {
                 if session.state() == SessionState::Ready {
-                    self.present_main_window()
-                        .process_session_intent(session.session_id(), intent);
+                    self.process_ready_session_intent(session, intent);
                 } else {
                     debug!(
                         session = session_id,
@@ -403,18 +516,17 @@ mod imp {
                         "Session is not ready, queuing intent…"
                     );
                     // Wait for the session to be ready.
-                    let cell = Rc::new(RefCell::new(Some((session_id, intent))));
+                    let cell = Rc::new(RefCell::new(Some(intent)));
                     let handler = session.connect_ready(clone!(
                         #[weak(rename_to = imp)]
                         self,
                         #[strong]
                         cell,
-                        move |_| {
+                        move |session| {
                             imp.intent_handler.disconnect_signals();
 
-                            if let Some((session_id, intent)) = cell.take() {
-                                imp.present_main_window()
-                                    .process_session_intent(&session_id, intent);
+                            if let Some(intent) = cell.take() {
+                                imp.process_ready_session_intent(session, intent);
                             }
                         }
                     ));
@@ -467,6 +579,19 @@ mod imp {
                     .set(self.session_list.upcast_ref(), vec![handler]);
             }
         }
+
+        /// Process the given intent for the given session, that is ready.
+        ///
+        /// Actions on notifications are handled without presenting the main
+        /// window.
+        fn process_ready_session_intent(&self, session: &Session, intent: SessionIntent) {
+            if intent.is_notification_action() {
+                session.notifications().process_action_intent(intent);
+            } else {
+                self.present_main_window()
+                    .process_session_intent(session.session_id(), intent);
+            }
+        }
     }
 }
 
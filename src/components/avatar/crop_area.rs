@@ -0,0 +1,324 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, glib, glib::clone, graphene, gsk};
+
+/// The maximum zoom level.
+const MAX_ZOOM: f64 = 5.0;
+/// The margin around the crop circle, in pixels.
+const CIRCLE_MARGIN: f32 = 12.0;
+/// The maximum size of the cropped texture, in pixels.
+const MAX_CROPPED_SIZE: f32 = 1024.0;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, glib::Properties)]
+    #[properties(wrapper_type = super::AvatarCropArea)]
+    pub struct AvatarCropArea {
+        /// The texture to crop.
+        #[property(get, set = Self::set_texture, explicit_notify, nullable)]
+        texture: RefCell<Option<gdk::Texture>>,
+        /// The zoom level.
+        ///
+        /// At 1, the smallest side of the texture fits in the crop circle.
+        #[property(get, set = Self::set_zoom, explicit_notify, minimum = 1.0, maximum = MAX_ZOOM, default = 1.0)]
+        zoom: Cell<f64>,
+        /// The position of the center of the crop circle, in texture
+        /// coordinates.
+        center: Cell<(f64, f64)>,
+        /// The center when the current drag gesture began.
+        drag_start_center: Cell<(f64, f64)>,
+        /// The zoom level when the current zoom gesture began.
+        zoom_gesture_start: Cell<f64>,
+    }
+
+    impl Default for AvatarCropArea {
+        fn default() -> Self {
+            Self {
+                texture: Default::default(),
+                zoom: Cell::new(1.0),
+                center: Default::default(),
+                drag_start_center: Default::default(),
+                zoom_gesture_start: Cell::new(1.0),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AvatarCropArea {
+        const NAME: &'static str = "AvatarCropArea";
+        type Type = super::AvatarCropArea;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("avatar-crop-area");
+            klass.set_accessible_role(gtk::AccessibleRole::Img);
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for AvatarCropArea {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            obj.set_overflow(gtk::Overflow::Hidden);
+            obj.set_cursor_from_name(Some("move"));
+
+            // Move the image with a pointer or a finger.
+            let drag_gesture = gtk::GestureDrag::new();
+            drag_gesture.connect_drag_begin(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _| {
+                    imp.drag_start_center.set(imp.center.get());
+                }
+            ));
+            drag_gesture.connect_drag_update(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, offset_x, offset_y| {
+                    let scale = imp.scale();
+                    if scale <= 0.0 {
+                        return;
+                    }
+
+                    let (start_x, start_y) = imp.drag_start_center.get();
+                    imp.set_center((start_x - offset_x / scale, start_y - offset_y / scale));
+                }
+            ));
+            obj.add_controller(drag_gesture);
+
+            // Zoom with two fingers.
+            let zoom_gesture = gtk::GestureZoom::new();
+            zoom_gesture.connect_begin(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _| {
+                    imp.zoom_gesture_start.set(imp.zoom.get());
+                }
+            ));
+            zoom_gesture.connect_scale_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, scale| {
+                    imp.set_zoom(imp.zoom_gesture_start.get() * scale);
+                }
+            ));
+            obj.add_controller(zoom_gesture);
+
+            // Zoom with the scroll wheel or a touchpad.
+            let scroll_controller =
+                gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+            scroll_controller.connect_scroll(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                glib::Propagation::Proceed,
+                move |_, _, dy| {
+                    imp.set_zoom(imp.zoom.get() * 1.1_f64.powf(-dy));
+                    glib::Propagation::Stop
+                }
+            ));
+            obj.add_controller(scroll_controller);
+        }
+    }
+
+    impl WidgetImpl for AvatarCropArea {
+        fn measure(&self, _orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            (200, 300, -1, -1)
+        }
+
+        fn size_allocate(&self, _width: i32, _height: i32, _baseline: i32) {
+            // Make sure that the image still covers the circle.
+            self.set_center(self.center.get());
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let Some(texture) = self.texture.borrow().clone() else {
+                return;
+            };
+            let obj = self.obj();
+
+            let width = obj.width() as f32;
+            let height = obj.height() as f32;
+            let scale = self.scale() as f32;
+            let (center_x, center_y) = self.center.get();
+
+            // Draw the texture so the center is in the middle of the widget.
+            let texture_rect = graphene::Rect::new(
+                width / 2.0 - center_x as f32 * scale,
+                height / 2.0 - center_y as f32 * scale,
+                texture.width() as f32 * scale,
+                texture.height() as f32 * scale,
+            );
+            snapshot.append_texture(&texture, &texture_rect);
+
+            // Dim the parts of the texture outside of the circle.
+            let diameter = self.circle_diameter();
+            let circle_rect = graphene::Rect::new(
+                (width - diameter) / 2.0,
+                (height - diameter) / 2.0,
+                diameter,
+                diameter,
+            );
+            let circle = gsk::RoundedRect::from_rect(circle_rect, diameter / 2.0);
+
+            snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+
+            snapshot.push_rounded_clip(&circle);
+            snapshot.append_color(&gdk::RGBA::BLACK, &circle_rect);
+            snapshot.pop();
+
+            snapshot.pop();
+
+            snapshot.append_color(
+                &gdk::RGBA::new(0.0, 0.0, 0.0, 0.6),
+                &graphene::Rect::new(0.0, 0.0, width, height),
+            );
+
+            snapshot.pop();
+        }
+    }
+
+    impl AvatarCropArea {
+        /// Set the texture to crop.
+        fn set_texture(&self, texture: Option<gdk::Texture>) {
+            if *self.texture.borrow() == texture {
+                return;
+            }
+
+            if let Some(texture) = &texture {
+                self.center.set((
+                    f64::from(texture.width()) / 2.0,
+                    f64::from(texture.height()) / 2.0,
+                ));
+            }
+
+            self.texture.replace(texture);
+            self.zoom.set(1.0);
+
+            let obj = self.obj();
+            obj.queue_draw();
+            obj.notify_texture();
+            obj.notify_zoom();
+        }
+
+        /// Set the zoom level.
+        fn set_zoom(&self, zoom: f64) {
+            let zoom = zoom.clamp(1.0, MAX_ZOOM);
+
+            if (self.zoom.get() - zoom).abs() < f64::EPSILON {
+                return;
+            }
+
+            self.zoom.set(zoom);
+
+            // Make sure that the image still covers the circle.
+            self.set_center(self.center.get());
+
+            self.obj().notify_zoom();
+        }
+
+        /// The diameter of the crop circle, in widget coordinates.
+        fn circle_diameter(&self) -> f32 {
+            let obj = self.obj();
+            let size = obj.width().min(obj.height()) as f32;
+            (size - 2.0 * CIRCLE_MARGIN).max(1.0)
+        }
+
+        /// The side of the cropped square, in texture coordinates.
+        fn cropped_side(&self) -> f64 {
+            let Some(texture) = self.texture.borrow().clone() else {
+                return 0.0;
+            };
+
+            f64::from(texture.width().min(texture.height())) / self.zoom.get()
+        }
+
+        /// The scale to apply to the texture to draw it in the widget.
+        fn scale(&self) -> f64 {
+            let side = self.cropped_side();
+            if side <= 0.0 {
+                return 0.0;
+            }
+
+            f64::from(self.circle_diameter()) / side
+        }
+
+        /// Set the position of the center of the crop circle, in texture
+        /// coordinates.
+        ///
+        /// The position is clamped so the circle is always inside the texture.
+        fn set_center(&self, (x, y): (f64, f64)) {
+            let Some(texture) = self.texture.borrow().clone() else {
+                return;
+            };
+
+            let half_side = self.cropped_side() / 2.0;
+            let max_x = f64::from(texture.width()) - half_side;
+            let max_y = f64::from(texture.height()) - half_side;
+
+            self.center.set((
+                x.clamp(half_side, max_x.max(half_side)),
+                y.clamp(half_side, max_y.max(half_side)),
+            ));
+            self.obj().queue_draw();
+        }
+
+        /// Render the cropped part of the texture.
+        pub(super) fn cropped_texture(&self) -> Option<gdk::Texture> {
+            let texture = self.texture.borrow().clone()?;
+            let renderer = self.obj().native()?.renderer()?;
+
+            let side = self.cropped_side() as f32;
+            if side <= 0.0 {
+                return None;
+            }
+            let (center_x, center_y) = self.center.get();
+
+            let output_side = side.min(MAX_CROPPED_SIZE).round();
+            let output_rect = graphene::Rect::new(0.0, 0.0, output_side, output_side);
+            let scale = output_side / side;
+
+            let snapshot = gtk::Snapshot::new();
+            snapshot.push_clip(&output_rect);
+            snapshot.append_scaled_texture(
+                &texture,
+                gsk::ScalingFilter::Trilinear,
+                &graphene::Rect::new(
+                    (side / 2.0 - center_x as f32) * scale,
+                    (side / 2.0 - center_y as f32) * scale,
+                    texture.width() as f32 * scale,
+                    texture.height() as f32 * scale,
+                ),
+            );
+            snapshot.pop();
+
+            let node = snapshot.to_node()?;
+            Some(renderer.render_texture(node, Some(&output_rect)))
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A widget to select the part of a texture to use as an avatar.
+    ///
+    /// The texture can be moved by dragging it, and zoomed with a pinch
+    /// gesture or by scrolling.
+    pub struct AvatarCropArea(ObjectSubclass<imp::AvatarCropArea>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl AvatarCropArea {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Render the cropped part of the texture.
+    pub(crate) fn cropped_texture(&self) -> Option<gdk::Texture> {
+        self.imp().cropped_texture()
+    }
+}
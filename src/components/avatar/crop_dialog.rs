@@ -0,0 +1,113 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, glib};
+use tracing::error;
+
+use super::AvatarCropArea;
+use crate::utils::OneshotNotifier;
+
+mod imp {
+    use std::cell::OnceCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/components/avatar/crop_dialog.ui")]
+    pub struct AvatarCropDialog {
+        #[template_child]
+        crop_area: TemplateChild<AvatarCropArea>,
+        notifier: OnceCell<OneshotNotifier<Option<gdk::Texture>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AvatarCropDialog {
+        const NAME: &'static str = "AvatarCropDialog";
+        type Type = super::AvatarCropDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            AvatarCropArea::ensure_type();
+
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AvatarCropDialog {}
+
+    impl WidgetImpl for AvatarCropDialog {}
+
+    impl AdwDialogImpl for AvatarCropDialog {
+        fn closed(&self) {
+            self.notifier().notify();
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl AvatarCropDialog {
+        /// The notifier to send the response.
+        fn notifier(&self) -> &OneshotNotifier<Option<gdk::Texture>> {
+            self.notifier
+                .get_or_init(|| OneshotNotifier::new("AvatarCropDialog"))
+        }
+
+        /// Crop the texture and close the dialog.
+        #[template_callback]
+        fn crop(&self) {
+            let Some(texture) = self.crop_area.cropped_texture() else {
+                error!("Could not crop avatar");
+                return;
+            };
+
+            self.notifier().notify_value(Some(texture));
+            self.obj().close();
+        }
+
+        /// Present the dialog to crop the given texture and wait for the user
+        /// to confirm.
+        ///
+        /// Returns `None` if the user cancelled.
+        pub(super) async fn crop_texture(
+            &self,
+            texture: &gdk::Texture,
+            parent: &gtk::Widget,
+        ) -> Option<gdk::Texture> {
+            self.crop_area.set_texture(Some(texture));
+
+            let receiver = self.notifier().listen();
+            self.obj().present(Some(parent));
+
+            receiver.await
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A dialog to crop and zoom an image to use as an avatar.
+    pub struct AvatarCropDialog(ObjectSubclass<imp::AvatarCropDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl AvatarCropDialog {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Present the dialog to crop the given texture and wait for the user to
+    /// confirm.
+    ///
+    /// Returns the cropped texture, or `None` if the user cancelled.
+    pub(crate) async fn crop_texture(
+        &self,
+        texture: &gdk::Texture,
+        parent: &impl IsA<gtk::Widget>,
+    ) -> Option<gdk::Texture> {
+        self.imp().crop_texture(texture, parent.upcast_ref()).await
+    }
+}
@@ -8,16 +8,20 @@ use gtk::{
 };
 use tracing::{debug, error};
 
-use super::{AvatarData, AvatarImage};
+use super::{
+    AvatarCropDialog, AvatarData, AvatarFrameChoice, AvatarFramePickerDialog, AvatarImage,
+};
 use crate::{
+    Application,
     components::{ActionButton, ActionState, AnimatedImagePaintable},
     toast,
     utils::{
-        BoundObject, BoundObjectWeakRef, CountedRef, SingleItemListModel, expression,
+        BoundObject, BoundObjectWeakRef, CountedRef, File, SingleItemListModel, expression,
         media::{
             FrameDimensions,
             image::{IMAGE_QUEUE, ImageError},
         },
+        save_data_to_tmp_file,
     },
 };
 
@@ -90,6 +94,11 @@ mod imp {
         /// The error encountered when loading the temporary avatar, if any.
         temp_error: Cell<Option<ImageError>>,
         temp_paintable_animation_ref: RefCell<Option<CountedRef>>,
+        /// The temporary file containing the frame chosen as avatar, if any.
+        ///
+        /// We need to keep a strong reference to the file or it will be
+        /// destroyed.
+        temp_frame_file: RefCell<Option<File>>,
     }
 
     #[glib::object_subclass]
@@ -328,7 +337,10 @@ mod imp {
         }
 
         /// Set the temporary paintable.
-        fn set_temp_paintable(&self, paintable: Result<Option<gdk::Paintable>, ImageError>) {
+        pub(super) fn set_temp_paintable(
+            &self,
+            paintable: Result<Option<gdk::Paintable>, ImageError>,
+        ) {
             let (paintable, error) = match paintable {
                 Ok(paintable) => (paintable, None),
                 Err(error) => (None, Some(error)),
@@ -365,7 +377,9 @@ mod imp {
                 return;
             };
 
-            if self.temp_avatar.is_mapped() {
+            if self.temp_avatar.is_mapped()
+                && !Application::default().system_settings().reduce_motion()
+            {
                 self.temp_paintable_animation_ref
                     .replace(Some(paintable.animation_ref()));
             }
@@ -502,8 +516,9 @@ impl EditableAvatar {
     /// Choose a new avatar.
     pub(super) async fn choose_avatar(&self) {
         let image_filter = gtk::FileFilter::new();
-        image_filter.set_name(Some(&gettext("Images")));
+        image_filter.set_name(Some(&gettext("Images and Videos")));
         image_filter.add_mime_type("image/*");
+        image_filter.add_mime_type("video/*");
 
         let filters = SingleItemListModel::new(Some(&image_filter));
 
@@ -541,11 +556,12 @@ impl EditableAvatar {
             .and_then(|info| info.content_type())
         {
             if gio::content_type_is_a(&content_type, "image/*") {
-                self.imp().set_temp_paintable_from_file(file.clone()).await;
-                self.emit_by_name::<()>("edit-avatar", &[&file]);
+                self.set_avatar_from_image_file(file).await;
+            } else if gio::content_type_is_a(&content_type, "video/*") {
+                self.set_avatar_from_video_file(&file).await;
             } else {
-                error!("Expected an image, got {content_type}");
-                toast!(self, gettext("The chosen file is not an image"));
+                error!("Expected an image or a video, got {content_type}");
+                toast!(self, gettext("The chosen file is not an image or a video"));
             }
         } else {
             error!("Could not get the content type of the file");
@@ -556,6 +572,94 @@ impl EditableAvatar {
         }
     }
 
+    /// Use the image in the given file as the new avatar.
+    ///
+    /// If the image is animated, the user can choose to use a single frame.
+    /// Static images are cropped by the user.
+    async fn set_avatar_from_image_file(&self, file: gio::File) {
+        let imp = self.imp();
+        imp.set_temp_paintable_from_file(file.clone()).await;
+
+        let Some(paintable) = self
+            .temp_paintable()
+            .and_downcast::<AnimatedImagePaintable>()
+        else {
+            // Load the image at its natural size to crop it.
+            match IMAGE_QUEUE
+                .add_file_request(file.clone().into(), None)
+                .await
+            {
+                Ok(image) => {
+                    self.crop_and_set_avatar(image.into_texture()).await;
+                }
+                Err(error) => {
+                    error!("Could not load avatar image to crop it: {error}");
+                    self.emit_by_name::<()>("edit-avatar", &[&file]);
+                }
+            }
+            return;
+        };
+
+        let dialog = AvatarFramePickerDialog::new();
+        dialog.set_animated_image(paintable);
+
+        match dialog.choose_frame(self).await {
+            Some(AvatarFrameChoice::Animation) => {
+                self.emit_by_name::<()>("edit-avatar", &[&file]);
+            }
+            Some(AvatarFrameChoice::Frame(texture)) => {
+                self.crop_and_set_avatar(texture).await;
+            }
+            None => {
+                imp.set_temp_paintable(Ok(None));
+            }
+        }
+    }
+
+    /// Use a frame of the video in the given file as the new avatar.
+    async fn set_avatar_from_video_file(&self, file: &gio::File) {
+        let dialog = AvatarFramePickerDialog::new();
+        dialog.set_video(file);
+
+        if let Some(AvatarFrameChoice::Frame(texture)) = dialog.choose_frame(self).await {
+            self.crop_and_set_avatar(texture).await;
+        }
+    }
+
+    /// Let the user crop the given texture, and use the result as the new
+    /// avatar.
+    async fn crop_and_set_avatar(&self, texture: gdk::Texture) {
+        let dialog = AvatarCropDialog::new();
+
+        let Some(cropped_texture) = dialog.crop_texture(&texture, self).await else {
+            self.imp().set_temp_paintable(Ok(None));
+            return;
+        };
+
+        self.set_avatar_from_texture(cropped_texture).await;
+    }
+
+    /// Use the given texture as the new avatar.
+    async fn set_avatar_from_texture(&self, texture: gdk::Texture) {
+        let data = texture.save_to_png_bytes().to_vec();
+
+        let file = match save_data_to_tmp_file(data).await {
+            Ok(file) => file,
+            Err(error) => {
+                error!("Could not save avatar frame to temporary file: {error}");
+                toast!(self, gettext("Could not save the chosen frame"));
+                return;
+            }
+        };
+        let gfile = file.as_gfile();
+
+        let imp = self.imp();
+        imp.temp_frame_file.replace(Some(file));
+        imp.set_temp_paintable(Ok(Some(texture.upcast())));
+
+        self.emit_by_name::<()>("edit-avatar", &[&gfile]);
+    }
+
     /// Connect to the signal emitted when a new avatar is selected.
     pub fn connect_edit_avatar<F: Fn(&Self, gio::File) + 'static>(
         &self,
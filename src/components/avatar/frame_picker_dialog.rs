@@ -0,0 +1,236 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, gio, glib, glib::clone};
+use tracing::error;
+
+use crate::{
+    components::AnimatedImagePaintable,
+    utils::{CountedRef, OneshotNotifier},
+};
+
+/// The choice of the user in an [`AvatarFramePickerDialog`].
+#[derive(Debug, Clone)]
+pub(crate) enum AvatarFrameChoice {
+    /// Use the original animated image.
+    Animation,
+    /// Use the given frame.
+    Frame(gdk::Texture),
+}
+
+mod imp {
+    use std::cell::{OnceCell, RefCell};
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/components/avatar/frame_picker_dialog.ui")]
+    pub struct AvatarFramePickerDialog {
+        #[template_child]
+        picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        position_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        pause_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        use_animation_button: TemplateChild<gtk::Button>,
+        /// The video to pick a frame from, if any.
+        video: RefCell<Option<gtk::MediaFile>>,
+        /// The animated image to pick a frame from, if any.
+        animated_image: RefCell<Option<AnimatedImagePaintable>>,
+        animation_ref: RefCell<Option<CountedRef>>,
+        notifier: OnceCell<OneshotNotifier<Option<AvatarFrameChoice>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AvatarFramePickerDialog {
+        const NAME: &'static str = "AvatarFramePickerDialog";
+        type Type = super::AvatarFramePickerDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AvatarFramePickerDialog {}
+
+    impl WidgetImpl for AvatarFramePickerDialog {}
+
+    impl AdwDialogImpl for AvatarFramePickerDialog {
+        fn closed(&self) {
+            self.animation_ref.take();
+
+            if let Some(video) = self.video.take() {
+                video.pause();
+            }
+
+            self.notifier().notify();
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl AvatarFramePickerDialog {
+        /// The notifier to send the response.
+        fn notifier(&self) -> &OneshotNotifier<Option<AvatarFrameChoice>> {
+            self.notifier
+                .get_or_init(|| OneshotNotifier::new("AvatarFramePickerDialog"))
+        }
+
+        /// Set the video in the given file to pick a frame from.
+        pub(super) fn set_video(&self, file: &gio::File) {
+            let video = gtk::MediaFile::for_file(file);
+            video.set_muted(true);
+
+            video.connect_duration_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |video| {
+                    // The duration is in microseconds.
+                    let duration = video.duration() as f64 / 1_000_000.0;
+                    let adjustment = imp.position_scale.adjustment();
+                    adjustment.set_upper(duration.max(0.0));
+                    adjustment.set_step_increment((duration / 100.0).max(0.04));
+                    adjustment.set_page_increment((duration / 10.0).max(0.4));
+                }
+            ));
+
+            self.picture.set_paintable(Some(&video));
+            self.position_scale.set_visible(true);
+            self.video.replace(Some(video));
+        }
+
+        /// Set the animated image to pick a frame from.
+        pub(super) fn set_animated_image(&self, paintable: AnimatedImagePaintable) {
+            self.picture.set_paintable(Some(&paintable));
+            self.pause_button.set_visible(true);
+            self.use_animation_button.set_visible(true);
+            self.animated_image.replace(Some(paintable));
+
+            self.update_animation();
+        }
+
+        /// Seek the video to the position of the scale.
+        #[template_callback]
+        fn seek(&self) {
+            let Some(video) = self.video.borrow().clone() else {
+                return;
+            };
+
+            // The timestamp is in microseconds.
+            let timestamp = (self.position_scale.value() * 1_000_000.0) as i64;
+            video.seek(timestamp);
+        }
+
+        /// Play or pause the animated image, according to the pause button.
+        #[template_callback]
+        fn update_animation(&self) {
+            let Some(paintable) = self.animated_image.borrow().clone() else {
+                return;
+            };
+
+            if self.pause_button.is_active() {
+                self.animation_ref.take();
+            } else if self.animation_ref.borrow().is_none() {
+                self.animation_ref.replace(Some(paintable.animation_ref()));
+            }
+        }
+
+        /// The texture of the current frame.
+        fn current_frame(&self) -> Option<gdk::Texture> {
+            if let Some(paintable) = self.animated_image.borrow().as_ref() {
+                return paintable.current_texture();
+            }
+
+            let image = self.picture.paintable()?.current_image();
+
+            if let Some(texture) = image.downcast_ref::<gdk::Texture>() {
+                return Some(texture.clone());
+            }
+
+            let width = image.intrinsic_width();
+            let height = image.intrinsic_height();
+            if width <= 0 || height <= 0 {
+                return None;
+            }
+
+            let snapshot = gtk::Snapshot::new();
+            image.snapshot(&snapshot, width.into(), height.into());
+            let node = snapshot.to_node()?;
+
+            let renderer = self.obj().native()?.renderer()?;
+            Some(renderer.render_texture(node, None))
+        }
+
+        /// Use the current frame.
+        #[template_callback]
+        fn use_frame(&self) {
+            let Some(texture) = self.current_frame() else {
+                error!("Could not get the current frame of the avatar");
+                return;
+            };
+
+            self.notifier()
+                .notify_value(Some(AvatarFrameChoice::Frame(texture)));
+            self.obj().close();
+        }
+
+        /// Use the original animated image.
+        #[template_callback]
+        fn use_animation(&self) {
+            self.notifier()
+                .notify_value(Some(AvatarFrameChoice::Animation));
+            self.obj().close();
+        }
+
+        /// Present the dialog and wait for the user to make a choice.
+        ///
+        /// Returns `None` if the user cancelled.
+        pub(super) async fn choose_frame(&self, parent: &gtk::Widget) -> Option<AvatarFrameChoice> {
+            let receiver = self.notifier().listen();
+
+            self.obj().present(Some(parent));
+
+            receiver.await
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A dialog to pick the frame of a video or an animated image to use as an
+    /// avatar.
+    pub struct AvatarFramePickerDialog(ObjectSubclass<imp::AvatarFramePickerDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl AvatarFramePickerDialog {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Set the video in the given file to pick a frame from.
+    pub(crate) fn set_video(&self, file: &gio::File) {
+        self.imp().set_video(file);
+    }
+
+    /// Set the animated image to pick a frame from.
+    pub(crate) fn set_animated_image(&self, paintable: AnimatedImagePaintable) {
+        self.imp().set_animated_image(paintable);
+    }
+
+    /// Present the dialog and wait for the user to make a choice.
+    ///
+    /// Returns `None` if the user cancelled.
+    pub(crate) async fn choose_frame(
+        &self,
+        parent: &impl IsA<gtk::Widget>,
+    ) -> Option<AvatarFrameChoice> {
+        self.imp().choose_frame(parent.upcast_ref()).await
+    }
+}
@@ -413,6 +413,8 @@ mod imp {
                 prefer_thumbnail: true,
             };
 
+            // The thumbnail is stored in the media cache of the session, so it follows the
+            // same retention policy as the other media.
             downloader
                 .download(client, settings, priority)
                 .await
@@ -1,13 +1,21 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{gdk, glib, glib::clone};
 
+mod crop_area;
 mod crop_circle;
+mod crop_dialog;
 mod data;
 mod editable;
+mod frame_picker_dialog;
 mod image;
 mod overlapping;
 
-use self::image::AvatarPaintableSize;
+use self::{
+    crop_area::AvatarCropArea,
+    crop_dialog::AvatarCropDialog,
+    frame_picker_dialog::{AvatarFrameChoice, AvatarFramePickerDialog},
+    image::AvatarPaintableSize,
+};
 pub use self::{
     data::AvatarData,
     editable::EditableAvatar,
@@ -15,6 +23,7 @@ pub use self::{
     overlapping::OverlappingAvatars,
 };
 use crate::{
+    Application,
     components::AnimatedImagePaintable,
     session::Room,
     utils::{BoundObject, BoundObjectWeakRef, CountedRef},
@@ -81,6 +90,7 @@ mod imp {
         paintable_animation_ref: RefCell<Option<CountedRef>>,
         watched_room_handler: RefCell<Option<glib::SignalHandlerId>>,
         watched_global_account_data_handler: RefCell<Option<glib::SignalHandlerId>>,
+        reduce_motion_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -105,7 +115,27 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for Avatar {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let reduce_motion_handler = Application::default()
+                .system_settings()
+                .connect_reduce_motion_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_animated_paintable_state();
+                    }
+                ));
+            self.reduce_motion_handler
+                .replace(Some(reduce_motion_handler));
+        }
+
         fn dispose(&self) {
+            if let Some(handler) = self.reduce_motion_handler.take() {
+                Application::default().system_settings().disconnect(handler);
+            }
+
             self.disconnect_safety_setting_signals();
         }
     }
@@ -397,8 +427,11 @@ mod imp {
         fn update_animated_paintable_state(&self) {
             let _old_paintable_animation_ref = self.paintable_animation_ref.take();
 
-            if !self.can_show_image() || !self.obj().is_mapped() {
-                // We do not need to animate the paintable.
+            if !self.can_show_image()
+                || !self.obj().is_mapped()
+                || Application::default().system_settings().reduce_motion()
+            {
+                // We do not need to animate the paintable, the first frame is shown.
                 return;
             }
 
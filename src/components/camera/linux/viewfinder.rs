@@ -5,7 +5,9 @@ use gtk::{
     prelude::*,
     subclass::prelude::*,
 };
-use matrix_sdk::encryption::verification::QrVerificationData;
+use matrix_sdk::{
+    authentication::oauth::qrcode::QrCodeData, encryption::verification::QrVerificationData,
+};
 use tokio::task::AbortHandle;
 use tracing::{debug, error};
 
@@ -87,17 +89,19 @@ mod imp {
                 move |_, code| {
                     match QrVerificationData::from_bytes(&code) {
                         Ok(data) => obj.emit_qrcode_detected(data),
-                        Err(error) => {
-                            let code = String::from_utf8_lossy(&code);
-
-                            if matches!(error, DecodingError::Header) {
-                                debug!("Detected non-Matrix QR Code: {code}");
+                        Err(DecodingError::Header) => {
+                            // This might be a QR code to log in a new device.
+                            if let Ok(data) = QrCodeData::from_bytes(&code) {
+                                obj.emit_login_qrcode_detected(data);
                             } else {
-                                error!(
-                                    "Could not decode Matrix verification QR code {code}: {error}"
-                                );
+                                let code = String::from_utf8_lossy(&code);
+                                debug!("Detected non-Matrix QR Code: {code}");
                             }
                         }
+                        Err(error) => {
+                            let code = String::from_utf8_lossy(&code);
+                            error!("Could not decode Matrix verification QR code {code}: {error}");
+                        }
                     }
                 }
             ));
@@ -7,7 +7,7 @@ mod viewfinder;
 
 pub(crate) use self::qrcode_scanner::QrCodeScanner;
 use self::{
-    qrcode_scanner::QrVerificationDataBoxed,
+    qrcode_scanner::{LoginQrCodeDataBoxed, QrVerificationDataBoxed},
     viewfinder::{
         CameraViewfinder, CameraViewfinderExt, CameraViewfinderImpl, CameraViewfinderState,
     },
@@ -4,7 +4,9 @@ use gtk::{
     glib,
     glib::{clone, closure_local},
 };
-use matrix_sdk::encryption::verification::QrVerificationData;
+use matrix_sdk::{
+    authentication::oauth::qrcode::QrCodeData, encryption::verification::QrVerificationData,
+};
 
 use super::{Camera, CameraExt, CameraViewfinder, CameraViewfinderExt, CameraViewfinderState};
 use crate::utils::BoundConstructOnlyObject;
@@ -13,6 +15,10 @@ use crate::utils::BoundConstructOnlyObject;
 #[boxed_type(name = "QrVerificationDataBoxed")]
 pub(super) struct QrVerificationDataBoxed(pub(super) QrVerificationData);
 
+#[derive(Clone, Debug, glib::Boxed)]
+#[boxed_type(name = "LoginQrCodeDataBoxed")]
+pub(super) struct LoginQrCodeDataBoxed(pub(super) QrCodeData);
+
 mod imp {
     use std::sync::LazyLock;
 
@@ -55,6 +61,10 @@ mod imp {
                         .param_types([QrVerificationDataBoxed::static_type()])
                         .run_first()
                         .build(),
+                    Signal::builder("login-qrcode-detected")
+                        .param_types([LoginQrCodeDataBoxed::static_type()])
+                        .run_first()
+                        .build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -83,6 +93,13 @@ mod imp {
                     obj.emit_by_name::<()>("qrcode-detected", &[&QrVerificationDataBoxed(data)]);
                 }
             ));
+            let login_qrcode_detected_handler = viewfinder.connect_login_qrcode_detected(clone!(
+                #[weak]
+                obj,
+                move |_, data| {
+                    obj.emit_by_name::<()>("login-qrcode-detected", &[&LoginQrCodeDataBoxed(data)]);
+                }
+            ));
 
             viewfinder.set_overflow(gtk::Overflow::Hidden);
             viewfinder.add_css_class("card");
@@ -90,8 +107,14 @@ mod imp {
             self.stack
                 .add_titled(&viewfinder, Some("camera"), &gettext("Camera"));
 
-            self.viewfinder
-                .set(viewfinder, vec![state_handler, qrcode_detected_handler]);
+            self.viewfinder.set(
+                viewfinder,
+                vec![
+                    state_handler,
+                    qrcode_detected_handler,
+                    login_qrcode_detected_handler,
+                ],
+            );
 
             self.update_visible_page();
         }
@@ -143,4 +166,19 @@ impl QrCodeScanner {
             }),
         )
     }
+
+    /// Connect to the signal emitted when a QR code to log in a new device is
+    /// detected.
+    pub fn connect_login_qrcode_detected<F: Fn(&Self, QrCodeData) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "login-qrcode-detected",
+            true,
+            closure_local!(move |obj: Self, data: LoginQrCodeDataBoxed| {
+                f(&obj, data.0);
+            }),
+        )
+    }
 }
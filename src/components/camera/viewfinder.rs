@@ -2,9 +2,11 @@
 
 use gettextrs::gettext;
 use gtk::{glib, glib::closure_local, prelude::*, subclass::prelude::*};
-use matrix_sdk::encryption::verification::QrVerificationData;
+use matrix_sdk::{
+    authentication::oauth::qrcode::QrCodeData, encryption::verification::QrVerificationData,
+};
 
-use super::QrVerificationDataBoxed;
+use super::{LoginQrCodeDataBoxed, QrVerificationDataBoxed};
 
 /// The possible states of a [`CameraViewfinder`].
 #[derive(Default, Debug, Copy, Clone, glib::Enum, PartialEq)]
@@ -62,6 +64,10 @@ mod imp {
                         .param_types([QrVerificationDataBoxed::static_type()])
                         .run_first()
                         .build(),
+                    Signal::builder("login-qrcode-detected")
+                        .param_types([LoginQrCodeDataBoxed::static_type()])
+                        .run_first()
+                        .build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -118,6 +124,16 @@ pub(super) trait CameraViewfinderExt: 'static {
 
     /// Emit the signal that a QR code was detected.
     fn emit_qrcode_detected(&self, data: QrVerificationData);
+
+    /// Connect to the signal emitted when a QR code to log in a new device is
+    /// detected.
+    fn connect_login_qrcode_detected<F: Fn(&Self, QrCodeData) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId;
+
+    /// Emit the signal that a QR code to log in a new device was detected.
+    fn emit_login_qrcode_detected(&self, data: QrCodeData);
 }
 
 impl<O: IsA<CameraViewfinder>> CameraViewfinderExt for O {
@@ -146,6 +162,23 @@ impl<O: IsA<CameraViewfinder>> CameraViewfinderExt for O {
     fn emit_qrcode_detected(&self, data: QrVerificationData) {
         self.emit_by_name::<()>("qrcode-detected", &[&QrVerificationDataBoxed(data)]);
     }
+
+    fn connect_login_qrcode_detected<F: Fn(&Self, QrCodeData) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "login-qrcode-detected",
+            true,
+            closure_local!(|obj: Self, data: LoginQrCodeDataBoxed| {
+                f(&obj, data.0);
+            }),
+        )
+    }
+
+    fn emit_login_qrcode_detected(&self, data: QrCodeData) {
+        self.emit_by_name::<()>("login-qrcode-detected", &[&LoginQrCodeDataBoxed(data)]);
+    }
 }
 
 /// Trait that must be implemented for types that subclass `CameraViewfinder`.
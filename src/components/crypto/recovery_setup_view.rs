@@ -8,7 +8,9 @@ use matrix_sdk::encryption::{
 use tracing::{debug, error, warn};
 
 use crate::{
-    components::{AuthDialog, AuthError, LoadingButton, SwitchLoadingRow},
+    components::{
+        AuthDialog, AuthError, LoadingButton, SwitchLoadingRow, confirm_reset_backup_dialog,
+    },
     session::{RecoveryState, Session},
     spawn_tokio, toast,
 };
@@ -302,6 +304,21 @@ mod imp {
         /// Reset recovery and optionally cross-signing and room keys backup.
         #[template_callback]
         async fn reset(&self) {
+            let reset_backup = self.reset_backup_row.is_active();
+            let backup_exists_on_server = self
+                .session
+                .upgrade()
+                .is_some_and(|session| session.security().backup_exists_on_server());
+
+            // Resetting the backup invalidates the keys of the old backup, so make
+            // sure that the user is aware of it.
+            if reset_backup
+                && backup_exists_on_server
+                && !confirm_reset_backup_dialog(&*self.obj()).await
+            {
+                return;
+            }
+
             self.reset_btn.set_is_loading(true);
 
             let reset_identity = self.reset_identity_row.is_active();
@@ -312,7 +329,6 @@ mod imp {
 
             let passphrase = self.reset_entry.text();
 
-            let reset_backup = self.reset_backup_row.is_active();
             if reset_backup {
                 self.reset_backup_and_recovery(passphrase).await;
             } else {
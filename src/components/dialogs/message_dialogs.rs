@@ -1,13 +1,19 @@
 //! Common message dialogs.
 
 use adw::prelude::*;
-use gettextrs::gettext;
+use gettextrs::{gettext, ngettext, pgettext};
+use gtk::glib::clone;
+use tracing::error;
 
 use crate::{
     i18n::gettext_f,
     ngettext_f,
     prelude::*,
-    session::{Member, Membership, Room, RoomCategory, User},
+    session::{
+        IdentityServer, IdentityServerError, IdentityServerPolicy, JoinRuleValue, Member,
+        Membership, Room, RoomCategory, Session, User,
+    },
+    spawn_tokio, toast,
 };
 
 /// Show a dialog to confirm leaving a room.
@@ -105,6 +111,66 @@ pub(crate) struct ConfirmLeaveRoomResponse {
     pub ignore_inviter: bool,
 }
 
+/// Show a dialog to confirm leaving a room where our own member is privileged.
+///
+/// If `is_last_admin` is `true`, no other joined member has the power level of
+/// our own member, so the user is offered to promote another member first.
+///
+/// Returns `None` if the user did not confirm.
+pub(crate) async fn confirm_leave_privileged_room_dialog(
+    room: &Room,
+    is_last_admin: bool,
+    parent: &impl IsA<gtk::Widget>,
+) -> Option<ConfirmLeavePrivilegedRoomResponse> {
+    let mut paragraphs = Vec::new();
+
+    let heading = if is_last_admin {
+        paragraphs.push(gettext(
+            "You are the only administrator of this room. If you leave, nobody will be able to change its settings or permissions. Consider promoting another member first.",
+        ));
+        gettext("Leave Room Without Administrators?")
+    } else {
+        gettext("Leave Room?")
+    };
+
+    if room.join_rule().value() == JoinRuleValue::Public {
+        paragraphs.push(gettext(
+            "Anyone can join this room. If you leave, you will not be able to moderate it anymore.",
+        ));
+    }
+
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(heading)
+        .body(paragraphs.join("\n\n"))
+        .build();
+    confirm_dialog.add_response("cancel", &gettext("Cancel"));
+
+    if is_last_admin {
+        confirm_dialog.add_response("promote", &gettext("Promote a Member"));
+        confirm_dialog.set_response_appearance("promote", adw::ResponseAppearance::Suggested);
+    }
+
+    confirm_dialog.add_response("leave", &gettext("Leave"));
+    confirm_dialog.set_response_appearance("leave", adw::ResponseAppearance::Destructive);
+
+    match confirm_dialog.choose_future(Some(parent)).await.as_str() {
+        "leave" => Some(ConfirmLeavePrivilegedRoomResponse::Leave),
+        "promote" => Some(ConfirmLeavePrivilegedRoomResponse::PromoteMember),
+        _ => None,
+    }
+}
+
+/// A response to the dialog to confirm leaving a room where our own member is
+/// privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfirmLeavePrivilegedRoomResponse {
+    /// The user wants to leave the room.
+    Leave,
+    /// The user wants to promote another member before leaving.
+    PromoteMember,
+}
+
 /// The room member destructive actions that need to be confirmed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum RoomMemberDestructiveAction {
@@ -251,6 +317,40 @@ impl RoomMemberDestructiveAction {
     }
 }
 
+/// The presets for the reason of a moderation action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModerationReasonPreset {
+    /// The member sent spam.
+    Spam,
+    /// The member was abusive.
+    Abuse,
+    /// The member posted off-topic messages.
+    OffTopic,
+}
+
+impl ModerationReasonPreset {
+    /// All the presets, in the order they are presented.
+    const ALL: &[Self] = &[Self::Spam, Self::Abuse, Self::OffTopic];
+
+    /// The localized name of this preset.
+    fn name(self) -> String {
+        match self {
+            Self::Spam => pgettext("moderation reason", "Spam"),
+            Self::Abuse => pgettext("moderation reason", "Abuse"),
+            Self::OffTopic => pgettext("moderation reason", "Off-Topic"),
+        }
+    }
+
+    /// The localized reason for this preset.
+    fn reason(self) -> String {
+        match self {
+            Self::Spam => gettext("Sending spam"),
+            Self::Abuse => gettext("Abusive behavior"),
+            Self::OffTopic => gettext("Off-topic messages"),
+        }
+    }
+}
+
 /// Show a dialog to confirm the given "destructive" action on the given room
 /// member.
 ///
@@ -271,11 +371,41 @@ pub(crate) async fn confirm_room_member_destructive_action_dialog(
     let reason_entry = adw::EntryRow::builder()
         .title(gettext("Reason (optional)"))
         .build();
+
+    // Add a list of presets to fill the reason, that can be edited afterwards.
+    let presets_model = gtk::StringList::new(&[]);
+    presets_model.append(&pgettext("moderation reason", "None"));
+    for preset in ModerationReasonPreset::ALL {
+        presets_model.append(&preset.name());
+    }
+
+    let presets_row = adw::ComboRow::builder()
+        .title(gettext("Reason Template"))
+        .model(&presets_model)
+        .build();
+    presets_row.connect_selected_notify(clone!(
+        #[weak]
+        reason_entry,
+        move |row| {
+            let Some(preset) = usize::try_from(row.selected())
+                .ok()
+                .and_then(|position| position.checked_sub(1))
+                .and_then(|index| ModerationReasonPreset::ALL.get(index))
+            else {
+                // Keep the reason that was entered manually.
+                return;
+            };
+
+            reason_entry.set_text(&preset.reason());
+        }
+    ));
+
     let list_box = gtk::ListBox::builder()
         .css_classes(["boxed-list"])
         .margin_top(6)
         .accessible_role(gtk::AccessibleRole::Group)
         .build();
+    list_box.append(&presets_row);
     list_box.append(&reason_entry);
     child.append(&list_box);
 
@@ -356,6 +486,157 @@ pub(crate) struct ConfirmRoomMemberDestructiveActionResponse {
     pub remove_events: bool,
 }
 
+/// Show a dialog to confirm the removal of the given number of events.
+///
+/// If `as_moderator` is `true`, some of the events were sent by other users.
+///
+/// Returns `None` if the user did not confirm.
+pub(crate) async fn confirm_redact_events_dialog(
+    count: usize,
+    as_moderator: bool,
+    parent: &impl IsA<gtk::Widget>,
+) -> Option<ConfirmRedactEventsResponse> {
+    let n = u32::try_from(count).unwrap_or(u32::MAX);
+
+    let (heading, body, response) = if as_moderator {
+        let heading = ngettext(
+            "Remove Message as Moderator?",
+            "Remove Messages as Moderator?",
+            n,
+        );
+        let body = ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "This message was not sent by you. Do you really want to remove it for everyone in the room? This cannot be undone.",
+            "Some of these {n} messages were not sent by you. Do you really want to remove them for everyone in the room? This cannot be undone.",
+            n,
+            &[("n", &n.to_string())],
+        );
+        let response = gettext("Remove as Moderator");
+
+        (heading, body, response)
+    } else {
+        let heading = ngettext("Remove Message?", "Remove Messages?", n);
+        let body = ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "Do you really want to remove this message for everyone in the room? This cannot be undone.",
+            "Do you really want to remove these {n} messages for everyone in the room? This cannot be undone.",
+            n,
+            &[("n", &n.to_string())],
+        );
+        let response = gettext("Remove");
+
+        (heading, body, response)
+    };
+
+    // Add an entry for the optional reason.
+    let reason_entry = adw::EntryRow::builder()
+        .title(gettext("Reason (optional)"))
+        .build();
+    let list_box = gtk::ListBox::builder()
+        .css_classes(["boxed-list"])
+        .margin_top(6)
+        .accessible_role(gtk::AccessibleRole::Group)
+        .build();
+    list_box.append(&reason_entry);
+
+    // Ask for confirmation.
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(heading)
+        .body(body)
+        .extra_child(&list_box)
+        .build();
+    confirm_dialog.add_responses(&[("cancel", &gettext("Cancel")), ("remove", &response)]);
+    confirm_dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+
+    if confirm_dialog.choose_future(Some(parent)).await != "remove" {
+        return None;
+    }
+
+    // Get the reason, and filter out if it is empty.
+    let reason = Some(reason_entry.text().trim().to_owned()).filter(|s| !s.is_empty());
+
+    Some(ConfirmRedactEventsResponse { reason })
+}
+
+/// A response to the dialog to confirm the removal of events.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ConfirmRedactEventsResponse {
+    /// The reason of the removal.
+    pub reason: Option<String>,
+}
+
+/// Show a dialog to confirm opening the given external link.
+///
+/// The full URI is shown to defend against deceptive links.
+///
+/// Returns `None` if the user did not confirm.
+pub(crate) async fn confirm_open_link_dialog(
+    uri: &str,
+    domain: &str,
+    parent: &impl IsA<gtk::Widget>,
+) -> Option<ConfirmOpenLinkResponse> {
+    let child = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .build();
+
+    // Show the full URI, it can be long so it should wrap anywhere.
+    let uri_label = gtk::Label::builder()
+        .label(uri)
+        .wrap(true)
+        .wrap_mode(gtk::pango::WrapMode::Char)
+        .selectable(true)
+        .css_classes(["monospace"])
+        .build();
+    child.append(&uri_label);
+
+    let always_allow_switch = adw::SwitchRow::builder()
+        .title(gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "Always allow links to {domain}",
+            &[("domain", domain)],
+        ))
+        .build();
+    let list_box = gtk::ListBox::builder()
+        .css_classes(["boxed-list"])
+        .accessible_role(gtk::AccessibleRole::Group)
+        .build();
+    list_box.append(&always_allow_switch);
+    child.append(&list_box);
+
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(gettext("Open Link?"))
+        .body(gettext("This link leads to the following address:"))
+        .extra_child(&child)
+        .build();
+    confirm_dialog.add_responses(&[
+        ("cancel", &gettext("Cancel")),
+        // Translators: This is a verb, as in 'Open the link'.
+        ("open", &gettext("Open")),
+    ]);
+    confirm_dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+
+    if confirm_dialog.choose_future(Some(parent)).await != "open" {
+        return None;
+    }
+
+    Some(ConfirmOpenLinkResponse {
+        always_allow_domain: always_allow_switch.is_active(),
+    })
+}
+
+/// A response to the dialog to confirm opening an external link.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ConfirmOpenLinkResponse {
+    /// Whether links to the same domain can be opened without confirmation.
+    pub always_allow_domain: bool,
+}
+
 /// Show a dialog to confirm muting one or several room members.
 pub(crate) async fn confirm_mute_room_member_dialog(
     members: &[impl IsA<User>],
@@ -474,6 +755,193 @@ pub(crate) async fn confirm_own_demotion_dialog(parent: &impl IsA<gtk::Widget>)
     confirm_dialog.choose_future(Some(parent)).await == "demote"
 }
 
+/// Show a dialog to confirm the disconnection of user sessions.
+///
+/// If `device_name` is `Some`, a single session with that name is
+/// disconnected, otherwise all the other sessions are disconnected.
+pub(crate) async fn confirm_disconnect_user_sessions_dialog(
+    device_name: Option<&str>,
+    parent: &impl IsA<gtk::Widget>,
+) -> bool {
+    let (heading, body) = if let Some(device_name) = device_name {
+        let heading = gettext("Disconnect Session?");
+        let body = gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this is a
+            // variable name.
+            "The session “{device_name}” will be signed out. You will need to log in again to use it. Its encryption keys will be lost if they are not backed up.",
+            &[("device_name", device_name)],
+        );
+        (heading, body)
+    } else {
+        let heading = gettext("Disconnect Other Sessions?");
+        let body = gettext(
+            "All your sessions except this one will be signed out. You will need to log in again to use them. Their encryption keys will be lost if they are not backed up.",
+        );
+        (heading, body)
+    };
+
+    // Ask for confirmation.
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(heading)
+        .body(body)
+        .build();
+    confirm_dialog.add_responses(&[
+        ("cancel", &gettext("Cancel")),
+        ("disconnect", &gettext("Disconnect")),
+    ]);
+    confirm_dialog.set_response_appearance("disconnect", adw::ResponseAppearance::Destructive);
+
+    confirm_dialog.choose_future(Some(parent)).await == "disconnect"
+}
+
+/// Show a dialog to confirm discarding a message that failed to be sent.
+pub(crate) async fn confirm_discard_failed_message_dialog(parent: &impl IsA<gtk::Widget>) -> bool {
+    let heading = gettext("Delete Message?");
+    let body = gettext(
+        "This message could not be sent. Its content will be lost if you delete it. This cannot be undone.",
+    );
+
+    // Ask for confirmation.
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(heading)
+        .body(body)
+        .build();
+    confirm_dialog.add_responses(&[
+        ("cancel", &gettext("Cancel")),
+        ("delete", &gettext("Delete")),
+    ]);
+    confirm_dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+    confirm_dialog.choose_future(Some(parent)).await == "delete"
+}
+
+/// Show a dialog to confirm the reset of the room keys backup.
+pub(crate) async fn confirm_reset_backup_dialog(parent: &impl IsA<gtk::Widget>) -> bool {
+    let heading = gettext("Reset Room Keys Backup?");
+    let body = gettext(
+        "The current backup will be deleted and replaced by a new one. Sessions that rely on the old backup will not be able to restore the keys to decrypt older messages. This cannot be undone.",
+    );
+
+    // Ask for confirmation.
+    let confirm_dialog = adw::AlertDialog::builder()
+        .default_response("cancel")
+        .heading(heading)
+        .body(body)
+        .build();
+    confirm_dialog.add_responses(&[("cancel", &gettext("Cancel")), ("reset", &gettext("Reset"))]);
+    confirm_dialog.set_response_appearance("reset", adw::ResponseAppearance::Destructive);
+
+    confirm_dialog.choose_future(Some(parent)).await == "reset"
+}
+
+/// Register with the given identity server and ask the user to accept its
+/// terms, if needed.
+///
+/// Errors are presented to the user with toasts.
+///
+/// Returns the access token for the identity server, or `None` if the
+/// connection failed or the user did not accept the terms.
+pub(crate) async fn connect_to_identity_server(
+    session: &Session,
+    identity_server: &IdentityServer,
+    parent: &impl IsA<gtk::Widget>,
+) -> Option<String> {
+    let client = session.client();
+
+    let identity_server_clone = identity_server.clone();
+    let client_clone = client.clone();
+    let handle = spawn_tokio!(async move {
+        let access_token = identity_server_clone.register(&client_clone).await?;
+        let policies = identity_server_clone
+            .unaccepted_terms(&client_clone)
+            .await?;
+        Ok::<_, IdentityServerError>((access_token, policies))
+    });
+
+    let (access_token, policies) = match handle.await.expect("task was not aborted") {
+        Ok(result) => result,
+        Err(error) => {
+            error!("Could not connect to identity server: {error}");
+            toast!(parent, gettext("Could not connect to the identity server"));
+            return None;
+        }
+    };
+
+    if policies.is_empty() {
+        return Some(access_token);
+    }
+
+    if !confirm_identity_server_terms_dialog(&identity_server.server_name(), &policies, parent)
+        .await
+    {
+        return None;
+    }
+
+    let identity_server = identity_server.clone();
+    let access_token_clone = access_token.clone();
+    let handle = spawn_tokio!(async move {
+        identity_server
+            .accept_terms(&client, &access_token_clone, &policies)
+            .await
+    });
+
+    if let Err(error) = handle.await.expect("task was not aborted") {
+        error!("Could not accept terms of identity server: {error}");
+        toast!(
+            parent,
+            gettext("Could not accept the terms of the identity server")
+        );
+        return None;
+    }
+
+    Some(access_token)
+}
+
+/// Show a dialog to ask the user to accept the given terms of the identity
+/// server with the given name.
+///
+/// Returns `true` if the user accepted the terms.
+async fn confirm_identity_server_terms_dialog(
+    server_name: &str,
+    policies: &[IdentityServerPolicy],
+    parent: &impl IsA<gtk::Widget>,
+) -> bool {
+    let links = policies
+        .iter()
+        .map(|policy| {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                policy.url.escape_markup(),
+                policy.name.escape_markup()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let body = gettext_f(
+        // Translators: Do NOT translate the content between '{' and '}', this is a
+        // variable name.
+        "To use the identity server {server_name}, you must accept its terms:",
+        &[("server_name", &server_name.escape_markup())],
+    );
+
+    let confirm_dialog = adw::AlertDialog::builder()
+        .heading(gettext("Accept Terms of Identity Server"))
+        .body(format!("{body}\n\n{links}"))
+        .body_use_markup(true)
+        .default_response("accept")
+        .build();
+    confirm_dialog.add_responses(&[
+        ("cancel", &gettext("Cancel")),
+        ("accept", &gettext("Accept")),
+    ]);
+    confirm_dialog.set_response_appearance("accept", adw::ResponseAppearance::Suggested);
+
+    confirm_dialog.choose_future(Some(parent)).await == "accept"
+}
+
 /// Show a dialog for the user to choose what to do about unsaved changes.
 pub(crate) async fn unsaved_changes_dialog(
     parent: &impl IsA<gtk::Widget>,
@@ -512,3 +980,42 @@ pub(crate) enum UnsavedChangesResponse {
     /// Cancel the current action.
     Cancel,
 }
+
+/// Show a dialog to write a quick reply in the given room.
+///
+/// The dialog is presented in its own window, so it can be used without
+/// showing the main window.
+///
+/// Returns `None` if the user cancelled or the message is empty.
+pub(crate) async fn quick_reply_dialog(room: &Room) -> Option<String> {
+    let message_entry = adw::EntryRow::builder()
+        .title(gettext("Message"))
+        .activates_default(true)
+        .build();
+    let list_box = gtk::ListBox::builder()
+        .css_classes(["boxed-list"])
+        .margin_top(6)
+        .accessible_role(gtk::AccessibleRole::Group)
+        .build();
+    list_box.append(&message_entry);
+
+    let dialog = adw::AlertDialog::builder()
+        .default_response("send")
+        .heading(gettext("Reply"))
+        .body(gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "Send a message in {room}",
+            &[("room", &room.display_name())],
+        ))
+        .extra_child(&list_box)
+        .build();
+    dialog.add_responses(&[("cancel", &gettext("Cancel")), ("send", &gettext("Send"))]);
+    dialog.set_response_appearance("send", adw::ResponseAppearance::Suggested);
+
+    if dialog.choose_future(None::<&gtk::Widget>).await != "send" {
+        return None;
+    }
+
+    Some(message_entry.text().trim().to_owned()).filter(|s| !s.is_empty())
+}
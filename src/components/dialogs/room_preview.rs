@@ -11,7 +11,7 @@ use crate::{
     session::{RemoteRoom, Session},
     toast,
     utils::{
-        LoadingState,
+        LoadingState, links,
         matrix::{MatrixIdUri, MatrixRoomIdUri},
     },
 };
@@ -94,7 +94,7 @@ mod imp {
                 glib::Propagation::Proceed,
                 move |_, uri| {
                     let Ok(uri) = MatrixIdUri::parse(uri) else {
-                        return glib::Propagation::Proceed;
+                        return links::handle_activated_external_link(&obj, uri);
                     };
                     let Some(parent_window) =
                         obj.ancestor(Window::static_type()).and_downcast::<Window>()
@@ -92,6 +92,17 @@ mod imp {
             self.update();
         }
 
+        /// Connect to the signal emitted when a link in the child label is
+        /// activated.
+        pub(super) fn connect_activate_link<
+            F: Fn(&gtk::Label, &str) -> glib::Propagation + 'static,
+        >(
+            &self,
+            f: F,
+        ) -> glib::SignalHandlerId {
+            self.child.connect_activate_link(f)
+        }
+
         /// Set the widgets to display.
         fn set_widgets<P: IsA<gtk::Widget>>(&self, widgets: Vec<P>) {
             for widget in self.widgets.borrow_mut().drain(..) {
@@ -337,6 +348,14 @@ impl LabelWithWidgets {
     ) {
         self.imp().set_label_and_widgets(label, widgets);
     }
+
+    /// Connect to the signal emitted when a link in the label is activated.
+    pub(crate) fn connect_activate_link<F: Fn(&gtk::Label, &str) -> glib::Propagation + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.imp().connect_activate_link(f)
+    }
 }
 
 impl Default for LabelWithWidgets {
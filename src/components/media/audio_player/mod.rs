@@ -579,11 +579,7 @@ impl AudioPlayerSource {
                     return Err(MediaFileError::NoSession);
                 };
 
-                message
-                    .message
-                    .clone()
-                    .into_tmp_file(&session.client())
-                    .await
+                message.message.clone().into_tmp_file(&session).await
             }
         }
     }
@@ -1,9 +1,11 @@
 use adw::{prelude::*, subclass::prelude::*};
 use geo_uri::GeoUri;
 use gettextrs::gettext;
-use gtk::{gdk, gio, glib};
+use gtk::{gdk, gio, glib, glib::clone};
 
-use super::{AnimatedImagePaintable, AudioPlayer, AudioPlayerSource, LocationViewer};
+use super::{
+    AnimatedImagePaintable, AudioPlayer, AudioPlayerSource, LocationViewer, ZoomablePicture,
+};
 use crate::{
     MEDIA_FILE_NOTIFIER,
     components::ContextMenuBin,
@@ -51,7 +53,10 @@ impl From<&str> for ContentType {
 }
 
 mod imp {
-    use std::cell::{Cell, RefCell};
+    use std::{
+        cell::{Cell, RefCell},
+        marker::PhantomData,
+    };
 
     use glib::subclass::InitializingObject;
 
@@ -70,6 +75,12 @@ mod imp {
         /// Whether to play the media content automatically.
         #[property(get, construct_only)]
         autoplay: Cell<bool>,
+        /// Whether images can be zoomed.
+        #[property(get, construct_only)]
+        zoomable: Cell<bool>,
+        /// Whether the image is zoomed in.
+        #[property(get = Self::is_zoomed)]
+        is_zoomed: PhantomData<bool>,
         /// The current media file.
         file: RefCell<Option<File>>,
         paintable_animation_ref: RefCell<Option<CountedRef>>,
@@ -115,6 +126,22 @@ mod imp {
             self.viewer.child().and_downcast()
         }
 
+        /// The paintable of the image child, if any.
+        pub(super) fn paintable(&self) -> Option<gdk::Paintable> {
+            if let Some(picture) = self.media_child::<ZoomablePicture>() {
+                picture.paintable()
+            } else {
+                self.media_child::<gtk::Picture>()
+                    .and_then(|picture| picture.paintable())
+            }
+        }
+
+        /// Whether the image is zoomed in.
+        fn is_zoomed(&self) -> bool {
+            self.media_child::<ZoomablePicture>()
+                .is_some_and(|picture| picture.is_zoomed())
+        }
+
         /// Show the fallback message for the given content type.
         pub(super) fn show_fallback(&self, content_type: ContentType) {
             let title = match content_type {
@@ -138,18 +165,38 @@ mod imp {
             self.set_visible_child("loading");
             self.clear();
 
-            let picture = if let Some(picture) = self.media_child::<gtk::Picture>() {
-                picture
+            if self.zoomable.get() {
+                let picture = if let Some(picture) = self.media_child::<ZoomablePicture>() {
+                    picture
+                } else {
+                    let picture = ZoomablePicture::new();
+                    picture.connect_is_zoomed_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.obj().notify_is_zoomed();
+                        }
+                    ));
+                    self.viewer.set_child(Some(&picture));
+                    picture
+                };
+
+                picture.set_paintable(Some(image));
             } else {
-                let picture = gtk::Picture::builder()
-                    .valign(gtk::Align::Center)
-                    .halign(gtk::Align::Center)
-                    .build();
-                self.viewer.set_child(Some(&picture));
-                picture
-            };
+                let picture = if let Some(picture) = self.media_child::<gtk::Picture>() {
+                    picture
+                } else {
+                    let picture = gtk::Picture::builder()
+                        .valign(gtk::Align::Center)
+                        .halign(gtk::Align::Center)
+                        .build();
+                    self.viewer.set_child(Some(&picture));
+                    picture
+                };
+
+                picture.set_paintable(Some(image));
+            }
 
-            picture.set_paintable(Some(image));
             self.update_animated_paintable_state();
             self.set_visible_child("viewer");
         }
@@ -252,11 +299,7 @@ mod imp {
         fn update_animated_paintable_state(&self) {
             self.paintable_animation_ref.take();
 
-            let Some(paintable) = self
-                .media_child::<gtk::Picture>()
-                .and_then(|p| p.paintable())
-                .and_downcast::<AnimatedImagePaintable>()
-            else {
+            let Some(paintable) = self.paintable().and_downcast::<AnimatedImagePaintable>() else {
                 return;
             };
 
@@ -271,6 +314,9 @@ mod imp {
             if let Some(video) = self.media_child::<gtk::Video>() {
                 video.set_file(None::<&gio::File>);
             }
+            if let Some(picture) = self.media_child::<ZoomablePicture>() {
+                picture.reset_zoom();
+            }
 
             self.paintable_animation_ref.take();
             self.file.take();
@@ -329,12 +375,30 @@ impl MediaContentViewer {
         self.imp().view_location(geo_uri);
     }
 
+    /// Zoom in the image by one step, if it is zoomable.
+    pub(crate) fn zoom_in(&self) {
+        if let Some(picture) = self.imp().media_child::<ZoomablePicture>() {
+            picture.zoom_in();
+        }
+    }
+
+    /// Zoom out the image by one step, if it is zoomable.
+    pub(crate) fn zoom_out(&self) {
+        if let Some(picture) = self.imp().media_child::<ZoomablePicture>() {
+            picture.zoom_out();
+        }
+    }
+
+    /// Reset the zoom level of the image, if it is zoomable.
+    pub(crate) fn reset_zoom(&self) {
+        if let Some(picture) = self.imp().media_child::<ZoomablePicture>() {
+            picture.reset_zoom();
+        }
+    }
+
     /// Get the texture displayed by this widget, if any.
     pub(crate) fn texture(&self) -> Option<gdk::Texture> {
-        let paintable = self
-            .imp()
-            .media_child::<gtk::Picture>()
-            .and_then(|p| p.paintable())?;
+        let paintable = self.imp().paintable()?;
 
         if let Some(paintable) = paintable.downcast_ref::<AnimatedImagePaintable>() {
             paintable.current_texture()
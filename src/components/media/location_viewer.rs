@@ -3,7 +3,10 @@ use geo_uri::GeoUri;
 use gtk::{gdk, gio, glib};
 use shumate::prelude::*;
 
-use crate::i18n::gettext_f;
+use crate::{Application, i18n::gettext_f};
+
+/// The URL of the tiles in the map style.
+const DEFAULT_MAP_TILE_URL: &str = "https://tileserver.gnome.org/data/v3/{z}/{x}/{y}.pbf";
 
 mod imp {
     use std::cell::Cell;
@@ -53,9 +56,13 @@ mod imp {
                 gio::ResourceLookupFlags::NONE,
             )
             .expect("should be able to load map style");
-            let renderer =
-                shumate::VectorRenderer::new("vector-tiles", &String::from_utf8_lossy(&style))
-                    .expect("should be able to read map style");
+            // Use the tile server from the settings.
+            let tile_url = Application::default().settings().string("map-tile-url");
+            // Quotes would break the JSON of the style, they are not valid in URLs anyway.
+            let tile_url = tile_url.replace('"', "%22");
+            let style = String::from_utf8_lossy(&style).replace(DEFAULT_MAP_TILE_URL, &tile_url);
+            let renderer = shumate::VectorRenderer::new("vector-tiles", &style)
+                .expect("should be able to read map style");
             renderer.set_license("© OpenMapTiles © OpenStreetMap contributors");
             renderer.set_license_uri("https://www.openstreetmap.org/copyright");
 
@@ -4,6 +4,7 @@ mod content_viewer;
 mod location_viewer;
 mod video_player;
 mod video_player_renderer;
+mod zoomable_picture;
 
 pub(crate) use self::{
     animated_image_paintable::AnimatedImagePaintable,
@@ -11,4 +12,5 @@ pub(crate) use self::{
     content_viewer::{ContentType, MediaContentViewer},
     location_viewer::LocationViewer,
     video_player::VideoPlayer,
+    zoomable_picture::ZoomablePicture,
 };
@@ -27,6 +27,12 @@ mod imp {
         /// Whether the player is displayed in its compact form.
         #[property(get, set = Self::set_compact, explicit_notify)]
         compact: Cell<bool>,
+        /// Whether the playback is paused.
+        ///
+        /// When the playback is paused, the current frame of the video is
+        /// shown.
+        #[property(get, set = Self::set_paused, explicit_notify)]
+        paused: Cell<bool>,
         /// The state of the video in this player.
         #[property(get, builder(LoadingState::default()))]
         state: Cell<LoadingState>,
@@ -92,7 +98,7 @@ mod imp {
 
             // Avoid more errors in the logs.
             if self.state.get() != LoadingState::Error {
-                self.player.play();
+                self.update_playback();
             }
         }
 
@@ -117,6 +123,30 @@ mod imp {
             self.obj().notify_compact();
         }
 
+        /// Set whether the playback is paused.
+        fn set_paused(&self, paused: bool) {
+            if self.paused.get() == paused {
+                return;
+            }
+
+            self.paused.set(paused);
+
+            if self.obj().is_mapped() && self.state.get() != LoadingState::Error {
+                self.update_playback();
+            }
+
+            self.obj().notify_paused();
+        }
+
+        /// Play or pause the video, according to whether it is paused.
+        fn update_playback(&self) {
+            if self.paused.get() {
+                self.player.pause();
+            } else {
+                self.player.play();
+            }
+        }
+
         /// Set the state of the media.
         fn set_state(&self, state: LoadingState) {
             if self.state.get() == state {
@@ -138,7 +168,7 @@ mod imp {
             self.player.set_uri(Some(uri.as_ref()));
             self.player.set_audio_track_enabled(false);
 
-            if self.obj().is_mapped() {
+            if self.obj().is_mapped() && !self.paused.get() {
                 self.player.play();
             } else {
                 // Pause, unlike stop, loads the info of the video.
@@ -0,0 +1,433 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, glib, glib::clone};
+
+/// The maximum zoom level.
+const MAX_ZOOM: f64 = 8.0;
+/// The factor applied to the zoom level when zooming in or out by one step.
+const ZOOM_STEP: f64 = 1.25;
+
+/// The position to keep in place on an axis while changing the zoom level.
+#[derive(Debug, Clone, Copy)]
+struct ZoomAnchor {
+    /// The position of the anchor in the picture, between `0.0` and `1.0`.
+    fraction: f64,
+    /// The position of the anchor in the visible area, in pixels.
+    point: f64,
+}
+
+impl ZoomAnchor {
+    /// Compute the anchor at the given point of the visible area of the given
+    /// adjustment, for the given size of the picture.
+    fn new(adjustment: &gtk::Adjustment, picture_size: f64, point: f64) -> Self {
+        let offset = picture_offset(adjustment, picture_size);
+        let fraction = ((adjustment.value() + point - offset) / picture_size).clamp(0.0, 1.0);
+
+        Self { fraction, point }
+    }
+
+    /// Move the given adjustment so this anchor is at the same place with the
+    /// given size of the picture.
+    fn apply(self, adjustment: &gtk::Adjustment, picture_size: f64) {
+        let offset = picture_offset(adjustment, picture_size);
+        adjustment.set_value(self.fraction * picture_size + offset - self.point);
+    }
+}
+
+/// The offset of the picture when it is smaller than the visible area of the
+/// given adjustment, because it is centered.
+fn picture_offset(adjustment: &gtk::Adjustment, picture_size: f64) -> f64 {
+    ((adjustment.page_size() - picture_size) / 2.0).max(0.0)
+}
+
+mod imp {
+    use std::{cell::Cell, marker::PhantomData};
+
+    use super::*;
+
+    #[derive(Debug, glib::Properties)]
+    #[properties(wrapper_type = super::ZoomablePicture)]
+    pub struct ZoomablePicture {
+        scrolled_window: gtk::ScrolledWindow,
+        picture: gtk::Picture,
+        /// The paintable displayed by this widget.
+        #[property(get = Self::paintable, set = Self::set_paintable, explicit_notify, nullable)]
+        paintable: PhantomData<Option<gdk::Paintable>>,
+        /// The current zoom level.
+        ///
+        /// At `1.0`, the picture fits in the widget.
+        #[property(get, minimum = 1.0, maximum = MAX_ZOOM, default = 1.0)]
+        zoom: Cell<f64>,
+        /// Whether the picture is zoomed in.
+        #[property(get = Self::is_zoomed)]
+        is_zoomed: PhantomData<bool>,
+        /// The size of the picture when it fits in the widget.
+        fit_size: Cell<(f64, f64)>,
+        /// The zoom level when the current zoom gesture started.
+        gesture_start_zoom: Cell<f64>,
+        /// The values of the adjustments when the current drag started.
+        drag_start_values: Cell<(f64, f64)>,
+        /// The last known position of the pointer.
+        pointer_position: Cell<Option<(f64, f64)>>,
+        /// The anchors to keep in place after a zoom change, on the horizontal
+        /// and vertical axis.
+        zoom_anchors: Cell<Option<(ZoomAnchor, ZoomAnchor)>>,
+    }
+
+    impl Default for ZoomablePicture {
+        fn default() -> Self {
+            Self {
+                scrolled_window: gtk::ScrolledWindow::builder()
+                    .hscrollbar_policy(gtk::PolicyType::Never)
+                    .vscrollbar_policy(gtk::PolicyType::Never)
+                    .propagate_natural_width(true)
+                    .propagate_natural_height(true)
+                    .build(),
+                picture: gtk::Picture::builder()
+                    .valign(gtk::Align::Center)
+                    .halign(gtk::Align::Center)
+                    .build(),
+                paintable: PhantomData,
+                zoom: Cell::new(1.0),
+                is_zoomed: PhantomData,
+                fit_size: Default::default(),
+                gesture_start_zoom: Cell::new(1.0),
+                drag_start_values: Default::default(),
+                pointer_position: Default::default(),
+                zoom_anchors: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ZoomablePicture {
+        const NAME: &'static str = "ZoomablePicture";
+        type Type = super::ZoomablePicture;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("zoomable-picture");
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ZoomablePicture {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            self.scrolled_window.set_child(Some(&self.picture));
+            obj.set_child(Some(&self.scrolled_window));
+
+            for adjustment in [
+                self.scrolled_window.hadjustment(),
+                self.scrolled_window.vadjustment(),
+            ] {
+                adjustment.connect_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.apply_zoom_anchors();
+                    }
+                ));
+            }
+
+            // Pinch to zoom.
+            let zoom_gesture = gtk::GestureZoom::new();
+            zoom_gesture.connect_begin(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _| {
+                    imp.gesture_start_zoom.set(imp.zoom.get());
+                }
+            ));
+            zoom_gesture.connect_scale_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |gesture, scale| {
+                    let Some((x, y)) = gesture.bounding_box_center() else {
+                        return;
+                    };
+
+                    imp.zoom_at(imp.gesture_start_zoom.get() * scale, x, y);
+                }
+            ));
+            self.scrolled_window.add_controller(zoom_gesture);
+
+            // Ctrl + scroll to zoom.
+            let motion_controller = gtk::EventControllerMotion::new();
+            motion_controller.connect_motion(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, x, y| {
+                    imp.pointer_position.set(Some((x, y)));
+                }
+            ));
+            motion_controller.connect_leave(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.pointer_position.set(None);
+                }
+            ));
+            self.scrolled_window.add_controller(motion_controller);
+
+            let scroll_controller =
+                gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+            scroll_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+            scroll_controller.connect_scroll(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                glib::Propagation::Proceed,
+                move |controller, _, dy| {
+                    if !controller
+                        .current_event_state()
+                        .contains(gdk::ModifierType::CONTROL_MASK)
+                    {
+                        return glib::Propagation::Proceed;
+                    }
+
+                    let (x, y) = imp
+                        .pointer_position
+                        .get()
+                        .unwrap_or_else(|| imp.visible_center());
+                    imp.zoom_at(imp.zoom.get() * ZOOM_STEP.powf(-dy), x, y);
+
+                    glib::Propagation::Stop
+                }
+            ));
+            self.scrolled_window.add_controller(scroll_controller);
+
+            // Drag with the mouse to pan. Touchscreens are already handled by the
+            // scrolled window.
+            let drag_gesture = gtk::GestureDrag::new();
+            drag_gesture.connect_drag_begin(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |gesture, _, _| {
+                    let is_touchscreen = gesture
+                        .device()
+                        .is_some_and(|device| device.source() == gdk::InputSource::Touchscreen);
+
+                    if !imp.is_zoomed() || is_touchscreen {
+                        gesture.set_state(gtk::EventSequenceState::Denied);
+                        return;
+                    }
+
+                    imp.drag_start_values.set((
+                        imp.scrolled_window.hadjustment().value(),
+                        imp.scrolled_window.vadjustment().value(),
+                    ));
+                    imp.obj().set_cursor_from_name(Some("grabbing"));
+                }
+            ));
+            drag_gesture.connect_drag_update(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |gesture, offset_x, offset_y| {
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+
+                    let (start_x, start_y) = imp.drag_start_values.get();
+                    imp.scrolled_window
+                        .hadjustment()
+                        .set_value(start_x - offset_x);
+                    imp.scrolled_window
+                        .vadjustment()
+                        .set_value(start_y - offset_y);
+                }
+            ));
+            drag_gesture.connect_drag_end(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _| {
+                    imp.obj().set_cursor_from_name(None);
+                }
+            ));
+            self.scrolled_window.add_controller(drag_gesture);
+        }
+    }
+
+    impl WidgetImpl for ZoomablePicture {}
+    impl BinImpl for ZoomablePicture {}
+
+    impl ZoomablePicture {
+        /// The paintable displayed by this widget.
+        fn paintable(&self) -> Option<gdk::Paintable> {
+            self.picture.paintable()
+        }
+
+        /// Set the paintable displayed by this widget.
+        fn set_paintable(&self, paintable: Option<&gdk::Paintable>) {
+            if self.paintable().as_ref() == paintable {
+                return;
+            }
+
+            self.reset_zoom();
+            self.picture.set_paintable(paintable);
+            self.obj().notify_paintable();
+        }
+
+        /// Whether the picture is zoomed in.
+        fn is_zoomed(&self) -> bool {
+            self.zoom.get() > 1.0
+        }
+
+        /// The center of the visible area.
+        fn visible_center(&self) -> (f64, f64) {
+            (
+                f64::from(self.scrolled_window.width()) / 2.0,
+                f64::from(self.scrolled_window.height()) / 2.0,
+            )
+        }
+
+        /// The size of the picture when it fits in the widget.
+        fn compute_fit_size(&self) -> (f64, f64) {
+            let width = f64::from(self.picture.width());
+            let height = f64::from(self.picture.height());
+            let aspect_ratio = self
+                .paintable()
+                .map(|paintable| paintable.intrinsic_aspect_ratio())
+                .unwrap_or_default();
+
+            if aspect_ratio <= 0.0 || height <= 0.0 {
+                (width, height)
+            } else if width / height > aspect_ratio {
+                (height * aspect_ratio, height)
+            } else {
+                (width, width / aspect_ratio)
+            }
+        }
+
+        /// Change the zoom level, keeping the given point of the visible area
+        /// in place.
+        pub(super) fn zoom_at(&self, zoom: f64, x: f64, y: f64) {
+            let zoom = zoom.clamp(1.0, MAX_ZOOM);
+            let old_zoom = self.zoom.get();
+
+            if (zoom - old_zoom).abs() < f64::EPSILON {
+                return;
+            }
+
+            let was_zoomed = self.is_zoomed();
+            if !was_zoomed {
+                self.fit_size.set(self.compute_fit_size());
+            }
+
+            let (fit_width, fit_height) = self.fit_size.get();
+            if fit_width <= 0.0 || fit_height <= 0.0 {
+                return;
+            }
+
+            let anchors = (
+                ZoomAnchor::new(&self.scrolled_window.hadjustment(), fit_width * old_zoom, x),
+                ZoomAnchor::new(
+                    &self.scrolled_window.vadjustment(),
+                    fit_height * old_zoom,
+                    y,
+                ),
+            );
+            self.zoom_anchors.set(Some(anchors));
+
+            self.zoom.set(zoom);
+            self.update_size();
+
+            let obj = self.obj();
+            obj.notify_zoom();
+
+            if was_zoomed != self.is_zoomed() {
+                obj.notify_is_zoomed();
+            }
+        }
+
+        /// Change the zoom level by the given number of steps, keeping the
+        /// center of the visible area in place.
+        pub(super) fn zoom_by_steps(&self, steps: f64) {
+            let (x, y) = self.visible_center();
+            self.zoom_at(self.zoom.get() * ZOOM_STEP.powf(steps), x, y);
+        }
+
+        /// Reset the zoom level so the picture fits in the widget.
+        pub(super) fn reset_zoom(&self) {
+            if !self.is_zoomed() {
+                return;
+            }
+
+            self.zoom_anchors.take();
+            self.zoom.set(1.0);
+            self.update_size();
+
+            let obj = self.obj();
+            obj.notify_zoom();
+            obj.notify_is_zoomed();
+        }
+
+        /// Update the size of the picture for the current zoom level.
+        fn update_size(&self) {
+            if self.is_zoomed() {
+                let zoom = self.zoom.get();
+                let (fit_width, fit_height) = self.fit_size.get();
+
+                self.picture.set_size_request(
+                    (fit_width * zoom).round() as i32,
+                    (fit_height * zoom).round() as i32,
+                );
+                self.scrolled_window
+                    .set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+            } else {
+                self.picture.set_size_request(-1, -1);
+                self.scrolled_window
+                    .set_policy(gtk::PolicyType::Never, gtk::PolicyType::Never);
+            }
+        }
+
+        /// Move the visible area to keep the zoom anchors in place, if any.
+        fn apply_zoom_anchors(&self) {
+            let Some((h_anchor, v_anchor)) = self.zoom_anchors.get() else {
+                return;
+            };
+
+            let zoom = self.zoom.get();
+            let (fit_width, fit_height) = self.fit_size.get();
+
+            h_anchor.apply(&self.scrolled_window.hadjustment(), fit_width * zoom);
+            v_anchor.apply(&self.scrolled_window.vadjustment(), fit_height * zoom);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A widget displaying a picture that can be zoomed and panned.
+    ///
+    /// The picture can be zoomed with a pinch gesture or with Ctrl + scroll,
+    /// and panned by dragging it or scrolling.
+    pub struct ZoomablePicture(ObjectSubclass<imp::ZoomablePicture>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ZoomablePicture {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Zoom in by one step.
+    pub(crate) fn zoom_in(&self) {
+        self.imp().zoom_by_steps(1.0);
+    }
+
+    /// Zoom out by one step.
+    pub(crate) fn zoom_out(&self) {
+        self.imp().zoom_by_steps(-1.0);
+    }
+
+    /// Reset the zoom level so the picture fits in the widget.
+    pub(crate) fn reset_zoom(&self) {
+        self.imp().reset_zoom();
+    }
+}
+
+impl Default for ZoomablePicture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,103 @@
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{EventId, OwnedEventId};
+
+use crate::{
+    components::{PillSource, PillSourceExt},
+    i18n::gettext_f,
+    prelude::*,
+};
+
+mod imp {
+    use std::cell::OnceCell;
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::EventLink)]
+    pub struct EventLink {
+        /// The room containing the event.
+        ///
+        /// This is either a local room or a remote room.
+        #[property(get, set = Self::set_room, construct_only)]
+        room: OnceCell<PillSource>,
+        /// The ID of the event.
+        pub(super) event_id: OnceCell<OwnedEventId>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for EventLink {
+        const NAME: &'static str = "EventLink";
+        type Type = super::EventLink;
+        type ParentType = PillSource;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for EventLink {}
+
+    impl PillSourceImpl for EventLink {
+        fn identifier(&self) -> String {
+            self.event_id().to_string()
+        }
+    }
+
+    impl EventLink {
+        /// Set the room containing the event.
+        fn set_room(&self, room: PillSource) {
+            let room = self.room.get_or_init(|| room);
+
+            // Use the avatar of the room.
+            room.avatar_data()
+                .bind_property("image", &self.obj().avatar_data(), "image")
+                .sync_create()
+                .build();
+
+            room.connect_display_name_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |room| {
+                    imp.update_display_name(room);
+                }
+            ));
+            self.update_display_name(room);
+        }
+
+        /// Update the display name for the given room.
+        fn update_display_name(&self, room: &PillSource) {
+            self.obj().set_display_name(gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "Message in {room}",
+                &[("room", &room.display_name())],
+            ));
+        }
+
+        /// The ID of the event.
+        pub(super) fn event_id(&self) -> &EventId {
+            self.event_id.get().expect("event ID should be initialized")
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A helper `PillSource` to represent a link to an event.
+    pub struct EventLink(ObjectSubclass<imp::EventLink>) @extends PillSource;
+}
+
+impl EventLink {
+    /// Constructs a link to the event with the given ID in the given room.
+    pub fn new(room: &impl IsA<PillSource>, event_id: OwnedEventId) -> Self {
+        let obj = glib::Object::builder::<Self>()
+            .property("room", room.upcast_ref())
+            .build();
+        obj.imp()
+            .event_id
+            .set(event_id)
+            .expect("event ID should be uninitialized");
+        obj
+    }
+
+    /// The ID of the event.
+    pub fn event_id(&self) -> &EventId {
+        self.imp().event_id()
+    }
+}
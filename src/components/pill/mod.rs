@@ -1,21 +1,26 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{glib, glib::clone};
+use matrix_sdk_ui::timeline::TimelineEventItemId;
+use tracing::error;
 
 mod at_room;
+mod event_link;
 mod search_entry;
 mod source;
 mod source_row;
 
 pub use self::{
     at_room::AtRoom,
+    event_link::EventLink,
     search_entry::PillSearchEntry,
     source::{PillSource, PillSourceExt, PillSourceImpl},
     source_row::PillSourceRow,
 };
 use super::{Avatar, AvatarImageSafetySetting, RoomPreviewDialog, UserProfileDialog};
 use crate::{
+    Window,
     prelude::*,
-    session::{Member, RemoteRoom, Room},
+    session::{Member, RemoteRoom, Room, User},
     session_view::SessionView,
     utils::{BoundObject, key_bindings},
 };
@@ -55,6 +60,9 @@ mod imp {
         /// This is required if `watched_safety_setting` is not `None`.
         #[property(get = Self::watched_room, set = Self::set_watched_room, nullable)]
         watched_room: PhantomData<Option<Room>>,
+        /// The CSS class of the color of the current user, if the source is a
+        /// user.
+        color_css_class: RefCell<Option<String>>,
         gesture_click: RefCell<Option<gtk::GestureClick>>,
     }
 
@@ -119,9 +127,23 @@ mod imp {
                 self.source.set(source, vec![display_name_handler]);
             }
 
+            self.update_color();
             self.obj().notify_source();
         }
 
+        /// Update the color of the display name for the current source.
+        fn update_color(&self) {
+            if let Some(css_class) = self.color_css_class.take() {
+                self.display_name.remove_css_class(&css_class);
+            }
+
+            if let Some(user) = self.source.obj().and_downcast::<User>() {
+                let css_class = user.color_css_class();
+                self.display_name.add_css_class(&css_class);
+                self.color_css_class.replace(Some(css_class));
+            }
+        }
+
         /// Set whether this widget can be activated.
         fn set_activatable(&self, activatable: bool) {
             if self.activatable.get() == activatable {
@@ -211,25 +233,66 @@ mod imp {
             self.display_name.set_label(&maybe_ellipsized);
         }
 
+        /// The ancestor session view of this pill.
+        ///
+        /// If this pill is in a dialog, this is the session view of the parent
+        /// window.
+        fn session_view(&self) -> Option<SessionView> {
+            let obj = self.obj();
+
+            obj.ancestor(SessionView::static_type())
+                .and_downcast::<SessionView>()
+                .or_else(|| {
+                    obj.root()
+                        .and_downcast::<Window>()
+                        .map(|window| window.session_view().clone())
+                })
+        }
+
         /// Activate the pill.
         ///
         /// This opens a known room or opens the profile of a user or unknown
-        /// room.
+        /// room. For a link to an event in the room that is currently shown,
+        /// it scrolls to the event.
         fn activate(&self) {
-            let Some(source) = self.source.obj() else {
+            let Some(mut source) = self.source.obj() else {
                 return;
             };
             let obj = self.obj();
 
+            if let Some(event_link) = source.downcast_ref::<EventLink>() {
+                let room = event_link.room();
+
+                if let Some(room) = room.downcast_ref::<Room>()
+                    && self
+                        .session_view()
+                        .and_then(|session_view| session_view.selected_room())
+                        .is_some_and(|selected_room| selected_room == *room)
+                {
+                    let event_key = TimelineEventItemId::EventId(event_link.event_id().to_owned());
+
+                    if obj
+                        .activate_action(
+                            "room-history.scroll-to-event",
+                            Some(&event_key.to_variant()),
+                        )
+                        .is_err()
+                    {
+                        error!("Could not activate `room-history.scroll-to-event` action");
+                    }
+                    return;
+                }
+
+                // Open the room like for a room pill.
+                source = room;
+            }
+
             if let Some(member) = source.downcast_ref::<Member>() {
                 let dialog = UserProfileDialog::new();
                 dialog.set_room_member(member.clone());
                 dialog.present(Some(&*obj));
             } else if let Some(room) = source.downcast_ref::<Room>() {
-                let Some(session_view) = obj
-                    .ancestor(SessionView::static_type())
-                    .and_downcast::<SessionView>()
-                else {
+                let Some(session_view) = self.session_view() else {
                     return;
                 };
 
@@ -268,7 +331,11 @@ impl Pill {
             .downcast_ref::<Room>()
             .cloned()
             .or_else(|| source.downcast_ref::<AtRoom>().map(AtRoom::room))
-        {
+            .or_else(|| {
+                source
+                    .downcast_ref::<EventLink>()
+                    .and_then(|event_link| event_link.room().downcast::<Room>().ok())
+            }) {
             // We must always watch the invite avatars setting for local rooms.
             (AvatarImageSafetySetting::InviteAvatars, Some(room))
         } else {
@@ -301,11 +301,14 @@ mod imp {
             self.direct_chat_button.set_is_loading(true);
             let obj = self.obj();
 
-            let Ok(room) = user.get_or_create_direct_chat().await else {
-                toast!(obj, &gettext("Could not create a new Direct Chat"));
-                self.direct_chat_button.set_is_loading(false);
+            let room = match user.get_or_create_direct_chat().await {
+                Ok(room) => room,
+                Err(error) => {
+                    toast!(obj, error.to_user_facing());
+                    self.direct_chat_button.set_is_loading(false);
 
-                return;
+                    return;
+                }
             };
 
             let Some(parent_window) = obj.root().and_downcast::<gtk::Window>() else {
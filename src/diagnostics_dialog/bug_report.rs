@@ -0,0 +1,250 @@
+use std::fmt::Write;
+
+use gettextrs::gettext;
+use gtk::glib;
+use matrix_sdk::reqwest::{self, StatusCode};
+use thiserror::Error;
+use tracing::error;
+
+use crate::{
+    config, gettext_f,
+    prelude::*,
+    session::{Session, SessionSyncMode},
+    spawn_tokio,
+    utils::logs,
+};
+
+/// Diagnostics about a session.
+#[derive(Debug, Clone)]
+pub(super) struct SessionDiagnostics {
+    /// The URL of the homeserver.
+    homeserver: String,
+    /// The versions of the Matrix specification supported by the homeserver,
+    /// if they could be loaded.
+    versions: Option<Vec<String>>,
+    /// The unstable features enabled on the homeserver.
+    features: Vec<String>,
+    /// The mode used to synchronize the session.
+    sync_mode: SessionSyncMode,
+}
+
+impl SessionDiagnostics {
+    /// Load the diagnostics of the given session.
+    pub(super) async fn load(session: &Session) -> Self {
+        let client = session.client();
+        let handle = spawn_tokio!(async move { client.supported_versions().await });
+
+        let (versions, features) = match handle.await.expect("task was not aborted") {
+            Ok(supported_versions) => (
+                Some(
+                    supported_versions
+                        .versions
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                ),
+                supported_versions
+                    .features
+                    .iter()
+                    .map(|feature| feature.as_str().to_owned())
+                    .collect(),
+            ),
+            Err(error) => {
+                error!("Could not get server supported versions: {error}");
+                (None, Vec::new())
+            }
+        };
+
+        Self {
+            homeserver: session.homeserver().to_string(),
+            versions,
+            features,
+            sync_mode: session.sync_mode(),
+        }
+    }
+
+    /// A description of these diagnostics, to present them in the interface.
+    pub(super) fn to_localized_string(&self) -> String {
+        let versions = self
+            .versions
+            .as_ref()
+            .map_or_else(|| gettext("unknown"), |versions| versions.join(", "));
+        let sync_mode = match self.sync_mode {
+            SessionSyncMode::Classic => gettext("Classic"),
+            SessionSyncMode::Sliding => gettext("Simplified Sliding Sync"),
+        };
+
+        gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this is a
+            // variable name.
+            "Homeserver: {homeserver}\nMatrix versions: {versions}\nSync mode: {sync_mode}",
+            &[
+                ("homeserver", &self.homeserver),
+                ("versions", &versions),
+                ("sync_mode", &sync_mode),
+            ],
+        )
+    }
+
+    /// Write these diagnostics in the given bug report.
+    fn write_report(&self, report: &mut String) {
+        let versions = self
+            .versions
+            .as_ref()
+            .map_or_else(|| "unknown".to_owned(), |versions| versions.join(", "));
+        let features = if self.features.is_empty() {
+            "none".to_owned()
+        } else {
+            self.features.join(", ")
+        };
+
+        let _ = writeln!(report, "Homeserver: {}", self.homeserver);
+        let _ = writeln!(report, "Matrix versions: {versions}");
+        let _ = writeln!(report, "Server features: {features}");
+        let _ = writeln!(report, "Sync mode: {:?}", self.sync_mode);
+    }
+}
+
+/// A bug report, with the diagnostics and the recent logs of the application.
+///
+/// The sensitive data, like access tokens, recovery keys and the content of
+/// messages, is removed from the report.
+#[derive(Debug, Clone)]
+pub(super) struct BugReport {
+    /// The description of the issue, provided by the user.
+    description: String,
+    /// The content of the report.
+    content: String,
+}
+
+impl BugReport {
+    /// Generate a bug report with the given description of the issue and
+    /// diagnostics of the sessions.
+    pub(super) fn generate(description: &str, sessions: &[SessionDiagnostics]) -> Self {
+        let description = logs::scrub_secrets(description.trim());
+
+        let mut report = String::from("# Fractal bug report\n\n");
+
+        if !description.is_empty() {
+            let _ = writeln!(report, "## Description\n\n{description}\n");
+        }
+
+        report.push_str("## Application\n\n");
+        let _ = writeln!(report, "Version: {} ({})", config::VERSION, config::PROFILE);
+        let _ = writeln!(report, "Build features: {}", build_features());
+        let _ = writeln!(
+            report,
+            "OS: {}",
+            glib::os_info("PRETTY_NAME").as_deref().unwrap_or("unknown")
+        );
+        let _ = writeln!(
+            report,
+            "GTK: {}.{}.{}",
+            gtk::major_version(),
+            gtk::minor_version(),
+            gtk::micro_version()
+        );
+        let _ = writeln!(
+            report,
+            "Libadwaita: {}.{}.{}",
+            adw::major_version(),
+            adw::minor_version(),
+            adw::micro_version()
+        );
+        let _ = writeln!(report, "Log filter: {}", logs::current_filter());
+
+        for (index, session) in sessions.iter().enumerate() {
+            let _ = writeln!(report, "\n## Session {}\n", index + 1);
+            session.write_report(&mut report);
+        }
+
+        report.push_str("\n## Recent logs\n\n");
+        report.push_str(&logs::recent_logs());
+
+        Self {
+            description,
+            // Scrub the whole report again, in case the diagnostics contain secrets.
+            content: logs::scrub_secrets(&report),
+        }
+    }
+
+    /// The name of the file where this report should be saved.
+    pub(super) fn file_name() -> String {
+        let date = glib::DateTime::now_local()
+            .and_then(|datetime| datetime.format("%Y-%m-%d_%H-%M-%S"))
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        format!("fractal-bug-report-{date}.md")
+    }
+
+    /// The content of this report.
+    pub(super) fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Upload this report to the rageshake server at the given URL, with the
+    /// given HTTP client.
+    pub(super) async fn upload(
+        &self,
+        http_client: reqwest::Client,
+        url: String,
+    ) -> Result<(), BugReportUploadError> {
+        let text = if self.description.is_empty() {
+            "Bug report from the diagnostics".to_owned()
+        } else {
+            self.description.clone()
+        };
+        let body = serde_json::json!({
+            "text": text,
+            "app": "fractal",
+            "version": config::VERSION,
+            "user_agent": format!("Fractal/{}", config::VERSION),
+            "logs": [{
+                "id": Self::file_name(),
+                "lines": self.content,
+            }],
+        });
+        let body = serde_json::to_vec(&body)?;
+
+        spawn_tokio!(async move {
+            let response = http_client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if !status.is_success() {
+                return Err(BugReportUploadError::Response(status));
+            }
+
+            Ok(())
+        })
+        .await
+        .expect("task was not aborted")
+    }
+}
+
+/// The Cargo features that were enabled when building the application.
+fn build_features() -> &'static str {
+    if cfg!(feature = "timeline-diff-check") {
+        "timeline-diff-check"
+    } else {
+        "none"
+    }
+}
+
+/// All errors that can occur when uploading a bug report.
+#[derive(Debug, Error)]
+pub(super) enum BugReportUploadError {
+    /// An error when serializing the report.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// An error when making the request.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The server returned an error.
+    #[error("The server returned an error: {0}")]
+    Response(StatusCode),
+}
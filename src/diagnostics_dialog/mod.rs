@@ -0,0 +1,295 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{gio, glib, glib::clone};
+use matrix_sdk::reqwest;
+use tracing::{debug, error};
+
+mod bug_report;
+
+use self::bug_report::{BugReport, SessionDiagnostics};
+use crate::{
+    Application,
+    components::LoadingButtonRow,
+    config,
+    session::Session,
+    spawn, toast,
+    utils::{
+        logs::{self, LogLevel},
+        matrix,
+    },
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/diagnostics_dialog/mod.ui")]
+    pub struct DiagnosticsDialog {
+        #[template_child]
+        version_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        log_filter_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        log_level_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        sessions_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        bug_report_description_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        save_bug_report_button: TemplateChild<LoadingButtonRow>,
+        #[template_child]
+        send_bug_report_button: TemplateChild<LoadingButtonRow>,
+        #[template_child]
+        logs_view: TemplateChild<gtk::TextView>,
+        /// The diagnostics of the sessions that were loaded.
+        sessions_diagnostics: RefCell<Vec<SessionDiagnostics>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DiagnosticsDialog {
+        const NAME: &'static str = "DiagnosticsDialog";
+        type Type = super::DiagnosticsDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DiagnosticsDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.version_row.set_subtitle(config::VERSION);
+            self.update_log_filter();
+
+            // Connect the handler after setting the initial value, to avoid changing the
+            // filter.
+            self.log_level_row.connect_selected_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |row| {
+                    let Some(level) = usize::try_from(row.selected())
+                        .ok()
+                        .and_then(|position| LogLevel::ALL.get(position))
+                    else {
+                        return;
+                    };
+
+                    logs::set_log_level(*level);
+                    imp.update_log_filter();
+                }
+            ));
+
+            // Bug reports can only be sent if a rageshake server is configured.
+            self.send_bug_report_button
+                .set_visible(!rageshake_url().is_empty());
+
+            self.load_sessions();
+            self.refresh_logs();
+        }
+    }
+
+    impl WidgetImpl for DiagnosticsDialog {}
+    impl AdwDialogImpl for DiagnosticsDialog {}
+
+    #[gtk::template_callbacks]
+    impl DiagnosticsDialog {
+        /// Update the row presenting the current log filter.
+        fn update_log_filter(&self) {
+            self.log_filter_row.set_subtitle(&logs::current_filter());
+        }
+
+        /// Add a row for each session.
+        fn load_sessions(&self) {
+            let sessions = Application::default()
+                .session_list()
+                .iter::<glib::Object>()
+                .filter_map(|item| item.ok().and_downcast::<Session>());
+
+            for session in sessions {
+                let row = adw::ActionRow::builder()
+                    .title(session.user_id().as_str())
+                    .subtitle(session.homeserver().as_str())
+                    .css_classes(["property"])
+                    .build();
+                self.sessions_group.add(&row);
+
+                spawn!(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    #[weak]
+                    row,
+                    async move {
+                        let diagnostics = SessionDiagnostics::load(&session).await;
+                        row.set_subtitle(&diagnostics.to_localized_string());
+                        imp.sessions_diagnostics.borrow_mut().push(diagnostics);
+                    }
+                ));
+            }
+        }
+
+        /// Load the recent logs in the view.
+        #[template_callback]
+        fn refresh_logs(&self) {
+            self.logs_view.buffer().set_text(&logs::recent_logs());
+
+            // Scroll to the latest logs.
+            let buffer = self.logs_view.buffer();
+            let mark = buffer.create_mark(None, &buffer.end_iter(), false);
+            self.logs_view.scroll_mark_onscreen(&mark);
+            buffer.delete_mark(&mark);
+        }
+
+        /// Generate a bug report with the current diagnostics.
+        fn bug_report(&self) -> BugReport {
+            BugReport::generate(
+                &self.bug_report_description_row.text(),
+                &self.sessions_diagnostics.borrow(),
+            )
+        }
+
+        /// Copy the diagnostics and the recent logs to the clipboard.
+        #[template_callback]
+        fn copy_logs(&self) {
+            let report = BugReport::generate("", &self.sessions_diagnostics.borrow());
+
+            self.obj().clipboard().set_text(report.content());
+            toast!(self.obj(), gettext("Logs copied to clipboard"));
+        }
+
+        /// Save a bug report to a file.
+        #[template_callback]
+        async fn save_bug_report(&self) {
+            let obj = self.obj();
+            let report = self.bug_report();
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Save Bug Report"))
+                .modal(true)
+                .accept_label(gettext("Save"))
+                .initial_name(BugReport::file_name())
+                .build();
+
+            let file = match dialog
+                .save_future(obj.root().and_downcast_ref::<gtk::Window>())
+                .await
+            {
+                Ok(file) => file,
+                Err(error) => {
+                    if error.matches(gtk::DialogError::Dismissed) {
+                        debug!("File dialog dismissed by user");
+                    } else {
+                        error!("Could not access file: {error}");
+                        toast!(obj, gettext("Could not access file"));
+                    }
+                    return;
+                }
+            };
+
+            self.save_bug_report_button.set_is_loading(true);
+
+            let result = file
+                .replace_contents_future(
+                    report.content().as_bytes().to_owned(),
+                    None,
+                    false,
+                    gio::FileCreateFlags::REPLACE_DESTINATION,
+                )
+                .await;
+
+            if let Err((_, error)) = result {
+                error!("Could not write bug report: {error}");
+                toast!(obj, gettext("Could not save bug report"));
+            } else {
+                toast!(obj, gettext("Bug report saved"));
+            }
+
+            self.save_bug_report_button.set_is_loading(false);
+        }
+
+        /// Send a bug report to the configured rageshake server.
+        #[template_callback]
+        async fn send_bug_report(&self) {
+            let url = rageshake_url();
+            if url.is_empty() {
+                return;
+            }
+
+            self.send_bug_report_button.set_is_loading(true);
+
+            let report = self.bug_report();
+            let obj = self.obj();
+
+            let http_client = match http_client() {
+                Ok(http_client) => http_client,
+                Err(error) => {
+                    error!("Could not build HTTP client: {error}");
+                    toast!(obj, gettext("Could not send bug report"));
+                    self.send_bug_report_button.set_is_loading(false);
+                    return;
+                }
+            };
+
+            if let Err(error) = report.upload(http_client, url).await {
+                error!("Could not send bug report: {error}");
+                toast!(obj, gettext("Could not send bug report"));
+            } else {
+                self.bug_report_description_row.set_text("");
+                toast!(obj, gettext("Bug report sent"));
+            }
+
+            self.send_bug_report_button.set_is_loading(false);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A dialog presenting information to debug issues, including the recent
+    /// logs.
+    pub struct DiagnosticsDialog(ObjectSubclass<imp::DiagnosticsDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl DiagnosticsDialog {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+}
+
+/// The URL of the rageshake server where bug reports can be sent.
+///
+/// Returns an empty string if no server is configured.
+fn rageshake_url() -> String {
+    Application::default()
+        .settings()
+        .string("rageshake-url")
+        .trim()
+        .to_owned()
+}
+
+/// The HTTP client to use to send bug reports.
+///
+/// Uses the client of the first session, if any, so the report goes through
+/// the same proxy as the requests to the homeserver.
+fn http_client() -> Result<reqwest::Client, reqwest::Error> {
+    let session = Application::default()
+        .session_list()
+        .iter::<glib::Object>()
+        .find_map(|item| item.ok().and_downcast::<Session>());
+
+    match session {
+        Some(session) => Ok(session.http_client()),
+        None => matrix::http_client(None),
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 
 use gtk::{glib, prelude::*};
+use ruma::{OwnedRoomId, RoomId};
 
 use crate::{session::VerificationKey, utils::matrix::MatrixIdUri};
 
@@ -13,6 +14,14 @@ pub(crate) enum SessionIntent {
     ShowMatrixId(MatrixIdUri),
     /// Show an ongoing identity verification.
     ShowIdentityVerification(VerificationKey),
+    /// Mark the room with the given ID as read.
+    ///
+    /// This is handled in the background, without presenting the window.
+    MarkRoomAsRead(OwnedRoomId),
+    /// Send a quick reply in the room with the given ID.
+    ///
+    /// This is handled without opening the room in the window.
+    ReplyInRoom(OwnedRoomId),
 }
 
 impl SessionIntent {
@@ -37,6 +46,24 @@ impl SessionIntent {
             .split_at(4)
             .1;
 
+    /// The application action name for the [`SessionIntent::MarkRoomAsRead`]
+    /// variant.
+    pub(crate) const MARK_ROOM_AS_READ_APP_ACTION_NAME: &str = "app.mark-room-as-read";
+
+    /// The action name without the `app.` prefix for the
+    /// [`SessionIntent::MarkRoomAsRead`] variant.
+    pub(crate) const MARK_ROOM_AS_READ_ACTION_NAME: &str =
+        Self::MARK_ROOM_AS_READ_APP_ACTION_NAME.split_at(4).1;
+
+    /// The application action name for the [`SessionIntent::ReplyInRoom`]
+    /// variant.
+    pub(crate) const REPLY_IN_ROOM_APP_ACTION_NAME: &str = "app.reply-in-room";
+
+    /// The action name without the `app.` prefix for the
+    /// [`SessionIntent::ReplyInRoom`] variant.
+    pub(crate) const REPLY_IN_ROOM_ACTION_NAME: &str =
+        Self::REPLY_IN_ROOM_APP_ACTION_NAME.split_at(4).1;
+
     /// Get the application action name for this session intent type.
     pub(crate) fn app_action_name(&self) -> &'static str {
         match self {
@@ -44,9 +71,17 @@ impl SessionIntent {
             SessionIntent::ShowIdentityVerification(_) => {
                 Self::SHOW_IDENTITY_VERIFICATION_APP_ACTION_NAME
             }
+            SessionIntent::MarkRoomAsRead(_) => Self::MARK_ROOM_AS_READ_APP_ACTION_NAME,
+            SessionIntent::ReplyInRoom(_) => Self::REPLY_IN_ROOM_APP_ACTION_NAME,
         }
     }
 
+    /// Whether this intent is an action on a notification that does not need
+    /// to show the main window.
+    pub(crate) fn is_notification_action(&self) -> bool {
+        matches!(self, Self::MarkRoomAsRead(_) | Self::ReplyInRoom(_))
+    }
+
     /// Convert the given `GVariant` to a [`SessionIntent::ShowMatrixId`] and
     /// session ID, given the intent type.
     ///
@@ -78,11 +113,36 @@ impl SessionIntent {
         Some((session_id, Self::ShowIdentityVerification(payload.get()?)))
     }
 
+    /// Convert the given `GVariant` to a [`SessionIntent::MarkRoomAsRead`] and
+    /// session ID, given the intent type.
+    ///
+    /// Returns a  `(session_id, intent)` tuple on success. Returns `None` if
+    /// the `GVariant` could not be parsed successfully.
+    pub(crate) fn mark_room_as_read_from_variant(
+        variant: &glib::Variant,
+    ) -> Option<(String, Self)> {
+        let (session_id, room_id) = room_id_from_variant(variant)?;
+        Some((session_id, Self::MarkRoomAsRead(room_id)))
+    }
+
+    /// Convert the given `GVariant` to a [`SessionIntent::ReplyInRoom`] and
+    /// session ID, given the intent type.
+    ///
+    /// Returns a  `(session_id, intent)` tuple on success. Returns `None` if
+    /// the `GVariant` could not be parsed successfully.
+    pub(crate) fn reply_in_room_from_variant(variant: &glib::Variant) -> Option<(String, Self)> {
+        let (session_id, room_id) = room_id_from_variant(variant)?;
+        Some((session_id, Self::ReplyInRoom(room_id)))
+    }
+
     /// Convert this intent to a `GVariant` with the given session ID.
     pub(crate) fn to_variant_with_session_id(&self, session_id: String) -> glib::Variant {
         let payload = match self {
             Self::ShowMatrixId(uri) => uri.to_variant(),
             Self::ShowIdentityVerification(key) => key.to_variant(),
+            Self::MarkRoomAsRead(room_id) | Self::ReplyInRoom(room_id) => {
+                room_id.as_str().to_variant()
+            }
         };
 
         SessionIntentActionParameter {
@@ -111,6 +171,17 @@ impl From<VerificationKey> for SessionIntent {
     }
 }
 
+/// Parse the given `GVariant` to a session ID and a room ID.
+fn room_id_from_variant(variant: &glib::Variant) -> Option<(String, OwnedRoomId)> {
+    let SessionIntentActionParameter {
+        session_id,
+        payload,
+    } = variant.get()?;
+    let room_id = RoomId::parse(payload.get::<String>()?).ok()?;
+
+    Some((session_id, room_id))
+}
+
 /// The payload of a [`SessionIntent`], when converted to a `GVariant` for an
 /// app action.
 #[derive(Debug, Clone, glib::Variant)]
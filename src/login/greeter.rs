@@ -1,7 +1,10 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
+use gtk::{glib, glib::clone};
 
-use crate::components::OfflineBanner;
+use crate::{
+    components::{Camera, CameraExt, OfflineBanner},
+    spawn,
+};
 
 mod imp {
     use glib::subclass::InitializingObject;
@@ -10,7 +13,10 @@ mod imp {
 
     #[derive(Debug, Default, gtk::CompositeTemplate)]
     #[template(resource = "/org/gnome/Fractal/ui/login/greeter.ui")]
-    pub struct Greeter {}
+    pub struct Greeter {
+        #[template_child]
+        qr_code_button: TemplateChild<gtk::Button>,
+    }
 
     #[glib::object_subclass]
     impl ObjectSubclass for Greeter {
@@ -31,7 +37,21 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for Greeter {}
+    impl ObjectImpl for Greeter {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // Logging in with a QR code is only possible if we can scan it.
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    let has_cameras = Camera::has_cameras().await;
+                    imp.qr_code_button.set_visible(has_cameras);
+                }
+            ));
+        }
+    }
     impl WidgetImpl for Greeter {}
 
     impl NavigationPageImpl for Greeter {
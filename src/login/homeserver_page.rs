@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{glib, glib::clone};
 use matrix_sdk::{
     Client, ClientBuildError, ClientBuilder, config::RequestConfig, sanitize_server_name,
 };
-use tracing::warn;
+use ruma::api::client::session::get_login_types::v3::LoginType;
+use tokio::task::AbortHandle;
+use tracing::{debug, warn};
 use url::Url;
 
 use super::Login;
@@ -12,11 +16,16 @@ use crate::{
     components::{LoadingButton, OfflineBanner},
     gettext_f,
     prelude::*,
-    spawn_tokio, toast,
+    spawn, spawn_tokio, toast,
     utils::BoundObjectWeakRef,
 };
 
+/// The time to wait after the homeserver was changed before checking it.
+const CHECK_DELAY: Duration = Duration::from_millis(500);
+
 mod imp {
+    use std::cell::RefCell;
+
     use glib::subclass::InitializingObject;
 
     use super::*;
@@ -30,10 +39,26 @@ mod imp {
         #[template_child]
         homeserver_help: TemplateChild<gtk::Label>,
         #[template_child]
+        check_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        check_spinner: TemplateChild<adw::Spinner>,
+        #[template_child]
+        check_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        check_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        check_details_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        manual_url_button: TemplateChild<gtk::Button>,
+        #[template_child]
         next_button: TemplateChild<LoadingButton>,
         /// The parent `Login` object.
         #[property(get, set = Self::set_login, explicit_notify, nullable)]
         login: BoundObjectWeakRef<Login>,
+        /// The source of the timeout to check the homeserver.
+        check_timeout: RefCell<Option<glib::SourceId>>,
+        /// The abort handle for the ongoing homeserver check.
+        check_abort_handle: RefCell<Option<AbortHandle>>,
     }
 
     #[glib::object_subclass]
@@ -82,6 +107,7 @@ mod imp {
                     move |_| {
                         imp.update_next_state();
                         imp.update_text();
+                        imp.schedule_check();
                     }
                 ));
 
@@ -122,6 +148,7 @@ mod imp {
             self.homeserver_entry.set_text("");
             self.next_button.set_is_loading(false);
             self.update_next_state();
+            self.cancel_check();
         }
 
         /// The current text from the homeserver entry.
@@ -144,11 +171,151 @@ mod imp {
         }
 
         /// Update the state of the "Next" button.
-        #[template_callback]
         fn update_next_state(&self) {
             self.next_button.set_sensitive(self.can_go_next());
         }
 
+        /// Handle when the homeserver in the entry changed.
+        #[template_callback]
+        fn homeserver_changed(&self) {
+            self.update_next_state();
+            self.schedule_check();
+        }
+
+        /// Schedule a check of the current homeserver.
+        ///
+        /// The check is delayed to avoid making requests while the user is
+        /// typing.
+        fn schedule_check(&self) {
+            self.cancel_check();
+
+            if !self.can_go_next() {
+                return;
+            }
+
+            self.check_timeout
+                .replace(Some(glib::timeout_add_local_once(
+                    CHECK_DELAY,
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move || {
+                            imp.check_timeout.take();
+
+                            spawn!(async move {
+                                imp.check_connectivity().await;
+                            });
+                        }
+                    ),
+                )));
+        }
+
+        /// Cancel the ongoing check of the homeserver, and hide its result.
+        fn cancel_check(&self) {
+            if let Some(source) = self.check_timeout.take() {
+                source.remove();
+            }
+            if let Some(handle) = self.check_abort_handle.take() {
+                handle.abort();
+            }
+
+            self.check_box.set_visible(false);
+        }
+
+        /// Check that the current homeserver can be reached and is supported.
+        async fn check_connectivity(&self) {
+            let Some(login) = self.login.obj() else {
+                return;
+            };
+
+            let autodiscovery = login.autodiscovery();
+            let homeserver = self.homeserver();
+
+            self.check_spinner.set_visible(true);
+            self.check_icon.set_visible(false);
+            self.check_label
+                .set_label(&gettext("Checking the homeserver…"));
+            self.check_details_label.set_visible(false);
+            self.manual_url_button.set_visible(false);
+            self.check_box.set_visible(true);
+
+            let handle =
+                spawn_tokio!(async move { HomeserverCheck::run(homeserver, autodiscovery).await });
+            self.check_abort_handle.replace(Some(handle.abort_handle()));
+
+            let Ok(result) = handle.await else {
+                // The task was aborted.
+                return;
+            };
+
+            self.check_abort_handle.take();
+            self.check_spinner.set_visible(false);
+            self.check_icon.set_visible(true);
+
+            match result {
+                Ok(check) => {
+                    self.check_icon.set_icon_name(Some("checkmark-symbolic"));
+                    self.check_icon.remove_css_class("error");
+                    self.check_icon.add_css_class("success");
+
+                    if autodiscovery {
+                        self.check_label.set_label(&gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "Found homeserver at {url}",
+                            &[("url", check.homeserver.as_str())],
+                        ));
+                    } else {
+                        self.check_label
+                            .set_label(&gettext("The homeserver is reachable"));
+                    }
+
+                    if let Some(login_methods) = check.login_methods_string() {
+                        self.check_details_label.set_label(&gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "Available login methods: {methods}",
+                            &[("methods", &login_methods)],
+                        ));
+                        self.check_details_label.set_visible(true);
+                    }
+                }
+                Err(error) => {
+                    self.check_icon.set_icon_name(Some("error-symbolic"));
+                    self.check_icon.remove_css_class("success");
+                    self.check_icon.add_css_class("error");
+
+                    let label = match error {
+                        HomeserverCheckError::Discovery if autodiscovery => {
+                            // Allow the user to enter the URL manually.
+                            self.manual_url_button.set_visible(true);
+                            gettext("Could not find a homeserver for this domain")
+                        }
+                        HomeserverCheckError::Discovery | HomeserverCheckError::Unreachable => {
+                            gettext("Could not connect to the homeserver")
+                        }
+                        HomeserverCheckError::UnsupportedVersion => gettext(
+                            "This homeserver does not support a compatible version of Matrix",
+                        ),
+                    };
+                    self.check_label.set_label(&label);
+                }
+            }
+        }
+
+        /// Switch to entering the URL of the homeserver manually.
+        #[template_callback]
+        fn enter_url_manually(&self) {
+            let Some(login) = self.login.obj() else {
+                return;
+            };
+
+            login.set_autodiscovery(false);
+            self.homeserver_entry.set_text("https://");
+            self.homeserver_entry.grab_focus();
+            self.homeserver_entry.set_position(-1);
+        }
+
         /// Check if the homeserver that was entered is valid.
         #[template_callback]
         async fn check_homeserver(&self) {
@@ -258,7 +425,7 @@ mod imp {
         }
 
         /// Construct a [`ClientBuilder`] with the proper configuration.
-        fn client_builder() -> ClientBuilder {
+        pub(super) fn client_builder() -> ClientBuilder {
             Client::builder().request_config(RequestConfig::new().retry_limit(2))
         }
 
@@ -304,3 +471,102 @@ impl LoginHomeserverPage {
         self.imp().build_client(autodiscovery).await
     }
 }
+
+/// The result of a successful homeserver check.
+#[derive(Debug, Clone)]
+struct HomeserverCheck {
+    /// The URL of the homeserver.
+    homeserver: Url,
+    /// Whether the homeserver supports the OAuth 2.0 API.
+    supports_oauth: bool,
+    /// The login types supported by the Matrix native API.
+    login_types: Vec<LoginType>,
+}
+
+impl HomeserverCheck {
+    /// Check the given homeserver.
+    ///
+    /// If `autodiscovery` is `true`, `homeserver` is the server name to use
+    /// for discovery, otherwise it is the URL of the homeserver.
+    async fn run(
+        homeserver: glib::GString,
+        autodiscovery: bool,
+    ) -> Result<Self, HomeserverCheckError> {
+        let builder = imp::LoginHomeserverPage::client_builder();
+        let builder = if autodiscovery {
+            builder.server_name_or_homeserver_url(homeserver)
+        } else {
+            builder
+                .respect_login_well_known(false)
+                .homeserver_url(homeserver)
+        };
+
+        let client = builder.build().await.map_err(|error| {
+            debug!("Could not build client during homeserver check: {error}");
+            HomeserverCheckError::Discovery
+        })?;
+
+        let versions = client.server_versions().await.map_err(|error| {
+            debug!("Could not get versions during homeserver check: {error}");
+            HomeserverCheckError::Unreachable
+        })?;
+        if versions.is_empty() {
+            return Err(HomeserverCheckError::UnsupportedVersion);
+        }
+
+        let supports_oauth = client.oauth().server_metadata().await.is_ok();
+        let login_types = if supports_oauth {
+            Vec::new()
+        } else {
+            client
+                .matrix_auth()
+                .get_login_types()
+                .await
+                .map(|response| response.flows)
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            homeserver: client.homeserver(),
+            supports_oauth,
+            login_types,
+        })
+    }
+
+    /// The human-readable list of login methods supported by the homeserver.
+    fn login_methods_string(&self) -> Option<String> {
+        if self.supports_oauth {
+            return Some(gettext("Authentication in the browser"));
+        }
+
+        let mut methods = Vec::new();
+
+        if self
+            .login_types
+            .iter()
+            .any(|login_type| matches!(login_type, LoginType::Password(_)))
+        {
+            methods.push(gettext("Password"));
+        }
+        if self
+            .login_types
+            .iter()
+            .any(|login_type| matches!(login_type, LoginType::Sso(_)))
+        {
+            methods.push(gettext("Single Sign-On"));
+        }
+
+        (!methods.is_empty()).then(|| methods.join(", "))
+    }
+}
+
+/// The possible errors when checking a homeserver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HomeserverCheckError {
+    /// The homeserver could not be discovered or the URL is invalid.
+    Discovery,
+    /// The homeserver could not be reached.
+    Unreachable,
+    /// The homeserver does not support any compatible Matrix version.
+    UnsupportedVersion,
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::glib;
@@ -6,12 +8,15 @@ use matrix_sdk::{
     authentication::oauth::{OAuthAuthorizationData, UrlOrQuery},
     utils::local_server::{LocalServerRedirectHandle, QueryString},
 };
-use tokio::task::AbortHandle;
+use tokio::{task::AbortHandle, time::timeout};
 use tracing::{error, warn};
 use url::Url;
 
 use super::Login;
-use crate::{APP_NAME, prelude::*, spawn_tokio, toast};
+use crate::{APP_NAME, gettext_f, prelude::*, spawn_tokio, toast};
+
+/// The time to wait for the browser to redirect to the local server.
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 
 mod imp {
     use std::cell::RefCell;
@@ -24,6 +29,8 @@ mod imp {
     #[template(resource = "/org/gnome/Fractal/ui/login/in_browser_page.ui")]
     #[properties(wrapper_type = super::LoginInBrowserPage)]
     pub struct LoginInBrowserPage {
+        #[template_child]
+        instructions: TemplateChild<gtk::Label>,
         #[template_child]
         continue_btn: TemplateChild<gtk::Button>,
         /// The ancestor `Login` object.
@@ -81,6 +88,19 @@ mod imp {
             data: LoginInBrowserData,
         ) {
             self.clean();
+
+            let instructions = if let Some(provider) = data.url().host_str() {
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "Click on the button below and follow the steps on {provider} in the browser",
+                    &[("provider", provider)],
+                )
+            } else {
+                gettext("Click on the button below and follow the steps in the browser")
+            };
+            self.instructions.set_label(&instructions);
+
             self.local_server_handle.replace(Some(local_server_handle));
             self.data.replace(Some(data));
         }
@@ -106,7 +126,8 @@ mod imp {
                 return;
             };
 
-            let handle = spawn_tokio!(async move { local_server_handle.await });
+            let handle =
+                spawn_tokio!(async move { timeout(REDIRECT_TIMEOUT, local_server_handle).await });
 
             self.abort_handle.replace(Some(handle.abort_handle()));
 
@@ -122,6 +143,14 @@ mod imp {
                 window.present();
             }
 
+            let Ok(result) = result else {
+                warn!("Could not log in: the redirect was not received in time");
+                self.abort_on_error(&gettext(
+                    "The authentication was not completed in time, try again",
+                ));
+                return;
+            };
+
             let Some(query_string) = result else {
                 warn!("Could not log in: missing query string in redirect URI");
                 self.abort_on_error(&gettext("An unexpected error occurred."));
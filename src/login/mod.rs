@@ -21,6 +21,7 @@ mod greeter;
 mod homeserver_page;
 mod in_browser_page;
 mod method_page;
+mod qr_code_page;
 mod session_setup_view;
 mod sso_idp_button;
 
@@ -30,6 +31,7 @@ use self::{
     homeserver_page::LoginHomeserverPage,
     in_browser_page::{LoginInBrowserData, LoginInBrowserPage},
     method_page::LoginMethodPage,
+    qr_code_page::LoginQrCodePage,
     session_setup_view::SessionSetupView,
 };
 use crate::{
@@ -50,6 +52,8 @@ enum LoginPage {
     Method,
     /// The page to log in with the browser.
     InBrowser,
+    /// The page to log in by scanning a QR code.
+    QrCode,
     /// The session setup stack.
     SessionSetup,
     /// The login is completed.
@@ -78,6 +82,8 @@ mod imp {
         #[template_child]
         in_browser_page: TemplateChild<LoginInBrowserPage>,
         #[template_child]
+        qr_code_page: TemplateChild<LoginQrCodePage>,
+        #[template_child]
         done_button: TemplateChild<gtk::Button>,
         /// Whether auto-discovery is enabled.
         #[property(get, set = Self::set_autodiscovery, construct, explicit_notify, default = true)]
@@ -160,6 +166,7 @@ mod imp {
                 LoginPage::Homeserver => self.homeserver_page.grab_focus(),
                 LoginPage::Method => self.method_page.grab_focus(),
                 LoginPage::InBrowser => self.in_browser_page.grab_focus(),
+                LoginPage::QrCode => self.qr_code_page.grab_focus(),
                 LoginPage::SessionSetup => {
                     if let Some(session_setup) = self.session_setup() {
                         session_setup.grab_focus()
@@ -565,9 +572,13 @@ fn client_registration_data() -> ClientRegistrationData {
 
     let mut client_metadata = ClientMetadata::new(
         ApplicationType::Native,
-        vec![OAuthGrantType::AuthorizationCode {
-            redirect_uris: vec![ipv4_localhost_uri, ipv6_localhost_uri],
-        }],
+        vec![
+            OAuthGrantType::AuthorizationCode {
+                redirect_uris: vec![ipv4_localhost_uri, ipv6_localhost_uri],
+            },
+            // Used to log in with a QR code.
+            OAuthGrantType::DeviceCode,
+        ],
         Localized::new(client_uri, None),
     );
     client_metadata.client_name = Some(Localized::new(APP_NAME.to_owned(), None));
@@ -0,0 +1,299 @@
+use adw::{prelude::*, subclass::prelude::*};
+use futures_util::StreamExt;
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+use matrix_sdk::{
+    Client,
+    authentication::oauth::qrcode::{LoginProgress, QrCodeData, QrCodeModeData},
+    config::RequestConfig,
+};
+use tokio::task::AbortHandle;
+use tracing::warn;
+
+use super::{Login, client_registration_data};
+use crate::{components::QrCodeScanner, prelude::*, spawn, spawn_tokio, toast};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/login/qr_code_page.ui")]
+    #[properties(wrapper_type = super::LoginQrCodePage)]
+    pub struct LoginQrCodePage {
+        #[template_child]
+        instructions: TemplateChild<gtk::Label>,
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        qrcode_scanner_bin: TemplateChild<adw::Bin>,
+        #[template_child]
+        check_code_label: TemplateChild<gtk::Label>,
+        /// The ancestor `Login` object.
+        #[property(get, set, nullable)]
+        login: glib::WeakRef<Login>,
+        /// The QR code scanner.
+        qrcode_scanner: RefCell<Option<QrCodeScanner>>,
+        /// The abort handle for the ongoing login.
+        abort_handle: RefCell<Option<AbortHandle>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LoginQrCodePage {
+        const NAME: &'static str = "LoginQrCodePage";
+        type Type = super::LoginQrCodePage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for LoginQrCodePage {}
+
+    impl WidgetImpl for LoginQrCodePage {}
+
+    impl NavigationPageImpl for LoginQrCodePage {
+        fn shown(&self) {
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.init_qrcode_scanner().await;
+                }
+            ));
+        }
+
+        fn hidden(&self) {
+            self.clean();
+        }
+    }
+
+    impl LoginQrCodePage {
+        /// Initialize the QR code scanner.
+        async fn init_qrcode_scanner(&self) {
+            self.show_scan_step();
+
+            if self.qrcode_scanner.borrow().is_some() {
+                return;
+            }
+
+            let Some(qrcode_scanner) = QrCodeScanner::new().await else {
+                self.abort_on_error(&gettext("Could not access the camera"));
+                return;
+            };
+
+            qrcode_scanner.connect_login_qrcode_detected(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, data| {
+                    spawn!(async move {
+                        imp.login_with_qr_code(data).await;
+                    });
+                }
+            ));
+
+            self.qrcode_scanner_bin.set_child(Some(&qrcode_scanner));
+            self.qrcode_scanner.replace(Some(qrcode_scanner));
+        }
+
+        /// Show the step to scan the QR code.
+        fn show_scan_step(&self) {
+            self.instructions.set_label(&gettext(
+                "Open the security settings on a device where you are already logged in, choose to link a new device and scan the QR code that is shown",
+            ));
+            self.stack.set_visible_child_name("scan");
+        }
+
+        /// Log in with the data of the given QR code.
+        async fn login_with_qr_code(&self, data: QrCodeData) {
+            let Some(login) = self.login.upgrade() else {
+                return;
+            };
+
+            if self.abort_handle.borrow().is_some() {
+                // We are already logging in.
+                return;
+            }
+
+            let QrCodeModeData::Reciprocate { server_name } = &data.mode_data else {
+                toast!(
+                    self.obj(),
+                    gettext(
+                        "This QR code must be scanned by a device where you are already logged in"
+                    )
+                );
+                return;
+            };
+
+            self.instructions
+                .set_label(&gettext("Connecting to your other device…"));
+            self.stack.set_visible_child_name("loading");
+
+            let server_name = server_name.clone();
+            let handle = spawn_tokio!(async move {
+                Client::builder()
+                    .request_config(RequestConfig::new().retry_limit(2))
+                    .server_name_or_homeserver_url(server_name)
+                    .build()
+                    .await
+            });
+            self.abort_handle.replace(Some(handle.abort_handle()));
+
+            let Ok(result) = handle.await else {
+                // The task was aborted.
+                return;
+            };
+
+            let client = match result {
+                Ok(client) => client,
+                Err(error) => {
+                    warn!("Could not discover homeserver from QR code: {error}");
+                    self.abort_handle.take();
+                    self.abort_on_error(&error.to_user_facing());
+                    return;
+                }
+            };
+
+            // Logging in with a QR code is only possible with the OAuth 2.0 API.
+            let oauth = client.oauth();
+            let handle = spawn_tokio!(async move { oauth.server_metadata().await });
+            self.abort_handle.replace(Some(handle.abort_handle()));
+
+            let Ok(result) = handle.await else {
+                // The task was aborted.
+                return;
+            };
+
+            if let Err(error) = result {
+                self.abort_handle.take();
+
+                if error.is_not_supported() {
+                    self.abort_on_error(&gettext(
+                        "Your homeserver does not support logging in with a QR code",
+                    ));
+                } else {
+                    warn!("Could not get authorization server metadata: {error}");
+                    self.abort_on_error(&gettext("Could not set up login"));
+                }
+                return;
+            }
+
+            login.set_client(Some(client.clone()));
+
+            let obj_weak = glib::SendWeakRef::from(self.obj().downgrade());
+            let handle = spawn_tokio!(async move {
+                let oauth = client.oauth();
+                let registration_data = client_registration_data();
+                let login_with_qr_code = oauth.login_with_qr_code(&data, Some(&registration_data));
+
+                // Watch the progress of the login.
+                let progress_stream = login_with_qr_code.subscribe_to_progress();
+                let fut = progress_stream.for_each(move |progress| {
+                    let obj_weak = obj_weak.clone();
+
+                    async move {
+                        let ctx = glib::MainContext::default();
+                        ctx.spawn(async move {
+                            spawn!(async move {
+                                if let Some(obj) = obj_weak.upgrade() {
+                                    obj.imp().update_progress(progress);
+                                }
+                            });
+                        });
+                    }
+                });
+                let progress_abort_handle = tokio::spawn(fut).abort_handle();
+
+                let result = login_with_qr_code.await;
+                progress_abort_handle.abort();
+
+                result
+            });
+            self.abort_handle.replace(Some(handle.abort_handle()));
+
+            let Ok(result) = handle.await else {
+                // The task was aborted.
+                return;
+            };
+
+            self.abort_handle.take();
+
+            match result {
+                Ok(()) => {
+                    login.create_session().await;
+                }
+                Err(error) => {
+                    warn!("Could not log in with QR code: {error}");
+                    self.abort_on_error(&gettext("Could not log in with the QR code"));
+                }
+            }
+        }
+
+        /// Update the UI for the given login progress.
+        fn update_progress(&self, progress: LoginProgress) {
+            match progress {
+                LoginProgress::EstablishingSecureChannel { check_code } => {
+                    self.instructions.set_label(&gettext(
+                        "Enter the following code on your other device to confirm that the connection is secure",
+                    ));
+                    self.check_code_label
+                        .set_label(&format!("{:02}", check_code.to_digit()));
+                    self.stack.set_visible_child_name("check-code");
+                }
+                LoginProgress::WaitingForToken { .. } => {
+                    self.instructions
+                        .set_label(&gettext("Follow the instructions on your other device"));
+                    self.stack.set_visible_child_name("loading");
+                }
+                LoginProgress::Starting | LoginProgress::Done => {}
+            }
+        }
+
+        /// Show the given error and go back to the previous screen.
+        fn abort_on_error(&self, error: &str) {
+            let obj = self.obj();
+            toast!(obj, error);
+
+            // Drop the client because it is bound to the homeserver.
+            if let Some(login) = self.login.upgrade() {
+                login.drop_client();
+            }
+
+            let _ = obj.activate_action("navigation.pop", None);
+        }
+
+        /// Reset this page.
+        fn clean(&self) {
+            if let Some(handle) = self.abort_handle.take() {
+                handle.abort();
+            }
+
+            // Drop the scanner to stop using the camera.
+            self.qrcode_scanner_bin.set_child(None::<&gtk::Widget>);
+            self.qrcode_scanner.take();
+            self.check_code_label.set_label("");
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A page to log in by scanning a QR code shown on another device.
+    pub struct LoginQrCodePage(ObjectSubclass<imp::LoginQrCodePage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl LoginQrCodePage {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+}
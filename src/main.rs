@@ -11,12 +11,14 @@ mod components;
 mod config;
 mod account_settings;
 mod contrib;
+mod diagnostics_dialog;
 mod error_page;
 mod i18n;
 mod identity_verification_view;
 mod intent;
 mod login;
 mod prelude;
+mod room_switcher_dialog;
 mod secret;
 mod session;
 mod session_list;
@@ -30,7 +32,6 @@ use std::sync::LazyLock;
 
 use gettextrs::*;
 use gtk::{IconTheme, gdk::Display, gio};
-use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
 use self::{application::*, config::*, i18n::*, utils::OneshotNotifier, window::Window};
 
@@ -48,12 +49,7 @@ fn main() {
     // Initialize logger, debug is carried out via debug!, info!, warn! and error!.
     // Default to the INFO level for this crate and WARN for everything else.
     // It can be overridden with the RUST_LOG environment variable.
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("fractal=info,warn"));
-
-    tracing_subscriber::registry()
-        .with(fmt::layer().with_filter(env_filter))
-        .init();
+    utils::logs::init();
 
     // Prepare i18n
     setlocale(LocaleCategory::LcAll, "");
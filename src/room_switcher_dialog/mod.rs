@@ -0,0 +1,318 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, gio, glib};
+use secular::normalized_lower_lay_string;
+
+use crate::{
+    Application, Window,
+    components::PillSourceRow,
+    prelude::*,
+    session::{Room, RoomCategory, Session, SessionState},
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, gtk::CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/room_switcher_dialog/mod.ui")]
+    pub struct RoomSwitcherDialog {
+        #[template_child]
+        search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        list_box: TemplateChild<gtk::ListBox>,
+        /// The rooms of all the sessions that can be switched to.
+        rooms: RefCell<Vec<Room>>,
+        /// The rooms matching the current search term, in the order they are
+        /// presented.
+        results: gio::ListStore,
+    }
+
+    impl Default for RoomSwitcherDialog {
+        fn default() -> Self {
+            Self {
+                search_entry: Default::default(),
+                stack: Default::default(),
+                list_box: Default::default(),
+                rooms: Default::default(),
+                results: gio::ListStore::new::<Room>(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RoomSwitcherDialog {
+        const NAME: &'static str = "RoomSwitcherDialog";
+        type Type = super::RoomSwitcherDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            PillSourceRow::ensure_type();
+
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RoomSwitcherDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.list_box.bind_model(Some(&self.results), |item| {
+                let row = PillSourceRow::new();
+                row.set_source(item.downcast_ref());
+                row.upcast()
+            });
+
+            self.load_rooms();
+            self.update_results();
+        }
+    }
+
+    impl WidgetImpl for RoomSwitcherDialog {}
+    impl AdwDialogImpl for RoomSwitcherDialog {}
+
+    #[gtk::template_callbacks]
+    impl RoomSwitcherDialog {
+        /// Collect the rooms of all the sessions that are ready.
+        fn load_rooms(&self) {
+            let session_list = Application::default().session_list();
+
+            let rooms = session_list
+                .iter::<glib::Object>()
+                .filter_map(|item| item.ok().and_downcast::<Session>())
+                .filter(|session| session.state() == SessionState::Ready)
+                .flat_map(|session| session.room_list().snapshot())
+                .filter(|room| {
+                    matches!(
+                        room.category(),
+                        RoomCategory::Invited
+                            | RoomCategory::Favorite
+                            | RoomCategory::Normal
+                            | RoomCategory::LowPriority
+                    )
+                })
+                .collect();
+
+            self.rooms.replace(rooms);
+        }
+
+        /// Update the list of rooms matching the current search term.
+        fn update_results(&self) {
+            let term = normalized_lower_lay_string(self.search_entry.text().trim());
+
+            let mut results = self
+                .rooms
+                .borrow()
+                .iter()
+                .filter_map(|room| room_match_score(room, &term).map(|score| (score, room)))
+                .collect::<Vec<_>>();
+
+            // Best matches first, then most recent rooms first.
+            results.sort_by(|(score_a, room_a), (score_b, room_b)| {
+                score_a
+                    .cmp(score_b)
+                    .then_with(|| room_b.latest_activity().cmp(&room_a.latest_activity()))
+            });
+
+            let results = results
+                .into_iter()
+                .take(MAX_RESULTS)
+                .map(|(_, room)| room.clone())
+                .collect::<Vec<_>>();
+            self.results.splice(0, self.results.n_items(), &results);
+
+            let page = if results.is_empty() {
+                "empty"
+            } else {
+                "results"
+            };
+            self.stack.set_visible_child_name(page);
+
+            self.list_box
+                .select_row(self.list_box.row_at_index(0).as_ref());
+        }
+
+        /// Handle when the search term changed.
+        #[template_callback]
+        fn search_changed(&self) {
+            self.update_results();
+        }
+
+        /// Move the selection by the given offset, wrapping around at the ends
+        /// of the list.
+        fn move_selection(&self, offset: i32) {
+            let n_items = i32::try_from(self.results.n_items()).unwrap_or(i32::MAX);
+            if n_items == 0 {
+                return;
+            }
+
+            let current = self
+                .list_box
+                .selected_row()
+                .map_or(-offset.signum(), |row| row.index());
+            let index = (current + offset).rem_euclid(n_items);
+
+            if let Some(row) = self.list_box.row_at_index(index) {
+                self.list_box.select_row(Some(&row));
+                // Make sure the row is visible.
+                row.grab_focus();
+                self.search_entry.grab_focus_without_selecting();
+            }
+        }
+
+        /// Select the next row.
+        #[template_callback]
+        fn select_next_row(&self) {
+            self.move_selection(1);
+        }
+
+        /// Select the previous row.
+        #[template_callback]
+        fn select_previous_row(&self) {
+            self.move_selection(-1);
+        }
+
+        /// Handle a key press in the search entry.
+        ///
+        /// Allows to navigate the results with the arrow keys without leaving
+        /// the search entry.
+        #[template_callback]
+        fn search_entry_key_pressed(
+            &self,
+            key: gdk::Key,
+            _keycode: u32,
+            _modifier: gdk::ModifierType,
+        ) -> glib::Propagation {
+            match key {
+                gdk::Key::Down | gdk::Key::KP_Down => {
+                    self.select_next_row();
+                    glib::Propagation::Stop
+                }
+                gdk::Key::Up | gdk::Key::KP_Up => {
+                    self.select_previous_row();
+                    glib::Propagation::Stop
+                }
+                _ => glib::Propagation::Proceed,
+            }
+        }
+
+        /// Switch to the room of the selected row.
+        #[template_callback]
+        fn activate_selected_row(&self) {
+            if let Some(row) = self.list_box.selected_row() {
+                self.row_activated(&row);
+            }
+        }
+
+        /// Switch to the room of the given row.
+        #[template_callback]
+        fn row_activated(&self, row: &gtk::ListBoxRow) {
+            let Some(room) = row
+                .downcast_ref::<PillSourceRow>()
+                .and_then(|row| row.source())
+                .and_downcast::<Room>()
+            else {
+                return;
+            };
+
+            let obj = self.obj();
+            let window = obj.root().and_downcast::<Window>();
+            obj.close();
+
+            if let Some(window) = window {
+                window.select_room(room);
+            }
+        }
+
+        /// Close this dialog.
+        #[template_callback]
+        fn close_dialog(&self) {
+            self.obj().close();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A dialog to search and jump to a room in any of the logged-in sessions.
+    pub struct RoomSwitcherDialog(ObjectSubclass<imp::RoomSwitcherDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl RoomSwitcherDialog {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+}
+
+/// The maximum number of rooms presented in the results.
+const MAX_RESULTS: usize = 50;
+
+/// The score of the given room for the given normalized search term.
+///
+/// The room is matched against its display name, its main alias and its ID.
+/// The lower the score, the better the match.
+///
+/// Returns `None` if the room does not match.
+fn room_match_score(room: &Room, term: &str) -> Option<u32> {
+    if term.is_empty() {
+        return Some(0);
+    }
+
+    let display_name = normalized_lower_lay_string(&room.display_name());
+    let alias = room
+        .aliases()
+        .alias()
+        .map(|alias| normalized_lower_lay_string(alias.as_str()));
+    let room_id = room.room_id().as_str().to_lowercase();
+
+    [Some(display_name), alias, Some(room_id)]
+        .into_iter()
+        .flatten()
+        .filter_map(|candidate| fuzzy_match_score(term, &candidate))
+        .min()
+}
+
+/// The score of the given candidate for the given search term.
+///
+/// Substring matches are better than fuzzy matches, and matches closer to the
+/// start are better. A fuzzy match requires all the characters of the term to
+/// appear in the candidate in the same order, and matches with fewer gaps are
+/// better. The lower the score, the better the match.
+///
+/// Returns `None` if the candidate does not match.
+fn fuzzy_match_score(term: &str, candidate: &str) -> Option<u32> {
+    if let Some(position) = candidate.find(term) {
+        return Some(u32::try_from(position).unwrap_or(u32::MAX / 2).min(999));
+    }
+
+    let mut candidate_chars = candidate.chars();
+    let mut gaps = 0_u32;
+
+    for term_char in term.chars() {
+        let mut found = false;
+
+        for candidate_char in candidate_chars.by_ref() {
+            if candidate_char == term_char {
+                found = true;
+                break;
+            }
+            gaps = gaps.saturating_add(1);
+        }
+
+        if !found {
+            return None;
+        }
+    }
+
+    Some(gaps.saturating_add(1000))
+}
@@ -14,7 +14,7 @@ use tracing::{debug, error, info};
 use url::Url;
 
 use super::{SESSION_ID_LENGTH, SecretError, SecretExt, SessionTokens, StoredSession};
-use crate::{APP_ID, PROFILE, gettext_f, prelude::*, spawn_tokio, utils::matrix};
+use crate::{APP_ID, Application, PROFILE, gettext_f, prelude::*, spawn_tokio, utils::matrix};
 
 /// The current version of the stored session.
 const CURRENT_VERSION: u8 = 7;
@@ -192,9 +192,14 @@ async fn log_out_session(session: StoredSession, access_token: String) {
         access_token,
         refresh_token: None,
     };
+    // Use the same proxy as the session, so the request goes through it.
+    let proxy = Application::default()
+        .session_list()
+        .settings()
+        .proxy(&session.id);
 
     spawn_tokio!(async move {
-        match matrix::client_with_stored_session(session, tokens).await {
+        match matrix::client_with_stored_session(session, tokens, proxy).await {
             Ok(client) => {
                 if let Err(error) = client.logout().await {
                     error!("Could not log out session: {error}");
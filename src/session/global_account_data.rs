@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use futures_util::StreamExt;
 use gtk::{
     glib,
@@ -5,9 +7,16 @@ use gtk::{
     prelude::*,
     subclass::prelude::*,
 };
-use ruma::events::media_preview_config::{
-    InviteAvatars, MediaPreviewConfigEventContent, MediaPreviews,
+use matrix_sdk::event_handler::EventHandlerDropGuard;
+use ruma::{
+    MxcUri, OwnedMxcUri,
+    events::{
+        AnyGlobalAccountDataEvent, GlobalAccountDataEventType,
+        media_preview_config::{InviteAvatars, MediaPreviewConfigEventContent, MediaPreviews},
+    },
+    serde::Raw,
 };
+use serde::Deserialize;
 use tokio::task::AbortHandle;
 use tracing::error;
 
@@ -18,6 +27,9 @@ use crate::{session::JoinRuleValue, spawn, spawn_tokio};
 const DEFAULT_MEDIA_PREVIEWS: MediaPreviews = MediaPreviews::Private;
 /// We enable the invite avatars by default.
 const DEFAULT_INVITE_AVATARS_ENABLED: bool = true;
+/// The type of the global account data event containing the custom emotes of
+/// the user.
+const USER_EMOTES_EVENT_TYPE: &str = "im.ponies.user_emotes";
 
 mod imp {
     use std::{
@@ -40,7 +52,10 @@ mod imp {
         /// Whether to display avatars in invites.
         #[property(get, default = DEFAULT_INVITE_AVATARS_ENABLED)]
         invite_avatars_enabled: Cell<bool>,
+        /// The shortcodes of the custom emotes of the user, by URI.
+        pub(super) user_emotes: RefCell<HashMap<OwnedMxcUri, String>>,
         abort_handle: RefCell<Option<AbortHandle>>,
+        user_emotes_drop_guard: OnceCell<EventHandlerDropGuard>,
     }
 
     impl Default for GlobalAccountData {
@@ -49,7 +64,9 @@ mod imp {
                 session: Default::default(),
                 media_previews_enabled: RefCell::new(DEFAULT_MEDIA_PREVIEWS),
                 invite_avatars_enabled: Cell::new(DEFAULT_INVITE_AVATARS_ENABLED),
+                user_emotes: Default::default(),
                 abort_handle: Default::default(),
+                user_emotes_drop_guard: Default::default(),
             }
         }
     }
@@ -63,8 +80,12 @@ mod imp {
     #[glib::derived_properties]
     impl ObjectImpl for GlobalAccountData {
         fn signals() -> &'static [Signal] {
-            static SIGNALS: LazyLock<Vec<Signal>> =
-                LazyLock::new(|| vec![Signal::builder("media-previews-enabled-changed").build()]);
+            static SIGNALS: LazyLock<Vec<Signal>> = LazyLock::new(|| {
+                vec![
+                    Signal::builder("media-previews-enabled-changed").build(),
+                    Signal::builder("user-emotes-changed").build(),
+                ]
+            });
             SIGNALS.as_ref()
         }
 
@@ -77,6 +98,7 @@ mod imp {
                 async move {
                     imp.init_media_previews_settings().await;
                     imp.apply_migrations().await;
+                    imp.load_user_emotes().await;
                 }
             ));
         }
@@ -156,6 +178,90 @@ mod imp {
             }
         }
 
+        /// Load the custom emotes of the user from the account data and watch
+        /// for changes.
+        async fn load_user_emotes(&self) {
+            let client = self.session().client();
+
+            let obj_weak = glib::SendWeakRef::from(self.obj().downgrade());
+            let handler = client.add_event_handler(move |event: Raw<AnyGlobalAccountDataEvent>| {
+                let obj_weak = obj_weak.clone();
+                async move {
+                    if event
+                        .get_field::<String>("type")
+                        .ok()
+                        .flatten()
+                        .is_none_or(|event_type| event_type != USER_EMOTES_EVENT_TYPE)
+                    {
+                        return;
+                    }
+
+                    let content = match event.get_field::<UserEmotesEventContent>("content") {
+                        Ok(Some(content)) => content,
+                        Ok(None) => UserEmotesEventContent::default(),
+                        Err(error) => {
+                            error!("Could not deserialize custom emotes: {error}");
+                            return;
+                        }
+                    };
+
+                    let ctx = glib::MainContext::default();
+                    ctx.spawn(async move {
+                        spawn!(async move {
+                            if let Some(obj) = obj_weak.upgrade() {
+                                obj.imp().update_user_emotes(content);
+                            }
+                        });
+                    });
+                }
+            });
+            let drop_guard = client.event_handler_drop_guard(handler);
+            // This is only called once.
+            let _ = self.user_emotes_drop_guard.set(drop_guard);
+
+            let handle = spawn_tokio!(async move {
+                client
+                    .account()
+                    .account_data_raw(GlobalAccountDataEventType::from(USER_EMOTES_EVENT_TYPE))
+                    .await
+            });
+
+            let raw = match handle.await.expect("task was not aborted") {
+                Ok(Some(raw)) => raw,
+                Ok(None) => return,
+                Err(error) => {
+                    error!("Could not load custom emotes: {error}");
+                    return;
+                }
+            };
+
+            let content = match raw.deserialize_as_unchecked::<UserEmotesEventContent>() {
+                Ok(content) => content,
+                Err(error) => {
+                    error!("Could not deserialize custom emotes: {error}");
+                    return;
+                }
+            };
+
+            self.update_user_emotes(content);
+        }
+
+        /// Update the custom emotes of the user with the given content.
+        fn update_user_emotes(&self, content: UserEmotesEventContent) {
+            let user_emotes = content
+                .images
+                .into_iter()
+                .map(|(shortcode, image)| (image.url, shortcode))
+                .collect::<HashMap<_, _>>();
+
+            if *self.user_emotes.borrow() == user_emotes {
+                return;
+            }
+
+            self.user_emotes.replace(user_emotes);
+            self.obj().emit_by_name::<()>("user-emotes-changed", &[]);
+        }
+
         /// Apply any necessary migrations.
         pub(super) async fn apply_migrations(&self) {
             let session_settings = self.session().settings();
@@ -279,6 +385,12 @@ impl GlobalAccountData {
         }
     }
 
+    /// The shortcode of the custom emote of the user with the given URI, if
+    /// any.
+    pub(crate) fn emote_shortcode(&self, uri: &MxcUri) -> Option<String> {
+        self.imp().user_emotes.borrow().get(uri).cloned()
+    }
+
     /// Set which rooms display media previews.
     pub(crate) async fn set_media_previews_enabled(
         &self,
@@ -305,4 +417,39 @@ impl GlobalAccountData {
             }),
         )
     }
+
+    /// Connect to the signal emitted when the custom emotes of the user
+    /// changed.
+    pub fn connect_user_emotes_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "user-emotes-changed",
+            true,
+            closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
+}
+
+/// The content of the global account data event containing the custom emotes
+/// of the user, as defined by [MSC2545].
+///
+/// We only care about the images, the metadata of the pack is ignored.
+///
+/// [MSC2545]: https://github.com/matrix-org/matrix-spec-proposals/pull/2545
+#[derive(Debug, Default, Deserialize)]
+struct UserEmotesEventContent {
+    /// The images of the pack, by shortcode.
+    #[serde(default)]
+    images: HashMap<String, UserEmote>,
+}
+
+/// A custom emote of the user.
+#[derive(Debug, Deserialize)]
+struct UserEmote {
+    /// The URI of the image.
+    url: OwnedMxcUri,
 }
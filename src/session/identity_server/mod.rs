@@ -0,0 +1,545 @@
+use std::collections::BTreeMap;
+
+use gtk::glib;
+use matrix_sdk::{
+    Client,
+    reqwest::{self, Method, StatusCode},
+};
+use ruma::{
+    ClientSecret, OwnedSessionId, OwnedUserId, UInt,
+    api::client::account::{
+        IdentityServerInfo, ThirdPartyIdRemovalStatus, bind_3pid, request_openid_token, unbind_3pid,
+    },
+    events::{GlobalAccountDataEventType, identity_server::IdentityServerEventContent},
+    serde::Raw,
+    thirdparty::Medium,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+#[cfg(test)]
+mod tests;
+
+/// The type of the global account data event that lists the terms accepted
+/// by the user.
+const ACCEPTED_TERMS_EVENT_TYPE: &str = "m.accepted_terms";
+
+/// An identity server, used to look up users by their third-party
+/// identifiers.
+///
+/// The methods of this type should be called in the tokio runtime.
+#[derive(Debug, Clone)]
+pub(crate) struct IdentityServer {
+    /// The base URL of the identity server.
+    base_url: Url,
+    /// The HTTP client to make requests to the identity server.
+    http_client: reqwest::Client,
+}
+
+impl IdentityServer {
+    /// Get the identity server configured in the account data of the given
+    /// client.
+    ///
+    /// The requests to the identity server are made with the given HTTP client,
+    /// which should be the one of the session.
+    ///
+    /// Returns `Ok(None)` if no identity server is configured.
+    pub(crate) async fn from_account_data(
+        client: &Client,
+        http_client: reqwest::Client,
+    ) -> Result<Option<Self>, IdentityServerError> {
+        let base_url = client
+            .account()
+            .account_data::<IdentityServerEventContent>()
+            .await?
+            .and_then(|raw| raw.deserialize().ok())
+            .and_then(|content| content.base_url);
+
+        let Some(base_url) = base_url else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            base_url: Url::parse(&base_url)?,
+            http_client,
+        }))
+    }
+
+    /// The name of this identity server, as used in requests to the
+    /// homeserver.
+    ///
+    /// This is the host and port of the base URL.
+    pub(crate) fn server_name(&self) -> String {
+        let host = self.base_url.host_str().unwrap_or_default();
+
+        if let Some(port) = self.base_url.port() {
+            format!("{host}:{port}")
+        } else {
+            host.to_owned()
+        }
+    }
+
+    /// Register to this identity server with an OpenID token obtained from
+    /// the homeserver.
+    ///
+    /// Returns the access token to use for requests to this identity server.
+    pub(crate) async fn register(&self, client: &Client) -> Result<String, IdentityServerError> {
+        let user_id = client
+            .user_id()
+            .ok_or(IdentityServerError::NotLoggedIn)?
+            .to_owned();
+        let openid_token = client
+            .send(request_openid_token::v3::Request::new(user_id))
+            .await
+            .map_err(matrix_sdk::Error::from)?;
+
+        let body = json!({
+            "access_token": openid_token.access_token,
+            "token_type": openid_token.token_type.as_str(),
+            "matrix_server_name": openid_token.matrix_server_name,
+            "expires_in": openid_token.expires_in.as_secs(),
+        });
+        let response: RegisterResponse = self
+            .request(Method::POST, "account/register", None, Some(body))
+            .await?;
+
+        Ok(response.token)
+    }
+
+    /// Get the terms of service of this identity server that were not
+    /// accepted yet by the user.
+    pub(crate) async fn unaccepted_terms(
+        &self,
+        client: &Client,
+    ) -> Result<Vec<IdentityServerPolicy>, IdentityServerError> {
+        let response: TermsResponse = self.request(Method::GET, "terms", None, None).await?;
+        let accepted = accepted_terms(client).await?;
+
+        let policies = response
+            .policies
+            .into_values()
+            .filter_map(|mut policy| {
+                // Prefer the English version, which is the most likely to exist.
+                let localized = policy
+                    .localized
+                    .remove("en")
+                    .or_else(|| policy.localized.into_values().next())?;
+                (!accepted.contains(&localized.url)).then_some(localized)
+            })
+            .collect();
+
+        Ok(policies)
+    }
+
+    /// Accept the given terms of service of this identity server.
+    pub(crate) async fn accept_terms(
+        &self,
+        client: &Client,
+        access_token: &str,
+        policies: &[IdentityServerPolicy],
+    ) -> Result<(), IdentityServerError> {
+        let urls = policies
+            .iter()
+            .map(|policy| policy.url.clone())
+            .collect::<Vec<_>>();
+
+        let _: serde_json::Value = self
+            .request(
+                Method::POST,
+                "terms",
+                Some(access_token),
+                Some(json!({ "user_accepts": urls })),
+            )
+            .await?;
+
+        // Remember the accepted terms in the account data, so they are not asked
+        // again.
+        let mut accepted = accepted_terms(client).await?;
+        for url in urls {
+            if !accepted.contains(&url) {
+                accepted.push(url);
+            }
+        }
+        let content = Raw::from_json(serde_json::value::to_raw_value(&AcceptedTermsContent {
+            accepted,
+        })?);
+        client
+            .account()
+            .set_account_data_raw(ACCEPTED_TERMS_EVENT_TYPE.into(), content)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Look up the Matrix users bound to the given third-party identifiers.
+    ///
+    /// Returns the ID of the user bound to each identifier, in the same
+    /// order.
+    pub(crate) async fn lookup(
+        &self,
+        access_token: &str,
+        threepids: &[(Medium, String)],
+    ) -> Result<Vec<Option<OwnedUserId>>, IdentityServerError> {
+        let details: HashDetailsResponse = self
+            .request(Method::GET, "hash_details", Some(access_token), None)
+            .await?;
+
+        let (algorithm, addresses) = if details.algorithms.iter().any(|a| a == "sha256") {
+            let addresses = threepids
+                .iter()
+                .map(|(medium, address)| {
+                    hash_threepid(medium.as_str(), address, &details.lookup_pepper)
+                })
+                .collect::<Vec<_>>();
+            ("sha256", addresses)
+        } else if details.algorithms.iter().any(|a| a == "none") {
+            let addresses = threepids
+                .iter()
+                .map(|(medium, address)| format!("{address} {}", medium.as_str()))
+                .collect::<Vec<_>>();
+            ("none", addresses)
+        } else {
+            return Err(IdentityServerError::UnsupportedLookup);
+        };
+
+        let mut response: LookupResponse = self
+            .request(
+                Method::POST,
+                "lookup",
+                Some(access_token),
+                Some(json!({
+                    "addresses": addresses,
+                    "algorithm": algorithm,
+                    "pepper": details.lookup_pepper,
+                })),
+            )
+            .await?;
+
+        Ok(addresses
+            .iter()
+            .map(|address| response.mappings.remove(address))
+            .collect())
+    }
+
+    /// Request a token to validate the given third-party identifier with this
+    /// identity server.
+    ///
+    /// For an email address, the user receives an email with a link to
+    /// follow. For a phone number, the user receives a text message with a
+    /// code, that must be submitted with [`Self::submit_msisdn_token()`].
+    ///
+    /// The phone number must be in the international format.
+    ///
+    /// Returns the ID of the validation session.
+    pub(crate) async fn request_validation_token(
+        &self,
+        access_token: &str,
+        client_secret: &ClientSecret,
+        medium: &Medium,
+        address: &str,
+        send_attempt: UInt,
+    ) -> Result<OwnedSessionId, IdentityServerError> {
+        let (endpoint, body) = match medium {
+            Medium::Email => (
+                "validate/email/requestToken",
+                json!({
+                    "client_secret": client_secret,
+                    "email": address,
+                    "send_attempt": send_attempt,
+                }),
+            ),
+            Medium::Msisdn => (
+                "validate/msisdn/requestToken",
+                json!({
+                    "client_secret": client_secret,
+                    // The country is ignored for numbers in the international format.
+                    "country": "",
+                    "phone_number": address,
+                    "send_attempt": send_attempt,
+                }),
+            ),
+            _ => return Err(IdentityServerError::UnsupportedMedium),
+        };
+
+        let response: ValidationSessionResponse = self
+            .request(Method::POST, endpoint, Some(access_token), Some(body))
+            .await?;
+
+        Ok(response.sid)
+    }
+
+    /// Submit the code received by text message to validate a phone number
+    /// with this identity server.
+    pub(crate) async fn submit_msisdn_token(
+        &self,
+        access_token: &str,
+        client_secret: &ClientSecret,
+        sid: &OwnedSessionId,
+        token: &str,
+    ) -> Result<(), IdentityServerError> {
+        let response: SubmitTokenResponse = self
+            .request(
+                Method::POST,
+                "validate/msisdn/submitToken",
+                Some(access_token),
+                Some(json!({
+                    "client_secret": client_secret,
+                    "sid": sid,
+                    "token": token,
+                })),
+            )
+            .await?;
+
+        if response.success {
+            Ok(())
+        } else {
+            Err(IdentityServerError::InvalidToken)
+        }
+    }
+
+    /// Bind the third-party identifier validated with the given session to the
+    /// account of the user, via the homeserver.
+    ///
+    /// This makes the identifier discoverable on this identity server.
+    pub(crate) async fn bind(
+        &self,
+        client: &Client,
+        access_token: String,
+        client_secret: &ClientSecret,
+        sid: OwnedSessionId,
+    ) -> Result<(), IdentityServerError> {
+        let request = bind_3pid::v3::Request::new(
+            client_secret.to_owned(),
+            IdentityServerInfo::new(self.server_name(), access_token),
+            sid,
+        );
+        client
+            .send(request)
+            .await
+            .map_err(matrix_sdk::Error::from)?;
+
+        Ok(())
+    }
+
+    /// Unbind the given third-party identifier from the account of the user on
+    /// this identity server, via the homeserver.
+    ///
+    /// The identifier stays associated to the account on the homeserver, but
+    /// it is not discoverable anymore.
+    pub(crate) async fn unbind(
+        &self,
+        client: &Client,
+        medium: Medium,
+        address: String,
+    ) -> Result<(), IdentityServerError> {
+        let mut request = unbind_3pid::v3::Request::new(medium, address);
+        request.id_server = Some(self.server_name());
+
+        let response = client
+            .send(request)
+            .await
+            .map_err(matrix_sdk::Error::from)?;
+
+        match response.id_server_unbind_result {
+            ThirdPartyIdRemovalStatus::Success => Ok(()),
+            _ => Err(IdentityServerError::UnbindFailed),
+        }
+    }
+
+    /// Make a request to the given endpoint of the version 2 of the identity
+    /// service API.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        access_token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, IdentityServerError> {
+        let url = self
+            .base_url
+            .join(&format!("_matrix/identity/v2/{endpoint}"))?;
+
+        let mut request = self.http_client.request(method, url);
+        if let Some(access_token) = access_token {
+            request = request.bearer_auth(access_token);
+        }
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(&body)?);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+
+        if !status.is_success() {
+            let errcode = serde_json::from_slice::<ErrorResponse>(&bytes)
+                .map(|error| error.errcode)
+                .unwrap_or_default();
+            return Err(IdentityServerError::Response { status, errcode });
+        }
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// Get the URLs of the terms of service accepted by the user.
+async fn accepted_terms(client: &Client) -> Result<Vec<String>, IdentityServerError> {
+    let accepted = client
+        .account()
+        .account_data_raw(GlobalAccountDataEventType::from(ACCEPTED_TERMS_EVENT_TYPE))
+        .await?
+        .and_then(|raw| raw.deserialize_as_unchecked::<AcceptedTermsContent>().ok())
+        .map(|content| content.accepted)
+        .unwrap_or_default();
+
+    Ok(accepted)
+}
+
+/// Hash the given third-party identifier with SHA-256 and the given pepper, as
+/// expected by the lookup endpoint of the identity service API.
+fn hash_threepid(medium: &str, address: &str, pepper: &str) -> String {
+    let mut checksum = glib::Checksum::new(glib::ChecksumType::Sha256)
+        .expect("SHA-256 should be a supported checksum type");
+    checksum.update(format!("{address} {medium} {pepper}").as_bytes());
+    let hash = glib::base64_encode(&checksum.digest());
+
+    // The hash uses the URL-safe alphabet, without padding.
+    hash.trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// A policy of an identity server that the user must accept.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct IdentityServerPolicy {
+    /// The name of the policy.
+    pub(crate) name: String,
+    /// The URL of the policy.
+    pub(crate) url: String,
+}
+
+/// A policy in the response of the `GET /terms` endpoint.
+#[derive(Debug, Deserialize)]
+struct TermsPolicy {
+    /// The localized versions of the policy.
+    #[serde(flatten)]
+    localized: BTreeMap<String, IdentityServerPolicy>,
+}
+
+/// The response of the `GET /terms` endpoint.
+#[derive(Debug, Deserialize)]
+struct TermsResponse {
+    /// The policies, by ID.
+    policies: BTreeMap<String, TermsPolicy>,
+}
+
+/// The response of the `GET /hash_details` endpoint.
+#[derive(Debug, Deserialize)]
+struct HashDetailsResponse {
+    /// The algorithms supported to hash the identifiers.
+    algorithms: Vec<String>,
+    /// The pepper to use when hashing the identifiers.
+    lookup_pepper: String,
+}
+
+/// The response of the `POST /lookup` endpoint.
+#[derive(Debug, Deserialize)]
+struct LookupResponse {
+    /// The user IDs, by hashed identifier.
+    #[serde(default)]
+    mappings: BTreeMap<String, OwnedUserId>,
+}
+
+/// The response of the `POST /validate/*/requestToken` endpoints.
+#[derive(Debug, Deserialize)]
+struct ValidationSessionResponse {
+    /// The ID of the validation session.
+    sid: OwnedSessionId,
+}
+
+/// The response of the `POST /validate/msisdn/submitToken` endpoint.
+#[derive(Debug, Deserialize)]
+struct SubmitTokenResponse {
+    /// Whether the validation succeeded.
+    success: bool,
+}
+
+/// The response of the `POST /account/register` endpoint.
+#[derive(Debug, Deserialize)]
+struct RegisterResponse {
+    /// The access token to use for requests to the identity server.
+    token: String,
+}
+
+/// The content of the `m.accepted_terms` account data event.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AcceptedTermsContent {
+    /// The URLs of the accepted policies.
+    #[serde(default)]
+    accepted: Vec<String>,
+}
+
+/// An error response from the identity server.
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    /// The error code.
+    errcode: String,
+}
+
+/// An error when communicating with an identity server.
+#[derive(Debug, Error)]
+pub(crate) enum IdentityServerError {
+    /// The client is not logged in.
+    #[error("The client is not logged in")]
+    NotLoggedIn,
+
+    /// An error occurred with the homeserver.
+    #[error(transparent)]
+    Homeserver(#[from] matrix_sdk::Error),
+
+    /// The URL of the identity server is invalid.
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+
+    /// An error occurred during the request to the identity server.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// The response of the identity server could not be (de)serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// The identity server does not support any known algorithm for lookups.
+    #[error("The identity server does not support any known lookup algorithm")]
+    UnsupportedLookup,
+
+    /// The medium of the third-party identifier is not supported.
+    #[error("The medium of the third-party identifier is not supported")]
+    UnsupportedMedium,
+
+    /// The validation token is invalid.
+    #[error("The validation token is invalid")]
+    InvalidToken,
+
+    /// The identifier could not be unbound from the identity server.
+    #[error("The identity server could not unbind the identifier")]
+    UnbindFailed,
+
+    /// The identity server returned an error.
+    #[error("The identity server returned an error: {status} {errcode}")]
+    Response {
+        /// The HTTP status code of the response.
+        status: StatusCode,
+        /// The Matrix error code in the response, if any.
+        errcode: String,
+    },
+}
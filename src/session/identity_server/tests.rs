@@ -0,0 +1,10 @@
+use super::hash_threepid;
+
+#[test]
+fn hash_threepid_for_lookup() {
+    // Example from the identity service API specification.
+    assert_eq!(
+        hash_threepid("email", "alice@example.com", "matrixrocks"),
+        "4kenr7N9drpCJ4AfalmlGQVsOn3o2RHjkADUpXJWZUc"
+    );
+}
@@ -4,21 +4,26 @@ use futures_util::{StreamExt, lock::Mutex};
 use gettextrs::gettext;
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
-    Client, SessionChange, config::SyncSettings, media::MediaRetentionPolicy, sync::SyncResponse,
+    Client, SessionChange, SlidingSync, SlidingSyncList, SlidingSyncMode, SlidingSyncVersion,
+    config::SyncSettings, media::MediaRetentionPolicy, reqwest, sync::RoomUpdates,
 };
 use ruma::{
+    OwnedRoomId,
     api::client::{
         filter::{FilterDefinition, RoomFilter},
         profile::{AvatarUrl, DisplayName},
         search::search_events::v3::UserProfile,
+        sync::sync_events::v5::request::RoomSubscription,
     },
     assign,
+    events::StateEventType,
 };
-use tokio::{task::AbortHandle, time::sleep};
+use tokio::{sync::broadcast::error::TryRecvError, task::AbortHandle, time::sleep};
 use tokio_stream::wrappers::BroadcastStream;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 mod global_account_data;
+mod identity_server;
 mod ignored_users;
 mod notifications;
 mod remote;
@@ -32,9 +37,9 @@ mod user_sessions_list;
 mod verification;
 
 pub(crate) use self::{
-    global_account_data::*, ignored_users::*, notifications::*, remote::*, room::*, room_list::*,
-    security::*, session_settings::*, sidebar_data::*, user::*, user_sessions_list::*,
-    verification::*,
+    global_account_data::*, identity_server::*, ignored_users::*, notifications::*, remote::*,
+    room::*, room_list::*, security::*, session_settings::*, sidebar_data::*, user::*,
+    user_sessions_list::*, verification::*,
 };
 use crate::{
     Application,
@@ -44,8 +49,9 @@ use crate::{
     session_list::{SessionInfo, SessionInfoImpl},
     spawn, spawn_tokio,
     utils::{
-        TokioDrop,
-        matrix::{self, ClientSetupError},
+        self, ExpressionListModel, TokioDrop,
+        matrix::{self, ClientSetupError, TmpFilesCache},
+        media::MEDIA_CACHE_LOCK,
     },
 };
 
@@ -61,6 +67,35 @@ const MISSED_SYNC_OFFLINE_COUNT: usize = 2;
 /// of missed attempts.
 const MISSED_SYNC_DELAYS: &[u64] = &[1, 5, 10, 20, 30];
 
+/// The ID of the sliding sync instance of a session.
+const SLIDING_SYNC_ID: &str = "fractal-main";
+/// The name of the sliding sync list containing all the rooms.
+const SLIDING_SYNC_ALL_ROOMS_LIST: &str = "all-rooms";
+/// The number of rooms to request at once when populating the list of rooms
+/// with sliding sync.
+const SLIDING_SYNC_BATCH_SIZE: u32 = 50;
+/// The number of events to request for the timeline of a room that is
+/// subscribed to with sliding sync.
+const SLIDING_SYNC_ROOM_TIMELINE_LIMIT: u32 = 20;
+/// The prefix of the name of the files of the media store, in the cache
+/// directory of a session.
+const MEDIA_STORE_FILE_PREFIX: &str = "matrix-sdk-media";
+/// The delay in seconds to wait for after the last change of the maximum size
+/// of the media cache, before applying it.
+const MEDIA_RETENTION_POLICY_UPDATE_DELAY: u32 = 2;
+
+/// The mode used to synchronize a session with its homeserver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[repr(i32)]
+#[enum_type(name = "SessionSyncMode")]
+pub enum SessionSyncMode {
+    /// The classic `/sync` endpoint.
+    #[default]
+    Classic,
+    /// The simplified sliding sync endpoint.
+    Sliding,
+}
+
 /// The state of the session.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, glib::Enum)]
 #[repr(i32)]
@@ -83,6 +118,11 @@ mod imp {
     pub struct Session {
         /// The Matrix client for this session.
         client: OnceCell<TokioDrop<Client>>,
+        /// The HTTP client for the requests of this session to other servers
+        /// than the homeserver.
+        ///
+        /// It uses the same proxy as the Matrix client.
+        pub(super) http_client: OnceCell<reqwest::Client>,
         /// The list model of the sidebar.
         #[property(get = Self::sidebar_list_model)]
         sidebar_list_model: OnceCell<SidebarListModel>,
@@ -98,6 +138,26 @@ mod imp {
         /// Whether this session is synchronized with the homeserver.
         #[property(get)]
         is_offline: Cell<bool>,
+        /// Whether this session is reducing its data usage.
+        ///
+        /// This is the case when the network connection is metered and the
+        /// user enabled the corresponding setting.
+        #[property(get)]
+        is_saving_data: Cell<bool>,
+        /// The mode used to synchronize this session with the homeserver.
+        #[property(get, builder(SessionSyncMode::default()))]
+        sync_mode: Cell<SessionSyncMode>,
+        /// Whether this session uses sliding sync, if the homeserver supports
+        /// it.
+        ///
+        /// This is initialized from the settings when the sync loop is first
+        /// started, so changes to the setting only apply to the next time the
+        /// session is restored.
+        use_sliding_sync: OnceCell<bool>,
+        /// The sliding sync instance of this session, if it uses sliding sync.
+        sliding_sync: RefCell<Option<SlidingSync>>,
+        /// The ID of the room that is subscribed to with sliding sync, if any.
+        subscribed_room: RefCell<Option<OwnedRoomId>>,
         /// The current settings for this session.
         #[property(get, construct_only)]
         settings: OnceCell<SessionSettings>,
@@ -116,11 +176,31 @@ mod imp {
         /// Information about security for this session.
         #[property(get)]
         security: SessionSecurity,
+        /// The total number of unread notifications in the rooms of this
+        /// session.
+        #[property(get)]
+        notification_count: Cell<u64>,
+        /// The number of rooms with unread mentions in this session.
+        #[property(get)]
+        mentions_count: Cell<u64>,
+        /// The number of rooms with unread messages in this session.
+        #[property(get)]
+        unread_rooms_count: Cell<u64>,
+        /// The aggregated highlight state of the rooms of this session.
+        #[property(get)]
+        highlight: Cell<HighlightFlags>,
+        /// The model used to watch the unread counts of the rooms.
+        unread_counts_model: OnceCell<ExpressionListModel>,
         /// The cache for remote data.
         remote_cache: OnceCell<RemoteCache>,
+        /// The temporary files of the media that were downloaded recently.
+        pub(super) tmp_files_cache: TmpFilesCache,
         session_changes_handle: RefCell<Option<AbortHandle>>,
         sync_handle: RefCell<Option<AbortHandle>>,
         network_monitor_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        network_metered_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        media_cache_max_size_handler_id: RefCell<Option<glib::SignalHandlerId>>,
+        media_retention_policy_source: RefCell<Option<glib::SourceId>>,
         homeserver_reachable_lock: Mutex<()>,
         homeserver_reachable_source: RefCell<Option<glib::SourceId>>,
         /// The number of missed synchronizations in a row.
@@ -140,13 +220,25 @@ mod imp {
     impl ObjectImpl for Session {
         fn dispose(&self) {
             // Needs to be disconnected or else it may restart the sync
+            let monitor = gio::NetworkMonitor::default();
             if let Some(handler_id) = self.network_monitor_handler_id.take() {
-                gio::NetworkMonitor::default().disconnect(handler_id);
+                monitor.disconnect(handler_id);
+            }
+            if let Some(handler_id) = self.network_metered_handler_id.take() {
+                monitor.disconnect(handler_id);
+            }
+            if let Some(handler_id) = self.media_cache_max_size_handler_id.take() {
+                Application::default()
+                    .system_settings()
+                    .disconnect(handler_id);
             }
 
             if let Some(source) = self.homeserver_reachable_source.take() {
                 source.remove();
             }
+            if let Some(source) = self.media_retention_policy_source.take() {
+                source.remove();
+            }
 
             if let Some(handle) = self.session_changes_handle.take() {
                 handle.abort();
@@ -155,6 +247,8 @@ mod imp {
             if let Some(handle) = self.sync_handle.take() {
                 handle.abort();
             }
+
+            self.tmp_files_cache.clear();
         }
     }
 
@@ -176,6 +270,7 @@ mod imp {
             self.ignored_users.set_session(Some(obj.clone()));
             self.notifications.set_session(Some(obj.clone()));
             self.user_sessions.init(&obj, obj.user_id().clone());
+            self.init_unread_counts();
 
             let monitor = gio::NetworkMonitor::default();
             let handler_id = monitor.connect_network_changed(clone!(
@@ -188,6 +283,104 @@ mod imp {
                 }
             ));
             self.network_monitor_handler_id.replace(Some(handler_id));
+
+            let metered_handler_id = monitor.connect_network_metered_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_is_saving_data();
+                }
+            ));
+            self.network_metered_handler_id
+                .replace(Some(metered_handler_id));
+
+            obj.settings()
+                .connect_reduce_data_on_metered_connections_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_is_saving_data();
+                    }
+                ));
+            self.update_is_saving_data();
+
+            let media_cache_max_size_handler_id = Application::default()
+                .system_settings()
+                .connect_media_cache_max_size_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.queue_media_retention_policy_update();
+                    }
+                ));
+            self.media_cache_max_size_handler_id
+                .replace(Some(media_cache_max_size_handler_id));
+        }
+
+        /// Queue an update of the retention policy of the media cache.
+        ///
+        /// The update is delayed until the setting has not changed for a
+        /// while, to avoid cleaning up the cache for every intermediate value.
+        fn queue_media_retention_policy_update(&self) {
+            if let Some(source) = self.media_retention_policy_source.take() {
+                source.remove();
+            }
+
+            let source = glib::timeout_add_seconds_local_once(
+                MEDIA_RETENTION_POLICY_UPDATE_DELAY,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move || {
+                        imp.media_retention_policy_source.take();
+                        imp.update_media_retention_policy();
+                    }
+                ),
+            );
+            self.media_retention_policy_source.replace(Some(source));
+        }
+
+        /// Update the retention policy of the media cache with the current
+        /// settings.
+        ///
+        /// The cache is cleaned up right away, to apply the new limits.
+        fn update_media_retention_policy(&self) {
+            let policy = Application::default()
+                .system_settings()
+                .media_retention_policy();
+            let session_id = self.obj().session_id().to_owned();
+            let media = self.client().media();
+
+            spawn_tokio!(async move {
+                if let Err(error) = media.set_media_retention_policy(policy).await {
+                    error!(
+                        session = session_id,
+                        "Could not update media retention policy: {error}"
+                    );
+                    return;
+                }
+
+                if let Err(error) = media.clean().await {
+                    error!(
+                        session = session_id,
+                        "Could not clean up media cache: {error}"
+                    );
+                }
+            });
+        }
+
+        /// Update whether this session is reducing its data usage.
+        fn update_is_saving_data(&self) {
+            let obj = self.obj();
+            let is_saving_data = obj.settings().reduce_data_on_metered_connections()
+                && gio::NetworkMonitor::default().is_network_metered();
+
+            if self.is_saving_data.get() == is_saving_data {
+                return;
+            }
+
+            self.is_saving_data.set(is_saving_data);
+            obj.notify_is_saving_data();
         }
 
         /// The Matrix client for this session.
@@ -212,6 +405,110 @@ mod imp {
             self.sidebar_list_model().item_list().room_list()
         }
 
+        /// Initialize the aggregation of the unread counts of the rooms.
+        fn init_unread_counts(&self) {
+            let model = self.unread_counts_model.get_or_init(|| {
+                let model = ExpressionListModel::new();
+                model.set_expressions(vec![
+                    Room::this_expression("notification-count").upcast(),
+                    Room::this_expression("highlight").upcast(),
+                    Room::this_expression("category").upcast(),
+                ]);
+                model.connect_items_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |model, _, _, _| {
+                        imp.update_unread_counts(model);
+                    }
+                ));
+                model
+            });
+
+            model.set_model(Some(self.room_list()));
+        }
+
+        /// Update the aggregated unread counts of the rooms in the given model.
+        fn update_unread_counts(&self, model: &ExpressionListModel) {
+            let mut notification_count = 0;
+            let mut mentions_count = 0;
+            let mut unread_rooms_count = 0;
+            let mut highlight = HighlightFlags::empty();
+
+            let mut rooms = Vec::new();
+            let mut spaces = Vec::new();
+
+            for room in model.iter::<glib::Object>() {
+                let Some(room) = room.ok().and_downcast::<Room>() else {
+                    continue;
+                };
+
+                match room.category() {
+                    RoomCategory::Invited
+                    | RoomCategory::Favorite
+                    | RoomCategory::Normal
+                    | RoomCategory::LowPriority => {}
+                    RoomCategory::Space => {
+                        spaces.push(room);
+                        continue;
+                    }
+                    RoomCategory::Knocked
+                    | RoomCategory::Left
+                    | RoomCategory::Outdated
+                    | RoomCategory::Ignored => continue,
+                }
+
+                let room_highlight = room.highlight();
+
+                notification_count += room.notification_count();
+                if room_highlight.contains(HighlightFlags::HIGHLIGHT) {
+                    mentions_count += 1;
+                }
+                if room_highlight.contains(HighlightFlags::BOLD) {
+                    unread_rooms_count += 1;
+                }
+                highlight |= room_highlight;
+
+                rooms.push(room);
+            }
+
+            // Aggregate the counts of the children of the spaces.
+            for space in spaces {
+                let space_id = space.room_id();
+                let (space_notification_count, space_highlight) = rooms
+                    .iter()
+                    .filter(|room| room.is_in_space(space_id))
+                    .fold((0, HighlightFlags::empty()), |(count, highlight), room| {
+                        (
+                            count + room.notification_count(),
+                            highlight | room.highlight(),
+                        )
+                    });
+                space.set_space_unread_counts(space_notification_count, space_highlight);
+            }
+
+            let obj = self.obj();
+
+            if self.notification_count.get() != notification_count {
+                self.notification_count.set(notification_count);
+                obj.notify_notification_count();
+            }
+
+            if self.mentions_count.get() != mentions_count {
+                self.mentions_count.set(mentions_count);
+                obj.notify_mentions_count();
+            }
+
+            if self.unread_rooms_count.get() != unread_rooms_count {
+                self.unread_rooms_count.set(unread_rooms_count);
+                obj.notify_unread_rooms_count();
+            }
+
+            if self.highlight.get() != highlight {
+                self.highlight.set(highlight);
+                obj.notify_highlight();
+            }
+        }
+
         /// The verification list of this session.
         pub(super) fn verification_list(&self) -> VerificationList {
             self.sidebar_list_model().item_list().verification_list()
@@ -269,7 +566,14 @@ mod imp {
             let monitor = gio::NetworkMonitor::default();
             let is_network_available = monitor.is_network_available();
 
-            let is_homeserver_reachable = if is_network_available {
+            let uses_proxy = !self.obj().settings().proxy().is_empty();
+
+            let is_homeserver_reachable = if is_network_available && uses_proxy {
+                // The homeserver might only be reachable through the proxy, so we cannot
+                // check it directly. Failed synchronizations will mark the session as
+                // offline anyway.
+                true
+            } else if is_network_available {
                 // Check if we can reach the homeserver.
                 let address = self.homeserver_address();
 
@@ -458,6 +762,9 @@ mod imp {
             }
 
             let client = self.client().clone();
+            let use_sliding_sync = *self
+                .use_sliding_sync
+                .get_or_init(|| self.obj().settings().use_sliding_sync());
             let obj_weak = glib::SendWeakRef::from(self.obj().downgrade());
 
             let handle = spawn_tokio!(async move {
@@ -467,39 +774,83 @@ mod imp {
                     error!("Could not subscribe event cache to sync responses: {error}");
                 }
 
-                // TODO: only create the filter once and reuse it in the future
-                let filter = assign!(FilterDefinition::default(), {
-                    room: assign!(RoomFilter::with_lazy_loading(), {
-                        include_leave: true,
-                    }),
+                let sliding_sync = if use_sliding_sync {
+                    build_sliding_sync(&client).await
+                } else {
+                    None
+                };
+
+                let sync_mode = if sliding_sync.is_some() {
+                    SessionSyncMode::Sliding
+                } else {
+                    SessionSyncMode::Classic
+                };
+                let obj_weak_clone = obj_weak.clone();
+                let sliding_sync_clone = sliding_sync.clone();
+                let ctx = glib::MainContext::default();
+                ctx.spawn(async move {
+                    spawn!(async move {
+                        if let Some(obj) = obj_weak_clone.upgrade() {
+                            let imp = obj.imp();
+                            imp.set_sync_mode(sync_mode);
+                            imp.set_sliding_sync(sliding_sync_clone);
+                        }
+                    });
                 });
 
-                let sync_settings = SyncSettings::new()
-                    .timeout(Duration::from_secs(30))
-                    .ignore_timeout_on_first_sync(true)
-                    .filter(filter.into());
-
-                let mut sync_stream = Box::pin(client.sync_stream(sync_settings).await);
-                while let Some(response) = sync_stream.next().await {
-                    let obj_weak = obj_weak.clone();
-                    let ctx = glib::MainContext::default();
-                    let delay = ctx
-                        .spawn(async move {
-                            spawn!(async move {
-                                if let Some(obj) = obj_weak.upgrade() {
-                                    obj.imp().handle_sync_response(response)
-                                } else {
-                                    None
+                if let Some(sliding_sync) = sliding_sync {
+                    // Sliding sync does not return the room updates directly, so we receive
+                    // them from the client.
+                    let mut room_updates_receiver = client.subscribe_to_all_room_updates();
+
+                    // The stream stops after an error, so restart it.
+                    loop {
+                        let mut sync_stream = Box::pin(sliding_sync.sync());
+                        while let Some(result) = sync_stream.next().await {
+                            let mut has_lagged = false;
+                            let response = result.map(|_| {
+                                let mut room_updates = Vec::new();
+
+                                loop {
+                                    match room_updates_receiver.try_recv() {
+                                        Ok(updates) => room_updates.push(updates),
+                                        Err(TryRecvError::Lagged(count)) => {
+                                            warn!(
+                                                "Missed {count} room updates from sliding sync, resynchronizing the room list"
+                                            );
+                                            has_lagged = true;
+                                        }
+                                        Err(_) => break,
+                                    }
                                 }
-                            })
-                            .await
-                            .expect("task was not aborted")
-                        })
-                        .await
-                        .expect("task was not aborted");
-
-                    if let Some(delay) = delay {
-                        sleep(delay).await;
+
+                                room_updates
+                            });
+
+                            handle_sync_response_on_main_context(obj_weak.clone(), response).await;
+
+                            if has_lagged {
+                                resync_room_list_on_main_context(obj_weak.clone()).await;
+                            }
+                        }
+                    }
+                } else {
+                    // TODO: only create the filter once and reuse it in the future
+                    let filter = assign!(FilterDefinition::default(), {
+                        room: assign!(RoomFilter::with_lazy_loading(), {
+                            include_leave: true,
+                        }),
+                    });
+
+                    let sync_settings = SyncSettings::new()
+                        .timeout(Duration::from_secs(30))
+                        .ignore_timeout_on_first_sync(true)
+                        .filter(filter.into());
+
+                    let mut sync_stream = Box::pin(client.sync_stream(sync_settings).await);
+                    while let Some(response) = sync_stream.next().await {
+                        let response = response.map(|response| vec![response.rooms]);
+                        handle_sync_response_on_main_context(obj_weak.clone(), response).await;
                     }
                 }
             })
@@ -508,21 +859,82 @@ mod imp {
             self.sync_handle.replace(Some(handle));
         }
 
+        /// Set the sliding sync instance of this session.
+        fn set_sliding_sync(&self, sliding_sync: Option<SlidingSync>) {
+            self.sliding_sync.replace(sliding_sync);
+
+            // Subscribe again to the room that was requested before the sliding sync
+            // instance was ready.
+            if let Some(room_id) = self.subscribed_room.take() {
+                self.subscribe_to_room(room_id);
+            }
+        }
+
+        /// Subscribe to the room with the given ID with sliding sync, to
+        /// receive all the new events of its timeline.
+        ///
+        /// The subscriptions to the previous rooms are kept, because their
+        /// timelines are still loaded.
+        pub(super) fn subscribe_to_room(&self, room_id: OwnedRoomId) {
+            if self.subscribed_room.borrow().as_ref() == Some(&room_id) {
+                return;
+            }
+
+            if let Some(sliding_sync) = self.sliding_sync.borrow().as_ref() {
+                let subscription = assign!(RoomSubscription::default(), {
+                    required_state: vec![
+                        (StateEventType::RoomMember, "$LAZY".to_owned()),
+                        (StateEventType::RoomMember, "$ME".to_owned()),
+                        (StateEventType::RoomPinnedEvents, String::new()),
+                    ],
+                    timeline_limit: SLIDING_SYNC_ROOM_TIMELINE_LIMIT.into(),
+                });
+
+                sliding_sync.subscribe_to_rooms(&[&room_id], Some(subscription), true);
+            }
+
+            self.subscribed_room.replace(Some(room_id));
+        }
+
+        /// Resynchronize the whole list of rooms with the rooms known by the
+        /// client.
+        pub(super) fn resync_room_list(&self) {
+            self.room_list().resync();
+        }
+
+        /// Set the mode used to synchronize this session with the homeserver.
+        fn set_sync_mode(&self, mode: SessionSyncMode) {
+            if self.sync_mode.get() == mode {
+                return;
+            }
+
+            info!(
+                session = self.obj().session_id(),
+                "Synchronizing with {mode:?} mode"
+            );
+
+            self.sync_mode.set(mode);
+            self.obj().notify_sync_mode();
+        }
+
         /// Handle the response received via sync.
         ///
         /// Returns the delay to wait for before making the next sync, if
         /// necessary.
-        fn handle_sync_response(
+        pub(super) fn handle_sync_response(
             &self,
-            response: Result<SyncResponse, matrix_sdk::Error>,
+            response: Result<Vec<RoomUpdates>, matrix_sdk::Error>,
         ) -> Option<Duration> {
             let obj = self.obj();
             let session_id = obj.session_id();
             debug!(session = session_id, "Received sync response");
 
             match response {
-                Ok(response) => {
-                    self.room_list().handle_room_updates(response.rooms);
+                Ok(room_updates) => {
+                    let room_list = self.room_list();
+                    for rooms in room_updates {
+                        room_list.handle_room_updates(rooms);
+                    }
 
                     if self.state.get() < SessionState::Ready {
                         self.set_state(SessionState::Ready);
@@ -723,6 +1135,7 @@ mod imp {
             obj.info().clone().delete().await;
 
             self.notifications.clear();
+            self.tmp_files_cache.clear();
 
             debug!(
                 session = obj.session_id(),
@@ -750,13 +1163,18 @@ impl Session {
             .ok_or(ClientSetupError::NoSessionTokens)?;
 
         let stored_session_clone = stored_session.clone();
+        let proxy = matrix::usable_proxy(&settings.proxy());
+        let http_client = matrix::http_client(proxy.as_deref())?;
+        let wanted_media_retention_policy = Application::default()
+            .system_settings()
+            .media_retention_policy();
         let client = spawn_tokio!(async move {
-            let client = matrix::client_with_stored_session(stored_session_clone, tokens).await?;
+            let client =
+                matrix::client_with_stored_session(stored_session_clone, tokens, proxy).await?;
 
             // Make sure that we use the proper retention policy.
             let media = client.media();
             let used_media_retention_policy = media.media_retention_policy().await?;
-            let wanted_media_retention_policy = MediaRetentionPolicy::default();
 
             if used_media_retention_policy != wanted_media_retention_policy {
                 media
@@ -773,6 +1191,10 @@ impl Session {
             .property("info", stored_session)
             .property("settings", settings)
             .build();
+        obj.imp()
+            .http_client
+            .set(http_client)
+            .expect("HTTP client should be uninitialized");
         obj.imp().set_client(client);
 
         Ok(obj)
@@ -809,11 +1231,35 @@ impl Session {
         self.imp().client().clone()
     }
 
+    /// The HTTP client to make requests to other servers than the
+    /// homeserver.
+    pub(crate) fn http_client(&self) -> reqwest::Client {
+        self.imp()
+            .http_client
+            .get()
+            .expect("HTTP client should be initialized")
+            .clone()
+    }
+
+    /// Subscribe to the room with the given ID, to receive all the new events
+    /// of its timeline.
+    ///
+    /// This is only necessary with sliding sync, because the list of rooms
+    /// only receives the latest event of each room.
+    pub(crate) fn subscribe_to_room(&self, room_id: OwnedRoomId) {
+        self.imp().subscribe_to_room(room_id);
+    }
+
     /// The cache for remote data.
     pub(crate) fn remote_cache(&self) -> &RemoteCache {
         self.imp().remote_cache()
     }
 
+    /// The temporary files of the media that were downloaded recently.
+    pub(crate) fn tmp_files_cache(&self) -> &TmpFilesCache {
+        &self.imp().tmp_files_cache
+    }
+
     /// Log out of this session.
     pub(crate) async fn log_out(&self) -> Result<(), String> {
         debug!(
@@ -839,6 +1285,41 @@ impl Session {
         }
     }
 
+    /// The size of the media cache of this session on disk, in bytes.
+    pub(crate) async fn media_cache_size(&self) -> u64 {
+        utils::files_size(self.info().cache_path(), MEDIA_STORE_FILE_PREFIX).await
+    }
+
+    /// Remove all the media from the cache of this session.
+    ///
+    /// Media that are still needed, like the ones waiting to be sent, are
+    /// kept.
+    pub(crate) async fn clear_media_cache(&self) -> Result<(), matrix_sdk::Error> {
+        let media = self.client().media();
+        let policy = Application::default()
+            .system_settings()
+            .media_retention_policy();
+
+        spawn_tokio!(async move {
+            // Make sure that no download uses the cache while the policy is changed.
+            let _guard = MEDIA_CACHE_LOCK.write().await;
+
+            // The media store evicts the media when the cache is bigger than the maximum
+            // size, so use a maximum size of 0 to evict all of them.
+            let clear_policy = MediaRetentionPolicy::empty()
+                .with_max_cache_size(Some(0))
+                .with_max_file_size(Some(0));
+            media.set_media_retention_policy(clear_policy).await?;
+            let result = media.clean().await;
+
+            // Restore the normal policy, even if the cleanup failed.
+            media.set_media_retention_policy(policy).await?;
+            result
+        })
+        .await
+        .expect("task was not aborted")
+    }
+
     /// Clean up this session after it was logged out.
     ///
     /// This should only be called if the session has been logged out without
@@ -865,3 +1346,104 @@ impl Session {
         })
     }
 }
+
+/// Build the sliding sync instance for the given client, if the homeserver
+/// supports it.
+async fn build_sliding_sync(client: &Client) -> Option<SlidingSync> {
+    let version = client
+        .available_sliding_sync_versions()
+        .await
+        .into_iter()
+        .find(|version| matches!(version, SlidingSyncVersion::Native));
+
+    let Some(version) = version else {
+        info!("Homeserver does not support sliding sync, falling back to classic sync");
+        return None;
+    };
+    client.set_sliding_sync_version(version);
+
+    // Load the rooms progressively, with only the state needed to present them
+    // in the sidebar. The rest is loaded lazily when a room is opened.
+    let all_rooms_list = SlidingSyncList::builder(SLIDING_SYNC_ALL_ROOMS_LIST)
+        .sync_mode(SlidingSyncMode::new_growing(SLIDING_SYNC_BATCH_SIZE))
+        .timeline_limit(1)
+        .required_state(vec![
+            (StateEventType::RoomAvatar, String::new()),
+            (StateEventType::RoomCanonicalAlias, String::new()),
+            (StateEventType::RoomCreate, String::new()),
+            (StateEventType::RoomEncryption, String::new()),
+            (StateEventType::RoomJoinRules, String::new()),
+            (StateEventType::RoomMember, "$LAZY".to_owned()),
+            (StateEventType::RoomMember, "$ME".to_owned()),
+            (StateEventType::RoomName, String::new()),
+            (StateEventType::RoomPowerLevels, String::new()),
+            (StateEventType::RoomTombstone, String::new()),
+            (StateEventType::RoomTopic, String::new()),
+            (StateEventType::SpaceChild, "*".to_owned()),
+            (StateEventType::SpaceParent, "*".to_owned()),
+        ]);
+
+    let result = match client.sliding_sync(SLIDING_SYNC_ID) {
+        Ok(builder) => {
+            builder
+                .with_all_extensions()
+                .poll_timeout(Duration::from_secs(30))
+                .network_timeout(Duration::from_secs(30))
+                .add_list(all_rooms_list)
+                .build()
+                .await
+        }
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(sliding_sync) => Some(sliding_sync),
+        Err(error) => {
+            error!("Could not build sliding sync, falling back to classic sync: {error}");
+            None
+        }
+    }
+}
+
+/// Resynchronize the list of rooms of the session on the main context.
+async fn resync_room_list_on_main_context(obj_weak: glib::SendWeakRef<Session>) {
+    let ctx = glib::MainContext::default();
+    ctx.spawn(async move {
+        spawn!(async move {
+            if let Some(obj) = obj_weak.upgrade() {
+                obj.imp().resync_room_list();
+            }
+        })
+        .await
+        .expect("task was not aborted");
+    })
+    .await
+    .expect("task was not aborted");
+}
+
+/// Handle the given sync response on the main context and wait for the delay
+/// before the next sync, if necessary.
+async fn handle_sync_response_on_main_context(
+    obj_weak: glib::SendWeakRef<Session>,
+    response: Result<Vec<RoomUpdates>, matrix_sdk::Error>,
+) {
+    let ctx = glib::MainContext::default();
+    let delay = ctx
+        .spawn(async move {
+            spawn!(async move {
+                if let Some(obj) = obj_weak.upgrade() {
+                    obj.imp().handle_sync_response(response)
+                } else {
+                    None
+                }
+            })
+            .await
+            .expect("task was not aborted")
+        })
+        .await
+        .expect("task was not aborted");
+
+    if let Some(delay) = delay {
+        sleep(delay).await;
+    }
+}
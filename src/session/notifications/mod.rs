@@ -1,34 +1,55 @@
 use std::{borrow::Cow, time::Duration};
 
 use gettextrs::gettext;
-use gtk::{gdk, gio, glib, prelude::*, subclass::prelude::*};
+use gtk::{gdk, gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{Room as MatrixRoom, sync::Notification};
 use ruma::{
     OwnedRoomId, RoomId, UserId,
-    api::client::device::get_device,
+    api::client::{device::get_device, receipt::create_receipt::v3::ReceiptType},
     events::{
         AnyMessageLikeEventContent, AnyStrippedStateEvent, AnySyncStateEvent, AnySyncTimelineEvent,
-        SyncStateEvent,
-        room::{member::MembershipState, message::MessageType},
+        Mentions, SyncStateEvent,
+        room::{
+            member::MembershipState,
+            message::{MessageType, RoomMessageEventContentWithoutRelation},
+        },
     },
     html::{HtmlSanitizerMode, RemoveReplyFallback},
 };
-use tracing::{debug, warn};
+use tracing::{debug, error, warn};
 
+mod notification_preview;
+mod notification_sound;
 mod notifications_settings;
+mod push_rules;
 
-pub(crate) use self::notifications_settings::{
-    NotificationsGlobalSetting, NotificationsRoomSetting, NotificationsSettings,
+pub(crate) use self::{
+    notification_preview::{
+        NotificationPreview, NotificationPreviewEvent, NotificationPreviewEventContent,
+    },
+    notification_sound::{
+        NotificationSound, NotificationSoundEvent, NotificationSoundEventContent,
+    },
+    notifications_settings::{
+        NotificationsGlobalSetting, NotificationsRoomSetting, NotificationsSettings,
+        RoomMuteDuration,
+    },
+    push_rules::{PushRuleSettings, StandardPushRule},
 };
-use super::{IdentityVerification, Session, VerificationKey};
+use super::{IdentityVerification, ReceiptPosition, Room, Session, VerificationKey};
 use crate::{
-    Application, Window, gettext_f,
+    Application, Window,
+    components::quick_reply_dialog,
+    gettext_f,
     intent::SessionIntent,
     prelude::*,
-    spawn_tokio,
+    spawn, spawn_tokio,
     utils::{
         OneshotNotifier,
-        matrix::{AnySyncOrStrippedTimelineEvent, MatrixEventIdUri, MatrixIdUri, MatrixRoomIdUri},
+        matrix::{
+            AnySyncOrStrippedTimelineEvent, MatrixEventIdUri, MatrixIdUri, MatrixRoomIdUri,
+            mentions_only_room,
+        },
     },
 };
 
@@ -116,6 +137,7 @@ impl Notifications {
         body: &str,
         session_id: &str,
         intent: &SessionIntent,
+        buttons: &[(String, SessionIntent)],
         icon: Option<&gdk::Texture>,
     ) {
         let notification = gio::Notification::new(title);
@@ -139,6 +161,12 @@ impl Notifications {
         let target_value = intent.to_variant_with_session_id(session_id.to_owned());
         notification.set_default_action_and_target_value(action, Some(&target_value));
 
+        for (label, intent) in buttons {
+            let action = intent.app_action_name();
+            let target_value = intent.to_variant_with_session_id(session_id.to_owned());
+            notification.add_button_with_target_value(label, action, Some(&target_value));
+        }
+
         if let Some(notification_icon) = icon {
             notification.set_icon(notification_icon);
         }
@@ -166,6 +194,13 @@ impl Notifications {
         };
 
         let app = Application::default();
+
+        // Only show mentions while do-not-disturb is active, if the user allows it.
+        let do_not_disturb = app.system_settings().do_not_disturb();
+        if do_not_disturb && !app.settings().boolean("do-not-disturb-allow-mentions") {
+            return;
+        }
+
         let window = app.active_window().and_downcast::<Window>();
         let session_id = session.session_id();
         let room_id = matrix_room.room_id();
@@ -194,6 +229,8 @@ impl Notifications {
         if !room.is_room_info_initialized() {
             // Wait for the room to finish initializing, otherwise we will not have the
             // display name or the avatar.
+            room.prioritize_room_info_init();
+
             let notifier = OneshotNotifier::<()>::new("Notifications::show_push");
             let receiver = notifier.listen();
 
@@ -215,6 +252,28 @@ impl Notifications {
             }
         };
 
+        let is_highlight = matrix_notification
+            .actions
+            .iter()
+            .any(|action| action.is_highlight());
+        let is_muted_at_room = is_highlight
+            && room.at_room_muted()
+            && event_mentions_only_room(
+                &event,
+                session.user_id(),
+                &room.own_member().display_name(),
+            );
+        let is_mention = is_highlight && !is_muted_at_room;
+
+        if do_not_disturb && !is_mention {
+            return;
+        }
+        // The homeserver notifies mentions of the whole room, so we need to ignore them
+        // if they are muted, unless all the messages of the room should notify.
+        if is_muted_at_room && !self.notifies_all_messages(&room) {
+            return;
+        }
+
         let is_direct = room.direct_member().is_some();
         let sender_id = event.sender();
         let owned_sender_id = sender_id.to_owned();
@@ -277,18 +336,63 @@ impl Notifications {
             format!("{session_id}//{matrix_uri}//{random_id}")
         };
 
-        let inhibit_image = is_invite && !session.global_account_data().invite_avatars_enabled();
-        let icon = room.avatar_data().as_notification_icon(inhibit_image).await;
+        // Only show as much as the user allows.
+        let preview = room.notification_preview();
+        let (title, body) = match preview {
+            NotificationPreview::Full => (room.display_name(), body),
+            NotificationPreview::SenderOnly if is_invite => (room.display_name(), body),
+            NotificationPreview::SenderOnly => (
+                room.display_name(),
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                gettext_f("{user} sent a message", &[("user", &sender_name)]),
+            ),
+            NotificationPreview::Hidden if is_invite => {
+                (gettext("New Invite"), gettext("You were invited to a room"))
+            }
+            NotificationPreview::Hidden => (
+                gettext("New Message"),
+                gettext("You received a new message"),
+            ),
+        };
+
+        let icon = if preview == NotificationPreview::Hidden {
+            // The avatar would reveal the room.
+            None
+        } else {
+            let inhibit_image =
+                is_invite && !session.global_account_data().invite_avatars_enabled();
+            room.avatar_data().as_notification_icon(inhibit_image).await
+        };
+
+        // Allow to act on messages directly from the notification.
+        let buttons = if is_invite {
+            vec![]
+        } else {
+            vec![
+                (
+                    gettext("Reply"),
+                    SessionIntent::ReplyInRoom(room_id.clone()),
+                ),
+                (
+                    gettext("Mark as Read"),
+                    SessionIntent::MarkRoomAsRead(room_id.clone()),
+                ),
+            ]
+        };
 
         Self::send_notification(
             &id,
-            &room.display_name(),
+            &title,
             &body,
             session_id,
             &SessionIntent::ShowMatrixId(matrix_uri),
+            &buttons,
             icon.as_ref(),
         );
 
+        room.notification_sound().await.play();
+
         self.imp()
             .push
             .borrow_mut()
@@ -298,15 +402,13 @@ impl Notifications {
     }
 
     /// Show a notification for the given in-room identity verification.
+    ///
+    /// If the application is active, a toast is shown instead, unless the
+    /// room of the verification is displayed.
     pub(crate) async fn show_in_room_identity_verification(
         &self,
         verification: &IdentityVerification,
     ) {
-        // Do not show notifications if they are disabled.
-        if !self.enabled() {
-            return;
-        }
-
         let Some(session) = self.session() else {
             return;
         };
@@ -322,6 +424,46 @@ impl Notifications {
         let user = verification.user();
         let user_id = user.user_id();
 
+        let intent = SessionIntent::ShowIdentityVerification(verification.key());
+
+        let window = Application::default()
+            .active_window()
+            .and_downcast::<Window>()
+            .filter(|w| w.is_active() && w.current_session_id().as_deref() == Some(session_id));
+
+        if let Some(window) = window {
+            // The verification info bar is visible in the room history.
+            if window
+                .session_view()
+                .selected_room()
+                .is_some_and(|r| r.room_id() == room_id)
+            {
+                return;
+            }
+
+            let title = gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "{user} sent a verification request",
+                &[("user", &glib::markup_escape_text(&user.display_name()))],
+            );
+            let toast = adw::Toast::builder()
+                .title(title)
+                .button_label(gettext("View"))
+                .action_name(intent.app_action_name())
+                .action_target(&intent.to_variant_with_session_id(session_id.to_owned()))
+                .priority(adw::ToastPriority::High)
+                .build();
+            window.add_toast(toast);
+
+            return;
+        }
+
+        // Do not show notifications if they are disabled.
+        if !self.enabled() {
+            return;
+        }
+
         let title = gettext("Verification Request");
         let body = gettext_f(
             // Translators: Do NOT translate the content between '{' and '}', this is a
@@ -333,14 +475,7 @@ impl Notifications {
         let icon = user.avatar_data().as_notification_icon(false).await;
 
         let id = format!("{session_id}//{room_id}//{user_id}//{flow_id}");
-        Self::send_notification(
-            &id,
-            &title,
-            &body,
-            session_id,
-            &SessionIntent::ShowIdentityVerification(verification.key()),
-            icon.as_ref(),
-        );
+        Self::send_notification(&id, &title, &body, session_id, &intent, &[], icon.as_ref());
 
         self.imp()
             .identity_verifications
@@ -400,6 +535,7 @@ impl Notifications {
             &body,
             session_id,
             &SessionIntent::ShowIdentityVerification(verification.key()),
+            &[],
             None,
         );
 
@@ -409,6 +545,98 @@ impl Notifications {
             .insert(verification.key(), id);
     }
 
+    /// Process the given intent triggered by the button of a notification.
+    pub(crate) fn process_action_intent(&self, intent: SessionIntent) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let room_id = match &intent {
+            SessionIntent::MarkRoomAsRead(room_id) | SessionIntent::ReplyInRoom(room_id) => room_id,
+            _ => {
+                warn!(?intent, "Intent is not a notification action");
+                return;
+            }
+        };
+
+        let Some(room) = session.room_list().get(room_id) else {
+            warn!("Could not process notification action for missing room {room_id}");
+            return;
+        };
+
+        spawn!(clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                match intent {
+                    SessionIntent::MarkRoomAsRead(_) => {
+                        obj.mark_room_as_read(&room).await;
+                    }
+                    SessionIntent::ReplyInRoom(_) => {
+                        obj.reply_in_room(&room).await;
+                    }
+                    _ => {}
+                }
+            }
+        ));
+    }
+
+    /// Mark the given room as read and remove its notifications.
+    async fn mark_room_as_read(&self, room: &Room) {
+        room.send_receipt(ReceiptType::Read, ReceiptPosition::End)
+            .await;
+        self.withdraw_all_for_room(room.room_id());
+    }
+
+    /// Ask the user for a message and send it in the given room.
+    ///
+    /// The message is sent as a normal message through the timeline of the
+    /// room, and is interpreted as Markdown if it is enabled in the settings.
+    async fn reply_in_room(&self, room: &Room) {
+        let Some(body) = quick_reply_dialog(room).await else {
+            return;
+        };
+
+        let markdown_enabled = Application::default()
+            .settings()
+            .boolean("markdown-enabled");
+        let content = if markdown_enabled {
+            RoomMessageEventContentWithoutRelation::text_markdown(body)
+        } else {
+            RoomMessageEventContentWithoutRelation::text_plain(body)
+        }
+        // To avoid triggering legacy pushrules, we must always include the mentions,
+        // even if they are empty.
+        .add_mentions(Mentions::new());
+
+        let matrix_timeline = room.live_timeline().matrix_timeline();
+        let handle = spawn_tokio!(async move {
+            matrix_timeline
+                .send(content.with_relation(None).into())
+                .await
+        });
+
+        if let Err(error) = handle.await.expect("task was not aborted") {
+            error!("Could not send reply from notification: {error}");
+            return;
+        }
+
+        self.withdraw_all_for_room(room.room_id());
+    }
+
+    /// Whether all the messages of the given room trigger notifications.
+    fn notifies_all_messages(&self, room: &Room) -> bool {
+        match room.notifications_setting() {
+            NotificationsRoomSetting::Global => match self.settings().global_setting() {
+                NotificationsGlobalSetting::All => true,
+                NotificationsGlobalSetting::DirectAndMentions => room.is_direct(),
+                NotificationsGlobalSetting::MentionsOnly => false,
+            },
+            NotificationsRoomSetting::All => true,
+            NotificationsRoomSetting::MentionsOnly | NotificationsRoomSetting::Mute => false,
+        }
+    }
+
     /// Ask the system to remove the known notifications for the room with the
     /// given ID.
     ///
@@ -455,6 +683,32 @@ impl Default for Notifications {
     }
 }
 
+/// Whether the given event is a message that mentions the whole room, but not
+/// the user with the given ID and display name.
+fn event_mentions_only_room(
+    event: &AnySyncOrStrippedTimelineEvent,
+    own_user_id: &UserId,
+    own_display_name: &str,
+) -> bool {
+    let AnySyncOrStrippedTimelineEvent::Sync(sync_event) = event else {
+        return false;
+    };
+    let AnySyncTimelineEvent::MessageLike(message_event) = &**sync_event else {
+        return false;
+    };
+    let Some(AnyMessageLikeEventContent::RoomMessage(message)) = message_event.original_content()
+    else {
+        return false;
+    };
+
+    mentions_only_room(
+        message.mentions.as_ref(),
+        message.body(),
+        own_user_id,
+        own_display_name,
+    )
+}
+
 /// Generate the notification body for the given event, if it is a message-like
 /// event.
 ///
@@ -0,0 +1,115 @@
+use gtk::{glib, prelude::*};
+use ruma::events::RoomAccountDataEventType;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::Application;
+
+/// The type of the room account data event containing the notification
+/// preview setting of a room.
+pub(crate) const NOTIFICATION_PREVIEW_EVENT_TYPE: &str = "org.gnome.fractal.notification_preview";
+
+/// How much of the content of a message is shown in notifications.
+#[derive(
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Clone,
+    Copy,
+    glib::Enum,
+    strum::EnumString,
+    strum::AsRefStr,
+    strum::FromRepr,
+)]
+#[enum_type(name = "NotificationPreview")]
+#[strum(serialize_all = "kebab-case")]
+#[repr(u32)]
+pub enum NotificationPreview {
+    /// Show the sender and the content of the message.
+    #[default]
+    Full,
+    /// Only show the sender of the message.
+    SenderOnly,
+    /// Do not show the sender nor the content of the message.
+    Hidden,
+}
+
+impl NotificationPreview {
+    /// The room account data event type for the per-room setting.
+    pub(crate) fn event_type() -> RoomAccountDataEventType {
+        NOTIFICATION_PREVIEW_EVENT_TYPE.into()
+    }
+
+    /// The global setting, stored in the application settings.
+    pub(crate) fn global() -> Self {
+        Application::default()
+            .settings()
+            .string("notification-preview")
+            .parse()
+            .unwrap_or_default()
+    }
+
+    /// Set the global setting, stored in the application settings.
+    pub(crate) fn set_global(self) {
+        if let Err(error) = Application::default()
+            .settings()
+            .set_string("notification-preview", self.as_ref())
+        {
+            error!("Could not change notification preview setting: {error}");
+        }
+    }
+
+    /// Whether the content of messages in encrypted rooms should never be
+    /// shown, regardless of the other settings.
+    pub(crate) fn hide_encrypted() -> bool {
+        Application::default()
+            .settings()
+            .boolean("notification-preview-hide-encrypted")
+    }
+
+    /// Compute the preview to use for a room, with the given room setting.
+    ///
+    /// If the room setting is `None`, the global setting is used.
+    pub(crate) fn resolve(room_setting: Option<Self>, is_encrypted: bool) -> Self {
+        if is_encrypted && Self::hide_encrypted() {
+            return Self::Hidden;
+        }
+
+        room_setting.unwrap_or_else(Self::global)
+    }
+}
+
+/// The content of the room account data event containing the notification
+/// preview setting of a room.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct NotificationPreviewEventContent {
+    /// The notification preview, as a string.
+    ///
+    /// If this is `None`, the global setting is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+impl From<Option<NotificationPreview>> for NotificationPreviewEventContent {
+    fn from(value: Option<NotificationPreview>) -> Self {
+        Self {
+            preview: value.map(|preview| preview.as_ref().to_owned()),
+        }
+    }
+}
+
+impl From<NotificationPreviewEventContent> for Option<NotificationPreview> {
+    fn from(value: NotificationPreviewEventContent) -> Self {
+        value.preview.and_then(|preview| preview.parse().ok())
+    }
+}
+
+/// A room account data event containing the notification preview setting of
+/// a room.
+#[derive(Debug, Deserialize)]
+pub(crate) struct NotificationPreviewEvent {
+    /// The content of the event.
+    pub content: NotificationPreviewEventContent,
+}
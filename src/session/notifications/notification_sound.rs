@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+
+use gtk::{gio, glib, prelude::*};
+use ruma::events::RoomAccountDataEventType;
+use serde::{Deserialize, Serialize};
+
+/// The type of the room account data event containing the notification sound
+/// of a room.
+pub(crate) const NOTIFICATION_SOUND_EVENT_TYPE: &str = "org.gnome.fractal.notification_sound";
+
+/// The directory of the freedesktop sound theme.
+const SOUND_THEME_DIR: &str = "/usr/share/sounds/freedesktop/stereo";
+
+thread_local! {
+    /// The sound that is currently playing.
+    ///
+    /// We need to keep a strong reference to it while it is playing.
+    static PLAYING_SOUND: RefCell<Option<gtk::MediaFile>> = const { RefCell::new(None) };
+}
+
+/// The possible sounds played for the notifications of a room.
+#[derive(
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Clone,
+    Copy,
+    glib::Enum,
+    strum::EnumString,
+    strum::AsRefStr,
+    strum::FromRepr,
+)]
+#[enum_type(name = "NotificationSound")]
+#[strum(serialize_all = "kebab-case")]
+#[repr(u32)]
+pub enum NotificationSound {
+    /// Use the default sound.
+    #[default]
+    Default,
+    /// Do not play a sound, but still show the notification.
+    Silent,
+    /// A short chime.
+    Message,
+    /// A bell.
+    Bell,
+    /// A completion sound.
+    Complete,
+}
+
+impl NotificationSound {
+    /// The room account data event type for this setting.
+    pub(crate) fn event_type() -> RoomAccountDataEventType {
+        NOTIFICATION_SOUND_EVENT_TYPE.into()
+    }
+
+    /// The name of the sound in the freedesktop sound theme, if any.
+    fn sound_name(self) -> Option<&'static str> {
+        match self {
+            Self::Default => Some("message-new-instant"),
+            Self::Silent => None,
+            Self::Message => Some("message"),
+            Self::Bell => Some("bell"),
+            Self::Complete => Some("complete"),
+        }
+    }
+
+    /// Play this sound.
+    ///
+    /// Stops the sound that was playing previously, if any.
+    pub(crate) fn play(self) {
+        let media_file = self.sound_name().map(|name| {
+            let file = gio::File::for_path(format!("{SOUND_THEME_DIR}/{name}.oga"));
+            gtk::MediaFile::for_file(&file)
+        });
+
+        if let Some(media_file) = &media_file {
+            media_file.play();
+        }
+
+        let previous = PLAYING_SOUND.replace(media_file);
+        if let Some(previous) = previous {
+            previous.pause();
+        }
+    }
+}
+
+/// The content of the room account data event containing the notification
+/// sound of a room.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct NotificationSoundEventContent {
+    /// The notification sound, as a string.
+    pub sound: String,
+}
+
+impl From<NotificationSound> for NotificationSoundEventContent {
+    fn from(value: NotificationSound) -> Self {
+        Self {
+            sound: value.as_ref().to_owned(),
+        }
+    }
+}
+
+impl From<NotificationSoundEventContent> for NotificationSound {
+    fn from(value: NotificationSoundEventContent) -> Self {
+        value.sound.parse().unwrap_or_default()
+    }
+}
+
+/// A room account data event containing the notification sound of a room.
+#[derive(Debug, Deserialize)]
+pub(crate) struct NotificationSoundEvent {
+    /// The content of the event.
+    pub content: NotificationSoundEventContent,
+}
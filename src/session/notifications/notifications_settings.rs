@@ -10,17 +10,22 @@ use matrix_sdk::{
 };
 use ruma::{
     OwnedRoomId, RoomId,
-    push::{PredefinedOverrideRuleId, RuleKind},
+    push::{PredefinedOverrideRuleId, RuleKind, Ruleset},
 };
+use strum::IntoEnumIterator;
 use tokio::task::AbortHandle;
 use tokio_stream::wrappers::BroadcastStream;
 use tracing::error;
 
+use super::{PushRuleSettings, StandardPushRule};
 use crate::{
-    session::{Room, Session, SessionState},
+    session::{Room, Session, SessionState, TemporaryMute},
     spawn, spawn_tokio,
 };
 
+/// The delay before trying again to unmute a room, in seconds.
+const UNMUTE_RETRY_DELAY: i64 = 60;
+
 /// The possible values for the global notifications setting.
 #[derive(
     Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum, strum::Display, strum::EnumString,
@@ -38,7 +43,9 @@ pub enum NotificationsGlobalSetting {
 }
 
 /// The possible values for a room notifications setting.
-#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum, strum::EnumString)]
+#[derive(
+    Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum, strum::EnumString, strum::AsRefStr,
+)]
 #[enum_type(name = "NotificationsRoomSetting")]
 #[strum(serialize_all = "kebab-case")]
 pub enum NotificationsRoomSetting {
@@ -75,6 +82,50 @@ impl From<RoomNotificationMode> for NotificationsRoomSetting {
     }
 }
 
+/// The possible durations to mute a room for.
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RoomMuteDuration {
+    /// One hour.
+    OneHour,
+    /// Eight hours.
+    EightHours,
+    /// Until tomorrow morning.
+    UntilTomorrow,
+    /// Until the room is unmuted.
+    Forever,
+}
+
+impl RoomMuteDuration {
+    /// The expiry of a mute with this duration starting now, as a number of
+    /// seconds since the Unix epoch.
+    ///
+    /// Returns `None` if the mute does not expire.
+    fn expiry(self) -> Option<i64> {
+        let now = glib::DateTime::now_local().ok()?;
+
+        let expiry = match self {
+            Self::OneHour => now.add_hours(1).ok()?,
+            Self::EightHours => now.add_hours(8).ok()?,
+            Self::UntilTomorrow => {
+                let tomorrow = now.add_days(1).ok()?;
+                glib::DateTime::from_local(
+                    tomorrow.year(),
+                    tomorrow.month(),
+                    tomorrow.day_of_month(),
+                    8,
+                    0,
+                    0.0,
+                )
+                .ok()?
+            }
+            Self::Forever => return None,
+        };
+
+        Some(expiry.to_unix())
+    }
+}
+
 mod imp {
     use std::cell::{Cell, RefCell};
 
@@ -104,6 +155,13 @@ mod imp {
         ///
         /// Any room not in this map uses the global setting.
         per_room_settings: RefCell<HashMap<OwnedRoomId, NotificationsRoomSetting>>,
+        /// Whether mentions of the whole room do not highlight rooms,
+        /// according to the push rules.
+        #[property(get)]
+        room_mentions_muted: Cell<bool>,
+        /// The timeouts to unmute the rooms that are muted temporarily, by
+        /// room ID.
+        mute_timeouts: RefCell<HashMap<OwnedRoomId, glib::SourceId>>,
         abort_handle: RefCell<Option<AbortHandle>>,
     }
 
@@ -119,6 +177,10 @@ mod imp {
             if let Some(handle) = self.abort_handle.take() {
                 handle.abort();
             }
+
+            for source in self.mute_timeouts.take().into_values() {
+                source.remove();
+            }
         }
     }
 
@@ -231,6 +293,7 @@ mod imp {
                 self,
                 async move {
                     imp.update().await;
+                    imp.init_temporary_mutes();
                 }
             ));
         }
@@ -268,6 +331,55 @@ mod imp {
 
             self.update_keywords_list().await;
             self.update_per_room_settings().await;
+            self.update_room_mentions_muted().await;
+        }
+
+        /// Load the push rules of the account.
+        pub(super) async fn push_rules(&self) -> Option<Ruleset> {
+            let session = self.session.upgrade()?;
+            let client = session.client();
+
+            let handle = spawn_tokio!(async move { client.account().push_rules().await });
+
+            match handle.await.expect("task was not aborted") {
+                Ok(ruleset) => Some(ruleset),
+                Err(error) => {
+                    error!("Could not load push rules: {error}");
+                    None
+                }
+            }
+        }
+
+        /// Update whether mentions of the whole room do not highlight rooms.
+        async fn update_room_mentions_muted(&self) {
+            let Some(ruleset) = self.push_rules().await else {
+                return;
+            };
+
+            let muted = StandardPushRule::RoomMentions
+                .settings(&ruleset)
+                .is_some_and(|settings| !settings.enabled || !settings.highlight);
+
+            if self.room_mentions_muted.get() == muted {
+                return;
+            }
+
+            self.room_mentions_muted.set(muted);
+            self.obj().notify_room_mentions_muted();
+
+            // Update the highlight of the rooms.
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            for room in session.room_list().iter::<Room>() {
+                let Ok(room) = room else {
+                    // Returns an error when the list changed, just stop.
+                    break;
+                };
+
+                room.update_at_room_highlight();
+            }
         }
 
         /// Set whether notifications are enabled for this session.
@@ -335,6 +447,101 @@ mod imp {
             list.splice(pos, old_len.saturating_sub(pos), additions);
         }
 
+        /// The notifications setting of the room with the given ID.
+        pub(super) fn per_room_setting(&self, room_id: &RoomId) -> NotificationsRoomSetting {
+            self.per_room_settings
+                .borrow()
+                .get(room_id)
+                .copied()
+                .unwrap_or_default()
+        }
+
+        /// Arm the timeouts of the rooms that are muted temporarily.
+        fn init_temporary_mutes(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            for (room_id, mute) in session.settings().temporary_mutes() {
+                self.arm_mute_timeout(room_id, mute.until);
+            }
+        }
+
+        /// Arm the timeout to unmute the room with the given ID at the given
+        /// time, as a number of seconds since the Unix epoch.
+        ///
+        /// If the time is in the past, the room is unmuted right away.
+        pub(super) fn arm_mute_timeout(&self, room_id: OwnedRoomId, until: i64) {
+            self.disarm_mute_timeout(&room_id);
+
+            let remaining =
+                u32::try_from(until.saturating_sub(now_unix()).max(0)).unwrap_or(u32::MAX);
+
+            let room_id_clone = room_id.clone();
+            let source = glib::timeout_add_seconds_local_once(
+                remaining,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move || {
+                        imp.mute_timeouts.borrow_mut().remove(&room_id_clone);
+
+                        spawn!(async move {
+                            imp.expire_temporary_mute(room_id_clone).await;
+                        });
+                    }
+                ),
+            );
+
+            self.mute_timeouts.borrow_mut().insert(room_id, source);
+        }
+
+        /// Disarm the timeout to unmute the room with the given ID, if any.
+        fn disarm_mute_timeout(&self, room_id: &RoomId) {
+            if let Some(source) = self.mute_timeouts.borrow_mut().remove(room_id) {
+                source.remove();
+            }
+        }
+
+        /// Forget the temporary mute of the room with the given ID, if any.
+        pub(super) fn clear_temporary_mute(&self, room_id: &RoomId) {
+            self.disarm_mute_timeout(room_id);
+
+            if let Some(session) = self.session.upgrade() {
+                session.settings().set_temporary_mute(room_id, None);
+            }
+        }
+
+        /// Restore the setting of the room with the given ID from before it
+        /// was muted temporarily.
+        async fn expire_temporary_mute(&self, room_id: OwnedRoomId) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let Some(mute) = session.settings().temporary_mute(&room_id) else {
+                return;
+            };
+
+            if self.per_room_setting(&room_id) != NotificationsRoomSetting::Mute {
+                // The setting was changed elsewhere, do not override it.
+                self.clear_temporary_mute(&room_id);
+                return;
+            }
+
+            let previous_setting = mute.previous_setting.parse().unwrap_or_default();
+
+            // This also forgets the temporary mute.
+            if self
+                .obj()
+                .set_per_room_setting(room_id.clone(), previous_setting)
+                .await
+                .is_err()
+            {
+                // Try again later.
+                self.arm_mute_timeout(room_id, now_unix() + UNMUTE_RETRY_DELAY);
+            }
+        }
+
         /// Update the local list of per-room settings with the remote one.
         pub(super) async fn update_per_room_settings(&self) {
             let Some(api) = self.api() else {
@@ -524,6 +731,103 @@ impl NotificationsSettings {
         Ok(())
     }
 
+    /// Load the settings of the standard push rules.
+    ///
+    /// Returns `None` if the push rules could not be loaded.
+    pub(crate) async fn push_rules_settings(
+        &self,
+    ) -> Option<HashMap<StandardPushRule, PushRuleSettings>> {
+        let ruleset = self.imp().push_rules().await?;
+
+        Some(
+            StandardPushRule::iter()
+                .filter_map(|rule| Some((rule, rule.settings(&ruleset)?)))
+                .collect(),
+        )
+    }
+
+    /// Set the settings of the given standard push rule.
+    pub(crate) async fn set_push_rule_settings(
+        &self,
+        rule: StandardPushRule,
+        settings: PushRuleSettings,
+    ) -> Result<(), NotificationSettingsError> {
+        let imp = self.imp();
+
+        let Some(api) = imp.api() else {
+            error!("Cannot update notifications settings when API is not initialized");
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+        let Some(ruleset) = imp.push_rules().await else {
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+
+        for (kind, rule_id) in rule.rule_ids(&ruleset) {
+            let api = api.clone();
+            let rule_id_clone = rule_id.clone();
+            let handle = spawn_tokio!(async move {
+                api.set_push_rule_actions(kind.clone(), &rule_id_clone, settings.actions())
+                    .await?;
+                api.set_push_rule_enabled(kind, &rule_id_clone, settings.enabled)
+                    .await
+            });
+
+            if let Err(error) = handle.await.expect("task was not aborted") {
+                error!("Could not update push rule `{rule_id}`: {error}");
+                return Err(error);
+            }
+        }
+
+        imp.update_room_mentions_muted().await;
+
+        Ok(())
+    }
+
+    /// Reset the predefined push rules to their default values.
+    pub(crate) async fn reset_push_rules(&self) -> Result<(), NotificationSettingsError> {
+        let imp = self.imp();
+
+        let Some(session) = self.session() else {
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+        let Some(api) = imp.api() else {
+            error!("Cannot update notifications settings when API is not initialized");
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+        let Some(ruleset) = imp.push_rules().await else {
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+        let default_ruleset = Ruleset::server_default(session.user_id());
+
+        for rule in StandardPushRule::iter().filter(|rule| rule.is_predefined()) {
+            for (kind, rule_id) in rule.rule_ids(&ruleset) {
+                let Some(default_rule) = default_ruleset.get(kind.clone(), &rule_id) else {
+                    continue;
+                };
+                let enabled = default_rule.enabled();
+                let actions = default_rule.actions().to_vec();
+
+                let api = api.clone();
+                let rule_id_clone = rule_id.clone();
+                let handle = spawn_tokio!(async move {
+                    api.set_push_rule_actions(kind.clone(), &rule_id_clone, actions)
+                        .await?;
+                    api.set_push_rule_enabled(kind, &rule_id_clone, enabled)
+                        .await
+                });
+
+                if let Err(error) = handle.await.expect("task was not aborted") {
+                    error!("Could not reset push rule `{rule_id}`: {error}");
+                    return Err(error);
+                }
+            }
+        }
+
+        imp.update_room_mentions_muted().await;
+
+        Ok(())
+    }
+
     /// Set the notification setting for the room with the given ID.
     pub(crate) async fn set_per_room_setting(
         &self,
@@ -549,10 +853,72 @@ impl NotificationsSettings {
             return Err(error);
         }
 
+        imp.clear_temporary_mute(&room_id);
         imp.update_per_room_settings().await;
 
         Ok(())
     }
+
+    /// Mute the room with the given ID for the given duration.
+    ///
+    /// When the mute expires, the previous setting of the room is restored.
+    pub(crate) async fn mute_room(
+        &self,
+        room_id: OwnedRoomId,
+        duration: RoomMuteDuration,
+    ) -> Result<(), NotificationSettingsError> {
+        let imp = self.imp();
+
+        let Some(session) = self.session() else {
+            return Err(NotificationSettingsError::UnableToUpdatePushRule);
+        };
+        let settings = session.settings();
+
+        // If the room is already muted temporarily, keep the setting from before.
+        let previous_setting = settings
+            .temporary_mute(&room_id)
+            .and_then(|mute| mute.previous_setting.parse().ok())
+            .unwrap_or_else(|| imp.per_room_setting(&room_id));
+        // The room should not stay muted when the mute expires.
+        let previous_setting = if previous_setting == NotificationsRoomSetting::Mute {
+            NotificationsRoomSetting::Global
+        } else {
+            previous_setting
+        };
+
+        self.set_per_room_setting(room_id.clone(), NotificationsRoomSetting::Mute)
+            .await?;
+
+        if let Some(until) = duration.expiry() {
+            settings.set_temporary_mute(
+                &room_id,
+                Some(TemporaryMute {
+                    until,
+                    previous_setting: previous_setting.as_ref().to_owned(),
+                }),
+            );
+            imp.arm_mute_timeout(room_id, until);
+        }
+
+        Ok(())
+    }
+
+    /// Unmute the room with the given ID.
+    ///
+    /// If the room was muted temporarily, the previous setting of the room is
+    /// restored.
+    pub(crate) async fn unmute_room(
+        &self,
+        room_id: OwnedRoomId,
+    ) -> Result<(), NotificationSettingsError> {
+        let setting = self
+            .session()
+            .and_then(|session| session.settings().temporary_mute(&room_id))
+            .and_then(|mute| mute.previous_setting.parse().ok())
+            .unwrap_or_default();
+
+        self.set_per_room_setting(room_id, setting).await
+    }
 }
 
 impl Default for NotificationsSettings {
@@ -561,6 +927,13 @@ impl Default for NotificationsSettings {
     }
 }
 
+/// The current time, as a number of seconds since the Unix epoch.
+fn now_unix() -> i64 {
+    glib::DateTime::now_utc()
+        .map(|now| now.to_unix())
+        .unwrap_or_default()
+}
+
 async fn default_rooms_notifications_is_all(
     api: MatrixNotificationSettings,
     is_one_to_one: bool,
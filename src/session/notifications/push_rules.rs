@@ -0,0 +1,182 @@
+use gettextrs::gettext;
+use ruma::push::{
+    Action, PredefinedContentRuleId, PredefinedOverrideRuleId, PredefinedUnderrideRuleId, RuleKind,
+    Ruleset, Tweak,
+};
+
+/// A kind of standard push rule that can be edited by the user.
+///
+/// Each kind can correspond to several push rules, for example the stable and
+/// legacy versions of a rule, or the encrypted and unencrypted versions of a
+/// rule.
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, strum::EnumIter)]
+pub(crate) enum StandardPushRule {
+    /// Messages that mention the user.
+    UserMentions,
+    /// Messages that mention the whole room.
+    RoomMentions,
+    /// Messages that contain one of the keywords of the user.
+    Keywords,
+    /// Invites for the user.
+    Invites,
+    /// Messages in rooms with a single other member.
+    OneToOneMessages,
+    /// Messages in rooms with several other members.
+    GroupMessages,
+    /// Incoming calls.
+    Calls,
+}
+
+impl StandardPushRule {
+    /// The localized title of this kind of rule.
+    pub(crate) fn title(self) -> String {
+        match self {
+            Self::UserMentions => gettext("Mentions of You"),
+            Self::RoomMentions => gettext("Mentions of @room"),
+            Self::Keywords => gettext("Keywords"),
+            Self::Invites => gettext("Invites"),
+            Self::OneToOneMessages => gettext("Messages in Direct Chats"),
+            Self::GroupMessages => gettext("Messages in Group Chats"),
+            Self::Calls => gettext("Calls"),
+        }
+    }
+
+    /// Whether this kind of rule is predefined by the specification.
+    ///
+    /// Only predefined rules can be reset to their default values.
+    pub(crate) fn is_predefined(self) -> bool {
+        !matches!(self, Self::Keywords)
+    }
+
+    /// The kinds and IDs of the push rules in the given ruleset that
+    /// correspond to this kind of rule.
+    pub(crate) fn rule_ids(self, ruleset: &Ruleset) -> Vec<(RuleKind, String)> {
+        #[allow(deprecated)]
+        let rule_ids = match self {
+            Self::Keywords => {
+                // Keywords are the content rules that were added by the user.
+                return ruleset
+                    .content
+                    .iter()
+                    .filter(|rule| !rule.default)
+                    .map(|rule| (RuleKind::Content, rule.rule_id.clone()))
+                    .collect();
+            }
+            Self::UserMentions => vec![
+                (
+                    RuleKind::Override,
+                    PredefinedOverrideRuleId::IsUserMention.to_string(),
+                ),
+                (
+                    RuleKind::Override,
+                    PredefinedOverrideRuleId::ContainsDisplayName.to_string(),
+                ),
+                (
+                    RuleKind::Content,
+                    PredefinedContentRuleId::ContainsUserName.to_string(),
+                ),
+            ],
+            Self::RoomMentions => vec![
+                (
+                    RuleKind::Override,
+                    PredefinedOverrideRuleId::IsRoomMention.to_string(),
+                ),
+                (
+                    RuleKind::Override,
+                    PredefinedOverrideRuleId::RoomNotif.to_string(),
+                ),
+            ],
+            Self::Invites => vec![(
+                RuleKind::Override,
+                PredefinedOverrideRuleId::InviteForMe.to_string(),
+            )],
+            Self::OneToOneMessages => vec![
+                (
+                    RuleKind::Underride,
+                    PredefinedUnderrideRuleId::RoomOneToOne.to_string(),
+                ),
+                (
+                    RuleKind::Underride,
+                    PredefinedUnderrideRuleId::EncryptedRoomOneToOne.to_string(),
+                ),
+            ],
+            Self::GroupMessages => vec![
+                (
+                    RuleKind::Underride,
+                    PredefinedUnderrideRuleId::Message.to_string(),
+                ),
+                (
+                    RuleKind::Underride,
+                    PredefinedUnderrideRuleId::Encrypted.to_string(),
+                ),
+            ],
+            Self::Calls => vec![(
+                RuleKind::Underride,
+                PredefinedUnderrideRuleId::Call.to_string(),
+            )],
+        };
+
+        // Only keep the rules that exist in the ruleset.
+        rule_ids
+            .into_iter()
+            .filter(|(kind, rule_id)| ruleset.get(kind.clone(), rule_id).is_some())
+            .collect()
+    }
+
+    /// The settings of this kind of rule in the given ruleset.
+    ///
+    /// Returns `None` if no rule of this kind exists in the ruleset.
+    pub(crate) fn settings(self, ruleset: &Ruleset) -> Option<PushRuleSettings> {
+        let (kind, rule_id) = self.rule_ids(ruleset).into_iter().next()?;
+        let rule = ruleset.get(kind, &rule_id)?;
+
+        Some(PushRuleSettings::new(rule.enabled(), rule.actions()))
+    }
+}
+
+/// The settings of a push rule.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PushRuleSettings {
+    /// Whether the rule is enabled.
+    pub enabled: bool,
+    /// Whether the rule triggers a notification.
+    pub notify: bool,
+    /// Whether the rule highlights the message.
+    pub highlight: bool,
+    /// Whether the rule plays a sound.
+    pub sound: bool,
+}
+
+impl PushRuleSettings {
+    /// Construct the settings of a push rule with the given state and actions.
+    fn new(enabled: bool, actions: &[Action]) -> Self {
+        Self {
+            enabled,
+            notify: actions.iter().any(Action::should_notify),
+            highlight: actions.iter().any(Action::is_highlight),
+            sound: actions
+                .iter()
+                .any(|action| matches!(action, Action::SetTweak(Tweak::Sound(_)))),
+        }
+    }
+
+    /// The actions of a push rule with these settings.
+    ///
+    /// A rule that does not notify has no actions, so the tweaks are ignored.
+    pub(crate) fn actions(self) -> Vec<Action> {
+        if !self.notify {
+            return vec![];
+        }
+
+        let mut actions = vec![Action::Notify];
+
+        if self.sound {
+            actions.push(Action::SetTweak(Tweak::Sound("default".to_owned())));
+        }
+        if self.highlight {
+            actions.push(Action::SetTweak(Tweak::Highlight(true)));
+        }
+
+        actions
+    }
+}
@@ -65,6 +65,15 @@ mod imp {
         /// Whether we can knock on the room.
         #[property(get)]
         can_knock: Cell<bool>,
+        /// Whether guests can join the room.
+        #[property(get)]
+        guest_can_join: Cell<bool>,
+        /// Whether the room is known to be encrypted.
+        ///
+        /// The public rooms directory does not provide this information, so it
+        /// is only accurate after the data was loaded from the homeserver.
+        #[property(get)]
+        is_encrypted: Cell<bool>,
         /// The information about this room in the room list.
         #[property(get)]
         room_list_info: RoomListRoomInfo,
@@ -254,6 +263,26 @@ mod imp {
             self.obj().notify_can_knock();
         }
 
+        /// Set whether guests can join the room.
+        fn set_guest_can_join(&self, guest_can_join: bool) {
+            if self.guest_can_join.get() == guest_can_join {
+                return;
+            }
+
+            self.guest_can_join.set(guest_can_join);
+            self.obj().notify_guest_can_join();
+        }
+
+        /// Set whether the room is known to be encrypted.
+        fn set_is_encrypted(&self, is_encrypted: bool) {
+            if self.is_encrypted.get() == is_encrypted {
+                return;
+            }
+
+            self.is_encrypted.set(is_encrypted);
+            self.obj().notify_is_encrypted();
+        }
+
         /// Set the loading state.
         pub(super) fn set_loading_state(&self, loading_state: LoadingState) {
             if self.loading_state.get() == loading_state {
@@ -278,6 +307,8 @@ mod imp {
             self.set_topic(data.topic.into_clean_string());
             self.set_joined_members_count(data.num_joined_members.try_into().unwrap_or(u32::MAX));
             self.set_join_rule(&data.join_rule);
+            self.set_guest_can_join(data.guest_can_join);
+            self.set_is_encrypted(data.encryption.is_some());
 
             if let Some(image) = self.obj().avatar_data().image() {
                 image.set_uri_and_info(data.avatar_url, None);
@@ -478,7 +509,7 @@ impl RemoteRoom {
     }
 
     /// Load the data of this room if it is considered to be stale.
-    pub(super) fn load_data_if_stale(&self) {
+    pub(crate) fn load_data_if_stale(&self) {
         let imp = self.imp();
 
         if !imp.is_data_stale() {
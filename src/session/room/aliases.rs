@@ -1,3 +1,4 @@
+use gettextrs::gettext;
 use gtk::{glib, glib::closure_local, prelude::*, subclass::prelude::*};
 use matrix_sdk::{deserialized_responses::RawSyncOrStrippedState, reqwest::StatusCode};
 use ruma::{
@@ -11,7 +12,7 @@ use ruma::{
 use tracing::error;
 
 use super::Room;
-use crate::spawn_tokio;
+use crate::{prelude::*, spawn_tokio};
 
 mod imp {
     use std::{cell::RefCell, marker::PhantomData, sync::LazyLock};
@@ -534,6 +535,20 @@ pub(crate) enum AddAltAliasError {
     Other,
 }
 
+impl UserFacingError for AddAltAliasError {
+    fn to_user_facing(&self) -> String {
+        match self {
+            Self::NotRegistered => {
+                gettext("Could not add public address: it is not registered as a local address")
+            }
+            Self::InvalidRoomId => {
+                gettext("Could not add public address: it does not belong to this room")
+            }
+            Self::Other => gettext("Could not add public address"),
+        }
+    }
+}
+
 /// All high-level errors that can happen when trying to register a local alias.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum RegisterLocalAliasError {
@@ -542,3 +557,12 @@ pub(crate) enum RegisterLocalAliasError {
     /// An other error occurred.
     Other,
 }
+
+impl UserFacingError for RegisterLocalAliasError {
+    fn to_user_facing(&self) -> String {
+        match self {
+            Self::AlreadyInUse => gettext("Could not register local address: it is already in use"),
+            Self::Other => gettext("Could not register local address"),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt::Write as _;
+
+use gettextrs::gettext;
+use gtk::{gio, glib, prelude::*};
+use matrix_sdk::room::MessagesOptions;
+use ruma::{
+    MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId,
+    api::client::filter::RoomEventFilter,
+    assign,
+    events::{
+        AnySyncMessageLikeEvent, AnySyncTimelineEvent, MessageLikeEventType, SyncMessageLikeEvent,
+        room::message::Relation,
+    },
+    uint,
+};
+use serde::Serialize;
+use tracing::{error, warn};
+
+#[cfg(test)]
+mod tests;
+
+use super::Room;
+use crate::{
+    gettext_f,
+    prelude::*,
+    spawn_tokio,
+    utils::{
+        matrix::{MediaMessage, message_plain_body, timestamp_to_date},
+        string::StrExt,
+    },
+};
+
+/// The format of an exported conversation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::FromRepr)]
+#[repr(u32)]
+pub(crate) enum ConversationExportFormat {
+    /// A HTML page, that can be opened in a web browser.
+    #[default]
+    Html,
+    /// A JSON file, that can be processed by other programs.
+    Json,
+}
+
+impl ConversationExportFormat {
+    /// The extension of the files with this format.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// The options to export a conversation.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ConversationExportOptions {
+    /// The format of the exported file.
+    pub format: ConversationExportFormat,
+    /// The earliest time of the exported messages, if any.
+    pub start: Option<MilliSecondsSinceUnixEpoch>,
+    /// The latest time of the exported messages, if any.
+    pub end: Option<MilliSecondsSinceUnixEpoch>,
+    /// Whether to download the media files into a folder next to the exported
+    /// file.
+    pub include_media: bool,
+}
+
+/// An error that occurred while exporting a conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum ConversationExportError {
+    /// The export was cancelled.
+    #[error("The export was cancelled")]
+    Cancelled,
+    /// The history of the room could not be loaded.
+    #[error("Could not load the history of the room")]
+    Load,
+    /// The exported file could not be written.
+    #[error("Could not write the exported file")]
+    Write,
+}
+
+/// A message of an exported conversation.
+#[derive(Debug, Serialize)]
+struct ExportedMessage {
+    /// The ID of the event of the message.
+    event_id: OwnedEventId,
+    /// The ID of the sender of the message.
+    sender: OwnedUserId,
+    /// The display name of the sender of the message.
+    sender_name: String,
+    /// The time when the message was sent.
+    timestamp: MilliSecondsSinceUnixEpoch,
+    /// The plain text of the message.
+    body: String,
+    /// The path of the media file of the message, relative to the exported
+    /// file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_file: Option<String>,
+    /// The media of the message, if any.
+    #[serde(skip)]
+    media: Option<MediaMessage>,
+}
+
+/// A conversation that is exported.
+#[derive(Debug, Serialize)]
+struct ExportedConversation {
+    /// The ID of the room.
+    room_id: String,
+    /// The name of the room.
+    room_name: String,
+    /// The messages in the conversation, in chronological order.
+    messages: Vec<ExportedMessage>,
+}
+
+/// Export the conversation of the given room to the given file.
+///
+/// The history of the room is paginated until the start of the chosen range.
+/// `progress` is called with the number of messages that were loaded so far.
+///
+/// Returns the number of exported messages.
+pub(crate) async fn export_conversation(
+    room: &Room,
+    options: ConversationExportOptions,
+    file: &gio::File,
+    cancellable: &gio::Cancellable,
+    progress: impl Fn(usize),
+) -> Result<usize, ConversationExportError> {
+    let mut messages = load_messages(room, options, cancellable, &progress).await?;
+    // The messages were loaded backwards.
+    messages.reverse();
+
+    if options.include_media {
+        save_media_files(room, file, &mut messages, cancellable).await?;
+    }
+
+    let count = messages.len();
+    let conversation = ExportedConversation {
+        room_id: room.room_id().to_string(),
+        room_name: room.display_name(),
+        messages,
+    };
+
+    let contents = match options.format {
+        ConversationExportFormat::Html => conversation_to_html(&conversation),
+        ConversationExportFormat::Json => {
+            serde_json::to_string_pretty(&conversation).map_err(|error| {
+                error!("Could not serialize conversation: {error}");
+                ConversationExportError::Write
+            })?
+        }
+    };
+
+    if let Err((_, error)) = file
+        .replace_contents_future(
+            contents,
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+        )
+        .await
+    {
+        error!("Could not write exported conversation: {error}");
+        return Err(ConversationExportError::Write);
+    }
+
+    Ok(count)
+}
+
+/// Load the messages of the given room in the range of the given options.
+///
+/// The messages are returned in reverse chronological order.
+async fn load_messages(
+    room: &Room,
+    options: ConversationExportOptions,
+    cancellable: &gio::Cancellable,
+    progress: &impl Fn(usize),
+) -> Result<Vec<ExportedMessage>, ConversationExportError> {
+    let members = room.get_or_create_members();
+    let mut messages = Vec::new();
+    let mut last_token = None::<String>;
+
+    loop {
+        if cancellable.is_cancelled() {
+            return Err(ConversationExportError::Cancelled);
+        }
+
+        let matrix_room = room.matrix_room().clone();
+        let from = last_token.clone();
+        let handle = spawn_tokio!(async move {
+            let filter_types = vec![
+                MessageLikeEventType::RoomEncrypted.to_string(),
+                MessageLikeEventType::RoomMessage.to_string(),
+            ];
+            let filter = assign!(RoomEventFilter::default(), {
+                types: Some(filter_types),
+            });
+            let options = assign!(MessagesOptions::backward().from(from.as_deref()), {
+                limit: uint!(100),
+                filter,
+            });
+
+            matrix_room.messages(options).await
+        });
+
+        let response = match handle.await.expect("task was not aborted") {
+            Ok(response) => response,
+            Err(error) => {
+                error!("Could not load conversation to export: {error}");
+                return Err(ConversationExportError::Load);
+            }
+        };
+
+        let mut reached_start = response.end.is_none();
+
+        for event in response.chunk {
+            let Ok(AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+                SyncMessageLikeEvent::Original(mut message_event),
+            ))) = event.raw().deserialize()
+            else {
+                continue;
+            };
+
+            if options
+                .start
+                .is_some_and(|start| message_event.origin_server_ts < start)
+            {
+                // The next events are all older.
+                reached_start = true;
+                break;
+            }
+            if options
+                .end
+                .is_some_and(|end| message_event.origin_server_ts > end)
+            {
+                continue;
+            }
+
+            // Filter out edits, they should be bundled with the original event.
+            if matches!(
+                message_event.content.relates_to,
+                Some(Relation::Replacement(_))
+            ) {
+                continue;
+            }
+
+            // Apply bundled edit.
+            if let Some(Relation::Replacement(replacement)) = message_event
+                .unsigned
+                .relations
+                .replace
+                .as_ref()
+                .and_then(|e| e.content.relates_to.as_ref())
+            {
+                message_event
+                    .content
+                    .apply_replacement(replacement.new_content.clone());
+            }
+
+            let sender_name = members
+                .get_or_create(message_event.sender.clone())
+                .display_name();
+            let msgtype = message_event.content.msgtype;
+            let media = MediaMessage::from_message(&msgtype);
+
+            messages.push(ExportedMessage {
+                event_id: message_event.event_id,
+                sender: message_event.sender,
+                sender_name,
+                timestamp: message_event.origin_server_ts,
+                body: message_plain_body(&msgtype),
+                media_file: None,
+                media,
+            });
+        }
+
+        progress(messages.len());
+
+        if reached_start {
+            return Ok(messages);
+        }
+
+        last_token = response.end;
+    }
+}
+
+/// Download the media files of the given messages into a folder next to the
+/// given file.
+async fn save_media_files(
+    room: &Room,
+    file: &gio::File,
+    messages: &mut [ExportedMessage],
+    cancellable: &gio::Cancellable,
+) -> Result<(), ConversationExportError> {
+    let Some(parent) = file.parent() else {
+        return Err(ConversationExportError::Write);
+    };
+    let basename = file
+        .basename()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    // Translators: Do NOT translate the content between '{' and '}', this is a
+    // variable name corresponding to the name of the exported file.
+    let folder_name = gettext_f("{name} Files", &[("name", &basename)]);
+    let folder = parent.child(&folder_name);
+
+    if let Err(error) = folder.make_directory_with_parents(gio::Cancellable::NONE)
+        && !error.matches(gio::IOErrorEnum::Exists)
+    {
+        error!("Could not create folder for exported media files: {error}");
+        return Err(ConversationExportError::Write);
+    }
+
+    let Some(session) = room.session() else {
+        return Err(ConversationExportError::Load);
+    };
+    let client = session.client();
+
+    for (index, message) in messages.iter_mut().enumerate() {
+        if cancellable.is_cancelled() {
+            return Err(ConversationExportError::Cancelled);
+        }
+        let Some(media) = message.media.clone() else {
+            continue;
+        };
+
+        let timestamp = timestamp_to_date(message.timestamp);
+        // Prefix the name with the index of the message to avoid collisions.
+        let filename = format!(
+            "{index:05}-{}",
+            sanitize_filename(&media.filename(&timestamp))
+        );
+
+        let content = match media.into_content(&client).await {
+            Ok(content) => content,
+            Err(error) => {
+                warn!(
+                    "Could not download media file of {}: {error}",
+                    message.event_id
+                );
+                continue;
+            }
+        };
+
+        if let Err((_, error)) = folder
+            .child(&filename)
+            .replace_contents_future(
+                content,
+                None,
+                false,
+                gio::FileCreateFlags::REPLACE_DESTINATION,
+            )
+            .await
+        {
+            error!("Could not write exported media file: {error}");
+            return Err(ConversationExportError::Write);
+        }
+
+        message.media_file = Some(format!("{folder_name}/{filename}"));
+    }
+
+    Ok(())
+}
+
+/// Sanitize the given name of a file sent by a remote user, so it can be used
+/// safely as the name of a file in the export folder.
+///
+/// Only the last component of the path is kept, and leading dots are removed
+/// so the file is not hidden.
+fn sanitize_filename(name: &str) -> String {
+    let name = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('.')
+        .trim();
+
+    if name.is_empty() {
+        "file".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Render the given conversation as a HTML page.
+fn conversation_to_html(conversation: &ExportedConversation) -> String {
+    let title = conversation.room_name.escape_markup();
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; max-width: 50em; margin: auto; }}\n\
+         .message {{ margin: 1em 0; }}\n\
+         .sender {{ font-weight: bold; }}\n\
+         .time {{ color: gray; font-size: smaller; margin-left: 0.5em; }}\n\
+         .body {{ white-space: pre-wrap; }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>{title}</h1>\n"
+    );
+
+    for message in &conversation.messages {
+        let time = timestamp_to_date(message.timestamp)
+            .to_local()
+            .and_then(|datetime| datetime.format("%x %X"))
+            .map(String::from)
+            .unwrap_or_default();
+
+        let body = if let Some(media_file) = &message.media_file {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                glib::Uri::escape_string(media_file, Some("/"), true).escape_markup(),
+                message.body.escape_markup()
+            )
+        } else {
+            message.body.escape_markup()
+        };
+
+        let _ = write!(
+            html,
+            "<div class=\"message\" id=\"{event_id}\">\n\
+             <span class=\"sender\" title=\"{sender}\">{sender_name}</span>\
+             <span class=\"time\">{time}</span>\n\
+             <div class=\"body\">{body}</div>\n\
+             </div>\n",
+            event_id = message.event_id.as_str().escape_markup(),
+            sender = message.sender.as_str().escape_markup(),
+            sender_name = message.sender_name.escape_markup(),
+        );
+    }
+
+    if conversation.messages.is_empty() {
+        let _ = writeln!(
+            html,
+            "<p>{}</p>",
+            gettext("There are no messages in the chosen period.").escape_markup()
+        );
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
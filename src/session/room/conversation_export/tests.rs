@@ -0,0 +1,21 @@
+use super::sanitize_filename;
+
+#[test]
+fn sanitize_regular_filename() {
+    assert_eq!(sanitize_filename("image.png"), "image.png");
+    assert_eq!(sanitize_filename("my file.tar.gz"), "my file.tar.gz");
+}
+
+#[test]
+fn sanitize_malicious_filename() {
+    assert_eq!(
+        sanitize_filename("x/../../../.config/autostart/evil.desktop"),
+        "evil.desktop"
+    );
+    assert_eq!(sanitize_filename("..\\..\\evil.exe"), "evil.exe");
+    assert_eq!(sanitize_filename("/etc/passwd"), "passwd");
+    assert_eq!(sanitize_filename(".bashrc"), "bashrc");
+    assert_eq!(sanitize_filename(".."), "file");
+    assert_eq!(sanitize_filename("dir/"), "file");
+    assert_eq!(sanitize_filename(""), "file");
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use gtk::{
     glib,
     glib::{clone, closure_local},
@@ -6,7 +8,8 @@ use gtk::{
 };
 use matrix_sdk::room::RoomMember;
 use ruma::{
-    OwnedEventId, OwnedUserId,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId,
+    api::client::presence::get_presence,
     events::room::{
         member::MembershipState,
         power_levels::{NotificationPowerLevelType, PowerLevelAction, UserPowerLevel},
@@ -16,7 +19,12 @@ use ruma::{
 use tracing::{debug, error};
 
 use super::{MemberRole, Room};
-use crate::{components::PillSource, prelude::*, session::User, spawn, spawn_tokio};
+use crate::{
+    components::PillSource, prelude::*, session::User, spawn, spawn_tokio, utils::LoadingState,
+};
+
+/// The duration after which the presence of a member is considered outdated.
+const PRESENCE_TTL: Duration = Duration::from_secs(5 * 60);
 
 /// The possible states of membership of a user in a room.
 #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
@@ -92,6 +100,26 @@ mod imp {
         /// The timestamp of the latest activity of this member.
         #[property(get, set = Self::set_latest_activity, explicit_notify)]
         latest_activity: Cell<u64>,
+        /// The timestamp of the latest activity of this member according to
+        /// their presence, in milliseconds since Unix epoch.
+        ///
+        /// This is `0` if it is unknown.
+        #[property(get)]
+        last_active: Cell<u64>,
+        /// Whether this member is currently active, according to their
+        /// presence.
+        #[property(get)]
+        is_currently_active: Cell<bool>,
+        /// The loading state of the presence of this member.
+        ///
+        /// It is only `Loading` during the first request. It stays `Ready` or
+        /// `Error` while the presence is refreshed.
+        #[property(get, builder(LoadingState::default()))]
+        presence_state: Cell<LoadingState>,
+        /// The time when the presence of this member was last loaded.
+        pub(super) presence_loaded_at: Cell<Option<Instant>>,
+        /// Whether the presence of this member is currently being requested.
+        pub(super) is_loading_presence: Cell<bool>,
         power_level_handlers: RefCell<Vec<glib::SignalHandlerId>>,
     }
 
@@ -104,6 +132,11 @@ mod imp {
                 role: Default::default(),
                 membership: Default::default(),
                 latest_activity: Default::default(),
+                last_active: Default::default(),
+                is_currently_active: Default::default(),
+                presence_state: Default::default(),
+                presence_loaded_at: Default::default(),
+                is_loading_presence: Default::default(),
                 power_level_handlers: Default::default(),
             }
         }
@@ -225,6 +258,31 @@ mod imp {
             self.latest_activity.set(activity);
             self.obj().notify_latest_activity();
         }
+
+        /// Set the presence of this member.
+        pub(super) fn set_presence(&self, last_active: u64, is_currently_active: bool) {
+            let obj = self.obj();
+
+            if self.last_active.get() != last_active {
+                self.last_active.set(last_active);
+                obj.notify_last_active();
+            }
+
+            if self.is_currently_active.get() != is_currently_active {
+                self.is_currently_active.set(is_currently_active);
+                obj.notify_is_currently_active();
+            }
+        }
+
+        /// Set the loading state of the presence of this member.
+        pub(super) fn set_presence_state(&self, state: LoadingState) {
+            if self.presence_state.get() == state {
+                return;
+            }
+
+            self.presence_state.set(state);
+            self.obj().notify_presence_state();
+        }
     }
 }
 
@@ -301,6 +359,74 @@ impl Member {
         }
     }
 
+    /// The delay before the presence of this member should be loaded again.
+    ///
+    /// Returns `Duration::ZERO` if it was never loaded or if it is outdated,
+    /// and `None` if it is currently being loaded.
+    pub(crate) fn presence_refresh_delay(&self) -> Option<Duration> {
+        let imp = self.imp();
+
+        if imp.is_loading_presence.get() {
+            return None;
+        }
+
+        let delay = imp
+            .presence_loaded_at
+            .get()
+            .map(|loaded_at| PRESENCE_TTL.saturating_sub(loaded_at.elapsed()))
+            .unwrap_or_default();
+        Some(delay)
+    }
+
+    /// Load the presence of this member, if it was not loaded yet or if it is
+    /// outdated.
+    ///
+    /// The presence should be loaded lazily when it needs to be presented.
+    pub(crate) async fn load_presence(&self) {
+        let imp = self.imp();
+
+        if self.presence_refresh_delay() != Some(Duration::ZERO) {
+            return;
+        }
+
+        imp.is_loading_presence.set(true);
+        if imp.presence_state.get() == LoadingState::Initial {
+            imp.set_presence_state(LoadingState::Loading);
+        }
+
+        let client = self.session().client();
+        let request = get_presence::v3::Request::new(self.user_id().clone());
+        let handle = spawn_tokio!(async move { client.send(request).await });
+
+        match handle.await.expect("task was not aborted") {
+            Ok(response) => {
+                let last_active = response
+                    .last_active_ago
+                    .map(|ago| {
+                        let now = u64::from(MilliSecondsSinceUnixEpoch::now().get());
+                        let ago = u64::try_from(ago.as_millis()).unwrap_or(u64::MAX);
+                        now.saturating_sub(ago)
+                    })
+                    .unwrap_or_default();
+
+                imp.set_presence(last_active, response.currently_active.unwrap_or_default());
+                imp.set_presence_state(LoadingState::Ready);
+            }
+            Err(error) => {
+                // Presence is often disabled on homeservers, so this is not unusual.
+                debug!("Could not load presence of {}: {error}", self.user_id());
+
+                // Keep the previous presence if we could load it.
+                if imp.presence_state.get() != LoadingState::Ready {
+                    imp.set_presence_state(LoadingState::Error);
+                }
+            }
+        }
+
+        imp.presence_loaded_at.set(Some(Instant::now()));
+        imp.is_loading_presence.set(false);
+    }
+
     /// The IDs of the events sent by this member that can be redacted.
     pub(crate) fn redactable_events(&self) -> Vec<OwnedEventId> {
         self.room()
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::HashSet};
+use std::{cell::RefCell, collections::HashSet, time::Duration};
 
 use futures_util::StreamExt;
 use gettextrs::gettext;
@@ -16,17 +16,26 @@ use matrix_sdk::{
     send_queue::RoomSendQueueUpdate,
 };
 use ruma::{
-    EventId, MatrixToUri, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId, UserId,
+    EventId, MatrixToUri, OwnedEventId, OwnedMxcUri, OwnedRoomId, OwnedTransactionId, OwnedUserId,
+    RoomId, UserId,
     api::client::{
         error::{ErrorKind, RetryAfter},
+        membership::{InvitationRecipient, Invite3pidInit, invite_user},
         receipt::create_receipt::v3::ReceiptType as ApiReceiptType,
     },
-    events::room::{
-        guest_access::GuestAccess,
-        history_visibility::HistoryVisibility,
-        member::{MembershipState, RoomMemberEventContent, SyncRoomMemberEvent},
+    events::{
+        StateEventType,
+        room::{
+            guest_access::GuestAccess,
+            history_visibility::HistoryVisibility,
+            member::{MembershipState, RoomMemberEventContent, SyncRoomMemberEvent},
+            third_party_invite::RoomThirdPartyInviteEventContent,
+        },
+        space::{child::SpaceChildEventContent, parent::SpaceParentEventContent},
     },
     room_version_rules::RoomVersionRules,
+    serde::Raw,
+    thirdparty::Medium,
 };
 use serde::Deserialize;
 use tokio_stream::wrappers::BroadcastStream;
@@ -34,40 +43,57 @@ use tracing::{debug, error, warn};
 
 mod aliases;
 mod category;
+mod conversation_export;
 mod highlight_flags;
 mod join_rule;
 mod member;
 mod member_list;
 mod permissions;
+mod space_hierarchy;
+#[cfg(test)]
+mod tests;
 mod timeline;
 mod typing_list;
 
 pub(crate) use self::{
     aliases::{AddAltAliasError, RegisterLocalAliasError, RoomAliases},
     category::{RoomCategory, TargetRoomCategory},
+    conversation_export::{
+        ConversationExportError, ConversationExportFormat, ConversationExportOptions,
+        export_conversation,
+    },
     highlight_flags::HighlightFlags,
     join_rule::{JoinRule, JoinRuleValue},
     member::{Member, Membership},
     member_list::*,
     permissions::*,
+    space_hierarchy::{SpaceHierarchy, SpaceHierarchyItem},
     timeline::*,
     typing_list::TypingList,
 };
 use super::{
-    IdentityVerification, Session, User, notifications::NotificationsRoomSetting,
-    room_list::RoomMetainfo,
+    IdentityServer, IdentityVerification, Session, User,
+    notifications::{
+        NotificationPreview, NotificationPreviewEvent, NotificationPreviewEventContent,
+        NotificationSound, NotificationSoundEvent, NotificationSoundEventContent,
+        NotificationsRoomSetting,
+    },
+    room_list::{RoomInitPriority, RoomMetainfo},
 };
 use crate::{
     components::{AtRoom, AvatarImage, AvatarUriSource, PillSource},
     gettext_f,
     prelude::*,
     spawn, spawn_tokio,
-    utils::{BoundObjectWeakRef, string::linkify},
+    utils::{BoundObjectWeakRef, LoadingState, string::linkify},
 };
 
 /// The default duration in seconds that we wait for before retrying failed
 /// sending requests.
 const DEFAULT_RETRY_AFTER: u32 = 30;
+/// The maximum duration in seconds that we wait for before retrying failed
+/// sending requests, when backing off after repeated failures.
+const MAX_RETRY_AFTER: u32 = 15 * 60;
 
 mod imp {
     use std::{
@@ -176,6 +202,10 @@ mod imp {
         /// If it is not known, it will return `0`.
         #[property(get)]
         latest_activity: Cell<u64>,
+        /// The latest event that counts as activity in this room, if it is
+        /// loaded.
+        #[property(get, nullable)]
+        latest_event: RefCell<Option<Event>>,
         /// Whether this room is marked as unread.
         #[property(get)]
         is_marked_unread: Cell<bool>,
@@ -191,9 +221,30 @@ mod imp {
         /// The highlight state of the room.
         #[property(get)]
         highlight: Cell<HighlightFlags>,
+        /// The total number of unread notifications in the children of this
+        /// space.
+        #[property(get)]
+        space_notification_count: Cell<u64>,
+        /// The aggregated highlight state of the children of this space.
+        #[property(get)]
+        space_highlight: Cell<HighlightFlags>,
+        /// Whether there is an unread mention of the whole room.
+        pub(super) has_unread_at_room: Cell<bool>,
+        /// Whether there is an unread highlight for another reason than a
+        /// mention of the whole room.
+        has_unread_other_highlight: Cell<bool>,
         /// Whether this room is encrypted.
         #[property(get)]
         is_encrypted: Cell<bool>,
+        /// How much of the content of messages can be shown for this room.
+        ///
+        /// This takes into account the room setting, the global setting and
+        /// whether this room is encrypted.
+        #[property(get, builder(NotificationPreview::default()))]
+        notification_preview: Cell<NotificationPreview>,
+        /// The notification preview setting of this room, if it overrides the
+        /// global setting.
+        pub(super) notification_preview_setting: Cell<Option<NotificationPreview>>,
         /// The join rule of this room.
         #[property(get)]
         join_rule: JoinRule,
@@ -207,6 +258,8 @@ mod imp {
         #[property(get = Self::version)]
         version: PhantomData<String>,
         /// Whether this room is federated.
+        ///
+        /// This can only be set when the room is created.
         #[property(get = Self::federated)]
         federated: PhantomData<bool>,
         /// The list of members currently typing in this room.
@@ -216,6 +269,9 @@ mod imp {
         /// The notifications settings for this room.
         #[property(get, set = Self::set_notifications_setting, explicit_notify, builder(NotificationsRoomSetting::default()))]
         notifications_setting: Cell<NotificationsRoomSetting>,
+        /// Whether mentions of the whole room are muted in this room.
+        #[property(get = Self::at_room_muted, set = Self::set_at_room_muted, explicit_notify)]
+        at_room_muted: PhantomData<bool>,
         /// The permissions of our own user in this room
         #[property(get)]
         permissions: Permissions,
@@ -237,6 +293,17 @@ mod imp {
         pub(super) parent_spaces: RefCell<HashSet<OwnedRoomId>>,
         /// The child rooms of this space (only populated if this room is a space).
         pub(super) child_rooms: RefCell<HashSet<OwnedRoomId>>,
+        /// The hierarchy of this space, if it was requested.
+        pub(super) space_hierarchy: OnceCell<SpaceHierarchy>,
+        /// Whether the send queue of this room is stopped because of a
+        /// recoverable error.
+        #[property(get)]
+        is_send_queue_stuck: Cell<bool>,
+        /// The transaction ID of the latest item that failed to be sent, and
+        /// the number of consecutive times it failed.
+        send_queue_failures: RefCell<Option<(OwnedTransactionId, u32)>>,
+        /// The source ID of the scheduled retry of the send queue, if any.
+        send_queue_retry_source: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -286,33 +353,27 @@ mod imp {
             // Load space relationships asynchronously
             obj.load_space_relationships();
 
+            // Schedule the initialization of the room info, it can be expensive with a lot
+            // of rooms so rooms that are more likely to be presented are initialized first.
+            let matrix_room = self.matrix_room();
+            let priority = if matrix_room.is_favourite()
+                || matches!(matrix_room.state(), RoomState::Invited | RoomState::Knocked)
+            {
+                RoomInitPriority::High
+            } else {
+                RoomInitPriority::Deferred
+            };
+            if let Some(session) = obj.session() {
+                session.room_list().init_queue().enqueue(&obj, priority);
+            }
+
             spawn!(
                 glib::Priority::DEFAULT_IDLE,
                 clone!(
                     #[weak(rename_to = imp)]
                     self,
                     async move {
-                        imp.update_with_room_info(imp.matrix_room().clone_info())
-                            .await;
-                        imp.watch_room_info();
-
-                        imp.is_room_info_initialized.set(true);
-                        imp.obj().notify_is_room_info_initialized();
-
-                        // Only initialize the following after we have loaded the category of the
-                        // room since we only load them for some categories.
-
-                        // Preload the timeline of rooms that the user is likely to visit and for
-                        // which we offer to show the timeline.
-                        let preload = matches!(
-                            imp.category.get(),
-                            RoomCategory::Favorite
-                                | RoomCategory::Normal
-                                | RoomCategory::LowPriority
-                        );
-                        imp.live_timeline().set_preload(preload);
-
-                        imp.permissions.init(&imp.obj()).await;
+                        imp.load_own_member().await;
                     }
                 )
             );
@@ -323,7 +384,9 @@ mod imp {
                     #[weak(rename_to = imp)]
                     self,
                     async move {
-                        imp.load_own_member().await;
+                        let setting = imp.obj().notification_preview_setting().await;
+                        imp.notification_preview_setting.set(setting);
+                        imp.update_notification_preview();
                     }
                 )
             );
@@ -340,6 +403,36 @@ mod imp {
             }
         }
 
+        /// Initialize the info of this room.
+        ///
+        /// This is scheduled by the
+        /// [`RoomInitQueue`](crate::session::RoomInitQueue) of the room list.
+        pub(super) async fn init_room_info(&self) {
+            if self.is_room_info_initialized.get() {
+                return;
+            }
+
+            self.update_with_room_info(self.matrix_room().clone_info())
+                .await;
+            self.watch_room_info();
+
+            self.is_room_info_initialized.set(true);
+            self.obj().notify_is_room_info_initialized();
+
+            // Only initialize the following after we have loaded the category of the
+            // room since we only load them for some categories.
+
+            // Preload the timeline of rooms that the user is likely to visit and for
+            // which we offer to show the timeline.
+            let preload = matches!(
+                self.category.get(),
+                RoomCategory::Favorite | RoomCategory::Normal | RoomCategory::LowPriority
+            );
+            self.live_timeline().set_preload(preload);
+
+            self.permissions.init(&self.obj()).await;
+        }
+
         /// The room API of the SDK.
         pub(super) fn matrix_room(&self) -> &MatrixRoom {
             self.matrix_room.get().expect("matrix room was initialized")
@@ -1218,6 +1311,13 @@ mod imp {
                 self.set_is_read(!has_unread);
             }
 
+            self.has_unread_at_room
+                .set(!self.is_read.get() && timeline.has_unread_at_room().await);
+            self.has_unread_other_highlight.set(
+                self.has_unread_at_room.get()
+                    && timeline.has_unread_highlight_without_at_room().await,
+            );
+
             self.update_highlight();
         }
 
@@ -1232,7 +1332,7 @@ mod imp {
         }
 
         /// Update the highlight of the room from the current state.
-        fn update_highlight(&self) {
+        pub(super) fn update_highlight(&self) {
             let mut highlight = HighlightFlags::empty();
 
             if matches!(self.category.get(), RoomCategory::Left) {
@@ -1247,7 +1347,15 @@ mod imp {
             } else {
                 let counts = self.matrix_room().unread_notification_counts();
 
-                if counts.highlight_count > 0 {
+                let has_highlight = if self.has_unread_at_room.get() && self.at_room_muted() {
+                    // The homeserver counts mentions of the whole room as highlights, so we
+                    // need to ignore them.
+                    self.has_unread_other_highlight.get()
+                } else {
+                    counts.highlight_count > 0
+                };
+
+                if has_highlight || self.should_highlight_at_room() {
                     highlight = HighlightFlags::all();
                 } else {
                     highlight = HighlightFlags::BOLD;
@@ -1258,6 +1366,18 @@ mod imp {
             self.set_highlight(highlight);
         }
 
+        /// Whether an unread mention of the whole room should highlight this
+        /// room.
+        fn should_highlight_at_room(&self) -> bool {
+            self.has_unread_at_room.get()
+                && !self.at_room_muted()
+                && self.notifications_setting.get() != NotificationsRoomSetting::Mute
+                && self.session.upgrade().is_some_and(|session| {
+                    let notifications = session.notifications();
+                    notifications.enabled() && !notifications.settings().room_mentions_muted()
+                })
+        }
+
         /// Set the number of unread notifications of this room.
         fn set_notification_count(&self, count: u64) {
             if self.notification_count.get() == count {
@@ -1279,6 +1399,30 @@ mod imp {
             self.obj().notify_has_notifications();
         }
 
+        /// Update the notification preview to use for this room.
+        pub(super) fn update_notification_preview(&self) {
+            let preview = NotificationPreview::resolve(
+                self.notification_preview_setting.get(),
+                self.is_encrypted.get(),
+            );
+
+            if self.notification_preview.get() == preview {
+                return;
+            }
+
+            self.notification_preview.set(preview);
+            self.obj().notify_notification_preview();
+        }
+
+        /// Set the latest event that counts as activity in this room.
+        ///
+        /// This always notifies, because the content of the same event might
+        /// have changed, for example after it was decrypted.
+        pub(super) fn set_latest_event(&self, event: Option<Event>) {
+            self.latest_event.replace(event);
+            self.obj().notify_latest_event();
+        }
+
         /// Update whether the room is encrypted from the SDK.
         async fn update_is_encrypted(&self) {
             let matrix_room = self.matrix_room();
@@ -1291,6 +1435,7 @@ mod imp {
                     if state.is_encrypted() {
                         self.is_encrypted.set(true);
                         self.obj().notify_is_encrypted();
+                        self.update_notification_preview();
                     }
                 }
                 Err(error) => {
@@ -1428,9 +1573,40 @@ mod imp {
             }
 
             self.notifications_setting.set(setting);
+
+            if self.has_unread_at_room.get() {
+                self.update_highlight();
+            }
+
             self.obj().notify_notifications_setting();
         }
 
+        /// Whether mentions of the whole room are muted in this room.
+        fn at_room_muted(&self) -> bool {
+            self.session
+                .upgrade()
+                .is_some_and(|session| session.settings().is_at_room_muted(self.room_id()))
+        }
+
+        /// Set whether mentions of the whole room are muted in this room.
+        fn set_at_room_muted(&self, muted: bool) {
+            if self.at_room_muted() == muted {
+                return;
+            }
+
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            session.settings().set_at_room_muted(self.room_id(), muted);
+
+            if self.has_unread_at_room.get() {
+                self.update_highlight();
+            }
+
+            self.obj().notify_at_room_muted();
+        }
+
         /// Set an ongoing verification in this room.
         fn set_verification(&self, verification: Option<IdentityVerification>) {
             if self.verification.obj().is_some() && verification.is_some() {
@@ -1557,32 +1733,13 @@ mod imp {
                     .for_each(move |update| {
                         let room_weak = room_weak.clone();
                         async move {
-                            let Ok(RoomSendQueueUpdate::SendError {
-                                error,
-                                is_recoverable: true,
-                                ..
-                            }) = update
-                            else {
-                                return;
-                            };
-
-                            let ctx = glib::MainContext::default();
-                            ctx.spawn(async move {
-                                spawn!(async move {
-                                    let Some(obj) = room_weak.upgrade() else {
-                                        return;
-                                    };
-                                    let Some(session) = obj.session() else {
-                                        return;
-                                    };
-
-                                    if session.is_offline() {
-                                        // The queue will be restarted when the session is back
-                                        // online.
-                                        return;
-                                    }
-
-                                    let duration = match error.client_api_error_kind() {
+                            let update = match update {
+                                Ok(RoomSendQueueUpdate::SendError {
+                                    transaction_id,
+                                    error,
+                                    is_recoverable,
+                                }) => {
+                                    let retry_after = match error.client_api_error_kind() {
                                         Some(ErrorKind::LimitExceeded {
                                             retry_after: Some(retry_after),
                                         }) => match retry_after {
@@ -1593,17 +1750,26 @@ mod imp {
                                         },
                                         _ => None,
                                     };
-                                    let retry_after = duration
-                                        .and_then(|d| d.as_secs().try_into().ok())
-                                        .unwrap_or(DEFAULT_RETRY_AFTER);
-
-                                    glib::timeout_add_seconds_local_once(retry_after, move || {
-                                        let matrix_room = obj.matrix_room().clone();
-                                        // Getting a room's send queue requires a tokio executor.
-                                        spawn_tokio!(async move {
-                                            matrix_room.send_queue().set_enabled(true);
-                                        });
-                                    });
+
+                                    SendQueueUpdate::Error {
+                                        transaction_id,
+                                        is_recoverable,
+                                        retry_after,
+                                    }
+                                }
+                                Ok(
+                                    RoomSendQueueUpdate::SentEvent { transaction_id, .. }
+                                    | RoomSendQueueUpdate::CancelledLocalEvent { transaction_id },
+                                ) => SendQueueUpdate::Done(transaction_id),
+                                _ => return,
+                            };
+
+                            let ctx = glib::MainContext::default();
+                            ctx.spawn(async move {
+                                spawn!(async move {
+                                    if let Some(obj) = room_weak.upgrade() {
+                                        obj.imp().handle_send_queue_update(update);
+                                    }
                                 });
                             });
                         }
@@ -1612,6 +1778,122 @@ mod imp {
             });
         }
 
+        /// Handle the given update of the send queue.
+        fn handle_send_queue_update(&self, update: SendQueueUpdate) {
+            match update {
+                SendQueueUpdate::Error {
+                    transaction_id,
+                    is_recoverable,
+                    retry_after,
+                } => {
+                    if !is_recoverable {
+                        // The queue is not stopped, the user needs to discard the item.
+                        return;
+                    }
+
+                    let failures = {
+                        let mut send_queue_failures = self.send_queue_failures.borrow_mut();
+                        let failures = match send_queue_failures.take() {
+                            Some((failed_transaction_id, failures))
+                                if failed_transaction_id == transaction_id =>
+                            {
+                                failures.saturating_add(1)
+                            }
+                            _ => 1,
+                        };
+                        *send_queue_failures = Some((transaction_id, failures));
+                        failures
+                    };
+
+                    self.set_is_send_queue_stuck(true);
+
+                    let Some(session) = self.session.upgrade() else {
+                        return;
+                    };
+
+                    if session.is_offline() {
+                        // The queue will be restarted when the session is back
+                        // online.
+                        return;
+                    }
+
+                    // Use an exponential backoff for repeated failures of the same item, but
+                    // always respect the delay requested by the server.
+                    let backoff = DEFAULT_RETRY_AFTER
+                        .saturating_mul(2_u32.saturating_pow(failures - 1))
+                        .min(MAX_RETRY_AFTER);
+                    let retry_after = retry_after
+                        .and_then(|d| u32::try_from(d.as_secs()).ok())
+                        .map_or(backoff, |server_retry_after| {
+                            server_retry_after.max(backoff)
+                        });
+
+                    debug!(
+                        room_id = self.room_id_string(),
+                        "Retrying to send messages in {retry_after}s after {failures} failures"
+                    );
+
+                    let source = glib::timeout_add_seconds_local_once(
+                        retry_after,
+                        clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move || {
+                                imp.send_queue_retry_source.take();
+                                imp.enable_send_queue();
+                            }
+                        ),
+                    );
+
+                    if let Some(source) = self.send_queue_retry_source.replace(Some(source)) {
+                        source.remove();
+                    }
+                }
+                SendQueueUpdate::Done(transaction_id) => {
+                    let is_failed_item = self.send_queue_failures.borrow().as_ref().is_some_and(
+                        |(failed_transaction_id, _)| *failed_transaction_id == transaction_id,
+                    );
+
+                    if is_failed_item {
+                        self.send_queue_failures.take();
+
+                        if self.is_send_queue_stuck.get() {
+                            // If the item was cancelled, the queue is still stopped.
+                            self.enable_send_queue();
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Set whether the send queue of this room is stopped because of a
+        /// recoverable error.
+        fn set_is_send_queue_stuck(&self, is_stuck: bool) {
+            if self.is_send_queue_stuck.get() == is_stuck {
+                return;
+            }
+
+            self.is_send_queue_stuck.set(is_stuck);
+            self.obj().notify_is_send_queue_stuck();
+        }
+
+        /// Enable the send queue of this room immediately.
+        ///
+        /// Cancels any scheduled retry.
+        pub(super) fn enable_send_queue(&self) {
+            if let Some(source) = self.send_queue_retry_source.take() {
+                source.remove();
+            }
+
+            self.set_is_send_queue_stuck(false);
+
+            let matrix_room = self.matrix_room().clone();
+            // Getting a room's send queue requires a tokio executor.
+            spawn_tokio!(async move {
+                matrix_room.send_queue().set_enabled(true);
+            });
+        }
+
         /// Change the category of this room.
         ///
         /// This makes the necessary to propagate the category to the
@@ -1730,6 +2012,27 @@ impl Room {
         self.imp().matrix_room()
     }
 
+    /// Initialize the info of this room.
+    ///
+    /// This should only be called by the
+    /// [`RoomInitQueue`](crate::session::RoomInitQueue), use
+    /// [`Self::prioritize_room_info_init()`] to request the room info sooner.
+    pub(crate) async fn init_room_info(&self) {
+        self.imp().init_room_info().await;
+    }
+
+    /// Make sure that the info of this room is initialized as soon as
+    /// possible, because it is needed.
+    pub(crate) fn prioritize_room_info_init(&self) {
+        if self.is_room_info_initialized() {
+            return;
+        }
+
+        if let Some(session) = self.session() {
+            session.room_list().init_queue().prioritize(self);
+        }
+    }
+
     /// The ID of this room.
     pub(crate) fn room_id(&self) -> &RoomId {
         self.imp().room_id()
@@ -1764,6 +2067,21 @@ impl Room {
         self.imp().successor_id.get()
     }
 
+    /// The hierarchy of this space.
+    ///
+    /// The hierarchy is cached, it is only loaded when requested.
+    pub(crate) fn space_hierarchy(&self) -> SpaceHierarchy {
+        self.imp()
+            .space_hierarchy
+            .get_or_init(|| SpaceHierarchy::new(self))
+            .clone()
+    }
+
+    /// Retry to send the messages in the send queue of this room immediately.
+    pub(crate) fn retry_send_queue(&self) {
+        self.imp().enable_send_queue();
+    }
+
     /// The `matrix.to` URI representation for this room.
     pub(crate) async fn matrix_to_uri(&self) -> MatrixToUri {
         let matrix_room = self.matrix_room().clone();
@@ -1841,6 +2159,13 @@ impl Room {
         let matrix_timeline = self.live_timeline().matrix_timeline();
         let identifier = event.identifier();
 
+        let is_adding = !event
+            .reactions()
+            .reaction_group_by_key(&key)
+            .is_some_and(|group| group.has_own_user());
+        let session = self.session();
+        let settings_key = is_adding.then(|| key.clone());
+
         let handle =
             spawn_tokio!(async move { matrix_timeline.toggle_reaction(&identifier, &key).await });
 
@@ -1849,6 +2174,10 @@ impl Room {
             return Err(());
         }
 
+        if let Some((session, key)) = session.zip(settings_key) {
+            session.settings().record_reaction_usage(&key);
+        }
+
         Ok(())
     }
 
@@ -1897,8 +2226,178 @@ impl Room {
         }
     }
 
+    /// Update the highlight of this room after a change of the settings about
+    /// mentions of the whole room.
+    pub(crate) fn update_at_room_highlight(&self) {
+        let imp = self.imp();
+
+        if imp.has_unread_at_room.get() {
+            imp.update_highlight();
+        }
+    }
+
+    /// The sound played for the notifications of this room.
+    ///
+    /// It is read from the room account data in the store.
+    pub(crate) async fn notification_sound(&self) -> NotificationSound {
+        let matrix_room = self.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .account_data(NotificationSound::event_type())
+                .await
+        });
+
+        let raw = match handle.await.expect("task was not aborted") {
+            Ok(Some(raw)) => raw,
+            Ok(None) => return NotificationSound::default(),
+            Err(error) => {
+                error!("Could not load notification sound of room: {error}");
+                return NotificationSound::default();
+            }
+        };
+
+        match raw.deserialize_as_unchecked::<NotificationSoundEvent>() {
+            Ok(event) => event.content.into(),
+            Err(error) => {
+                error!("Could not deserialize notification sound of room: {error}");
+                NotificationSound::default()
+            }
+        }
+    }
+
+    /// Set the sound played for the notifications of this room.
+    ///
+    /// It is stored in the room account data.
+    pub(crate) async fn set_notification_sound(&self, sound: NotificationSound) -> Result<(), ()> {
+        let content =
+            match serde_json::value::to_raw_value(&NotificationSoundEventContent::from(sound)) {
+                Ok(content) => Raw::from_json(content),
+                Err(error) => {
+                    error!("Could not serialize notification sound of room: {error}");
+                    return Err(());
+                }
+            };
+
+        let matrix_room = self.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .set_account_data_raw(NotificationSound::event_type(), content)
+                .await
+        });
+
+        match handle.await.expect("task was not aborted") {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Could not change notification sound of room: {error}");
+                Err(())
+            }
+        }
+    }
+
+    /// The notification preview setting of this room.
+    ///
+    /// Returns `None` if the global setting should be used. It is read from the
+    /// room account data in the store.
+    pub(crate) async fn notification_preview_setting(&self) -> Option<NotificationPreview> {
+        let matrix_room = self.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .account_data(NotificationPreview::event_type())
+                .await
+        });
+
+        let raw = match handle.await.expect("task was not aborted") {
+            Ok(Some(raw)) => raw,
+            Ok(None) => return None,
+            Err(error) => {
+                error!("Could not load notification preview setting of room: {error}");
+                return None;
+            }
+        };
+
+        match raw.deserialize_as_unchecked::<NotificationPreviewEvent>() {
+            Ok(event) => event.content.into(),
+            Err(error) => {
+                error!("Could not deserialize notification preview setting of room: {error}");
+                None
+            }
+        }
+    }
+
+    /// Set the notification preview setting of this room.
+    ///
+    /// Use `None` to use the global setting. It is stored in the room account
+    /// data.
+    pub(crate) async fn set_notification_preview_setting(
+        &self,
+        preview: Option<NotificationPreview>,
+    ) -> Result<(), ()> {
+        let content = match serde_json::value::to_raw_value(&NotificationPreviewEventContent::from(
+            preview,
+        )) {
+            Ok(content) => Raw::from_json(content),
+            Err(error) => {
+                error!("Could not serialize notification preview setting of room: {error}");
+                return Err(());
+            }
+        };
+
+        let matrix_room = self.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .set_account_data_raw(NotificationPreview::event_type(), content)
+                .await
+        });
+
+        match handle.await.expect("task was not aborted") {
+            Ok(_) => {
+                let imp = self.imp();
+                imp.notification_preview_setting.set(preview);
+                imp.update_notification_preview();
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not change notification preview setting of room: {error}");
+                Err(())
+            }
+        }
+    }
+
+    /// Update the notification preview to use for this room.
+    ///
+    /// This should be called when the global setting changed.
+    pub(crate) fn update_notification_preview(&self) {
+        self.imp().update_notification_preview();
+    }
+
+    /// Whether typing notifications are sent in this room.
+    ///
+    /// Uses the override of the session settings for this room, if any.
+    /// Otherwise, uses the global settings of the session, which might
+    /// exclude public rooms.
+    pub(crate) fn typing_notifications_enabled(&self) -> bool {
+        let Some(session) = self.session() else {
+            return false;
+        };
+        let settings = session.settings();
+
+        if let Some(enabled) = settings.typing_override(self.room_id()) {
+            return enabled;
+        }
+
+        settings.typing_enabled()
+            && (settings.typing_in_public_rooms_enabled()
+                || self.join_rule().value() != JoinRuleValue::Public)
+    }
+
     /// Send a typing notification for this room, with the given typing state.
+    ///
+    /// Does nothing if typing notifications are disabled in this room.
     pub(crate) fn send_typing_notification(&self, is_typing: bool) {
+        if !self.typing_notifications_enabled() {
+            return;
+        }
+
         let matrix_room = self.matrix_room();
         if matrix_room.state() != RoomState::Joined {
             return;
@@ -2051,6 +2550,70 @@ impl Room {
         }
     }
 
+    /// Invite the person with the given email address to this room, via the
+    /// given identity server.
+    ///
+    /// This should be used when the person does not have a Matrix account yet.
+    /// The identity server will send them an invite by email.
+    pub(crate) async fn invite_by_email(
+        &self,
+        email: String,
+        identity_server: &IdentityServer,
+        id_access_token: String,
+    ) -> MatrixResult<()> {
+        let matrix_room = self.matrix_room().clone();
+        let recipient = InvitationRecipient::ThirdPartyId(
+            Invite3pidInit {
+                id_server: identity_server.server_name(),
+                id_access_token,
+                medium: Medium::Email,
+                address: email,
+            }
+            .into(),
+        );
+
+        let handle = spawn_tokio!(async move {
+            let request =
+                invite_user::v3::Request::new(matrix_room.room_id().to_owned(), recipient);
+            matrix_room.client().send(request).await
+        });
+
+        handle.await.expect("task was not aborted")?;
+        Ok(())
+    }
+
+    /// The display names of the people with a pending third-party invite to
+    /// this room.
+    ///
+    /// These are people who were invited by email and did not join Matrix yet.
+    pub(crate) async fn pending_third_party_invites(&self) -> Vec<String> {
+        let matrix_room = self.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .get_state_events_static::<RoomThirdPartyInviteEventContent>()
+                .await
+        });
+
+        let events = match handle.await.expect("task was not aborted") {
+            Ok(events) => events,
+            Err(error) => {
+                error!("Could not get third-party invites: {error}");
+                return Vec::new();
+            }
+        };
+
+        // Revoked invites have an empty content, so they fail to deserialize.
+        events
+            .into_iter()
+            .filter_map(|raw| raw.deserialize().ok())
+            .filter_map(|event| {
+                event
+                    .original_content()
+                    .map(|content| content.display_name.clone())
+            })
+            .collect()
+    }
+
     /// Kick the given users from this room.
     ///
     /// The users are a list of `(user_id, reason)` tuples.
@@ -2196,6 +2759,75 @@ impl Room {
         }
     }
 
+    /// Set the name of our own user in this room only.
+    pub(crate) async fn set_own_room_display_name(
+        &self,
+        display_name: Option<String>,
+    ) -> MatrixResult<()> {
+        self.update_own_member_content(move |content| content.displayname = display_name)
+            .await
+    }
+
+    /// Set the avatar of our own user in this room only.
+    pub(crate) async fn set_own_room_avatar_url(
+        &self,
+        avatar_url: Option<OwnedMxcUri>,
+    ) -> MatrixResult<()> {
+        self.update_own_member_content(move |content| content.avatar_url = avatar_url)
+            .await
+    }
+
+    /// Use the global profile of our own user in this room.
+    pub(crate) async fn reset_own_room_profile(&self) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let user = session.user();
+
+        let display_name = user.has_display_name().then(|| user.display_name());
+        let avatar_url = user.avatar_data().image().and_then(|image| image.uri());
+
+        self.update_own_member_content(move |content| {
+            content.displayname = display_name;
+            content.avatar_url = avatar_url;
+        })
+        .await
+    }
+
+    /// Send a new member event for our own user, with the current content
+    /// modified by the given function.
+    ///
+    /// This is how the profile of a user can be changed in a single room.
+    async fn update_own_member_content<F>(&self, f: F) -> MatrixResult<()>
+    where
+        F: FnOnce(&mut RoomMemberEventContent) + Send + 'static,
+    {
+        let matrix_room = self.matrix_room().clone();
+        let room_id = self.room_id().to_owned();
+        let handle = spawn_tokio!(async move {
+            let own_user_id = matrix_room.own_user_id().to_owned();
+            let own_member = matrix_room.get_member_no_sync(&own_user_id).await?;
+
+            let mut content = own_member
+                .and_then(|member| member.event().original_content().cloned())
+                .unwrap_or_else(|| RoomMemberEventContent::new(MembershipState::Join));
+            f(&mut content);
+
+            matrix_room
+                .send_state_event_for_key(&own_user_id, content)
+                .await?;
+            Ok(())
+        });
+
+        let result = handle.await.expect("task was not aborted");
+
+        if let Err(error) = &result {
+            error!("Could not change own profile in room {room_id}: {error}");
+        }
+
+        result
+    }
+
     /// Forget a room that is left.
     pub(crate) async fn forget(&self) -> MatrixResult<()> {
         if self.category() != RoomCategory::Left {
@@ -2235,7 +2867,12 @@ impl Room {
 
         for event in events {
             if event.counts_as_activity(own_user_id) {
-                latest_activity = latest_activity.max(event.origin_server_ts().get().into());
+                let timestamp = event.origin_server_ts().get().into();
+
+                if timestamp >= latest_activity {
+                    latest_activity = timestamp;
+                    self.imp().set_latest_event(Some(event.clone()));
+                }
                 break;
             }
         }
@@ -2291,6 +2928,25 @@ impl Room {
         result
     }
 
+    /// Set the aggregated unread counts of the children of this space.
+    pub(crate) fn set_space_unread_counts(
+        &self,
+        notification_count: u64,
+        highlight: HighlightFlags,
+    ) {
+        let imp = self.imp();
+
+        if imp.space_notification_count.get() != notification_count {
+            imp.space_notification_count.set(notification_count);
+            self.notify_space_notification_count();
+        }
+
+        if imp.space_highlight.get() != highlight {
+            imp.space_highlight.set(highlight);
+            self.notify_space_highlight();
+        }
+    }
+
     /// Check if this room is not in any space (orphaned).
     pub(crate) fn is_orphaned(&self) -> bool {
         self.imp().parent_spaces.borrow().is_empty()
@@ -2301,11 +2957,161 @@ impl Room {
         self.imp().child_rooms.borrow().iter().cloned().collect()
     }
 
+    /// Remove a parent space from this room.
+    pub(crate) fn remove_parent_space(&self, space_id: &RoomId) {
+        self.imp().parent_spaces.borrow_mut().remove(space_id);
+    }
+
+    /// Remove a child room from this space.
+    pub(crate) fn remove_child_room(&self, room_id: &RoomId) {
+        self.imp().child_rooms.borrow_mut().remove(room_id);
+    }
+
+    /// Add the given room as a child of this space.
+    ///
+    /// If `set_parent` is `true` and we are allowed to, this space is also
+    /// set as the canonical parent of the child.
+    pub(crate) async fn add_space_child(
+        &self,
+        child: &Room,
+        suggested: bool,
+        set_parent: bool,
+    ) -> Result<(), ()> {
+        let Some(session) = self.session() else {
+            return Err(());
+        };
+        let via = vec![session.user_id().server_name().to_owned()];
+
+        let space_id = self.room_id().to_owned();
+        let child_id = child.room_id().to_owned();
+
+        let mut content = SpaceChildEventContent::new(via.clone());
+        content.suggested = suggested;
+
+        let matrix_room = self.matrix_room().clone();
+        let state_key = child_id.clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .send_state_event_for_key(&state_key, content)
+                .await
+        });
+
+        if let Err(error) = handle.await.expect("task was not aborted") {
+            error!("Could not add child {child_id} to space {space_id}: {error}");
+            return Err(());
+        }
+
+        self.add_child_room(child_id.clone());
+        child.add_parent_space(space_id.clone());
+
+        if set_parent && child.permissions().can_change_space_parents() {
+            let mut content = SpaceParentEventContent::new(via);
+            content.canonical = true;
+
+            let matrix_room = child.matrix_room().clone();
+            let state_key = space_id.clone();
+            let handle = spawn_tokio!(async move {
+                matrix_room
+                    .send_state_event_for_key(&state_key, content)
+                    .await
+            });
+
+            // The child relationship is enough for the room to appear in the
+            // space, so this is not a fatal error.
+            if let Err(error) = handle.await.expect("task was not aborted") {
+                warn!("Could not set space {space_id} as parent of {child_id}: {error}");
+            }
+        }
+
+        self.refresh_space_hierarchy();
+
+        Ok(())
+    }
+
+    /// Remove the room with the given ID from the children of this space.
+    ///
+    /// If the room is known and we are allowed to, this space is also removed
+    /// from the parents of the room.
+    pub(crate) async fn remove_space_child(&self, child_id: &RoomId) -> Result<(), ()> {
+        let space_id = self.room_id().to_owned();
+        let child_id = child_id.to_owned();
+
+        // Sending an empty content removes the relationship.
+        let matrix_room = self.matrix_room().clone();
+        let state_key = child_id.clone();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .send_state_event_raw(
+                    &StateEventType::SpaceChild.to_string(),
+                    state_key.as_str(),
+                    serde_json::json!({}),
+                )
+                .await
+        });
+
+        if let Err(error) = handle.await.expect("task was not aborted") {
+            error!("Could not remove child {child_id} from space {space_id}: {error}");
+            return Err(());
+        }
+
+        self.remove_child_room(&child_id);
+
+        let child = self
+            .session()
+            .and_then(|session| session.room_list().get(&child_id));
+        if let Some(child) = child {
+            child.remove_parent_space(&space_id);
+
+            if child.permissions().can_change_space_parents() {
+                let matrix_room = child.matrix_room().clone();
+                let state_key = space_id.clone();
+                let handle = spawn_tokio!(async move {
+                    let has_parent_event = matrix_room
+                        .get_state_event_static_for_key::<SpaceParentEventContent, _>(&state_key)
+                        .await?
+                        .is_some();
+
+                    if has_parent_event {
+                        matrix_room
+                            .send_state_event_raw(
+                                &StateEventType::SpaceParent.to_string(),
+                                state_key.as_str(),
+                                serde_json::json!({}),
+                            )
+                            .await?;
+                    }
+
+                    MatrixResult::Ok(())
+                });
+
+                if let Err(error) = handle.await.expect("task was not aborted") {
+                    warn!("Could not remove space {space_id} from parents of {child_id}: {error}");
+                }
+            }
+        }
+
+        self.refresh_space_hierarchy();
+
+        Ok(())
+    }
+
+    /// Reload the hierarchy of this space, if it was already loaded.
+    fn refresh_space_hierarchy(&self) {
+        let Some(hierarchy) = self.imp().space_hierarchy.get().cloned() else {
+            return;
+        };
+
+        if hierarchy.loading_state() == LoadingState::Initial {
+            return;
+        }
+
+        spawn!(async move {
+            hierarchy.load().await;
+        });
+    }
+
     /// Load space relationships from Matrix state events.
     pub(crate) fn load_space_relationships(&self) {
-        use ruma::events::space::child::SpaceChildEventContent;
-        use ruma::events::space::parent::SpaceParentEventContent;
-
         let matrix_room = self.matrix_room().clone();
         let room_id = matrix_room.room_id().to_owned();
         let is_space = matrix_room.is_space();
@@ -2392,6 +3198,7 @@ impl Room {
                 };
                 let room_list = session.room_list();
                 let this_room_id = room.room_id().to_owned();
+                let has_relationships = !child_room_ids.is_empty() || !parent_space_ids.is_empty();
 
                 // Add children and establish parent relationship on the child side
                 for child_id in child_room_ids {
@@ -2420,6 +3227,10 @@ impl Room {
                         parent_space.add_child_room(this_room_id.clone());
                     }
                 }
+
+                if has_relationships {
+                    room_list.emit_space_relationships_changed();
+                }
             }
         ));
     }
@@ -2454,18 +3265,73 @@ impl From<HistoryVisibility> for HistoryVisibilityValue {
     }
 }
 
-impl From<HistoryVisibilityValue> for HistoryVisibility {
-    fn from(value: HistoryVisibilityValue) -> Self {
+impl HistoryVisibilityValue {
+    /// The values that can be selected by the user, in the order they are
+    /// presented.
+    pub(crate) const SELECTABLE: [Self; 4] = [
+        Self::WorldReadable,
+        Self::Shared,
+        Self::Invited,
+        Self::Joined,
+    ];
+
+    /// The nick of this value, as used by actions targeting it.
+    pub(crate) fn nick(self) -> &'static str {
+        match self {
+            Self::WorldReadable => "world-readable",
+            Self::Shared => "shared",
+            Self::Invited => "invited",
+            Self::Joined => "joined",
+            Self::Unsupported => "unsupported",
+        }
+    }
+
+    /// The localized description of this value.
+    pub(crate) fn description(self) -> String {
+        match self {
+            Self::WorldReadable => gettext("Anyone, even if they are not in the room"),
+            Self::Shared => gettext("Members only, since this option was selected"),
+            Self::Invited => gettext("Members only, since they were invited"),
+            Self::Joined => gettext("Members only, since they joined the room"),
+            Self::Unsupported => gettext("Unsupported rule"),
+        }
+    }
+}
+
+impl TryFrom<HistoryVisibilityValue> for HistoryVisibility {
+    type Error = HistoryVisibilityValue;
+
+    /// Convert the given value to a history visibility.
+    ///
+    /// Returns the value as an error if it is [`HistoryVisibilityValue::Unsupported`].
+    fn try_from(value: HistoryVisibilityValue) -> Result<Self, Self::Error> {
         match value {
-            HistoryVisibilityValue::Invited => Self::Invited,
-            HistoryVisibilityValue::Joined => Self::Joined,
-            HistoryVisibilityValue::Shared => Self::Shared,
-            HistoryVisibilityValue::WorldReadable => Self::WorldReadable,
-            HistoryVisibilityValue::Unsupported => unimplemented!(),
+            HistoryVisibilityValue::Invited => Ok(Self::Invited),
+            HistoryVisibilityValue::Joined => Ok(Self::Joined),
+            HistoryVisibilityValue::Shared => Ok(Self::Shared),
+            HistoryVisibilityValue::WorldReadable => Ok(Self::WorldReadable),
+            HistoryVisibilityValue::Unsupported => Err(value),
         }
     }
 }
 
+/// An update of the send queue of a room that we handle.
+#[derive(Debug)]
+enum SendQueueUpdate {
+    /// Sending an item failed.
+    Error {
+        /// The transaction ID of the item.
+        transaction_id: OwnedTransactionId,
+        /// Whether the error is recoverable.
+        is_recoverable: bool,
+        /// The duration to wait for before retrying, as requested by the
+        /// server.
+        retry_after: Option<Duration>,
+    },
+    /// The item with the given transaction ID was sent or cancelled.
+    Done(OwnedTransactionId),
+}
+
 /// The position of the receipt to send.
 #[derive(Debug, Clone)]
 pub(crate) enum ReceiptPosition {
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{collections::BTreeSet, fmt};
 
 use gettextrs::gettext;
 use gtk::{
@@ -12,9 +12,13 @@ use ruma::{
     Int, OwnedUserId, UserId,
     events::{
         MessageLikeEventType, StateEventType, SyncStateEvent,
-        room::power_levels::{
-            NotificationPowerLevelType, PowerLevelAction, PowerLevelUserAction, RoomPowerLevels,
-            RoomPowerLevelsEventContent, RoomPowerLevelsSource, UserPowerLevel,
+        room::{
+            member::MembershipState,
+            power_levels::{
+                NotificationPowerLevelType, PowerLevelAction, PowerLevelUserAction,
+                RoomPowerLevels, RoomPowerLevelsEventContent, RoomPowerLevelsSource,
+                UserPowerLevel,
+            },
         },
     },
     int,
@@ -130,6 +134,17 @@ mod imp {
         /// Whether our own member can notify the whole room.
         #[property(get)]
         can_notify_room: Cell<bool>,
+        /// Whether our own member can add or remove children of the room, if
+        /// it is a space.
+        #[property(get)]
+        can_change_space_children: Cell<bool>,
+        /// Whether our own member can add or remove parent spaces of the room.
+        #[property(get)]
+        can_change_space_parents: Cell<bool>,
+        /// Whether our own member can change their name and avatar in this
+        /// room only.
+        #[property(get)]
+        can_change_own_profile: Cell<bool>,
     }
 
     impl Default for Permissions {
@@ -155,6 +170,9 @@ mod imp {
                 can_redact_own: Default::default(),
                 can_redact_other: Default::default(),
                 can_notify_room: Default::default(),
+                can_change_space_children: Default::default(),
+                can_change_space_parents: Default::default(),
+                can_change_own_profile: Default::default(),
             }
         }
     }
@@ -293,6 +311,9 @@ mod imp {
             self.update_can_redact_own();
             self.update_can_redact_other();
             self.update_can_notify_room();
+            self.update_can_change_space_children();
+            self.update_can_change_space_parents();
+            self.update_can_change_own_profile();
             self.obj().emit_by_name::<()>("changed", &[]);
         }
 
@@ -477,6 +498,51 @@ mod imp {
             self.can_notify_room.set(can_notify_room);
             self.obj().notify_can_notify_room();
         }
+
+        /// Update whether our own member can add or remove children of the
+        /// room.
+        fn update_can_change_space_children(&self) {
+            let can_change_space_children =
+                self.is_allowed_to(PowerLevelAction::SendState(StateEventType::SpaceChild));
+
+            if self.can_change_space_children.get() == can_change_space_children {
+                return;
+            }
+
+            self.can_change_space_children
+                .set(can_change_space_children);
+            self.obj().notify_can_change_space_children();
+        }
+
+        /// Update whether our own member can add or remove parent spaces of
+        /// the room.
+        fn update_can_change_space_parents(&self) {
+            let can_change_space_parents =
+                self.is_allowed_to(PowerLevelAction::SendState(StateEventType::SpaceParent));
+
+            if self.can_change_space_parents.get() == can_change_space_parents {
+                return;
+            }
+
+            self.can_change_space_parents.set(can_change_space_parents);
+            self.obj().notify_can_change_space_parents();
+        }
+
+        /// Update whether our own member can change their name and avatar in
+        /// this room only.
+        fn update_can_change_own_profile(&self) {
+            // According to the authorization rules, a member event that keeps our
+            // `join` membership is allowed regardless of the power levels for the
+            // `m.room.member` event type, so it only depends on being joined.
+            let can_change_own_profile = self.is_joined.get();
+
+            if self.can_change_own_profile.get() == can_change_own_profile {
+                return;
+            }
+
+            self.can_change_own_profile.set(can_change_own_profile);
+            self.obj().notify_can_change_own_profile();
+        }
     }
 }
 
@@ -570,6 +636,57 @@ impl Permissions {
         power_levels.user_can_do_to_user(own_user_id, user_id, action)
     }
 
+    /// Whether another joined member has at least the same power level as our
+    /// own member.
+    pub(crate) async fn is_own_power_level_shared(&self) -> bool {
+        let Some(room) = self.room() else {
+            return false;
+        };
+
+        let own_power_level = self.own_power_level();
+        let power_levels = self.power_levels();
+
+        // Privileged creators are not listed in the power levels.
+        let room_info = room.matrix_room().clone_info();
+        let privileged_creators = room_info
+            .room_version_rules_or_default()
+            .authorization
+            .explicitly_privilege_room_creators
+            .then(|| room_info.creators())
+            .flatten()
+            .unwrap_or_default();
+
+        let matrix_room = room.matrix_room().clone();
+        let handle = spawn_tokio!(async move {
+            let own_user_id = matrix_room.own_user_id();
+            let candidates = power_levels
+                .users
+                .keys()
+                .cloned()
+                .chain(privileged_creators)
+                .filter(|user_id| {
+                    user_id != own_user_id && power_levels.for_user(user_id) >= own_power_level
+                })
+                .collect::<BTreeSet<_>>();
+
+            for user_id in candidates {
+                match matrix_room.get_member_no_sync(&user_id).await {
+                    Ok(Some(member)) if *member.membership() == MembershipState::Join => {
+                        return true;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        error!("Could not get member {user_id}: {error}");
+                    }
+                }
+            }
+
+            false
+        });
+
+        handle.await.expect("task was not aborted")
+    }
+
     /// Whether our user can set the given power level for another user.
     pub(crate) fn can_set_user_power_level_to(&self, power_level: i64) -> bool {
         self.is_allowed_to(PowerLevelAction::SendState(StateEventType::RoomPowerLevels))
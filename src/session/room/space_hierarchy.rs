@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use ruma::{
+    OwnedRoomId,
+    api::client::space::{SpaceHierarchyRoomsChunk, get_hierarchy},
+    assign,
+    events::space::child::HierarchySpaceChildEvent,
+    room::RoomType,
+};
+use tracing::{error, warn};
+
+use super::Room;
+use crate::{
+    session::{RemoteRoom, Session},
+    spawn_tokio,
+    utils::{LoadingState, matrix::MatrixRoomIdUri},
+};
+
+/// The maximum number of rooms to request per page of the space hierarchy.
+const HIERARCHY_PAGE_LIMIT: u32 = 50;
+/// The maximum number of pages of the space hierarchy to load.
+const HIERARCHY_MAX_PAGES: usize = 10;
+
+mod imp {
+    use std::cell::{Cell, OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::SpaceHierarchyItem)]
+    pub struct SpaceHierarchyItem {
+        /// The room presented by this item.
+        #[property(get, construct_only)]
+        room: OnceCell<RemoteRoom>,
+        /// Whether this room is a space.
+        #[property(get, construct_only)]
+        is_space: Cell<bool>,
+        /// Whether this room is suggested by the parent space.
+        #[property(get, construct_only)]
+        is_suggested: Cell<bool>,
+        /// The children of this room, if it is a space.
+        #[property(get)]
+        children: OnceCell<gio::ListStore>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceHierarchyItem {
+        const NAME: &'static str = "SpaceHierarchyItem";
+        type Type = super::SpaceHierarchyItem;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SpaceHierarchyItem {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.children
+                .set(gio::ListStore::new::<super::SpaceHierarchyItem>())
+                .expect("children should be uninitialized");
+        }
+    }
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::SpaceHierarchy)]
+    pub struct SpaceHierarchy {
+        /// The space of this hierarchy.
+        #[property(get, construct_only)]
+        space: glib::WeakRef<Room>,
+        /// The direct children of the space.
+        #[property(get)]
+        children: OnceCell<gio::ListStore>,
+        /// The loading state of the hierarchy.
+        #[property(get, builder(LoadingState::default()))]
+        loading_state: Cell<LoadingState>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceHierarchy {
+        const NAME: &'static str = "SpaceHierarchy";
+        type Type = super::SpaceHierarchy;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SpaceHierarchy {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.children
+                .set(gio::ListStore::new::<super::SpaceHierarchyItem>())
+                .expect("children should be uninitialized");
+        }
+    }
+
+    impl SpaceHierarchy {
+        /// Set the loading state of the hierarchy.
+        pub(super) fn set_loading_state(&self, loading_state: LoadingState) {
+            if self.loading_state.get() == loading_state {
+                return;
+            }
+
+            self.loading_state.set(loading_state);
+            self.obj().notify_loading_state();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A room in the hierarchy of a space.
+    pub struct SpaceHierarchyItem(ObjectSubclass<imp::SpaceHierarchyItem>);
+}
+
+impl SpaceHierarchyItem {
+    /// Construct a new `SpaceHierarchyItem` with the given data.
+    fn new(room: &RemoteRoom, is_space: bool, is_suggested: bool) -> Self {
+        glib::Object::builder()
+            .property("room", room)
+            .property("is-space", is_space)
+            .property("is-suggested", is_suggested)
+            .build()
+    }
+}
+
+glib::wrapper! {
+    /// The hierarchy of the rooms in a space, as returned by the homeserver.
+    pub struct SpaceHierarchy(ObjectSubclass<imp::SpaceHierarchy>);
+}
+
+impl SpaceHierarchy {
+    /// Construct a new empty `SpaceHierarchy` for the given space.
+    pub(super) fn new(space: &Room) -> Self {
+        glib::Object::builder().property("space", space).build()
+    }
+
+    /// Load the hierarchy of the space if it was not loaded yet.
+    pub(crate) async fn load_if_needed(&self) {
+        if matches!(
+            self.loading_state(),
+            LoadingState::Initial | LoadingState::Error
+        ) {
+            self.load().await;
+        }
+    }
+
+    /// Load the hierarchy of the space.
+    ///
+    /// This replaces the cached hierarchy.
+    pub(crate) async fn load(&self) {
+        let imp = self.imp();
+
+        if self.loading_state() == LoadingState::Loading {
+            return;
+        }
+
+        let Some(space) = self.space() else {
+            return;
+        };
+        let Some(session) = space.session() else {
+            return;
+        };
+
+        imp.set_loading_state(LoadingState::Loading);
+
+        let client = session.client();
+        let space_id = space.room_id().to_owned();
+
+        let handle = spawn_tokio!(async move {
+            let mut chunks = Vec::new();
+            let mut from = None;
+
+            for _ in 0..HIERARCHY_MAX_PAGES {
+                let request = assign!(get_hierarchy::v1::Request::new(space_id.clone()), {
+                    from: from.take(),
+                    limit: Some(HIERARCHY_PAGE_LIMIT.into()),
+                });
+                let response = client.send(request).await?;
+
+                chunks.extend(response.rooms);
+                from = response.next_batch;
+
+                if from.is_none() {
+                    break;
+                }
+            }
+
+            matrix_sdk::Result::<_>::Ok(chunks)
+        });
+
+        let chunks = match handle.await.expect("task was not aborted") {
+            Ok(chunks) => chunks,
+            Err(error) => {
+                error!("Could not load space hierarchy: {error}");
+                imp.set_loading_state(LoadingState::Error);
+                return;
+            }
+        };
+
+        let mut chunks = chunks
+            .into_iter()
+            .map(|chunk| (chunk.summary.room_id.clone(), chunk))
+            .collect::<HashMap<_, _>>();
+
+        let children = self.children();
+        let items = chunks
+            .remove(space.room_id())
+            .map_or_else(Vec::new, |chunk| {
+                let mut visited = HashSet::from([space.room_id().to_owned()]);
+                build_children(&session, &chunk, &chunks, &mut visited)
+            });
+        children.splice(0, children.n_items(), &items);
+
+        imp.set_loading_state(LoadingState::Ready);
+    }
+}
+
+/// Build the items for the children of the given space chunk.
+///
+/// `visited` is used to avoid infinite loops in the hierarchy.
+fn build_children(
+    session: &Session,
+    space_chunk: &SpaceHierarchyRoomsChunk,
+    chunks: &HashMap<OwnedRoomId, SpaceHierarchyRoomsChunk>,
+    visited: &mut HashSet<OwnedRoomId>,
+) -> Vec<SpaceHierarchyItem> {
+    let mut children = space_chunk
+        .children_state
+        .iter()
+        .filter_map(|raw| match raw.deserialize() {
+            Ok(event) => Some(event),
+            Err(error) => {
+                warn!("Could not deserialize space child event: {error}");
+                None
+            }
+        })
+        .collect::<Vec<HierarchySpaceChildEvent>>();
+
+    // Sort the children like the spec recommends: by order, then by
+    // timestamp, then by room ID.
+    children.sort_by(|lhs, rhs| {
+        match (&lhs.content.order, &rhs.content.order) {
+            (Some(lhs_order), Some(rhs_order)) => lhs_order.cmp(rhs_order),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| lhs.origin_server_ts.cmp(&rhs.origin_server_ts))
+        .then_with(|| lhs.state_key.cmp(&rhs.state_key))
+    });
+
+    let mut items = Vec::with_capacity(children.len());
+
+    for child in children {
+        let room_id = child.state_key;
+
+        // The homeserver does not return rooms that we cannot access.
+        let Some(chunk) = chunks.get(&room_id) else {
+            continue;
+        };
+
+        if !visited.insert(room_id.clone()) {
+            continue;
+        }
+
+        let uri = MatrixRoomIdUri {
+            id: room_id.clone().into(),
+            via: child.content.via.clone(),
+        };
+        let room = RemoteRoom::with_data(session, uri, chunk.summary.clone());
+        let is_space = chunk.summary.room_type == Some(RoomType::Space);
+
+        let item = SpaceHierarchyItem::new(&room, is_space, child.content.suggested);
+
+        if is_space {
+            let grandchildren = build_children(session, chunk, chunks, visited);
+            item.children().extend_from_slice(&grandchildren);
+        }
+
+        items.push(item);
+    }
+
+    items
+}
@@ -0,0 +1,34 @@
+use gtk::{glib, glib::translate::IntoGlib, prelude::*};
+use ruma::events::room::history_visibility::HistoryVisibility;
+
+use super::HistoryVisibilityValue;
+
+#[test]
+fn history_visibility_value_mapping() {
+    for value in HistoryVisibilityValue::SELECTABLE {
+        let history_visibility = HistoryVisibility::try_from(value).unwrap();
+        assert_eq!(HistoryVisibilityValue::from(history_visibility), value);
+    }
+
+    assert_eq!(
+        HistoryVisibility::try_from(HistoryVisibilityValue::Unsupported),
+        Err(HistoryVisibilityValue::Unsupported)
+    );
+    assert_eq!(
+        HistoryVisibilityValue::from(HistoryVisibility::from("org.example.custom")),
+        HistoryVisibilityValue::Unsupported
+    );
+}
+
+#[test]
+fn history_visibility_value_nick() {
+    let enum_class = glib::EnumClass::with_type(HistoryVisibilityValue::static_type()).unwrap();
+
+    for value in HistoryVisibilityValue::SELECTABLE
+        .into_iter()
+        .chain([HistoryVisibilityValue::Unsupported])
+    {
+        let enum_value = enum_class.value(value.into_glib()).unwrap();
+        assert_eq!(enum_value.nick(), value.nick());
+    }
+}
@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
-use gtk::{gio, glib, glib::closure_local, prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{
+    gio, glib,
+    glib::{clone, closure_local},
+    prelude::*,
+    subclass::prelude::*,
+};
 use indexmap::IndexMap;
 use matrix_sdk_ui::timeline::{
-    AnyOtherFullStateEventContent, EmbeddedEvent, Error as TimelineError, EventSendState,
-    EventTimelineItem, MembershipChange, Message, MsgLikeKind, TimelineDetails,
+    AnyOtherFullStateEventContent, EmbeddedEvent, EncryptedMessage, Error as TimelineError,
+    EventSendState, EventTimelineItem, MembershipChange, Message, MsgLikeKind, TimelineDetails,
     TimelineEventItemId, TimelineItemContent,
 };
 use ruma::{
@@ -24,12 +30,23 @@ pub(crate) use self::{
 };
 use super::{Timeline, TimelineItem, TimelineItemImpl};
 use crate::{
+    gettext_f,
     prelude::*,
-    session::Member,
+    session::{Member, NotificationPreview},
     spawn_tokio,
-    utils::matrix::{MediaMessage, raw_eq, timestamp_to_date},
+    utils::matrix::{
+        MediaMessage, VisualMediaMessage, find_at_room, mentions_only_room, message_plain_body,
+        raw_eq, timestamp_to_date,
+    },
 };
 
+/// The maximum number of characters of the content of an event in its
+/// accessible summary.
+const ACCESSIBLE_SUMMARY_MAX_CHARS: usize = 140;
+/// The maximum number of characters of the content of an event in its preview
+/// in the sidebar.
+const SIDEBAR_PREVIEW_MAX_CHARS: usize = 80;
+
 /// The possible states of a message.
 #[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
 #[enum_type(name = "MessageState")]
@@ -113,6 +130,11 @@ mod imp {
         /// The state of this event.
         #[property(get, builder(MessageState::default()))]
         state: Cell<MessageState>,
+        /// Whether this event is waiting in the send queue for the session to
+        /// be back online.
+        #[property(get)]
+        is_waiting_for_connection: Cell<bool>,
+        offline_handler: RefCell<Option<glib::SignalHandlerId>>,
         /// Whether this event was edited.
         #[property(get = Self::is_edited)]
         is_edited: PhantomData<bool>,
@@ -148,6 +170,11 @@ mod imp {
         /// Whether this event has any read receipt.
         #[property(get = Self::has_read_receipts)]
         has_read_receipts: PhantomData<bool>,
+        /// Whether this event was read by other members.
+        ///
+        /// This is only computed for events sent by our own user.
+        #[property(get, set = Self::set_is_read_by_others, explicit_notify)]
+        is_read_by_others: Cell<bool>,
         /// The state of the header of the event in the room history.
         #[property(get, set = Self::set_header_state, explicit_notify, builder(EventHeaderState::default()))]
         header_state: Cell<EventHeaderState>,
@@ -175,6 +202,14 @@ mod imp {
                 self.reactions.set_user(session.user().clone());
             }
         }
+
+        fn dispose(&self) {
+            if let Some(handler) = self.offline_handler.take()
+                && let Some(session) = self.obj().room().session()
+            {
+                session.disconnect(handler);
+            }
+        }
     }
 
     impl TimelineItemImpl for Event {}
@@ -357,6 +392,51 @@ mod imp {
 
             self.state.set(state);
             self.obj().notify_state();
+
+            self.watch_offline();
+        }
+
+        /// Watch whether the session is offline, if this event is in the send
+        /// queue.
+        fn watch_offline(&self) {
+            let Some(session) = self.obj().room().session() else {
+                return;
+            };
+
+            if self.state.get() == MessageState::Sending {
+                if self.offline_handler.borrow().is_none() {
+                    let offline_handler = session.connect_is_offline_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_is_waiting_for_connection();
+                        }
+                    ));
+                    self.offline_handler.replace(Some(offline_handler));
+                }
+            } else if let Some(handler) = self.offline_handler.take() {
+                session.disconnect(handler);
+            }
+
+            self.update_is_waiting_for_connection();
+        }
+
+        /// Update whether this event is waiting in the send queue for the
+        /// session to be back online.
+        fn update_is_waiting_for_connection(&self) {
+            let is_waiting = self.state.get() == MessageState::Sending
+                && self
+                    .obj()
+                    .room()
+                    .session()
+                    .is_some_and(|session| session.is_offline());
+
+            if self.is_waiting_for_connection.get() == is_waiting {
+                return;
+            }
+
+            self.is_waiting_for_connection.set(is_waiting);
+            self.obj().notify_is_waiting_for_connection();
         }
 
         /// Whether this event was edited.
@@ -489,6 +569,16 @@ mod imp {
             self.header_state.set(state);
             self.obj().notify_header_state();
         }
+
+        /// Set whether this event was read by other members.
+        fn set_is_read_by_others(&self, is_read: bool) {
+            if self.is_read_by_others.get() == is_read {
+                return;
+            }
+
+            self.is_read_by_others.set(is_read);
+            self.obj().notify_is_read_by_others();
+        }
     }
 }
 
@@ -645,6 +735,26 @@ impl Event {
         }
     }
 
+    /// The encrypted message, if this event could not be decrypted.
+    pub(crate) fn unable_to_decrypt_message(&self) -> Option<EncryptedMessage> {
+        match self.item().content() {
+            TimelineItemContent::MsgLike(msg_like) => match &msg_like.kind {
+                MsgLikeKind::UnableToDecrypt(message) => Some(message.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether this event could not be decrypted.
+    pub(crate) fn is_unable_to_decrypt(&self) -> bool {
+        matches!(
+            self.item().content(),
+            TimelineItemContent::MsgLike(msg_like)
+                if matches!(msg_like.kind, MsgLikeKind::UnableToDecrypt(_))
+        )
+    }
+
     /// Whether this is a state event.
     pub(crate) fn is_state_event(&self) -> bool {
         matches!(
@@ -706,6 +816,20 @@ impl Event {
         }
     }
 
+    /// The visual media message of this event, if any.
+    pub(crate) fn visual_media_message(&self) -> Option<VisualMediaMessage> {
+        match self.item().content() {
+            TimelineItemContent::MsgLike(msg_like) => match &msg_like.kind {
+                MsgLikeKind::Message(message) => {
+                    VisualMediaMessage::from_message(message.msgtype())
+                }
+                MsgLikeKind::Sticker(sticker) => Some(sticker.content().clone().into()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Whether this event might contain an `@room` mention.
     ///
     /// This means that either it does not have intentional mentions, or it has
@@ -714,6 +838,156 @@ impl Event {
         self.item().content().can_contain_at_room()
     }
 
+    /// Whether this event mentions the whole room.
+    ///
+    /// This uses the intentional mentions if they are set, or looks for
+    /// `@room` in the body of the message otherwise. The sender must be allowed
+    /// to notify the whole room.
+    pub(crate) fn mentions_room(&self) -> bool {
+        let TimelineItemContent::MsgLike(msg_like) = self.item().content() else {
+            return false;
+        };
+        let MsgLikeKind::Message(message) = &msg_like.kind else {
+            return false;
+        };
+
+        let mentions_room = match message.mentions() {
+            Some(mentions) => mentions.room,
+            None => find_at_room(message.body()).is_some(),
+        };
+
+        mentions_room && self.sender().can_notify_room()
+    }
+
+    /// Whether this `Event` is highlighted only because it mentions the whole
+    /// room.
+    ///
+    /// The homeserver counts mentions of the whole room as highlights, so this
+    /// allows to ignore them.
+    pub(crate) fn is_highlighted_only_for_room(&self, own_user_id: &UserId) -> bool {
+        if !self.is_highlighted() {
+            return false;
+        }
+
+        let TimelineItemContent::MsgLike(msg_like) = self.item().content() else {
+            return false;
+        };
+        let MsgLikeKind::Message(message) = &msg_like.kind else {
+            return false;
+        };
+
+        let own_display_name = self.room().own_member().display_name();
+        mentions_only_room(
+            message.mentions(),
+            message.body(),
+            own_user_id,
+            &own_display_name,
+        )
+    }
+
+    /// A short plain text summary of this event, with its sender and a
+    /// preview of its content.
+    ///
+    /// This is meant to be presented to screen readers.
+    ///
+    /// Returns `None` if this event is not message-like.
+    pub(crate) fn accessible_summary(&self) -> Option<String> {
+        let preview = self.content_preview(ACCESSIBLE_SUMMARY_MAX_CHARS)?;
+
+        Some(gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this is a
+            // variable name.
+            "{sender}: {message}",
+            &[
+                ("sender", &self.sender().disambiguated_name()),
+                ("message", &preview),
+            ],
+        ))
+    }
+
+    /// A short plain text preview of this event, to present in the sidebar.
+    ///
+    /// The sender is only shown if `show_sender` is `true`, or if the event
+    /// was sent by our own user.
+    ///
+    /// Returns `None` if this event is not message-like or if the given
+    /// preview setting does not allow to show anything.
+    pub(crate) fn sidebar_preview(
+        &self,
+        preview: NotificationPreview,
+        show_sender: bool,
+    ) -> Option<String> {
+        if preview == NotificationPreview::Hidden {
+            return None;
+        }
+
+        let content = self.content_preview(SIDEBAR_PREVIEW_MAX_CHARS)?;
+        let item = self.item();
+
+        if item.is_own() {
+            return Some(if preview == NotificationPreview::SenderOnly {
+                gettext("You sent a message")
+            } else {
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "You: {message}",
+                    &[("message", &content)],
+                )
+            });
+        }
+
+        if !show_sender && preview == NotificationPreview::Full {
+            return Some(content);
+        }
+
+        let sender_name = match item.sender_profile() {
+            TimelineDetails::Ready(profile) => profile.display_name.clone(),
+            _ => None,
+        }
+        .unwrap_or_else(|| self.sender_id().localpart().to_owned());
+
+        Some(if preview == NotificationPreview::SenderOnly {
+            gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "{user} sent a message",
+                &[("user", &sender_name)],
+            )
+        } else {
+            gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "{sender}: {message}",
+                &[("sender", &sender_name), ("message", &content)],
+            )
+        })
+    }
+
+    /// A plain text preview of the content of this event, truncated to the
+    /// given number of characters.
+    ///
+    /// Returns `None` if this event is not message-like.
+    fn content_preview(&self, max_chars: usize) -> Option<String> {
+        let TimelineItemContent::MsgLike(msg_like) = self.item().content() else {
+            return None;
+        };
+
+        let mut preview = match &msg_like.kind {
+            MsgLikeKind::Message(message) => message_plain_body(message.msgtype()),
+            MsgLikeKind::Sticker(sticker) => sticker.content().body.clone(),
+            _ => return None,
+        };
+        preview = preview.collapse_whitespaces(true, true);
+
+        if let Some((pos, _)) = preview.char_indices().nth(max_chars) {
+            preview.truncate(pos);
+            preview.append_ellipsis();
+        }
+
+        Some(preview)
+    }
+
     /// Whether this event can show a header.
     pub(crate) fn can_show_header(&self) -> bool {
         self.item().content().can_show_header()
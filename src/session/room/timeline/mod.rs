@@ -15,7 +15,7 @@ use matrix_sdk_ui::{
     },
 };
 use ruma::{
-    OwnedEventId, UserId,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, UserId,
     events::{
         AnySyncMessageLikeEvent, AnySyncStateEvent, AnySyncTimelineEvent, SyncMessageLikeEvent,
         SyncStateEvent, room::message::MessageType,
@@ -39,17 +39,21 @@ pub(crate) use self::{
 use super::Room;
 use crate::{
     prelude::*,
+    session::DEFAULT_PAGINATION_BATCH_SIZE,
     spawn, spawn_tokio,
     utils::{LoadingState, SingleItemListModel},
 };
 
-/// The number of events to request when loading more history.
-const MAX_BATCH_SIZE: u16 = 20;
 /// The maximum time between contiguous events before we show their header, in
 /// milliseconds.
 ///
 /// This matches 20 minutes.
 const MAX_TIME_BETWEEN_HEADERS: u64 = 20 * 60 * 1000;
+/// The maximum age of an event appended at the end of the timeline for it to
+/// be considered as a new live message, in milliseconds.
+///
+/// This matches 1 minute.
+const MAX_LIVE_MESSAGE_AGE: u64 = 60 * 1000;
 
 mod imp {
     use std::{
@@ -97,6 +101,9 @@ mod imp {
         /// Whether we are loading events at the start of the timeline.
         #[property(get)]
         is_loading_start: Cell<bool>,
+        /// Whether we are loading events at the end of the timeline.
+        #[property(get)]
+        is_loading_end: Cell<bool>,
         /// Whether the timeline is empty.
         #[property(get = Self::is_empty)]
         is_empty: PhantomData<bool>,
@@ -106,9 +113,22 @@ mod imp {
         /// Whether we have reached the start of the timeline.
         #[property(get)]
         has_reached_start: Cell<bool>,
+        /// Whether we have reached the end of the timeline, i.e. the live
+        /// edge of the room.
+        #[property(get)]
+        has_reached_end: Cell<bool>,
         /// Whether we have the `m.room.create` event in the timeline.
         #[property(get)]
         has_room_create: Cell<bool>,
+        /// The messages appended at the end of the timeline while the current
+        /// diff list is handled.
+        appended_messages: RefCell<Vec<Event>>,
+        /// The events that were decrypted or that failed to be decrypted while
+        /// the current diff list is handled.
+        ///
+        /// They need to be re-added to the list so they are grouped correctly
+        /// in the room history.
+        decryption_changed_events: RefCell<Vec<Event>>,
         diff_handle: OnceCell<AbortHandle>,
         back_pagination_status_handle: OnceCell<AbortHandle>,
         read_receipts_changed_handle: OnceCell<AbortHandle>,
@@ -123,8 +143,14 @@ mod imp {
     #[glib::derived_properties]
     impl ObjectImpl for Timeline {
         fn signals() -> &'static [Signal] {
-            static SIGNALS: LazyLock<Vec<Signal>> =
-                LazyLock::new(|| vec![Signal::builder("read-change-trigger").build()]);
+            static SIGNALS: LazyLock<Vec<Signal>> = LazyLock::new(|| {
+                vec![
+                    Signal::builder("read-change-trigger").build(),
+                    Signal::builder("live-messages-received")
+                        .param_types([Event::static_type(), u32::static_type()])
+                        .build(),
+                ]
+            });
             SIGNALS.as_ref()
         }
 
@@ -205,6 +231,8 @@ mod imp {
             };
 
             let matrix_timeline = Arc::new(matrix_timeline);
+            // A live timeline is always at the end.
+            self.set_has_reached_end(true);
             self.matrix_timeline
                 .set(matrix_timeline.clone())
                 .expect("matrix timeline is uninitialized");
@@ -221,6 +249,8 @@ mod imp {
 
             if !values.is_empty() {
                 self.update_with_single_diff(VectorDiff::Append { values });
+                // The initial items are not live messages.
+                self.appended_messages.take();
             }
 
             let obj_weak = glib::SendWeakRef::from(self.obj().downgrade());
@@ -323,7 +353,7 @@ mod imp {
 
         /// Update the loading state of the timeline.
         fn update_loading_state(&self) {
-            let is_loading = self.is_loading_start.get();
+            let is_loading = self.is_loading_start.get() || self.is_loading_end.get();
 
             if is_loading {
                 self.set_state(LoadingState::Loading);
@@ -345,6 +375,18 @@ mod imp {
             self.obj().notify_is_loading_start();
         }
 
+        /// Set whether we are loading events at the end of the timeline.
+        fn set_loading_end(&self, is_loading_end: bool) {
+            if self.is_loading_end.get() == is_loading_end {
+                return;
+            }
+
+            self.is_loading_end.set(is_loading_end);
+
+            self.update_loading_state();
+            self.obj().notify_is_loading_end();
+        }
+
         /// Set whether we have reached the start of the timeline.
         fn set_has_reached_start(&self, has_reached_start: bool) {
             if self.has_reached_start.get() == has_reached_start {
@@ -357,6 +399,18 @@ mod imp {
             self.obj().notify_has_reached_start();
         }
 
+        /// Set whether we have reached the end of the timeline.
+        fn set_has_reached_end(&self, has_reached_end: bool) {
+            if self.has_reached_end.get() == has_reached_end {
+                // Nothing to do.
+                return;
+            }
+
+            self.has_reached_end.set(has_reached_end);
+
+            self.obj().notify_has_reached_end();
+        }
+
         /// Set whether the timeline has the `m.room.create` event of the room.
         fn set_has_room_create(&self, has_room_create: bool) {
             if self.has_room_create.get() == has_room_create {
@@ -410,7 +464,7 @@ mod imp {
 
         /// Preload the timeline, if there are not enough items.
         async fn preload(&self) {
-            if self.filtered_sdk_items.n_items() < u32::from(MAX_BATCH_SIZE) {
+            if self.filtered_sdk_items.n_items() < u32::from(self.batch_size()) {
                 self.paginate_backwards(|| ControlFlow::Break(())).await;
             }
         }
@@ -430,6 +484,9 @@ mod imp {
                 }
             }
 
+            self.readd_decryption_changed_events();
+            self.update_read_by_others();
+
             if *IS_AT_TRACE_LEVEL {
                 self.log_items();
             }
@@ -440,6 +497,94 @@ mod imp {
             }
 
             obj.emit_read_change_trigger();
+            self.emit_live_messages_received();
+        }
+
+        /// Re-add the events whose decryption state changed while the current
+        /// diff list was handled, so they are regrouped.
+        fn readd_decryption_changed_events(&self) {
+            let events = self.decryption_changed_events.take();
+            let sdk_items = self.sdk_items();
+
+            for event in events {
+                if let Some(pos) = sdk_items.find(&event) {
+                    sdk_items.splice(pos, 1, &[event]);
+                }
+            }
+        }
+
+        /// Update whether the events sent by our own user were read by other
+        /// members.
+        ///
+        /// The SDK only puts the read receipt of a user on the latest event
+        /// they read, so an event was read by another member if it or any later
+        /// event has a read receipt from them.
+        fn update_read_by_others(&self) {
+            let own_user_id = self.room().own_member().user_id().clone();
+            let sdk_items = self.sdk_items();
+            let mut is_read = false;
+
+            for pos in (0..sdk_items.n_items()).rev() {
+                let Some(event) = sdk_items.item(pos).and_downcast::<Event>() else {
+                    continue;
+                };
+
+                if !is_read {
+                    is_read = event
+                        .item()
+                        .read_receipts()
+                        .keys()
+                        .any(|user_id| *user_id != own_user_id);
+                }
+
+                if event.sender_id() == own_user_id {
+                    if is_read && event.is_read_by_others() {
+                        // Read receipts only move forward, so all the previous events are
+                        // already marked as read.
+                        break;
+                    }
+
+                    event.set_is_read_by_others(is_read);
+                }
+            }
+        }
+
+        /// Emit the signal for the live messages that were appended while the
+        /// current diff list was handled, if any.
+        ///
+        /// Only recent messages from other users are considered as live, to
+        /// ignore older messages appended when the timeline is reset or
+        /// when a gap is filled.
+        fn emit_live_messages_received(&self) {
+            let appended_messages = self.appended_messages.take();
+
+            if self.state.get() != LoadingState::Ready {
+                return;
+            }
+
+            let own_user_id = self.room().own_member().user_id().clone();
+            let now = u64::from(MilliSecondsSinceUnixEpoch::now().get());
+
+            let mut live_messages = appended_messages.into_iter().filter(|event| {
+                event.event_id().is_some()
+                    && event.sender_id() != own_user_id
+                    && event.is_message_like()
+                    && now.saturating_sub(u64::from(event.origin_server_ts().get()))
+                        <= MAX_LIVE_MESSAGE_AGE
+            });
+
+            let Some(mut latest) = live_messages.next() else {
+                return;
+            };
+            let mut count = 1_u32;
+
+            for event in live_messages {
+                latest = event;
+                count = count.saturating_add(1);
+            }
+
+            self.obj()
+                .emit_by_name::<()>("live-messages-received", &[&latest, &count]);
         }
 
         /// Attempt to minimize the given list of diffs.
@@ -542,6 +687,8 @@ mod imp {
         /// Update the items at the given position by removing the given number
         /// of items and adding the given items.
         fn update_items(&self, pos: u32, n_removals: u32, additions: &[TimelineItem]) {
+            let is_at_end = pos + n_removals == self.sdk_items().n_items();
+
             for i in pos..pos + n_removals {
                 let Some(item) = self.item_at(i) else {
                     // This should not happen.
@@ -558,6 +705,16 @@ mod imp {
             // after this batch.
             self.update_items_headers(pos, additions.len() as u32);
 
+            // Keep track of the messages appended at the end of the timeline.
+            if is_at_end {
+                self.appended_messages.borrow_mut().extend(
+                    additions
+                        .iter()
+                        .filter_map(|item| item.downcast_ref::<Event>())
+                        .cloned(),
+                );
+            }
+
             // Try to update the latest unread message.
             if !additions.is_empty() {
                 self.room().update_latest_activity(
@@ -656,6 +813,17 @@ mod imp {
             }
         }
 
+        /// The number of events to request when loading more history.
+        ///
+        /// This is read from the settings of the session every time, so changes
+        /// apply to the rooms that are already open.
+        fn batch_size(&self) -> u16 {
+            self.room()
+                .session()
+                .and_then(|session| u16::try_from(session.settings().pagination_batch_size()).ok())
+                .unwrap_or(DEFAULT_PAGINATION_BATCH_SIZE)
+        }
+
         /// Whether we can load more events at the start of the timeline with
         /// the current state.
         pub(super) fn can_paginate_backwards(&self) -> bool {
@@ -693,10 +861,9 @@ mod imp {
         /// Returns `true` if more events can be loaded.
         async fn paginate_backwards_inner(&self) -> bool {
             let matrix_timeline = self.matrix_timeline().clone();
+            let batch_size = self.batch_size();
             let handle =
-                spawn_tokio!(
-                    async move { matrix_timeline.paginate_backwards(MAX_BATCH_SIZE).await }
-                );
+                spawn_tokio!(async move { matrix_timeline.paginate_backwards(batch_size).await });
 
             match handle.await.expect("task was not aborted") {
                 Ok(reached_start) => {
@@ -714,6 +881,63 @@ mod imp {
             }
         }
 
+        /// Whether we can load more events at the end of the timeline with
+        /// the current state.
+        pub(super) fn can_paginate_forwards(&self) -> bool {
+            // We do not want to load twice at the same time, and it's useless to try to
+            // load more events before the timeline is ready or if we have reached the
+            // end of the timeline.
+            self.state.get() != LoadingState::Initial
+                && !self.is_loading_end.get()
+                && !self.has_reached_end.get()
+        }
+
+        /// Load more events at the end of the timeline until the given
+        /// function tells us to stop.
+        pub(super) async fn paginate_forwards<F>(&self, continue_fn: F)
+        where
+            F: Fn() -> ControlFlow<()>,
+        {
+            self.set_loading_end(true);
+
+            loop {
+                if !self.paginate_forwards_inner().await {
+                    break;
+                }
+
+                if continue_fn().is_break() {
+                    break;
+                }
+            }
+
+            self.set_loading_end(false);
+        }
+
+        /// Load more events at the end of the timeline.
+        ///
+        /// Returns `true` if more events can be loaded.
+        async fn paginate_forwards_inner(&self) -> bool {
+            let matrix_timeline = self.matrix_timeline().clone();
+            let batch_size = self.batch_size();
+            let handle =
+                spawn_tokio!(async move { matrix_timeline.paginate_forwards(batch_size).await });
+
+            match handle.await.expect("task was not aborted") {
+                Ok(reached_end) => {
+                    if reached_end {
+                        self.set_has_reached_end(true);
+                    }
+
+                    !reached_end
+                }
+                Err(error) => {
+                    error!("Could not load timeline: {error}");
+                    self.set_state(LoadingState::Error);
+                    false
+                }
+            }
+        }
+
         /// Add the typing row to the timeline, if it isn't present already.
         fn add_typing_row(&self) {
             self.end_items().set_is_hidden(false);
@@ -805,9 +1029,19 @@ mod imp {
         }
 
         fn update_item(&self, item: &TimelineItem, data: &Arc<SdkTimelineItem>) {
+            let was_unable_to_decrypt = item
+                .downcast_ref::<Event>()
+                .is_some_and(Event::is_unable_to_decrypt);
+
             item.update_with(data);
 
             if let Some(event) = item.downcast_ref::<Event>() {
+                if event.is_unable_to_decrypt() != was_unable_to_decrypt {
+                    self.decryption_changed_events
+                        .borrow_mut()
+                        .push(event.clone());
+                }
+
                 // Update the identifier in the event map, in case we switched from a
                 // transaction ID to an event ID.
                 self.event_map
@@ -979,6 +1213,21 @@ impl Timeline {
         imp.paginate_backwards(continue_fn).await;
     }
 
+    /// Load more events at the end of the timeline until the given function
+    /// tells us to stop.
+    pub(crate) async fn paginate_forwards<F>(&self, continue_fn: F)
+    where
+        F: Fn() -> ControlFlow<()>,
+    {
+        let imp = self.imp();
+
+        if !imp.can_paginate_forwards() {
+            return;
+        }
+
+        imp.paginate_forwards(continue_fn).await;
+    }
+
     /// Get the event with the given identifier from this `Timeline`.
     ///
     /// Use this method if you are sure the event has already been received.
@@ -1011,17 +1260,7 @@ impl Timeline {
     /// Returns `None` if it is not possible to know, for example if there are
     /// no events in the Timeline.
     pub(crate) async fn has_unread_messages(&self) -> Option<bool> {
-        let session = self.room().session()?;
-        let own_user_id = session.user_id().clone();
-        let matrix_timeline = self.matrix_timeline();
-
-        let user_receipt_item = spawn_tokio!(async move {
-            matrix_timeline
-                .latest_user_read_receipt_timeline_event_id(&own_user_id)
-                .await
-        })
-        .await
-        .expect("task was not aborted");
+        let user_receipt_item = self.own_read_receipt_event_id().await?;
 
         let sdk_items = self.imp().sdk_items();
         let count = sdk_items.n_items();
@@ -1047,6 +1286,86 @@ impl Timeline {
         None
     }
 
+    /// Whether this timeline has unread messages that mention the whole room.
+    pub(crate) async fn has_unread_at_room(&self) -> bool {
+        let Some(user_receipt_item) = self.own_read_receipt_event_id().await else {
+            return false;
+        };
+
+        let sdk_items = self.imp().sdk_items();
+        let count = sdk_items.n_items();
+
+        for pos in (0..count).rev() {
+            let Some(event) = sdk_items.item(pos).and_downcast::<Event>() else {
+                continue;
+            };
+
+            if user_receipt_item.is_some() && event.event_id() == user_receipt_item {
+                // We have read all the events after this one.
+                return false;
+            }
+            if event.counts_as_unread() && event.mentions_room() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Whether this timeline has unread messages that are highlighted for
+    /// another reason than mentioning the whole room.
+    pub(crate) async fn has_unread_highlight_without_at_room(&self) -> bool {
+        let Some(session) = self.room().session() else {
+            return false;
+        };
+        let Some(user_receipt_item) = self.own_read_receipt_event_id().await else {
+            return false;
+        };
+        let own_user_id = session.user_id();
+
+        let sdk_items = self.imp().sdk_items();
+        let count = sdk_items.n_items();
+
+        for pos in (0..count).rev() {
+            let Some(event) = sdk_items.item(pos).and_downcast::<Event>() else {
+                continue;
+            };
+
+            if user_receipt_item.is_some() && event.event_id() == user_receipt_item {
+                // We have read all the events after this one.
+                return false;
+            }
+            if event.counts_as_unread()
+                && event.is_highlighted()
+                && !event.is_highlighted_only_for_room(own_user_id)
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The ID of the event with the latest read receipt of our own user in
+    /// this timeline.
+    ///
+    /// Returns `None` if the session is not available.
+    async fn own_read_receipt_event_id(&self) -> Option<Option<OwnedEventId>> {
+        let session = self.room().session()?;
+        let own_user_id = session.user_id().clone();
+        let matrix_timeline = self.matrix_timeline();
+
+        let user_receipt_item = spawn_tokio!(async move {
+            matrix_timeline
+                .latest_user_read_receipt_timeline_event_id(&own_user_id)
+                .await
+        })
+        .await
+        .expect("task was not aborted");
+
+        Some(user_receipt_item)
+    }
+
     /// The IDs of redactable events sent by the given user in this timeline.
     pub(crate) fn redactable_events_for(&self, user_id: &UserId) -> Vec<OwnedEventId> {
         let mut events = vec![];
@@ -1092,6 +1411,24 @@ impl Timeline {
             }),
         )
     }
+
+    /// Connect to the signal emitted when new live messages from other users
+    /// were appended at the end of the timeline.
+    ///
+    /// The parameters are the latest new message and the number of new
+    /// messages.
+    pub(crate) fn connect_live_messages_received<F: Fn(&Self, &Event, u32) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "live-messages-received",
+            true,
+            closure_local!(move |obj: Self, latest: Event, count: u32| {
+                f(&obj, &latest, count);
+            }),
+        )
+    }
 }
 
 /// Whether the given event should be shown in the timeline.
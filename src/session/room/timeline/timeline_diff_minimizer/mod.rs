@@ -310,8 +310,75 @@ where
         let old_item_ids = self.load_items();
         let new_item_ids = self.apply_diff_to_items(&old_item_ids, diff_list);
         let item_diff_list = self.item_diff_list(&old_item_ids, &new_item_ids);
+
+        #[cfg(all(debug_assertions, feature = "timeline-diff-check"))]
+        self.check_item_diff_list(&old_item_ids, &new_item_ids, &item_diff_list);
+
         self.store.apply_item_diff_list(item_diff_list);
     }
+
+    /// Check that applying the given item diff list to the old items results
+    /// in the new items.
+    ///
+    /// Also checks that all the items that were updated are either in an
+    /// update or re-added.
+    ///
+    /// Panics if the item diff list is incorrect.
+    #[cfg(any(test, all(debug_assertions, feature = "timeline-diff-check")))]
+    fn check_item_diff_list(
+        &self,
+        old_item_ids: &[String],
+        new_item_ids: &[String],
+        item_diff_list: &[TimelineDiff<S::Item>],
+    ) {
+        let mut item_ids = old_item_ids.to_owned();
+        let mut processed_item_ids = Vec::new();
+
+        for item_diff in item_diff_list {
+            match item_diff {
+                TimelineDiff::Splice(splice_diff) => {
+                    let pos = splice_diff.pos as usize;
+                    let n_removals = splice_diff.n_removals as usize;
+                    assert!(
+                        pos + n_removals <= item_ids.len(),
+                        "splice out of bounds: len = {}; pos = {pos}; n_removals = {n_removals}",
+                        item_ids.len()
+                    );
+
+                    let additions = splice_diff
+                        .additions
+                        .iter()
+                        .map(S::Item::timeline_id)
+                        .collect::<Vec<_>>();
+                    processed_item_ids.extend(additions.iter().cloned());
+                    item_ids.splice(pos..pos + n_removals, additions);
+                }
+                TimelineDiff::Update(update_diff) => {
+                    let pos = update_diff.pos as usize;
+                    let n_items = update_diff.n_items as usize;
+                    assert!(
+                        pos + n_items <= item_ids.len(),
+                        "update out of bounds: len = {}; pos = {pos}; n_items = {n_items}",
+                        item_ids.len()
+                    );
+
+                    processed_item_ids.extend(item_ids[pos..pos + n_items].iter().cloned());
+                }
+            }
+        }
+
+        assert_eq!(
+            item_ids, new_item_ids,
+            "minimized item diff list does not match the naive result"
+        );
+
+        for item_id in &self.updated_item_ids {
+            assert!(
+                !new_item_ids.contains(item_id) || processed_item_ids.contains(item_id),
+                "updated item {item_id} was not processed by the minimized item diff list"
+            );
+        }
+    }
 }
 
 /// A minimized diff for timeline items.
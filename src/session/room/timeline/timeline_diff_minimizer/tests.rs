@@ -9,6 +9,7 @@ use std::{
 
 use assert_matches2::assert_matches;
 use matrix_sdk_ui::eyeball_im::Vector;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use super::*;
 
@@ -603,3 +604,275 @@ fn minimize_complex_diff() {
     assert_eq!(items[6].version.get(), 0);
     assert!(!items[6].processed.get());
 }
+
+/// The timeline IDs that can be used by generated items.
+const GENERATED_TIMELINE_IDS: [&str; 16] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p",
+];
+
+/// Apply the given diff list naively to the given list of `(timeline_id,
+/// version)` tuples.
+fn apply_diff_list_naively(
+    items: &mut Vec<(String, u8)>,
+    diff_list: Vec<VectorDiff<TestTimelineDiffItemData>>,
+) {
+    let entry = |data: TestTimelineDiffItemData| (data.timeline_id.to_owned(), data.version);
+
+    for diff in diff_list {
+        match diff {
+            VectorDiff::Append { values } => items.extend(values.into_iter().map(entry)),
+            VectorDiff::Clear => items.clear(),
+            VectorDiff::PushFront { value } => items.insert(0, entry(value)),
+            VectorDiff::PushBack { value } => items.push(entry(value)),
+            VectorDiff::PopFront => {
+                items.remove(0);
+            }
+            VectorDiff::PopBack => {
+                items.pop();
+            }
+            VectorDiff::Insert { index, value } => items.insert(index, entry(value)),
+            VectorDiff::Set { index, value } => items[index] = entry(value),
+            VectorDiff::Remove { index } => {
+                items.remove(index);
+            }
+            VectorDiff::Truncate { length } => items.truncate(length),
+            VectorDiff::Reset { values } => {
+                *items = values.into_iter().map(entry).collect();
+            }
+        }
+    }
+}
+
+/// Minimize the given diff list and apply it to the given store, and check
+/// that the result matches the naive application of the diff list.
+///
+/// Also checks that the items that are still in the list after the diff were
+/// not recreated.
+fn assert_minimized_diff_list_matches_naive(
+    store: &TestTimelineDiffItemStore,
+    diff_list: &[VectorDiff<TestTimelineDiffItemData>],
+) {
+    let old_items = store.items();
+    let mut expected = old_items
+        .iter()
+        .map(|item| (item.timeline_id.clone(), item.version.get()))
+        .collect::<Vec<_>>();
+    apply_diff_list_naively(&mut expected, diff_list.to_vec());
+
+    let mut minimizer = TimelineDiffMinimizer::new(store);
+    let old_item_ids = minimizer.load_items();
+    let new_item_ids = minimizer.apply_diff_to_items(&old_item_ids, diff_list.to_vec());
+    let item_diff_list = minimizer.item_diff_list(&old_item_ids, &new_item_ids);
+    minimizer.check_item_diff_list(&old_item_ids, &new_item_ids, &item_diff_list);
+    store.apply_item_diff_list(item_diff_list);
+
+    let items = store.items();
+    let result = items
+        .iter()
+        .map(|item| (item.timeline_id.clone(), item.version.get()))
+        .collect::<Vec<_>>();
+    assert_eq!(result, expected, "diff list: {diff_list:?}");
+
+    for item in &items {
+        if let Some(old_item) = old_items
+            .iter()
+            .find(|old_item| old_item.timeline_id == item.timeline_id)
+        {
+            assert!(
+                Rc::ptr_eq(&item.0, &old_item.0),
+                "item {} was recreated; diff list: {diff_list:?}",
+                item.timeline_id
+            );
+        }
+    }
+}
+
+/// Generate a random diff to apply to the given list of `(timeline_id,
+/// version)` tuples.
+///
+/// Only generates the variants supported by the minimizer, and never generates
+/// an item with a timeline ID that is already in the list.
+fn random_diff(rng: &mut StdRng, items: &[(String, u8)]) -> VectorDiff<TestTimelineDiffItemData> {
+    let unused_timeline_ids = GENERATED_TIMELINE_IDS
+        .into_iter()
+        .filter(|timeline_id| !items.iter().any(|(id, _)| id == timeline_id))
+        .collect::<Vec<_>>();
+    let new_data = |rng: &mut StdRng| {
+        (!unused_timeline_ids.is_empty()).then(|| TestTimelineDiffItemData {
+            timeline_id: unused_timeline_ids[rng.random_range(0..unused_timeline_ids.len())],
+            version: rng.random_range(0..4),
+        })
+    };
+
+    loop {
+        let len = items.len();
+
+        match rng.random_range(0..9) {
+            0 => {
+                if let Some(value) = new_data(rng) {
+                    return VectorDiff::PushFront { value };
+                }
+            }
+            1 => {
+                if let Some(value) = new_data(rng) {
+                    return VectorDiff::PushBack { value };
+                }
+            }
+            2 => {
+                if let Some(value) = new_data(rng) {
+                    let index = rng.random_range(0..=len);
+                    return VectorDiff::Insert { index, value };
+                }
+            }
+            3 => {
+                if let Some(value) = new_data(rng) {
+                    return VectorDiff::Append {
+                        values: Vector::from([value]),
+                    };
+                }
+            }
+            4 if len > 0 => return VectorDiff::PopFront,
+            5 if len > 0 => return VectorDiff::PopBack,
+            6 if len > 0 => {
+                let index = rng.random_range(0..len);
+                return VectorDiff::Remove { index };
+            }
+            // Set an item with the same timeline ID, this is an update.
+            7 if len > 0 => {
+                let index = rng.random_range(0..len);
+                let timeline_id = GENERATED_TIMELINE_IDS
+                    .into_iter()
+                    .find(|timeline_id| *timeline_id == items[index].0)
+                    .expect("timeline ID should be generated");
+                return VectorDiff::Set {
+                    index,
+                    value: TestTimelineDiffItemData {
+                        timeline_id,
+                        version: items[index].1.wrapping_add(1),
+                    },
+                };
+            }
+            // Set an item with a different timeline ID, this is a replacement.
+            8 if len > 0 => {
+                if let Some(value) = new_data(rng) {
+                    let index = rng.random_range(0..len);
+                    return VectorDiff::Set { index, value };
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Minimize diff lists that update items with `VectorDiff::Set`, mixed with
+/// other changes.
+#[test]
+fn minimize_set_update_diff() {
+    let data = |timeline_id, version| TestTimelineDiffItemData {
+        timeline_id,
+        version,
+    };
+
+    let store = TestTimelineDiffItemStore::default();
+    store.minimize_diff_list(vec![VectorDiff::Append {
+        values: Vector::from([data("a", 0), data("b", 0), data("c", 0), data("d", 0)]),
+    }]);
+
+    // Update the same item several times.
+    assert_minimized_diff_list_matches_naive(
+        &store,
+        &[
+            VectorDiff::Set {
+                index: 1,
+                value: data("b", 1),
+            },
+            VectorDiff::Set {
+                index: 1,
+                value: data("b", 2),
+            },
+        ],
+    );
+
+    // Update an item that moved.
+    assert_minimized_diff_list_matches_naive(
+        &store,
+        &[
+            VectorDiff::PopFront,
+            VectorDiff::Set {
+                index: 0,
+                value: data("b", 3),
+            },
+            VectorDiff::PushFront {
+                value: data("e", 0),
+            },
+        ],
+    );
+
+    // Update an item that is removed then re-added at the same position.
+    assert_minimized_diff_list_matches_naive(
+        &store,
+        &[
+            VectorDiff::Remove { index: 2 },
+            VectorDiff::Insert {
+                index: 2,
+                value: data("c", 1),
+            },
+            VectorDiff::Set {
+                index: 3,
+                value: data("d", 1),
+            },
+        ],
+    );
+
+    // Update an item right after it was created.
+    assert_minimized_diff_list_matches_naive(
+        &store,
+        &[
+            VectorDiff::PushBack {
+                value: data("f", 0),
+            },
+            VectorDiff::Set {
+                index: 4,
+                value: data("f", 1),
+            },
+        ],
+    );
+
+    // Update an item then remove it.
+    assert_minimized_diff_list_matches_naive(
+        &store,
+        &[
+            VectorDiff::Set {
+                index: 0,
+                value: data("e", 1),
+            },
+            VectorDiff::PopFront,
+        ],
+    );
+}
+
+/// Minimize deterministic sequences of random diff lists and compare the
+/// result with the naive application of the diff lists.
+#[test]
+fn minimize_random_diff_lists() {
+    let mut rng = StdRng::seed_from_u64(0x00F2_AC7A);
+
+    for _ in 0..50 {
+        let store = TestTimelineDiffItemStore::default();
+        let mut items = Vec::new();
+
+        for _ in 0..40 {
+            let n_diffs = rng.random_range(2..8);
+            let mut diff_list = Vec::with_capacity(n_diffs);
+
+            for _ in 0..n_diffs {
+                let diff = random_diff(&mut rng, &items);
+                apply_diff_list_naively(&mut items, vec![diff.clone()]);
+                diff_list.push(diff);
+            }
+
+            assert!(store.can_minimize_diff_list(&diff_list));
+            assert_minimized_diff_list_matches_naive(&store, &diff_list);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+};
+
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use tracing::debug;
+
+use crate::{session::Room, spawn};
+
+/// The maximum number of rooms whose info is initialized concurrently.
+const MAX_CONCURRENT_INITS: usize = 4;
+
+/// The priority of the initialization of the info of a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RoomInitPriority {
+    /// The room is likely to be presented to the user soon, like a favorite
+    /// or an invite.
+    High,
+    /// The room is initialized after the rooms with a high priority, unless it
+    /// is needed sooner.
+    Deferred,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::RoomInitQueue)]
+    pub struct RoomInitQueue {
+        /// The rooms to initialize first.
+        high_priority: RefCell<VecDeque<glib::WeakRef<Room>>>,
+        /// The rooms to initialize when there are no rooms with a high
+        /// priority.
+        deferred: RefCell<VecDeque<glib::WeakRef<Room>>>,
+        /// The number of rooms currently being initialized.
+        running_count: Cell<usize>,
+        /// The number of rooms whose info is waiting to be initialized or is
+        /// being initialized.
+        #[property(get)]
+        pending_count: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RoomInitQueue {
+        const NAME: &'static str = "RoomInitQueue";
+        type Type = super::RoomInitQueue;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for RoomInitQueue {}
+
+    impl RoomInitQueue {
+        /// Add the given room to the queue, with the given priority.
+        pub(super) fn enqueue(&self, room: &Room, priority: RoomInitPriority) {
+            let queue = match priority {
+                RoomInitPriority::High => &self.high_priority,
+                RoomInitPriority::Deferred => &self.deferred,
+            };
+            queue.borrow_mut().push_back(room.downgrade());
+
+            self.process_next();
+        }
+
+        /// Initialize the given room as soon as possible, if it is still
+        /// deferred.
+        pub(super) fn prioritize(&self, room: &Room) {
+            let position = self
+                .deferred
+                .borrow()
+                .iter()
+                .position(|weak_room| weak_room.upgrade().as_ref() == Some(room));
+
+            let Some(weak_room) =
+                position.and_then(|position| self.deferred.borrow_mut().remove(position))
+            else {
+                // The room is already being initialized, or has a high priority.
+                return;
+            };
+
+            // The room was requested most recently, so it should be the next one.
+            self.high_priority.borrow_mut().push_front(weak_room);
+            self.process_next();
+        }
+
+        /// The next room to initialize, if any.
+        fn pop_next(&self) -> Option<Room> {
+            loop {
+                let weak_room = self
+                    .high_priority
+                    .borrow_mut()
+                    .pop_front()
+                    .or_else(|| self.deferred.borrow_mut().pop_front())?;
+
+                // Skip rooms that were dropped.
+                if let Some(room) = weak_room.upgrade() {
+                    return Some(room);
+                }
+            }
+        }
+
+        /// Initialize the next rooms in the queue, as long as the
+        /// concurrency limit is not reached.
+        fn process_next(&self) {
+            while self.running_count.get() < MAX_CONCURRENT_INITS {
+                let Some(room) = self.pop_next() else {
+                    break;
+                };
+
+                self.running_count.set(self.running_count.get() + 1);
+
+                spawn!(
+                    glib::Priority::DEFAULT_IDLE,
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        async move {
+                            room.init_room_info().await;
+
+                            imp.running_count
+                                .set(imp.running_count.get().saturating_sub(1));
+                            imp.process_next();
+                        }
+                    )
+                );
+            }
+
+            self.update_pending_count();
+        }
+
+        /// Update the number of rooms waiting to be initialized.
+        fn update_pending_count(&self) {
+            let count = self.high_priority.borrow().len()
+                + self.deferred.borrow().len()
+                + self.running_count.get();
+            let count = u32::try_from(count).unwrap_or(u32::MAX);
+
+            if self.pending_count.get() == count {
+                return;
+            }
+
+            if count == 0 {
+                debug!("All rooms info are initialized");
+            }
+
+            self.pending_count.set(count);
+            self.obj().notify_pending_count();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A queue to schedule the initialization of the info of rooms.
+    ///
+    /// Rooms with a high priority are initialized first, and the number of
+    /// rooms initialized concurrently is limited, to avoid stampedes when
+    /// there are a lot of rooms.
+    pub struct RoomInitQueue(ObjectSubclass<imp::RoomInitQueue>);
+}
+
+impl RoomInitQueue {
+    pub(crate) fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Add the given room to the queue, with the given priority.
+    pub(crate) fn enqueue(&self, room: &Room, priority: RoomInitPriority) {
+        self.imp().enqueue(room, priority);
+    }
+
+    /// Initialize the given room as soon as possible, if it is still
+    /// deferred.
+    pub(crate) fn prioritize(&self, room: &Room) {
+        self.imp().prioritize(room);
+    }
+}
+
+impl Default for RoomInitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
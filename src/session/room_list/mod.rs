@@ -12,17 +12,22 @@ use gtk::{
     subclass::prelude::*,
 };
 use indexmap::IndexMap;
-use matrix_sdk::sync::RoomUpdates;
+use matrix_sdk::{RoomState, sync::RoomUpdates};
 use ruma::{OwnedRoomId, OwnedRoomOrAliasId, OwnedServerName, RoomId, RoomOrAliasId, UserId};
 use tracing::{error, warn};
 
+mod init_queue;
 mod metainfo;
 mod room_info;
 
 use self::metainfo::RoomListMetainfo;
-pub use self::{metainfo::RoomMetainfo, room_info::RoomListRoomInfo};
+pub use self::{
+    init_queue::{RoomInitPriority, RoomInitQueue},
+    metainfo::RoomMetainfo,
+    room_info::RoomListRoomInfo,
+};
 use crate::{
-    gettext_f,
+    Application, gettext_f,
     prelude::*,
     session::{Room, Session},
     spawn_tokio,
@@ -51,7 +56,11 @@ mod imp {
         /// The rooms metainfo that allow to restore this `RoomList` from its
         /// previous state.
         metainfo: RoomListMetainfo,
+        /// The queue to schedule the initialization of the info of the rooms.
+        #[property(get)]
+        init_queue: RoomInitQueue,
         pub(super) get_wait_source: RefCell<Option<glib::SourceId>>,
+        settings_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -64,20 +73,47 @@ mod imp {
     #[glib::derived_properties]
     impl ObjectImpl for RoomList {
         fn signals() -> &'static [Signal] {
-            static SIGNALS: LazyLock<Vec<Signal>> =
-                LazyLock::new(|| vec![Signal::builder("joining-rooms-changed").build()]);
+            static SIGNALS: LazyLock<Vec<Signal>> = LazyLock::new(|| {
+                vec![
+                    Signal::builder("joining-rooms-changed").build(),
+                    Signal::builder("space-relationships-changed").build(),
+                ]
+            });
             SIGNALS.as_ref()
         }
 
         fn constructed(&self) {
             self.parent_constructed();
             self.metainfo.set_room_list(&self.obj());
+
+            // Update the notification preview of the rooms when the global setting changes.
+            let settings_handler = Application::default().settings().connect_changed(
+                None,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, key| {
+                        if matches!(
+                            key,
+                            "notification-preview" | "notification-preview-hide-encrypted"
+                        ) {
+                            for room in imp.list.borrow().values() {
+                                room.update_notification_preview();
+                            }
+                        }
+                    }
+                ),
+            );
+            self.settings_handler.replace(Some(settings_handler));
         }
 
         fn dispose(&self) {
             if let Some(source) = self.get_wait_source.take() {
                 source.remove();
             }
+            if let Some(handler) = self.settings_handler.take() {
+                Application::default().settings().disconnect(handler);
+            }
         }
     }
 
@@ -294,6 +330,44 @@ mod imp {
             }
         }
 
+        /// Add the rooms known by the client that are missing from this list.
+        ///
+        /// This is used when some room updates were missed. The rooms that are
+        /// already in the list are kept up-to-date by their subscription to
+        /// the room info.
+        pub(super) fn resync(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let client = session.client();
+
+            let mut new_rooms = HashMap::new();
+
+            for matrix_room in client.rooms() {
+                let room_id = matrix_room.room_id().to_owned();
+                self.remove_joining_room((*room_id).into());
+
+                if self.get(&room_id).is_some() {
+                    continue;
+                }
+
+                let is_left = matrix_room.state() == RoomState::Left;
+                let room = Room::new(&session, matrix_room, None);
+
+                if !is_left {
+                    self.metainfo.watch_room(&room);
+                }
+
+                new_rooms.insert(room_id, room);
+            }
+
+            if !new_rooms.is_empty() {
+                let added = new_rooms.len();
+                self.list.borrow_mut().extend(new_rooms);
+                self.items_added(added);
+            }
+        }
+
         /// Join the room with the given identifier.
         pub(super) async fn join_by_id_or_alias(
             &self,
@@ -392,6 +466,11 @@ impl RoomList {
         self.imp().load().await;
     }
 
+    /// Add the rooms known by the client that are missing from this list.
+    pub(crate) fn resync(&self) {
+        self.imp().resync();
+    }
+
     /// Get a snapshot of the rooms list.
     pub(crate) fn snapshot(&self) -> Vec<Room> {
         self.imp().list.borrow().values().cloned().collect()
@@ -571,4 +650,25 @@ impl RoomList {
             }),
         )
     }
+
+    /// Emit the signal that the relationships between spaces and their
+    /// children changed.
+    pub(crate) fn emit_space_relationships_changed(&self) {
+        self.emit_by_name::<()>("space-relationships-changed", &[]);
+    }
+
+    /// Connect to the signal emitted when the relationships between spaces
+    /// and their children changed.
+    pub fn connect_space_relationships_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "space-relationships-changed",
+            true,
+            closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
 }
@@ -1,8 +1,8 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use gtk::{glib, prelude::*, subclass::prelude::*};
-use indexmap::IndexSet;
-use ruma::{OwnedServerName, events::media_preview_config::MediaPreviews};
+use indexmap::{IndexMap, IndexSet};
+use ruma::{OwnedRoomId, OwnedServerName, RoomId, events::media_preview_config::MediaPreviews};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -11,6 +11,14 @@ use crate::{Application, session_list::SessionListSettings};
 
 /// The current version of the stored session settings.
 const CURRENT_VERSION: u8 = 1;
+/// The maximum number of reactions to remember the usage of.
+const MAX_REACTIONS_USAGE: usize = 32;
+/// The default number of events to request when loading more history.
+pub(crate) const DEFAULT_PAGINATION_BATCH_SIZE: u16 = 20;
+/// The minimum number of events to request when loading more history.
+const MIN_PAGINATION_BATCH_SIZE: u16 = 10;
+/// The maximum number of events to request when loading more history.
+const MAX_PAGINATION_BATCH_SIZE: u16 = 100;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::struct_excessive_bools)]
@@ -44,10 +52,72 @@ pub(crate) struct StoredSessionSettings {
     )]
     typing_enabled: bool,
 
+    /// Whether typing notifications are enabled in public rooms for this
+    /// session.
+    ///
+    /// This only applies if typing notifications are enabled.
+    #[serde(
+        default = "ruma::serde::default_true",
+        skip_serializing_if = "ruma::serde::is_true"
+    )]
+    typing_in_public_rooms_enabled: bool,
+
+    /// The rooms where typing notifications are sent or not, overriding the
+    /// global setting.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    typing_overrides: BTreeMap<OwnedRoomId, bool>,
+
     /// The sections that are expanded.
     #[serde(default)]
     sections_expanded: SectionsExpanded,
 
+    /// The spaces that are expanded in the sidebar.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    expanded_spaces: BTreeSet<OwnedRoomId>,
+
+    /// Whether the sidebar shows all rooms at the root level, instead of only
+    /// the rooms that are not in any space.
+    #[serde(default, skip_serializing_if = "ruma::serde::is_default")]
+    show_all_rooms: bool,
+
+    /// The reactions that were sent, with how often they were used.
+    #[serde(default, skip_serializing_if = "ReactionsUsage::is_empty")]
+    reactions_usage: ReactionsUsage,
+
+    /// The rooms where mentions of the whole room are muted.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    at_room_muted_rooms: BTreeSet<OwnedRoomId>,
+
+    /// The rooms that are muted temporarily, with the expiry of the mute.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    temporary_mutes: BTreeMap<OwnedRoomId, TemporaryMute>,
+
+    /// The rooms where messages are sent as Markdown or not, overriding the
+    /// global setting.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    markdown_overrides: BTreeMap<OwnedRoomId, bool>,
+
+    /// The number of events to request when loading more history.
+    #[serde(
+        default = "default_pagination_batch_size",
+        skip_serializing_if = "is_default_pagination_batch_size"
+    )]
+    pagination_batch_size: u16,
+
+    /// The URL of the HTTP or SOCKS proxy to use for this session, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy: Option<String>,
+
+    /// Whether to reduce the data usage when the network connection is
+    /// metered.
+    #[serde(default, skip_serializing_if = "ruma::serde::is_default")]
+    reduce_data_on_metered_connections: bool,
+
+    /// Whether to use the simplified sliding sync endpoint, if the homeserver
+    /// supports it.
+    #[serde(default, skip_serializing_if = "ruma::serde::is_default")]
+    use_sliding_sync: bool,
+
     /// Which rooms display media previews for this session.
     ///
     /// Legacy setting from version 0 of the stored settings.
@@ -69,7 +139,19 @@ impl Default for StoredSessionSettings {
             notifications_enabled: true,
             public_read_receipts_enabled: true,
             typing_enabled: true,
+            typing_in_public_rooms_enabled: true,
+            typing_overrides: Default::default(),
             sections_expanded: Default::default(),
+            expanded_spaces: Default::default(),
+            show_all_rooms: false,
+            reactions_usage: Default::default(),
+            at_room_muted_rooms: Default::default(),
+            temporary_mutes: Default::default(),
+            markdown_overrides: Default::default(),
+            pagination_batch_size: DEFAULT_PAGINATION_BATCH_SIZE,
+            proxy: None,
+            reduce_data_on_metered_connections: false,
+            use_sliding_sync: false,
             media_previews_enabled: Default::default(),
             invite_avatars_enabled: Default::default(),
         }
@@ -101,6 +183,31 @@ mod imp {
         /// Whether typing notifications are enabled for this session.
         #[property(get = Self::typing_enabled, set = Self::set_typing_enabled, explicit_notify, default = true)]
         typing_enabled: PhantomData<bool>,
+        /// Whether typing notifications are enabled in public rooms for this
+        /// session.
+        ///
+        /// This only applies if typing notifications are enabled.
+        #[property(get = Self::typing_in_public_rooms_enabled, set = Self::set_typing_in_public_rooms_enabled, explicit_notify, default = true)]
+        typing_in_public_rooms_enabled: PhantomData<bool>,
+        /// The number of events to request when loading more history.
+        #[property(get = Self::pagination_batch_size, set = Self::set_pagination_batch_size, explicit_notify, minimum = MIN_PAGINATION_BATCH_SIZE.into(), maximum = MAX_PAGINATION_BATCH_SIZE.into(), default = DEFAULT_PAGINATION_BATCH_SIZE.into())]
+        pagination_batch_size: PhantomData<u32>,
+        /// The URL of the HTTP or SOCKS proxy to use for this session.
+        ///
+        /// An empty string means that no proxy is used. Changes only apply
+        /// to the next time the session is restored.
+        #[property(get = Self::proxy, set = Self::set_proxy, explicit_notify)]
+        proxy: PhantomData<String>,
+        /// Whether to reduce the data usage when the network connection is
+        /// metered.
+        #[property(get = Self::reduce_data_on_metered_connections, set = Self::set_reduce_data_on_metered_connections, explicit_notify)]
+        reduce_data_on_metered_connections: PhantomData<bool>,
+        /// Whether to use the simplified sliding sync endpoint, if the
+        /// homeserver supports it.
+        ///
+        /// Changes only apply to the next time the session is restored.
+        #[property(get = Self::use_sliding_sync, set = Self::set_use_sliding_sync, explicit_notify)]
+        use_sliding_sync: PhantomData<bool>,
     }
 
     #[glib::object_subclass]
@@ -163,6 +270,110 @@ mod imp {
             self.obj().notify_typing_enabled();
         }
 
+        /// Whether typing notifications are enabled in public rooms for this
+        /// session.
+        fn typing_in_public_rooms_enabled(&self) -> bool {
+            self.stored_settings.borrow().typing_in_public_rooms_enabled
+        }
+
+        /// Set whether typing notifications are enabled in public rooms for
+        /// this session.
+        fn set_typing_in_public_rooms_enabled(&self, enabled: bool) {
+            if self.typing_in_public_rooms_enabled() == enabled {
+                return;
+            }
+
+            self.stored_settings
+                .borrow_mut()
+                .typing_in_public_rooms_enabled = enabled;
+            session_list_settings().save();
+            self.obj().notify_typing_in_public_rooms_enabled();
+        }
+
+        /// The number of events to request when loading more history.
+        fn pagination_batch_size(&self) -> u32 {
+            self.stored_settings.borrow().pagination_batch_size.into()
+        }
+
+        /// Set the number of events to request when loading more history.
+        ///
+        /// The value is clamped to a sane range.
+        fn set_pagination_batch_size(&self, size: u32) {
+            let size = u16::try_from(size)
+                .unwrap_or(u16::MAX)
+                .clamp(MIN_PAGINATION_BATCH_SIZE, MAX_PAGINATION_BATCH_SIZE);
+
+            if self.stored_settings.borrow().pagination_batch_size == size {
+                return;
+            }
+
+            self.stored_settings.borrow_mut().pagination_batch_size = size;
+            session_list_settings().save();
+            self.obj().notify_pagination_batch_size();
+        }
+
+        /// The URL of the HTTP or SOCKS proxy to use for this session.
+        fn proxy(&self) -> String {
+            self.stored_settings
+                .borrow()
+                .proxy
+                .clone()
+                .unwrap_or_default()
+        }
+
+        /// Set the URL of the HTTP or SOCKS proxy to use for this session.
+        fn set_proxy(&self, proxy: String) {
+            let proxy = Some(proxy.trim().to_owned()).filter(|proxy| !proxy.is_empty());
+
+            if self.stored_settings.borrow().proxy == proxy {
+                return;
+            }
+
+            self.stored_settings.borrow_mut().proxy = proxy;
+            session_list_settings().save();
+            self.obj().notify_proxy();
+        }
+
+        /// Whether to reduce the data usage when the network connection is
+        /// metered.
+        fn reduce_data_on_metered_connections(&self) -> bool {
+            self.stored_settings
+                .borrow()
+                .reduce_data_on_metered_connections
+        }
+
+        /// Set whether to reduce the data usage when the network connection is
+        /// metered.
+        fn set_reduce_data_on_metered_connections(&self, reduce: bool) {
+            if self.reduce_data_on_metered_connections() == reduce {
+                return;
+            }
+
+            self.stored_settings
+                .borrow_mut()
+                .reduce_data_on_metered_connections = reduce;
+            session_list_settings().save();
+            self.obj().notify_reduce_data_on_metered_connections();
+        }
+
+        /// Whether to use the simplified sliding sync endpoint, if the
+        /// homeserver supports it.
+        fn use_sliding_sync(&self) -> bool {
+            self.stored_settings.borrow().use_sliding_sync
+        }
+
+        /// Set whether to use the simplified sliding sync endpoint, if the
+        /// homeserver supports it.
+        fn set_use_sliding_sync(&self, enabled: bool) {
+            if self.use_sliding_sync() == enabled {
+                return;
+            }
+
+            self.stored_settings.borrow_mut().use_sliding_sync = enabled;
+            session_list_settings().save();
+            self.obj().notify_use_sliding_sync();
+        }
+
         /// Apply the migration of the stored settings from version 0 to version
         /// 1.
         pub(crate) fn apply_version_1_migration(&self) {
@@ -246,6 +457,174 @@ impl SessionSettings {
         session_list_settings().save();
     }
 
+    /// Record that the given reaction was sent.
+    pub(crate) fn record_reaction_usage(&self, key: &str) {
+        self.imp()
+            .stored_settings
+            .borrow_mut()
+            .reactions_usage
+            .record(key);
+        session_list_settings().save();
+    }
+
+    /// The most frequently used reactions, up to the given number.
+    pub(crate) fn frequent_reactions(&self, count: usize) -> Vec<String> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .reactions_usage
+            .most_frequent(count)
+    }
+
+    /// The most recently used reactions, up to the given number.
+    pub(crate) fn recent_reactions(&self, count: usize) -> Vec<String> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .reactions_usage
+            .most_recent(count)
+    }
+
+    /// Whether mentions of the whole room are muted in the room with the
+    /// given ID.
+    pub(crate) fn is_at_room_muted(&self, room_id: &RoomId) -> bool {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .at_room_muted_rooms
+            .contains(room_id)
+    }
+
+    /// Set whether mentions of the whole room are muted in the room with the
+    /// given ID.
+    pub(crate) fn set_at_room_muted(&self, room_id: &RoomId, muted: bool) {
+        if self.is_at_room_muted(room_id) == muted {
+            return;
+        }
+
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if muted {
+                stored_settings
+                    .at_room_muted_rooms
+                    .insert(room_id.to_owned());
+            } else {
+                stored_settings.at_room_muted_rooms.remove(room_id);
+            }
+        }
+
+        session_list_settings().save();
+    }
+
+    /// The rooms that are muted temporarily.
+    pub(crate) fn temporary_mutes(&self) -> BTreeMap<OwnedRoomId, TemporaryMute> {
+        self.imp().stored_settings.borrow().temporary_mutes.clone()
+    }
+
+    /// The temporary mute of the room with the given ID, if any.
+    pub(crate) fn temporary_mute(&self, room_id: &RoomId) -> Option<TemporaryMute> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .temporary_mutes
+            .get(room_id)
+            .cloned()
+    }
+
+    /// Set the temporary mute of the room with the given ID.
+    ///
+    /// If `mute` is `None`, the temporary mute is removed.
+    pub(crate) fn set_temporary_mute(&self, room_id: &RoomId, mute: Option<TemporaryMute>) {
+        if self.temporary_mute(room_id) == mute {
+            return;
+        }
+
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if let Some(mute) = mute {
+                stored_settings
+                    .temporary_mutes
+                    .insert(room_id.to_owned(), mute);
+            } else {
+                stored_settings.temporary_mutes.remove(room_id);
+            }
+        }
+
+        session_list_settings().save();
+    }
+
+    /// Whether messages are sent as Markdown in the room with the given ID,
+    /// if it overrides the global setting.
+    pub(crate) fn markdown_override(&self, room_id: &RoomId) -> Option<bool> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .markdown_overrides
+            .get(room_id)
+            .copied()
+    }
+
+    /// Set whether messages are sent as Markdown in the room with the given
+    /// ID.
+    ///
+    /// If `enabled` is `None`, the global setting is used.
+    pub(crate) fn set_markdown_override(&self, room_id: &RoomId, enabled: Option<bool>) {
+        if self.markdown_override(room_id) == enabled {
+            return;
+        }
+
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if let Some(enabled) = enabled {
+                stored_settings
+                    .markdown_overrides
+                    .insert(room_id.to_owned(), enabled);
+            } else {
+                stored_settings.markdown_overrides.remove(room_id);
+            }
+        }
+
+        session_list_settings().save();
+    }
+
+    /// Whether typing notifications are sent in the room with the given ID,
+    /// if it overrides the global setting.
+    pub(crate) fn typing_override(&self, room_id: &RoomId) -> Option<bool> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .typing_overrides
+            .get(room_id)
+            .copied()
+    }
+
+    /// Set whether typing notifications are sent in the room with the given
+    /// ID.
+    ///
+    /// If `enabled` is `None`, the global setting is used.
+    pub(crate) fn set_typing_override(&self, room_id: &RoomId, enabled: Option<bool>) {
+        if self.typing_override(room_id) == enabled {
+            return;
+        }
+
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if let Some(enabled) = enabled {
+                stored_settings
+                    .typing_overrides
+                    .insert(room_id.to_owned(), enabled);
+            } else {
+                stored_settings.typing_overrides.remove(room_id);
+            }
+        }
+
+        session_list_settings().save();
+    }
+
     /// Whether the section with the given name is expanded.
     pub(crate) fn is_section_expanded(&self, section_name: SidebarSectionName) -> bool {
         self.imp()
@@ -264,6 +643,105 @@ impl SessionSettings {
             .set_section_expanded(section_name, expanded);
         session_list_settings().save();
     }
+
+    /// Whether the space with the given ID is expanded in the sidebar.
+    pub(crate) fn is_space_expanded(&self, space_id: &RoomId) -> bool {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .expanded_spaces
+            .contains(space_id)
+    }
+
+    /// Set whether the space with the given ID is expanded in the sidebar.
+    pub(crate) fn set_space_expanded(&self, space_id: &RoomId, expanded: bool) {
+        if self.is_space_expanded(space_id) == expanded {
+            return;
+        }
+
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if expanded {
+                stored_settings.expanded_spaces.insert(space_id.to_owned());
+            } else {
+                stored_settings.expanded_spaces.remove(space_id);
+            }
+        }
+
+        session_list_settings().save();
+    }
+
+    /// Whether the sidebar shows all rooms at the root level, instead of only
+    /// the rooms that are not in any space.
+    pub(crate) fn show_all_rooms(&self) -> bool {
+        self.imp().stored_settings.borrow().show_all_rooms
+    }
+
+    /// Set whether the sidebar shows all rooms at the root level.
+    pub(crate) fn set_show_all_rooms(&self, show_all: bool) {
+        if self.show_all_rooms() == show_all {
+            return;
+        }
+
+        self.imp().stored_settings.borrow_mut().show_all_rooms = show_all;
+        session_list_settings().save();
+    }
+}
+
+/// The usage of reactions.
+///
+/// The reactions are ordered from the least recently used to the most recently
+/// used, and associated to the number of times they were used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct ReactionsUsage(IndexMap<String, u32>);
+
+impl ReactionsUsage {
+    /// Whether no reaction was used.
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Record that the given reaction was used.
+    fn record(&mut self, key: &str) {
+        let count = self.0.shift_remove(key).unwrap_or_default();
+        self.0.insert(key.to_owned(), count.saturating_add(1));
+
+        if self.0.len() > MAX_REACTIONS_USAGE {
+            // Forget the least recently used reaction.
+            self.0.shift_remove_index(0);
+        }
+    }
+
+    /// The most frequently used reactions, up to the given number.
+    ///
+    /// Reactions used the same number of times are sorted by recency.
+    fn most_frequent(&self, count: usize) -> Vec<String> {
+        let mut reactions = self.0.iter().rev().collect::<Vec<_>>();
+        // The sort is stable so the order by recency is kept.
+        reactions.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+        reactions
+            .into_iter()
+            .take(count)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// The most recently used reactions, up to the given number.
+    fn most_recent(&self, count: usize) -> Vec<String> {
+        self.0.keys().rev().take(count).cloned().collect()
+    }
+}
+
+/// A temporary mute of a room.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct TemporaryMute {
+    /// When the mute expires, as a number of seconds since the Unix epoch.
+    pub(crate) until: i64,
+    /// The notifications setting of the room before it was muted.
+    ///
+    /// It is restored when the mute expires.
+    pub(crate) previous_setting: String,
 }
 
 /// The sections that are expanded.
@@ -349,6 +827,22 @@ impl From<MediaPreviewsGlobalSetting> for MediaPreviews {
     }
 }
 
+/// The default number of events to request when loading more history.
+///
+/// Used for the default value when deserializing.
+fn default_pagination_batch_size() -> u16 {
+    DEFAULT_PAGINATION_BATCH_SIZE
+}
+
+/// Whether the given number of events to request when loading more history is
+/// the default one.
+///
+/// Used to skip the value when serializing.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_default_pagination_batch_size(size: &u16) -> bool {
+    *size == DEFAULT_PAGINATION_BATCH_SIZE
+}
+
 /// The session list settings of the application.
 fn session_list_settings() -> SessionListSettings {
     Application::default().session_list().settings()
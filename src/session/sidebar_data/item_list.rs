@@ -5,7 +5,7 @@ use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use super::{
     SidebarIconItem, SidebarIconItemType, SidebarItem, SidebarSection, SidebarSectionName,
 };
-use crate::session::{RoomCategory, RoomList, VerificationList};
+use crate::session::{Room, RoomCategory, RoomList, VerificationList};
 
 /// The number of top-level items in the sidebar (including back button).
 const TOP_LEVEL_ITEMS_COUNT: usize = 11;
@@ -140,10 +140,16 @@ mod imp {
         /// Set whether to inhibit the expanded state of the sections.
         ///
         /// It means that all the sections will be expanded regardless of
-        /// their "is-expanded" property.
+        /// their "is-expanded" property, and that the children of the spaces
+        /// will not be shown after them.
         pub(super) fn inhibit_expanded(&self, inhibit: bool) {
             for item in self.list() {
                 item.set_inhibit_expanded(inhibit);
+
+                // Do not show the rooms twice, under their space and in their section.
+                if let Some(section) = item.inner_item().downcast_ref::<SidebarSection>() {
+                    section.set_inhibit_space_children(inhibit);
+                }
             }
         }
     }
@@ -177,7 +183,8 @@ impl SidebarItemList {
     /// Set whether to inhibit the expanded state of the sections.
     ///
     /// It means that all the sections will be expanded regardless of their
-    /// "is-expanded" property.
+    /// "is-expanded" property, and that the children of the spaces will not
+    /// be shown after them.
     pub(crate) fn inhibit_expanded(&self, inhibit: bool) {
         self.imp().inhibit_expanded(inhibit);
     }
@@ -207,25 +214,18 @@ impl SidebarItemList {
             .and_downcast()
     }
 
-    /// Notify all sections that the current space has changed.
-    pub(crate) fn notify_current_space_changed(&self) {
-        // Update back button visibility (position 1 in the list)
-        if let Some(back_item) = self.imp().list().get(1) {
-            // Get current space state from the session
-            let room_list = self.room_list();
-            if let Some(session) = room_list.session() {
-                let sidebar = session.sidebar_list_model();
-                let is_in_space = sidebar.current_space().is_some();
-
-                // Show back button only when inside a space
-                back_item.set_visible(is_in_space);
-            }
-        }
+    /// Update the sections and the back button for the given current space
+    /// and whether to show all rooms at the root level.
+    pub(crate) fn update_space_filter(&self, current_space: Option<&Room>, show_all_rooms: bool) {
+        let list = self.imp().list();
+
+        // Show back button only when inside a space (position 1 in the list)
+        list[1].set_visible(current_space.is_some());
 
-        // Trigger re-filtering in all sections by notifying them
-        for item in self.imp().list() {
+        // Trigger re-filtering in all sections
+        for item in list {
             if let Some(section) = item.inner_item().downcast_ref::<SidebarSection>() {
-                section.notify_current_space_changed();
+                section.update_space_filter(current_space, show_all_rooms);
             }
         }
     }
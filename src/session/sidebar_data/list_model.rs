@@ -34,6 +34,12 @@ mod imp {
         /// The current space being viewed (None = root level).
         #[property(get = Self::current_space, set = Self::set_current_space, explicit_notify, nullable)]
         current_space: std::cell::RefCell<Option<Room>>,
+        /// Whether to show all the rooms at the root level.
+        ///
+        /// If this is `false`, only the rooms that are not in any space are
+        /// shown at the root level.
+        #[property(get, set = Self::set_show_all_rooms, explicit_notify)]
+        show_all_rooms: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -90,6 +96,12 @@ mod imp {
         fn set_item_list(&self, item_list: SidebarItemList) {
             let item_list = self.item_list.get_or_init(|| item_list);
 
+            if let Some(session) = item_list.room_list().session() {
+                let show_all_rooms = session.settings().show_all_rooms();
+                self.show_all_rooms.set(show_all_rooms);
+                item_list.update_space_filter(None, show_all_rooms);
+            }
+
             let flattened_model = gtk::FlattenListModel::new(Some(item_list.clone()));
 
             // When search is active, only show rooms.
@@ -152,13 +164,30 @@ mod imp {
                 debug!("Navigating to root level");
             }
 
-            self.current_space.replace(space);
+            self.current_space.replace(space.clone());
             self.obj().notify_current_space();
 
             // Notify all sections and update back button visibility
             if let Some(item_list) = self.item_list.get() {
-                item_list.notify_current_space_changed();
+                item_list.update_space_filter(space.as_ref(), self.show_all_rooms.get());
+            }
+        }
+
+        /// Set whether to show all the rooms at the root level.
+        fn set_show_all_rooms(&self, show_all: bool) {
+            if self.show_all_rooms.get() == show_all {
+                return;
             }
+
+            self.show_all_rooms.set(show_all);
+
+            let item_list = self.item_list();
+            if let Some(session) = item_list.room_list().session() {
+                session.settings().set_show_all_rooms(show_all);
+            }
+
+            item_list.update_space_filter(self.current_space().as_ref(), show_all);
+            self.obj().notify_show_all_rooms();
         }
     }
 }
@@ -1,4 +1,5 @@
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{OwnedRoomId, RoomId};
 
 mod name;
 mod room_category_filter;
@@ -16,7 +17,8 @@ use crate::{
 
 mod imp {
     use std::{
-        cell::{Cell, OnceCell},
+        cell::{Cell, OnceCell, RefCell},
+        collections::HashMap,
         marker::PhantomData,
     };
 
@@ -33,7 +35,18 @@ mod imp {
         /// The filter of this section.
         pub(super) filter: RoomCategoryFilter,
         /// The current space being viewed (None = root level).
-        pub(super) current_space: std::cell::RefCell<Option<Room>>,
+        pub(super) current_space: RefCell<Option<Room>>,
+        /// The source model of the children of the spaces.
+        ///
+        /// Only used for the Spaces section.
+        space_children_source: OnceCell<gio::ListModel>,
+        /// The filters of the children of each space, by space ID.
+        ///
+        /// Only used for the Spaces section.
+        space_children_filters: RefCell<HashMap<OwnedRoomId, glib::WeakRef<gtk::CustomFilter>>>,
+        /// Whether to hide the children of the spaces, regardless of whether
+        /// the spaces are expanded.
+        inhibit_space_children: Cell<bool>,
         /// The name of this section.
         #[property(get, set = Self::set_name, construct_only, builder(SidebarSectionName::default()))]
         name: Cell<SidebarSectionName>,
@@ -73,6 +86,8 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
 
+            self.init_inner_model();
+
             let Some(settings) = self.session_settings() else {
                 return;
             };
@@ -104,11 +119,30 @@ mod imp {
 
         /// Set the source model of this section.
         fn set_model(&self, model: gio::ListModel) {
-            let model = self.model.get_or_init(|| model).clone();
+            self.model
+                .set(model)
+                .expect("model should be uninitialized");
+        }
+
+        /// Initialize the inner model of this section.
+        ///
+        /// This must be called after the name and the source model are set.
+        fn init_inner_model(&self) {
+            let model = self.model().clone();
             let obj = self.obj();
 
             // Special-case room lists so that they are sorted and in the right section.
-            let inner_model = if model.is::<RoomList>() {
+            let inner_model = if let Some(room_list) = model.downcast_ref::<RoomList>() {
+                // Update the filters when the parents or children of rooms change.
+                room_list.connect_space_relationships_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.filter.changed(gtk::FilterChange::Different);
+                        imp.invalidate_space_children(None);
+                    }
+                ));
+
                 // Filter the list to only show rooms for the proper category.
                 self.filter
                     .set_expression(Some(Room::this_expression("category").upcast()));
@@ -149,7 +183,27 @@ mod imp {
                 ));
                 notification_and_highlight_expr_model.set_model(Some(filter_model));
 
-                sort_model.upcast()
+                if self.name.get() == SidebarSectionName::Spaces {
+                    // Show the children of expanded spaces after them.
+                    let map_model = gtk::MapListModel::new(
+                        Some(sort_model),
+                        Some(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            #[upgrade_or_else]
+                            || gio::ListStore::new::<glib::Object>().upcast(),
+                            move |item| {
+                                let space =
+                                    item.downcast_ref::<Room>().expect("item should be a room");
+                                imp.space_with_children_model(space).upcast()
+                            }
+                        )),
+                    );
+
+                    gtk::FlattenListModel::new(Some(map_model)).upcast()
+                } else {
+                    sort_model.upcast()
+                }
             } else {
                 model
             };
@@ -169,6 +223,110 @@ mod imp {
                 .expect("inner model should be uninitialized");
         }
 
+        /// The source model of the children of the spaces.
+        fn space_children_source(&self) -> &gio::ListModel {
+            self.space_children_source.get_or_init(|| {
+                // Watch the properties used to filter and sort the children.
+                let expr_model = ExpressionListModel::new();
+                expr_model.set_expressions(vec![
+                    Room::this_expression("category").upcast(),
+                    Room::this_expression("latest-activity").upcast(),
+                ]);
+                expr_model.set_model(Some(self.model().clone()));
+                expr_model.upcast()
+            })
+        }
+
+        /// Create a model containing the given space, followed by its
+        /// children if it is expanded.
+        fn space_with_children_model(&self, space: &Room) -> gtk::FlattenListModel {
+            let filter = gtk::CustomFilter::new(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[weak]
+                space,
+                #[upgrade_or]
+                false,
+                move |item| item
+                    .downcast_ref::<Room>()
+                    .is_some_and(|room| imp.is_visible_space_child(&space, room))
+            ));
+            self.space_children_filters
+                .borrow_mut()
+                .insert(space.room_id().to_owned(), filter.downgrade());
+
+            let children_model =
+                gtk::FilterListModel::new(Some(self.space_children_source().clone()), Some(filter));
+
+            // Sort the children by activity, like the rooms in the other sections.
+            let sorter = gtk::NumericSorter::builder()
+                .expression(Room::this_expression("latest-activity"))
+                .sort_order(gtk::SortType::Descending)
+                .build();
+            let sort_model = gtk::SortListModel::new(Some(children_model), Some(sorter));
+
+            let space_model = gio::ListStore::new::<Room>();
+            space_model.append(space);
+
+            let models = gio::ListStore::new::<gio::ListModel>();
+            models.append(&space_model);
+            models.append(&sort_model);
+
+            gtk::FlattenListModel::new(Some(models))
+        }
+
+        /// Whether the given room should be presented as a child of the given
+        /// space.
+        fn is_visible_space_child(&self, space: &Room, room: &Room) -> bool {
+            if self.inhibit_space_children.get() || room.is_space() {
+                return false;
+            }
+
+            if !matches!(
+                room.category(),
+                RoomCategory::Favorite | RoomCategory::Normal | RoomCategory::LowPriority
+            ) {
+                return false;
+            }
+
+            room.is_in_space(space.room_id())
+                && self
+                    .session_settings()
+                    .is_some_and(|settings| settings.is_space_expanded(space.room_id()))
+        }
+
+        /// Invalidate the filter of the children of the space with the given
+        /// ID, or of all the spaces if it is `None`.
+        pub(super) fn invalidate_space_children(&self, space_id: Option<&RoomId>) {
+            let filters = {
+                let mut filters = self.space_children_filters.borrow_mut();
+
+                // Forget the filters of spaces that were removed.
+                filters.retain(|_, filter| filter.upgrade().is_some());
+
+                filters
+                    .iter()
+                    .filter(|(id, _)| space_id.is_none_or(|space_id| &***id == space_id))
+                    .filter_map(|(_, filter)| filter.upgrade())
+                    .collect::<Vec<_>>()
+            };
+
+            for filter in filters {
+                filter.changed(gtk::FilterChange::Different);
+            }
+        }
+
+        /// Set whether to hide the children of the spaces, regardless of
+        /// whether the spaces are expanded.
+        pub(super) fn set_inhibit_space_children(&self, inhibit: bool) {
+            if self.inhibit_space_children.get() == inhibit {
+                return;
+            }
+
+            self.inhibit_space_children.set(inhibit);
+            self.invalidate_space_children(None);
+        }
+
         /// Update each of the properties if needed and emit corresponding
         /// signals.
         fn update_notification_count_and_highlight(&self, model: &ExpressionListModel) {
@@ -265,7 +423,7 @@ mod imp {
         }
 
         /// The settings of the current session.
-        fn session_settings(&self) -> Option<SessionSettings> {
+        pub(super) fn session_settings(&self) -> Option<SessionSettings> {
             let model = self.model();
             let session = model
                 .downcast_ref::<RoomList>()
@@ -295,29 +453,18 @@ impl SidebarSection {
             .build()
     }
 
-    /// Notify this section that the current space has changed.
-    pub(crate) fn notify_current_space_changed(&self) {
-        // Get the current space from the global sidebar state
-        let current_space = if let Some(room_list) = self.model().downcast_ref::<RoomList>() {
-            if let Some(session) = room_list.session() {
-                let sidebar = session.sidebar_list_model();
-                sidebar.current_space()
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    /// Update the filter of this section for the given current space and
+    /// whether to show all rooms at the root level.
+    pub(crate) fn update_space_filter(&self, current_space: Option<&Room>, show_all_rooms: bool) {
+        let imp = self.imp();
 
         // Update the section's current space
-        self.imp().current_space.replace(current_space.clone());
+        imp.current_space.replace(current_space.cloned());
 
         // Pass it to the filter
-        self.imp()
-            .filter
-            .imp()
-            .current_space
-            .replace(current_space.clone());
+        let filter_imp = imp.filter.imp();
+        filter_imp.current_space.replace(current_space.cloned());
+        filter_imp.show_all_rooms.set(show_all_rooms);
 
         // Update display name for Spaces section
         if self.name() == SidebarSectionName::Spaces {
@@ -325,7 +472,31 @@ impl SidebarSection {
         }
 
         // Trigger the filter to re-evaluate all items
-        self.imp().filter.changed(gtk::FilterChange::Different);
+        imp.filter.changed(gtk::FilterChange::Different);
+    }
+
+    /// Set whether the given space is expanded in this section.
+    ///
+    /// When a space is expanded, its children are shown after it.
+    pub(crate) fn set_space_expanded(&self, space: &Room, expanded: bool) {
+        let imp = self.imp();
+
+        let Some(settings) = imp.session_settings() else {
+            return;
+        };
+
+        if settings.is_space_expanded(space.room_id()) == expanded {
+            return;
+        }
+
+        settings.set_space_expanded(space.room_id(), expanded);
+        imp.invalidate_space_children(Some(space.room_id()));
+    }
+
+    /// Set whether to hide the children of the spaces, regardless of whether
+    /// the spaces are expanded.
+    pub(crate) fn set_inhibit_space_children(&self, inhibit: bool) {
+        self.imp().set_inhibit_space_children(inhibit);
     }
 
     /// Whether this section should be shown for the drag-n-drop of a room with
@@ -20,6 +20,8 @@ mod imp {
         room_category: Cell<RoomCategory>,
         /// The current space being viewed (set by the section).
         pub(in crate::session::sidebar_data::section) current_space: RefCell<Option<Room>>,
+        /// Whether to show all rooms at the root level (set by the section).
+        pub(in crate::session::sidebar_data::section) show_all_rooms: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -71,8 +73,9 @@ mod imp {
                 // Inside a space: show only children of this space
                 room.is_in_space(space.room_id())
             } else {
-                // At root level: only show orphaned items (not in any space)
-                room.is_orphaned()
+                // At root level: only show orphaned items (not in any space), unless all
+                // rooms should be shown
+                self.show_all_rooms.get() || room.is_orphaned()
             }
         }
     }
@@ -2,15 +2,19 @@ use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::encryption::identities::UserIdentity;
 use ruma::{
     MatrixToUri, OwnedMxcUri, OwnedUserId,
-    api::client::profile::{AvatarUrl, DisplayName},
+    api::client::{
+        error::ErrorKind,
+        profile::{AvatarUrl, DisplayName},
+    },
 };
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use super::{IdentityVerification, Room, Session};
 use crate::{
     components::{AvatarImage, AvatarUriSource, PillSource},
     prelude::*,
     spawn, spawn_tokio,
+    utils::matrix::user_color_css_class,
 };
 
 #[glib::flags(name = "UserActions")]
@@ -24,6 +28,17 @@ impl Default for UserActions {
     }
 }
 
+/// An error when getting or creating a direct chat with a user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum DirectChatError {
+    /// The user does not exist.
+    #[error("user not found")]
+    UserNotFound,
+    /// The direct chat could not be created.
+    #[error("could not create direct chat")]
+    Create,
+}
+
 mod imp {
     use std::{
         cell::{Cell, OnceCell, RefCell},
@@ -303,8 +318,9 @@ impl User {
 
     /// Get or create a direct chat with this user.
     ///
-    /// If there is no existing direct chat, a new one is created.
-    pub(crate) async fn get_or_create_direct_chat(&self) -> Result<Room, ()> {
+    /// If there is no existing direct chat, a new encrypted one is created,
+    /// after checking that the user exists.
+    pub(crate) async fn get_or_create_direct_chat(&self) -> Result<Room, DirectChatError> {
         let user_id = self.user_id();
 
         if let Some(room) = self.direct_chat() {
@@ -312,8 +328,42 @@ impl User {
             return Ok(room);
         }
 
+        if !self.exists().await {
+            return Err(DirectChatError::UserNotFound);
+        }
+
         debug!("Creating direct chat with {user_id}…");
-        self.imp().create_direct_chat().await.map_err(|_| ())
+        self.imp()
+            .create_direct_chat()
+            .await
+            .map_err(|_| DirectChatError::Create)
+    }
+
+    /// Whether this user exists, according to the homeserver.
+    ///
+    /// The profile of the user is requested, and the user is only considered
+    /// to not exist if the homeserver says so. Other errors, like a homeserver
+    /// that does not allow to look up profiles, are ignored.
+    async fn exists(&self) -> bool {
+        let client = self.session().client();
+        let user_id = self.user_id().clone();
+        let handle =
+            spawn_tokio!(async move { client.account().fetch_user_profile_of(&user_id).await });
+
+        match handle.await.expect("task was not aborted") {
+            Ok(_) => true,
+            Err(error) => {
+                if error.client_api_error_kind() == Some(&ErrorKind::NotFound) {
+                    return false;
+                }
+
+                warn!(
+                    user_id = %self.user_id(),
+                    "Could not check whether user exists: {error}"
+                );
+                true
+            }
+        }
     }
 
     /// Ignore this user.
@@ -372,6 +422,11 @@ pub trait UserExt: IsA<User> {
             .set_uri_and_info(uri, None);
     }
 
+    /// The CSS class of the color to use for this user.
+    fn color_css_class(&self) -> String {
+        user_color_css_class(self.user_id())
+    }
+
     /// Get the `matrix.to` URI representation for this `User`.
     fn matrix_to_uri(&self) -> MatrixToUri {
         self.user_id().matrix_to_uri()
@@ -3,7 +3,7 @@ use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::encryption::identities::UserDevices;
 use ruma::{OwnedDeviceId, OwnedUserId};
 use tokio::task::AbortHandle;
-use tracing::error;
+use tracing::{debug, error};
 
 mod other_sessions_list;
 mod user_session;
@@ -11,7 +11,12 @@ mod user_session;
 use self::user_session::UserSessionData;
 pub use self::{other_sessions_list::OtherSessionsList, user_session::UserSession};
 use super::Session;
-use crate::{prelude::*, spawn, spawn_tokio, utils::LoadingState};
+use crate::{
+    components::{AuthDialog, AuthError},
+    prelude::*,
+    spawn, spawn_tokio,
+    utils::LoadingState,
+};
 
 mod imp {
     use std::{
@@ -312,6 +317,59 @@ impl UserSessionsList {
     pub(crate) fn get(&self, device_id: &OwnedDeviceId) -> Option<UserSession> {
         self.imp().get(device_id)
     }
+
+    /// Disconnect all the user sessions except the current one.
+    ///
+    /// Requires a widget because it might show a dialog for UIAA.
+    pub(crate) async fn delete_other_sessions(
+        &self,
+        parent: &impl IsA<gtk::Widget>,
+    ) -> Result<(), AuthError> {
+        let Some(session) = self.session() else {
+            return Err(AuthError::Unknown);
+        };
+
+        let device_ids = self
+            .other_sessions()
+            .iter::<UserSession>()
+            .filter_map(Result::ok)
+            .map(|user_session| user_session.device_id().clone())
+            .collect::<Vec<_>>();
+
+        if device_ids.is_empty() {
+            // Nothing to do.
+            return Ok(());
+        }
+
+        let dialog = AuthDialog::new(&session);
+
+        let res = dialog
+            .authenticate(parent, move |client, auth| {
+                let device_ids = device_ids.clone();
+                async move {
+                    client
+                        .delete_devices(&device_ids, auth)
+                        .await
+                        .map_err(Into::into)
+                }
+            })
+            .await;
+
+        match res {
+            Ok(_) => {
+                self.load().await;
+                Ok(())
+            }
+            Err(error) => {
+                if matches!(error, AuthError::UserCancelled) {
+                    debug!("Deletion of other user sessions cancelled by user");
+                } else {
+                    error!("Could not delete other user sessions: {error}");
+                }
+                Err(error)
+            }
+        }
+    }
 }
 
 impl Default for UserSessionsList {
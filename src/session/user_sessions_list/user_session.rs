@@ -13,7 +13,7 @@ use crate::{
     Application,
     components::{AuthDialog, AuthError},
     prelude::*,
-    session::Session,
+    session::{IdentityVerification, Session},
     spawn_tokio,
     system_settings::ClockFormat,
     utils::matrix::timestamp_to_date,
@@ -123,6 +123,10 @@ mod imp {
         /// Whether this user session is verified.
         #[property(get = Self::verified)]
         verified: PhantomData<bool>,
+        /// Whether this user session is signed by the crypto identity of its
+        /// user.
+        #[property(get = Self::cross_signed)]
+        cross_signed: PhantomData<bool>,
         system_settings_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
@@ -187,6 +191,7 @@ mod imp {
             let old_last_seen_ip = self.last_seen_ip();
             let old_last_seen_ts = self.last_seen_ts();
             let old_verified = self.verified();
+            let old_cross_signed = self.cross_signed();
 
             self.data.replace(Some(data));
 
@@ -206,6 +211,9 @@ mod imp {
             if self.verified() != old_verified {
                 obj.notify_verified();
             }
+            if self.cross_signed() != old_cross_signed {
+                obj.notify_cross_signed();
+            }
         }
 
         /// The ID of this user session, as a string.
@@ -411,6 +419,24 @@ mod imp {
                 .and_then(UserSessionData::crypto)
                 .is_some_and(CryptoDevice::is_verified)
         }
+
+        /// Whether this device is signed by the crypto identity of its user.
+        fn cross_signed(&self) -> bool {
+            self.data
+                .borrow()
+                .as_ref()
+                .and_then(UserSessionData::crypto)
+                .is_some_and(CryptoDevice::is_cross_signed_by_owner)
+        }
+
+        /// The crypto device of this user session, if any.
+        pub(super) fn crypto_device(&self) -> Option<CryptoDevice> {
+            self.data
+                .borrow()
+                .as_ref()
+                .and_then(UserSessionData::crypto)
+                .cloned()
+        }
     }
 }
 
@@ -468,6 +494,25 @@ impl UserSession {
         }
     }
 
+    /// Start the verification of this user session.
+    pub(crate) async fn verify(&self) -> Result<IdentityVerification, ()> {
+        let Some(session) = self.session() else {
+            return Err(());
+        };
+        let Some(device) = self.imp().crypto_device() else {
+            error!(
+                "Could not verify user session {}: crypto device not found",
+                self.device_id()
+            );
+            return Err(());
+        };
+
+        session
+            .verification_list()
+            .create_for_own_session(device)
+            .await
+    }
+
     /// Deletes the `UserSession`.
     ///
     /// Requires a widget because it might show a dialog for UIAA.
@@ -5,9 +5,12 @@ use gtk::{
     prelude::*,
     subclass::prelude::*,
 };
-use matrix_sdk::encryption::verification::{
-    CancelInfo, Emoji, QrVerification, QrVerificationData, QrVerificationState, SasState,
-    SasVerification, Verification, VerificationRequest, VerificationRequestState,
+use matrix_sdk::{
+    crypto::DeviceData,
+    encryption::verification::{
+        CancelInfo, Emoji, QrVerification, QrVerificationData, QrVerificationState, SasState,
+        SasVerification, Verification, VerificationRequest, VerificationRequestState,
+    },
 };
 use qrcode::QrCode;
 use ruma::{
@@ -715,6 +718,25 @@ impl IdentityVerification {
 
     /// The ID of the other device that is being verified.
     pub(crate) fn other_device_id(&self) -> Option<OwnedDeviceId> {
+        self.other_device_data()
+            .map(|device_data| device_data.device_id().to_owned())
+    }
+
+    /// The name to display for the other device that is being verified.
+    ///
+    /// Falls back to the ID of the device if it doesn't have a display name.
+    pub(crate) fn other_device_display_name(&self) -> Option<String> {
+        let device_data = self.other_device_data()?;
+
+        Some(
+            device_data
+                .display_name()
+                .map_or_else(|| device_data.device_id().to_string(), ToOwned::to_owned),
+        )
+    }
+
+    /// The data of the other device that is being verified.
+    fn other_device_data(&self) -> Option<DeviceData> {
         let request_state = self.imp().request().state();
         let other_device_data = match &request_state {
             VerificationRequestState::Requested {
@@ -733,7 +755,7 @@ impl IdentityVerification {
             | VerificationRequestState::Cancelled(_) => None?,
         };
 
-        Some(other_device_data.device_id().to_owned())
+        Some(other_device_data.clone())
     }
 
     /// Information about the verification cancellation, if any.
@@ -1,6 +1,7 @@
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
-    Client as MatrixClient, Room as MatrixRoom, encryption::verification::VerificationRequest,
+    Client as MatrixClient, Room as MatrixRoom,
+    encryption::{identities::Device as CryptoDevice, verification::VerificationRequest},
 };
 use ruma::{
     RoomId,
@@ -349,25 +350,37 @@ impl VerificationList {
         });
 
         match handle.await.expect("task was not aborted") {
-            Ok(request) => {
-                let room = if let Some(room_id) = request.room_id() {
-                    let Some(room) = session.room_list().get(room_id) else {
-                        error!(
-                            "Room for verification request `({}, {})` not found",
-                            request.other_user_id(),
-                            request.flow_id()
-                        );
-                        return Err(());
-                    };
-                    Some(room)
-                } else {
-                    None
-                };
+            Ok(request) => self.add_created_request(&session, request, &user).await,
+            Err(error) => {
+                error!("Could not create identity verification: {error}");
+                Err(())
+            }
+        }
+    }
 
-                let verification = IdentityVerification::new(request, &user, room.as_ref()).await;
-                self.imp().add(verification.clone());
+    /// Create and send a new verification request to the given session of our
+    /// own user.
+    pub(crate) async fn create_for_own_session(
+        &self,
+        device: CryptoDevice,
+    ) -> Result<IdentityVerification, ()> {
+        let Some(session) = self.session() else {
+            error!("Could not create identity verification: failed to upgrade session");
+            return Err(());
+        };
+
+        let supported_methods = load_supported_verification_methods().await;
+
+        let handle = spawn_tokio!(async move {
+            device
+                .request_verification_with_methods(supported_methods)
+                .await
+        });
 
-                Ok(verification)
+        match handle.await.expect("task was not aborted") {
+            Ok(request) => {
+                self.add_created_request(&session, request, &session.user())
+                    .await
             }
             Err(error) => {
                 error!("Could not create identity verification: {error}");
@@ -375,4 +388,31 @@ impl VerificationList {
             }
         }
     }
+
+    /// Add the given verification request that was created by us.
+    async fn add_created_request(
+        &self,
+        session: &Session,
+        request: VerificationRequest,
+        user: &User,
+    ) -> Result<IdentityVerification, ()> {
+        let room = if let Some(room_id) = request.room_id() {
+            let Some(room) = session.room_list().get(room_id) else {
+                error!(
+                    "Room for verification request `({}, {})` not found",
+                    request.other_user_id(),
+                    request.flow_id()
+                );
+                return Err(());
+            };
+            Some(room)
+        } else {
+            None
+        };
+
+        let verification = IdentityVerification::new(request, user, room.as_ref()).await;
+        self.imp().add(verification.clone());
+
+        Ok(verification)
+    }
 }
@@ -0,0 +1,198 @@
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use tracing::debug;
+
+use super::SessionList;
+use crate::{APP_ID, Application, session::Session};
+
+/// The D-Bus interface of the Unity launcher API, supported by most docks.
+const LAUNCHER_ENTRY_INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::LauncherBadge)]
+    pub struct LauncherBadge {
+        /// The list of sessions to count the unread rooms of.
+        #[property(get, set = Self::set_session_list, construct_only)]
+        session_list: glib::WeakRef<SessionList>,
+        /// The sessions that are watched, with their signal handlers.
+        sessions: RefCell<Vec<(glib::WeakRef<Session>, Vec<glib::SignalHandlerId>)>>,
+        /// The count that is currently displayed on the badge.
+        #[property(get)]
+        count: Cell<u64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LauncherBadge {
+        const NAME: &'static str = "LauncherBadge";
+        type Type = super::LauncherBadge;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for LauncherBadge {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            Application::default().settings().connect_changed(
+                Some("launcher-badge-mentions-only"),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_count();
+                    }
+                ),
+            );
+        }
+
+        fn dispose(&self) {
+            self.disconnect_sessions();
+        }
+    }
+
+    impl LauncherBadge {
+        /// Set the list of sessions to count the unread rooms of.
+        fn set_session_list(&self, session_list: &SessionList) {
+            session_list.connect_items_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _, _| {
+                    imp.watch_sessions();
+                }
+            ));
+
+            self.session_list.set(Some(session_list));
+            self.watch_sessions();
+        }
+
+        /// Disconnect the signal handlers of the watched sessions.
+        fn disconnect_sessions(&self) {
+            for (session, handlers) in self.sessions.take() {
+                if let Some(session) = session.upgrade() {
+                    for handler in handlers {
+                        session.disconnect(handler);
+                    }
+                }
+            }
+        }
+
+        /// Watch the unread counts of the sessions in the list.
+        fn watch_sessions(&self) {
+            self.disconnect_sessions();
+
+            let Some(session_list) = self.session_list.upgrade() else {
+                return;
+            };
+
+            let sessions = session_list
+                .iter::<glib::Object>()
+                .filter_map(|session| session.ok().and_downcast::<Session>())
+                .map(|session| {
+                    let mentions_count_handler = session.connect_mentions_count_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_count();
+                        }
+                    ));
+                    let unread_rooms_count_handler =
+                        session.connect_unread_rooms_count_notify(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move |_| {
+                                imp.update_count();
+                            }
+                        ));
+
+                    (
+                        session.downgrade(),
+                        vec![mentions_count_handler, unread_rooms_count_handler],
+                    )
+                })
+                .collect();
+            self.sessions.replace(sessions);
+
+            self.update_count();
+        }
+
+        /// Update the count of the badge.
+        fn update_count(&self) {
+            let mentions_only = Application::default()
+                .settings()
+                .boolean("launcher-badge-mentions-only");
+
+            let count = self
+                .sessions
+                .borrow()
+                .iter()
+                .filter_map(|(session, _)| session.upgrade())
+                .map(|session| {
+                    if mentions_only {
+                        session.mentions_count()
+                    } else {
+                        session.unread_rooms_count()
+                    }
+                })
+                .sum();
+
+            if self.count.get() == count {
+                return;
+            }
+
+            self.count.set(count);
+            self.obj().notify_count();
+
+            publish_count(count);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The badge displaying the number of unread rooms on the icon of the
+    /// application, in docks that support it.
+    pub struct LauncherBadge(ObjectSubclass<imp::LauncherBadge>);
+}
+
+impl LauncherBadge {
+    /// Construct a new `LauncherBadge` for the given list of sessions.
+    pub fn new(session_list: &SessionList) -> Self {
+        glib::Object::builder()
+            .property("session-list", session_list)
+            .build()
+    }
+}
+
+/// Publish the given count with the launcher API.
+///
+/// The badge is hidden when the count is `0`.
+fn publish_count(count: u64) {
+    let application = Application::default();
+    let Some(connection) = application.dbus_connection() else {
+        return;
+    };
+    let Some(object_path) = application.dbus_object_path() else {
+        return;
+    };
+
+    let properties = glib::VariantDict::new(None);
+    properties.insert_value("count", &count.cast_signed().to_variant());
+    properties.insert_value("count-visible", &(count > 0).to_variant());
+
+    let parameters = glib::Variant::tuple_from_iter([
+        format!("application://{APP_ID}.desktop").to_variant(),
+        properties.end(),
+    ]);
+
+    if let Err(error) = connection.emit_signal(
+        None,
+        &object_path,
+        LAUNCHER_ENTRY_INTERFACE,
+        "Update",
+        Some(&parameters),
+    ) {
+        debug!("Could not update launcher badge: {error}");
+    }
+}
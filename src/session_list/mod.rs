@@ -6,12 +6,14 @@ use indexmap::map::IndexMap;
 use tracing::{error, info};
 
 mod failed_session;
+mod launcher_badge;
 mod new_session;
 mod session_info;
 mod session_list_settings;
 
 pub(crate) use self::{
-    failed_session::*, new_session::*, session_info::*, session_list_settings::*,
+    failed_session::*, launcher_badge::LauncherBadge, new_session::*, session_info::*,
+    session_list_settings::*,
 };
 use crate::{
     prelude::*,
@@ -156,6 +158,9 @@ mod imp {
 
             self.set_state(LoadingState::Loading);
 
+            // The settings are needed to log out unsupported sessions.
+            self.settings.load();
+
             let mut sessions = match Secret::restore_sessions().await {
                 Ok(sessions) => sessions,
                 Err(error) => {
@@ -170,7 +175,6 @@ mod imp {
                 }
             };
 
-            self.settings.load();
             let session_ids = self.settings.session_ids();
 
             // Keep the order from the settings.
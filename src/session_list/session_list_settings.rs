@@ -6,6 +6,7 @@ use crate::{
     Application,
     secret::SESSION_ID_LENGTH,
     session::{SessionSettings, StoredSessionSettings},
+    utils::matrix::usable_proxy,
 };
 
 mod imp {
@@ -119,6 +120,12 @@ impl SessionListSettings {
         self.save();
     }
 
+    /// The URL of the proxy to use for the session with the given ID, if any.
+    pub(crate) fn proxy(&self, session_id: &str) -> Option<String> {
+        let proxy = self.imp().sessions.borrow().get(session_id)?.proxy();
+        usable_proxy(&proxy)
+    }
+
     /// Get the list of session IDs stored in these settings.
     pub(crate) fn session_ids(&self) -> IndexSet<String> {
         self.imp().sessions.borrow().keys().cloned().collect()
@@ -1,5 +1,6 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{glib, glib::clone};
+use ruma::OwnedEventId;
 
 use super::{Explore, Invite, InviteRequest, RoomHistory};
 use crate::{
@@ -177,6 +178,8 @@ mod imp {
 
             if let Some(item) = item {
                 let handler = if let Some(room) = item.downcast_ref::<Room>() {
+                    room.prioritize_room_info_init();
+
                     let category_handler = room.connect_category_notify(clone!(
                         #[weak(rename_to = imp)]
                         self,
@@ -252,6 +255,14 @@ mod imp {
             }
         }
 
+        /// Scroll to the event with the given ID in the room history, if it
+        /// is visible.
+        pub(super) fn scroll_to_event(&self, event_id: OwnedEventId) {
+            if self.visible_page() == ContentPage::RoomHistory {
+                self.room_history.scroll_to_event(event_id);
+            }
+        }
+
         /// All the header bars of the children of the content.
         pub(super) fn header_bars(&self) -> [&adw::HeaderBar; 6] {
             [
@@ -283,6 +294,12 @@ impl Content {
         self.imp().handle_paste_action();
     }
 
+    /// Scroll to the event with the given ID in the room history, if it is
+    /// visible.
+    pub(crate) fn scroll_to_event(&self, event_id: OwnedEventId) {
+        self.imp().scroll_to_event(event_id);
+    }
+
     /// All the header bars of the children of the content.
     pub(crate) fn header_bars(&self) -> [&adw::HeaderBar; 6] {
         self.imp().header_bars()
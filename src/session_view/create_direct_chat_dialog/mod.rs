@@ -9,6 +9,7 @@ use crate::{
     Window,
     components::{PillSource, PillSourceRow},
     gettext,
+    prelude::*,
     session::{Session, User},
 };
 
@@ -173,22 +174,25 @@ mod imp {
             self.set_visible_page(CreateDirectChatDialogPage::Loading);
             self.search_entry.set_sensitive(false);
 
-            if let Ok(room) = user.get_or_create_direct_chat().await {
-                let obj = self.obj();
+            match user.get_or_create_direct_chat().await {
+                Ok(room) => {
+                    let obj = self.obj();
 
-                let Some(window) = obj
-                    .parent()
-                    .and_then(|widget| widget.root())
-                    .and_downcast::<Window>()
-                else {
-                    return;
-                };
+                    let Some(window) = obj
+                        .parent()
+                        .and_then(|widget| widget.root())
+                        .and_downcast::<Window>()
+                    else {
+                        return;
+                    };
 
-                window.session_view().select_room(room);
-                obj.close();
-            } else {
-                self.show_error(&gettext("Could not create a new Direct Chat"));
-                self.search_entry.set_sensitive(true);
+                    window.session_view().select_room(room);
+                    obj.close();
+                }
+                Err(error) => {
+                    self.show_error(&error.to_user_facing());
+                    self.search_entry.set_sensitive(true);
+                }
             }
         }
     }
@@ -5,13 +5,21 @@ use matrix_sdk::{
     Error,
     ruma::{
         api::client::{
+            directory::set_room_visibility,
             error::ErrorKind,
-            room::{Visibility, create_room},
+            room::{
+                Visibility, create_room,
+                create_room::v3::{CreationContent, RoomPreset},
+            },
         },
         assign,
+        serde::Raw,
     },
 };
-use ruma::events::{InitialStateEvent, room::encryption::RoomEncryptionEventContent};
+use ruma::{
+    RoomAliasId,
+    events::{InitialStateEvent, room::encryption::RoomEncryptionEventContent},
+};
 use tracing::error;
 
 use crate::{
@@ -52,6 +60,10 @@ mod imp {
         room_address_error_revealer: TemplateChild<gtk::Revealer>,
         #[template_child]
         room_address_error: TemplateChild<gtk::Label>,
+        #[template_child]
+        publish: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        federated: TemplateChild<adw::SwitchRow>,
         /// The current session.
         #[property(get, set = Self::set_session, explicit_notify, nullable)]
         session: glib::WeakRef<Session>,
@@ -113,6 +125,7 @@ mod imp {
 
             let mut can_create = true;
             let room_address = self.room_address.text();
+            let localpart = room_address.trim();
 
             // We don't allow #, : in the room address
             let address_error = if room_address.contains(':') {
@@ -121,12 +134,18 @@ mod imp {
             } else if room_address.contains('#') {
                 can_create = false;
                 Some(gettext("Cannot contain “#”"))
+            } else if localpart.chars().any(char::is_whitespace) {
+                can_create = false;
+                Some(gettext("Cannot contain spaces"))
             } else if room_address.len() > MAX_BYTES {
                 can_create = false;
                 Some(gettext("Too long. Use a shorter address."))
-            } else if room_address.trim().is_empty() {
+            } else if localpart.is_empty() {
                 can_create = false;
                 None
+            } else if !self.is_valid_alias_localpart(localpart) {
+                can_create = false;
+                Some(gettext("Invalid address"))
             } else {
                 None
             };
@@ -145,6 +164,17 @@ mod imp {
             can_create
         }
 
+        /// Whether the given localpart makes a valid room alias on the
+        /// homeserver of the current session.
+        fn is_valid_alias_localpart(&self, localpart: &str) -> bool {
+            let Some(session) = self.session.upgrade() else {
+                return false;
+            };
+
+            let server_name = session.user_id().server_name();
+            RoomAliasId::parse(format!("#{localpart}:{server_name}")).is_ok()
+        }
+
         /// Validate the form and change the corresponding UI elements.
         #[template_callback]
         fn validate_form(&self) {
@@ -183,9 +213,25 @@ mod imp {
                 }
             );
 
+            // Federation can only be disabled when the room is created.
+            if !self.federated.is_active() {
+                let creation_content = assign!(CreationContent::new(), { federate: false });
+
+                match Raw::new(&creation_content) {
+                    Ok(creation_content) => request.creation_content = Some(creation_content),
+                    Err(error) => {
+                        error!("Could not serialize room creation content: {error}");
+                    }
+                }
+            }
+
+            // The room is only published in the directory after it was created.
+            request.visibility = Visibility::Private;
+            let mut publish = false;
+
             if self.visibility_private.is_active() {
                 // The room is private.
-                request.visibility = Visibility::Private;
+                request.preset = Some(RoomPreset::PrivateChat);
 
                 if self.encryption.is_active() {
                     let event = InitialStateEvent::with_empty_state_key(
@@ -195,8 +241,9 @@ mod imp {
                 }
             } else {
                 // The room is public.
-                request.visibility = Visibility::Public;
+                request.preset = Some(RoomPreset::PublicChat);
                 request.room_alias_name = Some(self.room_address.text().trim().to_owned());
+                publish = self.publish.is_active();
             }
 
             let client = session.client();
@@ -209,6 +256,20 @@ mod imp {
                     let Some(window) = obj.root().and_downcast::<Window>() else {
                         return;
                     };
+
+                    if publish {
+                        let client = session.client();
+                        let request = set_room_visibility::v3::Request::new(
+                            matrix_room.room_id().to_owned(),
+                            Visibility::Public,
+                        );
+                        let handle = spawn_tokio!(async move { client.send(request).await });
+
+                        if let Err(error) = handle.await.expect("task was not aborted") {
+                            error!("Could not publish new room in directory: {error}");
+                            toast!(window, gettext("Could not publish room in directory"));
+                        }
+                    }
                     if let Some(room) = session
                         .room_list()
                         .get_wait(matrix_room.room_id(), None)
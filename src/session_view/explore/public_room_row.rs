@@ -9,7 +9,7 @@ use crate::{
     prelude::*,
     session::RemoteRoom,
     toast,
-    utils::{matrix::MatrixIdUri, string::linkify},
+    utils::{links, matrix::MatrixIdUri, string::linkify},
 };
 
 mod imp {
@@ -76,7 +76,7 @@ mod imp {
                             obj.activate_action("session.show-matrix-uri", Some(&uri.to_variant()));
                         glib::Propagation::Stop
                     } else {
-                        glib::Propagation::Proceed
+                        links::handle_activated_external_link(&obj, uri)
                     }
                 }
             ));
@@ -124,6 +124,12 @@ mod imp {
             self.update_button();
             self.update_row();
             self.obj().notify_room();
+
+            // The public rooms directory does not say whether a room is encrypted, so
+            // load the full data of the room.
+            if let Some(room) = self.room.borrow().as_ref() {
+                room.load_data_if_stale();
+            }
         }
 
         /// Update this row for the current state.
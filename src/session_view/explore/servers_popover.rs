@@ -1,5 +1,5 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::glib;
+use gtk::{glib, glib::clone};
 use ruma::ServerName;
 use tracing::error;
 
@@ -7,7 +7,7 @@ use super::{ExploreServer, ExploreServerList, ExploreServerRow};
 use crate::session::Session;
 
 mod imp {
-    use std::marker::PhantomData;
+    use std::{cell::RefCell, marker::PhantomData};
 
     use glib::subclass::InitializingObject;
 
@@ -20,6 +20,8 @@ mod imp {
         #[template_child]
         pub(super) listbox: TemplateChild<gtk::ListBox>,
         #[template_child]
+        add_server_box: TemplateChild<gtk::Box>,
+        #[template_child]
         server_entry: TemplateChild<gtk::Entry>,
         /// The current session.
         #[property(get, set = Self::set_session, explicit_notify)]
@@ -30,6 +32,7 @@ mod imp {
         /// The selected server, if any.
         #[property(get = Self::selected_server)]
         selected_server: PhantomData<Option<ExploreServer>>,
+        is_saving_data_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -71,6 +74,14 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for ExploreServersPopover {
+        fn dispose(&self) {
+            if let Some(session) = self.session.upgrade()
+                && let Some(handler) = self.is_saving_data_handler.take()
+            {
+                session.disconnect(handler);
+            }
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
 
@@ -98,6 +109,12 @@ mod imp {
                 return;
             }
 
+            if let Some(session) = self.session.upgrade()
+                && let Some(handler) = self.is_saving_data_handler.take()
+            {
+                session.disconnect(handler);
+            }
+
             self.session.set(Some(session));
             self.server_list.set_session(session);
 
@@ -105,9 +122,54 @@ mod imp {
             self.listbox
                 .select_row(self.listbox.row_at_index(0).as_ref());
 
+            let is_saving_data_handler = session.connect_is_saving_data_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_custom_servers_state();
+                }
+            ));
+            self.is_saving_data_handler
+                .replace(Some(is_saving_data_handler));
+            self.update_custom_servers_state();
+
             self.obj().notify_session();
         }
 
+        /// Update whether the custom servers can be used, according to whether
+        /// the session is reducing its data usage.
+        ///
+        /// Searching the directory of other servers goes through federation, so
+        /// it is disabled when saving data.
+        fn update_custom_servers_state(&self) {
+            let is_saving_data = self
+                .session
+                .upgrade()
+                .is_some_and(|session| session.is_saving_data());
+
+            self.add_server_box.set_sensitive(!is_saving_data);
+
+            let mut index = 0;
+            while let Some(row) = self.listbox.row_at_index(index) {
+                let is_custom_server = row
+                    .downcast_ref::<ExploreServerRow>()
+                    .and_then(ExploreServerRow::server)
+                    .is_some_and(|server| server.server().is_some());
+
+                if is_custom_server {
+                    row.set_sensitive(!is_saving_data);
+
+                    if is_saving_data && row.is_selected() {
+                        // Fallback to our own server.
+                        self.listbox
+                            .select_row(self.listbox.row_at_index(0).as_ref());
+                    }
+                }
+
+                index += 1;
+            }
+        }
+
         /// Handle when the selected server has changed.
         #[template_callback]
         fn selected_server_changed(&self) {
@@ -159,6 +221,7 @@ mod imp {
             self.server_entry.set_text("");
 
             self.server_list.add_custom_server(server_name);
+            self.update_custom_servers_state();
 
             // Select the new server, it should be the last row in the list.
             let index = i32::try_from(self.server_list.n_items()).unwrap_or(i32::MAX);
@@ -11,7 +11,7 @@ use crate::{
     prelude::*,
     session::{MemberList, Room, RoomCategory, TargetRoomCategory, User},
     toast,
-    utils::matrix::MatrixIdUri,
+    utils::{links, matrix::MatrixIdUri},
 };
 
 mod imp {
@@ -97,7 +97,7 @@ mod imp {
                             obj.activate_action("session.show-matrix-uri", Some(&uri.to_variant()));
                         glib::Propagation::Stop
                     } else {
-                        glib::Propagation::Proceed
+                        links::handle_activated_external_link(&obj, uri)
                     }
                 }
             ));
@@ -6,7 +6,7 @@ use crate::{
     components::{Avatar, LoadingButton},
     session::{Room, RoomCategory, TargetRoomCategory},
     toast,
-    utils::matrix::MatrixIdUri,
+    utils::{links, matrix::MatrixIdUri},
 };
 
 mod imp {
@@ -82,7 +82,7 @@ mod imp {
                             obj.activate_action("session.show-matrix-uri", Some(&uri.to_variant()));
                         glib::Propagation::Stop
                     } else {
-                        glib::Propagation::Proceed
+                        links::handle_activated_external_link(&obj, uri)
                     }
                 }
             ));
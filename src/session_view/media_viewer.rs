@@ -1,20 +1,26 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
-use gtk::{gdk, glib, glib::clone, graphene};
+use gtk::{gdk, gio, glib, glib::clone, graphene};
 use ruma::OwnedEventId;
 use tracing::warn;
 
+use super::room_details::HistoryViewerEvent;
 use crate::{
     components::{MediaContentViewer, ScaleRevealer},
-    session::Room,
+    session::{Event, Room},
     spawn, toast,
-    utils::matrix::VisualMediaMessage,
+    utils::{
+        BoundObject,
+        matrix::{LinkFormat, VisualMediaMessage},
+    },
 };
 
 /// The duration of the animation to fade the background, in ms.
 const ANIMATION_DURATION: u32 = 250;
 /// The duration of the animation to cancel a swipe, in ms.
 const CANCEL_SWIPE_ANIMATION_DURATION: u32 = 400;
+/// The minimum horizontal velocity of a swipe to navigate between media.
+const NAVIGATION_SWIPE_MIN_VELOCITY: f64 = 500.0;
 
 mod imp {
     use std::{
@@ -53,6 +59,14 @@ mod imp {
         /// The filename of the media.
         #[property(get)]
         filename: RefCell<Option<String>>,
+        /// The list of items containing the media that can be navigated.
+        media_model: BoundObject<gio::ListModel>,
+        /// Whether there is a previous media to navigate to.
+        #[property(get)]
+        has_previous: Cell<bool>,
+        /// Whether there is a next media to navigate to.
+        #[property(get)]
+        has_next: Cell<bool>,
         /// The API to keep track of the animation to fade the background.
         animation: OnceCell<adw::TimedAnimation>,
         swipe_tracker: OnceCell<adw::SwipeTracker>,
@@ -82,6 +96,68 @@ mod imp {
                 "media-viewer.close",
             );
 
+            // Navigation actions
+            klass.install_action("media-viewer.previous", None, |obj, _, _| {
+                obj.imp().show_sibling(false);
+            });
+            klass.add_binding_action(
+                gdk::Key::Left,
+                gdk::ModifierType::empty(),
+                "media-viewer.previous",
+            );
+
+            klass.install_action("media-viewer.next", None, |obj, _, _| {
+                obj.imp().show_sibling(true);
+            });
+            klass.add_binding_action(
+                gdk::Key::Right,
+                gdk::ModifierType::empty(),
+                "media-viewer.next",
+            );
+
+            // Zoom actions
+            klass.install_action("media-viewer.zoom-in", None, |obj, _, _| {
+                obj.imp().media.zoom_in();
+            });
+            klass.add_binding_action(
+                gdk::Key::plus,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-in",
+            );
+            klass.add_binding_action(
+                gdk::Key::equal,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-in",
+            );
+            klass.add_binding_action(
+                gdk::Key::KP_Add,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-in",
+            );
+
+            klass.install_action("media-viewer.zoom-out", None, |obj, _, _| {
+                obj.imp().media.zoom_out();
+            });
+            klass.add_binding_action(
+                gdk::Key::minus,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-out",
+            );
+            klass.add_binding_action(
+                gdk::Key::KP_Subtract,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.zoom-out",
+            );
+
+            klass.install_action("media-viewer.reset-zoom", None, |obj, _, _| {
+                obj.imp().media.reset_zoom();
+            });
+            klass.add_binding_action(
+                gdk::Key::_0,
+                gdk::ModifierType::CONTROL_MASK,
+                "media-viewer.reset-zoom",
+            );
+
             // Menu actions
             klass.install_action("media-viewer.copy-image", None, |obj, _, _| {
                 obj.imp().copy_image();
@@ -113,6 +189,17 @@ mod imp {
 
             self.init_swipe_tracker();
 
+            // Do not close the viewer when trying to pan a zoomed image.
+            self.media.connect_is_zoomed_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |media| {
+                    if let Some(swipe_tracker) = imp.swipe_tracker.get() {
+                        swipe_tracker.set_enabled(!media.is_zoomed());
+                    }
+                }
+            ));
+
             // Bind `fullscreened` to the window property of the same name.
             obj.connect_root_notify(|obj| {
                 if let Some(window) = obj.root().and_downcast::<gtk::Window>() {
@@ -234,6 +321,7 @@ mod imp {
             self.message.replace(Some(message));
 
             self.update_menu_actions();
+            self.update_navigation();
             self.media.show_loading();
 
             spawn!(clone!(
@@ -247,6 +335,87 @@ mod imp {
             self.obj().notify_room();
         }
 
+        /// Set the list of items containing the media that can be navigated.
+        pub(super) fn set_media_model(&self, model: Option<gio::ListModel>) {
+            self.media_model.disconnect_signals();
+
+            if let Some(model) = model {
+                let items_changed_handler = model.connect_items_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _, _, _| {
+                        if imp.obj().is_visible() {
+                            imp.update_navigation();
+                        }
+                    }
+                ));
+
+                self.media_model.set(model, vec![items_changed_handler]);
+            }
+
+            self.update_navigation();
+        }
+
+        /// The position of the current media in the list of items, if any.
+        fn current_position(&self, model: &gio::ListModel) -> Option<u32> {
+            let event_id = self.event_id.borrow().clone()?;
+
+            (0..model.n_items()).find(|&position| {
+                model
+                    .item(position)
+                    .and_then(|item| item_event_id(&item))
+                    .is_some_and(|item_event_id| item_event_id == event_id)
+            })
+        }
+
+        /// The media before or after the current one in the list of items, if
+        /// any.
+        fn sibling(&self, forward: bool) -> Option<(VisualMediaMessage, OwnedEventId)> {
+            let model = self.media_model.obj()?;
+            let position = self.current_position(&model)?;
+
+            if forward {
+                (position + 1..model.n_items())
+                    .find_map(|position| model.item(position).and_then(|item| item_media(&item)))
+            } else {
+                (0..position)
+                    .rev()
+                    .find_map(|position| model.item(position).and_then(|item| item_media(&item)))
+            }
+        }
+
+        /// Show the media before or after the current one, if any.
+        fn show_sibling(&self, forward: bool) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let Some((message, event_id)) = self.sibling(forward) else {
+                return;
+            };
+
+            self.set_message(&room, message, Some(event_id));
+        }
+
+        /// Update whether there are previous and next media.
+        fn update_navigation(&self) {
+            let has_previous = self.sibling(false).is_some();
+            let has_next = self.sibling(true).is_some();
+
+            let obj = self.obj();
+
+            if self.has_previous.get() != has_previous {
+                self.has_previous.set(has_previous);
+                obj.notify_has_previous();
+            }
+            if self.has_next.get() != has_next {
+                self.has_next.set(has_next);
+                obj.notify_has_next();
+            }
+
+            obj.action_set_enabled("media-viewer.previous", has_previous);
+            obj.action_set_enabled("media-viewer.next", has_next);
+        }
+
         /// Set the filename of the media.
         fn set_filename(&self, filename: String) {
             if Some(&filename) == self.filename.borrow().as_ref() {
@@ -385,8 +554,7 @@ mod imp {
 
             let content_type = message.content_type();
 
-            let client = session.client();
-            match message.into_tmp_file(&client).await {
+            match message.into_tmp_file(&session).await {
                 Ok(file) => {
                     self.media.view_file(file, Some(content_type)).await;
                 }
@@ -467,6 +635,21 @@ mod imp {
             }
         }
 
+        /// Handle a swipe in the viewer.
+        #[template_callback]
+        fn handle_swipe(&self, velocity_x: f64, velocity_y: f64) {
+            if self.media.is_zoomed()
+                || velocity_x.abs() < NAVIGATION_SWIPE_MIN_VELOCITY
+                || velocity_x.abs() < velocity_y.abs()
+            {
+                return;
+            }
+
+            // Swiping to the left shows the next media, like turning a page.
+            let forward = (velocity_x < 0.0) != (self.obj().direction() == gtk::TextDirection::Rtl);
+            self.show_sibling(forward);
+        }
+
         /// Copy the current image to the clipboard.
         fn copy_image(&self) {
             let Some(texture) = self.media.texture() else {
@@ -489,13 +672,12 @@ mod imp {
             let Some(session) = room.session() else {
                 return;
             };
-            let client = session.client();
 
             media_message
                 .save_to_file(
                     // The timestamp should be unused for visual media messages.
                     &glib::DateTime::now_local().expect("Getting local time should work"),
-                    &client,
+                    &session,
                     &*self.obj(),
                 )
                 .await;
@@ -514,7 +696,8 @@ mod imp {
             let permalink = room.matrix_to_event_uri(event_id).await;
 
             let obj = self.obj();
-            obj.clipboard().set_text(&permalink.to_string());
+            obj.clipboard()
+                .set_text(&LinkFormat::from_settings().format(&permalink));
             toast!(obj, gettext("Message link copied to clipboard"));
         }
     }
@@ -548,4 +731,35 @@ impl MediaViewer {
     ) {
         self.imp().set_message(room, message, event_id);
     }
+
+    /// Set the list of items containing the media that can be navigated.
+    ///
+    /// The items of the list should be timeline events or history viewer
+    /// events. The previous and next media are the closest items before and
+    /// after the current one that contain visual media.
+    pub(crate) fn set_media_model(&self, model: Option<&impl IsA<gio::ListModel>>) {
+        self.imp()
+            .set_media_model(model.map(|model| model.clone().upcast()));
+    }
+}
+
+/// The event ID of the given item of the list of media, if any.
+fn item_event_id(item: &glib::Object) -> Option<OwnedEventId> {
+    if let Some(event) = item.downcast_ref::<Event>() {
+        event.event_id()
+    } else {
+        item.downcast_ref::<HistoryViewerEvent>()
+            .map(HistoryViewerEvent::event_id)
+    }
+}
+
+/// The visual media message and event ID of the given item of the list of
+/// media, if any.
+fn item_media(item: &glib::Object) -> Option<(VisualMediaMessage, OwnedEventId)> {
+    if let Some(event) = item.downcast_ref::<Event>() {
+        Some((event.visual_media_message()?, event.event_id()?))
+    } else {
+        let event = item.downcast_ref::<HistoryViewerEvent>()?;
+        Some((event.visual_media_message()?, event.event_id()))
+    }
 }
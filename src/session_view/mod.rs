@@ -13,12 +13,13 @@ mod media_viewer;
 mod room_details;
 mod room_history;
 mod sidebar;
+mod space_hierarchy_dialog;
 
 use self::{
     content::Content, create_direct_chat_dialog::CreateDirectChatDialog,
     create_room_dialog::CreateRoomDialog, explore::Explore, invite::Invite,
     invite_request::InviteRequest, media_viewer::MediaViewer, room_details::RoomDetails,
-    room_history::RoomHistory, sidebar::Sidebar,
+    room_history::RoomHistory, sidebar::Sidebar, space_hierarchy_dialog::SpaceHierarchyDialog,
 };
 use crate::{
     Window,
@@ -122,6 +123,27 @@ mod imp {
                 },
             );
 
+            klass.install_action(
+                "session.browse-space",
+                Some(&String::static_variant_type()),
+                |obj, _, parameter| {
+                    let Some(parameter) = parameter else {
+                        error!("Could not browse space without an ID");
+                        return;
+                    };
+                    let Some(room_id_str) = parameter.get::<String>() else {
+                        error!("Could not browse space with non-string ID");
+                        return;
+                    };
+                    let Ok(room_id) = <&RoomId>::try_from(room_id_str.as_str()) else {
+                        error!("Could not browse space with invalid ID");
+                        return;
+                    };
+
+                    obj.imp().browse_space(room_id);
+                },
+            );
+
             klass.install_action("session.create-room", None, |obj, _, _| {
                 obj.imp().create_room();
             });
@@ -143,7 +165,7 @@ mod imp {
                 obj.imp().toggle_room_search();
             });
             klass.add_binding_action(
-                gdk::Key::k,
+                gdk::Key::f,
                 gdk::ModifierType::CONTROL_MASK,
                 "session.toggle-room-search",
             );
@@ -219,6 +241,7 @@ mod imp {
 
                     // Withdraw the notifications of the newly selected item.
                     imp.withdraw_selected_item_notifications();
+                    imp.subscribe_to_selected_room();
                 }
             ));
 
@@ -400,6 +423,19 @@ mod imp {
             }
         }
 
+        /// Subscribe to the currently selected room, to receive all the new
+        /// events of its timeline.
+        fn subscribe_to_selected_room(&self) {
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+            let Some(room) = self.selected_room() else {
+                return;
+            };
+
+            session.subscribe_to_room(room.room_id().to_owned());
+        }
+
         /// Select the next room with the given read state in the given
         /// direction.
         ///
@@ -517,6 +553,20 @@ mod imp {
             dialog.present(Some(&*self.obj()));
         }
 
+        /// Show the dialog to browse the space with the given ID.
+        fn browse_space(&self, room_id: &RoomId) {
+            let Some(space) = self
+                .room_list()
+                .and_then(|room_list| room_list.get(room_id))
+            else {
+                warn!("The space with ID {room_id} could not be found");
+                return;
+            };
+
+            let dialog = SpaceHierarchyDialog::new(&space);
+            dialog.present(Some(&*self.obj()));
+        }
+
         /// Show the dialog to preview a room.
         ///
         /// If no room URI is provided, the user will have to enter one.
@@ -554,6 +604,8 @@ mod imp {
             media_message: VisualMediaMessage,
             event_id: Option<OwnedEventId>,
         ) {
+            self.media_viewer
+                .set_media_model(Some(&room.live_timeline().items()));
             self.media_viewer.set_message(room, media_message, event_id);
             self.media_viewer.reveal(source_widget);
         }
@@ -578,16 +630,27 @@ mod imp {
                 SessionIntent::ShowIdentityVerification(key) => {
                     self.select_identity_verification_by_id(&key);
                 }
+                intent @ (SessionIntent::MarkRoomAsRead(_) | SessionIntent::ReplyInRoom(_)) => {
+                    if let Some(session) = self.session.upgrade() {
+                        session.notifications().process_action_intent(intent);
+                    }
+                }
             }
         }
 
         /// Show the given `MatrixIdUri`.
         pub(super) fn show_matrix_uri(&self, uri: MatrixIdUri) {
             match uri {
-                MatrixIdUri::Room(room_uri)
-                | MatrixIdUri::Event(MatrixEventIdUri { room_uri, .. }) => {
+                MatrixIdUri::Room(room_uri) => {
                     self.preview_room(Some(room_uri));
                 }
+                MatrixIdUri::Event(MatrixEventIdUri { event_id, room_uri }) => {
+                    if self.select_room_if_exists(&room_uri.id) {
+                        self.content.scroll_to_event(event_id);
+                    } else {
+                        self.preview_room(Some(room_uri));
+                    }
+                }
                 MatrixIdUri::User(user_id) => {
                     self.show_user_profile_dialog(user_id);
                 }
@@ -569,12 +569,42 @@ mod imp {
             self.public_addresses_error_revealer.set_reveal_child(false);
 
             let aliases = room.aliases();
-            match aliases.add_alt_alias(alias).await {
+            let mut result = aliases.add_alt_alias(alias.clone()).await;
+
+            if result == Err(AddAltAliasError::NotRegistered)
+                && room
+                    .session()
+                    .is_some_and(|session| alias.server_name() == session.user_id().server_name())
+            {
+                // The address is on our homeserver, register it first.
+                match aliases.register_local_alias(alias.clone()).await {
+                    Ok(()) => {
+                        spawn!(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            async move {
+                                imp.update_local_addresses().await;
+                            }
+                        ));
+
+                        result = aliases.add_alt_alias(alias).await;
+                    }
+                    Err(error) => {
+                        toast!(self.obj(), error.to_user_facing());
+
+                        self.public_addresses_list.set_sensitive(true);
+                        row.set_is_loading(false);
+                        return;
+                    }
+                }
+            }
+
+            match result {
                 Ok(()) => {
                     row.set_text("");
                 }
                 Err(error) => {
-                    toast!(self.obj(), gettext("Could not add public address"));
+                    toast!(self.obj(), error.to_user_facing());
 
                     let label = match error {
                         AddAltAliasError::NotRegistered => {
@@ -746,7 +776,7 @@ mod imp {
                     row.set_text("");
                 }
                 Err(error) => {
-                    toast!(self.obj(), gettext("Could not register local address"));
+                    toast!(self.obj(), error.to_user_facing());
 
                     if let RegisterLocalAliasError::AlreadyInUse = error {
                         self.local_addresses_error
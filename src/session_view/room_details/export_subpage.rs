@@ -0,0 +1,260 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{gio, glib, glib::clone};
+use ruma::{MilliSecondsSinceUnixEpoch, UInt};
+use tracing::{debug, error};
+
+use crate::{
+    components::LoadingButtonRow,
+    ngettext_f,
+    prelude::*,
+    session::{
+        ConversationExportError, ConversationExportFormat, ConversationExportOptions, Room,
+        export_conversation,
+    },
+    toast,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/room_details/export_subpage.ui")]
+    #[properties(wrapper_type = super::ExportSubpage)]
+    pub struct ExportSubpage {
+        #[template_child]
+        format_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        include_media_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        date_range_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        start_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        start_calendar: TemplateChild<gtk::Calendar>,
+        #[template_child]
+        end_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        end_calendar: TemplateChild<gtk::Calendar>,
+        #[template_child]
+        export_button: TemplateChild<LoadingButtonRow>,
+        #[template_child]
+        progress_row: TemplateChild<adw::ActionRow>,
+        /// The room to export.
+        #[property(get, construct_only)]
+        room: glib::WeakRef<Room>,
+        /// The cancellable of the ongoing export, if any.
+        cancellable: RefCell<Option<gio::Cancellable>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ExportSubpage {
+        const NAME: &'static str = "RoomDetailsExportSubpage";
+        type Type = super::ExportSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ExportSubpage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // Default to the last month.
+            if let Ok(now) = glib::DateTime::now_local() {
+                if let Ok(month_ago) = now.add_months(-1) {
+                    self.start_calendar.select_day(&month_ago);
+                }
+                self.end_calendar.select_day(&now);
+            }
+
+            self.update_dates();
+        }
+
+        fn dispose(&self) {
+            if let Some(cancellable) = self.cancellable.take() {
+                cancellable.cancel();
+            }
+        }
+    }
+
+    impl WidgetImpl for ExportSubpage {}
+    impl NavigationPageImpl for ExportSubpage {}
+
+    #[gtk::template_callbacks]
+    impl ExportSubpage {
+        /// Update the buttons presenting the selected dates.
+        #[template_callback]
+        fn update_dates(&self) {
+            for (button, calendar) in [
+                (&*self.start_button, &*self.start_calendar),
+                (&*self.end_button, &*self.end_calendar),
+            ] {
+                let label = calendar.date().format("%x").unwrap_or_default();
+                button.set_label(&label);
+                button.popdown();
+            }
+        }
+
+        /// The export options selected by the user.
+        fn options(&self) -> ConversationExportOptions {
+            let format =
+                ConversationExportFormat::from_repr(self.format_row.selected()).unwrap_or_default();
+
+            let (start, end) = if self.date_range_row.is_active() {
+                let start = date_to_timestamp(&self.start_calendar.date(), false);
+                let end = date_to_timestamp(&self.end_calendar.date(), true);
+                (start, end)
+            } else {
+                (None, None)
+            };
+
+            ConversationExportOptions {
+                format,
+                start,
+                end,
+                include_media: self.include_media_row.is_active(),
+            }
+        }
+
+        /// Export the conversation to a file selected by the user.
+        #[template_callback]
+        async fn export(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let obj = self.obj();
+            let options = self.options();
+
+            let dialog = gtk::FileDialog::builder()
+                .title(gettext("Export Conversation"))
+                .modal(true)
+                .accept_label(gettext("Export"))
+                .initial_name(format!(
+                    "{}.{}",
+                    room.display_name(),
+                    options.format.extension()
+                ))
+                .build();
+
+            let file = match dialog
+                .save_future(obj.root().and_downcast_ref::<gtk::Window>())
+                .await
+            {
+                Ok(file) => file,
+                Err(error) => {
+                    if error.matches(gtk::DialogError::Dismissed) {
+                        debug!("File dialog dismissed by user");
+                    } else {
+                        error!("Could not access file: {error}");
+                        toast!(obj, gettext("Could not access file"));
+                    }
+                    return;
+                }
+            };
+
+            let cancellable = gio::Cancellable::new();
+            self.cancellable.replace(Some(cancellable.clone()));
+
+            self.export_button.set_is_loading(true);
+            self.progress_row.set_subtitle("");
+            self.progress_row.set_visible(true);
+
+            let progress = clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |count: usize| {
+                    let subtitle = ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}',
+                        // this is a variable name.
+                        "{count} message loaded",
+                        "{count} messages loaded",
+                        count.try_into().unwrap_or(u32::MAX),
+                        &[("count", &count.to_string())],
+                    );
+                    imp.progress_row.set_subtitle(&subtitle);
+                }
+            );
+
+            let result = export_conversation(&room, options, &file, &cancellable, progress).await;
+
+            match result {
+                Ok(count) => {
+                    let message = ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}',
+                        // this is a variable name.
+                        "Exported {count} message",
+                        "Exported {count} messages",
+                        count.try_into().unwrap_or(u32::MAX),
+                        &[("count", &count.to_string())],
+                    );
+                    toast!(obj, message);
+                }
+                Err(ConversationExportError::Cancelled) => {
+                    toast!(obj, gettext("Export cancelled"));
+                }
+                Err(_) => {
+                    toast!(obj, gettext("Could not export conversation"));
+                }
+            }
+
+            self.cancellable.take();
+            self.progress_row.set_visible(false);
+            self.export_button.set_is_loading(false);
+        }
+
+        /// Cancel the ongoing export.
+        #[template_callback]
+        fn cancel(&self) {
+            if let Some(cancellable) = self.cancellable.borrow().as_ref() {
+                cancellable.cancel();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Subpage to export the conversation of a room to a file.
+    pub struct ExportSubpage(ObjectSubclass<imp::ExportSubpage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ExportSubpage {
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+}
+
+/// Convert the day of the given date to a timestamp.
+///
+/// If `end_of_day` is `true`, the timestamp is the last millisecond of the
+/// day, otherwise it is the first one.
+fn date_to_timestamp(
+    date: &glib::DateTime,
+    end_of_day: bool,
+) -> Option<MilliSecondsSinceUnixEpoch> {
+    let start_of_day =
+        glib::DateTime::from_local(date.year(), date.month(), date.day_of_month(), 0, 0, 0.0)
+            .ok()?;
+
+    let millis = if end_of_day {
+        start_of_day.add_days(1).ok()?.to_unix() * 1000 - 1
+    } else {
+        start_of_day.to_unix() * 1000
+    };
+
+    Some(MilliSecondsSinceUnixEpoch(UInt::try_from(millis).ok()?))
+}
@@ -6,6 +6,7 @@ use gtk::{
     pango,
 };
 use ruma::{
+    OwnedRoomAliasId,
     api::client::{
         directory::{get_room_visibility, set_room_visibility},
         discovery::get_capabilities::v3::Capabilities,
@@ -30,11 +31,14 @@ use crate::{
     gettext_f,
     prelude::*,
     session::{
-        HistoryVisibilityValue, Member, MemberList, MembershipListKind, NotificationsRoomSetting,
-        Room, RoomCategory,
+        Member, MemberList, MembershipListKind, NotificationPreview, NotificationSound,
+        NotificationsRoomSetting, Room, RoomCategory,
     },
     spawn, spawn_tokio, toast,
-    utils::{BoundObjectWeakRef, TemplateCallbacks, expression, matrix::MatrixIdUri},
+    utils::{
+        BoundObjectWeakRef, TemplateCallbacks, expression, links,
+        matrix::{LinkFormat, MatrixIdUri},
+    },
 };
 
 mod imp {
@@ -78,6 +82,14 @@ mod imp {
         #[template_child]
         notifications_mute_row: TemplateChild<CheckLoadingRow>,
         #[template_child]
+        notifications_sound_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        notifications_preview_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        at_room_muted_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        typing_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
         addresses_group: TemplateChild<adw::PreferencesGroup>,
         #[template_child]
         edit_addresses_button: TemplateChild<gtk::Button>,
@@ -86,6 +98,12 @@ mod imp {
         canonical_alias_row: RefCell<Option<CopyableRow>>,
         alt_aliases_rows: RefCell<Vec<CopyableRow>>,
         #[template_child]
+        main_address_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        main_address_row: TemplateChild<adw::ComboRow>,
+        /// Whether the main address row is being updated from the room.
+        updating_main_address: Cell<bool>,
+        #[template_child]
         join_rule: TemplateChild<ButtonCountRow>,
         #[template_child]
         guest_access: TemplateChild<SwitchLoadingRow>,
@@ -119,6 +137,14 @@ mod imp {
         direct_members_list_has_bound_model: Cell<bool>,
         expr_watch: RefCell<Option<gtk::ExpressionWatch>>,
         notifications_settings_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+        /// Whether the notification sound row is being updated from the room.
+        updating_notification_sound: Cell<bool>,
+        /// Whether the notification preview row is being updated from the
+        /// room.
+        updating_notification_preview: Cell<bool>,
+        /// Whether the typing notifications row is being updated from the
+        /// room.
+        updating_typing_override: Cell<bool>,
         membership_handler: RefCell<Option<glib::SignalHandlerId>>,
         permissions_handler: RefCell<Option<glib::SignalHandlerId>>,
         canonical_alias_handler: RefCell<Option<glib::SignalHandlerId>>,
@@ -161,7 +187,7 @@ mod imp {
                 glib::Propagation::Proceed,
                 move |_, uri| {
                     let Ok(uri) = MatrixIdUri::parse(uri) else {
-                        return glib::Propagation::Proceed;
+                        return links::handle_activated_external_link(&obj, uri);
                     };
                     let Some(room_details) = obj
                         .ancestor(RoomDetails::static_type())
@@ -212,6 +238,7 @@ mod imp {
                 move |_| {
                     imp.update_upgrade_button();
                     imp.update_edit_addresses_button();
+                    imp.update_main_address();
                     imp.update_join_rule();
                     imp.update_guest_access();
                     imp.update_history_visibility();
@@ -312,6 +339,11 @@ mod imp {
                 )),
             ];
 
+            room.bind_property("at-room-muted", &*self.at_room_muted_row, "active")
+                .sync_create()
+                .bidirectional()
+                .build();
+
             self.room.set(room, room_handler_ids);
             obj.notify_room();
 
@@ -358,6 +390,21 @@ mod imp {
                     imp.update_publish().await;
                 }
             ));
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.update_notification_sound().await;
+                }
+            ));
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.update_notification_preview().await;
+                }
+            ));
+            self.update_typing_override();
 
             self.load_capabilities();
         }
@@ -637,6 +684,145 @@ mod imp {
             ));
         }
 
+        /// Update the notification sound row with the setting of the room.
+        async fn update_notification_sound(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let sound = room.notification_sound().await;
+
+            self.updating_notification_sound.set(true);
+            self.notifications_sound_row.set_selected(sound as u32);
+            self.updating_notification_sound.set(false);
+        }
+
+        /// The notification sound currently selected in the row.
+        fn selected_notification_sound(&self) -> NotificationSound {
+            NotificationSound::from_repr(self.notifications_sound_row.selected())
+                .unwrap_or_default()
+        }
+
+        /// Set the notification sound of the room to the one selected in the
+        /// row.
+        #[template_callback]
+        async fn set_notification_sound(&self) {
+            if self.updating_notification_sound.get() {
+                return;
+            }
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let sound = self.selected_notification_sound();
+
+            if room.set_notification_sound(sound).await.is_err() {
+                toast!(self.obj(), gettext("Could not change notification sound"));
+                self.update_notification_sound().await;
+            }
+        }
+
+        /// Play a preview of the notification sound selected in the row.
+        #[template_callback]
+        fn play_notification_sound(&self) {
+            self.selected_notification_sound().play();
+        }
+
+        /// Update the notification preview row with the setting of the room.
+        async fn update_notification_preview(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            // The first position is for the global setting.
+            let position = room
+                .notification_preview_setting()
+                .await
+                .map_or(0, |preview| preview as u32 + 1);
+
+            self.updating_notification_preview.set(true);
+            self.notifications_preview_row.set_selected(position);
+            self.updating_notification_preview.set(false);
+        }
+
+        /// Set the notification preview setting of the room to the one selected
+        /// in the row.
+        #[template_callback]
+        async fn set_notification_preview(&self) {
+            if self.updating_notification_preview.get() {
+                return;
+            }
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            // The first position is for the global setting.
+            let preview = self
+                .notifications_preview_row
+                .selected()
+                .checked_sub(1)
+                .and_then(NotificationPreview::from_repr);
+
+            if room
+                .set_notification_preview_setting(preview)
+                .await
+                .is_err()
+            {
+                toast!(
+                    self.obj(),
+                    gettext("Could not change notification content setting")
+                );
+                self.update_notification_preview().await;
+            }
+        }
+
+        /// Update the typing notifications row with the setting of the room.
+        fn update_typing_override(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            // The first position is for the global setting.
+            let position = match session.settings().typing_override(room.room_id()) {
+                None => 0,
+                Some(true) => 1,
+                Some(false) => 2,
+            };
+
+            self.updating_typing_override.set(true);
+            self.typing_row.set_selected(position);
+            self.updating_typing_override.set(false);
+        }
+
+        /// Set whether typing notifications are sent in the room, according to
+        /// the row.
+        #[template_callback]
+        fn set_typing_override(&self) {
+            if self.updating_typing_override.get() {
+                return;
+            }
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            // The first position is for the global setting.
+            let enabled = match self.typing_row.selected() {
+                1 => Some(true),
+                2 => Some(false),
+                _ => None,
+            };
+
+            session
+                .settings()
+                .set_typing_override(room.room_id(), enabled);
+        }
+
         /// Update the button to edit addresses.
         fn update_edit_addresses_button(&self) {
             let Some(room) = self.room.obj() else {
@@ -731,6 +917,99 @@ mod imp {
 
             self.no_addresses_label
                 .set_visible(!has_canonical_alias && alt_aliases_count == 0);
+
+            self.update_main_address();
+        }
+
+        /// The candidates for the main address of the room.
+        ///
+        /// The current main address is always first.
+        fn main_address_candidates(&self) -> Vec<OwnedRoomAliasId> {
+            let Some(room) = self.room.obj() else {
+                return vec![];
+            };
+            let aliases = room.aliases();
+
+            aliases
+                .canonical_alias()
+                .into_iter()
+                .chain(aliases.alt_aliases())
+                .collect()
+        }
+
+        /// Update the row to select the main address.
+        fn update_main_address(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let candidates = self.main_address_candidates();
+            let can_change = room.is_joined()
+                && room
+                    .permissions()
+                    .is_allowed_to(PowerLevelAction::SendState(
+                        StateEventType::RoomCanonicalAlias,
+                    ));
+
+            self.main_address_box
+                .set_visible(can_change && !candidates.is_empty());
+
+            let has_canonical_alias = room.aliases().canonical_alias().is_some();
+
+            let mut strings = vec![gettext("None")];
+            strings.extend(candidates.iter().map(ToString::to_string));
+            let model =
+                gtk::StringList::new(&strings.iter().map(String::as_str).collect::<Vec<_>>());
+
+            self.updating_main_address.set(true);
+            self.main_address_row.set_model(Some(&model));
+            self.main_address_row
+                .set_selected(u32::from(has_canonical_alias));
+            self.main_address_row.set_sensitive(true);
+            self.updating_main_address.set(false);
+        }
+
+        /// Change the main address of the room to the one selected by the
+        /// user.
+        #[template_callback]
+        async fn change_main_address(&self) {
+            if self.updating_main_address.get() {
+                return;
+            }
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let aliases = room.aliases();
+            let current = aliases.canonical_alias();
+            let selected = self.main_address_row.selected();
+
+            let new = if selected == 0 {
+                None
+            } else {
+                self.main_address_candidates()
+                    .into_iter()
+                    .nth((selected - 1) as usize)
+            };
+
+            if new == current {
+                return;
+            }
+
+            self.main_address_row.set_sensitive(false);
+
+            let result = match (new, &current) {
+                (Some(alias), _) => aliases.set_canonical_alias(alias).await,
+                (None, Some(current)) => aliases.remove_canonical_alias(current).await,
+                (None, None) => Ok(()),
+            };
+
+            // On success, the row is updated when the change is received from the
+            // homeserver.
+            if result.is_err() {
+                toast!(self.obj(), gettext("Could not change the main address"));
+                self.update_main_address();
+            }
         }
 
         fn remove_alt_aliases_rows(&self) {
@@ -749,7 +1028,8 @@ mod imp {
             let permalink = room.matrix_to_uri().await;
 
             let obj = self.obj();
-            obj.clipboard().set_text(&permalink.to_string());
+            obj.clipboard()
+                .set_text(&LinkFormat::from_settings().format(&permalink));
             toast!(obj, gettext("Room link copied to clipboard"));
         }
 
@@ -919,27 +1199,14 @@ mod imp {
 
             let history_visibility = room.history_visibility();
 
-            let text = match history_visibility {
-                HistoryVisibilityValue::WorldReadable => {
-                    gettext("Anyone, even if they are not in the room")
-                }
-                HistoryVisibilityValue::Shared => {
-                    gettext("Members only, since this option was selected")
-                }
-                HistoryVisibilityValue::Invited => gettext("Members only, since they were invited"),
-                HistoryVisibilityValue::Joined => {
-                    gettext("Members only, since they joined the room")
-                }
-                HistoryVisibilityValue::Unsupported => gettext("Unsupported rule"),
-            };
-            self.history_visibility.set_subtitle(&text);
+            self.history_visibility
+                .set_subtitle(&history_visibility.description());
 
-            let can_change = history_visibility != HistoryVisibilityValue::Unsupported
-                && room
-                    .permissions()
-                    .is_allowed_to(PowerLevelAction::SendState(
-                        StateEventType::RoomHistoryVisibility,
-                    ));
+            let can_change = room
+                .permissions()
+                .is_allowed_to(PowerLevelAction::SendState(
+                    StateEventType::RoomHistoryVisibility,
+                ));
 
             self.history_visibility.set_activatable(can_change);
         }
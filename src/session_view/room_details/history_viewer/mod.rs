@@ -8,12 +8,10 @@ mod visual_media;
 mod visual_media_item;
 
 pub(crate) use self::{
-    audio::AudioHistoryViewer, file::FileHistoryViewer, timeline::HistoryViewerTimeline,
-    visual_media::VisualMediaHistoryViewer,
+    audio::AudioHistoryViewer, event::HistoryViewerEvent, file::FileHistoryViewer,
+    timeline::HistoryViewerTimeline, visual_media::VisualMediaHistoryViewer,
 };
 use self::{
-    audio_row::AudioRow,
-    event::{HistoryViewerEvent, HistoryViewerEventType},
-    file_row::FileRow,
+    audio_row::AudioRow, event::HistoryViewerEventType, file_row::FileRow,
     visual_media_item::VisualMediaItem,
 };
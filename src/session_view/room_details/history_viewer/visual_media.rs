@@ -8,7 +8,7 @@ use crate::{
     prelude::*,
     session_view::MediaViewer,
     spawn,
-    utils::{BoundConstructOnlyObject, LoadingState},
+    utils::{BoundConstructOnlyObject, LoadingState, matrix::VisualMediaMessage},
 };
 
 /// The minimum number of items that should be loaded.
@@ -16,8 +16,35 @@ const MIN_N_ITEMS: u32 = 50;
 /// The minimum size requested by an item.
 const SIZE_REQUEST: i32 = 150;
 
+/// The filter applied to the media in the history viewer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+enum VisualMediaFilter {
+    /// Show all the images and videos.
+    #[default]
+    All,
+    /// Show only the images.
+    Images,
+    /// Show only the videos.
+    Videos,
+}
+
+impl VisualMediaFilter {
+    /// Whether the given media message matches this filter.
+    fn matches(self, media_message: &VisualMediaMessage) -> bool {
+        match self {
+            Self::All => true,
+            Self::Images => !matches!(media_message, VisualMediaMessage::Video(_)),
+            Self::Videos => matches!(media_message, VisualMediaMessage::Video(_)),
+        }
+    }
+}
+
 mod imp {
-    use std::ops::ControlFlow;
+    use std::{
+        cell::{Cell, OnceCell},
+        ops::ControlFlow,
+    };
 
     use glib::subclass::InitializingObject;
 
@@ -35,6 +62,12 @@ mod imp {
         stack: TemplateChild<gtk::Stack>,
         #[template_child]
         grid_view: TemplateChild<gtk::GridView>,
+        #[template_child]
+        filter_toggle_group: TemplateChild<adw::ToggleGroup>,
+        /// The filter applied to the media.
+        media_filter: Cell<VisualMediaFilter>,
+        /// The filter of the list model.
+        filter: OnceCell<gtk::CustomFilter>,
         /// The timeline containing the media events.
         #[property(get, set = Self::set_timeline, construct_only)]
         timeline: BoundConstructOnlyObject<HistoryViewerTimeline>,
@@ -98,6 +131,18 @@ mod imp {
             });
 
             self.grid_view.set_factory(Some(&factory));
+
+            self.filter_toggle_group.connect_active_name_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |toggle_group| {
+                    let media_filter = toggle_group
+                        .active_name()
+                        .and_then(|name| name.parse().ok())
+                        .unwrap_or_default();
+                    imp.set_media_filter(media_filter);
+                }
+            ));
         }
     }
 
@@ -108,11 +153,26 @@ mod imp {
     impl VisualMediaHistoryViewer {
         /// Set the timeline containing the media events.
         fn set_timeline(&self, timeline: HistoryViewerTimeline) {
-            let filter = gtk::CustomFilter::new(|obj| {
-                obj.downcast_ref::<HistoryViewerEvent>()
-                    .is_some_and(|e| e.event_type() == HistoryViewerEventType::Media)
-                    || obj.is::<LoadingRow>()
-            });
+            let filter = gtk::CustomFilter::new(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                false,
+                move |obj| {
+                    if obj.is::<LoadingRow>() {
+                        return true;
+                    }
+
+                    let media_filter = imp.media_filter.get();
+                    obj.downcast_ref::<HistoryViewerEvent>()
+                        .filter(|e| e.event_type() == HistoryViewerEventType::Media)
+                        .and_then(HistoryViewerEvent::visual_media_message)
+                        .is_some_and(|media_message| media_filter.matches(&media_message))
+                }
+            ));
+            self.filter
+                .set(filter.clone())
+                .expect("filter should be uninitialized");
             let filter_model =
                 gtk::FilterListModel::new(Some(timeline.with_loading_item().clone()), Some(filter));
 
@@ -145,6 +205,30 @@ mod imp {
             ));
         }
 
+        /// Set the filter applied to the media.
+        fn set_media_filter(&self, media_filter: VisualMediaFilter) {
+            if self.media_filter.get() == media_filter {
+                return;
+            }
+
+            self.media_filter.set(media_filter);
+
+            if let Some(filter) = self.filter.get() {
+                filter.changed(gtk::FilterChange::Different);
+            }
+
+            // There might not be enough items with the new filter.
+            if self.needs_more_items() {
+                spawn!(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    async move {
+                        imp.load_more_items().await;
+                    }
+                ));
+            }
+        }
+
         /// Initialize the timeline
         async fn init_timeline(&self) {
             self.load_more_items().await;
@@ -243,6 +327,12 @@ mod imp {
             let media_message = event
                 .visual_media_message()
                 .expect("visual media items should contain only visual message content");
+            let media_model = self
+                .grid_view
+                .model()
+                .and_downcast::<gtk::NoSelection>()
+                .and_then(|model| model.model());
+            self.media_viewer.set_media_model(media_model.as_ref());
             self.media_viewer
                 .set_message(&room, media_message, Some(event.event_id()));
             self.media_viewer.reveal(item);
@@ -3,7 +3,10 @@ use gettextrs::gettext;
 use gtk::{glib, glib::clone};
 use ruma::events::{
     StateEventType,
-    room::{history_visibility::RoomHistoryVisibilityEventContent, power_levels::PowerLevelAction},
+    room::{
+        history_visibility::{HistoryVisibility, RoomHistoryVisibilityEventContent},
+        power_levels::PowerLevelAction,
+    },
 };
 use tracing::error;
 
@@ -29,6 +32,10 @@ mod imp {
     pub struct HistoryVisibilitySubpage {
         #[template_child]
         save_button: TemplateChild<LoadingButton>,
+        #[template_child]
+        list_box: TemplateChild<gtk::ListBox>,
+        /// The row presenting an unsupported value.
+        unsupported_row: RefCell<Option<CheckLoadingRow>>,
         /// The presented room.
         #[property(get, set = Self::set_room, explicit_notify, nullable)]
         room: BoundObjectWeakRef<Room>,
@@ -63,6 +70,22 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for HistoryVisibilitySubpage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            for value in HistoryVisibilityValue::SELECTABLE {
+                self.list_box.append(&value_row(value));
+            }
+
+            // An unsupported value cannot be selected, but we show it if it is
+            // the current value, so the user knows what it is.
+            let unsupported_row = value_row(HistoryVisibilityValue::Unsupported);
+            unsupported_row.set_sensitive(false);
+            unsupported_row.set_visible(false);
+            self.list_box.append(&unsupported_row);
+            self.unsupported_row.replace(Some(unsupported_row));
+        }
+
         fn dispose(&self) {
             self.disconnect_signals();
         }
@@ -111,7 +134,12 @@ mod imp {
                 return;
             };
 
-            self.set_local_value(room.history_visibility());
+            let history_visibility = room.history_visibility();
+            self.set_local_value(history_visibility);
+
+            if let Some(row) = &*self.unsupported_row.borrow() {
+                row.set_visible(history_visibility == HistoryVisibilityValue::Unsupported);
+            }
 
             self.save_button.set_is_loading(false);
             self.update_changed();
@@ -135,28 +163,23 @@ mod imp {
                 return false;
             };
 
-            if room.history_visibility() == HistoryVisibilityValue::Unsupported {
-                return false;
-            }
-
             room.permissions()
                 .is_allowed_to(PowerLevelAction::SendState(
                     StateEventType::RoomHistoryVisibility,
                 ))
         }
 
-        /// Update whether the join rule was changed by the user.
+        /// Update whether the history visibility was changed by the user.
         #[template_callback]
         fn update_changed(&self) {
             let Some(room) = self.room.obj() else {
                 return;
             };
 
-            let changed = if self.can_change() {
-                let current_join_rule = room.history_visibility();
-                let new_join_rule = self.local_value.get();
+            let new_value = self.local_value.get();
 
-                current_join_rule != new_join_rule
+            let changed = if self.can_change() && new_value != HistoryVisibilityValue::Unsupported {
+                room.history_visibility() != new_value
             } else {
                 false
             };
@@ -177,9 +200,14 @@ mod imp {
                 return;
             };
 
+            let Ok(history_visibility) = HistoryVisibility::try_from(self.local_value.get()) else {
+                // We cannot send an unsupported value.
+                return;
+            };
+
             self.save_button.set_is_loading(true);
 
-            let content = RoomHistoryVisibilityEventContent::new(self.local_value.get().into());
+            let content = RoomHistoryVisibilityEventContent::new(history_visibility);
 
             let matrix_room = room.matrix_room().clone();
             let handle = spawn_tokio!(async move { matrix_room.send_state_event(content).await });
@@ -240,3 +268,12 @@ impl HistoryVisibilitySubpage {
         glib::Object::builder().property("room", room).build()
     }
 }
+
+/// Construct a row to select the given history visibility value.
+fn value_row(value: HistoryVisibilityValue) -> CheckLoadingRow {
+    let row = CheckLoadingRow::new();
+    row.set_title(&value.description());
+    row.set_action_name(Some("history-visibility.set-value"));
+    row.set_action_target_value(Some(&value.nick().to_variant()));
+    row
+}
@@ -1,5 +1,5 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gettextrs::ngettext;
+use gettextrs::{gettext, ngettext};
 use gtk::{gdk, glib, glib::clone};
 use tracing::error;
 
@@ -13,10 +13,11 @@ use self::{
     row::InviteRow,
 };
 use crate::{
-    components::{LoadingButton, PillSearchEntry, PillSource},
+    components::{LoadingButton, PillSearchEntry, PillSource, connect_to_identity_server},
+    gettext_f,
     prelude::*,
-    session::{Room, User},
-    toast,
+    session::{IdentityServer, Room, User},
+    spawn_tokio, toast,
 };
 
 mod imp {
@@ -48,6 +49,10 @@ mod imp {
         no_search_page: TemplateChild<adw::StatusPage>,
         #[template_child]
         error_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        email_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        email_invite_button: TemplateChild<LoadingButton>,
         /// The room users will be invited to.
         #[property(get, set = Self::set_room, construct_only)]
         room: glib::WeakRef<Room>,
@@ -118,6 +123,13 @@ mod imp {
                     imp.update_view();
                 }
             ));
+            invite_list.connect_search_term_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_view();
+                }
+            ));
 
             self.search_entry
                 .bind_property("text", invite_list, "search-term")
@@ -145,7 +157,22 @@ mod imp {
 
         /// Update the view for the current state of the list.
         fn update_view(&self) {
-            let state = self.invite_list().state();
+            let invite_list = self.invite_list();
+            let state = invite_list.state();
+
+            // Propose to invite by email if no Matrix user matches the email address.
+            if matches!(state, InviteListState::NoMatching | InviteListState::Error)
+                && let Some(email) = invite_list.search_term().filter(|term| is_email(term))
+            {
+                self.email_page.set_description(Some(&gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "{email} does not seem to use Matrix yet. They can be invited by email to join this room.",
+                    &[("email", &email.escape_markup())],
+                )));
+                self.stack.set_visible_child_name("email");
+                return;
+            }
 
             let page = match state {
                 InviteListState::Initial => "no-search",
@@ -244,9 +271,117 @@ mod imp {
 
             self.invite_button.set_is_loading(false);
         }
+
+        /// Invite the person with the email address in the search term to the
+        /// room.
+        #[template_callback]
+        async fn invite_by_email(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let Some(email) = self
+                .invite_list()
+                .search_term()
+                .filter(|term| is_email(term))
+            else {
+                return;
+            };
+
+            self.email_invite_button.set_is_loading(true);
+
+            if self.send_email_invite(&room, email).await {
+                self.close();
+            }
+
+            self.email_invite_button.set_is_loading(false);
+        }
+
+        /// Send an invite to the given email address via the identity server
+        /// of the user.
+        ///
+        /// Returns `true` if the invite was sent.
+        async fn send_email_invite(&self, room: &Room, email: String) -> bool {
+            let obj = self.obj();
+            let Some(session) = room.session() else {
+                return false;
+            };
+            let client = session.client();
+            let http_client = session.http_client();
+            let handle = spawn_tokio!(async move {
+                IdentityServer::from_account_data(&client, http_client).await
+            });
+
+            let identity_server = match handle.await.expect("task was not aborted") {
+                Ok(Some(identity_server)) => identity_server,
+                Ok(None) => {
+                    toast!(
+                        obj,
+                        gettext(
+                            "No identity server is configured, so invites cannot be sent by email"
+                        )
+                    );
+                    return false;
+                }
+                Err(error) => {
+                    error!("Could not get identity server: {error}");
+                    toast!(obj, gettext("Could not get the identity server"));
+                    return false;
+                }
+            };
+
+            let Some(access_token) =
+                connect_to_identity_server(&session, &identity_server, &*obj).await
+            else {
+                return false;
+            };
+
+            match room
+                .invite_by_email(email.clone(), &identity_server, access_token)
+                .await
+            {
+                Ok(()) => {
+                    toast!(
+                        obj,
+                        gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "An invite was sent to {email}",
+                            &[("email", &email)],
+                        )
+                    );
+                    true
+                }
+                Err(error) => {
+                    error!("Could not invite by email: {error}");
+                    toast!(
+                        obj,
+                        gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "Could not send an invite to {email}",
+                            &[("email", &email)],
+                        )
+                    );
+                    false
+                }
+            }
+        }
     }
 }
 
+/// Whether the given string looks like an email address.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+
+    // User IDs also contain an `@`, but at the start.
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.contains('@')
+        && !s.chars().any(char::is_whitespace)
+}
+
 glib::wrapper! {
     /// Subpage to invite new members to a room.
     pub struct InviteSubpage(ObjectSubclass<imp::InviteSubpage>)
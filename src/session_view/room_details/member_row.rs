@@ -1,11 +1,23 @@
-use gtk::{glib, prelude::*, subclass::prelude::*};
+use std::time::Duration;
+
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::MilliSecondsSinceUnixEpoch;
 
 use crate::{
     components::{Avatar, RoleBadge},
+    ngettext_f,
     session::Member,
-    utils::expression,
+    spawn,
+    utils::{BoundObject, LoadingState, expression},
 };
 
+/// The delay before loading the presence of the member presented by a row.
+///
+/// This avoids to make requests for rows that are only shown briefly while
+/// scrolling.
+const PRESENCE_LOAD_DELAY: Duration = Duration::from_millis(500);
+
 mod imp {
     use std::cell::{Cell, RefCell};
 
@@ -19,12 +31,18 @@ mod imp {
     pub struct MemberRow {
         #[template_child]
         role_badge: TemplateChild<RoleBadge>,
+        #[template_child]
+        last_active_label: TemplateChild<gtk::Label>,
         /// The room member presented by this row.
         #[property(get, set = Self::set_member, explicit_notify, nullable)]
-        member: RefCell<Option<Member>>,
+        member: BoundObject<Member>,
         /// Whether we should present the role of the user.
         #[property(get, construct_only)]
         show_role: Cell<bool>,
+        /// Whether we should present the latest activity of the user.
+        #[property(get, set = Self::set_show_last_active, explicit_notify)]
+        show_last_active: Cell<bool>,
+        presence_timeout: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -59,6 +77,12 @@ mod imp {
                 None::<&glib::Object>,
             );
         }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.presence_timeout.take() {
+                source_id.remove();
+            }
+        }
     }
 
     impl WidgetImpl for MemberRow {}
@@ -67,13 +91,133 @@ mod imp {
     impl MemberRow {
         /// Set the member displayed by this row.
         fn set_member(&self, member: Option<Member>) {
-            if *self.member.borrow() == member {
+            if self.member.obj() == member {
                 return;
             }
 
-            self.member.replace(member);
+            self.member.disconnect_signals();
+
+            if let Some(member) = member {
+                let last_active_handler = member.connect_last_active_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_last_active();
+                    }
+                ));
+                let currently_active_handler = member.connect_is_currently_active_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_last_active();
+                    }
+                ));
+                let presence_state_handler = member.connect_presence_state_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_last_active();
+                        imp.load_presence_later();
+                    }
+                ));
+
+                self.member.set(
+                    member,
+                    vec![
+                        last_active_handler,
+                        currently_active_handler,
+                        presence_state_handler,
+                    ],
+                );
+            }
+
+            self.update_last_active();
+            self.load_presence_later();
             self.obj().notify_member();
         }
+
+        /// Set whether we should present the latest activity of the user.
+        fn set_show_last_active(&self, show: bool) {
+            if self.show_last_active.get() == show {
+                return;
+            }
+
+            self.show_last_active.set(show);
+
+            self.update_last_active();
+            self.load_presence_later();
+            self.obj().notify_show_last_active();
+        }
+
+        /// Load the presence of the member after a delay, if necessary.
+        ///
+        /// The presence is loaded again when it is outdated, as long as the
+        /// row presents the same member.
+        fn load_presence_later(&self) {
+            if let Some(source_id) = self.presence_timeout.take() {
+                source_id.remove();
+            }
+
+            if !self.show_last_active.get() {
+                return;
+            }
+            let Some(member) = self.member.obj() else {
+                return;
+            };
+            let Some(delay) = member.presence_refresh_delay() else {
+                // The presence is being loaded, we will be notified when it is done.
+                return;
+            };
+
+            self.presence_timeout
+                .replace(Some(glib::timeout_add_local_once(
+                    delay.max(PRESENCE_LOAD_DELAY),
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move || {
+                            imp.presence_timeout.take();
+
+                            // Only load the presence if the row still presents the same member.
+                            if imp.member.obj().as_ref() != Some(&member) {
+                                return;
+                            }
+
+                            spawn!(clone!(
+                                #[weak]
+                                imp,
+                                async move {
+                                    member.load_presence().await;
+
+                                    // Update the relative time, even if the presence did not
+                                    // change.
+                                    imp.update_last_active();
+                                    imp.load_presence_later();
+                                }
+                            ));
+                        }
+                    ),
+                )));
+        }
+
+        /// Update the label presenting the latest activity of the member.
+        fn update_last_active(&self) {
+            let member = self.member.obj().filter(|_| self.show_last_active.get());
+
+            let Some(member) = member else {
+                self.last_active_label.set_visible(false);
+                return;
+            };
+
+            let label = match member.presence_state() {
+                LoadingState::Initial | LoadingState::Loading => None,
+                LoadingState::Ready | LoadingState::Error => Some(last_active_string(&member)),
+            };
+
+            self.last_active_label
+                .set_label(label.as_deref().unwrap_or_default());
+            self.last_active_label.set_visible(label.is_some());
+        }
     }
 }
 
@@ -92,3 +236,57 @@ impl MemberRow {
             .build()
     }
 }
+
+/// A localized string describing the latest activity of the given member.
+fn last_active_string(member: &Member) -> String {
+    if member.is_currently_active() {
+        return gettext("Active now");
+    }
+
+    let last_active = member.last_active();
+    if last_active == 0 {
+        return gettext("Last activity unknown");
+    }
+
+    let now = u64::from(MilliSecondsSinceUnixEpoch::now().get());
+    let minutes = now.saturating_sub(last_active) / 60_000;
+
+    if minutes < 1 {
+        return gettext("Active less than a minute ago");
+    }
+
+    if minutes < 60 {
+        let n = u32::try_from(minutes).unwrap_or(u32::MAX);
+        return ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "Active 1 minute ago",
+            "Active {n} minutes ago",
+            n,
+            &[("n", &n.to_string())],
+        );
+    }
+
+    let hours = minutes / 60;
+    if hours < 24 {
+        let n = u32::try_from(hours).unwrap_or(u32::MAX);
+        return ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}',
+            // this is a variable name.
+            "Active 1 hour ago",
+            "Active {n} hours ago",
+            n,
+            &[("n", &n.to_string())],
+        );
+    }
+
+    let n = u32::try_from(hours / 24).unwrap_or(u32::MAX);
+    ngettext_f(
+        // Translators: Do NOT translate the content between '{' and '}',
+        // this is a variable name.
+        "Active 1 day ago",
+        "Active {n} days ago",
+        n,
+        &[("n", &n.to_string())],
+    )
+}
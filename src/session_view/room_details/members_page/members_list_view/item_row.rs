@@ -51,7 +51,11 @@ mod imp {
 
             if let Some(item) = &item {
                 if let Some(member) = item.downcast_ref::<Member>() {
-                    let child = obj.child_or_else::<MemberRow>(|| MemberRow::new(true));
+                    let child = obj.child_or_else::<MemberRow>(|| {
+                        let child = MemberRow::new(true);
+                        child.set_show_last_active(true);
+                        child
+                    });
                     child.set_member(Some(member.clone()));
                     self.set_activatable(true);
                 } else if let Some(item) = item.downcast_ref::<MembershipSubpageItem>() {
@@ -126,6 +126,9 @@ mod imp {
 
         fn member_count_changed(&self, n: u32) {
             self.members_count.set_text(&format!("{n}"));
+            // The list of invited members can be empty when there are only pending invites
+            // by email.
+            self.members_count.set_visible(n > 0);
         }
     }
 }
@@ -14,6 +14,7 @@ use crate::{
     prelude::*,
     session::{Member, MemberList, MembershipListKind, Room},
     session_view::room_details::MembershipSubpageItem,
+    spawn,
     utils::{BoundObjectWeakRef, ExpressionListModel, LoadingState, expression},
 };
 
@@ -51,6 +52,10 @@ mod imp {
         members_stack_page: TemplateChild<gtk::StackPage>,
         #[template_child]
         list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
+        third_party_invites_clamp: TemplateChild<adw::Clamp>,
+        #[template_child]
+        third_party_invites_list: TemplateChild<gtk::ListBox>,
         /// The room containing the members to present.
         #[property(get, set = Self::set_room, construct_only)]
         room: glib::WeakRef<Room>,
@@ -59,6 +64,8 @@ mod imp {
         members: BoundObjectWeakRef<MemberList>,
         /// The items to add to the membership list, if any.
         extra_items: OnceCell<gtk::FilterListModel>,
+        /// The model with the sorting of the members.
+        sorted_members: gtk::SortListModel,
         /// The model with the search filter.
         filtered_model: gtk::FilterListModel,
         /// The kind of the membership list.
@@ -67,6 +74,11 @@ mod imp {
         /// Whether our own user can send an invite in the current room.
         #[property(get, set = Self::set_can_invite, explicit_notify)]
         can_invite: Cell<bool>,
+        /// Whether the members are sorted by their latest activity.
+        #[property(get, set = Self::set_sort_by_activity, explicit_notify)]
+        sort_by_activity: Cell<bool>,
+        /// Whether the room has pending invites by email.
+        has_third_party_invites: Cell<bool>,
         extra_members_state_handler: RefCell<Option<glib::SignalHandlerId>>,
         membership_items_changed_handlers:
             RefCell<HashMap<MembershipListKind, glib::SignalHandlerId>>,
@@ -85,6 +97,8 @@ mod imp {
             Self::bind_template_callbacks(klass);
 
             klass.set_css_name("members-list");
+
+            klass.install_property_action("members-list.sort-by-activity", "sort-by-activity");
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -143,7 +157,27 @@ mod imp {
     }
 
     impl WidgetImpl for MembersListView {}
-    impl NavigationPageImpl for MembersListView {}
+
+    impl NavigationPageImpl for MembersListView {
+        fn shown(&self) {
+            // The list of invited members displays the pending invites by email, and the
+            // list of joined members links to it when there are some.
+            if !matches!(
+                self.kind.get(),
+                MembershipListKind::Join | MembershipListKind::Invite
+            ) {
+                return;
+            }
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.update_third_party_invites().await;
+                }
+            ));
+        }
+    }
 
     #[gtk::template_callbacks]
     impl MembersListView {
@@ -191,6 +225,48 @@ mod imp {
             self.obj().notify_can_invite();
         }
 
+        /// Set whether the members are sorted by their latest activity.
+        fn set_sort_by_activity(&self, sort_by_activity: bool) {
+            if self.sort_by_activity.get() == sort_by_activity {
+                return;
+            }
+
+            self.sort_by_activity.set(sort_by_activity);
+            self.update_sorter();
+            self.obj().notify_sort_by_activity();
+        }
+
+        /// Update the sorter of the members list.
+        fn update_sorter(&self) {
+            let sorter = gtk::MultiSorter::new();
+
+            // The members that were active most recently first.
+            if self.sort_by_activity.get() {
+                sorter.append(gtk::CustomSorter::new(|a, b| {
+                    let a = a.downcast_ref::<Member>().expect("item is a Member");
+                    let b = b.downcast_ref::<Member>().expect("item is a Member");
+
+                    b.is_currently_active()
+                        .cmp(&a.is_currently_active())
+                        .then_with(|| b.last_active().cmp(&a.last_active()))
+                        .into()
+                }));
+            }
+
+            // Then by power level, then display name.
+            sorter.append(
+                gtk::NumericSorter::builder()
+                    .expression(Member::this_expression("power-level-i64"))
+                    .sort_order(gtk::SortType::Descending)
+                    .build(),
+            );
+            sorter.append(gtk::StringSorter::new(Some(Member::this_expression(
+                "display-name",
+            ))));
+
+            self.sorted_members.set_sorter(Some(&sorter));
+        }
+
         /// Initialize the members list used for this view.
         fn init_members_list(&self) {
             let Some(members) = self.members.obj() else {
@@ -213,36 +289,28 @@ mod imp {
                 .borrow_mut()
                 .insert(kind, items_changed_handler);
 
-            // Sort the members list by power level, then display name.
-            let power_level_expr = Member::this_expression("power-level-i64");
-            let sorter = gtk::MultiSorter::new();
-            sorter.append(
-                gtk::NumericSorter::builder()
-                    .expression(&power_level_expr)
-                    .sort_order(gtk::SortType::Descending)
-                    .build(),
-            );
-
-            let display_name_expr = Member::this_expression("display-name");
-            sorter.append(gtk::StringSorter::new(Some(&display_name_expr)));
-
             // We need to notify when a watched property changes so the sorter can update
             // the list.
             let expr_members = ExpressionListModel::new();
-            expr_members
-                .set_expressions(vec![power_level_expr.upcast(), display_name_expr.upcast()]);
+            expr_members.set_expressions(vec![
+                Member::this_expression("power-level-i64").upcast(),
+                Member::this_expression("display-name").upcast(),
+                Member::this_expression("last-active").upcast(),
+                Member::this_expression("is-currently-active").upcast(),
+            ]);
             expr_members.set_model(Some(membership_list));
 
-            let sorted_members = gtk::SortListModel::new(Some(expr_members), Some(sorter));
+            self.sorted_members.set_model(Some(&expr_members));
+            self.update_sorter();
 
             let full_model = if let Some(extra_items) = self.extra_items.get() {
                 let model_list = gio::ListStore::new::<gio::ListModel>();
                 model_list.append(extra_items);
-                model_list.append(&sorted_members);
+                model_list.append(&self.sorted_members);
 
                 gtk::FlattenListModel::new(Some(model_list)).upcast::<gio::ListModel>()
             } else {
-                sorted_members.upcast()
+                self.sorted_members.clone().upcast()
             };
             self.filtered_model.set_model(Some(&full_model));
 
@@ -261,15 +329,26 @@ mod imp {
                 return;
             }
 
-            let filter = gtk::CustomFilter::new(|item| {
-                if let Some(loading_row) = item.downcast_ref::<LoadingRow>() {
-                    loading_row.is_visible()
-                } else if let Some(subpage_item) = item.downcast_ref::<MembershipSubpageItem>() {
-                    subpage_item.model().n_items() != 0
-                } else {
-                    false
+            let filter = gtk::CustomFilter::new(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                false,
+                move |item| {
+                    if let Some(loading_row) = item.downcast_ref::<LoadingRow>() {
+                        loading_row.is_visible()
+                    } else if let Some(subpage_item) = item.downcast_ref::<MembershipSubpageItem>()
+                    {
+                        // The list of invited members also presents the pending invites by
+                        // email.
+                        subpage_item.model().n_items() != 0
+                            || (subpage_item.kind() == MembershipListKind::Invite
+                                && imp.has_third_party_invites.get())
+                    } else {
+                        false
+                    }
                 }
-            });
+            ));
 
             let loading_row = LoadingRow::new();
             let extra_members_state_handler = members.connect_state_notify(clone!(
@@ -353,6 +432,8 @@ mod imp {
             let membership_list = members.membership_list(kind);
             let count = membership_list.n_items();
             let is_empty = count == 0;
+            let has_third_party_invites =
+                kind == MembershipListKind::Invite && self.has_third_party_invites.get();
 
             let title = match kind {
                 MembershipListKind::Join => ngettext("Room Member", "Room Members", count),
@@ -368,7 +449,7 @@ mod imp {
             self.obj().set_title(&title);
             self.members_stack_page.set_title(&title);
 
-            let (visible_page, extra_items) = if is_empty {
+            let (visible_page, extra_items) = if is_empty && !has_third_party_invites {
                 match members.state() {
                     LoadingState::Initial | LoadingState::Loading => ("loading", None),
                     LoadingState::Error => ("error", None),
@@ -393,6 +474,40 @@ mod imp {
             self.stack.set_visible_child_name(visible_page);
         }
 
+        /// Update the pending invites by email.
+        async fn update_third_party_invites(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+
+            let display_names = room.pending_third_party_invites().await;
+            let has_third_party_invites = !display_names.is_empty();
+
+            if self.kind.get() == MembershipListKind::Invite {
+                self.third_party_invites_list.remove_all();
+                for display_name in &display_names {
+                    let row = adw::ActionRow::builder()
+                        .title(display_name)
+                        .use_markup(false)
+                        .build();
+                    self.third_party_invites_list.append(&row);
+                }
+
+                self.third_party_invites_clamp
+                    .set_visible(has_third_party_invites);
+            }
+
+            if self.has_third_party_invites.get() == has_third_party_invites {
+                return;
+            }
+            self.has_third_party_invites.set(has_third_party_invites);
+
+            if let Some(filter) = self.extra_items.get().and_then(|model| model.filter()) {
+                filter.changed(gtk::FilterChange::Different);
+            }
+            self.update_view();
+        }
+
         /// Update the "empty" page for the current state.
         fn update_empty_page(&self) {
             let kind = self.kind.get();
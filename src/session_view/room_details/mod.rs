@@ -9,6 +9,7 @@ use ruma::UserId;
 
 mod addresses_subpage;
 mod edit_details_subpage;
+mod export_subpage;
 mod general_page;
 mod history_viewer;
 mod history_visibility_subpage;
@@ -17,12 +18,16 @@ mod join_rule_subpage;
 mod member_row;
 mod members_page;
 mod membership_subpage_item;
+mod own_profile_subpage;
 mod permissions;
+mod server_acl_subpage;
 mod upgrade_dialog;
 
+pub(super) use self::history_viewer::HistoryViewerEvent;
 use self::{
     addresses_subpage::AddressesSubpage,
     edit_details_subpage::EditDetailsSubpage,
+    export_subpage::ExportSubpage,
     general_page::GeneralPage,
     history_viewer::{
         AudioHistoryViewer, FileHistoryViewer, HistoryViewerTimeline, VisualMediaHistoryViewer,
@@ -33,7 +38,9 @@ use self::{
     member_row::MemberRow,
     members_page::MembersPage,
     membership_subpage_item::MembershipSubpageItem,
+    own_profile_subpage::OwnProfileSubpage,
     permissions::PermissionsSubpage,
+    server_acl_subpage::ServerAclSubpage,
     upgrade_dialog::{UpgradeDialog, UpgradeInfo},
 };
 use crate::{
@@ -65,6 +72,12 @@ pub(super) enum SubpageName {
     JoinRule,
     /// The page to edit the history visibility of the room.
     HistoryVisibility,
+    /// The page to export the conversation to a file.
+    Export,
+    /// The page to edit the servers that can participate in the room.
+    ServerAcl,
+    /// The page to edit the name and avatar of our own user in the room.
+    OwnProfile,
 }
 
 /// The view to present when opening the room details.
@@ -246,6 +259,9 @@ mod imp {
                     }
                     SubpageName::JoinRule => JoinRuleSubpage::new(room).upcast(),
                     SubpageName::HistoryVisibility => HistoryVisibilitySubpage::new(room).upcast(),
+                    SubpageName::Export => ExportSubpage::new(room).upcast(),
+                    SubpageName::ServerAcl => ServerAclSubpage::new(room).upcast(),
+                    SubpageName::OwnProfile => OwnProfileSubpage::new(room).upcast(),
                 })
                 .clone()
         }
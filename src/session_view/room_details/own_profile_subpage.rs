@@ -0,0 +1,414 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{gio, glib, glib::clone};
+use ruma::OwnedMxcUri;
+use tracing::error;
+
+use crate::{
+    components::{
+        ActionButton, ActionState, EditableAvatar, LoadingButtonRow, UnsavedChangesResponse,
+        unsaved_changes_dialog,
+    },
+    prelude::*,
+    session::Room,
+    spawn_tokio, toast,
+    utils::{OngoingAsyncAction, media::FileInfo},
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/room_details/own_profile_subpage.ui")]
+    #[properties(wrapper_type = super::OwnProfileSubpage)]
+    pub struct OwnProfileSubpage {
+        #[template_child]
+        avatar: TemplateChild<EditableAvatar>,
+        #[template_child]
+        display_name_entry_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        display_name_button: TemplateChild<ActionButton>,
+        #[template_child]
+        reset_button_row: TemplateChild<LoadingButtonRow>,
+        /// The presented room.
+        #[property(get, set = Self::set_room, construct_only)]
+        room: glib::WeakRef<Room>,
+        changing_avatar: RefCell<Option<OngoingAsyncAction<OwnedMxcUri>>>,
+        changing_display_name: RefCell<Option<OngoingAsyncAction<String>>>,
+        avatar_uri_handler: RefCell<Option<glib::SignalHandlerId>>,
+        display_name_handler: RefCell<Option<glib::SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for OwnProfileSubpage {
+        const NAME: &'static str = "RoomDetailsOwnProfileSubpage";
+        type Type = super::OwnProfileSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for OwnProfileSubpage {
+        fn dispose(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let own_member = room.own_member();
+
+            if let Some(handler) = self.avatar_uri_handler.take() {
+                own_member
+                    .avatar_data()
+                    .image()
+                    .expect("member always has an avatar image")
+                    .disconnect(handler);
+            }
+            if let Some(handler) = self.display_name_handler.take() {
+                own_member.disconnect(handler);
+            }
+        }
+    }
+
+    impl WidgetImpl for OwnProfileSubpage {}
+    impl NavigationPageImpl for OwnProfileSubpage {}
+
+    #[gtk::template_callbacks]
+    impl OwnProfileSubpage {
+        /// Set the presented room.
+        fn set_room(&self, room: &Room) {
+            let own_member = room.own_member();
+
+            let avatar_uri_handler = own_member
+                .avatar_data()
+                .image()
+                .expect("member always has an avatar image")
+                .connect_uri_string_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |avatar_image| {
+                        imp.avatar_changed(avatar_image.uri().as_ref());
+                    }
+                ));
+            self.avatar_uri_handler.replace(Some(avatar_uri_handler));
+
+            let display_name_handler = own_member.connect_display_name_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |own_member| {
+                    imp.display_name_changed(&own_member.display_name());
+                }
+            ));
+            self.display_name_handler
+                .replace(Some(display_name_handler));
+
+            self.room.set(Some(room));
+        }
+
+        /// Handle when we receive an avatar URI change from the homeserver.
+        fn avatar_changed(&self, uri: Option<&OwnedMxcUri>) {
+            if let Some(action) = self.changing_avatar.borrow().as_ref() {
+                if uri != action.as_value() {
+                    // This is not the change we expected, maybe another device did a change too.
+                    // Let's wait for another change.
+                    return;
+                }
+            } else {
+                // No action is ongoing, we don't need to do anything.
+                return;
+            }
+
+            // Reset the state.
+            self.changing_avatar.take();
+            self.avatar.success();
+
+            let obj = self.obj();
+            if uri.is_none() {
+                toast!(obj, gettext("Avatar removed successfully"));
+            } else {
+                toast!(obj, gettext("Avatar changed successfully"));
+            }
+        }
+
+        /// Change the avatar in this room with the one in the given file.
+        #[template_callback]
+        async fn change_avatar(&self, file: gio::File) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            let obj = self.obj();
+            let avatar = &self.avatar;
+            avatar.edit_in_progress();
+
+            let info = match FileInfo::try_from_file(&file).await {
+                Ok(info) => info,
+                Err(error) => {
+                    error!("Could not load own room avatar file info: {error}");
+                    toast!(obj, gettext("Could not load file"));
+                    avatar.reset();
+                    return;
+                }
+            };
+
+            let data = match file.load_contents_future().await {
+                Ok((data, _)) => data,
+                Err(error) => {
+                    error!("Could not load own room avatar file: {error}");
+                    toast!(obj, gettext("Could not load file"));
+                    avatar.reset();
+                    return;
+                }
+            };
+
+            let client = session.client();
+            let handle =
+                spawn_tokio!(
+                    async move { client.media().upload(&info.mime, data.into(), None).await }
+                );
+
+            let uri = match handle.await.expect("task was not aborted") {
+                Ok(res) => res.content_uri,
+                Err(error) => {
+                    error!("Could not upload own room avatar: {error}");
+                    toast!(obj, gettext("Could not upload avatar"));
+                    avatar.reset();
+                    return;
+                }
+            };
+
+            let (action, weak_action) = OngoingAsyncAction::set(uri.clone());
+            self.changing_avatar.replace(Some(action));
+
+            // We don't need to handle the success of the request, we should receive the
+            // change via sync.
+            if room.set_own_room_avatar_url(Some(uri)).await.is_err() {
+                // Because this action can finish in avatar_changed, we must only act if this is
+                // still the current action.
+                if weak_action.is_ongoing() {
+                    self.changing_avatar.take();
+                    toast!(obj, gettext("Could not change avatar"));
+                    avatar.reset();
+                }
+            }
+        }
+
+        /// Remove the avatar in this room.
+        #[template_callback]
+        async fn remove_avatar(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+
+            let obj = self.obj();
+
+            // Ask for confirmation.
+            let confirm_dialog = adw::AlertDialog::builder()
+                .default_response("cancel")
+                .heading(gettext("Remove Avatar?"))
+                .body(gettext(
+                    "Do you really want to remove your avatar in this room?",
+                ))
+                .build();
+            confirm_dialog.add_responses(&[
+                ("cancel", &gettext("Cancel")),
+                ("remove", &gettext("Remove")),
+            ]);
+            confirm_dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+
+            if confirm_dialog.choose_future(Some(&*obj)).await != "remove" {
+                return;
+            }
+
+            let avatar = &self.avatar;
+            avatar.removal_in_progress();
+
+            let (action, weak_action) = OngoingAsyncAction::remove();
+            self.changing_avatar.replace(Some(action));
+
+            // We don't need to handle the success of the request, we should receive the
+            // change via sync.
+            if room.set_own_room_avatar_url(None).await.is_err() {
+                // Because this action can finish in avatar_changed, we must only act if this is
+                // still the current action.
+                if weak_action.is_ongoing() {
+                    self.changing_avatar.take();
+                    toast!(obj, gettext("Could not remove avatar"));
+                    avatar.reset();
+                }
+            }
+        }
+
+        /// Reset the display name entry and button.
+        fn reset_display_name(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+
+            let entry = &self.display_name_entry_row;
+            let button = &self.display_name_button;
+
+            entry.remove_css_class("error");
+            entry.set_sensitive(room.permissions().can_change_own_profile());
+            entry.set_text(&room.own_member().display_name());
+            button.set_visible(false);
+            button.set_state(ActionState::Confirm);
+        }
+
+        /// Handle when we receive a display name change from the homeserver.
+        fn display_name_changed(&self, name: &str) {
+            if let Some(action) = self.changing_display_name.borrow().as_ref() {
+                if action.as_value().is_some_and(|value| value != name) {
+                    // This is not the change we expected, maybe another device did a change too.
+                    // Let's wait for another change.
+                    return;
+                }
+            } else {
+                // No action is ongoing, we don't need to do anything.
+                return;
+            }
+
+            toast!(self.obj(), gettext("Name changed successfully"));
+
+            // Reset state.
+            self.changing_display_name.take();
+            self.reset_display_name();
+        }
+
+        /// Whether the display name in the entry row is different than the
+        /// one of our member.
+        fn was_display_name_edited(&self) -> bool {
+            let Some(room) = self.room.upgrade() else {
+                return false;
+            };
+
+            let own_member = room.own_member();
+            let text = self.display_name_entry_row.text();
+            let text = text.trim();
+
+            if text.is_empty() {
+                own_member.has_display_name()
+            } else {
+                text != own_member.display_name()
+            }
+        }
+
+        /// Handle when the display name was edited in the entry.
+        #[template_callback]
+        fn display_name_edited(&self) {
+            self.display_name_button
+                .set_visible(self.was_display_name_edited());
+        }
+
+        /// Change the display name in this room.
+        #[template_callback]
+        async fn change_display_name(&self) {
+            if !self.was_display_name_edited() {
+                // No change to send.
+                return;
+            }
+
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+
+            let entry = &self.display_name_entry_row;
+            let button = &self.display_name_button;
+
+            entry.set_sensitive(false);
+            button.set_state(ActionState::Loading);
+
+            // Trim whitespaces.
+            let display_name = Some(entry.text().trim())
+                .filter(|t| !t.is_empty())
+                .map(ToOwned::to_owned);
+
+            let (action, weak_action) = if let Some(display_name) = display_name.clone() {
+                OngoingAsyncAction::set(display_name)
+            } else {
+                OngoingAsyncAction::remove()
+            };
+            self.changing_display_name.replace(Some(action));
+
+            // We don't need to handle the success of the request, we should receive the
+            // change via sync.
+            if room.set_own_room_display_name(display_name).await.is_err() {
+                // Because this action can finish in display_name_changed, we must only act if
+                // this is still the current action.
+                if weak_action.is_ongoing() {
+                    self.changing_display_name.take();
+                    toast!(self.obj(), gettext("Could not change display name"));
+                    button.set_state(ActionState::Retry);
+                    entry.add_css_class("error");
+                    entry.set_sensitive(true);
+                }
+            }
+        }
+
+        /// Use the profile of the account in this room.
+        #[template_callback]
+        async fn reset_profile(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+
+            self.reset_button_row.set_is_loading(true);
+
+            if room.reset_own_room_profile().await.is_err() {
+                toast!(self.obj(), gettext("Could not reset profile"));
+            }
+
+            self.reset_button_row.set_is_loading(false);
+        }
+
+        /// Go back to the previous page in the room details.
+        ///
+        /// If there are changes in the page, ask the user to confirm.
+        #[template_callback]
+        async fn go_back(&self) {
+            let obj = self.obj();
+            let mut reset_after = false;
+
+            if self.was_display_name_edited() && self.changing_display_name.borrow().is_none() {
+                match unsaved_changes_dialog(&*obj).await {
+                    UnsavedChangesResponse::Save => self.change_display_name().await,
+                    UnsavedChangesResponse::Discard => reset_after = true,
+                    UnsavedChangesResponse::Cancel => return,
+                }
+            }
+
+            obj.activate_action("navigation.pop", None).unwrap();
+
+            if reset_after {
+                self.reset_display_name();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Subpage to edit the name and avatar of our own user in a room.
+    pub struct OwnProfileSubpage(ObjectSubclass<imp::OwnProfileSubpage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl OwnProfileSubpage {
+    /// Construct a new `OwnProfileSubpage` for the given room.
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+}
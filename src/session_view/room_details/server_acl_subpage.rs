@@ -0,0 +1,471 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+use matrix_sdk::deserialized_responses::RawSyncOrStrippedState;
+use ruma::events::{
+    StateEventType, SyncStateEvent,
+    room::{power_levels::PowerLevelAction, server_acl::RoomServerAclEventContent},
+};
+use tracing::error;
+
+use crate::{
+    components::{
+        EntryAddRow, LoadingButton, RemovableRow, UnsavedChangesResponse, unsaved_changes_dialog,
+    },
+    gettext_f,
+    prelude::*,
+    session::Room,
+    spawn, spawn_tokio, toast,
+    utils::BoundObjectWeakRef,
+};
+
+/// The list of server patterns to edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AclList {
+    /// The servers that are allowed.
+    Allow,
+    /// The servers that are denied.
+    Deny,
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/room_details/server_acl_subpage.ui")]
+    #[properties(wrapper_type = super::ServerAclSubpage)]
+    pub struct ServerAclSubpage {
+        #[template_child]
+        save_button: TemplateChild<LoadingButton>,
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        own_server_warning: TemplateChild<gtk::Box>,
+        #[template_child]
+        own_server_warning_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        allow_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        deny_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        allow_ip_literals_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        allow_add_row: TemplateChild<EntryAddRow>,
+        #[template_child]
+        deny_add_row: TemplateChild<EntryAddRow>,
+        /// The presented room.
+        #[property(get, set = Self::set_room, explicit_notify, nullable)]
+        room: BoundObjectWeakRef<Room>,
+        /// Whether the user can change the server ACL.
+        #[property(get)]
+        can_change: Cell<bool>,
+        /// Whether the server ACL was changed by the user.
+        #[property(get)]
+        changed: Cell<bool>,
+        /// The server ACL of the room, as it was loaded.
+        server_acl: RefCell<Option<RoomServerAclEventContent>>,
+        /// The local list of allowed servers.
+        allow: RefCell<Vec<String>>,
+        /// The local list of denied servers.
+        deny: RefCell<Vec<String>>,
+        permissions_handler: RefCell<Option<glib::SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ServerAclSubpage {
+        const NAME: &'static str = "RoomDetailsServerAclSubpage";
+        type Type = super::ServerAclSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ServerAclSubpage {
+        fn dispose(&self) {
+            self.disconnect_signals();
+        }
+    }
+
+    impl WidgetImpl for ServerAclSubpage {}
+    impl NavigationPageImpl for ServerAclSubpage {}
+
+    #[gtk::template_callbacks]
+    impl ServerAclSubpage {
+        /// Set the presented room.
+        fn set_room(&self, room: Option<&Room>) {
+            let Some(room) = room else {
+                // Just ignore when room is missing.
+                return;
+            };
+
+            self.disconnect_signals();
+
+            let permissions_handler = room.permissions().connect_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_can_change();
+                }
+            ));
+            self.permissions_handler.replace(Some(permissions_handler));
+
+            self.room.set(room, vec![]);
+
+            self.update_can_change();
+            self.obj().notify_room();
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.load().await;
+                }
+            ));
+        }
+
+        /// Update whether the user can change the server ACL.
+        fn update_can_change(&self) {
+            let can_change = self.room.obj().is_some_and(|room| {
+                room.permissions()
+                    .is_allowed_to(PowerLevelAction::SendState(StateEventType::RoomServerAcl))
+            });
+
+            if self.can_change.get() == can_change {
+                return;
+            }
+
+            self.can_change.set(can_change);
+
+            self.update_lists();
+            self.update_changed();
+            self.obj().notify_can_change();
+        }
+
+        /// Load the server ACL of the room.
+        async fn load(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            self.stack.set_visible_child_name("loading");
+
+            let matrix_room = room.matrix_room().clone();
+            let handle = spawn_tokio!(async move {
+                matrix_room
+                    .get_state_event_static::<RoomServerAclEventContent>()
+                    .await
+            });
+
+            let server_acl = match handle.await.expect("task was not aborted") {
+                Ok(Some(RawSyncOrStrippedState::Sync(raw_event))) => {
+                    match raw_event.deserialize() {
+                        Ok(SyncStateEvent::Original(event)) => Some(event.content),
+                        // The redacted event doesn't have a content.
+                        Ok(_) => None,
+                        Err(error) => {
+                            error!("Could not deserialize server ACL event: {error}");
+                            None
+                        }
+                    }
+                }
+                Ok(_) => None,
+                Err(error) => {
+                    error!("Could not get server ACL event: {error}");
+                    toast!(self.obj(), gettext("Could not load the server access list"));
+                    None
+                }
+            };
+
+            self.server_acl.replace(server_acl);
+            self.reset();
+
+            self.stack.set_visible_child_name("content");
+        }
+
+        /// Reset the local values to the ones of the loaded server ACL.
+        fn reset(&self) {
+            // Without a server ACL, all servers are allowed.
+            let server_acl = self.server_acl.borrow().clone().unwrap_or_else(|| {
+                RoomServerAclEventContent::new(true, vec!["*".to_owned()], vec![])
+            });
+
+            self.allow.replace(server_acl.allow);
+            self.deny.replace(server_acl.deny);
+            self.allow_ip_literals_row
+                .set_active(server_acl.allow_ip_literals);
+
+            self.save_button.set_is_loading(false);
+            self.update_lists();
+            self.update_changed();
+        }
+
+        /// The server ACL with the local values.
+        fn local_server_acl(&self) -> RoomServerAclEventContent {
+            RoomServerAclEventContent::new(
+                self.allow_ip_literals_row.is_active(),
+                self.allow.borrow().clone(),
+                self.deny.borrow().clone(),
+            )
+        }
+
+        /// Update the lists of servers.
+        fn update_lists(&self) {
+            for list in [AclList::Allow, AclList::Deny] {
+                let list_box = self.list_box(list);
+                list_box.remove_all();
+
+                for pattern in self.patterns(list).borrow().iter() {
+                    list_box.append(&self.create_row(list, pattern));
+                }
+
+                if self.can_change.get() {
+                    let add_row = self.add_row(list);
+                    if let Some(parent) = add_row.parent().and_downcast::<gtk::ListBox>() {
+                        parent.remove(add_row);
+                    }
+                    list_box.append(add_row);
+                }
+            }
+        }
+
+        /// The list box presenting the given list.
+        fn list_box(&self, list: AclList) -> &gtk::ListBox {
+            match list {
+                AclList::Allow => &self.allow_list,
+                AclList::Deny => &self.deny_list,
+            }
+        }
+
+        /// The row to add a pattern to the given list.
+        fn add_row(&self, list: AclList) -> &EntryAddRow {
+            match list {
+                AclList::Allow => &self.allow_add_row,
+                AclList::Deny => &self.deny_add_row,
+            }
+        }
+
+        /// The local patterns of the given list.
+        fn patterns(&self, list: AclList) -> &RefCell<Vec<String>> {
+            match list {
+                AclList::Allow => &self.allow,
+                AclList::Deny => &self.deny,
+            }
+        }
+
+        /// Create a row for the given pattern in the given list.
+        fn create_row(&self, list: AclList, pattern: &str) -> gtk::Widget {
+            if !self.can_change.get() {
+                let row = adw::ActionRow::builder()
+                    .title(pattern)
+                    .use_markup(false)
+                    .build();
+                return row.upcast();
+            }
+
+            let row = RemovableRow::new();
+            row.set_use_markup(false);
+            row.set_title(pattern);
+            row.set_remove_button_tooltip_text(Some(gettext("Remove server")));
+            row.set_remove_button_accessible_label(Some(gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}',
+                // this is a variable name.
+                "Remove “{server}”",
+                &[("server", pattern)],
+            )));
+
+            let pattern = pattern.to_owned();
+            row.connect_remove(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.patterns(list).borrow_mut().retain(|p| *p != pattern);
+                    imp.update_lists();
+                    imp.update_changed();
+                }
+            ));
+
+            row.upcast()
+        }
+
+        /// Add the pattern in the add row of the given list.
+        fn add_pattern(&self, list: AclList) {
+            let add_row = self.add_row(list);
+            let pattern = add_row.text().trim().to_owned();
+
+            if pattern.is_empty() || pattern.chars().any(char::is_whitespace) {
+                add_row.add_css_class("error");
+                return;
+            }
+            add_row.remove_css_class("error");
+
+            {
+                let mut patterns = self.patterns(list).borrow_mut();
+                if !patterns.contains(&pattern) {
+                    patterns.push(pattern);
+                }
+            }
+
+            add_row.set_text("");
+            self.update_lists();
+            self.update_changed();
+            add_row.grab_focus();
+        }
+
+        /// Add the pattern in the add row of the allowed servers.
+        #[template_callback]
+        fn add_allowed_server(&self) {
+            self.add_pattern(AclList::Allow);
+        }
+
+        /// Add the pattern in the add row of the denied servers.
+        #[template_callback]
+        fn add_denied_server(&self) {
+            self.add_pattern(AclList::Deny);
+        }
+
+        /// Update whether the server ACL was changed by the user.
+        #[template_callback]
+        fn update_changed(&self) {
+            self.update_own_server_warning();
+
+            let changed = self.can_change.get() && {
+                let local = self.local_server_acl();
+
+                match &*self.server_acl.borrow() {
+                    Some(server_acl) => {
+                        server_acl.allow != local.allow
+                            || server_acl.deny != local.deny
+                            || server_acl.allow_ip_literals != local.allow_ip_literals
+                    }
+                    // Sending an ACL that allows everything is not a change.
+                    None => {
+                        local.allow != ["*"] || !local.deny.is_empty() || !local.allow_ip_literals
+                    }
+                }
+            };
+
+            if self.changed.get() == changed {
+                return;
+            }
+
+            self.changed.set(changed);
+            self.obj().notify_changed();
+        }
+
+        /// Update the warning shown when the local values would prevent our
+        /// own server from participating in the room.
+        fn update_own_server_warning(&self) {
+            let Some(session) = self.room.obj().and_then(|room| room.session()) else {
+                return;
+            };
+
+            let server_name = session.user_id().server_name();
+            let is_allowed = self.local_server_acl().is_allowed(server_name);
+
+            if !is_allowed {
+                self.own_server_warning_label.set_label(&gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}',
+                    // this is a variable name.
+                    "Your homeserver {server} is not allowed. You will not be able to participate in this room anymore.",
+                    &[("server", server_name.as_str())],
+                ));
+            }
+            self.own_server_warning.set_visible(!is_allowed);
+        }
+
+        /// Save the changes of this page.
+        #[template_callback]
+        async fn save(&self) {
+            if !self.changed.get() {
+                // Nothing to do.
+                return;
+            }
+
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            self.save_button.set_is_loading(true);
+
+            let content = self.local_server_acl();
+
+            let matrix_room = room.matrix_room().clone();
+            let content_clone = content.clone();
+            let handle =
+                spawn_tokio!(async move { matrix_room.send_state_event(content_clone).await });
+
+            if let Err(error) = handle.await.expect("task was not aborted") {
+                error!("Could not change room server ACL: {error}");
+                toast!(
+                    self.obj(),
+                    gettext("Could not change the server access list")
+                );
+                self.save_button.set_is_loading(false);
+                return;
+            }
+
+            self.server_acl.replace(Some(content));
+            self.reset();
+        }
+
+        /// Go back to the previous page in the room details.
+        ///
+        /// If there are changes in the page, ask the user to confirm.
+        #[template_callback]
+        async fn go_back(&self) {
+            let obj = self.obj();
+            let mut reset_after = false;
+
+            if self.changed.get() {
+                match unsaved_changes_dialog(&*obj).await {
+                    UnsavedChangesResponse::Save => self.save().await,
+                    UnsavedChangesResponse::Discard => reset_after = true,
+                    UnsavedChangesResponse::Cancel => return,
+                }
+            }
+
+            let _ = obj.activate_action("navigation.pop", None);
+
+            if reset_after {
+                self.reset();
+            }
+        }
+
+        /// Disconnect all the signal handlers.
+        fn disconnect_signals(&self) {
+            if let Some(room) = self.room.obj()
+                && let Some(handler) = self.permissions_handler.take()
+            {
+                room.permissions().disconnect(handler);
+            }
+
+            self.room.disconnect_signals();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Subpage to view and edit the servers that can participate in a room.
+    pub struct ServerAclSubpage(ObjectSubclass<imp::ServerAclSubpage>)
+        @extends gtk::Widget, adw::NavigationPage,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ServerAclSubpage {
+    /// Construct a new `ServerAclSubpage` for the given room.
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+}
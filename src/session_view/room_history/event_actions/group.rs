@@ -9,12 +9,27 @@ use tracing::error;
 
 use super::EventPropertiesDialog;
 use crate::{
-    components::{RoomMemberDestructiveAction, confirm_room_member_destructive_action_dialog},
+    components::{
+        RoomMemberDestructiveAction, confirm_discard_failed_message_dialog,
+        confirm_redact_events_dialog, confirm_room_member_destructive_action_dialog,
+    },
     prelude::*,
     session::{Event, Membership, MessageState, Room},
     spawn, spawn_tokio, toast,
+    utils::{matrix::LinkFormat, media::image::Image},
 };
 
+/// The maximum number of characters of a custom reaction.
+const MAX_CUSTOM_REACTION_CHARS: usize = 32;
+
+/// Get the key of the custom reaction from the given text, if it is valid.
+///
+/// Leading and trailing whitespaces are ignored.
+fn custom_reaction_key(text: &str) -> Option<&str> {
+    let key = text.trim();
+    (!key.is_empty() && key.chars().count() <= MAX_CUSTOM_REACTION_CHARS).then_some(key)
+}
+
 /// Trait to help a row that presents an `Event` to provide the proper actions.
 pub(crate) trait EventActionsGroup: ObjectSubclass {
     /// The current event of the row, if any.
@@ -44,12 +59,22 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
 
         if has_event_id {
             action_group.add_action_entries([
-                // Create a permalink.
+                // Create a permalink, in the given format.
                 gio::ActionEntry::builder("permalink")
+                    .parameter_type(Some(&String::static_variant_type()))
                     .activate(clone!(
                         #[weak(rename_to = imp)]
                         self,
-                        move |_, _, _| {
+                        move |_, _, variant| {
+                            let Some(format) = variant
+                                .expect("permalink action should have a parameter")
+                                .get::<String>()
+                                .and_then(|s| s.parse::<LinkFormat>().ok())
+                            else {
+                                error!("Could not parse format of permalink");
+                                return;
+                            };
+
                             spawn!(async move {
                                 let Some(event) = imp.event() else {
                                     return;
@@ -59,7 +84,7 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
                                 };
 
                                 let obj = imp.obj();
-                                obj.clipboard().set_text(&permalink.to_string());
+                                obj.clipboard().set_text(&format.format(&permalink));
                                 toast!(obj, gettext("Message link copied to clipboard"));
                             });
                         }
@@ -120,6 +145,19 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
                     ))
                     .build()]);
             }
+
+            if state == MessageState::RecoverableError {
+                // Retry to send the event now.
+                action_group.add_action_entries([gio::ActionEntry::builder("retry-send")
+                    .activate(clone!(
+                        #[weak]
+                        room,
+                        move |_, _, _| {
+                            room.retry_send_queue();
+                        }
+                    ))
+                    .build()]);
+            }
         }
 
         self.add_message_like_actions(&action_group, &room, &event);
@@ -208,6 +246,20 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
                     ))
                     .build(),
             ]);
+
+            if permissions.can_send_reaction() {
+                action_group.add_action_entries([gio::ActionEntry::builder("custom-reaction")
+                    .activate(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_, _, _| {
+                            spawn!(async move {
+                                imp.send_custom_reaction().await;
+                            });
+                        }
+                    ))
+                    .build()]);
+            }
         }
 
         // Reply.
@@ -293,20 +345,6 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
                         .build()]);
                 }
             }
-            MessageType::File(_) => {
-                // Save message's file.
-                action_group.add_action_entries([gio::ActionEntry::builder("file-save")
-                    .activate(clone!(
-                        #[weak(rename_to = imp)]
-                        self,
-                        move |_, _, _| {
-                            spawn!(async move {
-                                imp.save_file().await;
-                            });
-                        }
-                    ))
-                    .build()]);
-            }
             MessageType::Notice(_) => {
                 // Copy text.
                 action_group.add_action_entries([gio::ActionEntry::builder("copy-text")
@@ -338,51 +376,38 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
                             }
                         ))
                         .build(),
-                    // Save the image to a file.
-                    gio::ActionEntry::builder("save-image")
+                    // Copy the original image to the clipboard.
+                    gio::ActionEntry::builder("copy-original-image")
                         .activate(clone!(
                             #[weak(rename_to = imp)]
                             self,
                             move |_, _, _| {
                                 spawn!(async move {
-                                    imp.save_file().await;
+                                    imp.copy_original_image().await;
                                 });
                             }
                         ))
                         .build(),
                 ]);
             }
-            MessageType::Video(_) => {
-                // Save the video to a file.
-                action_group.add_action_entries([gio::ActionEntry::builder("save-video")
-                    .activate(clone!(
-                        #[weak(rename_to = imp)]
-                        self,
-                        move |_, _, _| {
-                            spawn!(async move {
-                                imp.save_file().await;
-                            });
-                        }
-                    ))
-                    .build()]);
-            }
-            MessageType::Audio(_) => {
-                // Save the audio to a file.
-                action_group.add_action_entries([gio::ActionEntry::builder("save-audio")
-                    .activate(clone!(
-                        #[weak(rename_to = imp)]
-                        self,
-                        move |_, _, _| {
-                            spawn!(async move {
-                                imp.save_file().await;
-                            });
-                        }
-                    ))
-                    .build()]);
-            }
             _ => {}
         }
 
+        if event.media_message().is_some() {
+            // Save the media to a file.
+            action_group.add_action_entries([gio::ActionEntry::builder("save-as")
+                .activate(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _, _| {
+                        spawn!(async move {
+                            imp.save_file().await;
+                        });
+                    }
+                ))
+                .build()]);
+        }
+
         if event
             .media_message()
             .is_some_and(|media_message| media_message.caption().is_some())
@@ -552,12 +577,53 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
             return;
         };
 
-        let client = session.client();
         media_message
-            .save_to_file(&event.timestamp(), &client, &*self.obj())
+            .save_to_file(&event.timestamp(), &session, &*self.obj())
             .await;
     }
 
+    /// Copy the original image of this row to the clipboard.
+    async fn copy_original_image(&self)
+    where
+        Self::Type: IsA<gtk::Widget>,
+    {
+        let Some(event) = self.event() else {
+            error!("Could not copy image of timeline item that is not an event");
+            return;
+        };
+        let Some(session) = event.room().session() else {
+            // Should only happen if the process is being closed.
+            return;
+        };
+        let Some(media_message) = event.media_message() else {
+            error!("Could not copy image for non-media event");
+            return;
+        };
+
+        let obj = self.obj();
+        let client = session.client();
+
+        let data = match media_message.into_content(&client).await {
+            Ok(data) => data,
+            Err(error) => {
+                error!("Could not retrieve image: {error}");
+                toast!(obj, error.to_user_facing());
+                return;
+            }
+        };
+
+        let texture = match Image::from_bytes(data, None).await {
+            Ok(image) => image.into_texture(),
+            Err(error) => {
+                toast!(obj, error.to_string());
+                return;
+            }
+        };
+
+        obj.clipboard().set_texture(&texture);
+        toast!(obj, gettext("Image copied to clipboard"));
+    }
+
     /// Redact the event of this row.
     async fn redact_message(&self)
     where
@@ -572,27 +638,71 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
             return;
         };
         let obj = self.obj();
+        let room = event.room();
+        let as_moderator = event.sender_id() != *room.own_member().user_id();
 
-        let confirm_dialog = adw::AlertDialog::builder()
-            .default_response("cancel")
-            .heading(gettext("Remove Message?"))
-            .body(gettext(
-                "Do you really want to remove this message? This cannot be undone.",
-            ))
+        let Some(response) = confirm_redact_events_dialog(1, as_moderator, &*obj).await else {
+            return;
+        };
+
+        if room.redact(&[event_id], response.reason).await.is_err() {
+            toast!(obj, gettext("Could not remove message"));
+        }
+    }
+
+    /// Ask the user for a custom reaction and send it for the event of this
+    /// row.
+    async fn send_custom_reaction(&self)
+    where
+        Self::Type: IsA<gtk::Widget>,
+    {
+        if let Some(popover) = self.popover() {
+            popover.popdown();
+        }
+
+        let obj = self.obj();
+
+        // The entry includes a button to open the full emoji chooser.
+        let entry = gtk::Entry::builder()
+            .show_emoji_icon(true)
+            .activates_default(true)
+            .max_length(MAX_CUSTOM_REACTION_CHARS.try_into().unwrap_or(i32::MAX))
+            .placeholder_text(gettext("Emoji or text"))
+            .margin_top(6)
             .build();
-        confirm_dialog.add_responses(&[
+
+        let dialog = adw::AlertDialog::builder()
+            .default_response("react")
+            .heading(gettext("Custom Reaction"))
+            .body(gettext("React with any emoji or a short text"))
+            .extra_child(&entry)
+            .build();
+        dialog.add_responses(&[
             ("cancel", &gettext("Cancel")),
-            ("remove", &gettext("Remove")),
+            // Translators: This is a verb, as in 'React to the message'.
+            ("react", &gettext("React")),
         ]);
-        confirm_dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("react", adw::ResponseAppearance::Suggested);
+        dialog.set_response_enabled("react", false);
+
+        entry.connect_changed(clone!(
+            #[weak]
+            dialog,
+            move |entry| {
+                dialog.set_response_enabled("react", custom_reaction_key(&entry.text()).is_some());
+            }
+        ));
 
-        if confirm_dialog.choose_future(Some(&*obj)).await != "remove" {
+        if dialog.choose_future(Some(&*obj)).await != "react" {
             return;
         }
 
-        if event.room().redact(&[event_id], None).await.is_err() {
-            toast!(obj, gettext("Could not remove message"));
-        }
+        let text = entry.text();
+        let Some(key) = custom_reaction_key(&text) else {
+            return;
+        };
+
+        self.toggle_reaction(key.to_owned()).await;
     }
 
     /// Toggle the reaction with the given key for the event of this row.
@@ -679,6 +789,16 @@ pub(crate) trait EventActionsGroup: ObjectSubclass {
             return;
         };
 
+        // The content of a message that failed to be sent would be lost, so ask
+        // for confirmation.
+        if matches!(
+            event.state(),
+            MessageState::RecoverableError | MessageState::PermanentError
+        ) && !confirm_discard_failed_message_dialog(&*self.obj()).await
+        {
+            return;
+        }
+
         let matrix_timeline = event.timeline().matrix_timeline();
         let identifier = event.identifier();
         let handle = spawn_tokio!(async move { matrix_timeline.redact(&identifier, None).await });
@@ -1,7 +1,50 @@
 mod context_menu;
 mod group;
 mod properties_dialog;
+mod quick_reaction_bar;
 mod quick_reaction_chooser;
 
 use self::quick_reaction_chooser::*;
-pub(super) use self::{context_menu::*, group::*, properties_dialog::*};
+pub(super) use self::{
+    context_menu::*, group::*, properties_dialog::*, quick_reaction_bar::QuickReactionBar,
+};
+use crate::session::Session;
+
+/// The reactions to suggest when the user did not use enough reactions yet.
+const DEFAULT_QUICK_REACTIONS: &[&str] = &["👍️", "👎️", "😄", "🎉", "😕", "❤️", "🚀"];
+/// The number of recently used reactions to suggest, in addition to the most
+/// frequently used ones.
+const RECENT_QUICK_REACTIONS_COUNT: usize = 2;
+
+/// The reactions to suggest for the given session, up to the given number.
+///
+/// The most frequently used reactions come first, then the most recently used
+/// ones, and the list is completed with default reactions.
+fn quick_reactions(session: Option<&Session>, count: usize) -> Vec<String> {
+    let mut reactions = Vec::with_capacity(count);
+
+    if let Some(settings) = session.map(Session::settings) {
+        let recent_count = RECENT_QUICK_REACTIONS_COUNT.min(count / 2);
+        reactions.extend(settings.frequent_reactions(count - recent_count));
+
+        for key in settings.recent_reactions(count) {
+            if reactions.len() >= count {
+                break;
+            }
+            if !reactions.contains(&key) {
+                reactions.push(key);
+            }
+        }
+    }
+
+    for key in DEFAULT_QUICK_REACTIONS {
+        if reactions.len() >= count {
+            break;
+        }
+        if !reactions.iter().any(|reaction| reaction == key) {
+            reactions.push((*key).to_owned());
+        }
+    }
+
+    reactions
+}
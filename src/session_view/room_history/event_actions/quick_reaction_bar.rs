@@ -0,0 +1,98 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::glib;
+
+use super::quick_reactions;
+use crate::session::Session;
+
+/// The number of reactions to present in the bar.
+const QUICK_REACTION_BAR_COUNT: usize = 3;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session_view/room_history/event_actions/quick_reaction_bar.ui"
+    )]
+    pub struct QuickReactionBar {
+        #[template_child]
+        reaction_box: TemplateChild<gtk::Box>,
+        /// The keys of the reactions that are presented.
+        keys: RefCell<Vec<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for QuickReactionBar {
+        const NAME: &'static str = "QuickReactionBar";
+        type Type = super::QuickReactionBar;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for QuickReactionBar {}
+    impl WidgetImpl for QuickReactionBar {}
+    impl BinImpl for QuickReactionBar {}
+
+    impl QuickReactionBar {
+        /// Update the reactions presented for the given session.
+        pub(super) fn update(&self, session: Option<&Session>) {
+            let keys = quick_reactions(session, QUICK_REACTION_BAR_COUNT);
+
+            if *self.keys.borrow() == keys {
+                return;
+            }
+
+            while let Some(child) = self.reaction_box.first_child() {
+                self.reaction_box.remove(&child);
+            }
+
+            for key in &keys {
+                let button = gtk::Button::builder()
+                    .label(key)
+                    .action_name("event.toggle-reaction")
+                    .action_target(&key.to_variant())
+                    .css_classes(["flat", "circular"])
+                    .build();
+                self.reaction_box.append(&button);
+            }
+
+            self.keys.replace(keys);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A bar presenting the reactions most used by the user, to react quickly
+    /// to an event.
+    pub struct QuickReactionBar(ObjectSubclass<imp::QuickReactionBar>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl QuickReactionBar {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Update the reactions presented for the given session.
+    pub(crate) fn update(&self, session: Option<&Session>) {
+        self.imp().update(session);
+    }
+}
+
+impl Default for QuickReactionBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
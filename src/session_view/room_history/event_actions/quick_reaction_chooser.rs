@@ -4,61 +4,29 @@ use gtk::{
     glib::{clone, closure_local},
 };
 
-use crate::{session::ReactionList, utils::BoundObject};
-
-/// A quick reaction.
-#[derive(Debug, Clone, Copy)]
-struct QuickReaction {
-    /// The emoji that is presented.
-    key: &'static str,
-    /// The number of the column where this reaction is presented.
-    ///
-    /// There are 4 columns in total.
-    column: i32,
-    /// The number of the row where this reaction is presented.
-    ///
-    /// There are 2 rows in total.
-    row: i32,
-}
+use super::quick_reactions;
+use crate::{
+    prelude::*,
+    session::{ReactionList, Session},
+    utils::BoundObject,
+};
 
-/// The quick reactions to present.
-static QUICK_REACTIONS: &[QuickReaction] = &[
-    QuickReaction {
-        key: "👍️",
-        column: 0,
-        row: 0,
-    },
-    QuickReaction {
-        key: "👎️",
-        column: 1,
-        row: 0,
-    },
-    QuickReaction {
-        key: "😄",
-        column: 2,
-        row: 0,
-    },
-    QuickReaction {
-        key: "🎉",
-        column: 3,
-        row: 0,
-    },
-    QuickReaction {
-        key: "😕",
-        column: 0,
-        row: 1,
-    },
-    QuickReaction {
-        key: "❤️",
-        column: 1,
-        row: 1,
-    },
-    QuickReaction {
-        key: "🚀",
-        column: 2,
-        row: 1,
-    },
-];
+/// The number of quick reactions to present.
+///
+/// They are presented in 2 rows of 4 columns, the last cell being used by the
+/// "More reactions" button.
+const QUICK_REACTIONS_COUNT: usize = 7;
+/// The number of columns of the grid.
+const COLUMNS_COUNT: usize = 4;
+
+/// The position in the grid of the quick reaction at the given index.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn grid_position(index: usize) -> (i32, i32) {
+    (
+        (index % COLUMNS_COUNT) as i32,
+        (index / COLUMNS_COUNT) as i32,
+    )
+}
 
 mod imp {
 
@@ -81,6 +49,8 @@ mod imp {
         #[property(get, set = Self::set_reactions, explicit_notify, nullable)]
         reactions: BoundObject<ReactionList>,
         reaction_bindings: RefCell<HashMap<String, glib::Binding>>,
+        /// The keys of the quick reactions that are presented.
+        keys: RefCell<Vec<String>>,
     }
 
     #[glib::object_subclass]
@@ -110,20 +80,7 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
 
-            // Construct the quick reactions.
-            let grid = &self.reaction_grid;
-            for reaction in QUICK_REACTIONS {
-                let button = gtk::ToggleButton::builder()
-                    .label(reaction.key)
-                    .action_name("event.toggle-reaction")
-                    .action_target(&reaction.key.to_variant())
-                    .css_classes(["flat", "circular"])
-                    .build();
-                button.connect_clicked(|button| {
-                    button.activate_action("context-menu.close", None).unwrap();
-                });
-                grid.attach(&button, reaction.column, reaction.row, 1, 1);
-            }
+            self.update_keys(None);
         }
     }
 
@@ -147,18 +104,23 @@ mod imp {
             }
 
             // Reset the state of the buttons.
-            for row in 0..=1 {
-                for column in 0..=3 {
-                    if let Some(button) = self
-                        .reaction_grid
-                        .child_at(column, row)
-                        .and_downcast::<gtk::ToggleButton>()
-                    {
-                        button.set_active(false);
-                    }
+            for index in 0..self.keys.borrow().len() {
+                let (column, row) = grid_position(index);
+                if let Some(button) = self
+                    .reaction_grid
+                    .child_at(column, row)
+                    .and_downcast::<gtk::ToggleButton>()
+                {
+                    button.set_active(false);
                 }
             }
 
+            // Present the reactions used by the user.
+            let session = reactions
+                .as_ref()
+                .map(|reactions| reactions.user().session());
+            self.update_keys(session.as_ref());
+
             if let Some(reactions) = reactions {
                 let signal_handler = reactions.connect_items_changed(clone!(
                     #[weak(rename_to = imp)]
@@ -173,31 +135,68 @@ mod imp {
             self.update_reactions();
         }
 
+        /// Update the keys of the quick reactions for the given session.
+        fn update_keys(&self, session: Option<&Session>) {
+            let keys = quick_reactions(session, QUICK_REACTIONS_COUNT);
+
+            if *self.keys.borrow() == keys {
+                return;
+            }
+
+            for (_, binding) in self.reaction_bindings.borrow_mut().drain() {
+                binding.unbind();
+            }
+
+            let grid = &self.reaction_grid;
+            for index in 0..self.keys.borrow().len() {
+                let (column, row) = grid_position(index);
+                if let Some(button) = grid.child_at(column, row) {
+                    grid.remove(&button);
+                }
+            }
+
+            for (index, key) in keys.iter().enumerate() {
+                let button = gtk::ToggleButton::builder()
+                    .label(key)
+                    .action_name("event.toggle-reaction")
+                    .action_target(&key.to_variant())
+                    .css_classes(["flat", "circular"])
+                    .build();
+                button.connect_clicked(|button| {
+                    button.activate_action("context-menu.close", None).unwrap();
+                });
+
+                let (column, row) = grid_position(index);
+                grid.attach(&button, column, row, 1, 1);
+            }
+
+            self.keys.replace(keys);
+        }
+
         /// Update the state of the quick reactions.
         fn update_reactions(&self) {
             let mut reaction_bindings = self.reaction_bindings.borrow_mut();
             let reactions = self.reactions.obj();
 
-            for reaction_item in QUICK_REACTIONS {
+            for (index, key) in self.keys.borrow().iter().enumerate() {
+                let (column, row) = grid_position(index);
+
                 if let Some(reaction) = reactions
                     .as_ref()
-                    .and_then(|reactions| reactions.reaction_group_by_key(reaction_item.key))
+                    .and_then(|reactions| reactions.reaction_group_by_key(key))
                 {
-                    if reaction_bindings.get(reaction_item.key).is_none() {
-                        let button = self
-                            .reaction_grid
-                            .child_at(reaction_item.column, reaction_item.row)
-                            .unwrap();
+                    if reaction_bindings.get(key).is_none() {
+                        let button = self.reaction_grid.child_at(column, row).unwrap();
                         let binding = reaction
                             .bind_property("has-own-user", &button, "active")
                             .sync_create()
                             .build();
-                        reaction_bindings.insert(reaction_item.key.to_string(), binding);
+                        reaction_bindings.insert(key.clone(), binding);
                     }
-                } else if let Some(binding) = reaction_bindings.remove(reaction_item.key) {
+                } else if let Some(binding) = reaction_bindings.remove(key) {
                     if let Some(button) = self
                         .reaction_grid
-                        .child_at(reaction_item.column, reaction_item.row)
+                        .child_at(column, row)
                         .and_downcast::<gtk::ToggleButton>()
                     {
                         button.set_active(false);
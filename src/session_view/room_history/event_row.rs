@@ -1,18 +1,34 @@
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::{gdk, gio, glib, glib::clone};
+use gtk::{gdk, gio, glib, glib::clone, graphene};
 use matrix_sdk_ui::timeline::TimelineEventItemId;
+use tracing::error;
 
-use super::{EventActionsGroup, MessageRow, RoomHistory, StateRow};
+use super::{EventActionsGroup, MessageRow, QuickReactionBar, RoomHistory, StateRow};
 use crate::{
     components::ContextMenuBin,
     prelude::*,
     session::Event,
     session_view::room_history::message_toolbar::ComposerState,
-    utils::{BoundObject, BoundObjectWeakRef},
+    utils::{BoundObject, BoundObjectWeakRef, key_bindings},
 };
 
+/// The horizontal distance, in pixels, that a row must be swiped to reply to
+/// its event.
+const SWIPE_REPLY_THRESHOLD: f64 = 80.0;
+/// The maximum horizontal distance, in pixels, that a row can be translated
+/// when swiped.
+const SWIPE_MAX_OFFSET: f64 = 120.0;
+/// The horizontal distance, in pixels, after which a swipe is considered
+/// horizontal and claims the touch sequence.
+const SWIPE_CLAIM_DISTANCE: f64 = 12.0;
+/// The duration of the animation to snap a swiped row back, in milliseconds.
+const SWIPE_SNAP_BACK_DURATION: u32 = 200;
+
 mod imp {
-    use std::{cell::RefCell, rc::Rc};
+    use std::{
+        cell::{Cell, OnceCell, RefCell},
+        rc::Rc,
+    };
 
     use super::*;
 
@@ -23,6 +39,7 @@ mod imp {
         #[property(get, set = Self::set_room_history, construct_only)]
         room_history: glib::WeakRef<RoomHistory>,
         message_toolbar_handler: RefCell<Option<glib::SignalHandlerId>>,
+        selection_handler: RefCell<Option<glib::SignalHandlerId>>,
         composer_state: BoundObjectWeakRef<ComposerState>,
         /// The event presented by this row.
         #[property(get, set = Self::set_event, explicit_notify, nullable)]
@@ -32,6 +49,22 @@ mod imp {
         shortcut_controller: RefCell<Option<gtk::ShortcutController>>,
         permissions_handler: RefCell<Option<glib::SignalHandlerId>>,
         target_user_handler: RefCell<Option<glib::SignalHandlerId>>,
+        /// The point where the current swipe started.
+        swipe_start: Cell<Option<(f64, f64)>>,
+        /// Whether the current swipe claimed the touch sequence.
+        is_swipe_claimed: Cell<bool>,
+        /// The current horizontal translation of this row, due to a swipe.
+        swipe_offset: Cell<f64>,
+        /// The animation to snap the row back after a swipe.
+        swipe_animation: OnceCell<adw::TimedAnimation>,
+        /// The box containing the selection check button and the overlay.
+        child_box: gtk::Box,
+        /// The check button to select the event, in selection mode.
+        selection_check: gtk::CheckButton,
+        /// The overlay containing the widget presenting the event.
+        overlay: gtk::Overlay,
+        /// The bar to react quickly to the event, shown on hover.
+        quick_reaction_bar: OnceCell<QuickReactionBar>,
     }
 
     #[glib::object_subclass]
@@ -43,6 +76,18 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             klass.set_css_name("event-row");
             klass.set_accessible_role(gtk::AccessibleRole::ListItem);
+
+            klass.install_action("event-row.react", None, |obj, _, _| {
+                obj.imp().show_emoji_chooser();
+            });
+
+            // Keyboard navigation of the focused event.
+            key_bindings::add_activate_bindings(klass, "context-menu.activate");
+            klass.add_binding_action(gdk::Key::r, gdk::ModifierType::empty(), "event.reply");
+            klass.add_binding_action(gdk::Key::e, gdk::ModifierType::empty(), "event.edit");
+            for key in [gdk::Key::period, gdk::Key::semicolon] {
+                klass.add_binding_action(key, gdk::ModifierType::CONTROL_MASK, "event-row.react");
+            }
         }
     }
 
@@ -56,6 +101,28 @@ mod imp {
                 obj.imp().update_highlight();
             });
             obj.add_css_class("room-history-row");
+            obj.set_focusable(true);
+
+            self.selection_check.set_valign(gtk::Align::Center);
+            self.selection_check.set_visible(false);
+            self.selection_check.connect_toggled(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |check| {
+                    if let Some(room_history) = imp.room_history.upgrade()
+                        && let Some(event) = imp.event.obj()
+                    {
+                        room_history.set_event_selected(&event, check.is_active());
+                    }
+                }
+            ));
+            self.overlay.set_hexpand(true);
+            self.child_box.append(&self.selection_check);
+            self.child_box.append(&self.overlay);
+            obj.set_child(Some(&self.child_box));
+
+            self.init_swipe_gesture();
+            self.init_hover_controller();
         }
 
         fn dispose(&self) {
@@ -66,10 +133,29 @@ mod imp {
             {
                 room_history.message_toolbar().disconnect(handler);
             }
+            if let Some(handler) = self.selection_handler.take()
+                && let Some(room_history) = self.room_history.upgrade()
+            {
+                room_history.disconnect(handler);
+            }
         }
     }
 
-    impl WidgetImpl for EventRow {}
+    impl WidgetImpl for EventRow {
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let offset = self.swipe_offset.get();
+
+            if offset == 0.0 {
+                self.parent_snapshot(snapshot);
+                return;
+            }
+
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(offset as f32, 0.0));
+            self.parent_snapshot(snapshot);
+            snapshot.restore();
+        }
+    }
 
     impl ContextMenuBinImpl for EventRow {
         fn menu_opened(&self) {
@@ -130,7 +216,7 @@ mod imp {
 
         fn texture(&self) -> Option<gdk::Texture> {
             self.obj()
-                .child()
+                .child_property()
                 .and_downcast::<MessageRow>()
                 .and_then(|r| r.texture())
         }
@@ -141,6 +227,268 @@ mod imp {
     }
 
     impl EventRow {
+        /// Initialize the gesture to reply to the event by swiping the row on
+        /// touchscreens.
+        fn init_swipe_gesture(&self) {
+            let obj = self.obj();
+
+            let gesture = gtk::GestureSwipe::builder().touch_only(true).build();
+            gesture.connect_begin(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |gesture, sequence| {
+                    imp.swipe_animation().skip();
+                    imp.swipe_start.set(gesture.point(sequence));
+                }
+            ));
+            gesture.connect_update(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |gesture, sequence| {
+                    imp.update_swipe(gesture, sequence);
+                }
+            ));
+            gesture.connect_end(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _| {
+                    imp.end_swipe(true);
+                }
+            ));
+            gesture.connect_cancel(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _| {
+                    imp.end_swipe(false);
+                }
+            ));
+            obj.add_controller(gesture);
+        }
+
+        /// Initialize the controller to show the quick reaction bar when the
+        /// row is hovered.
+        fn init_hover_controller(&self) {
+            let motion = gtk::EventControllerMotion::new();
+            motion.connect_enter(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |controller, _, _| {
+                    let is_touch = controller
+                        .current_event_device()
+                        .is_some_and(|device| device.source() == gdk::InputSource::Touchscreen);
+
+                    if !is_touch {
+                        imp.show_quick_reaction_bar();
+                    }
+                }
+            ));
+            motion.connect_leave(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.hide_quick_reaction_bar();
+                }
+            ));
+            self.obj().add_controller(motion);
+        }
+
+        /// The bar to react quickly to the event.
+        fn quick_reaction_bar(&self) -> &QuickReactionBar {
+            self.quick_reaction_bar.get_or_init(|| {
+                let bar = QuickReactionBar::new();
+                bar.set_halign(gtk::Align::End);
+                bar.set_valign(gtk::Align::Start);
+                bar.set_visible(false);
+                self.overlay.add_overlay(&bar);
+                bar
+            })
+        }
+
+        /// Show the quick reaction bar, if reactions can be sent to the event.
+        fn show_quick_reaction_bar(&self) {
+            if !self.can_react() {
+                return;
+            }
+
+            let session = self.event.obj().and_then(|event| event.room().session());
+
+            let bar = self.quick_reaction_bar();
+            bar.update(session.as_ref());
+            bar.set_visible(true);
+        }
+
+        /// Show an emoji chooser to react to the event, if reactions can be
+        /// sent to the event.
+        fn show_emoji_chooser(&self) {
+            if !self.can_react() {
+                return;
+            }
+
+            let obj = self.obj();
+            let emoji_chooser = gtk::EmojiChooser::builder().has_arrow(false).build();
+
+            emoji_chooser.connect_emoji_picked(clone!(
+                #[weak]
+                obj,
+                move |_, emoji| {
+                    let _ = obj.activate_action("event.toggle-reaction", Some(&emoji.to_variant()));
+                }
+            ));
+            emoji_chooser.connect_closed(|emoji_chooser| {
+                emoji_chooser.unparent();
+            });
+            emoji_chooser.set_parent(&*obj);
+
+            emoji_chooser.popup();
+        }
+
+        /// Hide the quick reaction bar.
+        fn hide_quick_reaction_bar(&self) {
+            if let Some(bar) = self.quick_reaction_bar.get() {
+                bar.set_visible(false);
+            }
+        }
+
+        /// The animation to snap the row back after a swipe.
+        fn swipe_animation(&self) -> &adw::TimedAnimation {
+            self.swipe_animation.get_or_init(|| {
+                let target = adw::CallbackAnimationTarget::new(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |value| {
+                        imp.set_swipe_offset(value);
+                    }
+                ));
+
+                let animation = adw::TimedAnimation::new(
+                    &*self.obj(),
+                    0.0,
+                    0.0,
+                    SWIPE_SNAP_BACK_DURATION,
+                    target,
+                );
+                animation.set_easing(adw::Easing::EaseOutCubic);
+                animation
+            })
+        }
+
+        /// Whether the row is swiped in the reverse direction, i.e. towards
+        /// the left.
+        fn is_swipe_reversed(&self) -> bool {
+            self.obj().direction() == gtk::TextDirection::Rtl
+        }
+
+        /// Whether reactions can be sent to the event of this row.
+        fn can_react(&self) -> bool {
+            self.action_group
+                .borrow()
+                .as_ref()
+                .is_some_and(|action_group| action_group.has_action("toggle-reaction"))
+        }
+
+        /// Whether the event of this row can be replied to.
+        fn can_reply(&self) -> bool {
+            self.action_group
+                .borrow()
+                .as_ref()
+                .is_some_and(|action_group| action_group.has_action("reply"))
+        }
+
+        /// Update the current swipe with the given gesture.
+        fn update_swipe(&self, gesture: &gtk::GestureSwipe, sequence: Option<&gdk::EventSequence>) {
+            let Some((start_x, start_y)) = self.swipe_start.get() else {
+                return;
+            };
+            let Some((x, y)) = gesture.point(sequence) else {
+                return;
+            };
+
+            let dx = x - start_x;
+            let dy = y - start_y;
+
+            if !self.is_swipe_claimed.get() {
+                if !self.can_reply() || dy.abs() > dx.abs() {
+                    // Let the room history scroll.
+                    gesture.set_state(gtk::EventSequenceState::Denied);
+                    self.swipe_start.set(None);
+                    return;
+                }
+
+                if dx.abs() < SWIPE_CLAIM_DISTANCE {
+                    return;
+                }
+
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                self.is_swipe_claimed.set(true);
+            }
+
+            // In RTL locales, the row is swiped towards the left.
+            let offset = if self.is_swipe_reversed() {
+                dx.clamp(-SWIPE_MAX_OFFSET, 0.0)
+            } else {
+                dx.clamp(0.0, SWIPE_MAX_OFFSET)
+            };
+            self.set_swipe_offset(offset);
+        }
+
+        /// End the current swipe.
+        ///
+        /// If `apply` is `true` and the threshold was reached, the event of
+        /// this row is replied to.
+        fn end_swipe(&self, apply: bool) {
+            self.is_swipe_claimed.set(false);
+
+            if self.swipe_start.take().is_none() {
+                return;
+            }
+
+            let offset = self.swipe_offset.get();
+
+            if apply && offset.abs() >= SWIPE_REPLY_THRESHOLD {
+                self.reply();
+            }
+
+            if offset != 0.0 {
+                let animation = self.swipe_animation();
+                animation.set_value_from(offset);
+                animation.play();
+            }
+        }
+
+        /// Set the current horizontal translation of this row.
+        fn set_swipe_offset(&self, offset: f64) {
+            let obj = self.obj();
+            let was_past_threshold = self.swipe_offset.get().abs() >= SWIPE_REPLY_THRESHOLD;
+            let is_past_threshold = offset.abs() >= SWIPE_REPLY_THRESHOLD;
+
+            self.swipe_offset.set(offset);
+
+            if was_past_threshold != is_past_threshold {
+                if is_past_threshold {
+                    obj.add_css_class("swiped");
+                } else {
+                    obj.remove_css_class("swiped");
+                }
+            }
+
+            obj.queue_draw();
+        }
+
+        /// Reply to the event of this row.
+        fn reply(&self) {
+            let Some(event_id) = self.event.obj().and_then(|event| event.event_id()) else {
+                return;
+            };
+
+            if self
+                .obj()
+                .activate_action("room-history.reply", Some(&event_id.as_str().to_variant()))
+                .is_err()
+            {
+                error!("Could not activate `room-history.reply` action");
+            }
+        }
+
         /// Set the ancestor room history of this row.
         fn set_room_history(&self, room_history: &RoomHistory) {
             self.room_history.set(Some(room_history));
@@ -158,6 +506,15 @@ mod imp {
                 .replace(Some(message_toolbar_handler));
 
             self.watch_related_event(&message_toolbar.current_composer_state());
+
+            let selection_handler = room_history.connect_selection_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_selection();
+                }
+            ));
+            self.selection_handler.replace(Some(selection_handler));
         }
 
         /// Watch the related event for given current composer state of the
@@ -209,6 +566,7 @@ mod imp {
         fn set_event(&self, event: Option<Event>) {
             // Reinitialize the header.
             self.obj().remove_css_class("has-avatar");
+            self.hide_quick_reaction_bar();
 
             self.disconnect_event_signals();
 
@@ -245,6 +603,7 @@ mod imp {
                     self,
                     move |event| {
                         imp.build_event_widget(event.clone());
+                        imp.update_accessible_label();
                         imp.update_actions();
                     }
                 ));
@@ -253,6 +612,7 @@ mod imp {
                     self,
                     move |event| {
                         imp.build_event_widget(event.clone());
+                        imp.update_accessible_label();
                         imp.update_actions();
                     }
                 ));
@@ -277,8 +637,10 @@ mod imp {
                 self.build_event_widget(event);
             }
 
+            self.update_accessible_label();
             self.update_actions();
             self.update_highlight();
+            self.update_selection();
         }
 
         /// Construct the widget for the given event
@@ -317,11 +679,30 @@ mod imp {
             }
         }
 
+        /// Update the accessible label of this row.
+        ///
+        /// It is announced by screen readers when the row is focused.
+        fn update_accessible_label(&self) {
+            let obj = self.obj();
+
+            if let Some(summary) = self
+                .event
+                .obj()
+                .and_then(|event| event.accessible_summary())
+            {
+                obj.update_property(&[gtk::accessible::Property::Label(&summary)]);
+            } else {
+                obj.reset_property(gtk::AccessibleProperty::Label);
+            }
+        }
+
         /// Update the highlight state of this row.
         fn update_highlight(&self) {
             let obj = self.obj();
 
-            let highlight = self.event.obj().is_some_and(|event| event.is_highlighted());
+            let highlight = self.event.obj().is_some_and(|event| {
+                event.is_highlighted() || (event.mentions_room() && !event.room().at_room_muted())
+            });
             if highlight {
                 obj.add_css_class("highlight");
             } else {
@@ -329,6 +710,29 @@ mod imp {
             }
         }
 
+        /// Update the selection state of this row.
+        fn update_selection(&self) {
+            let Some(room_history) = self.room_history.upgrade() else {
+                return;
+            };
+
+            let is_selection_mode = room_history.is_selection_mode();
+            self.selection_check.set_visible(is_selection_mode);
+
+            if !is_selection_mode {
+                self.selection_check.set_active(false);
+                return;
+            }
+
+            let event = self.event.obj();
+            let can_select = event
+                .as_ref()
+                .is_some_and(|event| event.is_message_like() && event.event_id().is_some());
+            self.selection_check.set_sensitive(can_select);
+            self.selection_check
+                .set_active(event.is_some_and(|event| room_history.is_event_selected(&event)));
+        }
+
         /// Update this row for the related event with the given identifier.
         fn update_for_related_event(&self, related_event_id: Option<&TimelineEventItemId>) {
             let obj = self.obj();
@@ -402,10 +806,10 @@ impl EventRow {
 
 impl ChildPropertyExt for EventRow {
     fn child_property(&self) -> Option<gtk::Widget> {
-        self.child()
+        self.imp().overlay.child()
     }
 
     fn set_child_property(&self, child: Option<&impl IsA<gtk::Widget>>) {
-        self.set_child(child);
+        self.imp().overlay.set_child(child);
     }
 }
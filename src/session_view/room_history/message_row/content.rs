@@ -7,7 +7,8 @@ use tracing::{error, warn};
 
 use super::{
     audio::MessageAudio, caption::MessageCaption, file::MessageFile, location::MessageLocation,
-    reply::MessageReply, text::MessageText, visual_media::MessageVisualMedia,
+    reply::MessageReply, text::MessageText, unable_to_decrypt::MessageUnableToDecrypt,
+    visual_media::MessageVisualMedia,
 };
 use crate::{
     components::AudioPlayerMessage,
@@ -304,9 +305,14 @@ trait MessageContentContainer: ChildPropertyExt {
                         },
                     );
                 }
-                MsgLikeKind::UnableToDecrypt(_) => {
-                    let child = self.child_or_default::<MessageText>();
-                    child.with_plain_text(gettext("Could not decrypt this message, decryption will be retried once the keys are available."), format);
+                MsgLikeKind::UnableToDecrypt(message) => {
+                    if format == ContentFormat::Natural {
+                        let child = self.child_or_default::<MessageUnableToDecrypt>();
+                        child.set_messages(&room, &[message]);
+                    } else {
+                        let child = self.child_or_default::<MessageText>();
+                        child.with_plain_text(gettext("Could not decrypt this message, decryption will be retried once the keys are available."), format);
+                    }
                 }
                 MsgLikeKind::Redacted => {
                     let child = self.child_or_default::<MessageText>();
@@ -446,8 +452,7 @@ trait MessageContentContainer: ChildPropertyExt {
             MediaMessage::File(file) => {
                 let widget = self.child_or_default::<MessageFile>();
 
-                let media_message = MediaMessage::from(file);
-                widget.set_filename(Some(media_message.display_name()));
+                widget.set_media_message(file.into(), room, cache_key);
                 widget.set_format(format);
             }
             MediaMessage::Image(image) => {
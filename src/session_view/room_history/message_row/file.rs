@@ -1,9 +1,20 @@
 use adw::subclass::prelude::*;
 use gettextrs::gettext;
-use gtk::{glib, prelude::*};
+use gtk::{glib, glib::clone, prelude::*};
+use tokio::task::AbortHandle;
+use tracing::warn;
 
 use super::ContentFormat;
-use crate::gettext_f;
+use crate::{
+    gettext_f,
+    session::Room,
+    spawn,
+    utils::{
+        LoadingState,
+        matrix::{MediaMessage, MessageCacheKey},
+        media::MediaFileError,
+    },
+};
 
 mod imp {
     use std::cell::{Cell, RefCell};
@@ -16,12 +27,30 @@ mod imp {
     #[template(resource = "/org/gnome/Fractal/ui/session_view/room_history/message_row/file.ui")]
     #[properties(wrapper_type = super::MessageFile)]
     pub struct MessageFile {
+        #[template_child]
+        actions_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        retry_button: TemplateChild<gtk::Button>,
         /// The filename of the file.
         #[property(get, set = Self::set_filename, explicit_notify, nullable)]
         filename: RefCell<Option<String>>,
         /// Whether this file should be displayed in a compact format.
         #[property(get, set = Self::set_compact, explicit_notify)]
         compact: Cell<bool>,
+        /// The loading state of the download of the file.
+        #[property(get, builder(LoadingState::default()))]
+        state: Cell<LoadingState>,
+        /// The room where the message was sent.
+        room: glib::WeakRef<Room>,
+        /// The file message to download.
+        media_message: RefCell<Option<MediaMessage>>,
+        /// The cache key for the current file message.
+        ///
+        /// We only reset the download if the key changes. This is to avoid
+        /// resetting it when a local echo changes to a remote echo.
+        cache_key: RefCell<MessageCacheKey>,
+        /// The handle to cancel the ongoing download, if any.
+        download_abort_handle: RefCell<Option<AbortHandle>>,
     }
 
     #[glib::object_subclass]
@@ -32,6 +61,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
 
             klass.set_accessible_role(gtk::AccessibleRole::Group);
         }
@@ -42,11 +72,16 @@ mod imp {
     }
 
     #[glib::derived_properties]
-    impl ObjectImpl for MessageFile {}
+    impl ObjectImpl for MessageFile {
+        fn dispose(&self) {
+            self.abort_download();
+        }
+    }
 
     impl WidgetImpl for MessageFile {}
     impl BinImpl for MessageFile {}
 
+    #[gtk::template_callbacks]
     impl MessageFile {
         /// Set the filename of the file.
         fn set_filename(&self, filename: Option<String>) {
@@ -77,6 +112,104 @@ mod imp {
             self.compact.set(compact);
             self.obj().notify_compact();
         }
+
+        /// Set the loading state of the download of the file.
+        fn set_state(&self, state: LoadingState) {
+            if self.state.get() == state {
+                return;
+            }
+
+            let visible_page = match state {
+                LoadingState::Initial | LoadingState::Ready => "save",
+                LoadingState::Loading => "loading",
+                LoadingState::Error => "error",
+            };
+            self.actions_stack.set_visible_child_name(visible_page);
+
+            self.state.set(state);
+            self.obj().notify_state();
+        }
+
+        /// Set the file message to download.
+        pub(super) fn set_media_message(
+            &self,
+            media_message: MediaMessage,
+            room: &Room,
+            cache_key: MessageCacheKey,
+        ) {
+            self.set_filename(Some(media_message.display_name()));
+            self.media_message.replace(Some(media_message));
+            self.room.set(Some(room));
+
+            let should_reset = self.cache_key.borrow().should_reload(&cache_key);
+            self.cache_key.replace(cache_key);
+
+            if should_reset {
+                self.abort_download();
+                self.set_state(LoadingState::Initial);
+            }
+        }
+
+        /// Download the file and save it to a location selected by the user.
+        #[template_callback]
+        fn save_file(&self) {
+            if self.state.get() == LoadingState::Loading {
+                return;
+            }
+
+            let Some(session) = self.room.upgrade().and_then(|room| room.session()) else {
+                return;
+            };
+            let Some(media_message) = self.media_message.borrow().clone() else {
+                return;
+            };
+
+            let download = media_message.download_tmp_file(&session);
+            self.download_abort_handle.replace(download.abort_handle());
+            self.set_state(LoadingState::Loading);
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    match download.into_file().await {
+                        Ok(_) => {
+                            imp.download_abort_handle.take();
+                            imp.set_state(LoadingState::Ready);
+
+                            // The file is in the cache now, so the save dialog is shown right away.
+                            if let Err(error) = imp.obj().activate_action("event.save-as", None) {
+                                warn!("Could not activate action to save file: {error}");
+                            }
+                        }
+                        Err(MediaFileError::Cancelled) => {
+                            // The state was already reset when the download was cancelled.
+                        }
+                        Err(error) => {
+                            warn!("Could not retrieve file: {error}");
+                            imp.download_abort_handle.take();
+                            imp.retry_button
+                                .set_tooltip_text(Some(&gettext("Could not retrieve file")));
+                            imp.set_state(LoadingState::Error);
+                        }
+                    }
+                }
+            ));
+        }
+
+        /// Cancel the ongoing download.
+        #[template_callback]
+        fn cancel_download(&self) {
+            self.abort_download();
+            self.set_state(LoadingState::Initial);
+        }
+
+        /// Abort the ongoing download, if any.
+        fn abort_download(&self) {
+            if let Some(abort_handle) = self.download_abort_handle.take() {
+                abort_handle.abort();
+            }
+        }
     }
 }
 
@@ -92,6 +225,16 @@ impl MessageFile {
         glib::Object::new()
     }
 
+    /// Set the file message to download.
+    pub(crate) fn set_media_message(
+        &self,
+        media_message: MediaMessage,
+        room: &Room,
+        cache_key: MessageCacheKey,
+    ) {
+        self.imp().set_media_message(media_message, room, cache_key);
+    }
+
     /// Set the format of the content to present.
     pub(crate) fn set_format(&self, format: ContentFormat) {
         self.set_compact(matches!(
@@ -2,12 +2,17 @@ use adw::{prelude::*, subclass::prelude::*};
 use geo_uri::GeoUri;
 use gettextrs::gettext;
 use gtk::glib;
-use tracing::warn;
+use tracing::{error, warn};
 
 use super::ContentFormat;
-use crate::components::LocationViewer;
+use crate::{Application, components::LocationViewer, i18n::gettext_f};
+
+/// The settings key to know whether to show maps for locations.
+const SETTINGS_KEY_LOCATION_MAP_PREVIEWS: &str = "location-map-previews";
 
 mod imp {
+    use std::cell::{Cell, OnceCell, RefCell};
+
     use glib::subclass::InitializingObject;
 
     use super::*;
@@ -20,9 +25,19 @@ mod imp {
         #[template_child]
         overlay: TemplateChild<gtk::Overlay>,
         #[template_child]
-        location: TemplateChild<LocationViewer>,
+        placeholder: TemplateChild<gtk::Box>,
+        #[template_child]
+        placeholder_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        open_button: TemplateChild<gtk::Button>,
         #[template_child]
         overlay_error: TemplateChild<gtk::Image>,
+        /// The map showing the location, if maps are enabled.
+        location: OnceCell<LocationViewer>,
+        /// Whether to display this location in a compact format.
+        compact: Cell<bool>,
+        /// The `geo:` URI to display.
+        geo_uri: RefCell<Option<String>>,
     }
 
     #[glib::object_subclass]
@@ -33,6 +48,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
 
             klass.set_css_name("message-location");
             klass.set_accessible_role(gtk::AccessibleRole::Group);
@@ -51,7 +67,7 @@ mod imp {
 
     impl WidgetImpl for MessageLocation {
         fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
-            if self.location.compact() {
+            if self.compact.get() {
                 if orientation == gtk::Orientation::Horizontal {
                     (75, 75, -1, -1)
                 } else {
@@ -63,7 +79,7 @@ mod imp {
         }
 
         fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
-            let width = if self.location.compact() {
+            let width = if self.compact.get() {
                 width.min(75)
             } else {
                 width
@@ -73,20 +89,65 @@ mod imp {
         }
     }
 
+    #[gtk::template_callbacks]
     impl MessageLocation {
+        /// The map showing the location.
+        ///
+        /// It is only created when needed, because it loads map tiles.
+        fn location(&self) -> &LocationViewer {
+            self.location.get_or_init(|| {
+                let location = LocationViewer::new();
+                self.overlay.set_child(Some(&location));
+                location
+            })
+        }
+
         /// Set the `geo:` URI to display.
         pub(super) fn set_geo_uri(&self, uri: &str, format: ContentFormat) {
             let compact = matches!(format, ContentFormat::Compact | ContentFormat::Ellipsized);
-            self.location.set_compact(compact);
+            self.compact.set(compact);
+            self.geo_uri.replace(Some(uri.to_owned()));
+
+            let show_map = Application::default()
+                .settings()
+                .boolean(SETTINGS_KEY_LOCATION_MAP_PREVIEWS);
 
             match GeoUri::parse(uri) {
                 Ok(geo_uri) => {
-                    self.location.set_location(&geo_uri);
+                    if show_map {
+                        let location = self.location();
+                        location.set_compact(compact);
+                        location.set_location(&geo_uri);
+                        location.set_visible(true);
+                        self.placeholder.set_visible(false);
+                    } else {
+                        if let Some(location) = self.location.get() {
+                            location.set_visible(false);
+                        }
+
+                        self.placeholder_label.set_label(&gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "Location at latitude {latitude} and longitude {longitude}",
+                            &[
+                                ("latitude", &geo_uri.latitude().to_string()),
+                                ("longitude", &geo_uri.longitude().to_string()),
+                            ],
+                        ));
+                        self.placeholder_label.set_visible(!compact);
+                        self.placeholder.set_visible(true);
+                    }
+
+                    self.open_button.set_visible(!compact);
                     self.overlay_error.set_visible(false);
                 }
                 Err(error) => {
                     warn!("Encountered invalid geo URI: {error}");
-                    self.location.set_visible(false);
+                    if let Some(location) = self.location.get() {
+                        location.set_visible(false);
+                    }
+                    self.placeholder.set_visible(false);
+                    self.open_button.set_visible(false);
                     self.overlay_error.set_tooltip_text(Some(&gettext(
                         "Location is invalid and cannot be displayed",
                     )));
@@ -103,6 +164,22 @@ mod imp {
                 obj.remove_css_class("compact");
             }
         }
+
+        /// Open the location in the default maps app.
+        #[template_callback]
+        async fn open_in_maps_app(&self) {
+            let Some(uri) = self.geo_uri.borrow().clone() else {
+                return;
+            };
+            let obj = self.obj();
+
+            if let Err(error) = gtk::UriLauncher::new(&uri)
+                .launch_future(obj.root().and_downcast_ref::<gtk::Window>())
+                .await
+            {
+                error!("Could not open location in maps app: {error}");
+            }
+        }
     }
 }
 
@@ -23,6 +23,16 @@ mod imp {
         /// The state that is currently displayed.
         #[property(get, set = Self::set_state, explicit_notify, builder(MessageState::default()))]
         state: Cell<MessageState>,
+        /// Whether the message is waiting for the session to be back online.
+        #[property(get, set = Self::set_is_waiting_for_connection, explicit_notify)]
+        is_waiting_for_connection: Cell<bool>,
+        /// Whether to show whether the message was sent or read by other
+        /// members, when it is not in the send queue.
+        #[property(get, set = Self::set_show_receipt_state, explicit_notify)]
+        show_receipt_state: Cell<bool>,
+        /// Whether the message was read by other members.
+        #[property(get, set = Self::set_is_read, explicit_notify)]
+        is_read: Cell<bool>,
         #[template_child]
         stack: TemplateChild<gtk::Stack>,
     }
@@ -57,66 +67,113 @@ mod imp {
                 return;
             }
 
-            let stack = &self.stack;
+            self.state.set(state);
 
             let name = match state {
-                MessageState::None => {
+                MessageState::None | MessageState::Edited => {
                     if matches!(
                         prev_state,
                         MessageState::Sending
                             | MessageState::RecoverableError
                             | MessageState::PermanentError
-                    ) {
+                    ) && !self.show_receipt_state.get()
+                    {
                         // Show the sent icon for a few seconds.
                         glib::timeout_add_seconds_local_once(
                             SENT_VISIBLE_SECONDS,
                             clone!(
-                                #[weak]
-                                stack,
+                                #[weak(rename_to = imp)]
+                                self,
                                 move || {
-                                    stack.set_visible_child_name("none");
+                                    imp.update_settled_page();
                                 }
                             ),
                         );
 
                         "sent"
                     } else {
-                        "none"
+                        self.settled_page_name()
                     }
                 }
-                MessageState::Sending => "sending",
+                MessageState::Sending => self.sending_page_name(),
                 MessageState::RecoverableError => "warning",
                 MessageState::PermanentError => "error",
-                MessageState::Edited => {
-                    if matches!(
-                        prev_state,
-                        MessageState::Sending
-                            | MessageState::RecoverableError
-                            | MessageState::PermanentError
-                    ) {
-                        // Show the sent icon for a few seconds.
-                        glib::timeout_add_seconds_local_once(
-                            SENT_VISIBLE_SECONDS,
-                            clone!(
-                                #[weak]
-                                stack,
-                                move || {
-                                    stack.set_visible_child_name("edited");
-                                }
-                            ),
-                        );
-
-                        "sent"
-                    } else {
-                        "edited"
-                    }
-                }
             };
-            stack.set_visible_child_name(name);
+            self.stack.set_visible_child_name(name);
 
-            self.state.set(state);
             self.obj().notify_state();
         }
+
+        /// Set whether to show whether the message was sent or read by other
+        /// members.
+        fn set_show_receipt_state(&self, show: bool) {
+            if self.show_receipt_state.get() == show {
+                return;
+            }
+
+            self.show_receipt_state.set(show);
+            self.update_settled_page();
+            self.obj().notify_show_receipt_state();
+        }
+
+        /// Set whether the message was read by other members.
+        fn set_is_read(&self, is_read: bool) {
+            if self.is_read.get() == is_read {
+                return;
+            }
+
+            self.is_read.set(is_read);
+            self.update_settled_page();
+            self.obj().notify_is_read();
+        }
+
+        /// The name of the page to show when the message is not in the send
+        /// queue.
+        fn settled_page_name(&self) -> &'static str {
+            if self.show_receipt_state.get() {
+                if self.is_read.get() {
+                    "read"
+                } else {
+                    "delivered"
+                }
+            } else if self.state.get() == MessageState::Edited {
+                "edited"
+            } else {
+                "none"
+            }
+        }
+
+        /// Update the visible page, if the message is not in the send queue.
+        fn update_settled_page(&self) {
+            if matches!(self.state.get(), MessageState::None | MessageState::Edited) {
+                self.stack.set_visible_child_name(self.settled_page_name());
+            }
+        }
+
+        /// Set whether the message is waiting for the session to be back
+        /// online.
+        fn set_is_waiting_for_connection(&self, is_waiting: bool) {
+            if self.is_waiting_for_connection.get() == is_waiting {
+                return;
+            }
+
+            self.is_waiting_for_connection.set(is_waiting);
+
+            if self.state.get() == MessageState::Sending {
+                self.stack.set_visible_child_name(self.sending_page_name());
+            }
+
+            self.obj().notify_is_waiting_for_connection();
+        }
+
+        /// The name of the page to show when the message is being sent.
+        fn sending_page_name(&self) -> &'static str {
+            if self.is_waiting_for_connection.get() {
+                "waiting"
+            } else {
+                "sending"
+            }
+        }
     }
 }
 
@@ -13,9 +13,11 @@ mod reaction_list;
 mod reply;
 mod sender_name;
 mod text;
+mod unable_to_decrypt;
 mod visual_media;
 
 pub use self::content::{ContentFormat, MessageContent};
+pub(crate) use self::unable_to_decrypt::MessageUnableToDecrypt;
 use self::{
     message_state_stack::MessageStateStack, reaction_list::MessageReactionList,
     sender_name::MessageSenderName,
@@ -58,8 +60,9 @@ mod imp {
         reactions: TemplateChild<MessageReactionList>,
         #[template_child]
         read_receipts: TemplateChild<ReadReceiptsList>,
-        binding: RefCell<Option<glib::Binding>>,
+        bindings: RefCell<Vec<glib::Binding>>,
         system_settings_handler: RefCell<Option<glib::SignalHandlerId>>,
+        settings_handler: RefCell<Option<glib::SignalHandlerId>>,
         /// The event that is presented.
         #[property(get, set = Self::set_event, explicit_notify)]
         event: BoundObject<Event>,
@@ -122,16 +125,32 @@ mod imp {
             ));
             self.system_settings_handler
                 .replace(Some(system_settings_handler));
+
+            let settings_handler = Application::default().settings().connect_changed(
+                Some("message-bubbles"),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_header();
+                    }
+                ),
+            );
+            self.settings_handler.replace(Some(settings_handler));
         }
 
         fn dispose(&self) {
-            if let Some(binding) = self.binding.take() {
+            for binding in self.bindings.take() {
                 binding.unbind();
             }
 
             if let Some(handler) = self.system_settings_handler.take() {
                 Application::default().system_settings().disconnect(handler);
             }
+
+            if let Some(handler) = self.settings_handler.take() {
+                Application::default().settings().disconnect(handler);
+            }
         }
     }
 
@@ -146,7 +165,7 @@ mod imp {
 
             // Remove signals and bindings from the previous event.
             self.event.disconnect_signals();
-            if let Some(binding) = self.binding.take() {
+            for binding in self.bindings.take() {
                 binding.unbind();
             }
 
@@ -157,8 +176,56 @@ mod imp {
                 .bind_property("state", &*self.message_state, "state")
                 .sync_create()
                 .build();
+            let is_waiting_for_connection_binding = event
+                .bind_property(
+                    "is-waiting-for-connection",
+                    &*self.message_state,
+                    "is-waiting-for-connection",
+                )
+                .sync_create()
+                .build();
+
+            let is_read_binding = event
+                .bind_property("is-read-by-others", &*self.message_state, "is-read")
+                .sync_create()
+                .build();
+
+            let mut bindings = vec![
+                state_binding,
+                is_waiting_for_connection_binding,
+                is_read_binding,
+            ];
+
+            // Only show whether our own messages were read if we share our read receipts too.
+            if let Some(session) = event
+                .room()
+                .session()
+                .filter(|_| event.sender().is_own_user())
+            {
+                let show_receipt_state_binding = session
+                    .settings()
+                    .bind_property(
+                        "public-read-receipts-enabled",
+                        &*self.message_state,
+                        "show-receipt-state",
+                    )
+                    .sync_create()
+                    .build();
+                bindings.push(show_receipt_state_binding);
+            } else {
+                self.message_state.set_show_receipt_state(false);
+            }
 
-            self.binding.replace(Some(state_binding));
+            self.bindings.replace(bindings);
+
+            let is_waiting_for_connection_handler =
+                event.connect_is_waiting_for_connection_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_is_waiting_for_connection();
+                    }
+                ));
 
             let header_state_handler = event.connect_header_state_notify(clone!(
                 #[weak(rename_to = imp)]
@@ -195,6 +262,7 @@ mod imp {
                     header_state_handler,
                     timestamp_handler,
                     item_changed_handler,
+                    is_waiting_for_connection_handler,
                 ],
             );
             obj.notify_event();
@@ -203,6 +271,23 @@ mod imp {
             self.update_content();
             self.update_header();
             self.update_timestamp();
+            self.update_is_waiting_for_connection();
+        }
+
+        /// Update the style for whether the current event is waiting for the
+        /// session to be back online.
+        fn update_is_waiting_for_connection(&self) {
+            let obj = self.obj();
+
+            if self
+                .event
+                .obj()
+                .is_some_and(|event| event.is_waiting_for_connection())
+            {
+                obj.add_css_class("waiting-for-connection");
+            } else {
+                obj.remove_css_class("waiting-for-connection");
+            }
         }
 
         /// The sender of the event that is presented.
@@ -210,14 +295,19 @@ mod imp {
             self.event.obj().map(|event| event.sender())
         }
 
-        /// Update the header for the current event.
+        /// Update the header and the layout for the current event.
         fn update_header(&self) {
             let Some(event) = self.event.obj() else {
                 return;
             };
 
+            let bubbles = Application::default().settings().boolean("message-bubbles");
+            let is_own = bubbles && event.sender().is_own_user();
+            self.update_layout(bubbles, is_own);
+
             let header_state = event.header_state();
-            let avatar_name_visible = header_state == EventHeaderState::Full;
+            // In the bubble layout, we already know who sent our own messages.
+            let avatar_name_visible = header_state == EventHeaderState::Full && !is_own;
             let header_visible = header_state != EventHeaderState::Hidden;
 
             self.avatar_button.set_visible(avatar_name_visible);
@@ -225,7 +315,8 @@ mod imp {
             self.header.set_visible(header_visible);
 
             if let Some(row) = self.obj().parent() {
-                if avatar_name_visible {
+                // Keep the spacing between groups of messages.
+                if header_state == EventHeaderState::Full {
                     row.add_css_class("has-avatar");
                 } else {
                     row.remove_css_class("has-avatar");
@@ -233,6 +324,38 @@ mod imp {
             }
         }
 
+        /// Update the layout of this row.
+        ///
+        /// In the bubble layout, the content is presented in a bubble that is
+        /// aligned at the end of the row for our own messages, and at the start
+        /// otherwise.
+        fn update_layout(&self, bubbles: bool, is_own: bool) {
+            let obj = self.obj();
+
+            if bubbles {
+                obj.add_css_class("bubbles");
+                self.content.add_css_class("bubble");
+            } else {
+                obj.remove_css_class("bubbles");
+                self.content.remove_css_class("bubble");
+            }
+
+            if is_own {
+                obj.add_css_class("own");
+            } else {
+                obj.remove_css_class("own");
+            }
+
+            let (content_halign, header_halign) = match (bubbles, is_own) {
+                (false, _) => (gtk::Align::Fill, gtk::Align::Fill),
+                (true, false) => (gtk::Align::Start, gtk::Align::Fill),
+                (true, true) => (gtk::Align::End, gtk::Align::End),
+            };
+            self.content.set_halign(content_halign);
+            self.header.set_halign(header_halign);
+            self.reactions.set_halign(header_halign);
+        }
+
         /// Update the displayed timestamp for the current event with the
         /// current clock format setting.
         fn update_timestamp(&self) {
@@ -1,16 +1,28 @@
-use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use gtk::{gdk, gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{MxcUri, OwnedMxcUri, api::client::media::get_content_thumbnail::v3::Method};
+use tracing::warn;
 
 mod reaction_popover;
 
 use self::reaction_popover::ReactionPopover;
 use crate::{
-    gettext_f, ngettext_f,
+    Application, gettext_f, ngettext_f,
     prelude::*,
-    session::{Member, MemberList, ReactionData, ReactionGroup},
+    session::{GlobalAccountData, Member, MemberList, ReactionData, ReactionGroup, Room, Session},
     session_view::room_history::member_timestamp::MemberTimestamp,
-    utils::{BoundObjectWeakRef, EMOJI_REGEX, key_bindings},
+    spawn,
+    utils::{
+        BoundObjectWeakRef, EMOJI_REGEX, key_bindings,
+        media::{
+            FrameDimensions,
+            image::{ImageRequestPriority, ImageSource, ThumbnailDownloader, ThumbnailSettings},
+        },
+    },
 };
 
+/// The size of the image of a reaction key that is a custom emote.
+const REACTION_IMAGE_SIZE: u32 = 20;
+
 mod imp {
     use std::cell::RefCell;
 
@@ -29,6 +41,8 @@ mod imp {
         #[template_child]
         reaction_key: TemplateChild<gtk::Label>,
         #[template_child]
+        reaction_image: TemplateChild<gtk::Image>,
+        #[template_child]
         reaction_count: TemplateChild<gtk::Label>,
         /// The reaction senders group to display.
         #[property(get, set = Self::set_group, construct_only)]
@@ -41,6 +55,13 @@ mod imp {
         members: RefCell<Option<MemberList>>,
         /// The displayed member if there is only one reaction sender.
         reaction_member: BoundObjectWeakRef<Member>,
+        /// The watched room, to update the image of a custom emote when its
+        /// join rule changes.
+        watched_room: BoundObjectWeakRef<Room>,
+        /// The watched global account data, to update the image of a custom
+        /// emote when the media previews setting or the custom emotes of the
+        /// user change.
+        watched_global_account_data: BoundObjectWeakRef<GlobalAccountData>,
     }
 
     impl Default for MessageReaction {
@@ -48,11 +69,14 @@ mod imp {
             Self {
                 button: Default::default(),
                 reaction_key: Default::default(),
+                reaction_image: Default::default(),
                 reaction_count: Default::default(),
                 group: Default::default(),
                 list: gio::ListStore::new::<MemberTimestamp>(),
                 members: Default::default(),
                 reaction_member: Default::default(),
+                watched_room: Default::default(),
+                watched_global_account_data: Default::default(),
             }
         }
     }
@@ -91,6 +115,10 @@ mod imp {
             let key = group.key();
             self.reaction_key.set_label(&key);
 
+            let is_image = image_uri(&key).is_some();
+            self.reaction_key.set_visible(!is_image);
+            self.reaction_image.set_visible(is_image);
+
             if EMOJI_REGEX.is_match(&key) {
                 self.reaction_key.add_css_class("reaction-key-emoji");
                 self.reaction_key.remove_css_class("reaction-key-text");
@@ -137,6 +165,189 @@ mod imp {
             if let Some(group) = self.group.obj() {
                 self.items_changed(&group, 0, self.list.n_items(), group.n_items());
             }
+
+            self.watch_image_settings();
+            self.update_image();
+        }
+
+        /// Watch the settings that affect the image of the reaction key, if it
+        /// is a custom emote.
+        fn watch_image_settings(&self) {
+            self.watched_room.disconnect_signals();
+            self.watched_global_account_data.disconnect_signals();
+
+            let is_image = self
+                .group
+                .obj()
+                .is_some_and(|group| image_uri(&group.key()).is_some());
+            if !is_image {
+                return;
+            }
+
+            let Some(room) = self.members.borrow().as_ref().and_then(MemberList::room) else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            let room_handler = room.connect_join_rule_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_image();
+                }
+            ));
+            self.watched_room.set(&room, vec![room_handler]);
+
+            let global_account_data = session.global_account_data();
+            let media_previews_handler = global_account_data
+                .connect_media_previews_enabled_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_image();
+                    }
+                ));
+            let user_emotes_handler = global_account_data.connect_user_emotes_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_key_name();
+                }
+            ));
+            self.watched_global_account_data.set(
+                global_account_data,
+                vec![media_previews_handler, user_emotes_handler],
+            );
+        }
+
+        /// The current session, if any.
+        fn session(&self) -> Option<Session> {
+            self.members
+                .borrow()
+                .as_ref()
+                .and_then(MemberList::room)
+                .and_then(|room| room.session())
+        }
+
+        /// The name of the reaction key, as presented to the user.
+        ///
+        /// If the key is a custom emote of the user, this is its shortcode.
+        fn reaction_key_name(&self) -> Option<String> {
+            let key = self.group.obj()?.key();
+
+            let shortcode = image_uri(&key).and_then(|uri| {
+                self.session()?
+                    .global_account_data()
+                    .emote_shortcode(uri)
+                    .map(|shortcode| format!(":{shortcode}:"))
+            });
+
+            Some(shortcode.unwrap_or(key))
+        }
+
+        /// Update the places where the name of the reaction key is presented.
+        fn update_key_name(&self) {
+            if let Some(name) = self.reaction_key_name() {
+                self.reaction_image
+                    .update_property(&[gtk::accessible::Property::Label(&name)]);
+                self.reaction_key.set_label(&name);
+            }
+
+            self.update_tooltip();
+        }
+
+        /// Update the image of the reaction key, if it is a custom emote.
+        ///
+        /// The image is only shown if media previews are enabled for the room.
+        fn update_image(&self) {
+            let Some(uri) = self
+                .group
+                .obj()
+                .and_then(|group| image_uri(&group.key()).map(ToOwned::to_owned))
+            else {
+                return;
+            };
+            let Some(room) = self.members.borrow().as_ref().and_then(MemberList::room) else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            let show_image = session
+                .global_account_data()
+                .should_room_show_media_previews(&room);
+
+            if let Some(name) = self.reaction_key_name() {
+                self.reaction_image
+                    .update_property(&[gtk::accessible::Property::Label(&name)]);
+                self.reaction_key.set_label(&name);
+            }
+
+            self.reaction_key.set_visible(!show_image);
+            self.reaction_image.set_visible(show_image);
+
+            if !show_image || self.reaction_image.paintable().is_some() {
+                // Either the image should be hidden, or it was already loaded.
+                return;
+            }
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.load_image_inner(&session, uri).await;
+                }
+            ));
+        }
+
+        /// Download the image of the reaction key with the given URI.
+        async fn load_image_inner(&self, session: &Session, uri: OwnedMxcUri) {
+            // Do not animate the image if the user asked to reduce motion.
+            let animated = !Application::default().system_settings().reduce_motion();
+
+            let dimensions = FrameDimensions {
+                width: REACTION_IMAGE_SIZE,
+                height: REACTION_IMAGE_SIZE,
+            }
+            .scale(self.obj().scale_factor().try_into().unwrap_or(1));
+
+            let downloader = ThumbnailDownloader {
+                main: ImageSource {
+                    source: (&uri).into(),
+                    info: None,
+                },
+                alt: None,
+            };
+            let settings = ThumbnailSettings {
+                dimensions,
+                method: Method::Scale,
+                animated,
+                prefer_thumbnail: true,
+            };
+
+            match downloader
+                .download(session.client(), settings, ImageRequestPriority::Default)
+                .await
+            {
+                Ok(image) => {
+                    let paintable: gdk::Paintable = if animated {
+                        image.into()
+                    } else {
+                        image.into_static_paintable()
+                    };
+                    self.reaction_image.set_paintable(Some(&paintable));
+                }
+                Err(error) => {
+                    warn!("Could not load image of reaction key {uri}: {error}");
+
+                    // Fallback to showing the raw key.
+                    self.reaction_image.set_visible(false);
+                    self.reaction_key.set_visible(true);
+                }
+            }
         }
 
         /// Handle when the items changed.
@@ -165,7 +376,7 @@ mod imp {
 
         /// Update the text of the tooltip.
         fn update_tooltip(&self) {
-            let Some(group) = self.group.obj() else {
+            let Some(reaction_key) = self.reaction_key_name() else {
                 return;
             };
 
@@ -200,7 +411,7 @@ mod imp {
                     "1 member reacted with {reaction_key}",
                     "{n} members reacted with {reaction_key}",
                     n_items,
-                    &[("n", &n_items.to_string()), ("reaction_key", &group.key())],
+                    &[("n", &n_items.to_string()), ("reaction_key", &reaction_key)],
                 )
             });
 
@@ -210,7 +421,7 @@ mod imp {
         /// Update the text of the tooltip when there is a single sender in the
         /// group.
         fn update_member_tooltip(&self, member: &Member) {
-            let Some(group) = self.group.obj() else {
+            let Some(reaction_key) = self.reaction_key_name() else {
                 return;
             };
 
@@ -220,7 +431,7 @@ mod imp {
                 "{user} reacted with {reaction_key}",
                 &[
                     ("user", &member.disambiguated_name()),
-                    ("reaction_key", &group.key()),
+                    ("reaction_key", &reaction_key),
                 ],
             );
 
@@ -247,6 +458,12 @@ mod imp {
     }
 }
 
+/// The URI of the given reaction key, if it is a custom emote.
+fn image_uri(key: &str) -> Option<&MxcUri> {
+    let uri = <&MxcUri>::from(key);
+    uri.is_valid().then_some(uri)
+}
+
 glib::wrapper! {
     /// A widget displaying a reaction of a message.
     pub struct MessageReaction(ObjectSubclass<imp::MessageReaction>)
@@ -28,6 +28,8 @@ mod imp {
         #[property(get, set = Self::set_sender, explicit_notify, nullable)]
         sender: BoundObject<Member>,
         permissions_handler: RefCell<Option<glib::SignalHandlerId>>,
+        /// The CSS class of the color of the current sender.
+        color_css_class: RefCell<Option<String>>,
         gesture_click: RefCell<Option<gtk::GestureClick>>,
     }
 
@@ -118,10 +120,24 @@ mod imp {
                     .set(sender, vec![membership_handler, is_ignored_handler]);
             }
 
+            self.update_color();
             self.update_activatable();
             self.obj().notify_sender();
         }
 
+        /// Update the color of the name for the current sender.
+        fn update_color(&self) {
+            if let Some(css_class) = self.color_css_class.take() {
+                self.label.remove_css_class(&css_class);
+            }
+
+            if let Some(sender) = self.sender.obj() {
+                let css_class = sender.color_css_class();
+                self.label.add_css_class(&css_class);
+                self.color_css_class.replace(Some(css_class));
+            }
+        }
+
         /// Disconnect all the signals.
         fn disconnect_signals(&self) {
             if let Some(sender) = self.sender.obj() {
@@ -2,6 +2,7 @@
 
 use std::fmt::Write;
 
+use gettextrs::gettext;
 use ruma::html::{
     Children, NodeData, NodeRef,
     matrix::{AnchorUri, MatrixElement, SpanData},
@@ -33,8 +34,13 @@ pub(super) struct InlineHtmlBuilder<'a> {
     truncated: bool,
     /// Whether to account for `truncated` when appending children.
     ignore_truncated: bool,
+    /// Whether to reveal the content of spoilers.
+    reveal_spoilers: bool,
 }
 
+/// The URI of the links used to reveal spoilers.
+pub(super) const SPOILER_URI: &str = "fractal-spoiler:";
+
 impl<'a> InlineHtmlBuilder<'a> {
     /// Constructs a new inline HTML string builder for the given room.
     ///
@@ -55,9 +61,19 @@ impl<'a> InlineHtmlBuilder<'a> {
             inner: String::new(),
             truncated: false,
             ignore_truncated: false,
+            reveal_spoilers: false,
         }
     }
 
+    /// Set whether the content of spoilers should be revealed.
+    ///
+    /// By default, the content of spoilers is hidden behind a link to
+    /// [`SPOILER_URI`].
+    pub(super) fn reveal_spoilers(mut self, reveal: bool) -> Self {
+        self.reveal_spoilers = reveal;
+        self
+    }
+
     /// Enable mentions detection in the given room.
     ///
     /// If `detect_at_room` is `true`, it will also try to detect `@room`
@@ -331,6 +347,13 @@ impl<'a> InlineHtmlBuilder<'a> {
     /// Whether we are an inside an anchor or not decides if we try to linkify
     /// the text contained in the children nodes.
     fn append_span(&mut self, span: &SpanData, children: Children, should_linkify: bool) {
+        if let Some(reason) = &span.spoiler
+            && !self.reveal_spoilers
+        {
+            self.append_hidden_spoiler(reason.as_deref(), children);
+            return;
+        }
+
         self.inner.push_str("<span");
 
         if let Some(bg_color) = &span.bg_color {
@@ -347,6 +370,28 @@ impl<'a> InlineHtmlBuilder<'a> {
         self.inner.push_str("</span>");
     }
 
+    /// Append a spoiler with the given reason and hidden inline children.
+    ///
+    /// The text of the children is rendered as a link to [`SPOILER_URI`] with
+    /// the same foreground and background colors, so it is not readable until
+    /// it is revealed.
+    fn append_hidden_spoiler(&mut self, reason: Option<&str>, children: Children) {
+        let text = InlineHtmlBuilder::new(self.single_line, false, self.preserve_whitespace)
+            .build_with_nodes_text(children);
+
+        if let Some(reason) = reason.filter(|reason| !reason.is_empty()) {
+            let _ = write!(self.inner, "({}) ", reason.escape_markup());
+        }
+
+        // We need to escape the title twice because GTK doesn't take care of it.
+        let title = gettext("Reveal spoiler").escape_markup().escape_markup();
+        let _ = write!(
+            self.inner,
+            r##"<a href="{SPOILER_URI}" title="{title}"><span color="#77767b" bgcolor="#77767b">{}</span></a>"##,
+            text.escape_markup(),
+        );
+    }
+
     /// Append the text contained in the nodes to the string.
     ///
     /// Returns `true` if the text was ellipsized.
@@ -420,3 +465,15 @@ struct NodeContext {
     /// Whether this is the last child node of an element.
     is_last_child: bool,
 }
+
+/// Whether the given node or one of its descendants is a spoiler.
+pub(super) fn contains_spoiler(node: &NodeRef) -> bool {
+    if let NodeData::Element(data) = node.data()
+        && let MatrixElement::Span(span) = data.to_matrix().element
+        && span.spoiler.is_some()
+    {
+        return true;
+    }
+
+    node.children().any(|child| contains_spoiler(&child))
+}
@@ -13,7 +13,9 @@ mod inline_html;
 mod tests;
 mod widgets;
 
-use self::widgets::{HtmlWidgetConfig, new_message_label, widget_for_html_nodes};
+use self::widgets::{
+    HtmlWidgetConfig, new_message_label, new_message_label_with_widgets, widget_for_html_nodes,
+};
 use super::ContentFormat;
 use crate::{
     components::{AtRoom, LabelWithWidgets},
@@ -281,11 +283,9 @@ mod imp {
             }
 
             let obj = self.obj();
-            let child = obj.child_or_default::<LabelWithWidgets>();
+            let child = obj.child_or_else::<LabelWithWidgets>(new_message_label_with_widgets);
 
-            child.add_css_class("document");
             child.set_ellipsize(ellipsize);
-            child.set_use_markup(true);
             child.set_label_and_widgets(result, pills);
         }
 
@@ -151,3 +151,22 @@ fn emote_name() {
     assert_eq!(s, "<b>Jun</b> sent a beautiful picture.");
     assert!(pills.is_none());
 }
+
+#[test]
+fn hidden_spoiler() {
+    let html = Html::parse(r#"Before <span data-mx-spoiler="movie">the <b>end</b></span> after"#);
+    let (s, pills) = InlineHtmlBuilder::new(false, false, false).build_with_nodes(html.children());
+
+    assert_eq!(
+        s,
+        r##"Before (movie) <a href="fractal-spoiler:" title="Reveal spoiler"><span color="#77767b" bgcolor="#77767b">the end</span></a> after"##
+    );
+    assert!(pills.is_none());
+
+    let (s, pills) = InlineHtmlBuilder::new(false, false, false)
+        .reveal_spoilers(true)
+        .build_with_nodes(html.children());
+
+    assert_eq!(s, "Before <span>the <b>end</b></span> after");
+    assert!(pills.is_none());
+}
@@ -1,7 +1,7 @@
 //! Build HTML messages.
 
 use gettextrs::gettext;
-use gtk::{pango, prelude::*};
+use gtk::{glib, pango, prelude::*};
 use ruma::html::{
     Children, NodeRef,
     matrix::{MatrixElement, OrderedListData},
@@ -9,11 +9,15 @@ use ruma::html::{
 use sourceview::prelude::*;
 use tracing::debug;
 
-use super::{SUPPORTED_BLOCK_ELEMENTS, inline_html::InlineHtmlBuilder};
+use super::{
+    SUPPORTED_BLOCK_ELEMENTS,
+    inline_html::{InlineHtmlBuilder, SPOILER_URI, contains_spoiler},
+};
 use crate::{
-    components::{AtRoom, LabelWithWidgets},
+    components::{AtRoom, LabelWithWidgets, Pill},
     prelude::*,
     session::Room,
+    utils::links,
 };
 
 /// The immutable config fields to build a HTML widget tree.
@@ -36,14 +40,35 @@ pub(super) struct HtmlWidgetConfig<'a> {
 
 /// Construct a new label for displaying a message's content.
 pub(super) fn new_message_label() -> gtk::Label {
-    gtk::Label::builder()
+    let label = gtk::Label::builder()
         .wrap(true)
         .wrap_mode(pango::WrapMode::WordChar)
         .xalign(0.0)
         .valign(gtk::Align::Start)
         .use_markup(true)
         .css_classes(["document"])
-        .build()
+        .build();
+    label.connect_activate_link(handle_activated_link);
+    label
+}
+
+/// Construct a new label with widgets for displaying a message's content.
+pub(super) fn new_message_label_with_widgets() -> LabelWithWidgets {
+    let label = LabelWithWidgets::new();
+    label.add_css_class("document");
+    label.set_use_markup(true);
+    label.connect_activate_link(handle_activated_link);
+    label
+}
+
+/// Handle the given link activated in the given label.
+fn handle_activated_link(label: &gtk::Label, uri: &str) -> glib::Propagation {
+    // Spoilers are handled separately.
+    if uri == SPOILER_URI {
+        return glib::Propagation::Proceed;
+    }
+
+    links::handle_activated_link(label, uri)
 }
 
 /// Create a widget for the given HTML nodes in the given room.
@@ -177,6 +202,9 @@ fn group_inline_nodes(nodes: Vec<NodeRef>) -> Vec<NodeGroup> {
 
 /// Construct a `GtkLabel` for the given inline nodes.
 ///
+/// If the nodes contain spoilers, they are hidden until the user clicks on
+/// them.
+///
 /// Returns `None` if the label would have been empty.
 fn label_for_inline_html(
     nodes: impl IntoIterator<Item = NodeRef>,
@@ -184,29 +212,23 @@ fn label_for_inline_html(
     add_ellipsis: bool,
     sender_name: &mut Option<&str>,
 ) -> Option<gtk::Widget> {
+    let nodes = nodes.into_iter().collect::<Vec<_>>();
+    let has_spoilers = nodes.iter().any(contains_spoiler);
+    // Keep the sender name to be able to rebuild the label.
+    let owned_sender_name = sender_name.map(ToOwned::to_owned);
+
     let (text, widgets) =
-        InlineHtmlBuilder::new(config.ellipsize, add_ellipsis, config.is_preformatted)
-            .detect_mentions(config.room, config.detect_at_room)
-            .append_emote_with_name(sender_name)
-            .build_with_nodes(nodes);
+        inline_html_markup(nodes.clone(), config, add_ellipsis, sender_name, false);
 
     if text.is_empty() {
         return None;
     }
 
-    if let Some(widgets) = widgets {
-        for pill in &widgets {
-            if !pill.source().is_some_and(|s| s.is::<AtRoom>()) {
-                // Show the profile on click.
-                pill.set_activatable(true);
-            }
-        }
-        let w = LabelWithWidgets::new();
-        w.add_css_class("document");
-        w.set_use_markup(true);
+    let widget = if let Some(widgets) = widgets {
+        let w = new_message_label_with_widgets();
         w.set_ellipsize(config.ellipsize);
         w.set_label_and_widgets(text, widgets);
-        Some(w.upcast())
+        w.upcast::<gtk::Widget>()
     } else {
         let w = new_message_label();
         w.set_markup(&text);
@@ -215,8 +237,87 @@ fn label_for_inline_html(
         } else {
             pango::EllipsizeMode::None
         });
-        Some(w.upcast())
+        w.upcast()
+    };
+
+    if has_spoilers {
+        let room = config.room.clone();
+        let detect_at_room = config.detect_at_room;
+        let ellipsize = config.ellipsize;
+        let is_preformatted = config.is_preformatted;
+
+        let reveal_spoilers = move |widget: &gtk::Widget| {
+            let config = HtmlWidgetConfig {
+                room: &room,
+                detect_at_room,
+                ellipsize,
+                is_preformatted,
+            };
+            let (text, widgets) = inline_html_markup(
+                nodes.clone(),
+                config,
+                add_ellipsis,
+                &mut owned_sender_name.as_deref(),
+                true,
+            );
+
+            if let Some(label) = widget.downcast_ref::<LabelWithWidgets>() {
+                label.set_label_and_widgets(text, widgets.unwrap_or_default());
+            } else if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+                label.set_markup(&text);
+            }
+        };
+
+        let handle_activate_link = {
+            let widget = widget.downgrade();
+            move |_: &gtk::Label, uri: &str| {
+                if uri != SPOILER_URI {
+                    return glib::Propagation::Proceed;
+                }
+
+                if let Some(widget) = widget.upgrade() {
+                    reveal_spoilers(&widget);
+                }
+
+                glib::Propagation::Stop
+            }
+        };
+
+        if let Some(label) = widget.downcast_ref::<LabelWithWidgets>() {
+            label.connect_activate_link(handle_activate_link);
+        } else if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+            label.connect_activate_link(handle_activate_link);
+        }
+    }
+
+    Some(widget)
+}
+
+/// Construct the Pango markup and the pills for the given inline nodes.
+fn inline_html_markup(
+    nodes: Vec<NodeRef>,
+    config: HtmlWidgetConfig<'_>,
+    add_ellipsis: bool,
+    sender_name: &mut Option<&str>,
+    reveal_spoilers: bool,
+) -> (String, Option<Vec<Pill>>) {
+    let (text, widgets) =
+        InlineHtmlBuilder::new(config.ellipsize, add_ellipsis, config.is_preformatted)
+            .detect_mentions(config.room, config.detect_at_room)
+            .reveal_spoilers(reveal_spoilers)
+            .append_emote_with_name(sender_name)
+            .build_with_nodes(nodes);
+
+    if let Some(widgets) = &widgets {
+        for pill in widgets {
+            if !pill.source().is_some_and(|s| s.is::<AtRoom>()) {
+                // Show the profile on click.
+                pill.set_activatable(true);
+            }
+        }
     }
+
+    (text, widgets)
 }
 
 /// Create a widget for the given HTML block node.
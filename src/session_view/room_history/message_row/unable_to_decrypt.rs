@@ -0,0 +1,252 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::glib;
+use matrix_sdk::crypto::types::events::UtdCause;
+use matrix_sdk_ui::timeline::EncryptedMessage;
+use tracing::error;
+
+use crate::{components::LoadingButton, ngettext_f, session::Room, spawn_tokio, toast};
+
+/// The reason why a message could not be decrypted.
+///
+/// The variants are sorted from the most to the least likely to be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum UnableToDecryptReason {
+    /// The keys were not received yet, they might arrive later.
+    MissingKeys,
+    /// The message was sent before this session was logged in, and this
+    /// session is not verified.
+    UnverifiedSession,
+    /// The message was sent before this session was logged in, and the key
+    /// storage is disabled.
+    KeyStorageDisabled,
+    /// The message was sent before the user joined the room.
+    SentBeforeJoined,
+    /// The sender did not share the keys with this session.
+    Withheld,
+    /// The message was sent from a device that is not trusted.
+    InsecureSender,
+}
+
+impl UnableToDecryptReason {
+    /// Whether the keys might still be received for this reason.
+    fn can_be_resolved(self) -> bool {
+        matches!(self, Self::MissingKeys | Self::UnverifiedSession)
+    }
+
+    /// The description of this reason.
+    fn description(self) -> String {
+        match self {
+            Self::MissingKeys => gettext(
+                "The keys have not been received yet. Decryption will be retried automatically once they are available.",
+            ),
+            Self::UnverifiedSession => gettext(
+                "Verify this session to access the keys of messages sent before it was logged in.",
+            ),
+            Self::KeyStorageDisabled => gettext(
+                "Messages sent before this session was logged in cannot be decrypted because the key storage is disabled.",
+            ),
+            Self::SentBeforeJoined => {
+                gettext("You do not have access to messages sent before you joined the room.")
+            }
+            Self::Withheld => gettext("The sender did not share the keys with this session."),
+            Self::InsecureSender => {
+                gettext("The message was sent from a device that is not trusted.")
+            }
+        }
+    }
+}
+
+impl From<&EncryptedMessage> for UnableToDecryptReason {
+    #[allow(clippy::match_wildcard_for_single_variants)]
+    fn from(message: &EncryptedMessage) -> Self {
+        let EncryptedMessage::MegolmV1AesSha2 { cause, .. } = message else {
+            return Self::MissingKeys;
+        };
+
+        match cause {
+            UtdCause::HistoricalMessageAndDeviceIsUnverified => Self::UnverifiedSession,
+            UtdCause::HistoricalMessageAndBackupIsDisabled => Self::KeyStorageDisabled,
+            UtdCause::SentBeforeWeJoined => Self::SentBeforeJoined,
+            UtdCause::WithheldBySender | UtdCause::WithheldForUnverifiedOrInsecureDevice => {
+                Self::Withheld
+            }
+            UtdCause::VerificationViolation
+            | UtdCause::UnsignedDevice
+            | UtdCause::UnknownDevice => Self::InsecureSender,
+            _ => Self::MissingKeys,
+        }
+    }
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session_view/room_history/message_row/unable_to_decrypt.ui"
+    )]
+    pub struct MessageUnableToDecrypt {
+        #[template_child]
+        title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        description_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        request_keys_button: TemplateChild<LoadingButton>,
+        /// The room where the messages were sent.
+        room: glib::WeakRef<Room>,
+        /// The IDs of the sessions of the keys that are missing.
+        session_ids: RefCell<Vec<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageUnableToDecrypt {
+        const NAME: &'static str = "ContentMessageUnableToDecrypt";
+        type Type = super::MessageUnableToDecrypt;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageUnableToDecrypt {}
+
+    impl WidgetImpl for MessageUnableToDecrypt {}
+    impl BinImpl for MessageUnableToDecrypt {}
+
+    #[gtk::template_callbacks]
+    impl MessageUnableToDecrypt {
+        /// Set the messages that could not be decrypted.
+        pub(super) fn set_messages(&self, room: &Room, messages: &[EncryptedMessage]) {
+            self.room.set(Some(room));
+
+            let n = u32::try_from(messages.len()).unwrap_or(u32::MAX);
+            let title = if n > 1 {
+                ngettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "{n} message could not be decrypted",
+                    "{n} messages could not be decrypted",
+                    n,
+                    &[("n", &n.to_string())],
+                )
+            } else {
+                gettext("This message could not be decrypted")
+            };
+            self.title_label.set_label(&title);
+
+            // Present the reason that is the most likely to be resolved.
+            let reason = messages
+                .iter()
+                .map(UnableToDecryptReason::from)
+                .min()
+                .unwrap_or(UnableToDecryptReason::MissingKeys);
+            self.description_label.set_label(&reason.description());
+
+            let mut session_ids = messages
+                .iter()
+                .filter(|message| UnableToDecryptReason::from(*message).can_be_resolved())
+                .filter_map(|message| match message {
+                    EncryptedMessage::MegolmV1AesSha2 { session_id, .. } => {
+                        Some(session_id.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            session_ids.sort_unstable();
+            session_ids.dedup();
+
+            self.request_keys_button
+                .set_visible(!session_ids.is_empty());
+            self.session_ids.replace(session_ids);
+        }
+
+        /// Request the missing keys from the key storage.
+        ///
+        /// Decryption is retried automatically by the timeline when the keys
+        /// are received.
+        #[template_callback]
+        async fn request_keys(&self) {
+            let Some(room) = self.room.upgrade() else {
+                return;
+            };
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            let session_ids = self.session_ids.borrow().clone();
+            if session_ids.is_empty() {
+                return;
+            }
+
+            self.request_keys_button.set_is_loading(true);
+
+            let backups = session.client().encryption().backups();
+            let room_id = room.room_id().to_owned();
+            let handle = spawn_tokio!(async move {
+                if !backups.are_enabled().await {
+                    return Ok(false);
+                }
+
+                for session_id in session_ids {
+                    backups.download_room_key(&room_id, &session_id).await?;
+                }
+
+                Ok::<_, matrix_sdk::Error>(true)
+            });
+
+            let obj = self.obj();
+            match handle.await.expect("task was not aborted") {
+                Ok(true) => {}
+                Ok(false) => {
+                    toast!(
+                        obj,
+                        gettext("The key storage is disabled, set up recovery to enable it")
+                    );
+                }
+                Err(error) => {
+                    error!("Could not download room keys from the key storage: {error}");
+                    toast!(obj, gettext("Could not request the keys"));
+                }
+            }
+
+            self.request_keys_button.set_is_loading(false);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A widget presenting messages that could not be decrypted.
+    pub struct MessageUnableToDecrypt(ObjectSubclass<imp::MessageUnableToDecrypt>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl MessageUnableToDecrypt {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Set the messages that could not be decrypted, in the given room.
+    pub(crate) fn set_messages(&self, room: &Room, messages: &[EncryptedMessage]) {
+        self.imp().set_messages(room, messages);
+    }
+}
+
+impl Default for MessageUnableToDecrypt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,11 +2,12 @@ use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{gdk, glib, glib::clone};
 use ruma::api::client::media::get_content_thumbnail::v3::Method;
+use tokio::task::AbortHandle;
 use tracing::warn;
 
 use super::ContentFormat;
 use crate::{
-    Window,
+    Application, Window,
     components::{AnimatedImagePaintable, VideoPlayer},
     gettext_f,
     session::Room,
@@ -15,9 +16,10 @@ use crate::{
         CountedRef, File, LoadingState, TemplateCallbacks, key_bindings,
         matrix::{MessageCacheKey, VisualMediaMessage, VisualMediaType},
         media::{
-            FrameDimensions,
+            FrameDimensions, MediaFileError,
             image::{ImageRequestPriority, THUMBNAIL_MAX_DIMENSIONS, ThumbnailSettings},
         },
+        timeline_zoom,
     },
 };
 
@@ -67,7 +69,9 @@ mod imp {
         #[template_child]
         hide_preview_button: TemplateChild<gtk::Button>,
         #[template_child]
-        error: TemplateChild<gtk::Image>,
+        cancel_download_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        retry_button: TemplateChild<gtk::Button>,
         /// The room where the message was sent.
         room: glib::WeakRef<Room>,
         join_rule_handler: RefCell<Option<glib::SignalHandlerId>>,
@@ -99,7 +103,12 @@ mod imp {
         placeholder: RefCell<Option<gtk::Picture>>,
         /// The current video file, if any.
         file: RefCell<Option<File>>,
+        /// The handle to cancel the ongoing download of the video, if any.
+        download_abort_handle: RefCell<Option<AbortHandle>>,
         paintable_animation_ref: RefCell<Option<CountedRef>>,
+        /// Whether the pointer is over this widget.
+        hovered: Cell<bool>,
+        reduce_motion_handler: RefCell<Option<glib::SignalHandlerId>>,
         /// The texture of the current image preview, if any.
         #[property(get = Self::texture)]
         texture: PhantomData<Option<gdk::Texture>>,
@@ -132,7 +141,46 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for MessageVisualMedia {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let motion_controller = gtk::EventControllerMotion::new();
+            motion_controller.connect_enter(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _| {
+                    imp.hovered.set(true);
+                    imp.update_animated_paintable_state();
+                }
+            ));
+            motion_controller.connect_leave(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.hovered.set(false);
+                    imp.update_animated_paintable_state();
+                }
+            ));
+            self.obj().add_controller(motion_controller);
+
+            let reduce_motion_handler = Application::default()
+                .system_settings()
+                .connect_reduce_motion_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_animated_paintable_state();
+                    }
+                ));
+            self.reduce_motion_handler
+                .replace(Some(reduce_motion_handler));
+        }
+
         fn dispose(&self) {
+            if let Some(handler) = self.reduce_motion_handler.take() {
+                Application::default().system_settings().disconnect(handler);
+            }
+
             self.clear();
             self.overlay.unparent();
         }
@@ -147,7 +195,7 @@ mod imp {
             let max_size = if self.compact.get() {
                 MAX_COMPACT_DIMENSIONS
             } else {
-                THUMBNAIL_MAX_DIMENSIONS
+                THUMBNAIL_MAX_DIMENSIONS.scale_by(timeline_zoom::factor())
             };
             let max = max_size.dimension_for_orientation(orientation);
             let max_for_size = max_size
@@ -302,7 +350,10 @@ mod imp {
                         .global_account_data()
                         .should_room_show_media_previews(&room),
             );
-            self.error.set_visible(state == LoadingState::Error);
+            self.cancel_download_button.set_visible(
+                state == LoadingState::Loading && self.download_abort_handle.borrow().is_some(),
+            );
+            self.retry_button.set_visible(state == LoadingState::Error);
 
             let visible_page = match state {
                 LoadingState::Initial | LoadingState::Loading => {
@@ -320,8 +371,20 @@ mod imp {
             }
         }
 
-        /// Update the state of the animated paintable, if any.
+        /// Update the state of the animated paintable or of the video, if any.
+        ///
+        /// When motion should be reduced, the animation only plays while the
+        /// pointer is over the media.
         fn update_animated_paintable_state(&self) {
+            let should_play = self.obj().is_mapped()
+                && (self.hovered.get()
+                    || !Application::default().system_settings().reduce_motion());
+
+            if let Some(player) = self.media_child::<VideoPlayer>() {
+                player.set_paused(!should_play);
+                return;
+            }
+
             self.paintable_animation_ref.take();
 
             let Some(paintable) = self
@@ -332,7 +395,7 @@ mod imp {
                 return;
             };
 
-            if self.obj().is_mapped() {
+            if should_play {
                 self.paintable_animation_ref
                     .replace(Some(paintable.animation_ref()));
             }
@@ -613,6 +676,7 @@ mod imp {
         /// Hide the media.
         #[template_callback]
         fn hide_media(&self) {
+            self.abort_download();
             self.set_state(LoadingState::Initial);
             self.set_media_child(None::<&gtk::Widget>);
             self.file.take();
@@ -719,12 +783,7 @@ mod imp {
 
         /// Build the content for the video in the given media message.
         async fn build_video(&self, media_message: VisualMediaMessage) {
-            let Some(client) = self
-                .room
-                .upgrade()
-                .and_then(|room| room.session())
-                .map(|session| session.client())
-            else {
+            let Some(session) = self.room.upgrade().and_then(|room| room.session()) else {
                 return;
             };
 
@@ -733,15 +792,25 @@ mod imp {
                 return;
             }
 
-            let file = match media_message.into_tmp_file(&client).await {
+            let download = media_message.download_tmp_file(&session);
+            self.set_download_abort_handle(download.abort_handle());
+
+            let file = match download.into_file().await {
                 Ok(file) => file,
+                Err(MediaFileError::Cancelled) => {
+                    // The state was already reset when the download was cancelled.
+                    return;
+                }
                 Err(error) => {
+                    self.set_download_abort_handle(None);
                     warn!("Could not retrieve video: {error}");
                     self.set_error(&gettext("Could not retrieve media"));
                     return;
                 }
             };
 
+            self.set_download_abort_handle(None);
+
             if self.state.get() != LoadingState::Loading {
                 // Something occurred while the video was loading, cancel the task.
                 return;
@@ -763,13 +832,40 @@ mod imp {
             };
 
             child.set_compact(self.compact.get());
+            self.update_animated_paintable_state();
             child.play_video_file(file.as_gfile());
             self.file.replace(Some(file));
         }
 
+        /// Set the handle to cancel the ongoing download of the video.
+        fn set_download_abort_handle(&self, abort_handle: Option<AbortHandle>) {
+            self.download_abort_handle.replace(abort_handle);
+            self.update_visible_page();
+        }
+
+        /// Abort the ongoing download of the video, if any.
+        fn abort_download(&self) {
+            if let Some(abort_handle) = self.download_abort_handle.take() {
+                abort_handle.abort();
+            }
+        }
+
+        /// Cancel the ongoing download of the video.
+        #[template_callback]
+        fn cancel_download(&self) {
+            self.abort_download();
+            self.set_state(LoadingState::Initial);
+        }
+
+        /// Try to load the media again after an error.
+        #[template_callback]
+        fn retry(&self) {
+            self.show_media();
+        }
+
         /// Set the given error message for this media.
         fn set_error(&self, message: &str) {
-            self.error.set_tooltip_text(Some(message));
+            self.retry_button.set_tooltip_text(Some(message));
             self.set_state(LoadingState::Error);
         }
 
@@ -794,6 +890,7 @@ mod imp {
 
         /// Reset the state of this widget.
         fn clear(&self) {
+            self.abort_download();
             self.file.take();
 
             if let Some(room) = self.room.upgrade() {
@@ -811,7 +908,10 @@ mod imp {
 
         /// Handle when the widget is activated.
         fn activate(&self) {
-            if self.state.get() == LoadingState::Initial {
+            if matches!(
+                self.state.get(),
+                LoadingState::Initial | LoadingState::Error
+            ) {
                 self.show_media();
             } else {
                 self.show_media_viewer();
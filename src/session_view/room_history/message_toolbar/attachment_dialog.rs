@@ -1,10 +1,44 @@
+use std::time::Duration;
+
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::{gdk, gio, glib, glib::clone};
 
-use crate::{components::MediaContentViewer, spawn, utils::OneshotNotifier};
+use crate::{
+    components::MediaContentViewer,
+    gettext_f, spawn,
+    utils::{
+        OneshotNotifier,
+        media::{
+            filename_for_mime,
+            image::{DEFAULT_ENCODING_QUALITY, ImageEncodingFormat},
+        },
+    },
+};
+
+/// The formats that can be selected to encode an image, in the order of the
+/// drop down.
+const IMAGE_ENCODING_FORMATS: [ImageEncodingFormat; 3] = [
+    ImageEncodingFormat::Png,
+    ImageEncodingFormat::Jpeg,
+    ImageEncodingFormat::Webp,
+];
+/// The delay before encoding the image after its settings changed.
+const ENCODING_DELAY: Duration = Duration::from_millis(300);
+
+/// An image encoded with the settings of the dialog.
+#[derive(Debug)]
+struct EncodedImage {
+    /// The format of the image.
+    format: ImageEncodingFormat,
+    /// The quality of the image.
+    quality: u8,
+    /// The encoded image.
+    bytes: Vec<u8>,
+}
 
 mod imp {
-    use std::cell::OnceCell;
+    use std::cell::{OnceCell, RefCell};
 
     use super::*;
 
@@ -19,7 +53,21 @@ mod imp {
         send_button: TemplateChild<gtk::Button>,
         #[template_child]
         media: TemplateChild<MediaContentViewer>,
+        #[template_child]
+        image_settings_bar: TemplateChild<gtk::Box>,
+        #[template_child]
+        format_drop_down: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        quality_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        size_label: TemplateChild<gtk::Label>,
         notifier: OnceCell<OneshotNotifier<Option<()>>>,
+        /// The image to preview, if any.
+        image: RefCell<Option<gdk::Texture>>,
+        /// The image encoded with the current settings, if any.
+        encoded_image: RefCell<Option<EncodedImage>>,
+        /// The timeout to encode the image after its settings changed.
+        encoding_timeout: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -60,6 +108,10 @@ mod imp {
 
     impl AdwDialogImpl for AttachmentDialog {
         fn closed(&self) {
+            if let Some(source) = self.encoding_timeout.take() {
+                source.remove();
+            }
+
             self.notifier().notify();
         }
     }
@@ -81,7 +133,151 @@ mod imp {
         /// Set the image to preview.
         pub(super) fn set_image(&self, image: &gdk::Texture) {
             self.media.view_image(image);
-            self.set_loading(false);
+            self.image.replace(Some(image.clone()));
+
+            let format = ImageEncodingFormat::guess_for_texture(image);
+            let position = IMAGE_ENCODING_FORMATS
+                .iter()
+                .position(|f| *f == format)
+                .unwrap_or_default();
+            self.format_drop_down
+                .set_selected(position.try_into().unwrap_or_default());
+            self.quality_scale
+                .set_value(DEFAULT_ENCODING_QUALITY.into());
+            self.image_settings_bar.set_visible(true);
+
+            // The dialog stays loading until the image is encoded.
+            self.update_format();
+        }
+
+        /// The selected format to encode the image.
+        fn selected_format(&self) -> ImageEncodingFormat {
+            usize::try_from(self.format_drop_down.selected())
+                .ok()
+                .and_then(|position| IMAGE_ENCODING_FORMATS.get(position))
+                .copied()
+                .unwrap_or(ImageEncodingFormat::Png)
+        }
+
+        /// The selected quality to encode the image.
+        #[allow(clippy::cast_sign_loss)] // We need to convert the f64 to a u8.
+        fn selected_quality(&self) -> u8 {
+            // The adjustment of the scale guarantees that the value fits, but clamp it to
+            // be safe.
+            self.quality_scale.value().round().clamp(0.0, 100.0) as u8
+        }
+
+        /// Update the dialog for the selected format.
+        #[template_callback]
+        fn update_format(&self) {
+            if self.image.borrow().is_none() {
+                return;
+            }
+
+            let format = self.selected_format();
+            self.quality_scale.set_sensitive(format.is_lossy());
+            self.obj()
+                .set_title(&filename_for_mime(Some(format.mime_type().as_ref()), None));
+
+            self.queue_encoding();
+        }
+
+        /// Encode the image again after a delay, to avoid encoding it for
+        /// every step while the user changes the settings.
+        #[template_callback]
+        fn queue_encoding(&self) {
+            if self.image.borrow().is_none() {
+                return;
+            }
+
+            let has_current_encoding =
+                self.encoded_image.borrow().as_ref().is_some_and(|encoded| {
+                    encoded.format == self.selected_format()
+                        && (!encoded.format.is_lossy()
+                            || encoded.quality == self.selected_quality())
+                });
+            if has_current_encoding {
+                return;
+            }
+
+            self.send_button.set_sensitive(false);
+            self.size_label.set_label(&gettext("Computing size…"));
+
+            if let Some(source) = self.encoding_timeout.take() {
+                source.remove();
+            }
+
+            self.encoding_timeout
+                .replace(Some(glib::timeout_add_local_once(
+                    ENCODING_DELAY,
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move || {
+                            imp.encoding_timeout.take();
+
+                            spawn!(async move {
+                                imp.encode_image().await;
+                            });
+                        }
+                    ),
+                )));
+        }
+
+        /// Encode the image with the selected settings.
+        async fn encode_image(&self) {
+            let Some(image) = self.image.borrow().clone() else {
+                return;
+            };
+
+            let format = self.selected_format();
+            let quality = self.selected_quality();
+
+            let bytes = format.encode(&image, quality).await;
+
+            // Ignore the result if the settings changed in the meantime, it
+            // will be encoded again.
+            if format != self.selected_format()
+                || (format.is_lossy() && quality != self.selected_quality())
+            {
+                return;
+            }
+
+            let Some(bytes) = bytes else {
+                self.encoded_image.take();
+                self.size_label
+                    .set_label(&gettext("Could not encode the image in this format"));
+                return;
+            };
+
+            self.size_label.set_label(&gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "Size: {size}",
+                &[("size", &glib::format_size(bytes.len() as u64))],
+            ));
+            let is_first_encoding = self
+                .encoded_image
+                .replace(Some(EncodedImage {
+                    format,
+                    quality,
+                    bytes,
+                }))
+                .is_none();
+
+            if is_first_encoding {
+                self.set_loading(false);
+            } else {
+                // Do not move the focus while the user changes the settings.
+                self.send_button.set_sensitive(true);
+            }
+        }
+
+        /// The image encoded with the selected settings, if any.
+        pub(super) fn encoded_image(&self) -> Option<(ImageEncodingFormat, Vec<u8>)> {
+            self.encoded_image
+                .take()
+                .map(|encoded| (encoded.format, encoded.bytes))
         }
 
         /// Set the file to preview.
@@ -137,10 +333,22 @@ impl AttachmentDialog {
     }
 
     /// Set the image to preview.
+    ///
+    /// This also allows the user to choose the format and quality used to
+    /// encode the image.
     pub(crate) fn set_image(&self, image: &gdk::Texture) {
         self.imp().set_image(image);
     }
 
+    /// Take the image encoded with the format and quality selected by the
+    /// user.
+    ///
+    /// Returns `None` if this dialog is not previewing an image or if the
+    /// image could not be encoded.
+    pub(crate) fn encoded_image(&self) -> Option<(ImageEncodingFormat, Vec<u8>)> {
+        self.imp().encoded_image()
+    }
+
     /// Set the file to preview.
     pub(crate) fn set_file(&self, file: gio::File) {
         let imp = self.imp();
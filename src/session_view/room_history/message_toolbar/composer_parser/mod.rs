@@ -1,9 +1,10 @@
-use std::fmt::Write;
+use std::{fmt::Write, ops::Range};
 
 use gtk::prelude::*;
 use matrix_sdk::{ComposerDraft, ComposerDraftType};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
 use ruma::{
-    OwnedUserId,
+    OwnedRoomAliasId, OwnedUserId, RoomAliasId, UserId,
     events::{
         Mentions,
         room::message::{
@@ -13,6 +14,9 @@ use ruma::{
     },
 };
 
+#[cfg(test)]
+mod tests;
+
 use super::{
     ComposerState, RelationInfo,
     composer_state::{MENTION_END_TAG, MENTION_START_TAG},
@@ -108,9 +112,13 @@ impl<'a> ComposerParser<'a> {
 
     /// Parse the content of the message composer into the content of a message
     /// event.
+    ///
+    /// If `linkify_matrix_ids` is `true`, the user IDs and room aliases in the
+    /// text are converted to links in the formatted body.
     pub(super) async fn into_message_event_content(
         mut self,
         markdown_enabled: bool,
+        linkify_matrix_ids: bool,
     ) -> Option<RoomMessageEventContentWithoutRelation> {
         let message_len = self.message_len();
 
@@ -124,7 +132,18 @@ impl<'a> ComposerParser<'a> {
             match chunk {
                 ComposerChunk::Text(text) => {
                     plain_body.push_str(&text);
-                    formatted_body.push_str(&text);
+
+                    if markdown_enabled {
+                        // Markdown is linkified after the whole message is built, to know
+                        // which parts are escaped.
+                        formatted_body.push_str(&text);
+                    } else if linkify_matrix_ids {
+                        let (html, has_links) = linkify_html(&text);
+                        has_rich_mentions |= has_links;
+                        formatted_body.push_str(&html);
+                    } else {
+                        formatted_body.push_str(&text.escape_markup());
+                    }
                 }
                 ComposerChunk::Mention(source) => match Mention::from_source(&source).await {
                     Mention::Rich { name, uri, user_id } => {
@@ -162,6 +181,10 @@ impl<'a> ComposerParser<'a> {
             return None;
         }
 
+        if linkify_matrix_ids && markdown_enabled {
+            formatted_body = linkify_markdown(&formatted_body);
+        }
+
         let html_body = if markdown_enabled {
             FormattedBody::markdown(formatted_body).map(|b| b.body)
         } else if has_rich_mentions {
@@ -330,3 +353,146 @@ impl Mention {
         }
     }
 }
+
+/// A Matrix ID that can be converted to a link.
+enum MatrixIdLink {
+    /// A user ID.
+    User(OwnedUserId),
+    /// A room alias.
+    RoomAlias(OwnedRoomAliasId),
+}
+
+impl MatrixIdLink {
+    /// The `matrix.to` URI of this ID.
+    fn uri(&self) -> String {
+        match self {
+            Self::User(user_id) => user_id.matrix_to_uri().to_string(),
+            Self::RoomAlias(alias) => alias.matrix_to_uri().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for MatrixIdLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User(user_id) => user_id.fmt(f),
+            Self::RoomAlias(alias) => alias.fmt(f),
+        }
+    }
+}
+
+/// Find the user IDs and room aliases in the given text.
+///
+/// Returns the range of each ID in the text, with the parsed ID.
+fn find_matrix_ids(text: &str) -> Vec<(Range<usize>, MatrixIdLink)> {
+    let mut ids = Vec::new();
+    let mut prev_char = None;
+
+    for (start, c) in text.char_indices() {
+        let is_at_word_start = prev_char.is_none_or(|prev| !is_matrix_id_char(prev));
+        prev_char = Some(c);
+
+        if !is_at_word_start || !matches!(c, '@' | '#') {
+            continue;
+        }
+
+        let len = text[start + 1..]
+            .find(|c| !is_matrix_id_char(c))
+            .unwrap_or(text.len() - start - 1);
+        // Do not include the punctuation at the end of a sentence.
+        let candidate = text[start..=start + len].trim_end_matches(['.', ':']);
+        let range = start..start + candidate.len();
+
+        let id = if c == '@' {
+            UserId::parse(candidate).ok().map(MatrixIdLink::User)
+        } else {
+            RoomAliasId::parse(candidate)
+                .ok()
+                .map(MatrixIdLink::RoomAlias)
+        };
+
+        if let Some(id) = id {
+            ids.push((range, id));
+        }
+    }
+
+    ids
+}
+
+/// Whether the given `char` can be part of a user ID or a room alias, for
+/// linkification.
+fn is_matrix_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '=' | '-' | '/' | '+' | ':' | '[' | ']')
+}
+
+/// Convert the given plain text to HTML, with the user IDs and room aliases
+/// converted to links.
+///
+/// The linked user IDs are not added to the intentional mentions, so they do
+/// not notify the users.
+///
+/// Returns the HTML and whether some IDs were converted.
+fn linkify_html(text: &str) -> (String, bool) {
+    let mut html = String::with_capacity(text.len());
+    let mut has_links = false;
+    let mut end = 0;
+
+    for (range, id) in find_matrix_ids(text) {
+        has_links = true;
+        html.push_str(&text[end..range.start].escape_markup());
+        let _ = write!(
+            html,
+            "<a href=\"{}\">{}</a>",
+            id.uri().escape_markup(),
+            id.to_string().escape_markup()
+        );
+        end = range.end;
+    }
+    html.push_str(&text[end..].escape_markup());
+
+    (html, has_links)
+}
+
+/// Convert the user IDs and room aliases in the given Markdown to links.
+///
+/// The IDs in code, links and images are left untouched. The linked user IDs
+/// are not added to the intentional mentions, so they do not notify the
+/// users.
+fn linkify_markdown(markdown: &str) -> String {
+    let mut escaped_depth = 0_usize;
+    let mut ranges = Vec::new();
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Link { .. } | Tag::Image { .. }) => {
+                escaped_depth += 1;
+            }
+            Event::End(TagEnd::CodeBlock | TagEnd::Link | TagEnd::Image) => {
+                escaped_depth = escaped_depth.saturating_sub(1);
+            }
+            Event::Text(_) if escaped_depth == 0 => {
+                ranges.extend(find_matrix_ids(&markdown[range.clone()]).into_iter().map(
+                    |(id_range, id)| (range.start + id_range.start..range.start + id_range.end, id),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let mut linkified = String::with_capacity(markdown.len());
+    let mut end = 0;
+
+    for (range, id) in ranges {
+        if range.start < end {
+            // Already handled.
+            continue;
+        }
+
+        linkified.push_str(&markdown[end..range.start]);
+        let _ = write!(linkified, "[{id}]({})", id.uri());
+        end = range.end;
+    }
+    linkified.push_str(&markdown[end..]);
+
+    linkified
+}
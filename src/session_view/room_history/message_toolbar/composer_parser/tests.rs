@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use ruma::{RoomAliasId, UserId};
+
+use super::{find_matrix_ids, linkify_html, linkify_markdown};
+
+fn user_uri(user_id: &str) -> String {
+    UserId::parse(user_id).unwrap().matrix_to_uri().to_string()
+}
+
+fn alias_uri(alias: &str) -> String {
+    RoomAliasId::parse(alias)
+        .unwrap()
+        .matrix_to_uri()
+        .to_string()
+}
+
+fn find_ids(text: &str) -> Vec<(Range<usize>, String)> {
+    find_matrix_ids(text)
+        .into_iter()
+        .map(|(range, id)| (range, id.to_string()))
+        .collect()
+}
+
+#[test]
+fn find_user_ids_and_aliases() {
+    assert_eq!(
+        find_ids("@alice:example.org"),
+        vec![(0..18, "@alice:example.org".to_owned())]
+    );
+    assert_eq!(
+        find_ids("Hello @alice:example.org!"),
+        vec![(6..24, "@alice:example.org".to_owned())]
+    );
+    assert_eq!(
+        find_ids("Join #room:example.org and ask @bob:example.org."),
+        vec![
+            (5..22, "#room:example.org".to_owned()),
+            (31..47, "@bob:example.org".to_owned()),
+        ]
+    );
+
+    // The punctuation at the end of a sentence is not included.
+    assert_eq!(
+        find_ids("Ask @alice:example.org: now"),
+        vec![(4..22, "@alice:example.org".to_owned())]
+    );
+    // Multibyte characters before the ID.
+    assert_eq!(
+        find_ids("Été @alice:example.org"),
+        vec![(6..24, "@alice:example.org".to_owned())]
+    );
+    // With a port.
+    assert_eq!(
+        find_ids("@alice:example.org:8448"),
+        vec![(0..23, "@alice:example.org:8448".to_owned())]
+    );
+}
+
+#[test]
+fn ignore_invalid_ids() {
+    // Not at the start of a word.
+    assert!(find_ids("alice@example.org").is_empty());
+    assert!(find_ids("foo#room:example.org").is_empty());
+    // Missing server name.
+    assert!(find_ids("@alice").is_empty());
+    assert!(find_ids("#room").is_empty());
+    // Just the sigils.
+    assert!(find_ids("@ #").is_empty());
+    assert!(find_ids("").is_empty());
+}
+
+#[test]
+fn linkify_plain_text_to_html() {
+    assert_eq!(
+        linkify_html("Hello @alice:example.org"),
+        (
+            format!(
+                "Hello <a href=\"{}\">@alice:example.org</a>",
+                user_uri("@alice:example.org")
+            ),
+            true
+        )
+    );
+
+    // The text around the links is escaped.
+    assert_eq!(
+        linkify_html("a < b & @alice:example.org <i>"),
+        (
+            format!(
+                "a &lt; b &amp; <a href=\"{}\">@alice:example.org</a> &lt;i&gt;",
+                user_uri("@alice:example.org")
+            ),
+            true
+        )
+    );
+
+    // The text is escaped even without links.
+    assert_eq!(
+        linkify_html("<b>bold</b> & co"),
+        ("&lt;b&gt;bold&lt;/b&gt; &amp; co".to_owned(), false)
+    );
+}
+
+#[test]
+fn linkify_markdown_ids() {
+    assert_eq!(
+        linkify_markdown("Hello @alice:example.org"),
+        format!(
+            "Hello [@alice:example.org]({})",
+            user_uri("@alice:example.org")
+        )
+    );
+    assert_eq!(
+        linkify_markdown("*Join #room:example.org*"),
+        format!(
+            "*Join [#room:example.org]({})*",
+            alias_uri("#room:example.org")
+        )
+    );
+    assert_eq!(
+        linkify_markdown("- @alice:example.org\n- @bob:example.org"),
+        format!(
+            "- [@alice:example.org]({})\n- [@bob:example.org]({})",
+            user_uri("@alice:example.org"),
+            user_uri("@bob:example.org")
+        )
+    );
+}
+
+#[test]
+fn linkify_markdown_ignores_escaped_parts() {
+    // Inline code.
+    let markdown = "Use `@alice:example.org`";
+    assert_eq!(linkify_markdown(markdown), markdown);
+
+    // Code block.
+    let markdown = "```\n@alice:example.org\n```";
+    assert_eq!(linkify_markdown(markdown), markdown);
+
+    // Link.
+    let markdown = "[@alice:example.org](https://example.org)";
+    assert_eq!(linkify_markdown(markdown), markdown);
+
+    // Image.
+    let markdown = "![@alice:example.org](https://example.org/image.png)";
+    assert_eq!(linkify_markdown(markdown), markdown);
+}
@@ -16,8 +16,12 @@ use ruma::{
 use sourceview::prelude::*;
 use tracing::{error, warn};
 
-use super::ComposerParser;
+use super::{
+    ComposerParser,
+    emoji_replacement::{self, SETTINGS_KEY_EMOJI_REPLACEMENT},
+};
 use crate::{
+    Application,
     components::{AvatarImageSafetySetting, Pill, PillSource},
     session::{Event, Member, Room, Timeline},
     spawn, spawn_tokio,
@@ -32,7 +36,11 @@ pub(super) const MENTION_START_TAG: &str = "<org.gnome.fractal.mention>";
 pub(super) const MENTION_END_TAG: &str = "</org.gnome.fractal.mention>";
 
 mod imp {
-    use std::{cell::RefCell, marker::PhantomData, sync::LazyLock};
+    use std::{
+        cell::{Cell, OnceCell, RefCell},
+        marker::PhantomData,
+        sync::LazyLock,
+    };
 
     use futures_util::lock::Mutex;
     use glib::subclass::Signal;
@@ -48,6 +56,13 @@ mod imp {
         /// The buffer of this state.
         #[property(get)]
         buffer: sourceview::Buffer,
+        /// The adapter to check the spelling of the buffer.
+        spelling_adapter: OnceCell<spelling::TextBufferAdapter>,
+        /// Whether the last change in the buffer was the insertion of a single
+        /// character.
+        inserted_single_char: Cell<bool>,
+        /// Whether we are replacing text with an emoji.
+        is_replacing_emoji: Cell<bool>,
         /// The relation of this state.
         related_to: RefCell<Option<RelationInfo>>,
         /// Whether this state has a relation.
@@ -91,6 +106,16 @@ mod imp {
             let md_lang = sourceview::LanguageManager::default().language("markdown");
             self.buffer.set_language(md_lang.as_ref());
 
+            // Spell checking. The markdown language definition makes sure that code is not
+            // checked.
+            let spelling_adapter = crate::utils::spell_check::set_up_buffer(
+                &self.buffer,
+                &Application::default().settings(),
+            );
+            self.spelling_adapter
+                .set(spelling_adapter)
+                .expect("spelling adapter is uninitialized");
+
             self.buffer.connect_changed(clone!(
                 #[weak(rename_to = imp)]
                 self,
@@ -99,10 +124,85 @@ mod imp {
                     imp.trigger_draft_saving();
                 }
             ));
+
+            // Emoji replacement, only when typing.
+            self.buffer.connect_insert_text(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, text| {
+                    imp.inserted_single_char.set(text.chars().count() == 1);
+                }
+            ));
+            self.buffer.connect_delete_range(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _| {
+                    imp.inserted_single_char.set(false);
+                }
+            ));
+            self.buffer.connect_end_user_action(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.replace_emoji_before_cursor();
+                }
+            ));
         }
     }
 
     impl ComposerState {
+        /// Replace the emoticon or shortcode that was just typed before the
+        /// cursor with an emoji, if any.
+        ///
+        /// The replacement is a separate user action, so it can be undone to
+        /// get back the typed text.
+        fn replace_emoji_before_cursor(&self) {
+            if !self.inserted_single_char.replace(false)
+                || self.is_replacing_emoji.get()
+                || !Application::default()
+                    .settings()
+                    .boolean(SETTINGS_KEY_EMOJI_REPLACEMENT)
+            {
+                return;
+            }
+
+            let buffer = &self.buffer;
+            let cursor = buffer.iter_at_mark(&buffer.get_insert());
+            let mut line_start = cursor;
+            line_start.set_line_offset(0);
+            // Use a slice to keep the offsets of the pills.
+            let text = buffer.slice(&line_start, &cursor, true);
+
+            let Some((range, emoji)) = emoji_replacement::find_replacement(&text) else {
+                return;
+            };
+
+            let char_count = |text: &str| i32::try_from(text.chars().count()).unwrap_or(i32::MAX);
+            let start_offset = line_start
+                .offset()
+                .saturating_add(char_count(&text[..range.start]));
+            let end_offset = start_offset.saturating_add(char_count(&text[range]));
+            let mut start = buffer.iter_at_offset(start_offset);
+            let mut end = buffer.iter_at_offset(end_offset);
+
+            // Do not replace text in code. The markdown language definition uses the same
+            // class as for spell checking.
+            buffer.ensure_highlight(&start, &end);
+            if buffer.iter_has_context_class(&start, "no-spell-check") {
+                return;
+            }
+
+            self.is_replacing_emoji.set(true);
+            buffer.begin_user_action();
+
+            buffer.delete(&mut start, &mut end);
+            buffer.insert(&mut start, emoji);
+
+            buffer.end_user_action();
+            self.is_replacing_emoji.set(false);
+            self.inserted_single_char.set(false);
+        }
+
         /// Attach this state to the given view.
         pub(super) fn attach_to_view(&self, view: Option<&sourceview::View>) {
             self.view.set(view);
@@ -110,6 +210,12 @@ mod imp {
             if let Some(view) = view {
                 view.set_buffer(Some(&self.buffer));
 
+                // Add the suggestions to fix misspelled words to the context menu.
+                if let Some(spelling_adapter) = self.spelling_adapter.get() {
+                    view.set_extra_menu(Some(&spelling_adapter.menu_model()));
+                    view.insert_action_group("spelling", Some(spelling_adapter));
+                }
+
                 self.update_widgets();
 
                 for (widget, anchor) in &*self.widgets.borrow() {
@@ -278,7 +384,7 @@ mod imp {
         }
 
         /// Restore the state from the given draft.
-        async fn restore_from_draft(&self, timeline: &Timeline, draft: ComposerDraft) {
+        pub(super) async fn restore_from_draft(&self, timeline: &Timeline, draft: ComposerDraft) {
             let room = timeline.room();
 
             // Restore the relation.
@@ -289,9 +395,43 @@ mod imp {
             self.buffer.set_text("");
             self.widgets.borrow_mut().clear();
 
-            // Fill the buffer while inserting mentions.
-            let text = &draft.plain_text;
             let mut end_iter = self.buffer.end_iter();
+            self.insert_draft_text(&room, &draft.plain_text, &mut end_iter);
+
+            self.saved_draft.replace(Some(draft));
+        }
+
+        /// Restore the given draft of a message that was not sent, while
+        /// keeping the current content of the composer.
+        ///
+        /// The text of the draft is inserted in front of the current content,
+        /// and its relation is only restored if the composer has none.
+        pub(super) async fn restore_unsent_draft(&self, timeline: &Timeline, draft: ComposerDraft) {
+            let (start_iter, end_iter) = self.buffer.bounds();
+            if start_iter == end_iter && self.related_to.borrow().is_none() {
+                // The composer is empty, restore the draft as is.
+                self.restore_from_draft(timeline, draft).await;
+                return;
+            }
+
+            let room = timeline.room();
+
+            if self.related_to.borrow().is_none() {
+                self.restore_related_to_from_draft(&room, draft.draft_type.clone())
+                    .await;
+            }
+
+            let mut start_iter = self.buffer.start_iter();
+            self.insert_draft_text(&room, &draft.plain_text, &mut start_iter);
+
+            if !start_iter.is_end() {
+                self.buffer.insert(&mut start_iter, "\n");
+            }
+        }
+
+        /// Insert the given serialized text of a draft at the given iter,
+        /// while inserting mentions.
+        fn insert_draft_text(&self, room: &Room, text: &str, end_iter: &mut gtk::TextIter) {
             let mut pos = 0;
 
             while let Some(rel_start) = text[pos..].find(MENTION_START_TAG) {
@@ -306,18 +446,18 @@ mod imp {
                 let content_end = content_start + rel_content_end;
 
                 if start != pos {
-                    self.buffer.insert(&mut end_iter, &text[pos..start]);
+                    self.buffer.insert(end_iter, &text[pos..start]);
                 }
 
-                match DraftMention::new(&room, &text[content_start..content_end]) {
+                match DraftMention::new(room, &text[content_start..content_end]) {
                     DraftMention::Source(source) => {
                         // We do not need to watch safety settings for mentions, rooms will be
                         // watched automatically.
                         let pill = Pill::new(&source, AvatarImageSafetySetting::None, None);
-                        self.add_widget(pill.upcast(), &mut end_iter);
+                        self.add_widget(pill.upcast(), end_iter);
                     }
                     DraftMention::Text(s) => {
-                        self.buffer.insert(&mut end_iter, s);
+                        self.buffer.insert(end_iter, s);
                     }
                 }
 
@@ -325,10 +465,8 @@ mod imp {
             }
 
             if pos != text.len() {
-                self.buffer.insert(&mut end_iter, &text[pos..]);
+                self.buffer.insert(end_iter, &text[pos..]);
             }
-
-            self.saved_draft.replace(Some(draft));
         }
 
         /// Restore the relation from the given draft content.
@@ -468,6 +606,12 @@ impl ComposerState {
         obj
     }
 
+    /// Restore the given draft of a message that was not sent, while keeping
+    /// the current content of this state.
+    pub(crate) async fn restore_unsent_draft(&self, timeline: &Timeline, draft: ComposerDraft) {
+        self.imp().restore_unsent_draft(timeline, draft).await;
+    }
+
     /// Attach this state to the given view.
     pub(crate) fn attach_to_view(&self, view: Option<&sourceview::View>) {
         self.imp().attach_to_view(view);
@@ -0,0 +1,192 @@
+//! Replacement of emoticons and shortcodes with emoji in the composer.
+
+use std::ops::Range;
+
+#[cfg(test)]
+mod tests;
+
+/// The key of the setting for whether emoticons and shortcodes are replaced
+/// with emoji while typing.
+pub(super) const SETTINGS_KEY_EMOJI_REPLACEMENT: &str = "emoji-replacement";
+
+/// The ASCII emoticons that are replaced, with their emoji.
+const EMOTICONS: &[(&str, &str)] = &[
+    (":)", "😄"),
+    (":-)", "😄"),
+    (":D", "😁"),
+    (":-D", "😁"),
+    ("xD", "😆"),
+    ("XD", "😆"),
+    (";)", "😉"),
+    (";-)", "😉"),
+    (":(", "🙁"),
+    (":-(", "🙁"),
+    (":'(", "😢"),
+    (":P", "😛"),
+    (":-P", "😛"),
+    (":p", "😛"),
+    (":-p", "😛"),
+    (":O", "😮"),
+    (":-O", "😮"),
+    (":o", "😮"),
+    (":-o", "😮"),
+    (":|", "😐"),
+    (":-|", "😐"),
+    (":/", "😕"),
+    (":-/", "😕"),
+    (":*", "😘"),
+    (":-*", "😘"),
+    ("B)", "😎"),
+    ("<3", "❤️"),
+    ("</3", "💔"),
+];
+
+/// The shortcodes that are replaced, without the colons, with their emoji.
+///
+/// These are the names used by most Matrix clients for the most common emoji.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("angry", "😠"),
+    ("astonished", "😲"),
+    ("beer", "🍺"),
+    ("blush", "😊"),
+    ("broken_heart", "💔"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("cake", "🍰"),
+    ("cat", "🐱"),
+    ("clap", "👏"),
+    ("clown_face", "🤡"),
+    ("coffee", "☕"),
+    ("confused", "😕"),
+    ("crab", "🦀"),
+    ("cry", "😢"),
+    ("dog", "🐶"),
+    ("exclamation", "❗"),
+    ("expressionless", "😑"),
+    ("eyes", "👀"),
+    ("facepalm", "🤦"),
+    ("fire", "🔥"),
+    ("flushed", "😳"),
+    ("frowning_face", "☹️"),
+    ("ghost", "👻"),
+    ("grimacing", "😬"),
+    ("grin", "😁"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("joy", "😂"),
+    ("kissing_heart", "😘"),
+    ("laughing", "😆"),
+    ("mask", "😷"),
+    ("muscle", "💪"),
+    ("neutral_face", "😐"),
+    ("ok_hand", "👌"),
+    ("open_mouth", "😮"),
+    ("pensive", "😔"),
+    ("pizza", "🍕"),
+    ("pray", "🙏"),
+    ("question", "❓"),
+    ("rage", "😡"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("relieved", "😌"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("roll_eyes", "🙄"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("sleepy", "😪"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("stuck_out_tongue", "😛"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("unamused", "😒"),
+    ("upside_down_face", "🙃"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("worried", "😟"),
+    ("x", "❌"),
+    ("yum", "😋"),
+    ("zap", "⚡"),
+];
+
+/// Find an emoticon or a shortcode to replace at the end of the given text.
+///
+/// Emoticons are replaced when they are followed by a whitespace, and
+/// shortcodes when their closing colon is typed.
+///
+/// Returns the range to replace in the text, and the emoji to replace it with.
+pub(super) fn find_replacement(text: &str) -> Option<(Range<usize>, &'static str)> {
+    let last_char = text.chars().next_back()?;
+
+    if last_char == ':' {
+        find_shortcode(text)
+    } else if last_char.is_whitespace() {
+        find_emoticon(&text[..text.len() - last_char.len_utf8()])
+    } else {
+        None
+    }
+}
+
+/// Find a shortcode, including its colons, at the end of the given text.
+fn find_shortcode(text: &str) -> Option<(Range<usize>, &'static str)> {
+    // Remove the closing colon.
+    let before_closing = &text[..text.len() - 1];
+    let start = before_closing.rfind(|c: char| !is_shortcode_char(c))?;
+
+    if !before_closing[start..].starts_with(':') {
+        return None;
+    }
+
+    let shortcode = &before_closing[start + 1..];
+    if shortcode.is_empty() {
+        // Leave `::` alone.
+        return None;
+    }
+
+    // The opening colon must be at the start of a word, and not escaped by another colon.
+    if text[..start]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == ':')
+    {
+        return None;
+    }
+
+    let (_, emoji) = SHORTCODES.iter().find(|(code, _)| *code == shortcode)?;
+    Some((start..text.len(), emoji))
+}
+
+/// Find an emoticon in the last word of the given text.
+fn find_emoticon(text: &str) -> Option<(Range<usize>, &'static str)> {
+    let start = text
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let word = &text[start..];
+
+    let (_, emoji) = EMOTICONS.iter().find(|(emoticon, _)| *emoticon == word)?;
+    Some((start..text.len(), emoji))
+}
+
+/// Whether the given `char` can be part of a shortcode.
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '+' | '-')
+}
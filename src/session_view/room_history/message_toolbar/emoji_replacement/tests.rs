@@ -0,0 +1,53 @@
+use super::{find_emoticon, find_replacement, find_shortcode};
+
+#[test]
+fn replace_shortcode() {
+    assert_eq!(find_shortcode(":smile:"), Some((0..7, "😄")));
+    assert_eq!(find_shortcode("hello :+1:"), Some((6..10, "👍")));
+    assert_eq!(find_shortcode("é :tada:"), Some((3..9, "🎉")));
+
+    // Unknown shortcode.
+    assert_eq!(find_shortcode(":unknown_emoji:"), None);
+    // Empty shortcode.
+    assert_eq!(find_shortcode("::"), None);
+    // Not at the start of a word.
+    assert_eq!(find_shortcode("foo:smile:"), None);
+    // Escaped by another colon.
+    assert_eq!(find_shortcode("::smile:"), None);
+    // Uppercase characters are not part of shortcodes.
+    assert_eq!(find_shortcode(":Smile:"), None);
+    // No opening colon.
+    assert_eq!(find_shortcode("smile:"), None);
+}
+
+#[test]
+fn replace_emoticon() {
+    assert_eq!(find_emoticon(":)"), Some((0..2, "😄")));
+    assert_eq!(find_emoticon("hello <3"), Some((6..8, "❤️")));
+    assert_eq!(find_emoticon("ça va ;-)"), Some((7..10, "😉")));
+    assert_eq!(find_emoticon("line\n:D"), Some((5..7, "😁")));
+
+    // Part of a word.
+    assert_eq!(find_emoticon("foo:)"), None);
+    // Unknown emoticon.
+    assert_eq!(find_emoticon(":]"), None);
+    // Empty text.
+    assert_eq!(find_emoticon(""), None);
+}
+
+#[test]
+fn find_replacement_trigger() {
+    // Shortcodes are replaced when the closing colon is typed.
+    assert_eq!(find_replacement("I :heart:"), Some((2..9, "❤️")));
+    // Emoticons are replaced when followed by a whitespace.
+    assert_eq!(find_replacement("hi :) "), Some((3..5, "😄")));
+    assert_eq!(find_replacement("hi :)\u{a0}"), Some((3..5, "😄")));
+
+    // Emoticons are not replaced while still typing.
+    assert_eq!(find_replacement("hi :)"), None);
+    // Nothing to replace.
+    assert_eq!(find_replacement("hello "), None);
+    assert_eq!(find_replacement(""), None);
+    // URLs are not mistaken for emoticons.
+    assert_eq!(find_replacement("https://example.org "), None);
+}
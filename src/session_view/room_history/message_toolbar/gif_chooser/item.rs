@@ -0,0 +1,153 @@
+use gtk::{gdk, glib, glib::clone, prelude::*, subclass::prelude::*};
+use tracing::warn;
+
+use super::GifResult;
+use crate::{
+    Application,
+    session::Session,
+    spawn,
+    utils::media::{FrameDimensions, image::Image},
+};
+
+/// The size of the preview of a GIF.
+const PREVIEW_SIZE: u32 = 120;
+
+mod imp {
+    use std::cell::{OnceCell, RefCell};
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session_view/room_history/message_toolbar/gif_chooser/item.ui"
+    )]
+    pub struct GifChooserItem {
+        #[template_child]
+        picture: TemplateChild<gtk::Picture>,
+        /// The GIF displayed by this item.
+        pub(super) result: OnceCell<GifResult>,
+        /// The first frame of the GIF.
+        static_paintable: RefCell<Option<gdk::Paintable>>,
+        /// The animated GIF.
+        animated_paintable: RefCell<Option<gdk::Paintable>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GifChooserItem {
+        const NAME: &'static str = "GifChooserItem";
+        type Type = super::GifChooserItem;
+        type ParentType = gtk::FlowBoxChild;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for GifChooserItem {}
+    impl WidgetImpl for GifChooserItem {}
+    impl FlowBoxChildImpl for GifChooserItem {}
+
+    #[gtk::template_callbacks]
+    impl GifChooserItem {
+        /// Set the GIF displayed by this item and load its preview.
+        pub(super) fn set_result(&self, session: Session, result: GifResult) {
+            if let Some(title) = &result.title {
+                self.obj().set_tooltip_text(Some(title));
+                self.picture.set_alternative_text(Some(title));
+            }
+
+            let result = self.result.get_or_init(|| result).clone();
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.load_preview(&session, result).await;
+                }
+            ));
+        }
+
+        /// Download and decode the preview of the GIF.
+        async fn load_preview(&self, session: &Session, result: GifResult) {
+            let Ok(bytes) = result.download_preview(session).await else {
+                return;
+            };
+
+            let dimensions = FrameDimensions {
+                width: PREVIEW_SIZE,
+                height: PREVIEW_SIZE,
+            }
+            .scale(self.obj().scale_factor().try_into().unwrap_or(1));
+
+            let image = match Image::from_bytes(bytes, Some(dimensions)).await {
+                Ok(image) => image,
+                Err(error) => {
+                    warn!("Could not decode GIF preview: {error}");
+                    return;
+                }
+            };
+
+            self.static_paintable
+                .replace(Some(image.clone().into_static_paintable()));
+            self.animated_paintable.replace(Some(image.into()));
+
+            self.update_paintable(false);
+        }
+
+        /// Update the paintable of the preview.
+        ///
+        /// The GIF is only animated while it is hovered if the user asked to
+        /// reduce motion.
+        fn update_paintable(&self, is_hovered: bool) {
+            let animate = is_hovered || !Application::default().system_settings().reduce_motion();
+
+            let paintable = if animate {
+                self.animated_paintable.borrow().clone()
+            } else {
+                self.static_paintable.borrow().clone()
+            };
+
+            self.picture.set_paintable(paintable.as_ref());
+        }
+
+        /// Handle when the pointer enters the item.
+        #[template_callback]
+        fn pointer_entered(&self) {
+            self.update_paintable(true);
+        }
+
+        /// Handle when the pointer leaves the item.
+        #[template_callback]
+        fn pointer_left(&self) {
+            self.update_paintable(false);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// An item in the GIF chooser.
+    pub struct GifChooserItem(ObjectSubclass<imp::GifChooserItem>)
+        @extends gtk::Widget, gtk::FlowBoxChild,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl GifChooserItem {
+    /// Construct a new `GifChooserItem` for the given GIF.
+    pub fn new(session: Session, result: GifResult) -> Self {
+        let obj = glib::Object::new::<Self>();
+        obj.imp().set_result(session, result);
+        obj
+    }
+
+    /// The GIF displayed by this item.
+    pub(crate) fn result(&self) -> Option<GifResult> {
+        self.imp().result.get().cloned()
+    }
+}
@@ -0,0 +1,206 @@
+use gtk::{glib, glib::closure_local, prelude::*, subclass::prelude::*};
+
+mod item;
+mod provider;
+
+use self::item::GifChooserItem;
+pub(crate) use self::provider::{GifProvider, GifResult};
+use crate::session::Session;
+
+/// A page of the [`GifChooser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+enum GifChooserPage {
+    /// The page inviting the user to search.
+    Initial,
+    /// The loading page.
+    Loading,
+    /// The page with the results of the search.
+    Results,
+    /// The page when no results were found.
+    Empty,
+    /// The error page.
+    Error,
+}
+
+mod imp {
+    use std::{cell::Cell, sync::LazyLock};
+
+    use glib::subclass::{InitializingObject, Signal};
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session_view/room_history/message_toolbar/gif_chooser/mod.ui"
+    )]
+    #[properties(wrapper_type = super::GifChooser)]
+    pub struct GifChooser {
+        #[template_child]
+        search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        flow_box: TemplateChild<gtk::FlowBox>,
+        /// The current session.
+        #[property(get, set, nullable)]
+        session: glib::WeakRef<Session>,
+        /// The number of the latest search request.
+        ///
+        /// Used to ignore the responses of outdated requests.
+        search_counter: Cell<u64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GifChooser {
+        const NAME: &'static str = "GifChooser";
+        type Type = super::GifChooser;
+        type ParentType = gtk::Popover;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for GifChooser {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> = LazyLock::new(|| {
+                vec![
+                    Signal::builder("gif-selected")
+                        .param_types([GifResult::static_type()])
+                        .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+    }
+
+    impl WidgetImpl for GifChooser {}
+
+    impl PopoverImpl for GifChooser {
+        fn closed(&self) {
+            self.parent_closed();
+
+            // Reset the search.
+            self.search_entry.set_text("");
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl GifChooser {
+        /// Set the visible page of the chooser.
+        fn set_visible_page(&self, page: GifChooserPage) {
+            self.stack.set_visible_child_name(page.as_ref());
+        }
+
+        /// Remove all the results.
+        fn clear_results(&self) {
+            self.flow_box.remove_all();
+        }
+
+        /// Search for GIFs matching the current term.
+        #[template_callback]
+        async fn search(&self) {
+            let counter = self.search_counter.get() + 1;
+            self.search_counter.set(counter);
+
+            self.clear_results();
+
+            let term = self.search_entry.text().trim().to_owned();
+            if term.is_empty() {
+                self.set_visible_page(GifChooserPage::Initial);
+                return;
+            }
+
+            // Load the provider every time in case the setting changed.
+            let Some(provider) = GifProvider::from_settings() else {
+                self.set_visible_page(GifChooserPage::Error);
+                return;
+            };
+
+            let Some(session) = self.session.upgrade() else {
+                return;
+            };
+
+            self.set_visible_page(GifChooserPage::Loading);
+
+            let result = provider.search(&session, &term).await;
+
+            if self.search_counter.get() != counter {
+                // This search is outdated.
+                return;
+            }
+
+            match result {
+                Ok(results) if results.is_empty() => {
+                    self.set_visible_page(GifChooserPage::Empty);
+                }
+                Ok(results) => {
+                    for result in results {
+                        let item = GifChooserItem::new(session.clone(), result);
+                        self.flow_box.append(&item);
+                    }
+
+                    self.set_visible_page(GifChooserPage::Results);
+                }
+                Err(()) => {
+                    self.set_visible_page(GifChooserPage::Error);
+                }
+            }
+        }
+
+        /// Handle when a GIF was activated.
+        #[template_callback]
+        fn item_activated(&self, child: &gtk::FlowBoxChild) {
+            let Some(result) = child
+                .downcast_ref::<GifChooserItem>()
+                .and_then(GifChooserItem::result)
+            else {
+                return;
+            };
+
+            let obj = self.obj();
+            obj.popdown();
+            obj.emit_by_name::<()>("gif-selected", &[&result]);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A popover to search for GIFs and select one.
+    pub struct GifChooser(ObjectSubclass<imp::GifChooser>)
+        @extends gtk::Widget, gtk::Popover,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::ShortcutManager;
+}
+
+impl GifChooser {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Connect to the signal emitted when a GIF was selected.
+    pub fn connect_gif_selected<F: Fn(&Self, GifResult) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "gif-selected",
+            true,
+            closure_local!(move |obj: Self, result: GifResult| {
+                f(&obj, result);
+            }),
+        )
+    }
+}
+
+impl Default for GifChooser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
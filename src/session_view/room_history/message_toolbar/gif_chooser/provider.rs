@@ -0,0 +1,249 @@
+use gtk::{gio, glib, prelude::*};
+use matrix_sdk::{
+    media::{MediaFormat, MediaRequestParameters},
+    reqwest,
+};
+use ruma::{OwnedMxcUri, events::room::MediaSource};
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::error;
+
+use crate::{Application, session::Session, spawn_tokio, utils::media::MEDIA_CACHE_LOCK};
+
+/// The placeholder for the search term in the URL of the provider.
+const QUERY_PLACEHOLDER: &str = "{query}";
+/// The maximum size of a downloaded file, in bytes.
+const MAX_DOWNLOAD_SIZE: usize = 20 * 1024 * 1024;
+
+/// A provider to search for GIFs.
+///
+/// The provider is configured with the `gif-search-url` setting, which must
+/// contain the `{query}` placeholder. The endpoint must return a JSON object
+/// with a `results` array, where each result has at least a `url` field, that
+/// can be an MXC URI or an HTTP(S) URL, and optionally a `mimetype` field.
+#[derive(Debug, Clone)]
+pub(crate) struct GifProvider {
+    /// The URL template of the search endpoint.
+    url_template: String,
+}
+
+impl GifProvider {
+    /// Get the provider configured in the settings, if any.
+    pub(crate) fn from_settings() -> Option<Self> {
+        let url_template = Application::default()
+            .settings()
+            .string("gif-search-url")
+            .trim()
+            .to_owned();
+
+        url_template
+            .contains(QUERY_PLACEHOLDER)
+            .then_some(Self { url_template })
+    }
+
+    /// Search for GIFs matching the given term.
+    pub(super) async fn search(&self, session: &Session, term: &str) -> Result<Vec<GifResult>, ()> {
+        let escaped_term = glib::Uri::escape_string(term, None, false);
+        let url = self.url_template.replace(QUERY_PLACEHOLDER, &escaped_term);
+
+        let http_client = session.http_client();
+        let handle = spawn_tokio!(async move {
+            http_client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await
+        });
+
+        let bytes = match handle.await.expect("task was not aborted") {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                error!("Could not search for GIFs: {error}");
+                return Err(());
+            }
+        };
+
+        match serde_json::from_slice::<GifSearchResponse>(&bytes) {
+            Ok(response) => Ok(response.results),
+            Err(error) => {
+                error!("Could not deserialize GIF search results: {error}");
+                Err(())
+            }
+        }
+    }
+}
+
+/// The response of the search endpoint of a [`GifProvider`].
+#[derive(Debug, Deserialize)]
+struct GifSearchResponse {
+    /// The results of the search.
+    #[serde(default)]
+    results: Vec<GifResult>,
+}
+
+/// A result of a GIF search.
+#[derive(Debug, Clone, Deserialize, glib::Boxed)]
+#[boxed_type(name = "GifResult")]
+pub(crate) struct GifResult {
+    /// The URL of the GIF.
+    pub(crate) url: String,
+    /// The URL of a smaller version of the GIF, to use as a preview.
+    #[serde(default)]
+    pub(crate) preview_url: Option<String>,
+    /// The title of the GIF.
+    #[serde(default)]
+    pub(crate) title: Option<String>,
+    /// The width of the GIF.
+    #[serde(default)]
+    pub(crate) width: Option<u32>,
+    /// The height of the GIF.
+    #[serde(default)]
+    pub(crate) height: Option<u32>,
+    /// The size of the GIF, in bytes.
+    #[serde(default)]
+    pub(crate) size: Option<u32>,
+    /// The MIME type of the GIF.
+    ///
+    /// Providers often return WebP or MP4 files instead of actual GIFs.
+    #[serde(default)]
+    pub(crate) mimetype: Option<String>,
+}
+
+impl GifResult {
+    /// The MXC URI of the GIF, if the provider gives one.
+    pub(crate) fn mxc_uri(&self) -> Option<OwnedMxcUri> {
+        let uri = OwnedMxcUri::from(self.url.as_str());
+        uri.is_valid().then_some(uri)
+    }
+
+    /// The MIME type of this GIF, as given by the provider.
+    ///
+    /// Defaults to `image/gif`.
+    pub(crate) fn mime(&self) -> mime::Mime {
+        self.mimetype
+            .as_deref()
+            .and_then(|mimetype| mimetype.parse().ok())
+            .unwrap_or(mime::IMAGE_GIF)
+    }
+
+    /// Download the preview of this GIF.
+    pub(super) async fn download_preview(&self, session: &Session) -> Result<Vec<u8>, ()> {
+        download(session, self.preview_url.as_deref().unwrap_or(&self.url))
+            .await
+            .map(|(bytes, _)| bytes)
+    }
+
+    /// Download this GIF.
+    ///
+    /// Returns the content of the file and its MIME type.
+    pub(crate) async fn download(&self, session: &Session) -> Result<(Vec<u8>, mime::Mime), ()> {
+        download(session, &self.url).await
+    }
+}
+
+/// Download the file at the given URL.
+///
+/// Supports MXC URIs and HTTP(S) URLs. Files bigger than
+/// [`MAX_DOWNLOAD_SIZE`] are refused.
+///
+/// Returns the content of the file and its MIME type.
+async fn download(session: &Session, url: &str) -> Result<(Vec<u8>, mime::Mime), ()> {
+    let mxc_uri = OwnedMxcUri::from(url);
+
+    let handle = if mxc_uri.is_valid() {
+        let client = session.client();
+        spawn_tokio!(async move {
+            let request = MediaRequestParameters {
+                source: MediaSource::Plain(mxc_uri),
+                format: MediaFormat::File,
+            };
+            let _guard = MEDIA_CACHE_LOCK.read().await;
+            let bytes = client
+                .media()
+                .get_media_content(&request, true)
+                .await
+                .map_err(|error| error.to_string())?;
+
+            if bytes.len() > MAX_DOWNLOAD_SIZE {
+                return Err(DownloadError::TooBig.to_string());
+            }
+
+            Ok((bytes, None))
+        })
+    } else {
+        let url = url.to_owned();
+        let http_client = session.http_client();
+        spawn_tokio!(async move {
+            download_http(http_client, url)
+                .await
+                .map_err(|error| error.to_string())
+        })
+    };
+
+    let (bytes, content_type) = handle
+        .await
+        .expect("task was not aborted")
+        .map_err(|error| {
+            error!("Could not download GIF: {error}");
+        })?;
+
+    // Trust the server, or guess the type from the content.
+    let mime = content_type
+        .or_else(|| {
+            let (content_type, _) = gio::content_type_guess(None::<&str>, &bytes);
+            gio::content_type_get_mime_type(&content_type).and_then(|mime| mime.parse().ok())
+        })
+        .unwrap_or(mime::IMAGE_GIF);
+
+    Ok((bytes, mime))
+}
+
+/// Download the file at the given HTTP(S) URL with the given client.
+///
+/// Returns the content of the file and its MIME type, if the server provided a
+/// specific one.
+async fn download_http(
+    http_client: reqwest::Client,
+    url: String,
+) -> Result<(Vec<u8>, Option<mime::Mime>), DownloadError> {
+    let mut response = http_client.get(url).send().await?.error_for_status()?;
+
+    if response.content_length().is_some_and(|length| {
+        usize::try_from(length).map_or(true, |length| length > MAX_DOWNLOAD_SIZE)
+    }) {
+        return Err(DownloadError::TooBig);
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        // Ignore the generic type.
+        .filter(|mime| *mime != mime::APPLICATION_OCTET_STREAM);
+
+    // The Content-Length header might be missing or wrong, so check the size of the
+    // body while we receive it.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_DOWNLOAD_SIZE {
+            return Err(DownloadError::TooBig);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok((bytes, content_type))
+}
+
+/// An error when downloading a file.
+#[derive(Debug, Error)]
+enum DownloadError {
+    /// The request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The file is too big.
+    #[error("The file is bigger than {MAX_DOWNLOAD_SIZE} bytes")]
+    TooBig,
+}
@@ -0,0 +1,498 @@
+//! Conversion of pasted HTML to the representation of the composer.
+
+use ruma::html::{
+    Children, Html, NodeData, NodeRef,
+    matrix::{AnchorUri, MatrixElement},
+};
+
+use crate::{components::Pill, prelude::*, session::Room, utils::matrix::MatrixIdUri};
+
+/// The maximum size of pasted HTML that is converted, in bytes.
+///
+/// Bigger documents are pasted as plain text.
+pub(super) const MAX_PASTED_HTML_SIZE: usize = 512 * 1024;
+
+/// The HTML elements whose content is never pasted.
+const IGNORED_ELEMENTS: &[&str] = &["head", "meta", "script", "style", "template", "title"];
+
+/// The characters that are escaped in text, to avoid them being interpreted as
+/// Markdown syntax.
+const MARKDOWN_SPECIAL_CHARS: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '~'];
+
+/// The characters that are escaped at the start of a line, to avoid them being
+/// interpreted as Markdown block syntax.
+const MARKDOWN_BLOCK_CHARS: &[char] = &['#', '>', '-', '+'];
+
+/// A chunk of content converted from pasted HTML.
+#[derive(Debug)]
+pub(super) enum PastedChunk {
+    /// Text using the Markdown syntax.
+    Text(String),
+    /// A mention of a user or a room.
+    Mention(Pill),
+}
+
+/// Convert the given HTML to Markdown text and mentions in the given room.
+pub(super) fn html_to_pasted_chunks(html: &str, room: &Room) -> Vec<PastedChunk> {
+    let html = Html::parse(html);
+
+    let mut builder = MarkdownBuilder::new(room);
+    builder.append_nodes(html.children());
+    builder.build()
+}
+
+/// A break between blocks of content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BlockBreak {
+    /// No break.
+    None,
+    /// A new line.
+    Line,
+    /// A new paragraph.
+    Paragraph,
+}
+
+/// Helper type to construct Markdown from HTML nodes.
+#[derive(Debug)]
+struct MarkdownBuilder<'a> {
+    /// The room where the content is pasted.
+    room: &'a Room,
+    /// The chunks that were constructed.
+    chunks: Vec<PastedChunk>,
+    /// The text of the current chunk.
+    text: String,
+    /// Whether any content was added.
+    has_content: bool,
+    /// The break to add before the next content.
+    pending_break: BlockBreak,
+    /// The prefix of lines in the current block, for quotes and list items.
+    line_prefix: String,
+    /// The prefix of the last line that was started.
+    last_line_prefix: String,
+    /// Whether we are at the start of a line.
+    at_line_start: bool,
+    /// Whether we are right after the marker of a list item.
+    at_item_start: bool,
+    /// The depth of the current list.
+    list_depth: usize,
+}
+
+impl<'a> MarkdownBuilder<'a> {
+    /// Construct a new `MarkdownBuilder` for the given room.
+    fn new(room: &'a Room) -> Self {
+        Self {
+            room,
+            chunks: Vec::new(),
+            text: String::new(),
+            has_content: false,
+            pending_break: BlockBreak::None,
+            line_prefix: String::new(),
+            last_line_prefix: String::new(),
+            at_line_start: true,
+            at_item_start: false,
+            list_depth: 0,
+        }
+    }
+
+    /// Export the chunks that were constructed.
+    fn build(mut self) -> Vec<PastedChunk> {
+        self.text.truncate_end_whitespaces();
+
+        if !self.text.is_empty() {
+            self.chunks.push(PastedChunk::Text(self.text));
+        }
+
+        self.chunks
+    }
+
+    /// Request the given break before the next content.
+    fn start_block(&mut self, block_break: BlockBreak) {
+        // The first block of a list item is on the same line as the marker.
+        if !self.at_item_start {
+            self.pending_break = self.pending_break.max(block_break);
+        }
+    }
+
+    /// Start a new line.
+    fn push_newline(&mut self) {
+        self.text.truncate_end_whitespaces();
+        self.text.push('\n');
+        self.at_line_start = true;
+    }
+
+    /// Add the pending break, if any.
+    fn flush_break(&mut self) {
+        let block_break = std::mem::replace(&mut self.pending_break, BlockBreak::None);
+
+        if !self.has_content {
+            return;
+        }
+
+        if block_break == BlockBreak::Paragraph {
+            self.push_newline();
+
+            // Keep the prefix of the enclosing blocks on the empty line.
+            let common_prefix_len = self
+                .last_line_prefix
+                .bytes()
+                .zip(self.line_prefix.bytes())
+                .take_while(|(last, current)| last == current)
+                .count();
+            self.text
+                .push_str(self.last_line_prefix[..common_prefix_len].trim_end());
+        }
+
+        if block_break != BlockBreak::None {
+            self.push_newline();
+        }
+    }
+
+    /// Prepare the text to receive new content.
+    fn prepare_content(&mut self) {
+        self.flush_break();
+
+        if self.at_line_start {
+            self.text.push_str(&self.line_prefix);
+            self.last_line_prefix.clone_from(&self.line_prefix);
+            self.at_line_start = false;
+        }
+
+        self.has_content = true;
+        self.at_item_start = false;
+    }
+
+    /// Append the given Markdown string.
+    fn push_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+
+        self.prepare_content();
+        self.text.push_str(s);
+    }
+
+    /// Append the given mention.
+    fn push_mention(&mut self, pill: Pill) {
+        self.prepare_content();
+
+        if !self.text.is_empty() {
+            self.chunks
+                .push(PastedChunk::Text(std::mem::take(&mut self.text)));
+        }
+
+        self.chunks.push(PastedChunk::Mention(pill));
+    }
+
+    /// The current position in the content.
+    fn position(&self) -> (usize, usize) {
+        (self.chunks.len(), self.text.len())
+    }
+
+    /// Append the given nodes.
+    fn append_nodes(&mut self, nodes: impl IntoIterator<Item = NodeRef>) {
+        for node in nodes {
+            self.append_node(&node);
+        }
+    }
+
+    /// Append the given node.
+    fn append_node(&mut self, node: &NodeRef) {
+        match node.data() {
+            NodeData::Element(data) => {
+                if IGNORED_ELEMENTS.contains(&&*data.name.local) {
+                    return;
+                }
+
+                self.append_element_node(node, data.to_matrix().element);
+            }
+            NodeData::Text(text) => {
+                self.append_text(text.borrow().as_ref());
+            }
+            _ => {}
+        }
+    }
+
+    /// Append the given element node.
+    fn append_element_node(&mut self, node: &NodeRef, element: MatrixElement) {
+        match element {
+            MatrixElement::B | MatrixElement::Strong => {
+                self.append_delimited_children("**", node.children());
+            }
+            MatrixElement::I | MatrixElement::Em => {
+                self.append_delimited_children("*", node.children());
+            }
+            MatrixElement::Del | MatrixElement::S => {
+                self.append_delimited_children("~~", node.children());
+            }
+            MatrixElement::Code(_) => {
+                self.append_inline_code(&node_text(node));
+            }
+            MatrixElement::A(anchor) => {
+                let Some(uri) = anchor.href else {
+                    self.append_nodes(node.children());
+                    return;
+                };
+
+                if let Some(pill) = MatrixIdUri::try_from(&uri)
+                    .ok()
+                    .and_then(|id| id.into_pill(self.room))
+                {
+                    self.push_mention(pill);
+                    return;
+                }
+
+                let uri = match uri {
+                    AnchorUri::Matrix(uri) => uri.to_string(),
+                    AnchorUri::MatrixTo(uri) => uri.to_string(),
+                    AnchorUri::Other(uri) => uri.to_string(),
+                    _ => {
+                        self.append_nodes(node.children());
+                        return;
+                    }
+                };
+
+                if node_text(node).trim() == uri {
+                    // There is no need for a label that is the same as the URI.
+                    self.push_str(&uri);
+                    return;
+                }
+
+                self.push_str("[");
+                let position = self.position();
+
+                self.append_nodes(node.children());
+
+                if self.position() == position {
+                    // Use the URI as the label.
+                    self.text.push_str(&uri);
+                }
+
+                self.text.push_str(&format!("]({uri})"));
+            }
+            MatrixElement::Br => {
+                if self.has_content {
+                    self.flush_break();
+                    self.push_newline();
+                }
+            }
+            MatrixElement::P => {
+                self.append_block(BlockBreak::Paragraph, node.children());
+            }
+            MatrixElement::Div(_)
+            | MatrixElement::Details
+            | MatrixElement::Summary
+            | MatrixElement::Table
+            | MatrixElement::Caption => {
+                self.append_block(BlockBreak::Line, node.children());
+            }
+            MatrixElement::Tr => {
+                self.start_block(BlockBreak::Line);
+
+                // Separate the cells on the same line.
+                let cells = node.children().filter(|child| child.as_element().is_some());
+                for (index, cell) in cells.enumerate() {
+                    if index > 0 {
+                        self.push_str(" | ");
+                    }
+
+                    self.append_node(&cell);
+                }
+
+                self.start_block(BlockBreak::Line);
+            }
+            MatrixElement::H(heading) => {
+                self.start_block(BlockBreak::Paragraph);
+                self.push_str(&format!("{} ", "#".repeat(heading.level.value().into())));
+                self.append_nodes(node.children());
+                self.start_block(BlockBreak::Paragraph);
+            }
+            MatrixElement::Blockquote => {
+                self.start_block(BlockBreak::Paragraph);
+
+                let prefix_len = self.line_prefix.len();
+                self.line_prefix.push_str("> ");
+                self.append_nodes(node.children());
+                self.line_prefix.truncate(prefix_len);
+
+                self.start_block(BlockBreak::Paragraph);
+            }
+            MatrixElement::Ul => {
+                self.append_list(node.children(), None);
+            }
+            MatrixElement::Ol(list) => {
+                self.append_list(node.children(), Some(list.start.unwrap_or(1)));
+            }
+            MatrixElement::Hr => {
+                self.start_block(BlockBreak::Paragraph);
+                self.push_str("---");
+                self.start_block(BlockBreak::Paragraph);
+            }
+            MatrixElement::Pre => {
+                self.start_block(BlockBreak::Paragraph);
+                self.push_str("```");
+
+                for line in node_text(node).trim_end_matches('\n').lines() {
+                    self.push_newline();
+                    // Do not escape the content of code blocks.
+                    self.prepare_content();
+                    self.text.push_str(line);
+                }
+
+                self.push_newline();
+                self.push_str("```");
+                self.start_block(BlockBreak::Paragraph);
+            }
+            _ => {
+                self.append_nodes(node.children());
+            }
+        }
+    }
+
+    /// Append the given text.
+    fn append_text(&mut self, text: &str) {
+        let trim_start = self.at_line_start
+            || self.pending_break != BlockBreak::None
+            || self.text.ends_with(char::is_whitespace);
+        let text = text.collapse_whitespaces(trim_start, false);
+
+        if text.is_empty() {
+            return;
+        }
+
+        let mut escaped = String::with_capacity(text.len());
+
+        for (index, c) in text.char_indices() {
+            let is_line_start =
+                index == 0 && (self.at_line_start || self.pending_break != BlockBreak::None);
+
+            if MARKDOWN_SPECIAL_CHARS.contains(&c)
+                || (is_line_start && MARKDOWN_BLOCK_CHARS.contains(&c))
+            {
+                escaped.push('\\');
+            }
+
+            escaped.push(c);
+        }
+
+        self.push_str(&escaped);
+    }
+
+    /// Append the given inline code.
+    fn append_inline_code(&mut self, code: &str) {
+        let code = code.collapse_whitespaces(false, false);
+
+        if code.is_empty() {
+            return;
+        }
+
+        if code.contains('`') {
+            self.push_str(&format!("`` {code} ``"));
+        } else {
+            self.push_str(&format!("`{code}`"));
+        }
+    }
+
+    /// Append the given children surrounded by the given delimiter.
+    ///
+    /// Nothing is added if the children are empty.
+    fn append_delimited_children(&mut self, delimiter: &str, children: Children) {
+        self.push_str(delimiter);
+        let position = self.position();
+
+        self.append_nodes(children);
+
+        if self.position() == position {
+            // Remove the opening delimiter.
+            self.text.truncate(self.text.len() - delimiter.len());
+            return;
+        }
+
+        // Whitespace is not allowed before the closing delimiter.
+        let trimmed_len = self.text.trim_end().len();
+        let whitespace = self.text.split_off(trimmed_len);
+
+        self.text.push_str(delimiter);
+        self.text.push_str(&whitespace);
+    }
+
+    /// Append the given children as a block.
+    fn append_block(&mut self, block_break: BlockBreak, children: Children) {
+        self.start_block(block_break);
+        self.append_nodes(children);
+        self.start_block(block_break);
+    }
+
+    /// Append the given list items.
+    ///
+    /// If `start` is set, this is an ordered list starting at that number.
+    fn append_list(&mut self, children: Children, mut start: Option<i64>) {
+        let block_break = if self.list_depth == 0 {
+            BlockBreak::Paragraph
+        } else {
+            BlockBreak::Line
+        };
+        self.start_block(block_break);
+        self.list_depth += 1;
+
+        for child in children {
+            let is_item = child
+                .as_element()
+                .is_some_and(|element| matches!(element.to_matrix().element, MatrixElement::Li));
+
+            if !is_item {
+                self.append_node(&child);
+                continue;
+            }
+
+            let marker = if let Some(number) = &mut start {
+                let marker = format!("{number}. ");
+                *number += 1;
+                marker
+            } else {
+                "- ".to_owned()
+            };
+
+            self.start_block(BlockBreak::Line);
+            self.push_str(&marker);
+            self.at_item_start = true;
+
+            // The content of the item is aligned with the marker.
+            let prefix_len = self.line_prefix.len();
+            self.line_prefix.push_str(&" ".repeat(marker.len()));
+            self.append_nodes(child.children());
+            self.line_prefix.truncate(prefix_len);
+
+            self.at_item_start = false;
+        }
+
+        self.list_depth -= 1;
+        self.start_block(block_break);
+    }
+}
+
+/// Get the text content of the given node and its descendants.
+fn node_text(node: &NodeRef) -> String {
+    let mut text = String::new();
+    append_node_text(&mut text, node);
+    text
+}
+
+/// Append the text content of the given node and its descendants to the given
+/// string.
+fn append_node_text(text: &mut String, node: &NodeRef) {
+    match node.data() {
+        NodeData::Text(content) => {
+            text.push_str(content.borrow().as_ref());
+        }
+        NodeData::Element(data) => {
+            if matches!(data.to_matrix().element, MatrixElement::Br) {
+                text.push('\n');
+            }
+
+            for child in node.children() {
+                append_node_text(text, &child);
+            }
+        }
+        _ => {}
+    }
+}
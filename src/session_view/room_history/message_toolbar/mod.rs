@@ -1,11 +1,12 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use adw::{prelude::*, subclass::prelude::*};
 use futures_util::{StreamExt, future, lock::Mutex, pin_mut};
 use gettextrs::{gettext, pgettext};
 use gtk::{gdk, gio, glib, glib::clone};
 use matrix_sdk::{
-    attachment::{AttachmentInfo, BaseFileInfo, Thumbnail},
+    ComposerDraft,
+    attachment::{AttachmentInfo, BaseFileInfo, BaseImageInfo, BaseVideoInfo, Thumbnail},
     room::edit::EditedContent,
 };
 use matrix_sdk_ui::timeline::{
@@ -16,7 +17,12 @@ use ruma::{
     events::{
         Mentions,
         room::{
-            message::{LocationMessageEventContent, MessageType, RoomMessageEventContent},
+            ImageInfo,
+            message::{
+                ImageMessageEventContent, LocationMessageEventContent, MessageType,
+                RoomMessageEventContent, RoomMessageEventContentWithoutRelation, VideoInfo,
+                VideoMessageEventContent,
+            },
             tombstone::RoomTombstoneEventContent,
         },
     },
@@ -27,11 +33,19 @@ mod attachment_dialog;
 mod completion;
 mod composer_parser;
 mod composer_state;
+mod emoji_replacement;
+mod gif_chooser;
+mod html_paste;
+#[cfg(test)]
+mod tests;
 
 pub(crate) use self::composer_state::{ComposerState, MessageEventSource, RelationInfo};
 use self::{
-    attachment_dialog::AttachmentDialog, completion::CompletionPopover,
+    attachment_dialog::AttachmentDialog,
+    completion::CompletionPopover,
     composer_parser::ComposerParser,
+    gif_chooser::{GifChooser, GifProvider, GifResult},
+    html_paste::{MAX_PASTED_HTML_SIZE, PastedChunk, html_to_pasted_chunks},
 };
 use super::message_row::MessageContent;
 use crate::{
@@ -50,6 +64,15 @@ use crate::{
     },
 };
 
+/// The delay before checking the size of the message after a change.
+const SIZE_CHECK_DELAY: Duration = Duration::from_millis(500);
+/// The minimum number of characters of a display name to suggest converting
+/// it to a mention.
+const MENTION_SUGGESTION_MIN_CHARS: usize = 3;
+/// The key of the setting for whether messages are sent with Ctrl+Enter
+/// instead of Enter.
+const SETTINGS_KEY_CTRL_ENTER_TO_SEND: &str = "ctrl-enter-to-send";
+
 /// A map of composer state per-session and per-room.
 type ComposerStatesMap = HashMap<Option<String>, HashMap<Option<OwnedRoomId>, ComposerState>>;
 
@@ -69,9 +92,10 @@ mod imp {
     use std::{
         cell::{Cell, RefCell},
         marker::PhantomData,
+        sync::LazyLock,
     };
 
-    use glib::subclass::InitializingObject;
+    use glib::subclass::{InitializingObject, Signal};
 
     use super::*;
 
@@ -86,10 +110,26 @@ mod imp {
         #[template_child]
         send_button: TemplateChild<gtk::Button>,
         #[template_child]
+        gif_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        gif_chooser: TemplateChild<GifChooser>,
+        #[template_child]
+        size_warning_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        size_warning_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        split_message_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        mention_suggestion_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        mention_suggestion_label: TemplateChild<gtk::Label>,
+        #[template_child]
         related_event_header: TemplateChild<LabelWithWidgets>,
         #[template_child]
         related_event_content: TemplateChild<MessageContent>,
         #[template_child]
+        cancel_related_event_button: TemplateChild<gtk::Button>,
+        #[template_child]
         tombstoned_label: TemplateChild<gtk::Label>,
         #[template_child]
         tombstoned_button: TemplateChild<LoadingButton>,
@@ -99,21 +139,48 @@ mod imp {
         successor_room_list_info: RoomListRoomInfo,
         room_handlers: RefCell<Vec<glib::SignalHandlerId>>,
         send_message_permission_handler: RefCell<Option<glib::SignalHandlerId>>,
-        /// Whether outgoing messages should be interpreted as markdown.
-        #[property(get, set)]
+        /// Whether outgoing messages should be interpreted as markdown by
+        /// default.
+        ///
+        /// This is the global setting, that can be overridden per-room.
+        #[property(get, set = Self::set_default_markdown_enabled, explicit_notify)]
+        default_markdown_enabled: Cell<bool>,
+        /// Whether outgoing messages in the current room should be
+        /// interpreted as markdown.
+        ///
+        /// Setting this overrides the global setting for the current room.
+        #[property(get, set = Self::set_markdown_enabled, explicit_notify)]
         markdown_enabled: Cell<bool>,
+        /// Whether user IDs and room aliases in outgoing messages should be
+        /// converted to links.
+        #[property(get, set)]
+        linkify_matrix_ids_enabled: Cell<bool>,
         completion: CompletionPopover,
         /// The current composer state.
         #[property(get = Self::current_composer_state)]
         current_composer_state: PhantomData<ComposerState>,
         composer_state_handler: RefCell<Option<glib::SignalHandlerId>>,
-        buffer_handlers: RefCell<Option<(glib::SignalHandlerId, glib::Binding)>>,
+        buffer_handlers: RefCell<Option<([glib::SignalHandlerId; 2], glib::Binding)>>,
+        /// The member that is suggested to be mentioned, with the offset of
+        /// the start of their display name in the buffer.
+        mention_suggestion: RefCell<Option<(Member, i32)>>,
+        /// The mention suggestion that was dismissed by the user.
+        dismissed_mention_suggestion: RefCell<Option<(Member, i32)>>,
         /// The composer states, per-session and per-room.
         ///
         /// The fallback composer state has the `None` key.
         composer_states: RefCell<ComposerStatesMap>,
         /// A guard to avoid sending several messages at once.
         send_guard: Mutex<()>,
+        /// The source ID of the timeout to check the size of the message.
+        size_check_timeout: RefCell<Option<glib::SourceId>>,
+        /// The sending operations that are held to allow the user to undo
+        /// them.
+        pending_sends: RefCell<Vec<PendingSend>>,
+        /// The ID of the next pending sending operation.
+        next_pending_send_id: Cell<u64>,
+        /// The handler for the setting of the URL to search for GIFs.
+        gif_search_url_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -124,6 +191,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             CustomEntry::ensure_type();
+            GifChooser::ensure_type();
 
             Self::bind_template(klass);
             Self::bind_template_callbacks(klass);
@@ -138,7 +206,28 @@ mod imp {
                 },
             );
 
+            klass.install_action_async(
+                "message-toolbar.paste-plain-text",
+                None,
+                |obj, _, _| async move {
+                    obj.imp().paste_plain_text_from_clipboard().await;
+                },
+            );
+            klass.add_binding_action(
+                gdk::Key::v,
+                gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK,
+                "message-toolbar.paste-plain-text",
+            );
+
             klass.install_property_action("message-toolbar.markdown", "markdown-enabled");
+            klass.install_property_action(
+                "message-toolbar.default-markdown",
+                "default-markdown-enabled",
+            );
+            klass.install_property_action(
+                "message-toolbar.linkify-matrix-ids",
+                "linkify-matrix-ids-enabled",
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -148,6 +237,12 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for MessageToolbar {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("related-event-changed").build()]);
+            SIGNALS.as_ref()
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
             let obj = self.obj();
@@ -155,12 +250,48 @@ mod imp {
             // Markdown highlighting.
             let settings = Application::default().settings();
             settings
-                .bind("markdown-enabled", &*obj, "markdown-enabled")
+                .bind("markdown-enabled", &*obj, "default-markdown-enabled")
                 .build();
+            settings
+                .bind("linkify-matrix-ids", &*obj, "linkify-matrix-ids-enabled")
+                .build();
+
+            // The size of the message depends on whether it is parsed as markdown.
+            obj.connect_markdown_enabled_notify(|obj| {
+                obj.imp().queue_size_check();
+            });
+            // The links also add to the size of the message.
+            obj.connect_linkify_matrix_ids_enabled_notify(|obj| {
+                obj.imp().queue_size_check();
+            });
 
             // Tab auto-completion.
             self.completion.set_parent(&*self.message_entry);
 
+            // GIFs, only available if a provider is configured.
+            let gif_search_url_handler = settings.connect_changed(
+                Some("gif-search-url"),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_gif_button();
+                    }
+                ),
+            );
+            self.gif_search_url_handler
+                .replace(Some(gif_search_url_handler));
+            self.update_gif_button();
+            self.gif_chooser.connect_gif_selected(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, result| {
+                    spawn!(async move {
+                        imp.send_gif(result).await;
+                    });
+                }
+            ));
+
             // Location.
             let location = Location::new();
             obj.action_set_enabled("message-toolbar.send-location", location.is_available());
@@ -185,6 +316,16 @@ mod imp {
         }
 
         fn dispose(&self) {
+            if let Some(source) = self.size_check_timeout.take() {
+                source.remove();
+            }
+            if let Some(handler) = self.gif_search_url_handler.take() {
+                Application::default().settings().disconnect(handler);
+            }
+
+            // Do not lose the messages that are still pending.
+            self.hand_over_pending_sends();
+
             self.completion.unparent();
             self.disconnect_signals();
         }
@@ -226,6 +367,8 @@ mod imp {
             }
             let obj = self.obj();
 
+            // The messages should not be sent after leaving the room.
+            self.flush_pending_sends();
             self.disconnect_signals();
 
             if let Some(timeline) = timeline {
@@ -279,16 +422,74 @@ mod imp {
             }
 
             self.completion.set_room(timeline.map(Timeline::room));
+            self.gif_chooser
+                .set_session(timeline.and_then(|timeline| timeline.room().session()));
             self.timeline.set(timeline);
 
             self.update_successor_identifier();
             self.update_tombstoned_page();
             self.update_visible_page();
+            self.update_markdown_enabled();
 
             obj.notify_timeline();
             self.update_current_composer_state(old_timeline);
         }
 
+        /// Set whether outgoing messages should be interpreted as markdown by
+        /// default.
+        fn set_default_markdown_enabled(&self, enabled: bool) {
+            if self.default_markdown_enabled.get() == enabled {
+                return;
+            }
+
+            self.default_markdown_enabled.set(enabled);
+
+            self.obj().notify_default_markdown_enabled();
+            self.update_markdown_enabled();
+        }
+
+        /// Set whether outgoing messages in the current room should be
+        /// interpreted as markdown.
+        ///
+        /// The value is remembered for the current room if it is different
+        /// from the global setting.
+        fn set_markdown_enabled(&self, enabled: bool) {
+            if let Some(room) = self.timeline.upgrade().map(|timeline| timeline.room())
+                && let Some(session) = room.session()
+            {
+                let markdown_override =
+                    (enabled != self.default_markdown_enabled.get()).then_some(enabled);
+                session
+                    .settings()
+                    .set_markdown_override(room.room_id(), markdown_override);
+            }
+
+            if self.markdown_enabled.get() == enabled {
+                return;
+            }
+
+            self.markdown_enabled.set(enabled);
+            self.obj().notify_markdown_enabled();
+        }
+
+        /// Update whether outgoing messages in the current room should be
+        /// interpreted as markdown.
+        fn update_markdown_enabled(&self) {
+            let markdown_override = self
+                .timeline
+                .upgrade()
+                .map(|timeline| timeline.room())
+                .and_then(|room| room.session()?.settings().markdown_override(room.room_id()));
+            let enabled = markdown_override.unwrap_or_else(|| self.default_markdown_enabled.get());
+
+            if self.markdown_enabled.get() == enabled {
+                return;
+            }
+
+            self.markdown_enabled.set(enabled);
+            self.obj().notify_markdown_enabled();
+        }
+
         /// The stack page that should be presented given the current state.
         fn visible_page(&self) -> MessageToolbarPage {
             let Some(room) = self.timeline.upgrade().map(|timeline| timeline.room()) else {
@@ -424,9 +625,11 @@ mod imp {
             if let Some(handler) = self.composer_state_handler.take() {
                 old_composer_state.disconnect(handler);
             }
-            if let Some((handler, binding)) = self.buffer_handlers.take() {
+            if let Some((handlers, binding)) = self.buffer_handlers.take() {
                 let prev_buffer = self.message_entry.buffer();
-                prev_buffer.disconnect(handler);
+                for handler in handlers {
+                    prev_buffer.disconnect(handler);
+                }
 
                 binding.unbind();
             }
@@ -445,6 +648,14 @@ mod imp {
                     let is_empty = imp.is_buffer_empty();
                     imp.send_button.set_sensitive(!is_empty);
                     imp.send_typing_notification(!is_empty);
+                    imp.queue_size_check();
+                }
+            ));
+            let cursor_position_handler = buffer.connect_cursor_position_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_mention_suggestion();
                 }
             ));
 
@@ -457,8 +668,10 @@ mod imp {
                 .sync_create()
                 .build();
 
-            self.buffer_handlers
-                .replace(Some((text_notify_handler, markdown_binding)));
+            self.buffer_handlers.replace(Some((
+                [text_notify_handler, cursor_position_handler],
+                markdown_binding,
+            )));
 
             // Related event.
             let composer_state_handler = composer_state.connect_related_to_changed(clone!(
@@ -471,10 +684,77 @@ mod imp {
             self.composer_state_handler
                 .replace(Some(composer_state_handler));
             self.update_related_event();
+            self.update_mention_suggestion();
+            self.queue_size_check();
 
             obj.notify_current_composer_state();
         }
 
+        /// Queue a check of the size of the message in the composer.
+        ///
+        /// The check is delayed to avoid parsing the message after every
+        /// keystroke.
+        fn queue_size_check(&self) {
+            if let Some(source) = self.size_check_timeout.take() {
+                source.remove();
+            }
+
+            let source = glib::timeout_add_local_once(
+                SIZE_CHECK_DELAY,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move || {
+                        imp.size_check_timeout.take();
+
+                        spawn!(async move {
+                            imp.update_size_warning().await;
+                        });
+                    }
+                ),
+            );
+            self.size_check_timeout.replace(Some(source));
+        }
+
+        /// Update the warning about the size of the message in the composer.
+        async fn update_size_warning(&self) {
+            let composer_state = self.current_composer_state();
+            let size = ComposerParser::new(&composer_state, None)
+                .into_message_event_content(
+                    self.markdown_enabled.get(),
+                    self.linkify_matrix_ids_enabled.get(),
+                )
+                .await
+                .map(|content| message_content_size(&content));
+
+            if self.current_composer_state() != composer_state {
+                // The room changed while we were parsing the message.
+                return;
+            }
+
+            let limit = message_size_limit();
+            let oversized_size = size.filter(|size| *size > limit);
+
+            if let Some(size) = oversized_size {
+                self.size_warning_label.set_label(&gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "This message is too long to be sent ({size} of {limit} maximum)",
+                    &[
+                        ("size", &glib::format_size(size as u64)),
+                        ("limit", &glib::format_size(limit as u64)),
+                    ],
+                ));
+
+                // Edits cannot be split into several messages.
+                let is_edit = matches!(composer_state.related_to(), Some(RelationInfo::Edit(_)));
+                self.split_message_button.set_visible(!is_edit);
+            }
+
+            self.size_warning_revealer
+                .set_reveal_child(oversized_size.is_some());
+        }
+
         /// Update the displayed related event for the current state.
         fn update_related_event(&self) {
             let composer_state = self.current_composer_state();
@@ -488,6 +768,8 @@ mod imp {
                 }
                 None => {}
             }
+
+            self.obj().emit_by_name::<()>("related-event-changed", &[]);
         }
 
         /// Update the displayed related event for the given replied-to event.
@@ -522,6 +804,9 @@ mod imp {
             self.related_event_content
                 .update_for_related_event(&msgtype, message_event, &sender);
             self.related_event_content.set_visible(true);
+
+            self.cancel_related_event_button
+                .set_tooltip_text(Some(&gettext("Cancel Reply")));
         }
 
         /// Update the displayed related event for the given edit.
@@ -532,6 +817,9 @@ mod imp {
                 .set_label_and_widgets::<gtk::Widget>(label, vec![]);
 
             self.related_event_content.set_visible(false);
+
+            self.cancel_related_event_button
+                .set_tooltip_text(Some(&gettext("Cancel Edit")));
         }
 
         /// Clear the related event.
@@ -540,6 +828,146 @@ mod imp {
             self.current_composer_state().set_related_to(None);
         }
 
+        /// Find a member whose full display name was typed right before the
+        /// cursor.
+        ///
+        /// The display name must be unique among the joined members of the
+        /// room, to avoid mentioning the wrong person.
+        ///
+        /// Returns the member and the offset of the start of their display
+        /// name in the buffer.
+        fn find_mention_suggestion(&self) -> Option<(Member, i32)> {
+            let buffer = self.message_entry.buffer();
+            let cursor = buffer.iter_at_mark(&buffer.get_insert());
+
+            // The cursor must be at the end of a word.
+            let mut prev = cursor;
+            if cursor.char().is_alphanumeric()
+                || !prev.backward_char()
+                || !prev.char().is_alphanumeric()
+            {
+                return None;
+            }
+
+            let mut line_start = cursor;
+            line_start.set_line_offset(0);
+            // Use a slice to keep the offsets of the pills.
+            let text = buffer.slice(&line_start, &cursor, true);
+
+            let members = self.completion.member_list().joined_members()?;
+            let mut candidate: Option<(Member, String)> = None;
+            let mut is_ambiguous = false;
+
+            for member in members.iter::<Member>().filter_map(Result::ok) {
+                if member.is_own_user() || member.is_ignored() {
+                    continue;
+                }
+
+                let name = member.display_name();
+                if name.chars().count() < MENTION_SUGGESTION_MIN_CHARS
+                    || !text.ends_with(name.as_str())
+                {
+                    continue;
+                }
+
+                // The name must start a word.
+                let before = &text[..text.len() - name.len()];
+                if before
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphanumeric)
+                {
+                    continue;
+                }
+
+                // Prefer the longest name.
+                match &candidate {
+                    Some((_, candidate_name)) if candidate_name.len() > name.len() => {}
+                    Some((_, candidate_name)) if *candidate_name == name => {
+                        is_ambiguous = true;
+                    }
+                    _ => {
+                        candidate = Some((member, name));
+                        is_ambiguous = false;
+                    }
+                }
+            }
+
+            if is_ambiguous {
+                return None;
+            }
+
+            let (member, name) = candidate?;
+            let name_len = i32::try_from(name.chars().count()).unwrap_or(i32::MAX);
+            let start_offset = cursor.offset().saturating_sub(name_len);
+            Some((member, start_offset))
+        }
+
+        /// Update the suggestion to convert a typed display name to a mention.
+        fn update_mention_suggestion(&self) {
+            let suggestion = self
+                .can_compose_message()
+                .then(|| self.find_mention_suggestion())
+                .flatten()
+                .filter(|suggestion| {
+                    self.dismissed_mention_suggestion.borrow().as_ref() != Some(suggestion)
+                });
+
+            if let Some((member, _)) = &suggestion {
+                self.mention_suggestion_label.set_label(&gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "Mention {name} ({user_id})?",
+                    &[
+                        ("name", &member.display_name()),
+                        ("user_id", member.user_id().as_str()),
+                    ],
+                ));
+            }
+
+            self.mention_suggestion_revealer
+                .set_reveal_child(suggestion.is_some());
+            self.mention_suggestion.replace(suggestion);
+        }
+
+        /// Convert the suggested display name to a mention.
+        #[template_callback]
+        fn confirm_mention_suggestion(&self) {
+            let Some((member, start_offset)) = self.mention_suggestion.take() else {
+                return;
+            };
+
+            // Make sure that the text did not change since the suggestion.
+            if self
+                .find_mention_suggestion()
+                .is_none_or(|suggestion| suggestion != (member.clone(), start_offset))
+            {
+                self.update_mention_suggestion();
+                return;
+            }
+
+            let buffer = self.message_entry.buffer();
+            let mut start = buffer.iter_at_offset(start_offset);
+            let mut end = buffer.iter_at_mark(&buffer.get_insert());
+            buffer.delete(&mut start, &mut end);
+
+            // We do not need to watch safety settings for users.
+            let pill = member.to_pill(AvatarImageSafetySetting::None, None);
+            self.current_composer_state().add_widget(pill, &mut start);
+
+            self.mention_suggestion_revealer.set_reveal_child(false);
+            self.message_entry.grab_focus();
+        }
+
+        /// Dismiss the suggestion to convert a display name to a mention.
+        #[template_callback]
+        fn dismiss_mention_suggestion(&self) {
+            self.dismissed_mention_suggestion
+                .replace(self.mention_suggestion.take());
+            self.mention_suggestion_revealer.set_reveal_child(false);
+            self.message_entry.grab_focus();
+        }
+
         /// Add a mention of the given member to the message composer.
         pub(super) fn mention_member(&self, member: &Member) {
             if !self.can_compose_message() {
@@ -612,26 +1040,42 @@ mod imp {
             _keycode: u32,
             modifier: gdk::ModifierType,
         ) -> glib::Propagation {
-            // Do not capture key press if there is a mask other than CapsLock.
-            if modifier != gdk::ModifierType::NO_MODIFIER_MASK
-                && modifier != gdk::ModifierType::LOCK_MASK
-            {
-                return glib::Propagation::Proceed;
-            }
+            // Ignore CapsLock.
+            let modifier = modifier.difference(gdk::ModifierType::LOCK_MASK);
 
-            // Send message on enter.
+            // Depending on the settings, Enter either sends the message or inserts a new line,
+            // and Ctrl+Enter or Shift+Enter does the other.
             if matches!(
                 key,
                 gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::ISO_Enter,
             ) {
-                spawn!(clone!(
-                    #[weak(rename_to = imp)]
-                    self,
-                    async move {
-                        imp.send_text_message().await;
+                let has_alternate_modifier = modifier == gdk::ModifierType::CONTROL_MASK
+                    || modifier == gdk::ModifierType::SHIFT_MASK;
+
+                if modifier.is_empty() || has_alternate_modifier {
+                    let ctrl_enter_to_send = Application::default()
+                        .settings()
+                        .boolean(SETTINGS_KEY_CTRL_ENTER_TO_SEND);
+
+                    if has_alternate_modifier == ctrl_enter_to_send {
+                        spawn!(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            async move {
+                                imp.send_text_message().await;
+                            }
+                        ));
+                    } else {
+                        self.insert_newline();
                     }
-                ));
-                return glib::Propagation::Stop;
+
+                    return glib::Propagation::Stop;
+                }
+            }
+
+            // Do not capture key press if there is a mask other than CapsLock.
+            if !modifier.is_empty() {
+                return glib::Propagation::Proceed;
             }
 
             // Clear related event on escape.
@@ -659,6 +1103,19 @@ mod imp {
             glib::Propagation::Proceed
         }
 
+        /// Insert a new line at the cursor in the message entry.
+        fn insert_newline(&self) {
+            let buffer = self.message_entry.buffer();
+
+            buffer.begin_user_action();
+            buffer.delete_selection(true, true);
+            buffer.insert_interactive_at_cursor("\n", true);
+            buffer.end_user_action();
+
+            self.message_entry
+                .scroll_mark_onscreen(&buffer.get_insert());
+        }
+
         /// Send the text message that is currently in the message entry.
         #[template_callback]
         async fn send_text_message(&self) {
@@ -674,64 +1131,280 @@ mod imp {
 
             let composer_state = self.current_composer_state();
             let markdown_enabled = self.markdown_enabled.get();
+            let linkify_matrix_ids = self.linkify_matrix_ids_enabled.get();
 
             let Some(content) = ComposerParser::new(&composer_state, None)
-                .into_message_event_content(markdown_enabled)
+                .into_message_event_content(markdown_enabled, linkify_matrix_ids)
                 .await
             else {
                 return;
             };
 
-            let matrix_timeline = timeline.matrix_timeline();
+            if message_content_size(&content) > message_size_limit() {
+                toast!(
+                    self.obj(),
+                    gettext("This message is too long, split it or send it as a file")
+                );
+                self.update_size_warning().await;
+                return;
+            }
 
-            // Send event depending on relation.
-            match composer_state.related_to() {
-                Some(RelationInfo::Reply(message_event)) => {
-                    let event_id = message_event.event_id();
+            let related_to = composer_state.related_to();
 
-                    let handle =
-                        spawn_tokio!(
-                            async move { matrix_timeline.send_reply(content, event_id).await }
-                        );
+            // Keep the draft to be able to restore it if the user undoes the sending.
+            let draft = ComposerParser::new(&composer_state, None).into_composer_draft();
 
-                    if let Err(error) = handle.await.expect("task was not aborted") {
-                        error!("Could not send reply: {error}");
-                        toast!(self.obj(), gettext("Could not send reply"));
-                    }
+            // Clear the composer state.
+            composer_state.clear();
+
+            self.hold_send(
+                clone!(
+                    #[strong]
+                    timeline,
+                    move || send_text_content(&timeline, content, related_to)
+                ),
+                draft.map(|draft| restore_draft(composer_state, timeline, draft)),
+            );
+        }
+
+        /// Hold the given sending operation during the delay to undo sending,
+        /// if it is enabled.
+        ///
+        /// `send` is called once the delay is over, or immediately if the delay
+        /// is disabled. If the user undoes the sending, `send` is never called,
+        /// and `undo` is called to restore the state before sending, if any.
+        fn hold_send(
+            &self,
+            send: impl FnOnce() -> SendTask + 'static,
+            undo: Option<Box<dyn FnOnce()>>,
+        ) {
+            let delay = Application::default().settings().uint("undo-send-delay");
+            if delay == 0 {
+                self.report_send(send());
+                return;
+            }
+
+            let id = self.next_pending_send_id.get();
+            self.next_pending_send_id.set(id.wrapping_add(1));
+
+            let toast = adw::Toast::builder()
+                .title(gettext("Sending…"))
+                .button_label(gettext("Undo"))
+                .timeout(delay)
+                .build();
+            toast.connect_button_clicked(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.undo_pending_send(id);
                 }
-                Some(RelationInfo::Edit(event_id)) => {
-                    let matrix_room = timeline.room().matrix_room().clone();
-                    let handle = spawn_tokio!(async move {
-                        let full_content = matrix_room
-                            .make_edit_event(&event_id, EditedContent::RoomMessage(content))
-                            .await
-                            .map_err(matrix_sdk_ui::timeline::EditError::from)?;
-                        let send_queue = matrix_room.send_queue();
-                        send_queue.send(full_content).await?;
-                        Ok::<(), matrix_sdk_ui::timeline::Error>(())
-                    });
-                    if let Err(error) = handle.await.unwrap() {
-                        error!("Could not send edit: {error}");
-                        toast!(self.obj(), gettext("Could not send edit"));
+            ));
+
+            let timeout = glib::timeout_add_seconds_local_once(
+                delay,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move || {
+                        let Some(pending_send) = imp.take_pending_send(id) else {
+                            return;
+                        };
+
+                        // The source is destroyed after this callback, so we do not remove it.
+                        pending_send.toast.dismiss();
+                        imp.report_send((pending_send.send)());
                     }
+                ),
+            );
+
+            self.pending_sends.borrow_mut().push(PendingSend {
+                id,
+                timeout: Some(timeout),
+                toast: toast.clone(),
+                send: Box::new(send),
+                undo,
+            });
+
+            crate::utils::toast::add_toast(self.obj().upcast_ref(), toast);
+        }
+
+        /// Take the pending sending operation with the given ID.
+        fn take_pending_send(&self, id: u64) -> Option<PendingSend> {
+            let mut pending_sends = self.pending_sends.borrow_mut();
+            let index = pending_sends
+                .iter()
+                .position(|pending_send| pending_send.id == id)?;
+            Some(pending_sends.remove(index))
+        }
+
+        /// Undo the pending sending operation with the given ID.
+        fn undo_pending_send(&self, id: u64) {
+            let Some(mut pending_send) = self.take_pending_send(id) else {
+                return;
+            };
+
+            if let Some(timeout) = pending_send.timeout.take() {
+                timeout.remove();
+            }
+            if let Some(undo) = pending_send.undo {
+                undo();
+            }
+        }
+
+        /// Take all the pending sending operations, to send them immediately.
+        fn take_pending_sends(&self) -> Vec<PendingSend> {
+            let mut pending_sends = self.pending_sends.take();
+
+            for pending_send in &mut pending_sends {
+                if let Some(timeout) = pending_send.timeout.take() {
+                    timeout.remove();
                 }
-                _ => {
-                    let handle = spawn_tokio!(async move {
-                        matrix_timeline
-                            .send(content.with_relation(None).into())
-                            .await
-                    });
-                    if let Err(error) = handle.await.unwrap() {
-                        error!("Could not send message: {error}");
-                        toast!(self.obj(), gettext("Could not send message"));
-                    }
+                pending_send.toast.dismiss();
+            }
+
+            pending_sends
+        }
+
+        /// Send all the pending sending operations immediately.
+        fn flush_pending_sends(&self) {
+            for pending_send in self.take_pending_sends() {
+                self.report_send((pending_send.send)());
+            }
+        }
+
+        /// Hand all the pending sending operations over to the send queue of
+        /// the room immediately, without waiting for them.
+        ///
+        /// This should only be used when the toolbar is destroyed. The messages
+        /// are queued by tasks running on the tokio runtime, so they do not
+        /// depend on the toolbar, but errors cannot be reported anymore.
+        fn hand_over_pending_sends(&self) {
+            for pending_send in self.take_pending_sends() {
+                // The task is detached and keeps running in the background.
+                drop((pending_send.send)());
+            }
+        }
+
+        /// Report an error to the user if the given sending operation fails.
+        fn report_send(&self, task: SendTask) {
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    imp.finish_send(task).await;
                 }
+            ));
+        }
+
+        /// Wait for the given sending operation to finish, and report an error
+        /// to the user if it fails.
+        ///
+        /// Returns whether the message was queued successfully.
+        async fn finish_send(&self, task: SendTask) -> bool {
+            let success = task.handle.await.expect("task was not aborted");
+
+            if !success {
+                toast!(self.obj(), task.error_message);
+            }
+
+            success
+        }
+
+        /// Split the message that is currently in the message entry into
+        /// several messages that are small enough to be sent, and send them.
+        ///
+        /// Mentions are sent as plain text.
+        #[template_callback]
+        async fn split_message(&self) {
+            let Some(_send_guard) = self.send_guard.try_lock() else {
+                return;
+            };
+            if !self.can_compose_message() {
+                return;
+            }
+            let Some(timeline) = self.timeline.upgrade() else {
+                return;
+            };
+
+            let composer_state = self.current_composer_state();
+            let reply_to = match composer_state.related_to() {
+                Some(RelationInfo::Reply(message_event)) => Some(message_event.event_id()),
+                Some(RelationInfo::Edit(_)) => return,
+                None => None,
+            };
+
+            let text = ComposerParser::new(&composer_state, None).into_plain_text();
+            let contents =
+                split_message_text(&text, self.markdown_enabled.get(), message_size_limit());
+
+            let matrix_timeline = timeline.matrix_timeline();
+            let handle = spawn_tokio!(async move {
+                let mut contents = contents.into_iter();
+
+                // Only the first message is a reply.
+                if let Some(event_id) = reply_to
+                    && let Some(content) = contents.next()
+                {
+                    matrix_timeline.send_reply(content, event_id).await?;
+                }
+
+                for content in contents {
+                    matrix_timeline
+                        .send(content.with_relation(None).into())
+                        .await?;
+                }
+
+                Ok::<(), matrix_sdk_ui::timeline::Error>(())
+            });
+
+            if let Err(error) = handle.await.expect("task was not aborted") {
+                error!("Could not send split message: {error}");
+                toast!(self.obj(), gettext("Could not send message"));
+                return;
             }
 
-            // Clear the composer state.
             composer_state.clear();
         }
 
+        /// Send the message that is currently in the message entry as a text
+        /// file.
+        #[template_callback]
+        async fn send_message_as_file(&self) {
+            let Some(_send_guard) = self.send_guard.try_lock() else {
+                return;
+            };
+            if !self.can_compose_message() {
+                return;
+            }
+
+            let Some(timeline) = self.timeline.upgrade() else {
+                return;
+            };
+
+            let composer_state = self.current_composer_state();
+            let bytes = ComposerParser::new(&composer_state, None)
+                .into_plain_text()
+                .into_bytes();
+            // Keep the draft to be able to restore it if the user undoes the sending.
+            let draft = ComposerParser::new(&composer_state, None).into_composer_draft();
+            let size = u32::try_from(bytes.len()).ok().map(Into::into);
+
+            let source = AttachmentSource::Data {
+                bytes,
+                filename: filename_for_mime(Some(mime::TEXT_PLAIN.as_ref()), None),
+            };
+            let info = AttachmentInfo::File(BaseFileInfo { size });
+
+            let undo = draft.map(|draft| restore_draft(composer_state.clone(), timeline, draft));
+
+            if self
+                .send_attachment(source, mime::TEXT_PLAIN_UTF_8, info, None, undo)
+                .await
+            {
+                composer_state.clear();
+            }
+        }
+
         /// Open the emoji chooser in the message entry.
         #[template_callback]
         fn open_emoji(&self) {
@@ -741,6 +1414,109 @@ mod imp {
             self.message_entry.emit_insert_emoji();
         }
 
+        /// Update the visibility of the GIF button.
+        fn update_gif_button(&self) {
+            self.gif_button
+                .set_visible(GifProvider::from_settings().is_some());
+        }
+
+        /// Send the given GIF.
+        ///
+        /// The GIF is sent with its original URI if it is an MXC URI, otherwise
+        /// it is downloaded and uploaded to the homeserver.
+        async fn send_gif(&self, result: GifResult) {
+            let Some(_send_guard) = self.send_guard.try_lock() else {
+                return;
+            };
+            if !self.can_compose_message() {
+                return;
+            }
+            let Some(timeline) = self.timeline.upgrade() else {
+                return;
+            };
+            let Some(session) = timeline.room().session() else {
+                return;
+            };
+
+            let title = result
+                .title
+                .as_deref()
+                .map(str::trim)
+                .filter(|title| !title.is_empty());
+
+            if let Some(uri) = result.mxc_uri() {
+                let mime = result.mime();
+                let filename = gif_filename(title, &mime);
+
+                // Providers often return short videos instead of actual GIFs.
+                let msgtype = if mime.type_() == mime::VIDEO {
+                    let mut info = VideoInfo::new();
+                    info.mimetype = Some(mime.to_string());
+                    info.width = result.width.map(Into::into);
+                    info.height = result.height.map(Into::into);
+                    info.size = result.size.map(Into::into);
+
+                    MessageType::Video(
+                        VideoMessageEventContent::plain(filename, uri).info(Box::new(info)),
+                    )
+                } else {
+                    let mut info = ImageInfo::new();
+                    info.mimetype = Some(mime.to_string());
+                    info.width = result.width.map(Into::into);
+                    info.height = result.height.map(Into::into);
+                    info.size = result.size.map(Into::into);
+                    info.is_animated = Some(true);
+
+                    MessageType::Image(
+                        ImageMessageEventContent::plain(filename, uri).info(Box::new(info)),
+                    )
+                };
+
+                let content = RoomMessageEventContent::new(msgtype)
+                    // To avoid triggering legacy pushrules, we must always include the mentions,
+                    // even if they are empty.
+                    .add_mentions(Mentions::default());
+
+                let matrix_timeline = timeline.matrix_timeline();
+                let handle =
+                    spawn_tokio!(async move { matrix_timeline.send(content.into()).await });
+
+                if let Err(error) = handle.await.expect("task was not aborted") {
+                    error!("Could not send GIF: {error}");
+                    toast!(self.obj(), gettext("Could not send GIF"));
+                }
+
+                return;
+            }
+
+            let Ok((bytes, mime)) = result.download(&session).await else {
+                toast!(self.obj(), gettext("Could not send GIF"));
+                return;
+            };
+            let filename = gif_filename(title, &mime);
+
+            let size: Option<u32> = bytes.len().try_into().ok();
+            let info = if mime.type_() == mime::VIDEO {
+                AttachmentInfo::Video(BaseVideoInfo {
+                    width: result.width.map(Into::into),
+                    height: result.height.map(Into::into),
+                    size: size.map(Into::into),
+                    ..Default::default()
+                })
+            } else {
+                AttachmentInfo::Image(BaseImageInfo {
+                    width: result.width.map(Into::into),
+                    height: result.height.map(Into::into),
+                    size: size.map(Into::into),
+                    is_animated: Some(true),
+                    ..Default::default()
+                })
+            };
+
+            let source = AttachmentSource::Data { bytes, filename };
+            self.send_attachment(source, mime, info, None, None).await;
+        }
+
         /// Send the current location of the user.
         ///
         /// Shows a preview of the location first and asks the user to confirm
@@ -867,36 +1643,34 @@ mod imp {
         }
 
         /// Send the attachment with the given data.
+        ///
+        /// If the delay to undo sending is enabled, the attachment is held
+        /// during the delay and this returns `true`. If the user undoes the
+        /// sending, the attachment is not sent and `undo` is called, if any.
+        ///
+        /// Otherwise, returns whether the attachment was queued successfully.
         async fn send_attachment(
             &self,
             source: AttachmentSource,
             mime: mime::Mime,
             info: AttachmentInfo,
             thumbnail: Option<Thumbnail>,
-        ) {
+            undo: Option<Box<dyn FnOnce()>>,
+        ) -> bool {
             let Some(timeline) = self.timeline.upgrade() else {
-                return;
-            };
-
-            let config = AttachmentConfig {
-                info: Some(info),
-                thumbnail,
-                ..Default::default()
+                return false;
             };
 
-            let matrix_timeline = timeline.matrix_timeline();
-
-            let handle = spawn_tokio!(async move {
-                matrix_timeline
-                    .send_attachment(source, mime, config)
-                    .use_send_queue()
-                    .await
-            });
-
-            if let Err(error) = handle.await.unwrap() {
-                error!("Could not send file: {error}");
-                toast!(self.obj(), gettext("Could not send file"));
+            if Application::default().settings().uint("undo-send-delay") > 0 {
+                self.hold_send(
+                    move || send_attachment(&timeline, source, mime, info, thumbnail),
+                    undo,
+                );
+                return true;
             }
+
+            self.finish_send(send_attachment(&timeline, source, mime, info, thumbnail))
+                .await
         }
 
         /// Send the given texture as an image.
@@ -920,7 +1694,13 @@ mod imp {
                 return;
             }
 
-            let bytes = image.save_to_png_bytes();
+            let Some((format, bytes)) = dialog.encoded_image() else {
+                error!("Could not encode image to send");
+                toast!(obj, gettext("Could not send image"));
+                return;
+            };
+            let mime = format.mime_type();
+            let filename = filename_for_mime(Some(mime.as_ref()), None);
             let filesize = bytes.len().try_into().ok();
 
             let (mut base_info, thumbnail) = ImageInfoLoader::from(image)
@@ -929,11 +1709,8 @@ mod imp {
             base_info.size = filesize.map(Into::into);
 
             let info = AttachmentInfo::Image(base_info);
-            let source = AttachmentSource::Data {
-                bytes: bytes.to_vec(),
-                filename,
-            };
-            self.send_attachment(source, mime::IMAGE_PNG, info, thumbnail)
+            let source = AttachmentSource::Data { bytes, filename };
+            self.send_attachment(source, mime, info, thumbnail, None)
                 .await;
         }
 
@@ -1035,7 +1812,7 @@ mod imp {
                 _ => (AttachmentInfo::File(BaseFileInfo { size }), None),
             };
 
-            self.send_attachment(path.into(), file_info.mime, info, thumbnail)
+            self.send_attachment(path.into(), file_info.mime, info, thumbnail, None)
                 .await;
         }
 
@@ -1082,7 +1859,8 @@ mod imp {
             }
         }
 
-        /// Handle a click on the related event.
+        /// Handle a click on the related event, or on the button to scroll to
+        /// it.
         ///
         /// Scrolls to the corresponding event.
         #[template_callback]
@@ -1109,7 +1887,7 @@ mod imp {
 
             let formats = self.obj().clipboard().formats();
 
-            // We only handle files and supported images.
+            // We handle files and supported images.
             if formats.contains_type(gio::File::static_type())
                 || formats.contains_type(gdk::Texture::static_type())
             {
@@ -1122,7 +1900,120 @@ mod imp {
                         imp.read_clipboard_file().await;
                     }
                 ));
+            // We convert rich text to Markdown, if it is enabled.
+            } else if self.markdown_enabled.get() && formats.contain_mime_type("text/html") {
+                self.message_entry
+                    .stop_signal_emission_by_name("paste-clipboard");
+                spawn!(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    async move {
+                        imp.paste_html_from_clipboard().await;
+                    }
+                ));
+            }
+        }
+
+        /// Read the HTML in the clipboard, if it is not too big.
+        async fn read_clipboard_html(&self) -> Option<String> {
+            let clipboard = self.obj().clipboard();
+
+            let stream = match clipboard
+                .read_future(&["text/html"], glib::Priority::DEFAULT)
+                .await
+            {
+                Ok((stream, _)) => stream,
+                Err(error) => {
+                    warn!("Could not read HTML from the clipboard: {error}");
+                    return None;
+                }
+            };
+
+            // Read one more byte than the maximum size to detect bigger documents.
+            let (buffer, size) = match stream
+                .read_all_future(vec![0; MAX_PASTED_HTML_SIZE + 1], glib::Priority::DEFAULT)
+                .await
+            {
+                Ok((buffer, size, None)) => (buffer, size),
+                Ok((_, _, Some(error))) | Err((_, error)) => {
+                    warn!("Could not read HTML from the clipboard: {error}");
+                    return None;
+                }
+            };
+
+            if size > MAX_PASTED_HTML_SIZE {
+                debug!("HTML in the clipboard is too big, pasting it as plain text");
+                return None;
             }
+
+            Some(String::from_utf8_lossy(&buffer[..size]).into_owned())
+        }
+
+        /// Paste the HTML in the clipboard into the message entry, converted to
+        /// Markdown.
+        ///
+        /// Falls back to plain text if the HTML cannot be converted.
+        async fn paste_html_from_clipboard(&self) {
+            let Some(room) = self.timeline.upgrade().map(|timeline| timeline.room()) else {
+                return;
+            };
+
+            let chunks = self
+                .read_clipboard_html()
+                .await
+                .map(|html| html_to_pasted_chunks(&html, &room))
+                .filter(|chunks| !chunks.is_empty());
+
+            let Some(chunks) = chunks else {
+                self.paste_plain_text_from_clipboard().await;
+                return;
+            };
+
+            let buffer = self.message_entry.buffer();
+            let composer_state = self.current_composer_state();
+
+            buffer.begin_user_action();
+            buffer.delete_selection(true, true);
+
+            let mut iter = buffer.iter_at_mark(&buffer.get_insert());
+            for chunk in chunks {
+                match chunk {
+                    PastedChunk::Text(text) => buffer.insert(&mut iter, &text),
+                    PastedChunk::Mention(pill) => composer_state.add_widget(pill, &mut iter),
+                }
+            }
+
+            buffer.place_cursor(&iter);
+            buffer.end_user_action();
+
+            self.message_entry
+                .scroll_mark_onscreen(&buffer.get_insert());
+        }
+
+        /// Paste the text in the clipboard into the message entry, without any
+        /// formatting.
+        async fn paste_plain_text_from_clipboard(&self) {
+            if !self.can_compose_message() {
+                return;
+            }
+
+            let text = match self.obj().clipboard().read_text_future().await {
+                Ok(Some(text)) => text,
+                Ok(None) => return,
+                Err(error) => {
+                    warn!("Could not read text from the clipboard: {error}");
+                    return;
+                }
+            };
+
+            let buffer = self.message_entry.buffer();
+            buffer.begin_user_action();
+            buffer.delete_selection(true, true);
+            buffer.insert_at_cursor(&text);
+            buffer.end_user_action();
+
+            self.message_entry
+                .scroll_mark_onscreen(&buffer.get_insert());
         }
 
         /// Copy the content of the message entry to the clipboard.
@@ -1161,17 +2052,8 @@ mod imp {
             let Some(timeline) = self.timeline.upgrade() else {
                 return;
             };
-            let room = timeline.room();
-
-            let Some(session) = room.session() else {
-                return;
-            };
-
-            if !session.settings().typing_enabled() {
-                return;
-            }
 
-            room.send_typing_notification(typing);
+            timeline.room().send_typing_notification(typing);
         }
 
         /// Join or view the successor of the room, if possible.
@@ -1245,6 +2127,348 @@ mod imp {
     }
 }
 
+/// A sending operation held to allow the user to undo it.
+struct PendingSend {
+    /// The ID of this operation.
+    id: u64,
+    /// The source ID of the timeout at the end of the delay.
+    timeout: Option<glib::SourceId>,
+    /// The toast allowing to undo the operation.
+    toast: adw::Toast,
+    /// The function to call to start sending.
+    send: Box<dyn FnOnce() -> SendTask>,
+    /// The function to call to restore the state before sending if the
+    /// operation is undone, if any.
+    undo: Option<Box<dyn FnOnce()>>,
+}
+
+impl std::fmt::Debug for PendingSend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingSend")
+            .field("id", &self.id)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A sending operation that was started in the tokio runtime.
+struct SendTask {
+    /// The handle of the task, that resolves to whether the message was
+    /// queued successfully.
+    handle: tokio::task::JoinHandle<bool>,
+    /// The message to present to the user if the message could not be queued.
+    error_message: String,
+}
+
+/// Start sending the given text message content, with the given relation, in
+/// the given timeline.
+///
+/// The message is persisted by the send queue of the room once the returned
+/// task is finished.
+fn send_text_content(
+    timeline: &Timeline,
+    content: RoomMessageEventContentWithoutRelation,
+    related_to: Option<RelationInfo>,
+) -> SendTask {
+    let matrix_timeline = timeline.matrix_timeline();
+
+    // Send event depending on relation.
+    match related_to {
+        Some(RelationInfo::Reply(message_event)) => {
+            let event_id = message_event.event_id();
+            let handle = spawn_tokio!(async move {
+                let result = matrix_timeline.send_reply(content, event_id).await;
+
+                if let Err(error) = &result {
+                    error!("Could not send reply: {error}");
+                }
+                result.is_ok()
+            });
+
+            SendTask {
+                handle,
+                error_message: gettext("Could not send reply"),
+            }
+        }
+        Some(RelationInfo::Edit(event_id)) => {
+            let matrix_room = timeline.room().matrix_room().clone();
+            let handle = spawn_tokio!(async move {
+                let result = async {
+                    let full_content = matrix_room
+                        .make_edit_event(&event_id, EditedContent::RoomMessage(content))
+                        .await
+                        .map_err(matrix_sdk_ui::timeline::EditError::from)?;
+                    let send_queue = matrix_room.send_queue();
+                    send_queue.send(full_content).await?;
+                    Ok::<(), matrix_sdk_ui::timeline::Error>(())
+                }
+                .await;
+
+                if let Err(error) = &result {
+                    error!("Could not send edit: {error}");
+                }
+                result.is_ok()
+            });
+
+            SendTask {
+                handle,
+                error_message: gettext("Could not send edit"),
+            }
+        }
+        _ => {
+            let handle = spawn_tokio!(async move {
+                let result = matrix_timeline
+                    .send(content.with_relation(None).into())
+                    .await;
+
+                if let Err(error) = &result {
+                    error!("Could not send message: {error}");
+                }
+                result.is_ok()
+            });
+
+            SendTask {
+                handle,
+                error_message: gettext("Could not send message"),
+            }
+        }
+    }
+}
+
+/// Start sending the attachment with the given data in the given timeline.
+///
+/// The attachment is persisted by the send queue of the room once the
+/// returned task is finished.
+fn send_attachment(
+    timeline: &Timeline,
+    source: AttachmentSource,
+    mime: mime::Mime,
+    info: AttachmentInfo,
+    thumbnail: Option<Thumbnail>,
+) -> SendTask {
+    let config = AttachmentConfig {
+        info: Some(info),
+        thumbnail,
+        ..Default::default()
+    };
+
+    let matrix_timeline = timeline.matrix_timeline();
+    let handle = spawn_tokio!(async move {
+        let result = matrix_timeline
+            .send_attachment(source, mime, config)
+            .use_send_queue()
+            .await;
+
+        if let Err(error) = &result {
+            error!("Could not send file: {error}");
+        }
+        result.is_ok()
+    });
+
+    SendTask {
+        handle,
+        error_message: gettext("Could not send file"),
+    }
+}
+
+/// Construct a function to restore the given draft in the given composer
+/// state.
+fn restore_draft(
+    composer_state: ComposerState,
+    timeline: Timeline,
+    draft: ComposerDraft,
+) -> Box<dyn FnOnce()> {
+    Box::new(move || {
+        spawn!(async move {
+            composer_state.restore_unsent_draft(&timeline, draft).await;
+        });
+    })
+}
+
+/// The name of the file of a GIF with the given title and MIME type.
+fn gif_filename(title: Option<&str>, mime: &mime::Mime) -> String {
+    let Some(title) = title else {
+        return filename_for_mime(Some(mime.as_ref()), None);
+    };
+
+    match mime_guess::get_mime_extensions(mime).and_then(|extensions| extensions.first()) {
+        Some(extension) => format!("{title}.{extension}"),
+        None => title.to_owned(),
+    }
+}
+
+/// The maximum size of the content of a text message, in bytes.
+fn message_size_limit() -> usize {
+    Application::default()
+        .settings()
+        .uint("message-size-limit")
+        .try_into()
+        .unwrap_or(usize::MAX)
+}
+
+/// The size of the given message content, in bytes, once serialized.
+fn message_content_size(content: &RoomMessageEventContentWithoutRelation) -> usize {
+    serde_json::to_vec(content).map_or(0, |bytes| bytes.len())
+}
+
+/// Construct the content of a text message with the given body.
+fn text_message_content(
+    body: &str,
+    markdown_enabled: bool,
+) -> RoomMessageEventContentWithoutRelation {
+    let content = if markdown_enabled {
+        RoomMessageEventContentWithoutRelation::text_markdown(body)
+    } else {
+        RoomMessageEventContentWithoutRelation::text_plain(body)
+    };
+
+    // To avoid triggering legacy pushrules, we must always include the mentions,
+    // even if they are empty.
+    content.add_mentions(Mentions::default())
+}
+
+/// Split the given text into the contents of several messages whose size is
+/// under the given limit.
+///
+/// The text is split between lines when possible. When Markdown is enabled and
+/// the text is split inside a code block, the code block is closed at the end
+/// of a message and reopened at the start of the next one.
+fn split_message_text(
+    text: &str,
+    markdown_enabled: bool,
+    limit: usize,
+) -> Vec<RoomMessageEventContentWithoutRelation> {
+    let fits =
+        |chunk: &str| message_content_size(&text_message_content(chunk, markdown_enabled)) <= limit;
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    // The length of the code fence that was reopened at the start of `current`.
+    let mut prefix_len = 0;
+    // The opening line of the code block that is open at the end of `current`, if any.
+    let mut open_fence: Option<&str> = None;
+
+    for line in text.split_inclusive('\n') {
+        // The code block that is open before this line.
+        let fence_before = open_fence;
+        if markdown_enabled {
+            open_fence = match open_fence {
+                Some(opening) if closes_code_fence(line, opening) => None,
+                Some(opening) => Some(opening),
+                None => code_fence_marker(line).is_some().then_some(line),
+            };
+        }
+
+        current.push_str(line);
+
+        if fits(&close_code_fence(&current, open_fence)) {
+            continue;
+        }
+
+        // Try to start a new chunk with this line.
+        let previous_len = current.len() - line.len();
+        if previous_len > prefix_len {
+            current.truncate(previous_len);
+            chunks.push(close_code_fence(&current, fence_before));
+
+            current = reopen_code_fence(fence_before);
+            prefix_len = current.len();
+            current.push_str(line);
+        }
+
+        // The line alone is too long, split it at character boundaries.
+        while !fits(&close_code_fence(&current, open_fence)) {
+            // Always take at least one character after the reopened code fence to make
+            // progress.
+            let boundaries = current
+                .char_indices()
+                .map(|(index, _)| index)
+                .filter(|index| *index > prefix_len)
+                .collect::<Vec<_>>();
+            let count = boundaries
+                .partition_point(|index| fits(&close_code_fence(&current[..*index], fence_before)));
+            let Some(split_index) = boundaries.get(count.saturating_sub(1)).copied() else {
+                break;
+            };
+
+            chunks.push(close_code_fence(&current[..split_index], fence_before));
+
+            let rest = current.split_off(split_index);
+            current = reopen_code_fence(fence_before);
+            prefix_len = current.len();
+            current.push_str(&rest);
+        }
+    }
+    // If the last code block is not closed, it was not closed in the original text
+    // either.
+    chunks.push(current);
+
+    chunks
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .map(|chunk| text_message_content(&chunk, markdown_enabled))
+        .collect()
+}
+
+/// The marker of the Markdown code fence on the given line, if any.
+///
+/// The marker is the sequence of backticks or tildes opening or closing a
+/// fenced code block.
+fn code_fence_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+
+    // A code fence can be indented by up to 3 spaces.
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+
+    let fence_char = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let marker_len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+
+    (marker_len >= 3).then(|| &trimmed[..marker_len])
+}
+
+/// Whether the given line closes the code block opened by the given line.
+fn closes_code_fence(line: &str, opening_line: &str) -> bool {
+    let (Some(marker), Some(opening_marker)) =
+        (code_fence_marker(line), code_fence_marker(opening_line))
+    else {
+        return false;
+    };
+
+    // The closing fence must use the same character, be at least as long as the
+    // opening fence, and have no info string.
+    marker.starts_with(&opening_marker[..1])
+        && marker.len() >= opening_marker.len()
+        && line.trim_start_matches(' ')[marker.len()..]
+            .trim()
+            .is_empty()
+}
+
+/// Close the code block opened by the given line at the end of the given
+/// chunk, if any.
+fn close_code_fence(chunk: &str, opening_line: Option<&str>) -> String {
+    let Some(marker) = opening_line.and_then(code_fence_marker) else {
+        return chunk.to_owned();
+    };
+
+    let separator = if chunk.ends_with('\n') { "" } else { "\n" };
+    format!("{chunk}{separator}{marker}\n")
+}
+
+/// Reopen the code block opened by the given line, if any.
+fn reopen_code_fence(opening_line: Option<&str>) -> String {
+    let Some(opening_line) = opening_line else {
+        return String::new();
+    };
+
+    if opening_line.ends_with('\n') {
+        opening_line.to_owned()
+    } else {
+        format!("{opening_line}\n")
+    }
+}
+
 glib::wrapper! {
     /// A toolbar with different actions to send messages.
     pub struct MessageToolbar(ObjectSubclass<imp::MessageToolbar>)
@@ -1296,4 +2520,19 @@ impl MessageToolbar {
             }
         ));
     }
+
+    /// Connect to the signal emitted when the displayed related event
+    /// changed.
+    pub(crate) fn connect_related_event_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "related-event-changed",
+            true,
+            glib::closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
 }
@@ -0,0 +1,111 @@
+use ruma::events::room::message::RoomMessageEventContentWithoutRelation;
+
+use super::{
+    closes_code_fence, code_fence_marker, message_content_size, split_message_text,
+    text_message_content,
+};
+
+/// The bodies of the given message contents.
+fn bodies(contents: &[RoomMessageEventContentWithoutRelation]) -> Vec<&str> {
+    contents
+        .iter()
+        .map(|content| content.msgtype.body())
+        .collect()
+}
+
+/// The size limit that fits exactly the given text.
+fn limit_for(text: &str, markdown_enabled: bool) -> usize {
+    message_content_size(&text_message_content(text, markdown_enabled))
+}
+
+#[test]
+fn split_short_text() {
+    let text = "Hello\nWorld";
+    let contents = split_message_text(text, false, limit_for(text, false));
+    assert_eq!(bodies(&contents), [text]);
+}
+
+#[test]
+fn split_between_lines() {
+    let text = "aaaa\nbbbb\ncccc\n";
+    let limit = limit_for("aaaa\nbbbb\n", false);
+
+    let contents = split_message_text(text, false, limit);
+    assert_eq!(bodies(&contents), ["aaaa\nbbbb\n", "cccc\n"]);
+}
+
+#[test]
+fn split_long_line_at_char_boundaries() {
+    let text = "é🦀".repeat(100);
+    let limit = limit_for(&"é🦀".repeat(15), false);
+
+    let contents = split_message_text(&text, false, limit);
+    assert!(contents.len() > 1);
+    assert!(
+        contents
+            .iter()
+            .all(|content| message_content_size(content) <= limit)
+    );
+    assert_eq!(bodies(&contents).concat(), text);
+}
+
+#[test]
+fn split_inside_code_block() {
+    let code = (0..20)
+        .map(|i| format!("let line{i} = {i};\n"))
+        .collect::<String>();
+    let text = format!("Some code:\n```rust\n{code}```\nThe end\n");
+    let limit = limit_for(&format!("```rust\n{}```\n", &code[..code.len() / 3]), true);
+
+    let contents = split_message_text(&text, true, limit);
+    assert!(contents.len() > 2);
+
+    for body in bodies(&contents) {
+        assert!(limit_for(body, true) <= limit);
+
+        // The code blocks are balanced in each message.
+        let fences = body
+            .lines()
+            .filter(|line| code_fence_marker(line).is_some())
+            .count();
+        assert_eq!(fences % 2, 0, "unbalanced code block in {body:?}");
+
+        // The code is always in a code block with the language.
+        if body.contains("let line") {
+            assert!(body.starts_with("```rust\n") || body.contains("\n```rust\n"));
+        }
+    }
+
+    // All the lines of code are sent, in order.
+    let sent_code = bodies(&contents)
+        .concat()
+        .lines()
+        .filter(|line| line.starts_with("let line"))
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    assert_eq!(sent_code, code);
+}
+
+#[test]
+fn split_without_markdown_ignores_code_fences() {
+    let text = "```\naaaa\nbbbb\n```\n";
+    let limit = limit_for("```\naaaa\n", false);
+
+    let contents = split_message_text(text, false, limit);
+    assert_eq!(bodies(&contents).concat(), text);
+}
+
+#[test]
+fn code_fence_markers() {
+    assert_eq!(code_fence_marker("```rust\n"), Some("```"));
+    assert_eq!(code_fence_marker("   ~~~~\n"), Some("~~~~"));
+    assert_eq!(code_fence_marker("    ```\n"), None);
+    assert_eq!(code_fence_marker("``\n"), None);
+    assert_eq!(code_fence_marker("text ```\n"), None);
+
+    assert!(closes_code_fence("```\n", "```rust\n"));
+    assert!(closes_code_fence("`````", "```\n"));
+    assert!(!closes_code_fence("~~~\n", "```\n"));
+    assert!(!closes_code_fence("```\n", "````\n"));
+    assert!(!closes_code_fence("```rust\n", "```\n"));
+}
@@ -1,12 +1,12 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use adw::{prelude::*, subclass::prelude::*};
-use gettextrs::gettext;
+use gettextrs::{gettext, ngettext};
 use gtk::{gdk, gio, glib, glib::clone, graphene};
 use matrix_sdk::ruma::EventId;
 use matrix_sdk_ui::timeline::TimelineEventItemId;
 use ruma::{
-    OwnedEventId,
+    OwnedEventId, OwnedRoomId,
     api::client::receipt::create_receipt::v3::ReceiptType,
     events::room::{message::MessageType, power_levels::PowerLevelAction},
 };
@@ -21,7 +21,9 @@ mod message_toolbar;
 mod read_receipts_list;
 mod state;
 mod title;
+mod topic_dialog;
 mod typing_row;
+mod unable_to_decrypt_group_row;
 mod verification_info_bar;
 
 use self::{
@@ -29,39 +31,59 @@ use self::{
     event_actions::*,
     event_row::EventRow,
     message_row::MessageRow,
-    message_toolbar::MessageToolbar,
+    message_toolbar::{MessageToolbar, RelationInfo},
     read_receipts_list::ReadReceiptsList,
     state::{StateGroupRow, StateRow},
     title::RoomHistoryTitle,
+    topic_dialog::RoomHistoryTopicDialog,
     typing_row::TypingRow,
+    unable_to_decrypt_group_row::UnableToDecryptGroupRow,
     verification_info_bar::VerificationInfoBar,
 };
 use super::{RoomDetails, room_details};
 use crate::{
-    Window,
-    components::{DragOverlay, confirm_leave_room_dialog},
-    ngettext_f,
+    Application, Window,
+    account_settings::AccountSettings,
+    components::{
+        ConfirmLeavePrivilegedRoomResponse, DragOverlay, LoadingButton,
+        confirm_leave_privileged_room_dialog, confirm_leave_room_dialog,
+        confirm_redact_events_dialog,
+    },
+    gettext_f, ngettext_f,
     prelude::*,
     session::{
-        Event, MemberList, Membership, MembershipListKind, ReceiptPosition, Room,
-        TargetRoomCategory, Timeline, VirtualItem, VirtualItemKind,
+        Event, JoinRuleValue, MemberList, MemberRole, Membership, MembershipListKind,
+        ReceiptPosition, Room, TargetRoomCategory, Timeline, VirtualItem, VirtualItemKind,
     },
     spawn, toast,
-    utils::{BoundObject, GroupingListGroup, GroupingListModel, LoadingState, TemplateCallbacks},
+    utils::{
+        BoundObject, GroupingListGroup, GroupingListModel, LoadingState, TemplateCallbacks,
+        timeline_zoom,
+    },
 };
 
 /// The time to wait before considering that scrolling has ended.
 const SCROLL_TIMEOUT: Duration = Duration::from_millis(500);
-/// The time to wait before considering that messages on a screen where read.
+/// The minimum time to wait before considering that messages on a screen
+/// where read, to move the read marker.
 const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// The settings key for the delay before marking messages as read.
+const SETTINGS_KEY_MARK_AS_READ_DELAY: &str = "mark-as-read-delay";
+/// The time to wait before announcing new live messages, to group them during
+/// bursts.
+const ANNOUNCEMENT_TIMEOUT: Duration = Duration::from_secs(2);
+/// The maximum number of events in a group of state events for it to be
+/// expanded by default.
+const STATE_GROUP_EXPANDED_MAX_EVENTS: u32 = 5;
 
 mod imp {
     use std::{
         cell::{Cell, OnceCell, RefCell},
         ops::ControlFlow,
+        sync::LazyLock,
     };
 
-    use glib::subclass::InitializingObject;
+    use glib::subclass::{InitializingObject, Signal};
 
     use super::*;
 
@@ -88,23 +110,55 @@ mod imp {
         #[template_child]
         scroll_btn_revealer: TemplateChild<gtk::Revealer>,
         #[template_child]
+        load_error_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        load_error_retry_button: TemplateChild<LoadingButton>,
+        #[template_child]
+        related_event_chip_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        related_event_chip_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub(super) message_toolbar: TemplateChild<MessageToolbar>,
         #[template_child]
+        private_read_receipts_button: TemplateChild<gtk::Button>,
+        #[template_child]
         loading: TemplateChild<adw::Spinner>,
         #[template_child]
         error: TemplateChild<adw::StatusPage>,
         #[template_child]
+        retry_button: TemplateChild<LoadingButton>,
+        #[template_child]
+        empty: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        load_older_button: TemplateChild<LoadingButton>,
+        #[template_child]
         stack: TemplateChild<gtk::Stack>,
         #[template_child]
         drag_overlay: TemplateChild<DragOverlay>,
+        #[template_child]
+        selection_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        remove_selected_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        report_selected_button: TemplateChild<gtk::Button>,
         /// The context menu for rows presenting an [`Event`].
         event_context_menu: OnceCell<EventActionsContextMenu>,
+        /// Whether we requested to load events at the start of the timeline.
+        is_paginating_backwards: Cell<bool>,
+        /// Whether the user requested to load events again.
+        is_retrying_load: Cell<bool>,
         /// The timeline currently displayed.
         #[property(get, set = Self::set_timeline, explicit_notify, nullable)]
         timeline: BoundObject<Timeline>,
         /// Whether this is the only view visible, i.e. there is no sidebar.
         #[property(get, set)]
         is_only_view: Cell<bool>,
+        /// Whether the user is selecting events, to act on several events at
+        /// once.
+        #[property(get, set = Self::set_is_selection_mode, explicit_notify)]
+        is_selection_mode: Cell<bool>,
+        /// The events that are selected, in selection mode.
+        selected_events: RefCell<Vec<Event>>,
         /// The members of the current room.
         ///
         /// We hold a strong reference here to keep the list in memory as long
@@ -118,13 +172,32 @@ mod imp {
         is_sticky: Cell<bool>,
         /// The `GroupingListModel` used in the list view.
         grouping_model: OnceCell<GroupingListModel>,
+        /// The last visible event of the rooms that were left while they were
+        /// not scrolled to the bottom.
+        scroll_positions: RefCell<HashMap<OwnedRoomId, OwnedEventId>>,
+        /// The event to scroll to when the current timeline is ready.
+        pending_scroll_event: RefCell<Option<OwnedEventId>>,
+        /// Whether the groups of state events were expanded by the user, while
+        /// the current room is open.
+        state_groups_expanded: RefCell<HashMap<GroupingListGroup, bool>>,
         scroll_timeout: RefCell<Option<glib::SourceId>>,
+        /// The timeout to send the read receipt, if the delay to mark
+        /// messages as read is not 0.
+        receipt_timeout: RefCell<Option<glib::SourceId>>,
         read_timeout: RefCell<Option<glib::SourceId>>,
+        /// The position of the read marker to send when the current room is
+        /// left.
+        pending_read_marker: RefCell<Option<ReceiptPosition>>,
+        announcement_timeout: RefCell<Option<glib::SourceId>>,
+        /// The latest new live message and the number of new live messages
+        /// that were not announced yet.
+        pending_announcement: RefCell<Option<(Event, u32)>>,
         room_handler: RefCell<Option<glib::SignalHandlerId>>,
         permissions_handlers: RefCell<Vec<glib::SignalHandlerId>>,
         membership_handler: RefCell<Option<glib::SignalHandlerId>>,
         join_rule_handler: RefCell<Option<glib::SignalHandlerId>>,
         knock_items_changed_handler: RefCell<Option<glib::SignalHandlerId>>,
+        read_receipts_setting_handler: RefCell<Option<glib::SignalHandlerId>>,
         window_active_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
@@ -154,6 +227,14 @@ mod imp {
                 obj.imp().forget().await;
             });
 
+            klass.install_action("room-history.select-messages", None, |obj, _, _| {
+                obj.set_is_selection_mode(true);
+            });
+
+            klass.install_action("room-history.mark-as-read", None, |obj, _, _| {
+                obj.imp().mark_as_read();
+            });
+
             klass.install_action("room-history.details", None, |obj, _, _| {
                 obj.imp().open_room_details(room_details::InitialView::None);
             });
@@ -255,6 +336,38 @@ mod imp {
 
                 obj.imp().message_toolbar.set_edit(&event);
             });
+
+            klass.install_action("room-history.zoom-in", None, |_, _, _| {
+                timeline_zoom::zoom_in();
+            });
+            klass.install_action("room-history.zoom-out", None, |_, _, _| {
+                timeline_zoom::zoom_out();
+            });
+            klass.install_action("room-history.reset-zoom", None, |_, _, _| {
+                timeline_zoom::reset_zoom();
+            });
+
+            for key in [gdk::Key::plus, gdk::Key::equal, gdk::Key::KP_Add] {
+                klass.add_binding_action(
+                    key,
+                    gdk::ModifierType::CONTROL_MASK,
+                    "room-history.zoom-in",
+                );
+            }
+            for key in [gdk::Key::minus, gdk::Key::KP_Subtract] {
+                klass.add_binding_action(
+                    key,
+                    gdk::ModifierType::CONTROL_MASK,
+                    "room-history.zoom-out",
+                );
+            }
+            for key in [gdk::Key::_0, gdk::Key::KP_0] {
+                klass.add_binding_action(
+                    key,
+                    gdk::ModifierType::CONTROL_MASK,
+                    "room-history.reset-zoom",
+                );
+            }
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -264,20 +377,51 @@ mod imp {
 
     #[glib::derived_properties]
     impl ObjectImpl for RoomHistory {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("selection-changed").build()]);
+            SIGNALS.as_ref()
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
 
             self.init_listview();
             self.init_drop_target();
 
-            self.scroll_btn_revealer
-                .connect_child_revealed_notify(|revealer| {
+            for revealer in [
+                &*self.scroll_btn_revealer,
+                &*self.load_error_revealer,
+                &*self.related_event_chip_revealer,
+            ] {
+                revealer.connect_child_revealed_notify(|revealer| {
                     // Hide the revealer when we don't want to show the child and the animation is
                     // finished.
                     if !revealer.reveals_child() && !revealer.is_child_revealed() {
                         revealer.set_visible(false);
                     }
                 });
+            }
+
+            self.message_toolbar.connect_related_event_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_related_event_chip();
+                }
+            ));
+
+            // The manual action to mark as read depends on the delay.
+            Application::default().settings().connect_changed(
+                Some(SETTINGS_KEY_MARK_AS_READ_DELAY),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_room_menu();
+                    }
+                ),
+            );
 
             self.obj().connect_root_notify(|obj| {
                 let imp = obj.imp();
@@ -328,6 +472,13 @@ mod imp {
             // When the room history becomes mapped, trigger a read receipt update.
             self.trigger_read_receipts_update();
         }
+
+        fn unmap(&self) {
+            // The room is not visible anymore, we can move the "New Messages" divider.
+            self.send_pending_read_marker();
+
+            self.parent_unmap();
+        }
     }
 
     impl BinImpl for RoomHistory {}
@@ -364,7 +515,13 @@ mod imp {
             self.listview
                 .set_vscroll_policy(gtk::ScrollablePolicy::Natural);
 
-            let selection_model = gtk::NoSelection::new(Some(self.grouping_model().clone()));
+            // The selection follows the keyboard navigation, so it can be presented by
+            // assistive technologies.
+            let selection_model = gtk::SingleSelection::builder()
+                .model(self.grouping_model())
+                .autoselect(false)
+                .can_unselect(true)
+                .build();
             self.listview.set_model(Some(&selection_model));
 
             self.set_sticky(true);
@@ -444,6 +601,12 @@ mod imp {
                 if let Some(handler) = self.join_rule_handler.take() {
                     room.join_rule().disconnect(handler);
                 }
+
+                if let Some(handler) = self.read_receipts_setting_handler.take()
+                    && let Some(session) = room.session()
+                {
+                    session.settings().disconnect(handler);
+                }
             }
 
             if let Some(members) = self.room_members.take()
@@ -464,13 +627,26 @@ mod imp {
                 return;
             }
 
+            self.save_scroll_position();
+            self.send_pending_read_marker();
+            self.pending_scroll_event.take();
+
             self.disconnect_all();
             if let Some(source_id) = self.scroll_timeout.take() {
                 source_id.remove();
             }
+            if let Some(source_id) = self.receipt_timeout.take() {
+                source_id.remove();
+            }
             if let Some(source_id) = self.read_timeout.take() {
                 source_id.remove();
             }
+            if let Some(source_id) = self.announcement_timeout.take() {
+                source_id.remove();
+            }
+            self.pending_announcement.take();
+            self.state_groups_expanded.borrow_mut().clear();
+            self.set_is_selection_mode(false);
 
             if let Some(timeline) = timeline {
                 let room = timeline.room();
@@ -538,6 +714,20 @@ mod imp {
 
                 self.room_handler.replace(Some(is_direct_handler));
 
+                if let Some(session) = room.session() {
+                    let read_receipts_setting_handler = session
+                        .settings()
+                        .connect_public_read_receipts_enabled_notify(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move |_| {
+                                imp.update_private_read_receipts_button();
+                            }
+                        ));
+                    self.read_receipts_setting_handler
+                        .replace(Some(read_receipts_setting_handler));
+                }
+
                 let empty_handler = timeline.connect_is_empty_notify(clone!(
                     #[weak(rename_to = imp)]
                     self,
@@ -546,42 +736,195 @@ mod imp {
                     }
                 ));
 
+                let reached_start_handler = timeline.connect_has_reached_start_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_view();
+                    }
+                ));
+
                 let state_handler = timeline.connect_state_notify(clone!(
                     #[weak(rename_to = imp)]
                     self,
                     move |timeline| {
-                        imp.update_view();
+                        if timeline.state() != LoadingState::Loading {
+                            imp.is_retrying_load.set(false);
+                        }
 
                         // Always test if we need to load more when the timeline is ready.
                         // This is mostly to make sure that we load events if the timeline was not
                         // initialized when the room was opened.
                         if timeline.state() == LoadingState::Ready {
+                            imp.scroll_to_pending_event();
                             imp.load_more_events_if_needed();
                         }
+
+                        imp.update_view();
                     }
                 ));
 
-                self.timeline
-                    .set(timeline.clone(), vec![empty_handler, state_handler]);
+                let live_messages_handler = timeline.connect_live_messages_received(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, latest, count| {
+                        imp.queue_live_messages_announcement(latest, count);
+                    }
+                ));
+
+                self.timeline.set(
+                    timeline.clone(),
+                    vec![
+                        empty_handler,
+                        reached_start_handler,
+                        state_handler,
+                        live_messages_handler,
+                    ],
+                );
 
                 timeline.remove_empty_typing_row();
                 self.grouping_model().set_model(Some(timeline.items()));
 
                 self.trigger_read_receipts_update();
-                self.scroll_down();
+                self.restore_scroll_position(&timeline);
             } else {
                 self.grouping_model().set_model(None::<gio::ListModel>);
             }
 
-            self.update_view();
             self.load_more_events_if_needed();
+            self.update_view();
             self.update_room_menu();
             self.update_invite_action();
             self.update_pending_knocks();
+            self.update_private_read_receipts_button();
 
             self.obj().notify_timeline();
         }
 
+        /// Update the button showing that our read receipts are private.
+        fn update_private_read_receipts_button(&self) {
+            let is_private = self
+                .room()
+                .and_then(|room| room.session())
+                .is_some_and(|session| !session.settings().public_read_receipts_enabled());
+            self.private_read_receipts_button.set_visible(is_private);
+        }
+
+        /// Remember the scroll position of the current timeline, if it is not
+        /// scrolled to the bottom.
+        fn save_scroll_position(&self) {
+            let Some(room) = self.room() else {
+                return;
+            };
+            let room_id = room.room_id().to_owned();
+
+            let event_id = if self.is_sticky.get() {
+                None
+            } else {
+                self.last_visible_event_id()
+            };
+
+            let mut scroll_positions = self.scroll_positions.borrow_mut();
+            if let Some(event_id) = event_id {
+                scroll_positions.insert(room_id, event_id);
+            } else {
+                scroll_positions.remove(&room_id);
+            }
+        }
+
+        /// Restore the scroll position of the given timeline, if it was
+        /// remembered.
+        ///
+        /// Otherwise, scroll to the bottom.
+        fn restore_scroll_position(&self, timeline: &Timeline) {
+            let Some(event_id) = self
+                .scroll_positions
+                .borrow_mut()
+                .remove(timeline.room().room_id())
+            else {
+                self.scroll_down();
+                return;
+            };
+
+            self.scroll_to_event_id(event_id);
+        }
+
+        /// Scroll to the event with the given ID in the current timeline, as
+        /// soon as it is ready.
+        pub(super) fn scroll_to_event_id(&self, event_id: OwnedEventId) {
+            let Some(timeline) = self.timeline.obj() else {
+                return;
+            };
+
+            self.set_sticky(false);
+            self.pending_scroll_event.replace(Some(event_id));
+
+            if timeline.state() == LoadingState::Ready {
+                self.scroll_to_pending_event();
+            }
+        }
+
+        /// Scroll to the event that was pending, loading more events until it
+        /// is found if necessary.
+        ///
+        /// If the event cannot be found, scroll to the bottom.
+        fn scroll_to_pending_event(&self) {
+            let Some(timeline) = self.timeline.obj() else {
+                return;
+            };
+            let Some(event_id) = self.pending_scroll_event.take() else {
+                return;
+            };
+            let key = TimelineEventItemId::EventId(event_id);
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    if timeline.find_event_position(&key).is_none() {
+                        timeline
+                            .paginate_backwards(clone!(
+                                #[weak]
+                                timeline,
+                                #[strong]
+                                key,
+                                #[upgrade_or]
+                                ControlFlow::Break(()),
+                                move || {
+                                    if timeline.find_event_position(&key).is_some() {
+                                        ControlFlow::Break(())
+                                    } else {
+                                        ControlFlow::Continue(())
+                                    }
+                                }
+                            ))
+                            .await;
+                    }
+
+                    if imp.timeline.obj().as_ref() != Some(&timeline) {
+                        // The timeline changed in the meantime.
+                        return;
+                    }
+
+                    let Some(pos) = timeline.find_event_position(&key) else {
+                        imp.scroll_down();
+                        return;
+                    };
+
+                    // Wait until the next tick, to make sure that the GtkListView has created the
+                    // items.
+                    glib::idle_add_local_once(clone!(
+                        #[weak]
+                        imp,
+                        move || {
+                            imp.listview
+                                .scroll_to(pos as u32, gtk::ListScrollFlags::NONE, None);
+                        }
+                    ));
+                }
+            ));
+        }
+
         /// The room of the current timeline, if any.
         pub(super) fn room(&self) -> Option<Room> {
             self.timeline.obj().map(|timeline| timeline.room())
@@ -591,15 +934,39 @@ mod imp {
         fn grouping_model(&self) -> &GroupingListModel {
             self.grouping_model.get_or_init(|| {
                 GroupingListModel::new(|lhs, rhs| {
-                    lhs.downcast_ref::<Event>()
-                        .is_some_and(Event::is_state_group_event)
-                        && rhs
-                            .downcast_ref::<Event>()
-                            .is_some_and(Event::is_state_group_event)
+                    let (Some(lhs), Some(rhs)) =
+                        (lhs.downcast_ref::<Event>(), rhs.downcast_ref::<Event>())
+                    else {
+                        return false;
+                    };
+
+                    // Group state events, and events that could not be decrypted so a gap in
+                    // the keys does not flood the history.
+                    (lhs.is_state_group_event() && rhs.is_state_group_event())
+                        || (lhs.is_unable_to_decrypt() && rhs.is_unable_to_decrypt())
                 })
             })
         }
 
+        /// Whether the given group of state events should be expanded.
+        ///
+        /// Large groups are collapsed by default, unless the user expanded
+        /// them while the room is open.
+        fn is_state_group_expanded(&self, group: &GroupingListGroup) -> bool {
+            self.state_groups_expanded
+                .borrow()
+                .get(group)
+                .copied()
+                .unwrap_or_else(|| group.n_items() <= STATE_GROUP_EXPANDED_MAX_EVENTS)
+        }
+
+        /// Remember whether the given group of state events is expanded.
+        pub(super) fn set_state_group_expanded(&self, group: &GroupingListGroup, expanded: bool) {
+            self.state_groups_expanded
+                .borrow_mut()
+                .insert(group.clone(), expanded);
+        }
+
         /// Bind the given `GtkListItem` to its item.
         fn bind_list_item_to_item(&self, list_item: &gtk::ListItem) {
             let Some(item) = list_item.item() else {
@@ -608,14 +975,31 @@ mod imp {
                 return;
             };
 
+            // Rows presenting events are focusable themselves, to handle keyboard
+            // navigation of the focused event.
+            let is_event = item.is::<Event>();
+            list_item.set_focusable(!is_event);
+            list_item.set_selectable(is_event);
+
             if let Some(event) = item.downcast_ref::<Event>() {
                 let child = list_item.child_or_else::<EventRow>(|| EventRow::new(&self.obj()));
                 child.set_event(Some(event.clone()));
             } else if let Some(virtual_item) = item.downcast_ref::<VirtualItem>() {
                 set_virtual_item_child(list_item, virtual_item);
             } else if let Some(group) = item.downcast_ref::<GroupingListGroup>() {
+                if group
+                    .item(0)
+                    .and_downcast_ref::<Event>()
+                    .is_some_and(Event::is_unable_to_decrypt)
+                {
+                    let child = list_item.child_or_default::<UnableToDecryptGroupRow>();
+                    child.set_group(Some(group.clone()));
+                    return;
+                }
+
                 let child = list_item.child_or_default::<StateGroupRow>();
                 child.set_group(Some(group.clone()));
+                child.set_is_expanded(self.is_state_group_expanded(group));
             } else {
                 error!("Could not build widget for unsupported room history item: {item:?}");
             }
@@ -631,8 +1015,15 @@ mod imp {
                     .emit_scroll_child(gtk::ScrollType::End, false);
             } else {
                 self.set_is_auto_scrolling(false);
-                self.set_sticky(is_at_bottom);
+                self.set_sticky(
+                    is_at_bottom
+                        && self
+                            .timeline
+                            .obj()
+                            .is_none_or(|timeline| timeline.has_reached_end()),
+                );
                 self.update_scroll_btn();
+                self.update_related_event_chip();
 
                 // Remove the typing row if the user scrolls up.
                 if !is_at_bottom && let Some(timeline) = self.timeline.obj() {
@@ -681,7 +1072,6 @@ mod imp {
         }
 
         /// Scroll to the bottom of the timeline.
-        #[template_callback]
         fn scroll_down(&self) {
             if self.is_at_bottom() {
                 // Nothing to do.
@@ -717,7 +1107,19 @@ mod imp {
 
         /// Update the visibility of the scroll button.
         fn update_scroll_btn(&self) {
-            let is_at_bottom = self.is_at_bottom();
+            let is_at_bottom = self.is_at_bottom()
+                && self
+                    .timeline
+                    .obj()
+                    .is_none_or(|timeline| timeline.has_reached_end());
+
+            let transition_type = if Application::default().system_settings().reduce_motion() {
+                gtk::RevealerTransitionType::None
+            } else {
+                gtk::RevealerTransitionType::Crossfade
+            };
+            self.scroll_btn_revealer
+                .set_transition_type(transition_type);
 
             if !is_at_bottom {
                 // Show the revealer so we can reveal the button.
@@ -727,6 +1129,55 @@ mod imp {
             self.scroll_btn_revealer.set_reveal_child(!is_at_bottom);
         }
 
+        /// Update the chip reminding the user of the event related to the
+        /// message in the composer.
+        ///
+        /// The chip is only shown when the related event is not visible.
+        fn update_related_event_chip(&self) {
+            let related_to = self.message_toolbar.current_composer_state().related_to();
+            let label = related_to
+                .filter(|related_to| !self.is_event_visible(&related_to.event_id()))
+                .and_then(|related_to| match related_to {
+                    RelationInfo::Reply(event) => {
+                        let room = self.timeline.obj()?.room();
+                        let sender = room.get_or_create_members().get_or_create(event.sender());
+
+                        Some(gettext_f(
+                            // Translators: Do NOT translate the content between '{' and '}', this
+                            // is a variable name.
+                            "Replying to {user}",
+                            &[("user", &sender.display_name())],
+                        ))
+                    }
+                    RelationInfo::Edit(_) => Some(gettext("Editing a message")),
+                });
+
+            let transition_type = if Application::default().system_settings().reduce_motion() {
+                gtk::RevealerTransitionType::None
+            } else {
+                gtk::RevealerTransitionType::Crossfade
+            };
+            self.related_event_chip_revealer
+                .set_transition_type(transition_type);
+
+            if let Some(label) = &label {
+                self.related_event_chip_label.set_label(label);
+                // Show the revealer so we can reveal the chip.
+                self.related_event_chip_revealer.set_visible(true);
+            }
+
+            self.related_event_chip_revealer
+                .set_reveal_child(label.is_some());
+        }
+
+        /// Scroll to the event related to the message in the composer.
+        #[template_callback]
+        fn scroll_to_related_event(&self) {
+            if let Some(related_to) = self.message_toolbar.current_composer_state().related_to() {
+                self.scroll_to_event(&TimelineEventItemId::EventId(related_to.event_id()));
+            }
+        }
+
         /// Update the room menu for the current state.
         fn update_room_menu(&self) {
             let Some(room) = self.room() else {
@@ -745,6 +1196,15 @@ mod imp {
                 "room-history.forget",
                 matches!(membership, Membership::Leave | Membership::Ban),
             );
+            obj.action_set_enabled(
+                "room-history.select-messages",
+                membership == Membership::Join,
+            );
+            // Only offer to mark as read manually when it is never done automatically.
+            obj.action_set_enabled(
+                "room-history.mark-as-read",
+                membership == Membership::Join && mark_as_read_delay().is_none(),
+            );
 
             self.room_menu.set_visible(true);
         }
@@ -755,16 +1215,52 @@ mod imp {
                 return;
             };
 
-            let visible_child_name = if timeline.is_empty() {
-                if timeline.state() == LoadingState::Error {
-                    "error"
-                } else {
-                    "loading"
-                }
-            } else {
+            let state = timeline.state();
+            let is_loading = state == LoadingState::Loading || self.is_paginating_backwards.get();
+            let is_retrying = self.is_retrying_load.get() && is_loading;
+            let current_page = self.stack.visible_child_name();
+
+            let visible_child_name = if !timeline.is_empty() {
                 "content"
+            } else if is_retrying && current_page.as_deref() == Some("error") {
+                // Keep the current page while retrying, the progress is shown in the button.
+                "error"
+            } else if is_retrying && current_page.as_deref() == Some("empty") {
+                "empty"
+            } else if state == LoadingState::Error {
+                "error"
+            } else if state == LoadingState::Ready && !is_loading {
+                "empty"
+            } else {
+                "loading"
             };
             self.stack.set_visible_child_name(visible_child_name);
+
+            // We can only load older events if we have not reached the start of the timeline.
+            let has_reached_start = timeline.has_reached_start();
+            self.load_older_button.set_visible(!has_reached_start);
+            self.empty.set_description(Some(&if has_reached_start {
+                gettext("There are no messages in this room yet")
+            } else {
+                gettext("Older messages might be available")
+            }));
+
+            for button in [
+                &*self.retry_button,
+                &*self.load_older_button,
+                &*self.load_error_retry_button,
+            ] {
+                button.set_is_loading(is_retrying);
+            }
+
+            // Offer to retry when loading fails while events are shown.
+            let show_load_error = visible_child_name == "content"
+                && (state == LoadingState::Error
+                    || (is_retrying && self.load_error_revealer.reveals_child()));
+            if show_load_error {
+                self.load_error_revealer.set_visible(true);
+            }
+            self.load_error_revealer.set_reveal_child(show_load_error);
         }
 
         /// Whether we need to load more events at the start of the timeline.
@@ -784,11 +1280,34 @@ mod imp {
             adj.value() < adj.page_size() * 2.0
         }
 
+        /// Whether we need to load more events at the end of the timeline.
+        fn needs_more_events_at_the_end(&self) -> bool {
+            if self
+                .timeline
+                .obj()
+                .is_none_or(|timeline| timeline.has_reached_end())
+            {
+                return false;
+            }
+
+            // Load more messages when the user gets close to the bottom of the known room
+            // history. Use the page size twice to detect if the user gets close to
+            // the bottom.
+            let adj = self
+                .listview
+                .vadjustment()
+                .expect("GtkListView has a vadjustment");
+            adj.upper() - adj.value() - adj.page_size() < adj.page_size() * 2.0
+        }
+
         /// Load more events in the history if needed.
         fn load_more_events_if_needed(&self) {
             if self.needs_more_events_at_the_start() {
                 self.load_more_events_at_the_start();
             }
+            if self.needs_more_events_at_the_end() {
+                self.load_more_events_at_the_end();
+            }
         }
 
         /// Load more events at the beginning of the history.
@@ -797,6 +1316,10 @@ mod imp {
                 return;
             };
 
+            // The timeline only updates its state once the pagination has started, so we
+            // track it to avoid presenting the history as empty in the meantime.
+            self.is_paginating_backwards.set(true);
+
             spawn!(clone!(
                 #[weak(rename_to = imp)]
                 self,
@@ -816,16 +1339,81 @@ mod imp {
                             }
                         ))
                         .await;
+
+                    imp.is_paginating_backwards.set(false);
+                    imp.update_view();
                 }
             ));
         }
 
+        /// Load more events at the end of the history.
+        fn load_more_events_at_the_end(&self) {
+            let Some(timeline) = self.timeline.obj() else {
+                return;
+            };
+
+            spawn!(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    timeline
+                        .paginate_forwards(clone!(
+                            #[weak]
+                            imp,
+                            #[upgrade_or]
+                            ControlFlow::Break(()),
+                            move || {
+                                if imp.needs_more_events_at_the_end() {
+                                    ControlFlow::Continue(())
+                                } else {
+                                    ControlFlow::Break(())
+                                }
+                            }
+                        ))
+                        .await;
+                }
+            ));
+        }
+
+        /// Go to the latest events of the room.
+        ///
+        /// If the end of the timeline was not reached, load all the events up
+        /// to the end first.
+        #[template_callback]
+        async fn go_to_latest(&self) {
+            let Some(timeline) = self.timeline.obj() else {
+                return;
+            };
+
+            if !timeline.has_reached_end() {
+                timeline
+                    .paginate_forwards(|| ControlFlow::Continue(()))
+                    .await;
+            }
+
+            if self.timeline.obj().is_some_and(|t| t == timeline) {
+                self.scroll_down();
+            }
+        }
+
         /// Load more events in the history, regardless of if we need them.
         ///
-        /// This should only be used to try to fix timeline loading errors.
+        /// This should only be used to try to fix timeline loading errors, or
+        /// when no events are visible.
         #[template_callback]
         fn load_more_events(&self) {
+            let Some(timeline) = self.timeline.obj() else {
+                return;
+            };
+
+            self.is_retrying_load.set(true);
             self.load_more_events_at_the_start();
+
+            if !timeline.has_reached_end() {
+                self.load_more_events_at_the_end();
+            }
+
+            self.update_view();
         }
 
         /// Scroll to the event with the given identifier.
@@ -856,6 +1444,74 @@ mod imp {
                 && self.obj().is_mapped()
         }
 
+        /// Queue the announcement of the given new live messages to assistive
+        /// technologies.
+        ///
+        /// The announcement is delayed to group the messages received in a
+        /// burst.
+        fn queue_live_messages_announcement(&self, latest: &Event, count: u32) {
+            if !self.is_active() {
+                return;
+            }
+
+            {
+                let mut pending_announcement = self.pending_announcement.borrow_mut();
+                let pending_count = pending_announcement
+                    .as_ref()
+                    .map_or(0, |(_, pending_count)| *pending_count);
+                *pending_announcement = Some((latest.clone(), pending_count.saturating_add(count)));
+            }
+
+            if self.announcement_timeout.borrow().is_some() {
+                return;
+            }
+
+            self.announcement_timeout
+                .replace(Some(glib::timeout_add_local_once(
+                    ANNOUNCEMENT_TIMEOUT,
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move || {
+                            imp.announce_live_messages();
+                        }
+                    ),
+                )));
+        }
+
+        /// Announce the pending new live messages to assistive technologies.
+        fn announce_live_messages(&self) {
+            self.announcement_timeout.take();
+
+            let Some((latest, count)) = self.pending_announcement.take() else {
+                return;
+            };
+            let Some(summary) = latest.accessible_summary() else {
+                return;
+            };
+
+            if !self.is_active() {
+                return;
+            }
+
+            let message = if count == 1 {
+                summary
+            } else {
+                ngettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}',
+                    // this is a variable name. `{latest}` is the sender and a preview of
+                    // the latest message.
+                    "{count} new message. Latest: {latest}",
+                    "{count} new messages. Latest: {latest}",
+                    count,
+                    &[("count", &count.to_string()), ("latest", &summary)],
+                )
+            };
+
+            self.obj()
+                .announce(&message, gtk::AccessibleAnnouncementPriority::Medium);
+        }
+
         /// Trigger the process to update read receipts.
         fn trigger_read_receipts_update(&self) {
             let Some(timeline) = self.timeline.obj() else {
@@ -866,6 +1522,9 @@ mod imp {
                 if let Some(source_id) = self.scroll_timeout.take() {
                     source_id.remove();
                 }
+                if let Some(source_id) = self.receipt_timeout.take() {
+                    source_id.remove();
+                }
                 if let Some(source_id) = self.read_timeout.take() {
                     source_id.remove();
                 }
@@ -890,9 +1549,15 @@ mod imp {
         }
 
         /// Update the read receipts.
+        ///
+        /// The read receipt is sent after the delay to mark messages as read,
+        /// if they should be marked as read automatically.
         fn update_read_receipts(&self) {
             self.scroll_timeout.take();
 
+            if let Some(source_id) = self.receipt_timeout.take() {
+                source_id.remove();
+            }
             if let Some(source_id) = self.read_timeout.take() {
                 source_id.remove();
             }
@@ -901,8 +1566,13 @@ mod imp {
                 return;
             }
 
+            let Some(delay) = mark_as_read_delay() else {
+                // The user marks messages as read manually.
+                return;
+            };
+
             self.read_timeout.replace(Some(glib::timeout_add_local_once(
-                READ_TIMEOUT,
+                READ_TIMEOUT.max(delay),
                 clone!(
                     #[weak(rename_to = imp)]
                     self,
@@ -912,6 +1582,30 @@ mod imp {
                 ),
             )));
 
+            if delay.is_zero() {
+                self.send_read_receipt();
+            } else {
+                self.receipt_timeout
+                    .replace(Some(glib::timeout_add_local_once(
+                        delay,
+                        clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move || {
+                                imp.receipt_timeout.take();
+                                imp.send_read_receipt();
+                            }
+                        ),
+                    )));
+            }
+        }
+
+        /// Send a read receipt for the current position in the room history.
+        fn send_read_receipt(&self) {
+            if !self.is_active() {
+                return;
+            }
+
             let Some(position) = self.receipt_position() else {
                 return;
             };
@@ -927,6 +1621,10 @@ mod imp {
         }
 
         /// Update the read marker.
+        ///
+        /// The read marker is the position of the "New Messages" divider, so
+        /// to keep the divider where it was when the room was opened, it is
+        /// only sent when the room is left.
         fn update_read_marker(&self) {
             self.read_timeout.take();
 
@@ -938,14 +1636,35 @@ mod imp {
                 return;
             };
 
-            spawn!(clone!(
-                #[weak(rename_to = imp)]
-                self,
-                async move {
-                    let Some(room) = imp.room() else { return };
-                    room.send_receipt(ReceiptType::FullyRead, position).await;
-                }
-            ));
+            self.pending_read_marker.replace(Some(position));
+        }
+
+        /// Mark all the messages of the current room as read.
+        fn mark_as_read(&self) {
+            let Some(room) = self.room() else {
+                return;
+            };
+
+            self.pending_read_marker.replace(Some(ReceiptPosition::End));
+
+            spawn!(async move {
+                room.send_receipt(ReceiptType::Read, ReceiptPosition::End)
+                    .await;
+            });
+        }
+
+        /// Send the pending read marker of the current room, if any.
+        fn send_pending_read_marker(&self) {
+            let Some(position) = self.pending_read_marker.take() else {
+                return;
+            };
+            let Some(room) = self.room() else {
+                return;
+            };
+
+            spawn!(async move {
+                room.send_receipt(ReceiptType::FullyRead, position).await;
+            });
         }
 
         /// The position where a receipt should point to.
@@ -959,35 +1678,46 @@ mod imp {
             Some(position)
         }
 
-        /// Get the ID of the last visible event in the room history.
-        fn last_visible_event_id(&self) -> Option<OwnedEventId> {
+        /// Whether the given item of the list view is in the visible part of
+        /// the room history.
+        fn is_item_in_view(&self, item: &gtk::Widget) -> bool {
             let listview = &*self.listview;
-            let mut child = listview.last_child();
             // The visible part of the listview spans between 0 and max.
             let max = listview.height() as f32;
 
+            // Vertical position of the top of the item.
+            let top_pos = item
+                .compute_point(listview, &graphene::Point::new(0.0, 0.0))
+                .unwrap()
+                .y();
+            // Vertical position of the bottom of the item.
+            let bottom_pos = item
+                .compute_point(listview, &graphene::Point::new(0.0, item.height() as f32))
+                .unwrap()
+                .y();
+
+            let top_in_view = top_pos > 0.0 && top_pos <= max;
+            let bottom_in_view = bottom_pos > 0.0 && bottom_pos <= max;
+            // If a message is too big and takes more space than the current view.
+            let content_in_view = top_pos <= max && bottom_pos > 0.0;
+            top_in_view || bottom_in_view || content_in_view
+        }
+
+        /// The event presented by the given item of the list view, if any.
+        fn item_event(item: &gtk::Widget) -> Option<Event> {
+            item.first_child()
+                .and_downcast::<EventRow>()
+                .and_then(|row| row.event())
+        }
+
+        /// Get the ID of the last visible event in the room history.
+        fn last_visible_event_id(&self) -> Option<OwnedEventId> {
+            let mut child = self.listview.last_child();
+
             while let Some(item) = child {
-                // Vertical position of the top of the item.
-                let top_pos = item
-                    .compute_point(listview, &graphene::Point::new(0.0, 0.0))
-                    .unwrap()
-                    .y();
-                // Vertical position of the bottom of the item.
-                let bottom_pos = item
-                    .compute_point(listview, &graphene::Point::new(0.0, item.height() as f32))
-                    .unwrap()
-                    .y();
-
-                let top_in_view = top_pos > 0.0 && top_pos <= max;
-                let bottom_in_view = bottom_pos > 0.0 && bottom_pos <= max;
-                // If a message is too big and takes more space than the current view.
-                let content_in_view = top_pos <= max && bottom_pos > 0.0;
-                if (top_in_view || bottom_in_view || content_in_view)
-                    && let Some(event_id) = item
-                        .first_child()
-                        .and_downcast::<EventRow>()
-                        .and_then(|row| row.event())
-                        .and_then(|event| event.event_id())
+                if self.is_item_in_view(&item)
+                    && let Some(event_id) =
+                        Self::item_event(&item).and_then(|event| event.event_id())
                 {
                     return Some(event_id);
                 }
@@ -998,16 +1728,53 @@ mod imp {
             None
         }
 
+        /// Whether the event with the given ID is in the visible part of the
+        /// room history.
+        fn is_event_visible(&self, event_id: &EventId) -> bool {
+            let mut child = self.listview.first_child();
+
+            while let Some(item) = child {
+                if Self::item_event(&item)
+                    .is_some_and(|event| event.event_id().as_deref() == Some(event_id))
+                {
+                    return self.is_item_in_view(&item);
+                }
+
+                child = item.next_sibling();
+            }
+
+            false
+        }
+
         /// Leave the room.
         async fn leave(&self) {
             let Some(room) = self.room() else {
                 return;
             };
 
-            if confirm_leave_room_dialog(&room, &*self.obj())
-                .await
-                .is_none()
-            {
+            let obj = self.obj();
+            let permissions = room.permissions();
+            let own_role = permissions.role(permissions.own_power_level());
+
+            // Members with a privileged role should be warned about the consequences of
+            // leaving.
+            let is_admin = matches!(own_role, MemberRole::Administrator | MemberRole::Creator);
+            let is_last_admin = is_admin && !permissions.is_own_power_level_shared().await;
+            let can_moderate = is_admin || own_role == MemberRole::Moderator;
+            let is_public = room.join_rule().value() == JoinRuleValue::Public;
+
+            if is_last_admin || (can_moderate && is_public) {
+                match confirm_leave_privileged_room_dialog(&room, is_last_admin, &*obj).await {
+                    Some(ConfirmLeavePrivilegedRoomResponse::Leave) => {}
+                    Some(ConfirmLeavePrivilegedRoomResponse::PromoteMember) => {
+                        self.open_room_details(room_details::InitialView::Subpage(
+                            room_details::SubpageName::Permissions,
+                        ));
+                        return;
+                    }
+                    None => return,
+                }
+            } else if confirm_leave_room_dialog(&room, &*obj).await.is_none() {
                 return;
             }
 
@@ -1017,7 +1784,7 @@ mod imp {
                 .is_err()
             {
                 toast!(
-                    self.obj(),
+                    obj,
                     gettext(
                         // Translators: Do NOT translate the content between '{' and '}', this is a variable name.
                         "Could not leave {room}",
@@ -1138,9 +1905,269 @@ mod imp {
                 MembershipListKind::Knock,
             ));
         }
+
+        /// Set whether the user is selecting events.
+        ///
+        /// The selection is cleared when the selection mode changes.
+        fn set_is_selection_mode(&self, is_selection_mode: bool) {
+            if self.is_selection_mode.get() == is_selection_mode {
+                return;
+            }
+
+            self.selected_events.borrow_mut().clear();
+            self.is_selection_mode.set(is_selection_mode);
+
+            let obj = self.obj();
+            obj.notify_is_selection_mode();
+            self.selection_changed();
+        }
+
+        /// Whether the given event is selected.
+        pub(super) fn is_event_selected(&self, event: &Event) -> bool {
+            self.selected_events.borrow().contains(event)
+        }
+
+        /// Set whether the given event is selected.
+        pub(super) fn set_event_selected(&self, event: &Event, selected: bool) {
+            if !self.is_selection_mode.get() || self.is_event_selected(event) == selected {
+                return;
+            }
+
+            if selected {
+                self.selected_events.borrow_mut().push(event.clone());
+            } else {
+                self.selected_events.borrow_mut().retain(|e| e != event);
+            }
+
+            self.selection_changed();
+        }
+
+        /// Handle when the selection changed.
+        fn selection_changed(&self) {
+            self.update_selection_bar();
+            self.obj().emit_by_name::<()>("selection-changed", &[]);
+        }
+
+        /// Update the bar to act on the selected events.
+        fn update_selection_bar(&self) {
+            let selected_events = self.selected_events.borrow();
+            let n = u32::try_from(selected_events.len()).unwrap_or(u32::MAX);
+
+            let label = if n == 0 {
+                gettext("Select Messages")
+            } else {
+                ngettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}',
+                    // this is a variable name.
+                    "{n} message selected",
+                    "{n} messages selected",
+                    n,
+                    &[("n", &n.to_string())],
+                )
+            };
+            self.selection_label.set_label(&label);
+
+            let can_redact_all = selected_events.iter().all(can_redact_event);
+            self.remove_selected_button
+                .set_sensitive(n > 0 && can_redact_all);
+            let remove_tooltip = (!can_redact_all)
+                .then(|| gettext("You are not allowed to remove some of the selected messages"));
+            self.remove_selected_button
+                .set_tooltip_text(remove_tooltip.as_deref());
+
+            self.report_selected_button.set_sensitive(n > 0);
+        }
+
+        /// Only keep the selected events with the given IDs.
+        fn retain_selected_events(&self, event_ids: &[&EventId]) {
+            self.selected_events.borrow_mut().retain(|event| {
+                event
+                    .event_id()
+                    .is_some_and(|event_id| event_ids.iter().any(|id| **id == *event_id))
+            });
+            self.selection_changed();
+        }
+
+        /// Leave the selection mode.
+        #[template_callback]
+        fn leave_selection_mode(&self) {
+            self.set_is_selection_mode(false);
+        }
+
+        /// Remove the selected events.
+        #[template_callback]
+        async fn remove_selected_events(&self) {
+            let Some(room) = self.room() else {
+                return;
+            };
+
+            let own_user_id = room.own_member().user_id().clone();
+            let (event_ids, as_moderator) = {
+                let selected_events = self.selected_events.borrow();
+                let events = selected_events
+                    .iter()
+                    .filter(|event| can_redact_event(event))
+                    .collect::<Vec<_>>();
+                let as_moderator = events.iter().any(|event| event.sender_id() != own_user_id);
+                let event_ids = events
+                    .into_iter()
+                    .filter_map(Event::event_id)
+                    .collect::<Vec<_>>();
+
+                (event_ids, as_moderator)
+            };
+            if event_ids.is_empty() {
+                return;
+            }
+
+            let obj = self.obj();
+
+            let Some(response) =
+                confirm_redact_events_dialog(event_ids.len(), as_moderator, &*obj).await
+            else {
+                return;
+            };
+
+            match room.redact(&event_ids, response.reason).await {
+                Ok(()) => self.set_is_selection_mode(false),
+                Err(failed) => {
+                    let n = u32::try_from(failed.len()).unwrap_or(u32::MAX);
+                    toast!(
+                        obj,
+                        ngettext(
+                            // Translators: Do NOT translate the content between '{' and '}',
+                            // this is a variable name.
+                            "Could not remove 1 message, it is still selected",
+                            "Could not remove {n} messages, they are still selected",
+                            n,
+                        ),
+                        n,
+                    );
+
+                    self.retain_selected_events(&failed);
+                }
+            }
+        }
+
+        /// Report the selected events.
+        #[template_callback]
+        async fn report_selected_events(&self) {
+            let Some(room) = self.room() else {
+                return;
+            };
+
+            let event_ids = self
+                .selected_events
+                .borrow()
+                .iter()
+                .filter_map(Event::event_id)
+                .collect::<Vec<_>>();
+            if event_ids.is_empty() {
+                return;
+            }
+
+            let n = u32::try_from(event_ids.len()).unwrap_or(u32::MAX);
+            let obj = self.obj();
+
+            // Ask the user to confirm, and provide optional reason.
+            let reason_entry = adw::EntryRow::builder()
+                .title(gettext("Reason (optional)"))
+                .build();
+            let list_box = gtk::ListBox::builder()
+                .css_classes(["boxed-list"])
+                .margin_top(6)
+                .accessible_role(gtk::AccessibleRole::Group)
+                .build();
+            list_box.append(&reason_entry);
+
+            let confirm_dialog = adw::AlertDialog::builder()
+                .default_response("cancel")
+                .heading(ngettext("Report Message?", "Report Messages?", n))
+                .body(ngettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}',
+                    // this is a variable name.
+                    "Reporting a message will send its unique ID to the administrator of your homeserver. The administrator will not be able to see the content of the message if it is encrypted or redacted.",
+                    "Reporting these {n} messages will send their unique IDs to the administrator of your homeserver. The administrator will not be able to see the content of the messages if they are encrypted or redacted.",
+                    n,
+                    &[("n", &n.to_string())],
+                ))
+                .extra_child(&list_box)
+                .build();
+            confirm_dialog.add_responses(&[
+                ("cancel", &gettext("Cancel")),
+                // Translators: This is a verb, as in 'Report Messages'.
+                ("report", &gettext("Report")),
+            ]);
+            confirm_dialog.set_response_appearance("report", adw::ResponseAppearance::Destructive);
+
+            if confirm_dialog.choose_future(Some(&*obj)).await != "report" {
+                return;
+            }
+
+            let reason = Some(reason_entry.text())
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+            let events = event_ids
+                .into_iter()
+                .map(|event_id| (event_id, reason.clone()))
+                .collect::<Vec<_>>();
+
+            match room.report_events(&events).await {
+                Ok(()) => {
+                    toast!(obj, ngettext("Message reported", "Messages reported", n));
+                    self.set_is_selection_mode(false);
+                }
+                Err(failed) => {
+                    let n = u32::try_from(failed.len()).unwrap_or(u32::MAX);
+                    toast!(
+                        obj,
+                        ngettext(
+                            // Translators: Do NOT translate the content between '{' and '}',
+                            // this is a variable name.
+                            "Could not report 1 message, it is still selected",
+                            "Could not report {n} messages, they are still selected",
+                            n,
+                        ),
+                        n,
+                    );
+
+                    self.retain_selected_events(&failed);
+                }
+            }
+        }
+
+        /// Open the settings to change whether our read receipts are private.
+        #[template_callback]
+        fn open_read_receipts_settings(&self) {
+            let Some(session) = self.room().and_then(|room| room.session()) else {
+                return;
+            };
+
+            let dialog = AccountSettings::new(&session);
+            dialog.set_visible_page_name("safety");
+            dialog.present(Some(&*self.obj()));
+        }
+
+        /// Retry to send the messages that failed to be sent.
+        #[template_callback]
+        fn retry_send_queue(&self) {
+            if let Some(room) = self.room() {
+                room.retry_send_queue();
+            }
+        }
     }
 }
 
+/// The delay before marking messages as read, from the settings.
+///
+/// Returns `None` if messages should never be marked as read automatically.
+fn mark_as_read_delay() -> Option<Duration> {
+    let delay = Application::default()
+        .settings()
+        .int(SETTINGS_KEY_MARK_AS_READ_DELAY);
+    u64::try_from(delay).ok().map(Duration::from_secs)
+}
+
 glib::wrapper! {
     /// A view that displays the timeline of a room and ways to send new messages.
     pub struct RoomHistory(ObjectSubclass<imp::RoomHistory>)
@@ -1179,10 +2206,58 @@ impl RoomHistory {
         self.imp().message_toolbar.handle_paste_action();
     }
 
+    /// Scroll to the event with the given ID in the current timeline.
+    ///
+    /// More events are loaded until the event is found.
+    pub(crate) fn scroll_to_event(&self, event_id: OwnedEventId) {
+        self.imp().scroll_to_event_id(event_id);
+    }
+
+    /// Remember whether the given group of state events is expanded, while
+    /// the current room is open.
+    pub(super) fn set_state_group_expanded(&self, group: &GroupingListGroup, expanded: bool) {
+        self.imp().set_state_group_expanded(group, expanded);
+    }
+
     /// The context menu for rows presenting an [`Event`].
     fn event_context_menu(&self) -> &EventActionsContextMenu {
         self.imp().event_context_menu()
     }
+
+    /// Whether the given event is selected.
+    pub(super) fn is_event_selected(&self, event: &Event) -> bool {
+        self.imp().is_event_selected(event)
+    }
+
+    /// Set whether the given event is selected.
+    pub(super) fn set_event_selected(&self, event: &Event, selected: bool) {
+        self.imp().set_event_selected(event, selected);
+    }
+
+    /// Connect to the signal emitted when the selection mode or the selected
+    /// events changed.
+    pub(super) fn connect_selection_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "selection-changed",
+            true,
+            glib::closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
+}
+
+/// Whether our own user is allowed to redact the given event.
+fn can_redact_event(event: &Event) -> bool {
+    let room = event.room();
+    let permissions = room.permissions();
+    let is_from_own_user = event.sender_id() == *room.own_member().user_id();
+
+    event.event_id().is_some()
+        && ((is_from_own_user && permissions.can_redact_own()) || permissions.can_redact_other())
 }
 
 /// Set the proper child of the given `GtkListItem` for the given
@@ -6,7 +6,7 @@ use crate::{
     ngettext_f,
     prelude::*,
     session::{Event, Room},
-    session_view::room_history::ReadReceiptsList,
+    session_view::room_history::{ReadReceiptsList, RoomHistory},
     utils::{BoundObject, GroupingListGroup, key_bindings},
 };
 
@@ -176,7 +176,18 @@ mod imp {
         /// Toggle whether this group is expanded.
         #[template_callback]
         fn toggle_expanded(&self) {
-            self.set_is_expanded(!self.is_expanded.get());
+            let is_expanded = !self.is_expanded.get();
+            self.set_is_expanded(is_expanded);
+
+            // Remember the choice of the user, in case this row is recycled.
+            if let Some(group) = self.group.obj()
+                && let Some(room_history) = self
+                    .obj()
+                    .ancestor(RoomHistory::static_type())
+                    .and_downcast::<RoomHistory>()
+            {
+                room_history.set_state_group_expanded(&group, is_expanded);
+            }
         }
 
         /// Handle when items changed in the underlying group.
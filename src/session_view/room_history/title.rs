@@ -1,6 +1,8 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{glib, glib::clone};
+use tracing::error;
 
+use super::RoomHistoryTopicDialog;
 use crate::{prelude::*, session::Room, utils::BoundObjectWeakRef};
 
 mod imp {
@@ -37,6 +39,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
 
             klass.set_css_name("room-title");
         }
@@ -57,6 +60,7 @@ mod imp {
 
     impl BinImpl for RoomHistoryTitle {}
 
+    #[gtk::template_callbacks]
     impl RoomHistoryTitle {
         /// Set the room to present the title of.
         fn set_room(&self, room: Option<Room>) {
@@ -146,6 +150,27 @@ mod imp {
             self.button.set_valign(button_valign);
             self.subtitle_label.set_visible(has_subtitle);
         }
+
+        /// Open the dialog presenting the full topic of the room.
+        #[template_callback]
+        fn open_topic_dialog(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+            let obj = self.obj();
+
+            let dialog = RoomHistoryTopicDialog::new(&room);
+            dialog.connect_open_room_details(clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    if obj.activate_action("room-history.details", None).is_err() {
+                        error!("Could not activate `room-history.details` action");
+                    }
+                }
+            ));
+            dialog.present(Some(&*obj));
+        }
     }
 }
 
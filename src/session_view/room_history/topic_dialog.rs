@@ -0,0 +1,272 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+
+use crate::{
+    Window,
+    components::{AtRoom, CopyableRow, LabelWithWidgets, ToastableDialog},
+    ngettext_f,
+    prelude::*,
+    session::Room,
+    utils::{BoundObjectWeakRef, links, matrix::MatrixIdUri, string::Linkifier},
+};
+
+mod imp {
+    use std::{cell::RefCell, sync::LazyLock};
+
+    use glib::subclass::{InitializingObject, Signal};
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/room_history/topic_dialog.ui")]
+    #[properties(wrapper_type = super::RoomHistoryTopicDialog)]
+    pub struct RoomHistoryTopicDialog {
+        #[template_child]
+        topic_label: TemplateChild<LabelWithWidgets>,
+        #[template_child]
+        no_topic_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        members_count_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        addresses_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        addresses_list: TemplateChild<gtk::ListBox>,
+        /// The room to present the topic of.
+        #[property(get, set = Self::set_room, explicit_notify, nullable)]
+        room: BoundObjectWeakRef<Room>,
+        aliases_handler: RefCell<Option<glib::SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RoomHistoryTopicDialog {
+        const NAME: &'static str = "RoomHistoryTopicDialog";
+        type Type = super::RoomHistoryTopicDialog;
+        type ParentType = ToastableDialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            LabelWithWidgets::ensure_type();
+
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for RoomHistoryTopicDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: LazyLock<Vec<Signal>> =
+                LazyLock::new(|| vec![Signal::builder("open-room-details").build()]);
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // Show Matrix URIs in the app.
+            self.topic_label.connect_activate_link(clone!(
+                #[weak]
+                obj,
+                #[upgrade_or]
+                glib::Propagation::Proceed,
+                move |_, uri| {
+                    let Ok(uri) = MatrixIdUri::parse(uri) else {
+                        return links::handle_activated_external_link(&obj, uri);
+                    };
+                    let Some(parent_window) = obj.root().and_downcast::<Window>() else {
+                        return glib::Propagation::Proceed;
+                    };
+
+                    parent_window.session_view().show_matrix_uri(uri);
+                    obj.close();
+
+                    glib::Propagation::Stop
+                }
+            ));
+        }
+
+        fn dispose(&self) {
+            self.disconnect_signals();
+        }
+    }
+
+    impl WidgetImpl for RoomHistoryTopicDialog {}
+    impl AdwDialogImpl for RoomHistoryTopicDialog {}
+    impl ToastableDialogImpl for RoomHistoryTopicDialog {}
+
+    #[gtk::template_callbacks]
+    impl RoomHistoryTopicDialog {
+        /// Set the room to present the topic of.
+        fn set_room(&self, room: Option<Room>) {
+            if self.room.obj() == room {
+                return;
+            }
+
+            self.disconnect_signals();
+
+            if let Some(room) = room {
+                let topic_handler = room.connect_topic_linkified_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_topic();
+                    }
+                ));
+                let members_count_handler = room.connect_joined_members_count_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_members_count();
+                    }
+                ));
+
+                let aliases_handler = room.aliases().connect_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_addresses();
+                    }
+                ));
+                self.aliases_handler.replace(Some(aliases_handler));
+
+                self.room
+                    .set(&room, vec![topic_handler, members_count_handler]);
+            }
+
+            self.update_topic();
+            self.update_members_count();
+            self.update_addresses();
+            self.obj().notify_room();
+        }
+
+        /// Disconnect the signal handlers of this dialog.
+        fn disconnect_signals(&self) {
+            if let Some(room) = self.room.obj()
+                && let Some(handler) = self.aliases_handler.take()
+            {
+                room.aliases().disconnect(handler);
+            }
+
+            self.room.disconnect_signals();
+        }
+
+        /// Update the topic of the room.
+        fn update_topic(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let Some(topic) = room.topic() else {
+                self.topic_label.set_visible(false);
+                self.no_topic_label.set_visible(true);
+                return;
+            };
+
+            let mut label = String::with_capacity(topic.len());
+            let mut pills = Vec::new();
+            Linkifier::new(&mut label)
+                .detect_mentions(&room, &mut pills, false)
+                .linkify(&topic);
+            label.truncate_end_whitespaces();
+
+            for pill in &pills {
+                if !pill.source().is_some_and(|s| s.is::<AtRoom>()) {
+                    pill.set_activatable(true);
+                }
+            }
+
+            self.topic_label.set_use_markup(true);
+            self.topic_label.set_label_and_widgets(label, pills);
+
+            self.topic_label.set_visible(true);
+            self.no_topic_label.set_visible(false);
+        }
+
+        /// Update the number of members of the room.
+        fn update_members_count(&self) {
+            let Some(room) = self.room.obj() else {
+                return;
+            };
+
+            let count = room.joined_members_count();
+            self.members_count_label.set_label(&count.to_string());
+
+            let n = u32::try_from(count).unwrap_or(u32::MAX);
+            self.members_count_label.set_tooltip_text(Some(&ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}',
+                // this is a variable name.
+                "1 member",
+                "{n} members",
+                n,
+                &[("n", &count.to_string())],
+            )));
+        }
+
+        /// Update the addresses of the room.
+        fn update_addresses(&self) {
+            self.addresses_list.remove_all();
+
+            let Some(room) = self.room.obj() else {
+                self.addresses_box.set_visible(false);
+                return;
+            };
+            let aliases = room.aliases();
+
+            let addresses = aliases
+                .canonical_alias()
+                .into_iter()
+                .chain(aliases.alt_aliases())
+                .collect::<Vec<_>>();
+
+            for address in &addresses {
+                let row = CopyableRow::new();
+                row.set_title(address.as_str());
+                row.set_copy_button_tooltip_text(Some(gettext("Copy address")));
+                row.set_toast_text(Some(gettext("Address copied to clipboard")));
+
+                self.addresses_list.append(&row);
+            }
+
+            self.addresses_box.set_visible(!addresses.is_empty());
+        }
+
+        /// Open the details of the room.
+        #[template_callback]
+        fn open_room_details(&self) {
+            let obj = self.obj();
+            obj.close();
+            obj.emit_by_name::<()>("open-room-details", &[]);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A dialog to show the full topic of a room.
+    pub struct RoomHistoryTopicDialog(ObjectSubclass<imp::RoomHistoryTopicDialog>)
+        @extends gtk::Widget, adw::Dialog, ToastableDialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl RoomHistoryTopicDialog {
+    /// Construct a new `RoomHistoryTopicDialog` for the given room.
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+
+    /// Connect to the signal emitted when the user wants to open the details
+    /// of the room.
+    pub fn connect_open_room_details<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "open-room-details",
+            true,
+            glib::closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
+}
@@ -2,6 +2,7 @@ use adw::{prelude::*, subclass::prelude::*};
 use gtk::{glib, glib::clone};
 
 use crate::{
+    Application,
     components::OverlappingAvatars,
     i18n::{gettext_f, ngettext_f},
     prelude::*,
@@ -23,7 +24,11 @@ mod imp {
         #[template_child]
         avatar_list: TemplateChild<OverlappingAvatars>,
         #[template_child]
+        extra_avatars_label: TemplateChild<gtk::Label>,
+        #[template_child]
         label: TemplateChild<gtk::Label>,
+        #[template_child]
+        typing_dots: TemplateChild<gtk::Box>,
         /// The list of members that are currently typing.
         #[property(get, set = Self::set_list, explicit_notify, nullable)]
         list: BoundObjectWeakRef<TypingList>,
@@ -122,30 +127,70 @@ mod imp {
                 return;
             }
 
-            let label = if n == 1 {
-                let user = list
-                    .item(0)
-                    .and_downcast::<Member>()
-                    .expect("typing list has a member")
-                    .disambiguated_name();
+            let names = list
+                .iter::<glib::Object>()
+                .take(3)
+                .filter_map(|member| member.ok().and_downcast::<Member>())
+                .map(|member| format!("<b>{}</b>", member.disambiguated_name().escape_markup()))
+                .collect::<Vec<_>>();
 
-                gettext_f(
+            let label = match names.as_slice() {
+                [user] => gettext_f(
                     // Translators: Do NOT translate the content between '{' and '}', these are
                     // variable names.
-                    "{user} is typing…",
-                    &[("user", &format!("<b>{user}</b>"))],
-                )
-            } else {
-                ngettext_f(
+                    "{user} is typing",
+                    &[("user", user)],
+                ),
+                [user1, user2] => gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', these are
+                    // variable names.
+                    "{user1} and {user2} are typing",
+                    &[("user1", user1), ("user2", user2)],
+                ),
+                [user1, user2, user3] if n == 3 => gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', these are
+                    // variable names.
+                    "{user1}, {user2} and {user3} are typing",
+                    &[("user1", user1), ("user2", user2), ("user3", user3)],
+                ),
+                [user1, user2, ..] => {
+                    let others = n - 2;
+                    ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}', these
+                        // are variable names.
+                        "{user1}, {user2} and {n} other are typing",
+                        "{user1}, {user2} and {n} others are typing",
+                        others,
+                        &[
+                            ("user1", user1),
+                            ("user2", user2),
+                            ("n", &others.to_string()),
+                        ],
+                    )
+                }
+                _ => ngettext_f(
                     // Translators: Do NOT translate the content between '{' and '}', these are
                     // variable names.
-                    "{n} member is typing…",
-                    "{n} members are typing…",
+                    "{n} member is typing",
+                    "{n} members are typing",
                     n,
                     &[("n", &n.to_string())],
-                )
+                ),
             };
             self.label.set_label(&label);
+
+            let extra_avatars = n.saturating_sub(self.avatar_list.max_avatars());
+            if extra_avatars > 0 {
+                self.extra_avatars_label
+                    .set_label(&format!("+{extra_avatars}"));
+            }
+            self.extra_avatars_label.set_visible(extra_avatars > 0);
+
+            if Application::default().system_settings().reduce_motion() {
+                self.typing_dots.remove_css_class("animated");
+            } else {
+                self.typing_dots.add_css_class("animated");
+            }
         }
     }
 }
@@ -0,0 +1,116 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{glib, glib::clone};
+
+use super::message_row::MessageUnableToDecrypt;
+use crate::{
+    prelude::*,
+    session::Event,
+    utils::{BoundObject, GroupingListGroup},
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, glib::Properties)]
+    #[properties(wrapper_type = super::UnableToDecryptGroupRow)]
+    pub struct UnableToDecryptGroupRow {
+        content: MessageUnableToDecrypt,
+        /// The group of events that could not be decrypted displayed by this
+        /// widget.
+        #[property(get, set = Self::set_group, explicit_notify, nullable)]
+        group: BoundObject<GroupingListGroup>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for UnableToDecryptGroupRow {
+        const NAME: &'static str = "UnableToDecryptGroupRow";
+        type Type = super::UnableToDecryptGroupRow;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_accessible_role(gtk::AccessibleRole::ListItem);
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for UnableToDecryptGroupRow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            obj.add_css_class("room-history-row");
+            self.content.add_css_class("event-content");
+            obj.set_child(Some(&self.content));
+        }
+    }
+
+    impl WidgetImpl for UnableToDecryptGroupRow {}
+    impl BinImpl for UnableToDecryptGroupRow {}
+
+    impl UnableToDecryptGroupRow {
+        /// Set the group presented by this row.
+        fn set_group(&self, group: Option<GroupingListGroup>) {
+            if self.group.obj() == group {
+                return;
+            }
+
+            self.group.disconnect_signals();
+
+            if let Some(group) = group {
+                let items_changed_handler = group.connect_items_changed(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _, _, _| {
+                        imp.update_content();
+                    }
+                ));
+
+                self.group.set(group, vec![items_changed_handler]);
+            }
+
+            self.update_content();
+            self.obj().notify_group();
+        }
+
+        /// Update the content of this row for the current group.
+        fn update_content(&self) {
+            let Some(group) = self.group.obj() else {
+                return;
+            };
+
+            let events = group
+                .iter::<glib::Object>()
+                .filter_map(|item| item.ok().and_downcast::<Event>())
+                .collect::<Vec<_>>();
+            let Some(room) = events.first().map(TimelineItemExt::room) else {
+                return;
+            };
+
+            let messages = events
+                .iter()
+                .filter_map(Event::unable_to_decrypt_message)
+                .collect::<Vec<_>>();
+            self.content.set_messages(&room, &messages);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A row presenting a group of consecutive events that could not be
+    /// decrypted.
+    pub struct UnableToDecryptGroupRow(ObjectSubclass<imp::UnableToDecryptGroupRow>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl UnableToDecryptGroupRow {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl Default for UnableToDecryptGroupRow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -30,6 +30,8 @@ mod imp {
         #[template_child]
         label: TemplateChild<gtk::Label>,
         #[template_child]
+        device_label: TemplateChild<gtk::Label>,
+        #[template_child]
         accept_btn: TemplateChild<LoadingButton>,
         #[template_child]
         cancel_btn: TemplateChild<LoadingButton>,
@@ -171,10 +173,23 @@ mod imp {
                 ));
                 self.accept_btn.set_label(&gettext("Verify"));
                 self.cancel_btn.set_label(&gettext("Decline"));
+
+                // Show the session that sent the request, if it is known.
+                let device_name = verification.other_device_display_name();
+                if let Some(device_name) = &device_name {
+                    self.device_label.set_label(&gettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}', this
+                        // is a variable name.
+                        "From the session “{device_name}”",
+                        &[("device_name", device_name)],
+                    ));
+                }
+                self.device_label.set_visible(device_name.is_some());
             } else {
                 self.label.set_label(&gettext("Verification in progress"));
                 self.accept_btn.set_label(&gettext("Continue"));
                 self.cancel_btn.set_label(&gettext("Cancel"));
+                self.device_label.set_visible(false);
             }
 
             self.revealer.set_reveal_child(true);
@@ -49,6 +49,8 @@ mod imp {
         #[template_child]
         security_banner: TemplateChild<adw::Banner>,
         #[template_child]
+        space_filter_toggle_group: TemplateChild<adw::ToggleGroup>,
+        #[template_child]
         scrolled_window: TemplateChild<gtk::ScrolledWindow>,
         #[template_child]
         listview: TemplateChild<gtk::ListView>,
@@ -70,6 +72,7 @@ mod imp {
         #[property(get, set = Self::set_list_model, explicit_notify, nullable)]
         list_model: glib::WeakRef<SidebarListModel>,
         expr_watch: RefCell<Option<gtk::ExpressionWatch>>,
+        list_model_bindings: RefCell<Vec<glib::Binding>>,
         session_handler: RefCell<Option<glib::SignalHandlerId>>,
         security_handlers: RefCell<Vec<glib::SignalHandlerId>>,
     }
@@ -186,6 +189,9 @@ mod imp {
             if let Some(expr_watch) = self.expr_watch.take() {
                 expr_watch.unwatch();
             }
+            for binding in self.list_model_bindings.take() {
+                binding.unbind();
+            }
 
             if let Some(user) = self.user.take() {
                 let session = user.session();
@@ -293,6 +299,9 @@ mod imp {
             if let Some(expr_watch) = self.expr_watch.take() {
                 expr_watch.unwatch();
             }
+            for binding in self.list_model_bindings.take() {
+                binding.unbind();
+            }
 
             if let Some(list_model) = list_model {
                 let expr_watch = expression::normalize_string(
@@ -300,6 +309,26 @@ mod imp {
                 )
                 .bind(&list_model.string_filter(), "search", None::<&glib::Object>);
                 self.expr_watch.replace(Some(expr_watch));
+
+                // The filter only applies at the root level.
+                let visible_binding = list_model
+                    .bind_property("current-space", &*self.space_filter_toggle_group, "visible")
+                    .transform_to(|_, space: Option<Room>| Some(space.is_none()))
+                    .sync_create()
+                    .build();
+                let show_all_binding = list_model
+                    .bind_property(
+                        "show-all-rooms",
+                        &*self.space_filter_toggle_group,
+                        "active-name",
+                    )
+                    .transform_to(|_, show_all: bool| Some(if show_all { "all" } else { "home" }))
+                    .transform_from(|_, name: Option<String>| Some(name.as_deref() == Some("all")))
+                    .bidirectional()
+                    .sync_create()
+                    .build();
+                self.list_model_bindings
+                    .replace(vec![visible_binding, show_all_binding]);
             }
 
             self.list_model.set(list_model);
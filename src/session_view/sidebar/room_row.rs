@@ -1,17 +1,19 @@
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::{gdk, glib, glib::clone};
 
 use super::SidebarRow;
 use crate::{
+    Application,
     components::Avatar,
     i18n::{gettext_f, ngettext_f},
     prelude::*,
-    session::{HighlightFlags, Room, RoomCategory},
+    session::{HighlightFlags, NotificationsRoomSetting, Room, RoomCategory},
     utils::{BoundObject, TemplateCallbacks},
 };
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     use glib::subclass::InitializingObject;
 
@@ -28,11 +30,26 @@ mod imp {
         #[template_child]
         display_name: TemplateChild<gtk::Label>,
         #[template_child]
+        favorite_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        muted_icon: TemplateChild<gtk::Image>,
+        #[template_child]
         notification_count: TemplateChild<gtk::Label>,
+        #[template_child]
+        space_notification_count: TemplateChild<gtk::Label>,
+        #[template_child]
+        space_unread_dot: TemplateChild<gtk::Image>,
+        #[template_child]
+        preview_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        space_expander: TemplateChild<gtk::ToggleButton>,
         direct_icon: RefCell<Option<gtk::Image>>,
         /// The room represented by this row.
         #[property(get, set = Self::set_room, explicit_notify, nullable)]
         room: BoundObject<Room>,
+        /// Whether to show a preview of the latest message of the room.
+        #[property(get, set = Self::set_show_preview, explicit_notify)]
+        show_preview: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -43,6 +60,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
             TemplateCallbacks::bind_template_callbacks(klass);
 
             klass.set_css_name("room");
@@ -85,12 +103,19 @@ mod imp {
                 }
             ));
             self.obj().add_controller(drag);
+
+            Application::default()
+                .settings()
+                .bind("sidebar-message-preview", &*self.obj(), "show-preview")
+                .get()
+                .build();
         }
     }
 
     impl WidgetImpl for SidebarRoomRow {}
     impl BinImpl for SidebarRoomRow {}
 
+    #[gtk::template_callbacks]
     impl SidebarRoomRow {
         /// Set the room represented by this row.
         fn set_room(&self, room: Option<Room>) {
@@ -101,6 +126,9 @@ mod imp {
             self.room.disconnect_signals();
 
             if let Some(room) = room {
+                // The room is visible, so we need its info now.
+                room.prioritize_room_info_init();
+
                 let highlight_handler = room.connect_highlight_notify(clone!(
                     #[weak(rename_to = imp)]
                     self,
@@ -113,8 +141,24 @@ mod imp {
                     self,
                     move |_| {
                         imp.update_direct_icon();
+                        imp.update_preview();
+                    }
+                ));
+                let latest_event_handler = room.connect_latest_event_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_preview();
                     }
                 ));
+                let notification_preview_handler =
+                    room.connect_notification_preview_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_preview();
+                        }
+                    ));
                 let name_handler = room.connect_display_name_notify(clone!(
                     #[weak(rename_to = imp)]
                     self,
@@ -134,6 +178,33 @@ mod imp {
                     self,
                     move |_| {
                         imp.update_display_name();
+                        imp.update_favorite_icon();
+                        imp.update_space_badge();
+                    }
+                ));
+
+                let notifications_setting_handler =
+                    room.connect_notifications_setting_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_muted_icon();
+                        }
+                    ));
+
+                let space_notification_count_handler = room
+                    .connect_space_notification_count_notify(clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_| {
+                            imp.update_space_badge();
+                        }
+                    ));
+                let space_highlight_handler = room.connect_space_highlight_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_space_badge();
                     }
                 ));
 
@@ -142,9 +213,14 @@ mod imp {
                     vec![
                         highlight_handler,
                         direct_handler,
+                        latest_event_handler,
+                        notification_preview_handler,
                         name_handler,
                         notifications_count_handler,
                         category_handler,
+                        notifications_setting_handler,
+                        space_notification_count_handler,
+                        space_highlight_handler,
                     ],
                 );
 
@@ -154,9 +230,90 @@ mod imp {
             self.update_display_name();
             self.update_highlight();
             self.update_direct_icon();
+            self.update_favorite_icon();
+            self.update_muted_icon();
+            self.update_space_badge();
+            self.update_space_expander();
+            self.update_preview();
             self.obj().notify_room();
         }
 
+        /// Update the state of the button to expand the space.
+        fn update_space_expander(&self) {
+            let is_expanded = self
+                .room
+                .obj()
+                .filter(Room::is_space)
+                .and_then(|room| {
+                    let session = room.session()?;
+                    Some(session.settings().is_space_expanded(room.room_id()))
+                })
+                .unwrap_or_default();
+
+            self.space_expander.set_active(is_expanded);
+        }
+
+        /// Toggle whether the space is expanded.
+        #[template_callback]
+        fn toggle_space_expanded(&self) {
+            let is_expanded = self.space_expander.is_active();
+
+            let tooltip = if is_expanded {
+                gettext("Hide Rooms")
+            } else {
+                gettext("Show Rooms")
+            };
+            self.space_expander.set_tooltip_text(Some(&tooltip));
+            self.space_expander
+                .update_state(&[gtk::accessible::State::Expanded(Some(is_expanded))]);
+
+            let Some(room) = self.room.obj().filter(Room::is_space) else {
+                return;
+            };
+            let Some(section) = self
+                .parent_row()
+                .and_then(|row| row.sidebar())
+                .and_then(|sidebar| sidebar.list_model())
+                .and_then(|list_model| {
+                    list_model
+                        .item_list()
+                        .section_from_room_category(RoomCategory::Space)
+                })
+            else {
+                return;
+            };
+
+            section.set_space_expanded(&room, is_expanded);
+        }
+
+        /// Set whether to show a preview of the latest message of the room.
+        fn set_show_preview(&self, show: bool) {
+            if self.show_preview.get() == show {
+                return;
+            }
+
+            self.show_preview.set(show);
+            self.update_preview();
+            self.obj().notify_show_preview();
+        }
+
+        /// Update the preview of the latest message of the room.
+        fn update_preview(&self) {
+            let preview = self
+                .show_preview
+                .get()
+                .then(|| self.room.obj())
+                .flatten()
+                .and_then(|room| {
+                    room.latest_event()?
+                        .sidebar_preview(room.notification_preview(), !room.is_direct())
+                });
+
+            self.preview_label
+                .set_label(preview.as_deref().unwrap_or_default());
+            self.preview_label.set_visible(preview.is_some());
+        }
+
         /// Update the display name of the room according to the current state.
         fn update_display_name(&self) {
             let Some(room) = self.room.obj() else {
@@ -264,6 +421,48 @@ mod imp {
             }
         }
 
+        /// Update the badge with the unread counts of the children of the
+        /// space.
+        fn update_space_badge(&self) {
+            let (notification_count, highlight) = self
+                .room
+                .obj()
+                .filter(|room| room.category() == RoomCategory::Space)
+                .map(|room| (room.space_notification_count(), room.space_highlight()))
+                .unwrap_or_default();
+
+            self.space_notification_count
+                .set_label(&notification_count.to_string());
+            self.space_notification_count
+                .set_visible(notification_count > 0);
+            self.space_unread_dot
+                .set_visible(notification_count == 0 && highlight.contains(HighlightFlags::BOLD));
+
+            if highlight.contains(HighlightFlags::HIGHLIGHT) {
+                self.space_notification_count.add_css_class("highlight");
+            } else {
+                self.space_notification_count.remove_css_class("highlight");
+            }
+        }
+
+        /// Update the visibility of the favorite icon.
+        fn update_favorite_icon(&self) {
+            let is_favorite = self
+                .room
+                .obj()
+                .is_some_and(|room| room.category() == RoomCategory::Favorite);
+            self.favorite_icon.set_visible(is_favorite);
+        }
+
+        /// Update the visibility of the muted icon.
+        fn update_muted_icon(&self) {
+            let is_muted = self
+                .room
+                .obj()
+                .is_some_and(|room| room.notifications_setting() == NotificationsRoomSetting::Mute);
+            self.muted_icon.set_visible(is_muted);
+        }
+
         /// Update the accessibility label of this row.
         fn update_accessibility_label(&self) {
             let Some(parent) = self.obj().parent() else {
@@ -11,8 +11,9 @@ use crate::{
     components::{ContextMenuBin, confirm_leave_room_dialog},
     prelude::*,
     session::{
-        IdentityVerification, ReceiptPosition, Room, RoomCategory, SidebarIconItem,
-        SidebarIconItemType, SidebarSection, TargetRoomCategory, User,
+        IdentityVerification, NotificationsRoomSetting, ReceiptPosition, Room, RoomCategory,
+        RoomMuteDuration, SidebarIconItem, SidebarIconItemType, SidebarSection, TargetRoomCategory,
+        User,
     },
     spawn, spawn_tokio, toast,
     utils::BoundObjectWeakRef,
@@ -35,6 +36,7 @@ mod imp {
         room_handler: RefCell<Option<glib::SignalHandlerId>>,
         room_join_rule_handler: RefCell<Option<glib::SignalHandlerId>>,
         room_is_read_handler: RefCell<Option<glib::SignalHandlerId>>,
+        room_notifications_setting_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -81,6 +83,22 @@ mod imp {
                 move |_, v, _, _| imp.drop_end(v)
             ));
             self.obj().add_controller(drop);
+
+            // Set up shortcuts to change the category of the room.
+            let shortcut_controller = gtk::ShortcutController::new();
+            for (trigger, action) in [
+                ("<Control>d", "action(room-row.toggle-favorite)"),
+                ("<Control><Shift>d", "action(room-row.toggle-lowpriority)"),
+            ] {
+                shortcut_controller.add_shortcut(gtk::Shortcut::new(
+                    Some(
+                        gtk::ShortcutTrigger::parse_string(trigger)
+                            .expect("trigger string should be valid"),
+                    ),
+                    gtk::ShortcutAction::parse_string(action),
+                ));
+            }
+            self.obj().add_controller(shortcut_controller);
         }
 
         fn dispose(&self) {
@@ -91,6 +109,9 @@ mod imp {
                 if let Some(handler) = self.room_is_read_handler.take() {
                     room.disconnect(handler);
                 }
+                if let Some(handler) = self.room_notifications_setting_handler.take() {
+                    room.disconnect(handler);
+                }
             }
         }
     }
@@ -164,6 +185,9 @@ mod imp {
                 if let Some(handler) = self.room_is_read_handler.take() {
                     room.disconnect(handler);
                 }
+                if let Some(handler) = self.room_notifications_setting_handler.take() {
+                    room.disconnect(handler);
+                }
             }
 
             self.item.replace(item.clone());
@@ -212,6 +236,17 @@ mod imp {
                     self.room_is_read_handler
                         .replace(Some(room_is_read_handler));
 
+                    let room_notifications_setting_handler = room
+                        .connect_notifications_setting_notify(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move |_| {
+                                imp.update_context_menu();
+                            }
+                        ));
+                    self.room_notifications_setting_handler
+                        .replace(Some(room_notifications_setting_handler));
+
                     child.set_room(Some(room.clone()));
                 } else if let Some(icon_item) = item.downcast_ref::<SidebarIconItem>() {
                     let child = obj.child_or_default::<SidebarIconItemRow>();
@@ -277,6 +312,7 @@ mod imp {
                         | RoomCategory::Normal
                         | RoomCategory::LowPriority
                         | RoomCategory::Left
+                        | RoomCategory::Space
                 )
             })
         }
@@ -418,6 +454,43 @@ mod imp {
                         .build()]);
                     }
 
+                    action_group.add_action_entries([
+                        gio::ActionEntry::builder("toggle-favorite")
+                            .activate(clone!(
+                                #[weak(rename_to = imp)]
+                                self,
+                                move |_, _, _| {
+                                    if let Some(room) = imp.room() {
+                                        spawn!(async move {
+                                            imp.toggle_room_category(
+                                                &room,
+                                                TargetRoomCategory::Favorite,
+                                            )
+                                            .await;
+                                        });
+                                    }
+                                }
+                            ))
+                            .build(),
+                        gio::ActionEntry::builder("toggle-lowpriority")
+                            .activate(clone!(
+                                #[weak(rename_to = imp)]
+                                self,
+                                move |_, _, _| {
+                                    if let Some(room) = imp.room() {
+                                        spawn!(async move {
+                                            imp.toggle_room_category(
+                                                &room,
+                                                TargetRoomCategory::LowPriority,
+                                            )
+                                            .await;
+                                        });
+                                    }
+                                }
+                            ))
+                            .build(),
+                    ]);
+
                     action_group.add_action_entries([gio::ActionEntry::builder("leave")
                         .activate(clone!(
                             #[weak(rename_to = imp)]
@@ -433,6 +506,45 @@ mod imp {
                         ))
                         .build()]);
 
+                    if room.notifications_setting() == NotificationsRoomSetting::Mute {
+                        action_group.add_action_entries([gio::ActionEntry::builder("unmute")
+                            .activate(clone!(
+                                #[weak(rename_to = imp)]
+                                self,
+                                move |_, _, _| {
+                                    if let Some(room) = imp.room() {
+                                        spawn!(async move {
+                                            imp.unmute_room(&room).await;
+                                        });
+                                    }
+                                }
+                            ))
+                            .build()]);
+                    } else {
+                        action_group.add_action_entries([gio::ActionEntry::builder("mute")
+                            .parameter_type(Some(&String::static_variant_type()))
+                            .activate(clone!(
+                                #[weak(rename_to = imp)]
+                                self,
+                                move |_, _, variant| {
+                                    let Some(duration) = variant
+                                        .and_then(glib::Variant::get::<String>)
+                                        .and_then(|duration| duration.parse().ok())
+                                    else {
+                                        error!("Could not parse mute duration");
+                                        return;
+                                    };
+
+                                    if let Some(room) = imp.room() {
+                                        spawn!(async move {
+                                            imp.mute_room(&room, duration).await;
+                                        });
+                                    }
+                                }
+                            ))
+                            .build()]);
+                    }
+
                     if room.is_read() {
                         action_group.add_action_entries([gio::ActionEntry::builder(
                             "mark-as-unread",
@@ -499,7 +611,23 @@ mod imp {
                         ))
                         .build()]);
                 }
-                RoomCategory::Outdated | RoomCategory::Space | RoomCategory::Ignored => {}
+                RoomCategory::Space => {
+                    action_group.add_action_entries([gio::ActionEntry::builder("browse-space")
+                        .activate(clone!(
+                            #[weak(rename_to = imp)]
+                            self,
+                            move |_, _, _| {
+                                if let Some(room) = imp.room() {
+                                    let _ = imp.obj().activate_action(
+                                        "session.browse-space",
+                                        Some(&room.room_id().as_str().to_variant()),
+                                    );
+                                }
+                            }
+                        ))
+                        .build()]);
+                }
+                RoomCategory::Outdated | RoomCategory::Ignored => {}
             }
 
             if matches!(
@@ -687,6 +815,20 @@ mod imp {
             ret
         }
 
+        /// Toggle whether the given room is in the given category.
+        ///
+        /// If the room is already in the category, it is moved back to the
+        /// normal category.
+        async fn toggle_room_category(&self, room: &Room, category: TargetRoomCategory) {
+            let target_category = if room.category() == category {
+                TargetRoomCategory::Normal
+            } else {
+                category
+            };
+
+            self.set_room_category(room, target_category).await;
+        }
+
         /// Change the category of the given room.
         async fn set_room_category(&self, room: &Room, category: TargetRoomCategory) {
             let obj = self.obj();
@@ -771,6 +913,44 @@ mod imp {
             }
         }
 
+        /// Mute the given room for the given duration.
+        async fn mute_room(&self, room: &Room, duration: RoomMuteDuration) {
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            if session
+                .notifications()
+                .settings()
+                .mute_room(room.room_id().to_owned(), duration)
+                .await
+                .is_err()
+            {
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                toast!(self.obj(), gettext("Could not mute {room}"), @room);
+            }
+        }
+
+        /// Unmute the given room.
+        async fn unmute_room(&self, room: &Room) {
+            let Some(session) = room.session() else {
+                return;
+            };
+
+            if session
+                .notifications()
+                .settings()
+                .unmute_room(room.room_id().to_owned())
+                .await
+                .is_err()
+            {
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                toast!(self.obj(), gettext("Could not unmute {room}"), @room);
+            }
+        }
+
         /// Forget the given room.
         async fn forget_room(&self, room: &Room) {
             if room.forget().await.is_err() {
@@ -0,0 +1,206 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+
+use crate::{
+    components::{LoadingButton, PillSourceRow},
+    session::{Room, RoomCategory},
+    toast,
+};
+
+mod imp {
+    use std::cell::OnceCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session_view/space_hierarchy_dialog/add_child_dialog.ui"
+    )]
+    #[properties(wrapper_type = super::AddChildDialog)]
+    pub struct AddChildDialog {
+        #[template_child]
+        search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        suggested_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        main_space_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        add_button: TemplateChild<LoadingButton>,
+        /// The space where rooms will be added.
+        #[property(get, set = Self::set_space, construct_only)]
+        space: OnceCell<Room>,
+        /// The filter for the candidate rooms.
+        filter: OnceCell<gtk::CustomFilter>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AddChildDialog {
+        const NAME: &'static str = "SpaceHierarchyAddChildDialog";
+        type Type = super::AddChildDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            PillSourceRow::ensure_type();
+
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for AddChildDialog {}
+
+    impl WidgetImpl for AddChildDialog {}
+    impl AdwDialogImpl for AddChildDialog {}
+
+    #[gtk::template_callbacks]
+    impl AddChildDialog {
+        /// Set the space where rooms will be added.
+        fn set_space(&self, space: Room) {
+            let space = self.space.get_or_init(|| space);
+            let Some(session) = space.session() else {
+                return;
+            };
+
+            let filter = gtk::CustomFilter::new(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                #[upgrade_or]
+                false,
+                move |item| item
+                    .downcast_ref::<Room>()
+                    .is_some_and(|room| imp.is_candidate(room))
+            ));
+            let filter_model =
+                gtk::FilterListModel::new(Some(session.room_list()), Some(filter.clone()));
+            self.filter
+                .set(filter)
+                .expect("filter should be uninitialized");
+
+            filter_model.connect_items_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |model, _, _, _| {
+                    imp.update_visible_page(model.n_items());
+                }
+            ));
+            self.update_visible_page(filter_model.n_items());
+
+            self.list_box.bind_model(Some(&filter_model), |item| {
+                let row = PillSourceRow::new();
+                row.set_source(item.downcast_ref());
+                row.upcast()
+            });
+        }
+
+        /// Whether the given room can be added to the space.
+        fn is_candidate(&self, room: &Room) -> bool {
+            let Some(space) = self.space.get() else {
+                return false;
+            };
+
+            if room == space || room.is_in_space(space.room_id()) {
+                return false;
+            }
+
+            if !matches!(
+                room.category(),
+                RoomCategory::Favorite
+                    | RoomCategory::Normal
+                    | RoomCategory::LowPriority
+                    | RoomCategory::Space
+            ) {
+                return false;
+            }
+
+            let term = self.search_entry.text().to_lowercase();
+            term.is_empty()
+                || room.display_name().to_lowercase().contains(&term)
+                || room.room_id().as_str().contains(&term)
+        }
+
+        /// Update the visible page for the given number of candidates.
+        fn update_visible_page(&self, n_items: u32) {
+            let page = if n_items == 0 { "empty" } else { "results" };
+            self.stack.set_visible_child_name(page);
+        }
+
+        /// Handle when the search term changed.
+        #[template_callback]
+        fn search_changed(&self) {
+            if let Some(filter) = self.filter.get() {
+                filter.changed(gtk::FilterChange::Different);
+            }
+        }
+
+        /// The currently selected room.
+        fn selected_room(&self) -> Option<Room> {
+            self.list_box
+                .selected_row()
+                .and_downcast::<PillSourceRow>()?
+                .source()
+                .and_downcast()
+        }
+
+        /// Update the state of the add button.
+        #[template_callback]
+        fn update_add_button(&self) {
+            self.add_button
+                .set_sensitive(self.selected_room().is_some());
+        }
+
+        /// Add the selected room to the space.
+        #[template_callback]
+        async fn add_child(&self) {
+            let Some(space) = self.space.get() else {
+                return;
+            };
+            let Some(room) = self.selected_room() else {
+                return;
+            };
+
+            self.add_button.set_is_loading(true);
+
+            let suggested = self.suggested_row.is_active();
+            let set_parent = self.main_space_row.is_active();
+
+            let obj = self.obj();
+            if space
+                .add_space_child(&room, suggested, set_parent)
+                .await
+                .is_ok()
+            {
+                obj.close();
+            } else {
+                toast!(obj, gettext("Could not add the room to the space"));
+            }
+
+            self.add_button.set_is_loading(false);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Dialog to pick a room to add to a space.
+    pub struct AddChildDialog(ObjectSubclass<imp::AddChildDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl AddChildDialog {
+    /// Construct a new `AddChildDialog` for the given space.
+    pub fn new(space: &Room) -> Self {
+        glib::Object::builder().property("space", space).build()
+    }
+}
@@ -0,0 +1,251 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{glib, glib::clone};
+use tracing::error;
+
+mod add_child_dialog;
+mod row;
+
+use self::{add_child_dialog::AddChildDialog, row::SpaceHierarchyRow};
+use crate::{
+    session::{Room, SpaceHierarchy, SpaceHierarchyItem},
+    spawn,
+    utils::LoadingState,
+};
+
+/// A page of the [`SpaceHierarchyDialog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+enum SpaceHierarchyDialogPage {
+    /// The loading page.
+    Loading,
+    /// The page displaying the hierarchy.
+    Content,
+    /// The page when the space has no children.
+    Empty,
+    /// The error page.
+    Error,
+}
+
+mod imp {
+    use std::cell::{OnceCell, RefCell};
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/space_hierarchy_dialog/mod.ui")]
+    #[properties(wrapper_type = super::SpaceHierarchyDialog)]
+    pub struct SpaceHierarchyDialog {
+        #[template_child]
+        stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        list_view: TemplateChild<gtk::ListView>,
+        #[template_child]
+        refresh_button: TemplateChild<gtk::Button>,
+        /// The space to browse.
+        #[property(get, set = Self::set_space, construct_only)]
+        space: OnceCell<Room>,
+        hierarchy_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceHierarchyDialog {
+        const NAME: &'static str = "SpaceHierarchyDialog";
+        type Type = super::SpaceHierarchyDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SpaceHierarchyDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // Set up the item factory for the GtkListView.
+            let factory = gtk::SignalListItemFactory::new();
+            factory.connect_setup(move |_, list_item| {
+                let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                    error!("List item factory did not receive a list item: {list_item:?}");
+                    return;
+                };
+                list_item.set_activatable(false);
+                list_item.set_selectable(false);
+
+                let expander = gtk::TreeExpander::new();
+                expander.set_indent_for_icon(true);
+                expander.set_child(Some(&SpaceHierarchyRow::new()));
+                list_item.set_child(Some(&expander));
+            });
+            factory.connect_bind(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, list_item| {
+                    let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                        error!("List item factory did not receive a list item: {list_item:?}");
+                        return;
+                    };
+                    let Some(expander) = list_item.child().and_downcast::<gtk::TreeExpander>()
+                    else {
+                        return;
+                    };
+                    let tree_list_row = list_item.item().and_downcast::<gtk::TreeListRow>();
+                    let item = tree_list_row
+                        .as_ref()
+                        .and_then(gtk::TreeListRow::item)
+                        .and_downcast::<SpaceHierarchyItem>();
+
+                    // We can only remove the direct children of the space.
+                    let parent_space = tree_list_row
+                        .as_ref()
+                        .is_some_and(|row| row.depth() == 0)
+                        .then(|| imp.space.get().cloned())
+                        .flatten();
+
+                    expander.set_list_row(tree_list_row.as_ref());
+                    if let Some(row) = expander.child().and_downcast::<SpaceHierarchyRow>() {
+                        row.set_parent_space(parent_space);
+                        row.set_item(item);
+                    }
+                }
+            ));
+            self.list_view.set_factory(Some(&factory));
+        }
+
+        fn dispose(&self) {
+            if let Some(space) = self.space.get() {
+                let hierarchy = space.space_hierarchy();
+                for handler in self.hierarchy_handlers.take() {
+                    hierarchy.disconnect(handler);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for SpaceHierarchyDialog {}
+    impl AdwDialogImpl for SpaceHierarchyDialog {}
+
+    #[gtk::template_callbacks]
+    impl SpaceHierarchyDialog {
+        /// Set the space to browse.
+        fn set_space(&self, space: Room) {
+            let space = self.space.get_or_init(|| space);
+            let hierarchy = space.space_hierarchy();
+
+            let loading_state_handler = hierarchy.connect_loading_state_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.update_view();
+                }
+            ));
+            let children_handler = hierarchy.children().connect_items_changed(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, _, _, _| {
+                    imp.update_view();
+                }
+            ));
+            self.hierarchy_handlers
+                .replace(vec![loading_state_handler, children_handler]);
+
+            let tree_model = gtk::TreeListModel::new(hierarchy.children(), false, false, |item| {
+                let item = item.downcast_ref::<SpaceHierarchyItem>()?;
+
+                if !item.is_space() {
+                    return None;
+                }
+
+                let children = item.children();
+                (children.n_items() > 0).then(|| children.upcast())
+            });
+            self.list_view
+                .set_model(Some(&gtk::NoSelection::new(Some(tree_model))));
+
+            self.update_view();
+
+            spawn!(async move {
+                hierarchy.load_if_needed().await;
+            });
+        }
+
+        /// The hierarchy of the space.
+        fn hierarchy(&self) -> Option<SpaceHierarchy> {
+            self.space.get().map(Room::space_hierarchy)
+        }
+
+        /// Set the visible page of the dialog.
+        fn set_visible_page(&self, page: SpaceHierarchyDialogPage) {
+            self.stack.set_visible_child_name(page.as_ref());
+        }
+
+        /// Update the view for the current state of the hierarchy.
+        fn update_view(&self) {
+            let Some(hierarchy) = self.hierarchy() else {
+                return;
+            };
+
+            let loading_state = hierarchy.loading_state();
+            let has_children = hierarchy.children().n_items() > 0;
+
+            let page = match loading_state {
+                // Keep showing the cached hierarchy while it is refreshed.
+                LoadingState::Initial | LoadingState::Loading if !has_children => {
+                    SpaceHierarchyDialogPage::Loading
+                }
+                LoadingState::Error if !has_children => SpaceHierarchyDialogPage::Error,
+                _ if has_children => SpaceHierarchyDialogPage::Content,
+                _ => SpaceHierarchyDialogPage::Empty,
+            };
+            self.set_visible_page(page);
+
+            self.refresh_button
+                .set_sensitive(loading_state != LoadingState::Loading);
+        }
+
+        /// Show the dialog to add a room to the space.
+        #[template_callback]
+        fn add_child(&self) {
+            let Some(space) = self.space.get() else {
+                return;
+            };
+
+            let dialog = AddChildDialog::new(space);
+            dialog.present(Some(&*self.obj()));
+        }
+
+        /// Reload the hierarchy of the space.
+        #[template_callback]
+        fn refresh(&self) {
+            let Some(hierarchy) = self.hierarchy() else {
+                return;
+            };
+
+            spawn!(async move {
+                hierarchy.load().await;
+            });
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Dialog to browse the rooms and subspaces of a space.
+    pub struct SpaceHierarchyDialog(ObjectSubclass<imp::SpaceHierarchyDialog>)
+        @extends gtk::Widget, adw::Dialog,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager;
+}
+
+impl SpaceHierarchyDialog {
+    /// Construct a new `SpaceHierarchyDialog` for the given space.
+    pub fn new(space: &Room) -> Self {
+        glib::Object::builder().property("space", space).build()
+    }
+}
@@ -0,0 +1,285 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone};
+
+use crate::{
+    Window,
+    components::{Avatar, LoadingButton},
+    gettext_f,
+    prelude::*,
+    session::{Room, SpaceHierarchyItem},
+    toast,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate, glib::Properties)]
+    #[template(resource = "/org/gnome/Fractal/ui/session_view/space_hierarchy_dialog/row.ui")]
+    #[properties(wrapper_type = super::SpaceHierarchyRow)]
+    pub struct SpaceHierarchyRow {
+        #[template_child]
+        avatar: TemplateChild<Avatar>,
+        #[template_child]
+        display_name: TemplateChild<gtk::Label>,
+        #[template_child]
+        topic: TemplateChild<gtk::Label>,
+        #[template_child]
+        button: TemplateChild<LoadingButton>,
+        #[template_child]
+        remove_button: TemplateChild<LoadingButton>,
+        /// The space that the room can be removed from, if any.
+        #[property(get, set = Self::set_parent_space, explicit_notify, nullable)]
+        parent_space: glib::WeakRef<Room>,
+        /// The item displayed by this row.
+        #[property(get, set = Self::set_item, explicit_notify, nullable)]
+        item: RefCell<Option<SpaceHierarchyItem>>,
+        room_list_info_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceHierarchyRow {
+        const NAME: &'static str = "SpaceHierarchyRow";
+        type Type = super::SpaceHierarchyRow;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SpaceHierarchyRow {
+        fn dispose(&self) {
+            self.disconnect_signals();
+        }
+    }
+
+    impl WidgetImpl for SpaceHierarchyRow {}
+    impl BinImpl for SpaceHierarchyRow {}
+
+    #[gtk::template_callbacks]
+    impl SpaceHierarchyRow {
+        /// Set the space that the room can be removed from.
+        fn set_parent_space(&self, space: Option<&Room>) {
+            if self.parent_space.upgrade().as_ref() == space {
+                return;
+            }
+
+            self.parent_space.set(space);
+
+            self.update_remove_button();
+            self.obj().notify_parent_space();
+        }
+
+        /// Set the item displayed by this row.
+        fn set_item(&self, item: Option<SpaceHierarchyItem>) {
+            if *self.item.borrow() == item {
+                return;
+            }
+
+            self.disconnect_signals();
+
+            if let Some(item) = &item {
+                let room_list_info = item.room().room_list_info();
+                let is_joining_handler = room_list_info.connect_is_joining_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_button();
+                    }
+                ));
+                let local_room_handler = room_list_info.connect_local_room_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_button();
+                    }
+                ));
+
+                self.room_list_info_handlers
+                    .replace(vec![is_joining_handler, local_room_handler]);
+            }
+
+            self.item.replace(item);
+
+            self.update_button();
+            self.update_remove_button();
+            self.update_row();
+            self.obj().notify_item();
+        }
+
+        /// Update this row for the current state.
+        fn update_row(&self) {
+            let Some(room) = self.item.borrow().as_ref().map(SpaceHierarchyItem::room) else {
+                return;
+            };
+
+            self.avatar.set_data(Some(room.avatar_data()));
+            self.display_name.set_text(&room.display_name());
+
+            let topic = room
+                .topic()
+                .map(|topic| topic.trim().to_owned())
+                .filter(|topic| !topic.is_empty());
+            self.topic.set_text(topic.as_deref().unwrap_or_default());
+            self.topic.set_visible(topic.is_some());
+        }
+
+        /// Update the join/view button of this row.
+        fn update_button(&self) {
+            let Some(room) = self.item.borrow().as_ref().map(SpaceHierarchyItem::room) else {
+                return;
+            };
+
+            let room_list_info = room.room_list_info();
+            let room_name = room.display_name();
+
+            let (label, accessible_desc) = if room_list_info.local_room().is_some() {
+                (
+                    // Translators: This is a verb, as in 'View Room'.
+                    gettext("View"),
+                    gettext_f("View {room_name}", &[("room_name", &room_name)]),
+                )
+            } else if room.can_knock() {
+                (
+                    gettext("Request an Invite"),
+                    gettext_f(
+                        "Request an invite to {room_name}",
+                        &[("room_name", &room_name)],
+                    ),
+                )
+            } else {
+                (
+                    gettext("Join"),
+                    gettext_f("Join {room_name}", &[("room_name", &room_name)]),
+                )
+            };
+
+            self.button.set_content_label(label);
+            self.button
+                .update_property(&[gtk::accessible::Property::Description(&accessible_desc)]);
+
+            self.button.set_is_loading(room_list_info.is_joining());
+        }
+
+        /// Update the visibility of the remove button of this row.
+        fn update_remove_button(&self) {
+            let can_remove = self.item.borrow().is_some()
+                && self
+                    .parent_space
+                    .upgrade()
+                    .is_some_and(|space| space.permissions().can_change_space_children());
+            self.remove_button.set_visible(can_remove);
+        }
+
+        /// Remove the room from the parent space.
+        #[template_callback]
+        async fn remove_from_space(&self) {
+            let Some(space) = self.parent_space.upgrade() else {
+                return;
+            };
+            let Some(room_id) = self
+                .item
+                .borrow()
+                .as_ref()
+                .and_then(|item| item.room().room_id())
+            else {
+                return;
+            };
+
+            self.remove_button.set_is_loading(true);
+
+            if space.remove_space_child(&room_id).await.is_err() {
+                toast!(
+                    self.obj(),
+                    gettext("Could not remove the room from the space")
+                );
+            }
+
+            self.remove_button.set_is_loading(false);
+        }
+
+        /// Join or view the room.
+        #[template_callback]
+        async fn join_or_view(&self) {
+            let Some(room) = self.item.borrow().as_ref().map(SpaceHierarchyItem::room) else {
+                return;
+            };
+
+            let obj = self.obj();
+
+            if let Some(local_room) = room.room_list_info().local_room() {
+                if let Some(window) = obj.root().and_downcast::<Window>() {
+                    window.session_view().select_room(local_room);
+                }
+                if let Some(dialog) = obj
+                    .ancestor(adw::Dialog::static_type())
+                    .and_downcast::<adw::Dialog>()
+                {
+                    dialog.close();
+                }
+            } else {
+                let Some(session) = room.session() else {
+                    return;
+                };
+
+                let uri = room.uri();
+
+                let result = if room.can_knock() {
+                    session
+                        .room_list()
+                        .knock(uri.id.clone(), uri.via.clone())
+                        .await
+                } else {
+                    session
+                        .room_list()
+                        .join_by_id_or_alias(uri.id.clone(), uri.via.clone())
+                        .await
+                };
+
+                if let Err(error) = result {
+                    toast!(obj, error);
+                }
+            }
+        }
+
+        /// Disconnect the signal handlers of this row.
+        fn disconnect_signals(&self) {
+            if let Some(item) = self.item.borrow().as_ref() {
+                let room_list_info = item.room().room_list_info();
+                for handler in self.room_list_info_handlers.take() {
+                    room_list_info.disconnect(handler);
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A row representing a room in the hierarchy of a space.
+    pub struct SpaceHierarchyRow(ObjectSubclass<imp::SpaceHierarchyRow>)
+        @extends gtk::Widget, adw::Bin,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl SpaceHierarchyRow {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl Default for SpaceHierarchyRow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
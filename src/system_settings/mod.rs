@@ -1,8 +1,18 @@
-use gtk::{glib, prelude::*, subclass::prelude::*};
+use std::time::Duration;
+
+use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use matrix_sdk::media::MediaRetentionPolicy;
 use tracing::error;
 
+use crate::config;
+
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(test)]
+mod tests;
+
+/// The number of minutes in a day.
+const MINUTES_PER_DAY: i32 = 24 * 60;
 
 /// The clock format setting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, glib::Enum)]
@@ -31,8 +41,30 @@ impl Default for ClockFormat {
     }
 }
 
+/// The key of the setting for the preference to reduce motion.
+const SETTINGS_KEY_REDUCE_MOTION: &str = "reduce-motion";
+
+/// The key of the setting for the maximum size of the media cache, in
+/// megabytes.
+const SETTINGS_KEY_MEDIA_CACHE_MAX_SIZE: &str = "media-cache-max-size";
+/// The number of bytes in a megabyte.
+const BYTES_PER_MEGABYTE: u64 = 1_000_000;
+
+/// The preference of the user to reduce motion in the interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ReduceMotionPreference {
+    /// Follow the system setting.
+    #[default]
+    System,
+    /// Always reduce motion.
+    Always,
+    /// Never reduce motion.
+    Never,
+}
+
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, OnceCell, RefCell};
 
     use super::*;
 
@@ -51,6 +83,27 @@ mod imp {
         /// The clock format setting.
         #[property(get, builder(ClockFormat::default()))]
         pub(super) clock_format: Cell<ClockFormat>,
+        /// The application settings.
+        settings: OnceCell<gio::Settings>,
+        /// Whether notifications are currently suppressed, either because of
+        /// the do-not-disturb schedule or because they were snoozed.
+        #[property(get)]
+        do_not_disturb: Cell<bool>,
+        /// Whether notifications are currently snoozed.
+        #[property(get)]
+        snoozed: Cell<bool>,
+        /// The source ID of the timeout to update the do-not-disturb state.
+        update_timeout: RefCell<Option<glib::SourceId>>,
+        /// Whether motion in the interface should be reduced.
+        ///
+        /// This follows the animations setting of GTK, unless it is overridden
+        /// in the application settings.
+        #[property(get)]
+        reduce_motion: Cell<bool>,
+        /// The maximum size of the media cache of each session, in
+        /// megabytes.
+        #[property(get)]
+        media_cache_max_size: Cell<u32>,
     }
 
     #[glib::object_subclass]
@@ -61,7 +114,154 @@ mod imp {
     }
 
     #[glib::derived_properties]
-    impl ObjectImpl for SystemSettings {}
+    impl ObjectImpl for SystemSettings {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let settings = self.settings();
+            for key in [
+                "do-not-disturb-schedule-enabled",
+                "do-not-disturb-start",
+                "do-not-disturb-end",
+                "notifications-snoozed-until",
+            ] {
+                settings.connect_changed(
+                    Some(key),
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |_, _| {
+                            imp.update_do_not_disturb();
+                        }
+                    ),
+                );
+            }
+
+            // The schedule is evaluated every minute.
+            let source_id = glib::timeout_add_seconds_local(
+                60,
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    #[upgrade_or]
+                    glib::ControlFlow::Break,
+                    move || {
+                        imp.update_do_not_disturb();
+                        glib::ControlFlow::Continue
+                    }
+                ),
+            );
+            self.update_timeout.replace(Some(source_id));
+
+            self.update_do_not_disturb();
+
+            settings.connect_changed(
+                Some(SETTINGS_KEY_REDUCE_MOTION),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_reduce_motion();
+                    }
+                ),
+            );
+            if let Some(gtk_settings) = gtk::Settings::default() {
+                gtk_settings.connect_gtk_enable_animations_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| {
+                        imp.update_reduce_motion();
+                    }
+                ));
+            }
+
+            self.update_reduce_motion();
+
+            settings.connect_changed(
+                Some(SETTINGS_KEY_MEDIA_CACHE_MAX_SIZE),
+                clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        imp.update_media_cache_max_size();
+                    }
+                ),
+            );
+            self.update_media_cache_max_size();
+        }
+
+        fn dispose(&self) {
+            if let Some(source_id) = self.update_timeout.take() {
+                source_id.remove();
+            }
+        }
+    }
+
+    impl SystemSettings {
+        /// Update whether motion in the interface should be reduced.
+        fn update_reduce_motion(&self) {
+            let reduce_motion = match self.obj().reduce_motion_preference() {
+                ReduceMotionPreference::System => gtk::Settings::default()
+                    .is_some_and(|gtk_settings| !gtk_settings.is_gtk_enable_animations()),
+                ReduceMotionPreference::Always => true,
+                ReduceMotionPreference::Never => false,
+            };
+
+            if self.reduce_motion.get() == reduce_motion {
+                return;
+            }
+
+            self.reduce_motion.set(reduce_motion);
+            self.obj().notify_reduce_motion();
+        }
+
+        /// Update the maximum size of the media cache.
+        fn update_media_cache_max_size(&self) {
+            let max_size = self.settings().uint(SETTINGS_KEY_MEDIA_CACHE_MAX_SIZE);
+
+            if self.media_cache_max_size.get() == max_size {
+                return;
+            }
+
+            self.media_cache_max_size.set(max_size);
+            self.obj().notify_media_cache_max_size();
+        }
+
+        /// The application settings.
+        pub(super) fn settings(&self) -> &gio::Settings {
+            self.settings
+                .get_or_init(|| gio::Settings::new(config::APP_ID))
+        }
+
+        /// Update whether notifications are currently suppressed.
+        pub(super) fn update_do_not_disturb(&self) {
+            let settings = self.settings();
+            let Ok(now) = glib::DateTime::now_local() else {
+                error!("Could not get current local time");
+                return;
+            };
+
+            let snoozed = settings.int64("notifications-snoozed-until") > now.to_unix();
+            let scheduled = settings.boolean("do-not-disturb-schedule-enabled")
+                && is_in_schedule(
+                    now.hour() * 60 + now.minute(),
+                    settings.int("do-not-disturb-start"),
+                    settings.int("do-not-disturb-end"),
+                );
+            let do_not_disturb = snoozed || scheduled;
+
+            let obj = self.obj();
+
+            if self.snoozed.get() != snoozed {
+                self.snoozed.set(snoozed);
+                obj.notify_snoozed();
+            }
+            if self.do_not_disturb.get() != do_not_disturb {
+                self.do_not_disturb.set(do_not_disturb);
+                obj.notify_do_not_disturb();
+            }
+        }
+    }
 }
 
 glib::wrapper! {
@@ -89,6 +289,66 @@ impl SystemSettings {
         self.imp().clock_format.set(clock_format);
         self.notify_clock_format();
     }
+
+    /// The preference of the user to reduce motion in the interface.
+    pub(crate) fn reduce_motion_preference(&self) -> ReduceMotionPreference {
+        self.imp()
+            .settings()
+            .string(SETTINGS_KEY_REDUCE_MOTION)
+            .parse()
+            .unwrap_or_default()
+    }
+
+    /// Set the preference of the user to reduce motion in the interface.
+    pub(crate) fn set_reduce_motion_preference(&self, preference: ReduceMotionPreference) {
+        if let Err(error) = self
+            .imp()
+            .settings()
+            .set_string(SETTINGS_KEY_REDUCE_MOTION, preference.as_ref())
+        {
+            error!("Could not save reduce motion preference: {error}");
+        }
+    }
+
+    /// The retention policy to use for the media cache of the sessions.
+    ///
+    /// The media store of the SDK evicts the media that were accessed least
+    /// recently first when the maximum size of the cache is reached.
+    pub(crate) fn media_retention_policy(&self) -> MediaRetentionPolicy {
+        let max_cache_size = u64::from(self.media_cache_max_size()) * BYTES_PER_MEGABYTE;
+        let default_policy = MediaRetentionPolicy::default();
+
+        // The maximum size of a single file cannot be bigger than the whole cache.
+        let max_file_size = default_policy
+            .max_file_size
+            .map(|max_file_size| max_file_size.min(max_cache_size));
+
+        default_policy
+            .with_max_cache_size(Some(max_cache_size))
+            .with_max_file_size(max_file_size)
+    }
+
+    /// Snooze notifications for the given duration, or stop snoozing them if
+    /// the duration is `None`.
+    pub(crate) fn snooze_notifications(&self, duration: Option<Duration>) {
+        let until = duration
+            .and_then(|duration| {
+                let now = glib::DateTime::now_local().ok()?;
+                let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+                Some(now.to_unix().saturating_add(secs))
+            })
+            .unwrap_or_default();
+
+        let imp = self.imp();
+        if let Err(error) = imp
+            .settings()
+            .set_int64("notifications-snoozed-until", until)
+        {
+            error!("Could not snooze notifications: {error}");
+        }
+
+        imp.update_do_not_disturb();
+    }
 }
 
 impl Default for SystemSettings {
@@ -97,6 +357,22 @@ impl Default for SystemSettings {
     }
 }
 
+/// Whether the given time is in the schedule with the given start and end.
+///
+/// All the values are in minutes since midnight. The schedule can span
+/// midnight, if `end` is before `start`.
+fn is_in_schedule(time: i32, start: i32, end: i32) -> bool {
+    let time = time.rem_euclid(MINUTES_PER_DAY);
+    let start = start.rem_euclid(MINUTES_PER_DAY);
+    let end = end.rem_euclid(MINUTES_PER_DAY);
+
+    if start <= end {
+        start <= time && time < end
+    } else {
+        start <= time || time < end
+    }
+}
+
 /// Public trait that must be implemented for everything that derives from
 /// `SystemSettings`.
 pub trait SystemSettingsImpl: ObjectImpl {}
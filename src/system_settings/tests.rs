@@ -0,0 +1,47 @@
+use super::{MINUTES_PER_DAY, is_in_schedule};
+
+#[test]
+fn schedule_in_same_day() {
+    // 09:00 to 17:00.
+    let (start, end) = (9 * 60, 17 * 60);
+
+    assert!(!is_in_schedule(8 * 60 + 59, start, end));
+    assert!(is_in_schedule(start, start, end));
+    assert!(is_in_schedule(12 * 60, start, end));
+    assert!(is_in_schedule(end - 1, start, end));
+    // The end is excluded.
+    assert!(!is_in_schedule(end, start, end));
+    assert!(!is_in_schedule(23 * 60, start, end));
+}
+
+#[test]
+fn schedule_past_midnight() {
+    // 22:00 to 07:00.
+    let (start, end) = (22 * 60, 7 * 60);
+
+    assert!(!is_in_schedule(21 * 60 + 59, start, end));
+    assert!(is_in_schedule(start, start, end));
+    assert!(is_in_schedule(23 * 60 + 59, start, end));
+    assert!(is_in_schedule(0, start, end));
+    assert!(is_in_schedule(3 * 60, start, end));
+    assert!(is_in_schedule(end - 1, start, end));
+    assert!(!is_in_schedule(end, start, end));
+    assert!(!is_in_schedule(12 * 60, start, end));
+}
+
+#[test]
+fn schedule_out_of_range_values() {
+    // Values are normalized to a single day.
+    let (start, end) = (22 * 60, 7 * 60);
+
+    assert!(is_in_schedule(MINUTES_PER_DAY + 60, start, end));
+    assert!(is_in_schedule(-60, start, end));
+    assert!(!is_in_schedule(MINUTES_PER_DAY + 12 * 60, start, end));
+}
+
+#[test]
+fn empty_schedule() {
+    // A schedule with the same start and end is empty.
+    assert!(!is_in_schedule(0, 60, 60));
+    assert!(!is_in_schedule(60, 60, 60));
+}
@@ -4,7 +4,7 @@ use gettextrs::gettext;
 use matrix_sdk::{ClientBuildError, Error, HttpError};
 use ruma::api::client::error::{ErrorBody, ErrorKind, RetryAfter, StandardErrorBody};
 
-use crate::ngettext_f;
+use crate::{ngettext_f, session::DirectChatError};
 
 pub trait UserFacingError {
     fn to_user_facing(&self) -> String;
@@ -82,3 +82,12 @@ impl UserFacingError for ClientBuildError {
         }
     }
 }
+
+impl UserFacingError for DirectChatError {
+    fn to_user_facing(&self) -> String {
+        match self {
+            Self::UserNotFound => gettext("This user does not exist."),
+            Self::Create => gettext("Could not create a new Direct Chat"),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Helpers to handle links activated by the user.
+
+use gtk::{glib, prelude::*};
+use tracing::error;
+use url::Url;
+
+use super::matrix::MatrixIdUri;
+use crate::{Application, Window, components::confirm_open_link_dialog, spawn};
+
+/// The settings key to know whether to confirm opening external links.
+const SETTINGS_KEY_CONFIRM_OPENING_LINKS: &str = "confirm-opening-links";
+/// The settings key for the domains of external links that can be opened
+/// without confirmation.
+pub(crate) const SETTINGS_KEY_ALLOWED_LINK_DOMAINS: &str = "allowed-link-domains";
+
+/// Handle the given link activated in the given widget.
+///
+/// Matrix URIs are shown in the app, and external links might need to be
+/// confirmed before being opened, according to the settings.
+///
+/// Returns `glib::Propagation::Proceed` if the link should be opened with the
+/// default handler.
+pub(crate) fn handle_activated_link(
+    widget: &impl IsA<gtk::Widget>,
+    uri: &str,
+) -> glib::Propagation {
+    if let Ok(matrix_uri) = MatrixIdUri::parse(uri) {
+        let Some(window) = widget.root().and_downcast::<Window>() else {
+            return glib::Propagation::Proceed;
+        };
+
+        window.session_view().show_matrix_uri(matrix_uri);
+        return glib::Propagation::Stop;
+    }
+
+    handle_activated_external_link(widget, uri)
+}
+
+/// Handle the given external link activated in the given widget.
+///
+/// The user might need to confirm opening the link, according to the settings.
+///
+/// Returns `glib::Propagation::Proceed` if the link should be opened with the
+/// default handler.
+pub(crate) fn handle_activated_external_link(
+    widget: &impl IsA<gtk::Widget>,
+    uri: &str,
+) -> glib::Propagation {
+    let Some(domain) = domain_to_confirm(uri) else {
+        return glib::Propagation::Proceed;
+    };
+
+    let widget = widget.clone().upcast::<gtk::Widget>();
+    let uri = uri.to_owned();
+    spawn!(async move {
+        confirm_and_open_link(&widget, &uri, domain).await;
+    });
+
+    glib::Propagation::Stop
+}
+
+/// The domain of the given URI, if opening it needs to be confirmed.
+///
+/// Only web links need to be confirmed, other links like `mailto:` or `geo:`
+/// do not hide their destination.
+fn domain_to_confirm(uri: &str) -> Option<String> {
+    let settings = Application::default().settings();
+
+    if !settings.boolean(SETTINGS_KEY_CONFIRM_OPENING_LINKS) {
+        return None;
+    }
+
+    let url = Url::parse(uri).ok()?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let domain = url.host_str()?.to_lowercase();
+
+    let is_allowed = settings
+        .strv(SETTINGS_KEY_ALLOWED_LINK_DOMAINS)
+        .iter()
+        .any(|allowed_domain| allowed_domain.as_str() == domain);
+
+    (!is_allowed).then_some(domain)
+}
+
+/// Ask the user to confirm opening the given link, and open it.
+async fn confirm_and_open_link(widget: &gtk::Widget, uri: &str, domain: String) {
+    let Some(response) = confirm_open_link_dialog(uri, &domain, widget).await else {
+        return;
+    };
+
+    if response.always_allow_domain {
+        let settings = Application::default().settings();
+
+        let mut allowed_domains = settings
+            .strv(SETTINGS_KEY_ALLOWED_LINK_DOMAINS)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        allowed_domains.push(domain);
+
+        if let Err(error) = settings.set_strv(SETTINGS_KEY_ALLOWED_LINK_DOMAINS, allowed_domains) {
+            error!("Could not save allowed link domains: {error}");
+        }
+    }
+
+    if let Err(error) = gtk::UriLauncher::new(uri)
+        .launch_future(widget.root().and_downcast_ref::<gtk::Window>())
+        .await
+    {
+        error!("Could not open link: {error}");
+    }
+}
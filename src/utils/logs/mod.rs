@@ -0,0 +1,230 @@
+//! Capture of the recent logs of the application.
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{LazyLock, Mutex, OnceLock},
+};
+
+use gtk::glib;
+use regex::Regex;
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{EnvFilter, Layer, Registry, layer::Context, reload};
+
+#[cfg(test)]
+mod tests;
+
+/// The default filter of the logs.
+///
+/// Default to the INFO level for this crate and WARN for everything else.
+const DEFAULT_LOG_FILTER: &str = "fractal=info,warn";
+/// The maximum number of log lines to keep in memory.
+const MAX_LOG_LINES: usize = 2000;
+
+/// The recent log lines.
+static LOG_LINES: LazyLock<Mutex<VecDeque<String>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(MAX_LOG_LINES)));
+/// The handle to change the filter of the logs at runtime.
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// The filter of the logs that was used when the application started.
+static INITIAL_LOG_FILTER: OnceLock<String> = OnceLock::new();
+/// Regex matching secrets that should not appear in the logs, like access
+/// tokens.
+static SECRETS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)((?:access_token|refresh_token|password)["']?\s*[:=]\s*(?:Some\()?["']?|Bearer\s+)[^\s"'&,)}]+|\b(?:syt|syr|mat|mct|mar)_[A-Za-z0-9_-]+"#,
+    )
+    .expect("secrets regex should be valid")
+});
+/// Regex matching recovery keys, that are 12 groups of 4 base58 characters.
+static RECOVERY_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:[1-9A-HJ-NP-Za-km-z]{4}\s){11}[1-9A-HJ-NP-Za-km-z]{4}\b")
+        .expect("recovery key regex should be valid")
+});
+/// Regex matching the content of messages, in JSON or in debug output.
+///
+/// JSON that was escaped in a string, for example in the debug output of a
+/// raw event, is also supported. In that case, the quotes delimiting the value
+/// are escaped once, and the escape sequences inside the value are escaped
+/// twice.
+static MESSAGE_CONTENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?P<prefix>(?:body|formatted_body)\\?"?\s*[:=]\s*)(?:(?P<escaped_quote>\\")(?:[^"\\]|\\\\(?:\\"|\\\\|[^"\\]))*\\"|(?P<quote>")(?:[^"\\]|\\.)*")"#,
+    )
+    .expect("message content regex should be valid")
+});
+
+/// The levels of logs that can be selected at runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogLevel {
+    /// The filter used when the application started.
+    #[default]
+    Initial,
+    /// Debug logs for this crate and the Matrix SDK.
+    Debug,
+    /// Trace logs for this crate and the Matrix SDK.
+    Trace,
+}
+
+impl LogLevel {
+    /// All the log levels, in the order they are presented.
+    pub(crate) const ALL: &[Self] = &[Self::Initial, Self::Debug, Self::Trace];
+
+    /// The filter for this level.
+    fn filter(self) -> &'static str {
+        match self {
+            Self::Initial => initial_filter(),
+            Self::Debug => "fractal=debug,matrix_sdk=debug,matrix_sdk_ui=debug,info",
+            Self::Trace => "fractal=trace,matrix_sdk=trace,matrix_sdk_ui=trace,info",
+        }
+    }
+}
+
+/// Initialize the logger.
+///
+/// The logs are printed to the standard output and kept in memory to be
+/// presented in the diagnostics. The filter can be overridden with the
+/// `RUST_LOG` environment variable.
+pub(crate) fn init() {
+    use tracing_subscriber::{fmt, prelude::*};
+
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    INITIAL_LOG_FILTER.get_or_init(|| env_filter.to_string());
+
+    let (filter_layer, filter_handle) = reload::Layer::new(env_filter);
+    LOG_FILTER_HANDLE.get_or_init(|| filter_handle);
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt::layer())
+        .with(LogBufferLayer)
+        .init();
+}
+
+/// The filter of the logs that was used when the application started.
+pub(crate) fn initial_filter() -> &'static str {
+    INITIAL_LOG_FILTER
+        .get()
+        .map_or(DEFAULT_LOG_FILTER, String::as_str)
+}
+
+/// Change the level of the logs until the application is restarted.
+pub(crate) fn set_log_level(level: LogLevel) {
+    let Some(handle) = LOG_FILTER_HANDLE.get() else {
+        return;
+    };
+
+    let filter = level.filter();
+    match EnvFilter::try_new(filter) {
+        Ok(env_filter) => {
+            if let Err(error) = handle.reload(env_filter) {
+                tracing::error!("Could not change the log filter: {error}");
+            } else {
+                tracing::info!("Changed the log filter to `{filter}`");
+            }
+        }
+        Err(error) => {
+            tracing::error!("Could not parse the log filter `{filter}`: {error}");
+        }
+    }
+}
+
+/// The current filter of the logs.
+pub(crate) fn current_filter() -> String {
+    LOG_FILTER_HANDLE
+        .get()
+        .and_then(|handle| handle.with_current(ToString::to_string).ok())
+        .unwrap_or_else(|| initial_filter().to_owned())
+}
+
+/// The recent logs, with one event per line.
+///
+/// The sensitive data is removed from the logs.
+pub(crate) fn recent_logs() -> String {
+    let Ok(lines) = LOG_LINES.lock() else {
+        return String::new();
+    };
+
+    let mut logs = String::new();
+    for line in lines.iter() {
+        logs.push_str(line);
+        logs.push('\n');
+    }
+
+    logs
+}
+
+/// Remove the sensitive data from the given text.
+///
+/// This removes access tokens, passwords, recovery keys and the content of
+/// messages.
+pub(crate) fn scrub_secrets(text: &str) -> String {
+    let text = SECRETS_REGEX.replace_all(text, |captures: &regex::Captures<'_>| {
+        format!(
+            "{}[REDACTED]",
+            captures.get(1).map_or("", |prefix| prefix.as_str())
+        )
+    });
+    let text = RECOVERY_KEY_REGEX.replace_all(&text, "[REDACTED]");
+    MESSAGE_CONTENT_REGEX
+        .replace_all(&text, |captures: &regex::Captures<'_>| {
+            let quote = captures
+                .name("escaped_quote")
+                .or_else(|| captures.name("quote"))
+                .map_or("\"", |quote| quote.as_str());
+            format!("{}{quote}[REDACTED]{quote}", &captures["prefix"])
+        })
+        .into_owned()
+}
+
+/// A [`Layer`] that keeps the recent logs in memory.
+struct LogBufferLayer;
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let time = glib::DateTime::now_local()
+            .and_then(|datetime| datetime.format("%T.%f"))
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+
+        let mut line = format!("{time} {:>5} {}:", metadata.level(), metadata.target());
+        event.record(&mut FieldsVisitor(&mut line));
+        let line = scrub_secrets(&line);
+
+        let Ok(mut lines) = LOG_LINES.lock() else {
+            return;
+        };
+
+        if lines.len() == MAX_LOG_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// A visitor to write the fields of an event to a string.
+struct FieldsVisitor<'a>(&'a mut String);
+
+impl Visit for FieldsVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
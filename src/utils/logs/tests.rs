@@ -0,0 +1,87 @@
+use super::scrub_secrets;
+
+#[test]
+fn scrub_message_body() {
+    assert_eq!(
+        scrub_secrets(r#"{"body":"hello world","msgtype":"m.text"}"#),
+        r#"{"body":"[REDACTED]","msgtype":"m.text"}"#
+    );
+
+    // Escaped quotes do not end the value.
+    assert_eq!(
+        scrub_secrets(r#"{"body":"hello \"world\" secret","msgtype":"m.text"}"#),
+        r#"{"body":"[REDACTED]","msgtype":"m.text"}"#
+    );
+
+    // Debug output of a struct.
+    assert_eq!(
+        scrub_secrets(
+            r#"RoomMessageEventContent { body: "a \"secret\" message", formatted: None }"#
+        ),
+        r#"RoomMessageEventContent { body: "[REDACTED]", formatted: None }"#
+    );
+}
+
+#[test]
+fn scrub_formatted_body() {
+    assert_eq!(
+        scrub_secrets(
+            r#"{"formatted_body":"<b>hi</b> \\ \"x\"","format":"org.matrix.custom.html","body":"hi"}"#
+        ),
+        r#"{"formatted_body":"[REDACTED]","format":"org.matrix.custom.html","body":"[REDACTED]"}"#
+    );
+}
+
+#[test]
+fn scrub_escaped_json() {
+    // JSON in the debug output of a string is escaped twice.
+    assert_eq!(
+        scrub_secrets(r#"content: "{\"body\":\"hi \\\"x\\\" y \\\\\",\"msgtype\":\"m.text\"}""#),
+        r#"content: "{\"body\":\"[REDACTED]\",\"msgtype\":\"m.text\"}""#
+    );
+}
+
+#[test]
+fn scrub_tokens() {
+    assert_eq!(
+        scrub_secrets("access_token=syt_YWxpY2U_abcdef_123 next"),
+        "access_token=[REDACTED] next"
+    );
+    assert_eq!(
+        scrub_secrets(r#"{"access_token":"abcdef","refresh_token":"ghijkl"}"#),
+        r#"{"access_token":"[REDACTED]","refresh_token":"[REDACTED]"}"#
+    );
+    assert_eq!(
+        scrub_secrets("Authorization: Bearer abc.def-ghi"),
+        "Authorization: Bearer [REDACTED]"
+    );
+    assert_eq!(
+        scrub_secrets("refreshing with syr_abc_123"),
+        "refreshing with [REDACTED]"
+    );
+    assert_eq!(
+        scrub_secrets(r#"password: Some("hunter2")"#),
+        r#"password: Some("[REDACTED]")"#
+    );
+}
+
+#[test]
+fn scrub_recovery_key() {
+    assert_eq!(
+        scrub_secrets(
+            "Recovery key: EsTj 3yST y93F SLpB jJsz eAXc 2XzA ygD3 w69H fGaN TKBj jXEd end"
+        ),
+        "Recovery key: [REDACTED] end"
+    );
+}
+
+#[test]
+fn keep_matrix_ids() {
+    let text = "Sync for @alice:example.org in !room:example.org with $event:example.org and #alias:example.org";
+    assert_eq!(scrub_secrets(text), text);
+
+    assert_eq!(
+        scrub_secrets(r#"{"access_token":"abcdef","user_id":"@alice:example.org"}"#),
+        r#"{"access_token":"[REDACTED]","user_id":"@alice:example.org"}"#
+    );
+}
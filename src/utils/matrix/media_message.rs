@@ -1,24 +1,35 @@
+use std::{cell::RefCell, rc::Rc};
+
 use gettextrs::gettext;
 use gtk::{gio, glib, prelude::*};
+use indexmap::IndexMap;
 use matrix_sdk::Client;
-use ruma::events::{
-    room::message::{
-        AudioMessageEventContent, FileMessageEventContent, FormattedBody, ImageMessageEventContent,
-        MessageType, VideoMessageEventContent,
+use ruma::{
+    MxcUri, OwnedMxcUri,
+    events::{
+        room::{
+            MediaSource,
+            message::{
+                AudioMessageEventContent, FileMessageEventContent, FormattedBody,
+                ImageMessageEventContent, MessageType, VideoMessageEventContent,
+            },
+        },
+        sticker::{StickerEventContent, StickerMediaSource},
     },
-    sticker::StickerEventContent,
 };
+use tokio::task::{AbortHandle, JoinHandle};
 use tracing::{debug, error};
 
 use crate::{
     components::ContentType,
     gettext_f,
     prelude::*,
+    session::Session,
     toast,
     utils::{
         File,
         media::{
-            FrameDimensions, MediaFileError,
+            FrameDimensions, MEDIA_CACHE_LOCK, MediaFileError,
             audio::normalize_waveform,
             image::{
                 Blurhash, Image, ImageError, ImageRequestPriority, ImageSource,
@@ -29,6 +40,49 @@ use crate::{
     },
 };
 
+/// The maximum number of downloaded media files to keep in the cache.
+const MAX_CACHED_TMP_FILES: usize = 8;
+
+/// The temporary files of the media that were downloaded recently by a
+/// session, by URI of their source.
+///
+/// This allows to open again a media that was already downloaded without
+/// writing it again to a file. When the cache is full, the least recently used
+/// file is forgotten.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TmpFilesCache(Rc<RefCell<IndexMap<OwnedMxcUri, File>>>);
+
+impl TmpFilesCache {
+    /// Get the file of the media with the given URI, if it is in the cache.
+    ///
+    /// The file is marked as the most recently used.
+    fn get(&self, uri: &MxcUri) -> Option<File> {
+        let mut files = self.0.borrow_mut();
+        let index = files.get_index_of(uri)?;
+
+        let last_index = files.len() - 1;
+        files.move_index(index, last_index);
+        files.get_index(last_index).map(|(_, file)| file.clone())
+    }
+
+    /// Insert the file of the media with the given URI in the cache.
+    fn insert(&self, uri: OwnedMxcUri, file: File) {
+        let mut files = self.0.borrow_mut();
+        files.shift_remove(&uri);
+        files.insert(uri, file);
+
+        if files.len() > MAX_CACHED_TMP_FILES {
+            // Forget the least recently used file.
+            files.shift_remove_index(0);
+        }
+    }
+
+    /// Remove all the files from the cache.
+    pub(crate) fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
 /// Get the filename of a media message.
 macro_rules! filename {
     ($message:ident, $mime_fallback:expr) => {{
@@ -177,14 +231,13 @@ impl MediaMessage {
 
         macro_rules! content {
             ($event_content:expr) => {{
-                Ok(
-                    $crate::spawn_tokio!(
-                        async move { media.get_file(&$event_content, true).await }
-                    )
-                    .await
-                    .unwrap()?
-                    .expect("All media message types have a file"),
-                )
+                Ok($crate::spawn_tokio!(async move {
+                    let _guard = MEDIA_CACHE_LOCK.read().await;
+                    media.get_file(&$event_content, true).await
+                })
+                .await
+                .unwrap()?
+                .expect("All media message types have a file"))
             }};
         }
 
@@ -197,13 +250,73 @@ impl MediaMessage {
         }
     }
 
-    /// Fetch the content of the media with the given client and write it to a
+    /// The URI of the source of the media.
+    fn source_uri(&self) -> &MxcUri {
+        let source = match self {
+            Self::Audio(c) => &c.source,
+            Self::File(c) => &c.source,
+            Self::Image(c) => &c.source,
+            Self::Video(c) => &c.source,
+            Self::Sticker(c) => {
+                return match &c.source {
+                    StickerMediaSource::Plain(uri) => uri,
+                    StickerMediaSource::Encrypted(file) => &file.url,
+                };
+            }
+        };
+
+        match source {
+            MediaSource::Plain(uri) => uri,
+            MediaSource::Encrypted(file) => &file.url,
+        }
+    }
+
+    /// Start downloading the content of the media with the given session to a
     /// temporary file.
     ///
+    /// If the media was downloaded recently, the cached file is reused.
+    pub(crate) fn download_tmp_file(self, session: &Session) -> MediaFileDownload {
+        let cache = session.tmp_files_cache().clone();
+        let uri = self.source_uri().to_owned();
+
+        if let Some(file) = cache.get(&uri) {
+            return MediaFileDownload::Cached(file);
+        }
+
+        let media = session.client().media();
+
+        macro_rules! download {
+            ($event_content:expr) => {{
+                let event_content = $event_content;
+                $crate::spawn_tokio!(async move {
+                    let _guard = MEDIA_CACHE_LOCK.read().await;
+                    media
+                        .get_file(&event_content, true)
+                        .await
+                        .map(|data| data.expect("All media message types have a file"))
+                })
+            }};
+        }
+
+        let handle = match self {
+            Self::Audio(c) => download!(c),
+            Self::File(c) => download!(c),
+            Self::Image(c) => download!(c),
+            Self::Video(c) => download!(c),
+            Self::Sticker(c) => download!(*c),
+        };
+
+        MediaFileDownload::Ongoing { cache, uri, handle }
+    }
+
+    /// Fetch the content of the media with the given session and write it to
+    /// a temporary file.
+    ///
+    /// If the media was downloaded recently, the cached file is reused.
+    ///
     /// Returns an error if something occurred while fetching the content.
-    pub(crate) async fn into_tmp_file(self, client: &Client) -> Result<File, MediaFileError> {
-        let data = self.into_content(client).await?;
-        Ok(save_data_to_tmp_file(data).await?)
+    pub(crate) async fn into_tmp_file(self, session: &Session) -> Result<File, MediaFileError> {
+        self.download_tmp_file(session).into_file().await
     }
 
     /// Save the content of the media to a file selected by the user.
@@ -212,16 +325,21 @@ impl MediaMessage {
     pub(crate) async fn save_to_file(
         self,
         timestamp: &glib::DateTime,
-        client: &Client,
+        session: &Session,
         parent: &impl IsA<gtk::Widget>,
     ) {
         let filename = self.filename(timestamp);
 
-        let data = match self.into_content(client).await {
-            Ok(data) => data,
+        let tmp_file = match self.into_tmp_file(session).await {
+            Ok(file) => file,
             Err(error) => {
                 error!("Could not retrieve media file: {error}");
-                toast!(parent, error.to_user_facing());
+
+                let message = match error {
+                    MediaFileError::Sdk(error) => error.to_user_facing(),
+                    _ => gettext("Could not retrieve media"),
+                };
+                toast!(parent, message);
 
                 return;
             }
@@ -239,13 +357,16 @@ impl MediaMessage {
             .await
         {
             Ok(file) => {
-                if let Err(error) = file.replace_contents(
-                    &data,
-                    None,
-                    false,
-                    gio::FileCreateFlags::REPLACE_DESTINATION,
-                    gio::Cancellable::NONE,
-                ) {
+                if let Err(error) = tmp_file
+                    .as_gfile()
+                    .copy_future(
+                        &file,
+                        gio::FileCopyFlags::OVERWRITE,
+                        glib::Priority::DEFAULT,
+                    )
+                    .0
+                    .await
+                {
                     error!("Could not save file: {error}");
                     toast!(parent, gettext("Could not save file"));
                 }
@@ -280,6 +401,64 @@ impl From<StickerEventContent> for MediaMessage {
     }
 }
 
+/// The plain text body of a message with the given type, as presented in the
+/// interface.
+///
+/// For a media message, this is the name of the media.
+pub(crate) fn message_plain_body(msgtype: &MessageType) -> String {
+    MediaMessage::from_message(msgtype)
+        .map_or_else(|| msgtype.body().to_owned(), |media| media.display_name())
+}
+
+/// The download of the content of a media message to a temporary file.
+#[derive(Debug)]
+pub(crate) enum MediaFileDownload {
+    /// The media was already downloaded.
+    Cached(File),
+    /// The media is being downloaded.
+    Ongoing {
+        /// The cache where the file should be stored.
+        cache: TmpFilesCache,
+        /// The URI of the source of the media.
+        uri: OwnedMxcUri,
+        /// The handle of the task downloading the media.
+        handle: JoinHandle<Result<Vec<u8>, matrix_sdk::Error>>,
+    },
+}
+
+impl MediaFileDownload {
+    /// The handle to cancel the download, if it is ongoing.
+    pub(crate) fn abort_handle(&self) -> Option<AbortHandle> {
+        match self {
+            Self::Cached(_) => None,
+            Self::Ongoing { handle, .. } => Some(handle.abort_handle()),
+        }
+    }
+
+    /// Wait for the download to finish and write the content to a temporary
+    /// file.
+    ///
+    /// Returns an error if something occurred while fetching the content, or
+    /// if the download was cancelled.
+    pub(crate) async fn into_file(self) -> Result<File, MediaFileError> {
+        let (cache, uri, handle) = match self {
+            Self::Cached(file) => return Ok(file),
+            Self::Ongoing { cache, uri, handle } => (cache, uri, handle),
+        };
+
+        let data = match handle.await {
+            Ok(result) => result?,
+            Err(error) if error.is_cancelled() => return Err(MediaFileError::Cancelled),
+            Err(error) => std::panic::resume_unwind(error.into_panic()),
+        };
+        let file = save_data_to_tmp_file(data).await?;
+
+        cache.insert(uri, file.clone());
+
+        Ok(file)
+    }
+}
+
 /// A visual media message.
 #[derive(Debug, Clone)]
 pub(crate) enum VisualMediaMessage {
@@ -419,13 +598,21 @@ impl VisualMediaMessage {
             .map(Some)
     }
 
-    /// Fetch the content of the media with the given client and write it to a
+    /// Start downloading the content of the media with the given session to a
     /// temporary file.
     ///
+    /// If the media was downloaded recently, the cached file is reused.
+    pub(crate) fn download_tmp_file(self, session: &Session) -> MediaFileDownload {
+        MediaMessage::from(self).download_tmp_file(session)
+    }
+
+    /// Fetch the content of the media with the given session and write it to
+    /// a temporary file.
+    ///
     /// Returns an error if something occurred while fetching the content or
     /// saving the content to a file.
-    pub(crate) async fn into_tmp_file(self, client: &Client) -> Result<File, MediaFileError> {
-        MediaMessage::from(self).into_tmp_file(client).await
+    pub(crate) async fn into_tmp_file(self, session: &Session) -> Result<File, MediaFileError> {
+        MediaMessage::from(self).into_tmp_file(session).await
     }
 
     /// Save the content of the media to a file selected by the user.
@@ -434,11 +621,11 @@ impl VisualMediaMessage {
     pub(crate) async fn save_to_file(
         self,
         timestamp: &glib::DateTime,
-        client: &Client,
+        session: &Session,
         parent: &impl IsA<gtk::Widget>,
     ) {
         MediaMessage::from(self)
-            .save_to_file(timestamp, client, parent)
+            .save_to_file(timestamp, session, parent)
             .await;
     }
 }
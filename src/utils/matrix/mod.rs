@@ -13,12 +13,13 @@ use matrix_sdk::{
     config::RequestConfig,
     deserialized_responses::RawAnySyncOrStrippedTimelineEvent,
     encryption::{BackupDownloadStrategy, EncryptionSettings},
+    reqwest,
 };
 use ruma::{
     EventId, IdParseError, MatrixToUri, MatrixUri, MatrixUriError, MilliSecondsSinceUnixEpoch,
     OwnedEventId, OwnedRoomAliasId, OwnedRoomId, OwnedRoomOrAliasId, OwnedServerName,
     OwnedTransactionId, OwnedUserId, RoomId, RoomOrAliasId, UserId,
-    events::{AnyStrippedStateEvent, AnySyncTimelineEvent},
+    events::{AnyStrippedStateEvent, AnySyncTimelineEvent, Mentions},
     html::{
         Children, Html, NodeRef, StrTendril,
         matrix::{AnchorUri, MatrixElement},
@@ -31,10 +32,14 @@ use tracing::error;
 
 pub(crate) mod ext_traits;
 mod media_message;
+#[cfg(test)]
+mod tests;
 
 pub(crate) use self::media_message::*;
 use crate::{
-    components::{AvatarImageSafetySetting, Pill},
+    Application,
+    components::{AvatarImageSafetySetting, EventLink, Pill},
+    config,
     prelude::*,
     secret::StoredSession,
     session::Room,
@@ -166,6 +171,9 @@ pub(crate) enum ClientSetupError {
     /// An error accessing the session tokens.
     #[error("Could not access session tokens")]
     NoSessionTokens,
+    /// An error when building the HTTP client.
+    #[error("HTTP client build error: {0}")]
+    Http(#[from] reqwest::Error),
 }
 
 impl UserFacingError for ClientSetupError {
@@ -175,14 +183,19 @@ impl UserFacingError for ClientSetupError {
             Self::Sdk(err) => err.to_user_facing(),
             Self::NoSessionId => gettext("Could not generate unique session ID"),
             Self::NoSessionTokens => gettext("Could not access the session tokens"),
+            Self::Http(_) => gettext("Could not set up the HTTP client"),
         }
     }
 }
 
 /// Create a [`Client`] with the given stored session.
+///
+/// If `proxy` is set, it is the URL of the HTTP or SOCKS proxy to use for all
+/// the requests of the client.
 pub(crate) async fn client_with_stored_session(
     session: StoredSession,
     tokens: SessionTokens,
+    proxy: Option<String>,
 ) -> Result<Client, ClientSetupError> {
     let has_refresh_token = tokens.refresh_token.is_some();
     let data_path = session.data_path();
@@ -229,6 +242,10 @@ pub(crate) async fn client_with_stored_session(
         client_builder = client_builder.handle_refresh_tokens();
     }
 
+    if let Some(proxy) = proxy {
+        client_builder = client_builder.proxy(proxy);
+    }
+
     let client = client_builder.build().await?;
 
     client.restore_session(session_data).await?;
@@ -236,6 +253,41 @@ pub(crate) async fn client_with_stored_session(
     Ok(client)
 }
 
+/// The URL of the proxy to use, if the given setting is set and valid.
+///
+/// An invalid proxy is ignored, so the session can still be restored, and the
+/// user can fix the proxy in the settings of the session.
+pub(crate) fn usable_proxy(proxy: &str) -> Option<String> {
+    if proxy.is_empty() {
+        return None;
+    }
+
+    match reqwest::Proxy::all(proxy) {
+        Ok(_) => Some(proxy.to_owned()),
+        Err(error) => {
+            error!("Could not use invalid proxy, falling back to no proxy: {error}");
+            None
+        }
+    }
+}
+
+/// Create an HTTP client to make requests to other servers than the
+/// homeserver.
+///
+/// If `proxy` is set, it is the URL of the HTTP or SOCKS proxy to use for all
+/// the requests of the client. It should be the same as the one used for the
+/// [`Client`] of the session, so that all the requests go through it. If it is
+/// invalid, an error is logged and no proxy is used.
+pub(crate) fn http_client(proxy: Option<&str>) -> Result<reqwest::Client, reqwest::Error> {
+    let mut builder = reqwest::Client::builder().user_agent(format!("Fractal/{}", config::VERSION));
+
+    if let Some(proxy) = proxy.and_then(usable_proxy) {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    builder.build()
+}
+
 /// Find mentions in the given HTML string.
 ///
 /// Returns a list of `(pill, mention_content)` tuples.
@@ -273,8 +325,10 @@ fn node_as_mention(node: &NodeRef, room: &Room) -> Option<(Pill, StrTendril)> {
         return None;
     };
 
-    // Mentions contain Matrix URIs.
-    let id = MatrixIdUri::try_from(anchor.href?).ok()?;
+    // Mentions contain Matrix URIs, of users or rooms.
+    let id = MatrixIdUri::try_from(anchor.href?)
+        .ok()
+        .filter(|id| !matches!(id, MatrixIdUri::Event(_)))?;
 
     // Mentions contain one text child node.
     let child = node.children().next()?;
@@ -316,6 +370,50 @@ pub(crate) fn find_at_room(s: &str) -> Option<usize> {
     None
 }
 
+/// Whether a message with the given intentional mentions and body mentions the
+/// whole room, but not the user with the given ID and display name.
+///
+/// If the message has no intentional mentions, the legacy behavior of looking
+/// for `@room`, the user ID and the display name in the body is used.
+pub(crate) fn mentions_only_room(
+    mentions: Option<&Mentions>,
+    body: &str,
+    own_user_id: &UserId,
+    own_display_name: &str,
+) -> bool {
+    match mentions {
+        Some(mentions) => mentions.room && !mentions.user_ids.contains(own_user_id),
+        None => {
+            find_at_room(body).is_some()
+                && !contains_word(body, own_user_id.as_str())
+                && !contains_word(body, own_user_id.localpart())
+                && !contains_word(body, own_display_name)
+        }
+    }
+}
+
+/// Whether the given string contains the given word, ignoring case.
+///
+/// This uses the same algorithm as the pushrules from the Matrix spec to detect
+/// the display name of the user in the `body`.
+fn contains_word(s: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+
+    let s = s.to_lowercase();
+    let word = word.to_lowercase();
+
+    s.match_indices(&word).any(|(pos, _)| {
+        let is_at_word_start = pos == 0 || s[..pos].ends_with(char_is_ascii_word_boundary);
+        let pos_after_match = pos + word.len();
+        let is_at_word_end = pos_after_match == s.len()
+            || s[pos_after_match..].starts_with(char_is_ascii_word_boundary);
+
+        is_at_word_start && is_at_word_end
+    })
+}
+
 /// Whether the given `char` is a word boundary, according to the Matrix spec.
 ///
 /// A word boundary is any character not in the sets `[A-Z]`, `[a-z]`, `[0-9]`
@@ -324,6 +422,28 @@ fn char_is_ascii_word_boundary(c: char) -> bool {
     !c.is_ascii_alphanumeric() && c != '_'
 }
 
+/// The number of colors in the palette used for users.
+///
+/// The colors are defined in the stylesheet, with the `user-color-{n}` CSS
+/// classes, where `n` is between `1` and this number.
+const USER_COLORS_COUNT: u32 = 8;
+
+/// The CSS class of the color to use for the user with the given ID.
+///
+/// The color is derived from a hash of the user ID, so it is stable across
+/// sessions and clients that use the same algorithm.
+pub(crate) fn user_color_css_class(user_id: &UserId) -> String {
+    // Use the same algorithm as Element, based on Java's `String.hashCode()`.
+    let hash = user_id.as_str().encode_utf16().fold(0_i32, |hash, c| {
+        hash.wrapping_shl(5)
+            .wrapping_sub(hash)
+            .wrapping_add(i32::from(c))
+    });
+    let index = hash.unsigned_abs() % USER_COLORS_COUNT + 1;
+
+    format!("user-color-{index}")
+}
+
 /// Compare two raw JSON sources.
 pub(crate) fn raw_eq<T, U>(lhs: Option<&Raw<T>>, rhs: Option<&Raw<U>>) -> bool {
     let Some(lhs) = lhs else {
@@ -405,6 +525,28 @@ impl MatrixIdUri {
 
                 Some(pill)
             }
+            Self::Event(MatrixEventIdUri { event_id, room_uri }) => {
+                let session = room.session()?;
+
+                let pill =
+                    if let Some(uri_room) = session.room_list().get_by_identifier(&room_uri.id) {
+                        // We do not need to watch safety settings for local rooms, they will be
+                        // watched automatically.
+                        Pill::new(
+                            &EventLink::new(&uri_room, event_id),
+                            AvatarImageSafetySetting::None,
+                            None,
+                        )
+                    } else {
+                        Pill::new(
+                            &EventLink::new(&session.remote_cache().room(room_uri), event_id),
+                            AvatarImageSafetySetting::MediaPreviews,
+                            Some(room.clone()),
+                        )
+                    };
+
+                Some(pill)
+            }
             Self::User(user_id) => {
                 // We should have a strong reference to the list wherever we show a user pill,
                 // so we can use `get_or_create_members()`.
@@ -413,7 +555,6 @@ impl MatrixIdUri {
                 // We do not need to watch safety settings for users.
                 Some(Pill::new(&user, AvatarImageSafetySetting::None, None))
             }
-            Self::Event(_) => None,
         }
     }
 
@@ -516,6 +657,60 @@ impl TryFrom<AnchorUri> for MatrixIdUri {
     }
 }
 
+/// The key of the setting for the format of copied links.
+const SETTINGS_KEY_LINK_FORMAT: &str = "link-format";
+
+/// The format of the links to rooms and events shared by the user.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum LinkFormat {
+    /// A `https://matrix.to` link.
+    #[default]
+    MatrixTo,
+    /// A `matrix:` URI, as defined in MSC2312.
+    Matrix,
+}
+
+impl LinkFormat {
+    /// The format of links from the settings.
+    pub(crate) fn from_settings() -> Self {
+        let value = Application::default()
+            .settings()
+            .string(SETTINGS_KEY_LINK_FORMAT);
+
+        value.parse().unwrap_or_else(|_| {
+            error!("Invalid value for link format setting: {value}");
+            Self::default()
+        })
+    }
+
+    /// Save this format of links to the settings.
+    pub(crate) fn save_to_settings(self) {
+        if let Err(error) = Application::default()
+            .settings()
+            .set_string(SETTINGS_KEY_LINK_FORMAT, self.as_ref())
+        {
+            error!("Could not change link format setting: {error}");
+        }
+    }
+
+    /// Format the given `matrix.to` URI in this format.
+    ///
+    /// The servers to route through are preserved.
+    pub(crate) fn format(self, uri: &MatrixToUri) -> String {
+        match self {
+            Self::MatrixTo => uri.to_string(),
+            Self::Matrix => match MatrixIdUri::try_from(uri) {
+                Ok(id_uri) => id_uri.as_matrix_uri().to_string(),
+                Err(error) => {
+                    error!("Could not convert matrix.to URI to matrix: URI: {error}");
+                    uri.to_string()
+                }
+            },
+        }
+    }
+}
+
 impl StaticVariantType for MatrixIdUri {
     fn static_variant_type() -> Cow<'static, glib::VariantTy> {
         String::static_variant_type()
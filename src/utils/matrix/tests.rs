@@ -0,0 +1,87 @@
+use ruma::{events::Mentions, owned_user_id, user_id};
+
+use super::mentions_only_room;
+
+#[test]
+fn mentions_only_room_with_intentional_mentions() {
+    let own_user_id = user_id!("@alice:example.org");
+
+    // Only the room is mentioned.
+    let mentions = Mentions::with_room_mention();
+    assert!(mentions_only_room(
+        Some(&mentions),
+        "Hello everyone",
+        own_user_id,
+        "Alice"
+    ));
+
+    // The user is also mentioned.
+    let mut mentions = Mentions::with_user_ids([owned_user_id!("@alice:example.org")]);
+    mentions.room = true;
+    assert!(!mentions_only_room(
+        Some(&mentions),
+        "Hello everyone",
+        own_user_id,
+        "Alice"
+    ));
+
+    // The body is ignored.
+    let mentions = Mentions::new();
+    assert!(!mentions_only_room(
+        Some(&mentions),
+        "@room hello",
+        own_user_id,
+        "Alice"
+    ));
+}
+
+#[test]
+fn mentions_only_room_legacy() {
+    let own_user_id = user_id!("@alice:example.org");
+
+    // Only the room is mentioned.
+    assert!(mentions_only_room(
+        None,
+        "@room hello",
+        own_user_id,
+        "Alice"
+    ));
+
+    // The room is not mentioned.
+    assert!(!mentions_only_room(
+        None,
+        "Hello everyone",
+        own_user_id,
+        "Alice"
+    ));
+    assert!(!mentions_only_room(
+        None,
+        "Hello @roommates",
+        own_user_id,
+        "Alice"
+    ));
+
+    // The user is also mentioned by display name.
+    assert!(!mentions_only_room(
+        None,
+        "@room hello, and alice, please check this",
+        own_user_id,
+        "Alice"
+    ));
+
+    // The user is also mentioned by user ID.
+    assert!(!mentions_only_room(
+        None,
+        "@room hello @alice:example.org",
+        own_user_id,
+        "Alice Liddell"
+    ));
+
+    // The display name is only part of a word.
+    assert!(mentions_only_room(
+        None,
+        "@room hello to Alicea",
+        own_user_id,
+        "Alice"
+    ));
+}
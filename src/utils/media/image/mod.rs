@@ -1,9 +1,9 @@
 //! Collection of methods for images.
 
-use std::{cmp::Ordering, error::Error, fmt, str::FromStr, time::Duration};
+use std::{cmp::Ordering, collections::HashSet, error::Error, fmt, str::FromStr, time::Duration};
 
 use gettextrs::gettext;
-use gtk::{gdk, gio, glib, graphene, gsk, prelude::*};
+use gtk::{gdk, gdk_pixbuf, gio, glib, graphene, gsk, prelude::*};
 use matrix_sdk::{
     Client,
     attachment::{BaseImageInfo, Thumbnail},
@@ -44,6 +44,14 @@ const SVG_CONTENT_TYPE: &str = "image/svg+xml";
 const WEBP_CONTENT_TYPE: &str = "image/webp";
 /// The default WebP quality used for a generated thumbnail.
 const WEBP_DEFAULT_QUALITY: f32 = 60.0;
+/// The default quality used to encode an image in a lossy format.
+pub(crate) const DEFAULT_ENCODING_QUALITY: u8 = 85;
+/// The number of distinct colors above which an image is considered to be a
+/// photo.
+const PHOTO_MIN_COLORS: usize = 4096;
+/// The maximum number of pixels that are sampled to guess the kind of an
+/// image.
+const MAX_SAMPLED_PIXELS: usize = 100_000;
 /// The maximum file size threshold in bytes for requesting or generating a
 /// thumbnail.
 ///
@@ -175,6 +183,36 @@ impl fmt::Debug for Image {
     }
 }
 
+impl Image {
+    /// Decode the image in the given bytes.
+    ///
+    /// Set `request_dimensions` if the image will be shown at specific
+    /// dimensions. To show the image at its natural size, set it to `None`.
+    pub(crate) async fn from_bytes(
+        bytes: Vec<u8>,
+        request_dimensions: Option<FrameDimensions>,
+    ) -> Result<Self, ImageError> {
+        ImageDecoderSource::with_bytes(bytes)
+            .await?
+            .decode_image(request_dimensions)
+            .await
+    }
+
+    /// Convert this image into a paintable that is never animated.
+    ///
+    /// Only the first frame of an animated image is kept.
+    pub(crate) fn into_static_paintable(self) -> gdk::Paintable {
+        self.into_texture().upcast()
+    }
+
+    /// Convert this image into a texture.
+    ///
+    /// Only the first frame of an animated image is kept.
+    pub(crate) fn into_texture(self) -> gdk::Texture {
+        self.first_frame.texture()
+    }
+}
+
 impl From<Image> for gdk::Paintable {
     fn from(value: Image) -> Self {
         if value.first_frame.has_delay() {
@@ -603,6 +641,117 @@ impl Blurhash {
     }
 }
 
+/// A format to encode an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImageEncodingFormat {
+    /// PNG, a lossless format.
+    Png,
+    /// JPEG, a lossy format that is well suited for photos.
+    Jpeg,
+    /// WebP, a lossy format.
+    Webp,
+}
+
+impl ImageEncodingFormat {
+    /// The MIME type of this format.
+    pub(crate) fn mime_type(self) -> mime::Mime {
+        match self {
+            Self::Png => mime::IMAGE_PNG,
+            Self::Jpeg => mime::IMAGE_JPEG,
+            Self::Webp => {
+                mime::Mime::from_str(WEBP_CONTENT_TYPE).expect("content type should be valid")
+            }
+        }
+    }
+
+    /// Whether this format is lossy, i.e. whether the quality can be chosen.
+    pub(crate) fn is_lossy(self) -> bool {
+        !matches!(self, Self::Png)
+    }
+
+    /// Guess the best format to encode the given texture.
+    ///
+    /// PNG is used for images with transparency or few colors, like
+    /// screenshots. JPEG is used for images with many colors, like photos.
+    pub(crate) fn guess_for_texture(texture: &gdk::Texture) -> Self {
+        let mut downloader = gdk::TextureDownloader::new(texture);
+        downloader.set_format(gdk::MemoryFormat::R8g8b8a8);
+        let (data, _) = downloader.download_bytes();
+
+        let pixels = data.chunks_exact(4);
+        // Only sample some pixels of big images.
+        let step = (pixels.len() / MAX_SAMPLED_PIXELS).max(1);
+        let mut colors = HashSet::new();
+
+        for pixel in pixels.step_by(step) {
+            if pixel[3] != u8::MAX {
+                // JPEG does not support transparency.
+                return Self::Png;
+            }
+
+            colors.insert([pixel[0], pixel[1], pixel[2]]);
+
+            if colors.len() > PHOTO_MIN_COLORS {
+                return Self::Jpeg;
+            }
+        }
+
+        Self::Png
+    }
+
+    /// Encode the given texture in this format, with the given quality between
+    /// 0 and 100.
+    ///
+    /// The quality is ignored for lossless formats.
+    pub(crate) async fn encode(self, texture: &gdk::Texture, quality: u8) -> Option<Vec<u8>> {
+        if self == Self::Png {
+            return Some(texture.save_to_png_bytes().to_vec());
+        }
+
+        let dimensions = FrameDimensions::with_texture(texture)?;
+        let (downloader_format, webp_layout) = if self == Self::Jpeg {
+            // JPEG does not support transparency.
+            (gdk::MemoryFormat::R8g8b8, webp::PixelLayout::Rgb)
+        } else {
+            TextureThumbnailer::texture_format_to_thumbnail_format(texture.format())?
+        };
+
+        let mut downloader = gdk::TextureDownloader::new(texture);
+        downloader.set_format(downloader_format);
+        let (data, stride) = downloader.download_bytes();
+
+        // Because it can take some time, spawn on a separate thread.
+        RUNTIME
+            .spawn_blocking(move || match self {
+                Self::Jpeg => {
+                    let pixbuf = gdk_pixbuf::Pixbuf::from_bytes(
+                        &data,
+                        gdk_pixbuf::Colorspace::Rgb,
+                        false,
+                        8,
+                        dimensions.width.try_into().ok()?,
+                        dimensions.height.try_into().ok()?,
+                        stride.try_into().ok()?,
+                    );
+
+                    pixbuf
+                        .save_to_bufferv("jpeg", &[("quality", &quality.to_string())])
+                        .inspect_err(|error| {
+                            warn!("Could not encode image as JPEG: {error}");
+                        })
+                        .ok()
+                }
+                _ => {
+                    let encoder =
+                        webp::Encoder::new(&data, webp_layout, dimensions.width, dimensions.height);
+                    Some(encoder.encode(quality.into()).to_vec())
+                }
+            })
+            .await
+            .expect("task was not aborted")
+    }
+}
+
 /// An API to download a thumbnail for a media.
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ThumbnailDownloader<'a> {
@@ -946,6 +1095,7 @@ impl From<MediaFileError> for ImageError {
             MediaFileError::Sdk(_) => Self::Download,
             MediaFileError::File(_) => Self::File,
             MediaFileError::NoSession => Self::Unknown,
+            MediaFileError::Cancelled => Self::Aborted,
         }
     }
 }
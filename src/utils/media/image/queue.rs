@@ -21,7 +21,7 @@ use crate::{
     spawn, spawn_tokio,
     utils::{
         File,
-        media::{FrameDimensions, MediaFileError},
+        media::{FrameDimensions, MEDIA_CACHE_LOCK, MediaFileError},
     },
 };
 
@@ -484,10 +484,13 @@ impl IntoFuture for DownloadRequest {
 
         Box::pin(async move {
             let media = client.media();
-            let data = spawn_tokio!(async move { media.get_media_content(&settings, true).await })
-                .await
-                .expect("task should not be aborted")
-                .map_err(MediaFileError::from)?;
+            let data = spawn_tokio!(async move {
+                let _guard = MEDIA_CACHE_LOCK.read().await;
+                media.get_media_content(&settings, true).await
+            })
+            .await
+            .expect("task should not be aborted")
+            .map_err(MediaFileError::from)?;
 
             let file = ImageDecoderSource::with_bytes(data).await?;
 
@@ -13,6 +13,13 @@ pub(crate) mod audio;
 pub(crate) mod image;
 pub(crate) mod video;
 
+/// The lock of the media caches.
+///
+/// Downloads that use the media cache hold a read lock, and clearing the media
+/// cache holds the write lock, so a download is never rejected or evicted
+/// while the cache is being cleared.
+pub(crate) static MEDIA_CACHE_LOCK: tokio::sync::RwLock<()> = tokio::sync::RwLock::const_new(());
+
 /// Get a default filename for a mime type.
 ///
 /// Tries to guess the file extension, but it might not find it.
@@ -131,6 +138,9 @@ pub(crate) enum MediaFileError {
     /// [`Session`]: crate::session::Session
     #[error("Could not access session")]
     NoSession,
+    /// The download of the media was cancelled.
+    #[error("Download was cancelled")]
+    Cancelled,
 }
 
 /// The dimensions of a frame.
@@ -190,6 +200,18 @@ impl FrameDimensions {
         self
     }
 
+    /// Scale these dimensions with the given fractional factor.
+    pub(crate) fn scale_by(self, factor: f64) -> Self {
+        // We need to convert the f64 to a u32.
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let scale = |value: u32| (f64::from(value) * factor.max(0.0)) as u32;
+
+        Self {
+            width: scale(self.width),
+            height: scale(self.height),
+        }
+    }
+
     /// Scale these dimensions to fit into the requested dimensions while
     /// preserving the aspect ratio and respecting the given content fit.
     pub(crate) fn scale_to_fit(self, requested: Self, content_fit: gtk::ContentFit) -> Self {
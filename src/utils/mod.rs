@@ -25,7 +25,9 @@ mod expression_list_model;
 mod fixed_selection;
 mod grouping_list_model;
 pub(crate) mod key_bindings;
+pub(crate) mod links;
 mod location;
+pub(crate) mod logs;
 mod macros;
 pub(crate) mod matrix;
 pub(crate) mod media;
@@ -33,8 +35,10 @@ pub(crate) mod notifications;
 mod placeholder_object;
 mod single_item_list_model;
 pub(crate) mod sourceview;
+pub(crate) mod spell_check;
 pub(crate) mod string;
 mod template_callbacks;
+pub(crate) mod timeline_zoom;
 pub(crate) mod toast;
 
 pub(crate) use self::{
@@ -522,6 +526,32 @@ pub(crate) async fn save_data_to_tmp_file(data: Vec<u8>) -> Result<File, std::io
         .expect("task was not aborted")
 }
 
+/// Compute the total size of the files in the given directory whose name
+/// starts with the given prefix.
+///
+/// Files that cannot be accessed are ignored.
+pub(crate) async fn files_size(dir: PathBuf, prefix: &'static str) -> u64 {
+    fn files_size_inner(dir: &Path, prefix: &str) -> u64 {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+            .map(|entry| match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    RUNTIME
+        .spawn_blocking(move || files_size_inner(&dir, prefix))
+        .await
+        .expect("task was not aborted")
+}
+
 /// A counted reference.
 ///
 /// Can be used to perform some actions when the count is 0 or non-zero.
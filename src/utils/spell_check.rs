@@ -0,0 +1,65 @@
+//! Helpers to check the spelling of the text in the message composer.
+
+use gtk::{gio, glib, prelude::*};
+use tracing::warn;
+
+/// The key of the setting for whether spell checking is enabled.
+const SETTINGS_KEY_SPELL_CHECKING_ENABLED: &str = "spell-checking-enabled";
+/// The key of the setting for the language used for spell checking.
+///
+/// An empty string means that the language of the system is used.
+pub(crate) const SETTINGS_KEY_SPELL_CHECKING_LANGUAGE: &str = "spell-checking-language";
+
+/// Initialize spell checking and apply the language from the given settings
+/// to the default checker, and keep it up-to-date.
+pub(crate) fn set_up(settings: &gio::Settings) {
+    spelling::init();
+
+    update_language(settings);
+    settings.connect_changed(Some(SETTINGS_KEY_SPELL_CHECKING_LANGUAGE), |settings, _| {
+        update_language(settings);
+    });
+}
+
+/// Update the language of the default checker from the given settings.
+fn update_language(settings: &gio::Settings) {
+    let code = Some(settings.string(SETTINGS_KEY_SPELL_CHECKING_LANGUAGE))
+        .filter(|code| !code.is_empty())
+        .or_else(|| spelling::Provider::default().default_code());
+
+    let Some(code) = code else {
+        warn!("Could not find a language for spell checking");
+        return;
+    };
+
+    spelling::Checker::default().set_language(&code);
+}
+
+/// The languages available for spell checking, as `(code, name)` tuples.
+pub(crate) fn available_languages() -> Vec<(glib::GString, glib::GString)> {
+    spelling::Provider::default()
+        .list_languages()
+        .iter::<spelling::Language>()
+        .filter_map(Result::ok)
+        .map(|language| (language.code(), language.name()))
+        .collect()
+}
+
+/// Check the spelling of the given buffer with the default checker.
+///
+/// Spell checking is enabled according to the given settings.
+///
+/// Returns the adapter, that provides the menu and actions to fix the
+/// misspelled words.
+pub(crate) fn set_up_buffer(
+    buffer: &sourceview::Buffer,
+    settings: &gio::Settings,
+) -> spelling::TextBufferAdapter {
+    let adapter = spelling::TextBufferAdapter::new(buffer, &spelling::Checker::default());
+    settings
+        .bind(SETTINGS_KEY_SPELL_CHECKING_ENABLED, &adapter, "enabled")
+        .get_only()
+        .build();
+
+    adapter
+}
@@ -0,0 +1,88 @@
+//! Helpers to scale the content of the room history.
+
+use gtk::{gdk, gio, prelude::*};
+use tracing::warn;
+
+use crate::Application;
+
+/// The key of the setting for the zoom level of the room history, in percent.
+const SETTINGS_KEY_TIMELINE_ZOOM: &str = "timeline-zoom";
+/// The default zoom level, in percent.
+const DEFAULT_ZOOM: f64 = 100.0;
+/// The minimum zoom level, in percent.
+const MIN_ZOOM: f64 = 50.0;
+/// The maximum zoom level, in percent.
+const MAX_ZOOM: f64 = 300.0;
+/// The step between zoom levels, in percent.
+const ZOOM_STEP: f64 = 10.0;
+
+/// Apply the zoom level of the room history from the given settings to the
+/// default display, and keep it up-to-date.
+///
+/// The text of the room history is scaled by setting its font size relative
+/// to the rest of the interface, so the size of the elements that depend on
+/// the font size, like pills and code blocks, is scaled proportionally.
+pub(crate) fn set_up(settings: &gio::Settings) {
+    let Some(display) = gdk::Display::default() else {
+        warn!("Could not apply zoom level of the room history: no default display");
+        return;
+    };
+
+    let provider = gtk::CssProvider::new();
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+
+    update_provider(&provider, settings.double(SETTINGS_KEY_TIMELINE_ZOOM));
+    settings.connect_changed(Some(SETTINGS_KEY_TIMELINE_ZOOM), move |settings, _| {
+        update_provider(&provider, settings.double(SETTINGS_KEY_TIMELINE_ZOOM));
+    });
+}
+
+/// The current zoom level of the room history, as a factor.
+pub(crate) fn factor() -> f64 {
+    current_zoom() / 100.0
+}
+
+/// Increase the zoom level of the room history.
+pub(crate) fn zoom_in() {
+    set_zoom(current_zoom() + ZOOM_STEP);
+}
+
+/// Decrease the zoom level of the room history.
+pub(crate) fn zoom_out() {
+    set_zoom(current_zoom() - ZOOM_STEP);
+}
+
+/// Reset the zoom level of the room history to the default.
+pub(crate) fn reset_zoom() {
+    set_zoom(DEFAULT_ZOOM);
+}
+
+/// The current zoom level of the room history, in percent.
+fn current_zoom() -> f64 {
+    Application::default()
+        .settings()
+        .double(SETTINGS_KEY_TIMELINE_ZOOM)
+        .clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+/// Set the zoom level of the room history, in percent.
+fn set_zoom(zoom: f64) {
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+
+    if let Err(error) = Application::default()
+        .settings()
+        .set_double(SETTINGS_KEY_TIMELINE_ZOOM, zoom)
+    {
+        warn!("Could not save zoom level of the room history: {error}");
+    }
+}
+
+/// Update the given CSS provider for the given zoom level, in percent.
+fn update_provider(provider: &gtk::CssProvider, zoom: f64) {
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    provider.load_from_string(&format!(".room-history-list {{ font-size: {zoom}%; }}"));
+}
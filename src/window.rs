@@ -10,13 +10,15 @@ use crate::{
     account_settings::AccountSettings,
     account_switcher::{AccountSwitcherButton, AccountSwitcherPopover},
     components::OfflineBanner,
+    diagnostics_dialog::DiagnosticsDialog,
     error_page::ErrorPage,
     intent::SessionIntent,
     login::Login,
     prelude::*,
+    room_switcher_dialog::RoomSwitcherDialog,
     secret::SESSION_ID_LENGTH,
-    session::{Session, SessionState},
-    session_list::{FailedSession, SessionInfo},
+    session::{Room, Session, SessionState},
+    session_list::{FailedSession, NewSession, SessionInfo},
     session_view::SessionView,
     toast,
     utils::{FixedSelection, LoadingState},
@@ -109,6 +111,32 @@ mod imp {
                 obj.imp().show_session();
             });
 
+            klass.install_action(
+                "win.select-session",
+                Some(&u32::static_variant_type()),
+                |obj, _, variant| {
+                    if let Some(index) = variant.and_then(glib::Variant::get::<u32>) {
+                        obj.imp().select_session_at(index);
+                    }
+                },
+            );
+            klass.install_action("win.select-next-session", None, |obj, _, _| {
+                obj.imp().select_adjacent_session(true);
+            });
+            klass.install_action("win.select-previous-session", None, |obj, _, _| {
+                obj.imp().select_adjacent_session(false);
+            });
+
+            klass.install_action("win.jump-to-room", None, |obj, _, _| {
+                let dialog = RoomSwitcherDialog::new();
+                dialog.present(Some(obj));
+            });
+
+            klass.install_action("win.show-diagnostics", None, |obj, _, _| {
+                let dialog = DiagnosticsDialog::new();
+                dialog.present(Some(obj));
+            });
+
             klass.install_action("win.toggle-fullscreen", None, |obj, _, _| {
                 if obj.is_fullscreen() {
                     obj.unfullscreen();
@@ -273,6 +301,66 @@ mod imp {
             self.session_selection.set_selected_item(selected_session);
         }
 
+        /// Whether the session at the given index can be selected.
+        ///
+        /// Sessions that are still loading cannot be selected.
+        fn can_select_session_at(&self, index: u32) -> bool {
+            let Some(item) = self.session_selection.item(index) else {
+                return false;
+            };
+
+            if item.is::<NewSession>() {
+                return false;
+            }
+
+            item.downcast_ref::<Session>()
+                .is_none_or(|session| session.state() != SessionState::Init)
+        }
+
+        /// Select the session at the given index, if it can be selected.
+        fn select_session_at(&self, index: u32) {
+            if !self.can_select_session_at(index) {
+                return;
+            }
+
+            if self.session_selection.selected() == index {
+                // Make sure the session is displayed.
+                self.show_session();
+            } else {
+                self.session_selection.set_selected(index);
+            }
+        }
+
+        /// Select the next or previous session that can be selected.
+        ///
+        /// Wraps around at the ends of the list.
+        fn select_adjacent_session(&self, next: bool) {
+            let n_items = self.session_selection.n_items();
+            if n_items < 2 {
+                return;
+            }
+
+            let selected = self.session_selection.selected();
+            let start = if selected == gtk::INVALID_LIST_POSITION {
+                0
+            } else {
+                selected
+            };
+
+            for offset in 1..n_items {
+                let index = if next {
+                    (start + offset) % n_items
+                } else {
+                    (start + n_items - offset) % n_items
+                };
+
+                if self.can_select_session_at(index) {
+                    self.session_selection.set_selected(index);
+                    return;
+                }
+            }
+        }
+
         /// Load the window size from the settings.
         fn load_window_size(&self) {
             let obj = self.obj();
@@ -522,6 +610,23 @@ impl Window {
         &self.imp().session_view
     }
 
+    /// Switch to the session of the given room and select the room.
+    pub(crate) fn select_room(&self, room: Room) {
+        let Some(session) = room.session() else {
+            return;
+        };
+
+        if !self.imp().set_current_session_by_id(session.session_id()) {
+            error!(
+                "Cannot switch to unknown session of room {}",
+                room.room_id()
+            );
+            return;
+        }
+
+        self.session_view().select_room(room);
+    }
+
     /// Open the error page and display the given secret error message.
     pub(crate) fn show_secret_error(&self, message: &str) {
         self.imp().show_secret_error(message);